@@ -35,9 +35,15 @@ class MainActivity : TauriActivity() {
 
   override fun onResume() {
     super.onResume()
+    SecureStoreBridge.attachActivity(this)
     flushPendingPairLink()
   }
 
+  override fun onPause() {
+    SecureStoreBridge.attachActivity(null)
+    super.onPause()
+  }
+
   private fun cachePairLink(intent: Intent?) {
     val data = intent?.data ?: return
     if (!isPairingUri(data)) return
@@ -1,14 +1,32 @@
 package dev.yourconnector.mobile
 
+import android.app.Activity
 import android.content.Context
+import android.hardware.biometrics.BiometricManager
+import android.hardware.biometrics.BiometricPrompt
+import android.os.Build
+import android.os.CancellationSignal
+import android.os.Looper
 import android.security.keystore.KeyGenParameterSpec
 import android.security.keystore.KeyProperties
 import android.util.Base64
+import java.lang.ref.WeakReference
+import java.security.KeyFactory
+import java.security.KeyPair
+import java.security.KeyPairGenerator
 import java.security.KeyStore
+import java.security.PrivateKey
+import java.security.spec.MGF1ParameterSpec
+import java.security.spec.X509EncodedKeySpec
+import java.util.concurrent.CountDownLatch
+import java.util.concurrent.TimeUnit
+import java.util.concurrent.atomic.AtomicReference
 import javax.crypto.Cipher
 import javax.crypto.KeyGenerator
 import javax.crypto.SecretKey
 import javax.crypto.spec.GCMParameterSpec
+import javax.crypto.spec.OAEPParameterSpec
+import javax.crypto.spec.PSource
 
 /**
  * Android secure storage bridge for Rust JNI calls.
@@ -16,6 +34,8 @@ import javax.crypto.spec.GCMParameterSpec
  * Storage model:
  * - AES key is generated and kept in Android Keystore.
  * - Cipher text is persisted in SharedPreferences.
+ * - Presence-gated values are sealed to an auth-per-use Keystore RSA key; reading them back
+ *   requires unlocking that key via BiometricPrompt.
  */
 object SecureStoreBridge {
   private const val STORE_NAME = "yc_secure_store_v1"
@@ -25,6 +45,26 @@ object SecureStoreBridge {
   private const val KEY_SIZE_BITS = 256
   private const val GCM_TAG_BITS = 128
   private const val GCM_IV_SIZE_BYTES = 12
+  private const val PRESENCE_KEY_ALIAS = "dev.yourconnector.mobile.presence.rsa"
+  private const val PRESENCE_TRANSFORMATION = "RSA/ECB/OAEPWithSHA-256AndMGF1Padding"
+  private const val PRESENCE_KEY_SIZE_BITS = 2048
+  private const val PRESENCE_TIMEOUT_SECONDS = 120L
+  // Android Keystore only supports SHA-1 for the OAEP MGF1 digest.
+  private val PRESENCE_OAEP_SPEC = OAEPParameterSpec(
+    "SHA-256",
+    "MGF1",
+    MGF1ParameterSpec.SHA1,
+    PSource.PSpecified.DEFAULT
+  )
+
+  @Volatile
+  private var foregroundActivity: WeakReference<Activity>? = null
+
+  /** Records the activity that hosts the auth prompt; MainActivity calls this on resume. */
+  @JvmStatic
+  fun attachActivity(activity: Activity?) {
+    foregroundActivity = activity?.let { WeakReference(it) }
+  }
 
   private fun prefKey(service: String, account: String): String = "$service::$account"
 
@@ -87,6 +127,139 @@ object SecureStoreBridge {
     }
   }
 
+  /**
+   * Seals [value] with the public half of an auth-per-use Keystore RSA key pair. Writing needs no
+   * prompt; only [getWithPresence] can open it again. Returns null on success, otherwise the
+   * reason.
+   */
+  @JvmStatic
+  fun setWithPresence(
+    context: Context,
+    service: String,
+    account: String,
+    value: ByteArray
+  ): String? {
+    if (Build.VERSION.SDK_INT < Build.VERSION_CODES.R) {
+      return "user presence requires Android 11+"
+    }
+    return try {
+      // Encrypt with a plain copy of the public key so Keystore digest restrictions do not apply.
+      val keystorePublic = getOrCreatePresenceKeyPair().public
+      val publicKey = KeyFactory.getInstance(keystorePublic.algorithm)
+        .generatePublic(X509EncodedKeySpec(keystorePublic.encoded))
+      val cipher = Cipher.getInstance(PRESENCE_TRANSFORMATION)
+      cipher.init(Cipher.ENCRYPT_MODE, publicKey, PRESENCE_OAEP_SPEC)
+      val encoded = Base64.encodeToString(cipher.doFinal(value), Base64.NO_WRAP)
+
+      val prefs = context.applicationContext.getSharedPreferences(STORE_NAME, Context.MODE_PRIVATE)
+      val saved = prefs.edit().putString(prefKey(service, account), encoded).commit()
+      if (saved) {
+        null
+      } else {
+        "shared preferences commit failed"
+      }
+    } catch (error: Exception) {
+      "${error.javaClass.simpleName}: ${error.message ?: "unknown"}"
+    }
+  }
+
+  /**
+   * Opens a value written by [setWithPresence]. The private key is unlocked per use by a
+   * biometric / device credential prompt, so this blocks the calling (non-UI) thread until the
+   * prompt resolves. Returns null when absent, the plain bytes on success, otherwise the failure
+   * reason as a String.
+   */
+  @JvmStatic
+  fun getWithPresence(context: Context, service: String, account: String, reason: String): Any? {
+    val prefs = context.applicationContext.getSharedPreferences(STORE_NAME, Context.MODE_PRIVATE)
+    val encoded = prefs.getString(prefKey(service, account), null) ?: return null
+    if (Build.VERSION.SDK_INT < Build.VERSION_CODES.R) {
+      return "user presence requires Android 11+"
+    }
+    if (Looper.myLooper() == Looper.getMainLooper()) {
+      return "auth prompt cannot block the main thread"
+    }
+    val activity = (context as? Activity) ?: foregroundActivity?.get()
+      ?: return "no foreground activity for auth prompt"
+    val cipherText: ByteArray
+    val cipher: Cipher
+    try {
+      cipherText = Base64.decode(encoded, Base64.NO_WRAP)
+      cipher = Cipher.getInstance(PRESENCE_TRANSFORMATION)
+      cipher.init(Cipher.DECRYPT_MODE, getOrCreatePresenceKeyPair().private, PRESENCE_OAEP_SPEC)
+    } catch (error: Exception) {
+      return "${error.javaClass.simpleName}: ${error.message ?: "unknown"}"
+    }
+
+    val latch = CountDownLatch(1)
+    val cancel = CancellationSignal()
+    // Written on the main thread by the prompt callback and read here after the latch.
+    val outcome = AtomicReference<Any?>("auth prompt timed out")
+    activity.runOnUiThread {
+      val prompt = BiometricPrompt.Builder(activity)
+        .setTitle(reason)
+        .setAllowedAuthenticators(
+          BiometricManager.Authenticators.BIOMETRIC_STRONG or
+            BiometricManager.Authenticators.DEVICE_CREDENTIAL
+        )
+        .build()
+      prompt.authenticate(
+        BiometricPrompt.CryptoObject(cipher),
+        cancel,
+        activity.mainExecutor,
+        object : BiometricPrompt.AuthenticationCallback() {
+          override fun onAuthenticationSucceeded(result: BiometricPrompt.AuthenticationResult) {
+            outcome.set(
+              try {
+                result.cryptoObject?.cipher?.doFinal(cipherText) ?: "auth result has no cipher"
+              } catch (error: Exception) {
+                "${error.javaClass.simpleName}: ${error.message ?: "unknown"}"
+              }
+            )
+            latch.countDown()
+          }
+
+          override fun onAuthenticationError(errorCode: Int, errString: CharSequence) {
+            outcome.set("auth error $errorCode: $errString")
+            latch.countDown()
+          }
+        }
+      )
+    }
+    if (!latch.await(PRESENCE_TIMEOUT_SECONDS, TimeUnit.SECONDS)) {
+      cancel.cancel()
+    }
+    return outcome.get()
+  }
+
+  private fun getOrCreatePresenceKeyPair(): KeyPair {
+    val keyStore = KeyStore.getInstance(KEYSTORE_PROVIDER)
+    keyStore.load(null)
+    val existing = keyStore.getKey(PRESENCE_KEY_ALIAS, null)
+    if (existing is PrivateKey) {
+      return KeyPair(keyStore.getCertificate(PRESENCE_KEY_ALIAS).publicKey, existing)
+    }
+
+    val generator = KeyPairGenerator.getInstance(KeyProperties.KEY_ALGORITHM_RSA, KEYSTORE_PROVIDER)
+    val spec = KeyGenParameterSpec.Builder(
+      PRESENCE_KEY_ALIAS,
+      KeyProperties.PURPOSE_ENCRYPT or KeyProperties.PURPOSE_DECRYPT
+    )
+      .setKeySize(PRESENCE_KEY_SIZE_BITS)
+      .setDigests(KeyProperties.DIGEST_SHA256)
+      .setEncryptionPaddings(KeyProperties.ENCRYPTION_PADDING_RSA_OAEP)
+      .setUserAuthenticationRequired(true)
+      .setUserAuthenticationParameters(
+        0,
+        KeyProperties.AUTH_BIOMETRIC_STRONG or KeyProperties.AUTH_DEVICE_CREDENTIAL
+      )
+      // The sealed device key cannot be re-created, so new enrollments must not destroy it.
+      .setInvalidatedByBiometricEnrollment(false)
+      .build()
+    generator.initialize(spec)
+    return generator.generateKeyPair()
+  }
+
   private fun getOrCreateSecretKey(): SecretKey {
     val keyStore = KeyStore.getInstance(KEYSTORE_PROVIDER)
     keyStore.load(null)
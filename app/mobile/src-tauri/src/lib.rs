@@ -2,39 +2,74 @@
 // 1. 启动 Tauri Mobile 应用并监听配对深链。
 // 2. 提供前端可调用的安全凭证命令（设备密钥、签名、会话存取）。
 
-#[cfg(all(
-    not(any(target_os = "ios", target_os = "macos")),
-    not(target_os = "android")
-))]
-use std::collections::HashMap;
-#[cfg(all(
-    not(any(target_os = "ios", target_os = "macos")),
-    not(target_os = "android")
-))]
-use std::sync::{Mutex, OnceLock};
 use std::{
+    collections::HashMap,
     fs::{self, OpenOptions},
-    io::{BufRead, BufReader, Write},
-    path::PathBuf,
+    io::{BufRead, BufReader, Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, OnceLock},
 };
 
+use argon2::Argon2;
 use base64::{Engine as _, engine::general_purpose::URL_SAFE_NO_PAD};
+use chacha20poly1305::{
+    KeyInit, XChaCha20Poly1305, XNonce,
+    aead::{Aead, Payload},
+};
 use ed25519_dalek::{Signer, SigningKey};
 #[cfg(target_os = "android")]
 use jni::objects::{JByteArray, JObject, JString, JValue};
 #[cfg(target_os = "android")]
 use jni::JavaVM;
 use rand::RngCore;
+#[cfg(any(target_os = "ios", target_os = "macos"))]
+use security_framework::access_control::{ProtectionMode, SecAccessControl};
+#[cfg(any(target_os = "ios", target_os = "macos"))]
+use security_framework::passwords::{
+    AccessControlOptions, PasswordOptions, delete_generic_password_options, generic_password,
+    set_generic_password_options,
+};
+#[cfg(any(target_os = "ios", target_os = "macos"))]
+use security_framework_sys::base::{errSecAuthFailed, errSecItemNotFound};
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 use tauri::Manager;
 #[cfg(any(target_os = "ios", target_os = "macos"))]
 use tauri::RunEvent;
 
-/// Keychain 服务名：设备私钥。
+/// Keychain 服务名：旧版未受保护的设备私钥，仅用于首次使用时迁移后删除。
 const KEYCHAIN_SERVICE_DEVICE_KEY: &str = "dev.yourconnector.mobile.device-key";
+/// Keychain 服务名：设备公钥（非机密），供绑定查询免验证读取。
+const KEYCHAIN_SERVICE_DEVICE_PUBLIC_KEY: &str = "dev.yourconnector.mobile.device-key.public";
 /// Keychain 服务名：设备会话。
 const KEYCHAIN_SERVICE_DEVICE_SESSION: &str = "dev.yourconnector.mobile.device-session";
+/// Keychain 服务名：设备私钥唯一存放处，需用户在场（生物识别/设备密码）才能读取。
+const KEYCHAIN_SERVICE_DEVICE_KEY_PRESENCE: &str = "dev.yourconnector.mobile.device-key.presence";
+/// 用户在场校验失败（取消/验证不通过/无法弹出验证）的错误码，前端据此提示重新验证。
+const USER_PRESENCE_FAILED: &str = "USER_PRESENCE_FAILED";
+/// Android 系统验证弹窗上展示的用途说明（iOS 由 `NSFaceIDUsageDescription` 提供）。
+#[cfg(target_os = "android")]
+const USER_PRESENCE_REASON: &str = "验证身份以使用设备密钥";
+
+/// 凭证迁移包格式标记（同时作为 AEAD 附加数据）。
+const CREDENTIAL_BUNDLE_MAGIC: &[u8] = b"ycb1";
+/// 凭证迁移包明文版本。
+const CREDENTIAL_BUNDLE_VERSION: u32 = 1;
+/// 口令派生盐长度。
+const CREDENTIAL_BUNDLE_SALT_BYTES: usize = 16;
+/// XChaCha20-Poly1305 nonce 长度。
+const CREDENTIAL_BUNDLE_NONCE_BYTES: usize = 24;
+/// 迁移口令最短字符数。
+const CREDENTIAL_BUNDLE_MIN_PASSPHRASE_CHARS: usize = 8;
+/// 导入时目标设备已有不同私钥且未声明覆盖的错误码。
+const DEVICE_KEY_EXISTS: &str = "DEVICE_KEY_EXISTS";
+
+/// Keychain 错误码：用户取消验证（security-framework-sys 未导出）。
+#[cfg(any(target_os = "ios", target_os = "macos"))]
+const ERR_SEC_USER_CANCELED: i32 = -128;
+/// Keychain 错误码：当前不允许弹出交互（如应用在后台）。
+#[cfg(any(target_os = "ios", target_os = "macos"))]
+const ERR_SEC_INTERACTION_NOT_ALLOWED: i32 = -25308;
 
 /// 设备公钥响应体。
 #[derive(Debug, Serialize)]
@@ -65,6 +100,26 @@ struct DeviceSession {
     credential_id: String,
 }
 
+/// 凭证迁移包明文：设备私钥种子与该设备的会话凭证。
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CredentialBundle {
+    version: u32,
+    device_id: String,
+    key_id: String,
+    seed: String,
+    sessions: Vec<DeviceSession>,
+}
+
+/// 凭证迁移包导入结果。
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CredentialImportResult {
+    device_id: String,
+    key_id: String,
+    system_ids: Vec<String>,
+}
+
 /// 聊天存储 bootstrap 返回结构。
 #[derive(Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -72,6 +127,44 @@ struct ChatStoreBootstrap {
     index: serde_json::Value,
 }
 
+/// 会话搜索命中项。
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ChatSearchHit {
+    conversation_key: String,
+    message_id: String,
+    role: String,
+    snippet: String,
+    ts: String,
+}
+
+/// 会话分页读取结果：按时间正序的事件行，`has_more` 表示游标之前仍有更早的行。
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ChatConversationPage {
+    rows: Vec<serde_json::Value>,
+    has_more: bool,
+}
+
+/// 会话压缩结果：压缩前后的 JSONL 行数。
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ChatCompactResult {
+    lines_before: usize,
+    lines_after: usize,
+}
+
+/// 按时长清理结果：单个会话被移除的事件数与保留快照中被移除的消息数，
+/// `deleted` 表示会话因清空而被删除。
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ChatPruneResult {
+    conversation_key: String,
+    removed_events: usize,
+    removed_messages: usize,
+    deleted: bool,
+}
+
 /// 仅非 Apple / 非 Android 平台下的简易内存安全存储（开发构建兜底）。
 #[cfg(all(
     not(any(target_os = "ios", target_os = "macos")),
@@ -149,6 +242,17 @@ fn android_secure_get(service: &str, account: &str) -> Result<Option<Vec<u8>>, S
 
 #[cfg(target_os = "android")]
 fn android_secure_set(service: &str, account: &str, value: &[u8]) -> Result<(), String> {
+    android_store_bytes("set", service, account, value)
+}
+
+/// 调用 `SecureStoreBridge` 的写入方法（`set` / `setWithPresence`），二者签名一致。
+#[cfg(target_os = "android")]
+fn android_store_bytes(
+    method: &str,
+    service: &str,
+    account: &str,
+    value: &[u8],
+) -> Result<(), String> {
     with_android_context(|env, context| {
         let class = env
             .find_class(ANDROID_SECURE_STORE_CLASS)
@@ -169,7 +273,7 @@ fn android_secure_set(service: &str, account: &str, value: &[u8]) -> Result<(),
         let result = env
             .call_static_method(
                 class,
-                "set",
+                method,
                 "(Landroid/content/Context;Ljava/lang/String;Ljava/lang/String;[B)Ljava/lang/String;",
                 &[
                     JValue::Object(context),
@@ -178,19 +282,19 @@ fn android_secure_set(service: &str, account: &str, value: &[u8]) -> Result<(),
                     JValue::Object(&value_obj),
                 ],
             )
-            .map_err(|err| format!("call SecureStoreBridge.set failed: {err}"))?;
+            .map_err(|err| format!("call SecureStoreBridge.{method} failed: {err}"))?;
         let err_obj = result
             .l()
-            .map_err(|err| format!("SecureStoreBridge.set decode failed: {err}"))?;
+            .map_err(|err| format!("SecureStoreBridge.{method} decode failed: {err}"))?;
         if err_obj.is_null() {
             return Ok(());
         }
         let err_jstr = JString::from(err_obj);
         let err_msg: String = env
             .get_string(&err_jstr)
-            .map_err(|err| format!("read SecureStoreBridge.set error failed: {err}"))?
+            .map_err(|err| format!("read SecureStoreBridge.{method} error failed: {err}"))?
             .into();
-        Err(format!("android secure set failed: {err_msg}"))
+        Err(format!("android secure {method} failed: {err_msg}"))
     })
 }
 
@@ -236,6 +340,147 @@ fn android_secure_delete(service: &str, account: &str) -> Result<(), String> {
     })
 }
 
+/// 读取受用户在场保护的字节：存在时弹出系统验证，通过后由 Keystore 私钥解封。
+#[cfg(target_os = "android")]
+fn android_presence_get(service: &str, account: &str) -> Result<Option<Vec<u8>>, String> {
+    with_android_context(|env, context| {
+        let class = env
+            .find_class(ANDROID_SECURE_STORE_CLASS)
+            .map_err(|err| format!("find SecureStoreBridge failed: {err}"))?;
+        let service_arg = env
+            .new_string(service)
+            .map_err(|err| format!("new service string failed: {err}"))?;
+        let account_arg = env
+            .new_string(account)
+            .map_err(|err| format!("new account string failed: {err}"))?;
+        let reason_arg = env
+            .new_string(USER_PRESENCE_REASON)
+            .map_err(|err| format!("new reason string failed: {err}"))?;
+        let service_obj = JObject::from(service_arg);
+        let account_obj = JObject::from(account_arg);
+        let reason_obj = JObject::from(reason_arg);
+
+        let result = env
+            .call_static_method(
+                class,
+                "getWithPresence",
+                "(Landroid/content/Context;Ljava/lang/String;Ljava/lang/String;Ljava/lang/String;)\
+                 Ljava/lang/Object;",
+                &[
+                    JValue::Object(context),
+                    JValue::Object(&service_obj),
+                    JValue::Object(&account_obj),
+                    JValue::Object(&reason_obj),
+                ],
+            )
+            .map_err(|err| format!("call SecureStoreBridge.getWithPresence failed: {err}"))?;
+        let value_obj = result
+            .l()
+            .map_err(|err| format!("SecureStoreBridge.getWithPresence decode failed: {err}"))?;
+        if value_obj.is_null() {
+            return Ok(None);
+        }
+        // 成功返回 byte[]，失败返回原因字符串。
+        let failed = env
+            .is_instance_of(&value_obj, "java/lang/String")
+            .map_err(|err| format!("inspect getWithPresence result failed: {err}"))?;
+        if failed {
+            let err_jstr = JString::from(value_obj);
+            let err_msg: String = env
+                .get_string(&err_jstr)
+                .map_err(|err| format!("read getWithPresence error failed: {err}"))?
+                .into();
+            return Err(format!("{USER_PRESENCE_FAILED}: {err_msg}"));
+        }
+
+        let value_array = JByteArray::from(value_obj);
+        let bytes = env
+            .convert_byte_array(&value_array)
+            .map_err(|err| format!("decode secure bytes failed: {err}"))?;
+        Ok(Some(bytes))
+    })
+}
+
+/// Apple 端在场条目的查询条件（macOS 需走 data protection keychain 才支持访问控制）。
+#[cfg(any(target_os = "ios", target_os = "macos"))]
+fn apple_presence_options(account: &str) -> PasswordOptions {
+    #[allow(unused_mut)]
+    let mut options =
+        PasswordOptions::new_generic_password(KEYCHAIN_SERVICE_DEVICE_KEY_PRESENCE, account);
+    #[cfg(target_os = "macos")]
+    options.use_protected_keychain();
+    options
+}
+
+/// 将 Keychain 读取错误归类：取消、验证失败、无法交互视为用户在场校验失败。
+#[cfg(any(target_os = "ios", target_os = "macos"))]
+fn apple_presence_error(err: security_framework::base::Error) -> String {
+    match err.code() {
+        ERR_SEC_USER_CANCELED | errSecAuthFailed | ERR_SEC_INTERACTION_NOT_ALLOWED => {
+            format!("{USER_PRESENCE_FAILED}: {err}")
+        }
+        _ => format!("keychain get failed: {err}"),
+    }
+}
+
+/// 读取受用户在场保护的字节：每次读取都由系统弹出生物识别/设备密码验证；开发兜底存储不做校验。
+fn presence_get(account: &str) -> Result<Option<Vec<u8>>, String> {
+    #[cfg(any(target_os = "ios", target_os = "macos"))]
+    {
+        match generic_password(apple_presence_options(account)) {
+            Ok(raw) => Ok(Some(raw)),
+            Err(err) if err.code() == errSecItemNotFound => Ok(None),
+            Err(err) => Err(apple_presence_error(err)),
+        }
+    }
+    #[cfg(target_os = "android")]
+    {
+        android_presence_get(KEYCHAIN_SERVICE_DEVICE_KEY_PRESENCE, account)
+    }
+    #[cfg(all(
+        not(any(target_os = "ios", target_os = "macos")),
+        not(target_os = "android")
+    ))]
+    {
+        Ok(secure_get(KEYCHAIN_SERVICE_DEVICE_KEY_PRESENCE, account))
+    }
+}
+
+/// 写入受用户在场保护的字节（写入无需验证），覆盖已有条目。
+fn presence_set(account: &str, value: &[u8]) -> Result<(), String> {
+    #[cfg(any(target_os = "ios", target_os = "macos"))]
+    {
+        // 带访问控制的条目无法免验证更新，先删除再重建。
+        let _ = delete_generic_password_options(apple_presence_options(account));
+        // 仅在设备解锁时可读且不随备份/钥匙串同步迁移到其他设备。
+        let access_control = SecAccessControl::create_with_protection(
+            Some(ProtectionMode::AccessibleWhenUnlockedThisDeviceOnly),
+            AccessControlOptions::USER_PRESENCE.bits(),
+        )
+        .map_err(|err| format!("keychain access control failed: {err}"))?;
+        let mut options = apple_presence_options(account);
+        options.set_access_control(access_control);
+        set_generic_password_options(value, options)
+            .map_err(|err| format!("keychain set failed: {err}"))
+    }
+    #[cfg(target_os = "android")]
+    {
+        android_store_bytes(
+            "setWithPresence",
+            KEYCHAIN_SERVICE_DEVICE_KEY_PRESENCE,
+            account,
+            value,
+        )
+    }
+    #[cfg(all(
+        not(any(target_os = "ios", target_os = "macos")),
+        not(target_os = "android")
+    ))]
+    {
+        secure_set(KEYCHAIN_SERVICE_DEVICE_KEY_PRESENCE, account, value)
+    }
+}
+
 /// 从 Keychain（或兜底存储）读取字节。
 fn secure_get(service: &str, account: &str) -> Option<Vec<u8>> {
     #[cfg(any(target_os = "ios", target_os = "macos"))]
@@ -331,22 +576,51 @@ fn key_id_for_public_key(public_key: &[u8]) -> String {
     format!("kid_{}", URL_SAFE_NO_PAD.encode(&digest[..10]))
 }
 
-/// 读取或创建设备私钥。
-fn load_or_create_signing_key(device_id: &str) -> Result<SigningKey, String> {
+/// 由 32 字节种子恢复签名私钥。
+fn signing_key_from_seed(raw: &[u8]) -> Result<SigningKey, String> {
+    let seed: [u8; 32] = raw
+        .try_into()
+        .map_err(|_| "device key length invalid".to_string())?;
+    Ok(SigningKey::from_bytes(&seed))
+}
+
+/// 保存设备私钥：种子只写入受在场保护的条目，公钥另存普通条目，返回公钥。
+fn store_device_seed(account: &str, seed: &[u8]) -> Result<[u8; 32], String> {
+    let public_key = signing_key_from_seed(seed)?.verifying_key().to_bytes();
+    presence_set(account, seed)?;
+    secure_set(KEYCHAIN_SERVICE_DEVICE_PUBLIC_KEY, account, &public_key)?;
+    Ok(public_key)
+}
+
+/// 把旧版未受保护的设备私钥迁入受在场保护的条目，并删除旧条目。
+fn migrate_unprotected_device_key(account: &str) -> Result<(), String> {
+    let Some(seed) = secure_get(KEYCHAIN_SERVICE_DEVICE_KEY, account) else {
+        return Ok(());
+    };
+    store_device_seed(account, &seed)?;
+    secure_delete(KEYCHAIN_SERVICE_DEVICE_KEY, account)
+}
+
+/// 读取设备公钥，不存在时生成新的设备私钥；只读公钥条目，因此不会弹出在场验证。
+fn load_or_create_device_public_key(device_id: &str) -> Result<[u8; 32], String> {
     let account = device_private_key_account(device_id);
-    if let Some(raw) = secure_get(KEYCHAIN_SERVICE_DEVICE_KEY, &account) {
-        if raw.len() != 32 {
-            return Err("device key length invalid".to_string());
-        }
-        let mut seed = [0_u8; 32];
-        seed.copy_from_slice(&raw);
-        return Ok(SigningKey::from_bytes(&seed));
+    migrate_unprotected_device_key(&account)?;
+    if let Some(public_key) = secure_get(KEYCHAIN_SERVICE_DEVICE_PUBLIC_KEY, &account)
+        .and_then(|raw| <[u8; 32]>::try_from(raw.as_slice()).ok())
+    {
+        return Ok(public_key);
     }
-
     let mut seed = [0_u8; 32];
     rand::rngs::OsRng.fill_bytes(&mut seed);
-    secure_set(KEYCHAIN_SERVICE_DEVICE_KEY, &account, &seed)?;
-    Ok(SigningKey::from_bytes(&seed))
+    store_device_seed(&account, &seed)
+}
+
+/// 读取设备私钥：每次都须通过系统生物识别/设备密码验证，失败返回以 `USER_PRESENCE_FAILED` 开头的错误。
+fn load_signing_key(device_id: &str) -> Result<SigningKey, String> {
+    let account = device_private_key_account(device_id);
+    migrate_unprotected_device_key(&account)?;
+    let raw = presence_get(&account)?.ok_or_else(|| "设备私钥不存在".to_string())?;
+    signing_key_from_seed(&raw)
 }
 
 /// 读取或创建设备密钥对并返回公开信息。
@@ -356,23 +630,30 @@ fn auth_get_device_binding(device_id: String) -> Result<DeviceKeyBinding, String
     if normalized_device.is_empty() {
         return Err("deviceId 不能为空".to_string());
     }
-    let signing_key = load_or_create_signing_key(normalized_device)?;
-    let verifying_key = signing_key.verifying_key();
-    let pub_bytes = verifying_key.to_bytes();
+    let pub_bytes = load_or_create_device_public_key(normalized_device)?;
     Ok(DeviceKeyBinding {
         key_id: key_id_for_public_key(&pub_bytes),
         public_key: URL_SAFE_NO_PAD.encode(pub_bytes),
     })
 }
 
-/// 使用设备私钥对给定 payload 进行签名。
+/// 使用设备私钥对给定 payload 进行签名；读取私钥须先通过用户在场校验，
+/// 失败返回以 `USER_PRESENCE_FAILED` 开头的错误。
 #[tauri::command]
-fn auth_sign_payload(device_id: String, payload: String) -> Result<DeviceSignature, String> {
+async fn auth_sign_payload(device_id: String, payload: String) -> Result<DeviceSignature, String> {
+    // 系统验证弹窗会阻塞读取线程，放到阻塞线程池执行，避免卡住主线程。
+    tauri::async_runtime::spawn_blocking(move || sign_payload(&device_id, &payload))
+        .await
+        .map_err(|err| format!("sign task failed: {err}"))?
+}
+
+/// 签名实现：校验在场后读取设备私钥并签名。
+fn sign_payload(device_id: &str, payload: &str) -> Result<DeviceSignature, String> {
     let normalized_device = device_id.trim();
     if normalized_device.is_empty() {
         return Err("deviceId 不能为空".to_string());
     }
-    let signing_key = load_or_create_signing_key(normalized_device)?;
+    let signing_key = load_signing_key(normalized_device)?;
     let verifying_key = signing_key.verifying_key();
     let pub_bytes = verifying_key.to_bytes();
     let signature = signing_key.sign(payload.as_bytes());
@@ -416,6 +697,187 @@ fn auth_clear_session(system_id: String, device_id: String) -> Result<(), String
     Ok(())
 }
 
+/// 由口令与盐派生 XChaCha20-Poly1305 密钥（Argon2id 默认参数）。
+fn credential_bundle_cipher(passphrase: &str, salt: &[u8]) -> Result<XChaCha20Poly1305, String> {
+    if passphrase.chars().count() < CREDENTIAL_BUNDLE_MIN_PASSPHRASE_CHARS {
+        return Err(format!(
+            "passphrase 至少 {CREDENTIAL_BUNDLE_MIN_PASSPHRASE_CHARS} 个字符"
+        ));
+    }
+    let mut key = [0_u8; 32];
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|err| format!("derive bundle key failed: {err}"))?;
+    Ok(XChaCha20Poly1305::new(&key.into()))
+}
+
+/// 导出实现：校验用户在场后读取设备私钥与指定 system 的会话，
+/// 加密为 `magic | salt | nonce | ciphertext`。
+fn export_credential_bundle(
+    device_id: &str,
+    passphrase: &str,
+    system_ids: &[String],
+) -> Result<String, String> {
+    let normalized_device = device_id.trim();
+    if normalized_device.is_empty() {
+        return Err("deviceId 不能为空".to_string());
+    }
+    // 导出等同于交出私钥，读取私钥本身即须通过用户在场校验。
+    let signing_key = load_signing_key(normalized_device)?;
+    let seed_bytes = signing_key.to_bytes();
+    let public_key = signing_key.verifying_key().to_bytes();
+
+    let mut sessions = Vec::new();
+    for system_id in system_ids {
+        let account = device_session_account(system_id.trim(), normalized_device);
+        let Some(raw) = secure_get(KEYCHAIN_SERVICE_DEVICE_SESSION, &account) else {
+            continue;
+        };
+        let session: DeviceSession =
+            serde_json::from_slice(&raw).map_err(|err| format!("decode session failed: {err}"))?;
+        sessions.push(session);
+    }
+    let bundle = CredentialBundle {
+        version: CREDENTIAL_BUNDLE_VERSION,
+        device_id: normalized_device.to_string(),
+        key_id: key_id_for_public_key(&public_key),
+        seed: URL_SAFE_NO_PAD.encode(seed_bytes),
+        sessions,
+    };
+    let plaintext =
+        serde_json::to_vec(&bundle).map_err(|err| format!("encode bundle failed: {err}"))?;
+
+    let mut salt = [0_u8; CREDENTIAL_BUNDLE_SALT_BYTES];
+    rand::rngs::OsRng.fill_bytes(&mut salt);
+    let mut nonce = [0_u8; CREDENTIAL_BUNDLE_NONCE_BYTES];
+    rand::rngs::OsRng.fill_bytes(&mut nonce);
+    let ciphertext = credential_bundle_cipher(passphrase, &salt)?
+        .encrypt(
+            XNonce::from_slice(&nonce),
+            Payload {
+                msg: &plaintext,
+                aad: CREDENTIAL_BUNDLE_MAGIC,
+            },
+        )
+        .map_err(|_| "encrypt bundle failed".to_string())?;
+
+    let mut blob = CREDENTIAL_BUNDLE_MAGIC.to_vec();
+    blob.extend_from_slice(&salt);
+    blob.extend_from_slice(&nonce);
+    blob.extend_from_slice(&ciphertext);
+    Ok(URL_SAFE_NO_PAD.encode(blob))
+}
+
+/// 导入实现：解密校验后写回安全存储；keyId 按种子重新计算，必须与包内及会话记录一致。
+fn import_credential_bundle(
+    passphrase: &str,
+    blob: &str,
+    overwrite: bool,
+) -> Result<CredentialImportResult, String> {
+    let raw = URL_SAFE_NO_PAD
+        .decode(blob.trim())
+        .map_err(|_| "bundle 不是有效的 base64".to_string())?;
+    let header_len = CREDENTIAL_BUNDLE_MAGIC.len()
+        + CREDENTIAL_BUNDLE_SALT_BYTES
+        + CREDENTIAL_BUNDLE_NONCE_BYTES;
+    if raw.len() <= header_len || !raw.starts_with(CREDENTIAL_BUNDLE_MAGIC) {
+        return Err("bundle 格式无效".to_string());
+    }
+    let (salt, rest) = raw[CREDENTIAL_BUNDLE_MAGIC.len()..].split_at(CREDENTIAL_BUNDLE_SALT_BYTES);
+    let (nonce, ciphertext) = rest.split_at(CREDENTIAL_BUNDLE_NONCE_BYTES);
+    let plaintext = credential_bundle_cipher(passphrase, salt)?
+        .decrypt(
+            XNonce::from_slice(nonce),
+            Payload {
+                msg: ciphertext,
+                aad: CREDENTIAL_BUNDLE_MAGIC,
+            },
+        )
+        .map_err(|_| "口令错误或 bundle 已损坏".to_string())?;
+    let bundle: CredentialBundle =
+        serde_json::from_slice(&plaintext).map_err(|err| format!("decode bundle failed: {err}"))?;
+    if bundle.version != CREDENTIAL_BUNDLE_VERSION {
+        return Err(format!("不支持的 bundle 版本：{}", bundle.version));
+    }
+
+    let device_id = bundle.device_id.trim().to_string();
+    if device_id.is_empty() {
+        return Err("bundle 缺少 deviceId".to_string());
+    }
+    let seed = URL_SAFE_NO_PAD
+        .decode(&bundle.seed)
+        .ok()
+        .filter(|seed| seed.len() == 32)
+        .ok_or_else(|| "bundle 私钥无效".to_string())?;
+    let mut seed_bytes = [0_u8; 32];
+    seed_bytes.copy_from_slice(&seed);
+    let public_key = SigningKey::from_bytes(&seed_bytes)
+        .verifying_key()
+        .to_bytes();
+    let key_id = key_id_for_public_key(&public_key);
+    if key_id != bundle.key_id {
+        return Err("bundle keyId 与私钥不匹配".to_string());
+    }
+    if let Some(session) = bundle.sessions.iter().find(|session| {
+        session.device_id != device_id || (!session.key_id.is_empty() && session.key_id != key_id)
+    }) {
+        return Err(format!(
+            "bundle 会话 {} 与设备密钥不匹配",
+            session.system_id
+        ));
+    }
+
+    let account = device_private_key_account(&device_id);
+    migrate_unprotected_device_key(&account)?;
+    // 比对公钥即可判断是否为同一私钥，无需读取（并弹出验证）现有私钥。
+    if let Some(existing) = secure_get(KEYCHAIN_SERVICE_DEVICE_PUBLIC_KEY, &account)
+        && existing != public_key
+        && !overwrite
+    {
+        return Err(format!("{DEVICE_KEY_EXISTS}: 设备已有不同私钥，需显式覆盖"));
+    }
+    store_device_seed(&account, &seed)?;
+    let mut system_ids = Vec::new();
+    for session in bundle.sessions {
+        system_ids.push(session.system_id.clone());
+        auth_store_session(session)?;
+    }
+    Ok(CredentialImportResult {
+        device_id,
+        key_id,
+        system_ids,
+    })
+}
+
+/// 导出加密的凭证迁移包（设备私钥 + 指定 system 的会话），返回 base64 文本。
+#[tauri::command]
+async fn auth_export_bundle(
+    device_id: String,
+    passphrase: String,
+    system_ids: Vec<String>,
+) -> Result<String, String> {
+    // Argon2 派生较耗时，放到阻塞线程池执行。
+    tauri::async_runtime::spawn_blocking(move || {
+        export_credential_bundle(&device_id, &passphrase, &system_ids)
+    })
+    .await
+    .map_err(|err| format!("export task failed: {err}"))?
+}
+
+/// 导入凭证迁移包；目标设备已有不同私钥时须 `overwrite=true`，否则返回 `DEVICE_KEY_EXISTS`。
+#[tauri::command]
+async fn auth_import_bundle(
+    passphrase: String,
+    blob: String,
+    overwrite: Option<bool>,
+) -> Result<CredentialImportResult, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        import_credential_bundle(&passphrase, &blob, overwrite.unwrap_or(false))
+    })
+    .await
+    .map_err(|err| format!("import task failed: {err}"))?
+}
+
 /// 聊天存储根目录：`<appData>/chat`。
 fn chat_store_root(app: &tauri::AppHandle) -> Result<PathBuf, String> {
     let app_data_dir = app
@@ -450,18 +912,91 @@ fn conversation_path(app: &tauri::AppHandle, conversation_key: &str) -> Result<P
         .join(conversation_file_name(normalized)))
 }
 
-/// 读取索引（不存在时返回空对象）。
-fn read_chat_index(app: &tauri::AppHandle) -> Result<serde_json::Value, String> {
-    let index_path = chat_index_path(app)?;
-    let bytes = match fs::read(index_path) {
+/// 搜索默认返回条数。
+const CHAT_SEARCH_DEFAULT_LIMIT: usize = 50;
+/// 搜索返回条数上限。
+const CHAT_SEARCH_MAX_LIMIT: usize = 200;
+/// 每个会话文件最多扫描的尾部字节数（新状态总在文件末尾）。
+const CHAT_SEARCH_MAX_BYTES_PER_FILE: u64 = 4 * 1024 * 1024;
+/// 命中片段在匹配位置前后保留的字符数。
+const CHAT_SEARCH_SNIPPET_CONTEXT_CHARS: usize = 40;
+/// 反向逐行读取时每次向前读入的字节数。
+const CHAT_REVERSE_READ_CHUNK_BYTES: u64 = 64 * 1024;
+/// 本地会话数默认上限，超出后淘汰最久未更新的会话。
+const CHAT_STORE_DEFAULT_MAX_CONVERSATIONS: usize = 100;
+/// 一天的秒数（按时长清理换算截止时间）。
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// 会话文件锁：追加与压缩按文件路径互斥，避免压缩改写期间丢失并发追加的事件。
+fn conversation_lock(path: &Path) -> Arc<Mutex<()>> {
+    static LOCKS: OnceLock<Mutex<HashMap<PathBuf, Arc<Mutex<()>>>>> = OnceLock::new();
+    let mut guard = LOCKS
+        .get_or_init(|| Mutex::new(HashMap::new()))
+        .lock()
+        .unwrap_or_else(|err| err.into_inner());
+    guard.entry(path.to_path_buf()).or_default().clone()
+}
+
+/// 索引单份滚动备份路径：`index.json.bak`。
+fn chat_index_backup_path(index_path: &Path) -> PathBuf {
+    index_path.with_extension("json.bak")
+}
+
+/// 读取并解析索引文件；文件不存在时返回 `None`。
+fn load_chat_index_file(path: &Path) -> Result<Option<serde_json::Value>, String> {
+    let bytes = match fs::read(path) {
         Ok(raw) => raw,
-        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(serde_json::json!({})),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
         Err(err) => return Err(format!("read chat index failed: {err}")),
     };
     serde_json::from_slice::<serde_json::Value>(&bytes)
+        .map(Some)
         .map_err(|err| format!("decode chat index failed: {err}"))
 }
 
+/// 读取索引：主文件缺失或损坏时回退到 `.bak` 并用备份恢复主文件；两者都不存在时返回空对象。
+fn read_chat_index_file(index_path: &Path) -> Result<serde_json::Value, String> {
+    let primary = load_chat_index_file(index_path);
+    if let Ok(Some(index)) = primary {
+        return Ok(index);
+    }
+    let backup_path = chat_index_backup_path(index_path);
+    match (primary, load_chat_index_file(&backup_path)) {
+        (_, Ok(Some(index))) => {
+            eprintln!("[chat_store] chat index unreadable, recovered from backup");
+            // 恢复失败不影响本次读取，下次写入会重新生成主文件。
+            let _ = fs::copy(&backup_path, index_path);
+            Ok(index)
+        }
+        (Ok(_), _) => Ok(serde_json::json!({})),
+        (Err(err), _) => Err(err),
+    }
+}
+
+/// 原子写入索引：先写同目录临时文件并落盘，再把旧主文件滚动为 `.bak`、临时文件 rename 为主文件；
+/// 进程在任一步被杀时，主文件或备份至少有一份完整可读。
+fn write_chat_index_file(index_path: &Path, index: &serde_json::Value) -> Result<(), String> {
+    let bytes = serde_json::to_vec_pretty(index)
+        .map_err(|err| format!("encode chat index failed: {err}"))?;
+    let tmp_path = index_path.with_extension("json.tmp");
+    let mut file = fs::File::create(&tmp_path)
+        .map_err(|err| format!("create chat index temp failed: {err}"))?;
+    file.write_all(&bytes)
+        .and_then(|_| file.sync_all())
+        .map_err(|err| format!("write chat index temp failed: {err}"))?;
+    match fs::rename(index_path, chat_index_backup_path(index_path)) {
+        Ok(_) => {}
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+        Err(err) => return Err(format!("rotate chat index backup failed: {err}")),
+    }
+    fs::rename(&tmp_path, index_path).map_err(|err| format!("replace chat index failed: {err}"))
+}
+
+/// 读取索引（不存在时返回空对象，主文件损坏时回退到备份）。
+fn read_chat_index(app: &tauri::AppHandle) -> Result<serde_json::Value, String> {
+    read_chat_index_file(&chat_index_path(app)?)
+}
+
 /// 启动时读取聊天索引。
 #[tauri::command]
 fn chat_store_bootstrap(app: tauri::AppHandle) -> Result<ChatStoreBootstrap, String> {
@@ -470,7 +1005,106 @@ fn chat_store_bootstrap(app: tauri::AppHandle) -> Result<ChatStoreBootstrap, Str
     })
 }
 
-/// 追加写入会话事件（JSONL）。
+/// 从文件末尾向前逐行读取（跳过空行），分页与尾部探测只读需要的部分，不从头扫描整个文件。
+struct ReverseLines {
+    file: fs::File,
+    /// 尚未读入缓冲的文件前段长度。
+    pos: u64,
+    /// 已读入但尚未切出完整行的字节（紧接在 `pos` 之后）。
+    buf: Vec<u8>,
+}
+
+impl ReverseLines {
+    /// 打开会话文件；文件不存在时返回 `None`。
+    fn open(path: &Path) -> Result<Option<Self>, String> {
+        let file = match fs::File::open(path) {
+            Ok(handle) => handle,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(format!("open chat conversation failed: {err}")),
+        };
+        let pos = file
+            .metadata()
+            .map_err(|err| format!("stat chat conversation failed: {err}"))?
+            .len();
+        Ok(Some(Self {
+            file,
+            pos,
+            buf: Vec::new(),
+        }))
+    }
+
+    /// 向前读入一个分块，拼到缓冲区前面。
+    fn read_previous_chunk(&mut self) -> Result<(), String> {
+        let read_len = self.pos.min(CHAT_REVERSE_READ_CHUNK_BYTES);
+        self.pos -= read_len;
+        let mut chunk = vec![0u8; read_len as usize];
+        self.file
+            .seek(SeekFrom::Start(self.pos))
+            .and_then(|_| self.file.read_exact(&mut chunk))
+            .map_err(|err| format!("read chat conversation failed: {err}"))?;
+        chunk.extend_from_slice(&self.buf);
+        self.buf = chunk;
+        Ok(())
+    }
+}
+
+impl Iterator for ReverseLines {
+    type Item = Result<String, String>;
+
+    /// 返回前一条非空行；读取失败时返回错误并结束迭代。
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let line = match self.buf.iter().rposition(|byte| *byte == b'\n') {
+                Some(newline) => {
+                    let line = self.buf.split_off(newline + 1);
+                    self.buf.truncate(newline);
+                    line
+                }
+                None if self.pos == 0 => {
+                    if self.buf.is_empty() {
+                        return None;
+                    }
+                    std::mem::take(&mut self.buf)
+                }
+                None => {
+                    if let Err(err) = self.read_previous_chunk() {
+                        self.pos = 0;
+                        self.buf.clear();
+                        return Some(Err(err));
+                    }
+                    continue;
+                }
+            };
+            let text = String::from_utf8_lossy(&line).trim().to_string();
+            if !text.is_empty() {
+                return Some(Ok(text));
+            }
+        }
+    }
+}
+
+/// 事件行的存储序号；历史行没有 `seq` 时视为 0（早于所有带序号的行）。
+fn chat_event_seq(event: &serde_json::Value) -> u64 {
+    event
+        .get("seq")
+        .and_then(|value| value.as_u64())
+        .unwrap_or(0)
+}
+
+/// 读取会话文件末尾最后一个可解析事件的序号，文件不存在或为空时为 0。
+fn last_chat_event_seq(path: &Path) -> Result<u64, String> {
+    let Some(lines) = ReverseLines::open(path)? else {
+        return Ok(0);
+    };
+    for line in lines {
+        if let Ok(event) = serde_json::from_str::<serde_json::Value>(&line?) {
+            return Ok(chat_event_seq(&event));
+        }
+    }
+    Ok(0)
+}
+
+/// 追加写入会话事件（JSONL），并按文件内递增顺序为每个事件写入 `seq`，供分页游标使用。
 #[tauri::command]
 fn chat_store_append_events(
     app: tauri::AppHandle,
@@ -482,13 +1116,20 @@ fn chat_store_append_events(
     }
 
     let conv_path = conversation_path(&app, &conversation_key)?;
+    let lock = conversation_lock(&conv_path);
+    let _guard = lock.lock().unwrap_or_else(|err| err.into_inner());
+    let mut seq = last_chat_event_seq(&conv_path)?;
     let mut file = OpenOptions::new()
         .create(true)
         .append(true)
         .open(conv_path)
         .map_err(|err| format!("open chat conversation failed: {err}"))?;
 
-    for item in events {
+    for mut item in events {
+        if let Some(obj) = item.as_object_mut() {
+            seq += 1;
+            obj.insert("seq".to_string(), serde_json::Value::from(seq));
+        }
         let line =
             serde_json::to_string(&item).map_err(|err| format!("encode chat event failed: {err}"))?;
         file.write_all(line.as_bytes())
@@ -499,93 +1140,368 @@ fn chat_store_append_events(
     Ok(())
 }
 
-/// 读取指定会话事件（支持可选倒序截断）。
+/// 从文件末尾向前分页：跳过不早于游标（`before_seq` / `before_ts`）的行，收集至多 `limit` 行，
+/// 多探测一行判断是否还有更早的历史；返回行按时间正序排列。
+fn load_conversation_page(
+    path: &Path,
+    limit: Option<usize>,
+    before_seq: Option<u64>,
+    before_ts: Option<&str>,
+) -> Result<ChatConversationPage, String> {
+    let Some(lines) = ReverseLines::open(path)? else {
+        return Ok(ChatConversationPage {
+            rows: Vec::new(),
+            has_more: false,
+        });
+    };
+    let mut rows = Vec::new();
+    let mut has_more = false;
+    for line in lines {
+        let Ok(event) = serde_json::from_str::<serde_json::Value>(&line?) else {
+            continue;
+        };
+        let before_seq_cursor = before_seq.is_none_or(|cursor| chat_event_seq(&event) < cursor);
+        let before_ts_cursor = before_ts.is_none_or(|cursor| {
+            event
+                .get("ts")
+                .and_then(|value| value.as_str())
+                .unwrap_or("")
+                < cursor
+        });
+        if !(before_seq_cursor && before_ts_cursor) {
+            continue;
+        }
+        if limit.is_some_and(|max_rows| rows.len() >= max_rows) {
+            has_more = true;
+            break;
+        }
+        rows.push(event);
+    }
+    rows.reverse();
+    Ok(ChatConversationPage { rows, has_more })
+}
+
+/// 读取指定会话事件：`limit` 截取最新的若干行，`before_seq` / `before_ts` 游标向前翻页。
 #[tauri::command]
 fn chat_store_load_conversation(
     app: tauri::AppHandle,
     conversation_key: String,
     limit: Option<usize>,
-) -> Result<Vec<serde_json::Value>, String> {
+    before_seq: Option<u64>,
+    before_ts: Option<String>,
+) -> Result<ChatConversationPage, String> {
+    let conv_path = conversation_path(&app, &conversation_key)?;
+    let before_ts = before_ts
+        .as_deref()
+        .map(str::trim)
+        .filter(|value| !value.is_empty());
+    load_conversation_page(&conv_path, limit, before_seq, before_ts)
+}
+
+/// 压缩事件归并键：快照整体覆盖、同一消息只保留最后一行，其余事件各自保留。
+fn compaction_slot(event: &serde_json::Value, position: usize) -> String {
+    if event.get("type").and_then(|value| value.as_str()) == Some("snapshot") {
+        return "snapshot".to_string();
+    }
+    match event.get("messageId").and_then(|value| value.as_str()) {
+        Some(message_id) if !message_id.trim().is_empty() => format!("message:{message_id}"),
+        _ => format!("event:{position}"),
+    }
+}
+
+/// 折叠会话事件：只保留最新快照与每条消息的最后一个事件，其余事件各自保留；
+/// 每个保留行位于其最后一个来源事件的位置，保证尾部读取仍能拿到最新状态。
+fn compact_chat_events(events: Vec<serde_json::Value>) -> Vec<serde_json::Value> {
+    let mut slots: HashMap<String, (usize, serde_json::Value)> = HashMap::new();
+    for (position, event) in events.into_iter().enumerate() {
+        slots.insert(compaction_slot(&event, position), (position, event));
+    }
+
+    let mut rows = slots.into_values().collect::<Vec<_>>();
+    rows.sort_by_key(|(position, _)| *position);
+    rows.into_iter().map(|(_, event)| event).collect()
+}
+
+/// 经临时文件 + rename 原子替换会话 JSONL；调用方须持有会话文件锁。
+fn rewrite_conversation_file(path: &Path, events: &[serde_json::Value]) -> Result<(), String> {
+    let mut body = String::new();
+    for item in events {
+        let line = serde_json::to_string(item)
+            .map_err(|err| format!("encode chat event failed: {err}"))?;
+        body.push_str(&line);
+        body.push('\n');
+    }
+    let tmp_path = path.with_extension("jsonl.tmp");
+    let mut file = fs::File::create(&tmp_path)
+        .map_err(|err| format!("create chat conversation temp failed: {err}"))?;
+    file.write_all(body.as_bytes())
+        .and_then(|_| file.sync_all())
+        .map_err(|err| format!("write chat conversation temp failed: {err}"))?;
+    fs::rename(&tmp_path, path).map_err(|err| format!("replace chat conversation failed: {err}"))
+}
+
+/// 压缩会话 JSONL：折叠为最新状态后经临时文件 + rename 原子替换，返回压缩前后行数。
+#[tauri::command]
+fn chat_store_compact_conversation(
+    app: tauri::AppHandle,
+    conversation_key: String,
+) -> Result<ChatCompactResult, String> {
     let conv_path = conversation_path(&app, &conversation_key)?;
-    let file = match fs::File::open(conv_path) {
+    let lock = conversation_lock(&conv_path);
+    let _guard = lock.lock().unwrap_or_else(|err| err.into_inner());
+
+    let raw = match fs::read_to_string(&conv_path) {
+        Ok(raw) => raw,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+            return Ok(ChatCompactResult {
+                lines_before: 0,
+                lines_after: 0,
+            });
+        }
+        Err(err) => return Err(format!("read chat conversation failed: {err}")),
+    };
+    let lines = raw
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>();
+    let lines_before = lines.len();
+    let events = lines
+        .into_iter()
+        .filter_map(|line| serde_json::from_str::<serde_json::Value>(line).ok())
+        .collect::<Vec<_>>();
+    let compacted = compact_chat_events(events);
+
+    rewrite_conversation_file(&conv_path, &compacted)?;
+    Ok(ChatCompactResult {
+        lines_before,
+        lines_after: compacted.len(),
+    })
+}
+
+/// 大小写不敏感地查找 `needle`（已小写），返回原文中的字节区间。
+fn find_case_insensitive(text: &str, needle: &[char]) -> Option<(usize, usize)> {
+    text.char_indices().find_map(|(start, _)| {
+        let mut lowered = text[start..].char_indices().flat_map(|(offset, ch)| {
+            let end = start + offset + ch.len_utf8();
+            ch.to_lowercase().map(move |lower| (end, lower))
+        });
+        let mut end = start;
+        for want in needle {
+            let (next_end, lower) = lowered.next()?;
+            if lower != *want {
+                return None;
+            }
+            end = next_end;
+        }
+        Some((start, end))
+    })
+}
+
+/// 截取匹配位置前后若干字符作为片段，首尾被截断时补省略号。
+fn search_snippet(text: &str, start: usize, end: usize) -> String {
+    let head = text[..start]
+        .char_indices()
+        .rev()
+        .nth(CHAT_SEARCH_SNIPPET_CONTEXT_CHARS - 1)
+        .map(|(idx, _)| idx)
+        .unwrap_or(0);
+    let tail = text[end..]
+        .char_indices()
+        .nth(CHAT_SEARCH_SNIPPET_CONTEXT_CHARS)
+        .map(|(idx, _)| end + idx)
+        .unwrap_or(text.len());
+    let mut snippet = String::new();
+    if head > 0 {
+        snippet.push('…');
+    }
+    snippet.push_str(&text[head..tail]);
+    if tail < text.len() {
+        snippet.push('…');
+    }
+    snippet.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// 提取一行事件中携带的消息：快照取 `conversation.messages`，折叠后的 `message` 事件取自身。
+fn event_messages(event: &serde_json::Value) -> Vec<&serde_json::Value> {
+    match event.get("type").and_then(|value| value.as_str()) {
+        Some("snapshot") => event
+            .pointer("/conversation/messages")
+            .and_then(|value| value.as_array())
+            .map(|items| items.iter().collect())
+            .unwrap_or_default(),
+        Some("message") => vec![event],
+        _ => Vec::new(),
+    }
+}
+
+/// 流式扫描单个会话文件尾部，按消息 ID 去重（后出现的状态覆盖先前的）。
+fn search_conversation_file(
+    path: &Path,
+    conversation_key: &str,
+    needle: &[char],
+) -> Result<Vec<ChatSearchHit>, String> {
+    let mut file = match fs::File::open(path) {
         Ok(handle) => handle,
         Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
         Err(err) => return Err(format!("open chat conversation failed: {err}")),
     };
-    let reader = BufReader::new(file);
-    let mut rows = Vec::new();
-    for line in reader.lines() {
-        let raw = line.map_err(|err| format!("read chat line failed: {err}"))?;
-        let trimmed = raw.trim();
-        if trimmed.is_empty() {
+    let len = file
+        .metadata()
+        .map_err(|err| format!("stat chat conversation failed: {err}"))?
+        .len();
+    let start = len.saturating_sub(CHAT_SEARCH_MAX_BYTES_PER_FILE);
+    file.seek(SeekFrom::Start(start))
+        .map_err(|err| format!("seek chat conversation failed: {err}"))?;
+    let mut lines = BufReader::new(file).lines();
+    if start > 0 {
+        // 从文件中段开始时首行可能不完整，直接跳过。
+        let _ = lines.next();
+    }
+
+    let mut order = Vec::new();
+    let mut hits: HashMap<String, ChatSearchHit> = HashMap::new();
+    for line in lines {
+        let Ok(raw) = line else {
             continue;
-        }
-        let Ok(value) = serde_json::from_str::<serde_json::Value>(trimmed) else {
+        };
+        let Ok(event) = serde_json::from_str::<serde_json::Value>(raw.trim()) else {
             continue;
         };
-        rows.push(value);
-    }
-
-    if let Some(max_rows) = limit {
-        if rows.len() > max_rows {
-            let split = rows.len() - max_rows;
-            rows = rows.split_off(split);
+        let event_ts = event
+            .get("ts")
+            .and_then(|value| value.as_str())
+            .unwrap_or("");
+        for message in event_messages(&event) {
+            let message_id = message
+                .get("id")
+                .or_else(|| message.get("messageId"))
+                .and_then(|value| value.as_str())
+                .unwrap_or("")
+                .to_string();
+            let text = message
+                .get("text")
+                .and_then(|value| value.as_str())
+                .unwrap_or("");
+            let Some((match_start, match_end)) = find_case_insensitive(text, needle) else {
+                hits.remove(&message_id);
+                continue;
+            };
+            let ts = message
+                .get("ts")
+                .and_then(|value| value.as_str())
+                .unwrap_or(event_ts);
+            if !hits.contains_key(&message_id) {
+                order.push(message_id.clone());
+            }
+            hits.insert(
+                message_id.clone(),
+                ChatSearchHit {
+                    conversation_key: conversation_key.to_string(),
+                    message_id,
+                    role: message
+                        .get("role")
+                        .and_then(|value| value.as_str())
+                        .unwrap_or("")
+                        .to_string(),
+                    snippet: search_snippet(text, match_start, match_end),
+                    ts: ts.to_string(),
+                },
+            );
         }
     }
-    Ok(rows)
+    Ok(order
+        .into_iter()
+        .filter_map(|message_id| hits.remove(&message_id))
+        .collect())
 }
 
-/// 幂等覆盖聊天索引文件。
+/// 全文搜索已存储的会话消息（大小写不敏感），按索引中的会话顺序返回命中。
 #[tauri::command]
-fn chat_store_upsert_index(app: tauri::AppHandle, index: serde_json::Value) -> Result<(), String> {
-    let index_path = chat_index_path(&app)?;
-    let bytes =
-        serde_json::to_vec_pretty(&index).map_err(|err| format!("encode chat index failed: {err}"))?;
-    fs::write(index_path, bytes).map_err(|err| format!("write chat index failed: {err}"))
+fn chat_store_search(
+    app: tauri::AppHandle,
+    query: String,
+    limit: Option<usize>,
+) -> Result<Vec<ChatSearchHit>, String> {
+    let needle = query.trim().to_lowercase().chars().collect::<Vec<_>>();
+    if needle.is_empty() {
+        return Ok(Vec::new());
+    }
+    let limit = limit
+        .unwrap_or(CHAT_SEARCH_DEFAULT_LIMIT)
+        .clamp(1, CHAT_SEARCH_MAX_LIMIT);
+
+    let index = read_chat_index(&app)?;
+    let mut results = Vec::new();
+    for key in indexed_conversation_keys(&index) {
+        let conv_path = conversation_path(&app, &key)?;
+        for hit in search_conversation_file(&conv_path, &key, &needle)? {
+            results.push(hit);
+            if results.len() >= limit {
+                return Ok(results);
+            }
+        }
+    }
+    Ok(results)
 }
 
-/// 删除指定会话的本地存储（索引 + JSONL）。
-#[tauri::command]
-fn chat_store_delete_conversation(
-    app: tauri::AppHandle,
-    conversation_key: String,
-) -> Result<(), String> {
-    let normalized_key = conversation_key.trim();
-    if normalized_key.is_empty() {
-        return Err("conversationKey 不能为空".to_string());
+/// 列出索引中的会话 key：文件名是 key 的哈希，借助索引把文件映射回会话 key；
+/// 排序优先、其余按 key 补齐。
+fn indexed_conversation_keys(index: &serde_json::Value) -> Vec<String> {
+    let mut keys = index
+        .get("conversationOrder")
+        .and_then(|value| value.as_array())
+        .map(|order| {
+            order
+                .iter()
+                .filter_map(|item| item.as_str())
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    if let Some(by_key) = index
+        .get("conversationsByKey")
+        .and_then(|value| value.as_object())
+    {
+        for key in by_key.keys() {
+            if !keys.contains(key) {
+                keys.push(key.clone());
+            }
+        }
     }
+    keys
+}
 
-    let mut index = read_chat_index(&app)?;
-    let Some(index_obj) = index.as_object_mut() else {
-        index = serde_json::json!({});
-        chat_store_upsert_index(app.clone(), index)?;
-        let conv_path = conversation_path(&app, normalized_key)?;
-        return match fs::remove_file(conv_path) {
-            Ok(_) => Ok(()),
-            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(()),
-            Err(err) => Err(format!("delete chat conversation failed: {err}")),
-        };
-    };
+/// 幂等覆盖聊天索引文件（原子替换并保留一份滚动备份）。
+#[tauri::command]
+fn chat_store_upsert_index(app: tauri::AppHandle, index: serde_json::Value) -> Result<(), String> {
+    write_chat_index_file(&chat_index_path(&app)?, &index)
+}
 
+/// 从索引中移除会话条目；被移除的是当前会话时改为排序首位。
+fn remove_conversation_from_index(
+    index_obj: &mut serde_json::Map<String, serde_json::Value>,
+    conversation_key: &str,
+) {
     if let Some(by_key) = index_obj
         .get_mut("conversationsByKey")
         .and_then(|value| value.as_object_mut())
     {
-        by_key.remove(normalized_key);
+        by_key.remove(conversation_key);
     }
 
     if let Some(order) = index_obj
         .get_mut("conversationOrder")
         .and_then(|value| value.as_array_mut())
     {
-        order.retain(|item| item.as_str() != Some(normalized_key));
+        order.retain(|item| item.as_str() != Some(conversation_key));
     }
 
     let active_key = index_obj
         .get("activeConversationKey")
         .and_then(|value| value.as_str())
         .unwrap_or("");
-    if active_key == normalized_key {
+    if active_key == conversation_key {
         let next_active = index_obj
             .get("conversationOrder")
             .and_then(|value| value.as_array())
@@ -597,9 +1513,13 @@ fn chat_store_delete_conversation(
             serde_json::Value::String(next_active.to_string()),
         );
     }
+}
 
-    chat_store_upsert_index(app.clone(), index)?;
-    let conv_path = conversation_path(&app, normalized_key)?;
+/// 删除会话 JSONL 文件（不存在视为成功），与追加/压缩按文件锁互斥。
+fn remove_conversation_file(app: &tauri::AppHandle, conversation_key: &str) -> Result<(), String> {
+    let conv_path = conversation_path(app, conversation_key)?;
+    let lock = conversation_lock(&conv_path);
+    let _guard = lock.lock().unwrap_or_else(|err| err.into_inner());
     match fs::remove_file(conv_path) {
         Ok(_) => Ok(()),
         Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(()),
@@ -607,15 +1527,226 @@ fn chat_store_delete_conversation(
     }
 }
 
-/// 将系统深链事件透传给 WebView。前端通过 `window.__YC_HANDLE_PAIR_LINK__` 接收并解析。
+/// 删除指定会话的本地存储（索引 + JSONL）。
+#[tauri::command]
+fn chat_store_delete_conversation(
+    app: tauri::AppHandle,
+    conversation_key: String,
+) -> Result<(), String> {
+    let normalized_key = conversation_key.trim();
+    if normalized_key.is_empty() {
+        return Err("conversationKey 不能为空".to_string());
+    }
+
+    let mut index = read_chat_index(&app)?;
+    match index.as_object_mut() {
+        Some(index_obj) => remove_conversation_from_index(index_obj, normalized_key),
+        None => index = serde_json::json!({}),
+    }
+    chat_store_upsert_index(app.clone(), index)?;
+    remove_conversation_file(&app, normalized_key)
+}
+
+/// 选出超出会话数上限时应淘汰的会话：按 `updatedAt` 从旧到新，当前会话不参与淘汰。
+fn select_lru_evictions(index: &serde_json::Value, max_conversations: usize) -> Vec<String> {
+    let Some(by_key) = index
+        .get("conversationsByKey")
+        .and_then(|value| value.as_object())
+    else {
+        return Vec::new();
+    };
+    let excess = by_key.len().saturating_sub(max_conversations.max(1));
+    if excess == 0 {
+        return Vec::new();
+    }
+    let active_key = index
+        .get("activeConversationKey")
+        .and_then(|value| value.as_str())
+        .unwrap_or("");
+    let mut candidates = by_key
+        .iter()
+        .filter(|(key, _)| key.as_str() != active_key)
+        .map(|(key, conv)| {
+            // 前端统一写入 ISO 8601 UTC 时间，字典序即时间序；缺失时视为最旧。
+            let updated_at = conv
+                .get("updatedAt")
+                .and_then(|value| value.as_str())
+                .unwrap_or("");
+            (updated_at.to_string(), key.clone())
+        })
+        .collect::<Vec<_>>();
+    candidates.sort();
+    candidates
+        .into_iter()
+        .take(excess)
+        .map(|(_, key)| key)
+        .collect()
+}
+
+/// 按会话数上限淘汰最久未更新的会话（索引 + JSONL），当前会话不会被淘汰；返回被淘汰的 key。
+#[tauri::command]
+fn chat_store_evict_conversations(
+    app: tauri::AppHandle,
+    max_conversations: Option<usize>,
+) -> Result<Vec<String>, String> {
+    let max_conversations = max_conversations.unwrap_or(CHAT_STORE_DEFAULT_MAX_CONVERSATIONS);
+    let mut index = read_chat_index(&app)?;
+    let evicted = select_lru_evictions(&index, max_conversations);
+    let Some(index_obj) = index.as_object_mut() else {
+        return Ok(Vec::new());
+    };
+    if evicted.is_empty() {
+        return Ok(evicted);
+    }
+    for key in &evicted {
+        remove_conversation_from_index(index_obj, key);
+    }
+    chat_store_upsert_index(app.clone(), index)?;
+    for key in &evicted {
+        remove_conversation_file(&app, key)?;
+    }
+    Ok(evicted)
+}
+
+/// 把 Unix 秒格式化为与前端 `toISOString()` 同格式的 UTC 时间（毫秒固定为 0），便于按字典序比较。
+fn format_utc_iso(unix_secs: u64) -> String {
+    let days = (unix_secs / SECONDS_PER_DAY) as i64;
+    let secs_of_day = unix_secs % SECONDS_PER_DAY;
+    // 公历日期换算（days-from-civil 的逆运算），纪元取 0000-03-01。
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}.000Z",
+        secs_of_day / 3600,
+        secs_of_day % 3600 / 60,
+        secs_of_day % 60
+    )
+}
+
+/// 判断事件或消息的 `ts` 是否早于截止时间；缺少 `ts` 的条目无法判断时长，视为未过期。
+fn is_before_cutoff(item: &serde_json::Value, cutoff: &str) -> bool {
+    item.get("ts")
+        .and_then(|value| value.as_str())
+        .is_some_and(|ts| ts < cutoff)
+}
+
+/// 删除会话文件中 `ts` 早于截止时间的事件，并移除保留快照里 `conversation.messages` 中
+/// 同样过期的消息，有变化时原子改写；返回（移除事件数, 移除快照消息数, 剩余事件数）。
+fn prune_conversation_file(path: &Path, cutoff: &str) -> Result<(usize, usize, usize), String> {
+    let lock = conversation_lock(path);
+    let _guard = lock.lock().unwrap_or_else(|err| err.into_inner());
+    let raw = match fs::read_to_string(path) {
+        Ok(raw) => raw,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok((0, 0, 0)),
+        Err(err) => return Err(format!("read chat conversation failed: {err}")),
+    };
+    let events = raw
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .filter_map(|line| serde_json::from_str::<serde_json::Value>(line).ok())
+        .collect::<Vec<_>>();
+    let total = events.len();
+    let mut kept = events
+        .into_iter()
+        .filter(|event| !is_before_cutoff(event, cutoff))
+        .collect::<Vec<_>>();
+    let removed_events = total - kept.len();
+    let mut removed_messages = 0;
+    for event in &mut kept {
+        if let Some(messages) = event
+            .pointer_mut("/conversation/messages")
+            .and_then(|value| value.as_array_mut())
+        {
+            let before = messages.len();
+            messages.retain(|message| !is_before_cutoff(message, cutoff));
+            removed_messages += before - messages.len();
+        }
+    }
+    // 清空的会话交由删除流程移除文件，这里不改写。
+    if (removed_events > 0 || removed_messages > 0) && !kept.is_empty() {
+        rewrite_conversation_file(path, &kept)?;
+    }
+    Ok((removed_events, removed_messages, kept.len()))
+}
+
+/// 按时长清理聊天记录：删除各会话中早于 `max_age_days` 天的事件与快照消息，清空的会话连同
+/// 索引条目一并删除；返回有内容被移除的会话及其移除数。
+#[tauri::command]
+fn chat_store_prune_by_age(
+    app: tauri::AppHandle,
+    max_age_days: u64,
+) -> Result<Vec<ChatPruneResult>, String> {
+    if max_age_days == 0 {
+        return Err("maxAgeDays 必须大于 0".to_string());
+    }
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default();
+    let cutoff = format_utc_iso(now.saturating_sub(max_age_days.saturating_mul(SECONDS_PER_DAY)));
+
+    let mut index = read_chat_index(&app)?;
+    let mut results = Vec::new();
+    for key in indexed_conversation_keys(&index) {
+        let (removed_events, removed_messages, remaining) =
+            prune_conversation_file(&conversation_path(&app, &key)?, &cutoff)?;
+        if removed_events > 0 || removed_messages > 0 {
+            results.push(ChatPruneResult {
+                conversation_key: key,
+                removed_events,
+                removed_messages,
+                deleted: remaining == 0,
+            });
+        }
+    }
+
+    let emptied = results
+        .iter()
+        .filter(|result| result.deleted)
+        .map(|result| result.conversation_key.as_str())
+        .collect::<Vec<_>>();
+    if emptied.is_empty() {
+        return Ok(results);
+    }
+    if let Some(index_obj) = index.as_object_mut() {
+        for key in &emptied {
+            remove_conversation_from_index(index_obj, key);
+        }
+        chat_store_upsert_index(app.clone(), index)?;
+    }
+    for key in &emptied {
+        remove_conversation_file(&app, key)?;
+    }
+    Ok(results)
+}
+
+/// 深链 host 对应的前端接收钩子；未识别的 host 返回 `None`，不转发。
 #[cfg(any(target_os = "ios", target_os = "macos"))]
-fn forward_pairing_link(app: &tauri::AppHandle, raw_url: &str) {
+fn deep_link_hook(host: &str) -> Option<&'static str> {
+    match host {
+        "pair" => Some("__YC_HANDLE_PAIR_LINK__"),
+        "open" => Some("__YC_HANDLE_OPEN_LINK__"),
+        "tool" => Some("__YC_HANDLE_TOOL_LINK__"),
+        _ => None,
+    }
+}
+
+/// 将系统深链事件透传给 WebView，前端通过 `window.<hook>` 接收并解析。
+#[cfg(any(target_os = "ios", target_os = "macos"))]
+fn forward_deep_link(app: &tauri::AppHandle, hook: &str, raw_url: &str) {
     let encoded = match serde_json::to_string(raw_url) {
         Ok(value) => value,
         Err(_) => return,
     };
-    let script =
-        format!("window.__YC_HANDLE_PAIR_LINK__ && window.__YC_HANDLE_PAIR_LINK__({encoded});");
+    let script = format!("window.{hook} && window.{hook}({encoded});");
     for webview in app.webview_windows().values() {
         let _ = webview.eval(script.clone());
     }
@@ -632,11 +1763,17 @@ pub fn run() {
             auth_store_session,
             auth_load_session,
             auth_clear_session,
+            auth_export_bundle,
+            auth_import_bundle,
             chat_store_bootstrap,
             chat_store_append_events,
             chat_store_load_conversation,
+            chat_store_compact_conversation,
+            chat_store_search,
             chat_store_upsert_index,
             chat_store_delete_conversation,
+            chat_store_evict_conversations,
+            chat_store_prune_by_age,
         ])
         .build(tauri::generate_context!())
         .expect("failed to build mobile tauri app")
@@ -644,10 +1781,161 @@ pub fn run() {
             #[cfg(any(target_os = "ios", target_os = "macos"))]
             if let RunEvent::Opened { urls } = _event {
                 for url in urls {
-                    if url.scheme() == "yc" && url.host_str() == Some("pair") {
-                        forward_pairing_link(_app, url.as_str());
+                    if url.scheme() != "yc" {
+                        continue;
+                    }
+                    if let Some(hook) = url.host_str().and_then(deep_link_hook) {
+                        forward_deep_link(_app, hook, url.as_str());
                     }
                 }
             }
         });
 }
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::{
+        chat_index_backup_path, format_utc_iso, last_chat_event_seq, load_conversation_page,
+        prune_conversation_file, read_chat_index_file, write_chat_index_file,
+    };
+
+    /// 创建本测试独占的临时目录。
+    fn temp_dir(label: &str) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "yc-{label}-{}-{}",
+            std::process::id(),
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|elapsed| elapsed.as_nanos())
+                .unwrap_or_default()
+        ));
+        fs::create_dir_all(&dir).expect("create temp dir");
+        dir
+    }
+
+    #[test]
+    fn corrupt_chat_index_recovers_from_rolling_backup() {
+        let dir = temp_dir("chat-index");
+        let index_path = dir.join("index.json");
+        assert_eq!(
+            read_chat_index_file(&index_path).expect("missing index"),
+            serde_json::json!({})
+        );
+
+        let first = serde_json::json!({ "conversationOrder": ["a"] });
+        let second = serde_json::json!({ "conversationOrder": ["b", "a"] });
+        write_chat_index_file(&index_path, &first).expect("write first");
+        write_chat_index_file(&index_path, &second).expect("write second");
+        assert!(!index_path.with_extension("json.tmp").exists());
+
+        // 模拟写入中途被杀导致的截断主文件。
+        fs::write(&index_path, b"{\"conversationOrder\": [\"b\"").expect("corrupt primary");
+        assert_eq!(read_chat_index_file(&index_path).expect("recover"), first);
+        assert_eq!(
+            read_chat_index_file(&index_path).expect("restored primary"),
+            first
+        );
+
+        fs::write(&index_path, b"{").expect("corrupt primary");
+        fs::write(chat_index_backup_path(&index_path), b"{").expect("corrupt backup");
+        assert!(read_chat_index_file(&index_path).is_err());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn conversation_pages_walk_backwards_from_the_tail() {
+        let dir = temp_dir("chat-page");
+        let path = dir.join("conv.jsonl");
+        // 行数足以跨越多个反向读取分块；末行不带换行，模拟追加中断。
+        let body = (1..=3000)
+            .map(|seq| format!(r#"{{"type":"snapshot","seq":{seq},"ts":"t{seq:05}"}}"#))
+            .collect::<Vec<_>>()
+            .join("\n");
+        fs::write(&path, body).expect("write conversation");
+        let seqs = |rows: &[serde_json::Value]| {
+            rows.iter()
+                .map(|row| row["seq"].as_u64().unwrap_or_default())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(last_chat_event_seq(&path).expect("last seq"), 3000);
+
+        let tail = load_conversation_page(&path, Some(100), None, None).expect("tail");
+        assert_eq!(seqs(&tail.rows), (2901..=3000).collect::<Vec<_>>());
+        assert!(tail.has_more);
+
+        let previous =
+            load_conversation_page(&path, Some(100), Some(2901), None).expect("previous");
+        assert_eq!(seqs(&previous.rows), (2801..=2900).collect::<Vec<_>>());
+        assert!(previous.has_more);
+
+        let first = load_conversation_page(&path, Some(100), None, Some("t00003")).expect("ts");
+        assert_eq!(seqs(&first.rows), vec![1, 2]);
+        assert!(!first.has_more);
+
+        let missing =
+            load_conversation_page(&dir.join("none.jsonl"), Some(10), None, None).expect("missing");
+        assert!(missing.rows.is_empty() && !missing.has_more);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn prune_by_age_drops_events_before_cutoff() {
+        assert_eq!(format_utc_iso(0), "1970-01-01T00:00:00.000Z");
+        assert_eq!(format_utc_iso(951_782_400), "2000-02-29T00:00:00.000Z");
+        assert_eq!(format_utc_iso(1_791_998_999), "2026-10-14T17:29:59.000Z");
+
+        let dir = temp_dir("chat-prune");
+        let path = dir.join("conv.jsonl");
+        let cutoff = "2026-10-01T00:00:00.000Z";
+        let body = [
+            r#"{"type":"message","seq":1,"ts":"2026-09-01T08:00:00.000Z"}"#,
+            r#"{"type":"message","seq":2}"#,
+            r#"{"type":"message","seq":3,"ts":"2026-09-30T23:59:59.999Z"}"#,
+            concat!(
+                r#"{"type":"snapshot","seq":4,"ts":"2026-10-01T00:00:00.000Z","#,
+                r#""conversation":{"messages":[{"id":"m1","ts":"2026-09-02T00:00:00.000Z"},"#,
+                r#"{"id":"m2"},{"id":"m3","ts":"2026-10-01T00:00:00.000Z"}]}}"#,
+            ),
+        ]
+        .join("\n");
+        fs::write(&path, body).expect("write conversation");
+        assert_eq!(
+            prune_conversation_file(&path, cutoff).expect("prune"),
+            (2, 1, 2)
+        );
+        let page = load_conversation_page(&path, None, None, None).expect("page");
+        let seqs = page
+            .rows
+            .iter()
+            .map(|row| row["seq"].as_u64().unwrap_or_default())
+            .collect::<Vec<_>>();
+        assert_eq!(seqs, vec![2, 4]);
+        let message_ids = page.rows[1]["conversation"]["messages"]
+            .as_array()
+            .map(|messages| {
+                messages
+                    .iter()
+                    .map(|message| message["id"].as_str().unwrap_or_default())
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        assert_eq!(message_ids, vec!["m2", "m3"]);
+        assert!(!path.with_extension("jsonl.tmp").exists());
+
+        // 全部过期时保留文件，交由删除流程处理。
+        let stale = dir.join("stale.jsonl");
+        fs::write(&stale, r#"{"seq":1,"ts":"2026-01-01T00:00:00.000Z"}"#).expect("write stale");
+        assert_eq!(
+            prune_conversation_file(&stale, cutoff).expect("prune"),
+            (1, 0, 0)
+        );
+        assert!(stale.exists());
+        assert_eq!(
+            prune_conversation_file(&dir.join("none.jsonl"), cutoff).expect("missing"),
+            (0, 0, 0)
+        );
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -46,6 +46,9 @@ export function createChatFlow({
   const pendingMediaStageByKey = {};
   const dispatchingByConversationKey = {};
   const MEDIA_STAGE_TIMEOUT_MS = 30_000;
+  const MAX_STORED_CONVERSATIONS = 100;
+  const CHAT_RETENTION_DAYS_STORAGE_KEY = "yc_chat_retention_days";
+  const DAY_MS = 24 * 60 * 60 * 1000;
   const MEDIA_INPUT_ACCEPT = "image/*,video/*";
   const FILE_INPUT_ACCEPT = ".pdf,.txt,.md,.json,.csv,.zip,.tar,.gz,.log,.yaml,.yml,.toml,.xml,.doc,.docx,.xls,.xlsx,.ppt,.pptx,.rtf,.js,.ts,.tsx,.jsx,.py,.rs,.go,.java,.kt,.swift,.c,.cpp,.h,.hpp,.sh";
 
@@ -1033,9 +1036,95 @@ export function createChatFlow({
         });
       }
       await persistIndex();
+      await evictStaleConversations();
     }, 260);
   }
 
+  /**
+   * 本地会话数超过上限时淘汰最久未更新的会话（当前会话除外），并同步移出内存状态，
+   * 避免后续索引写回时把已删除的会话带回来。
+   */
+  async function evictStaleConversations() {
+    let evicted = [];
+    try {
+      evicted = await tauriInvoke("chat_store_evict_conversations", {
+        maxConversations: MAX_STORED_CONVERSATIONS,
+      });
+    } catch (error) {
+      addLog(`chat evict conversations failed: ${error}`, {
+        level: "warn",
+        scope: "chat",
+        action: "evict_conversations",
+        outcome: "failed",
+        detail: String(error || ""),
+      });
+      return;
+    }
+    if (!Array.isArray(evicted) || evicted.length === 0) return;
+    for (const key of evicted) {
+      // eslint-disable-next-line no-await-in-loop
+      await deleteConversationByKey(String(key || ""), { deleteStore: false });
+    }
+    addLog(`chat evicted ${evicted.length} stale conversations`, {
+      scope: "chat",
+      action: "evict_conversations",
+      outcome: "success",
+    });
+  }
+
+  /**
+   * 读取按时长清理聊天记录的保留天数；未设置或非法时返回 0（不清理）。
+   */
+  function readChatRetentionDays() {
+    try {
+      const days = Number.parseInt(localStorage.getItem(CHAT_RETENTION_DAYS_STORAGE_KEY) || "", 10);
+      return Number.isFinite(days) && days > 0 ? days : 0;
+    } catch (_) {
+      return 0;
+    }
+  }
+
+  /**
+   * 按保留天数清理本地聊天记录：被清空的会话移出内存状态，其余会话丢弃内存中同样过期的消息，
+   * 避免后续快照落盘时把已清理的消息写回。
+   */
+  async function pruneExpiredChatHistory() {
+    const maxAgeDays = readChatRetentionDays();
+    if (maxAgeDays <= 0) return;
+    let results = [];
+    try {
+      results = await tauriInvoke("chat_store_prune_by_age", { maxAgeDays });
+    } catch (error) {
+      addLog(`chat prune by age failed: ${error}`, {
+        level: "warn",
+        scope: "chat",
+        action: "prune_by_age",
+        outcome: "failed",
+        detail: String(error || ""),
+      });
+      return;
+    }
+    const cutoff = new Date(Date.now() - maxAgeDays * DAY_MS).toISOString();
+    const rows = Array.isArray(results) ? results : [];
+    for (const row of rows) {
+      if (!row || !row.deleted) continue;
+      // eslint-disable-next-line no-await-in-loop
+      await deleteConversationByKey(String(row.conversationKey || ""), { deleteStore: false });
+    }
+    Object.values(asMap(state.chat.conversationsByKey)).forEach((conv) => {
+      if (!conv || !Array.isArray(conv.messages)) return;
+      conv.messages = conv.messages.filter((msg) => !msg || !msg.ts || String(msg.ts) >= cutoff);
+    });
+    if (rows.length === 0) return;
+    await persistIndex();
+    render();
+    addLog(`chat pruned history older than ${maxAgeDays} days in ${rows.length} conversations`, {
+      scope: "chat",
+      action: "prune_by_age",
+      outcome: "success",
+    });
+  }
+
   function touchConversation(conv, { persist = true } = {}) {
     normalizeMessageSelectionByConversation(conv);
     conv.updatedAt = new Date().toISOString();
@@ -1494,14 +1583,39 @@ export function createChatFlow({
     return true;
   }
 
+  async function compactConversationLog(key) {
+    try {
+      const result = asMap(await tauriInvoke("chat_store_compact_conversation", {
+        conversationKey: key,
+      }));
+      addLog(`chat compact ${result.linesBefore} -> ${result.linesAfter} lines`, {
+        scope: "chat",
+        action: "compact_conversation",
+        outcome: "success",
+      });
+    } catch (error) {
+      addLog(`chat compact failed: ${error}`, {
+        level: "warn",
+        scope: "chat",
+        action: "compact_conversation",
+        outcome: "failed",
+        detail: String(error || ""),
+      });
+    }
+  }
+
   async function hydrateConversationFromLog(key) {
     try {
-      const rows = await tauriInvoke("chat_store_load_conversation", {
+      const page = asMap(await tauriInvoke("chat_store_load_conversation", {
         conversationKey: key,
         limit: 120,
-      });
-      const list = Array.isArray(rows) ? rows : [];
+      }));
+      const list = Array.isArray(page.rows) ? page.rows : [];
       if (!list.length) return;
+      if (page.hasMore) {
+        // 尾部一页之前仍有更早的行，说明历史较长，后台折叠为最新状态，缩短后续加载。
+        void compactConversationLog(key);
+      }
       const snapshots = list
         .map((item) => asMap(item))
         .filter((item) => String(item.type || "") === "snapshot")
@@ -2989,6 +3103,7 @@ export function createChatFlow({
       normalizeConversationOnlineState();
       void persistIndex();
       render();
+      void pruneExpiredChatHistory();
     } catch (error) {
       addLog(`chat bootstrap failed: ${error}`, {
         level: "warn",
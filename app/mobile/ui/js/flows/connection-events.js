@@ -124,6 +124,25 @@ export function createConnectionEvents({
         return;
       }
 
+      if (type === "resync_hint") {
+        // relay 检测到 sidecar 上行 seq 断档，拉取最新快照补齐丢失的状态。
+        addLog(
+          `检测到事件断档，已请求刷新快照 (seq ${payload.expectedSeq} -> ${payload.receivedSeq})`,
+          {
+            level: "warn",
+            scope: "connection",
+            action: "resync",
+            outcome: "started",
+            traceId,
+            eventId,
+            eventType: type,
+            hostId,
+          },
+        );
+        requestToolsRefresh(hostId);
+        return;
+      }
+
       if (type === "tools_snapshot") {
         const parsed = asListOfMap(payload.tools);
         runtime.tools = sanitizeTools(hostId, parsed, false);
@@ -169,6 +188,11 @@ export function createConnectionEvents({
         return;
       }
 
+      if (type === "tool_label_updated") {
+        handleToolLabelUpdated(hostId, payload, { traceId, eventId, eventType: type });
+        return;
+      }
+
       if (type === "tool_details_snapshot") {
         applyToolDetailsSnapshot(hostId, payload, { traceId, eventId, eventType: type });
         return;
@@ -298,7 +322,7 @@ export function createConnectionEvents({
     const ok = asBool(payload.ok);
     const reason = String(payload.reason || "");
     const rawAction = String(payload.action || "connect");
-    const action = ["connect", "disconnect", "refresh", "reset"].includes(rawAction)
+    const action = ["connect", "disconnect", "refresh", "reset", "prune"].includes(rawAction)
       ? rawAction
       : "connect";
     const host = hostById(hostId);
@@ -317,6 +341,11 @@ export function createConnectionEvents({
       connectedTool || candidateTool || { name: logicalToolId || toolId, toolId: logicalToolId || toolId },
     );
 
+    if (action === "prune") {
+      handleToolPruned(hostId, runtime, toolId, ok, reason, eventMeta);
+      return;
+    }
+
     if (isConnect && toolId) {
       const pendingTraceId = String(runtime.toolConnectTraceIds[toolId] || "");
       const incomingTraceId = String(eventMeta.traceId || "");
@@ -660,6 +689,79 @@ export function createConnectionEvents({
     requestToolsRefresh(hostId);
   }
 
+  function handleToolPruned(hostId, runtime, toolId, ok, reason, eventMeta = {}) {
+    // sidecar 自动移出长期未被发现的工具：不对应任何用户请求，不弹窗、不重绑重试。
+    const host = hostById(hostId);
+    const logMeta = {
+      scope: "tool_whitelist",
+      action: "prune_tool",
+      traceId: String(eventMeta.traceId || ""),
+      eventId: String(eventMeta.eventId || ""),
+      eventType: String(eventMeta.eventType || ""),
+      hostId,
+      hostName: host ? host.displayName : "",
+      toolId,
+      detail: reason,
+    };
+    if (!ok) {
+      addLog(`自动移出缺失工具失败 (${host ? host.displayName : hostId}): ${toolId || "--"} ${reason}`, {
+        ...logMeta,
+        level: "warn",
+        outcome: "failed",
+      });
+      return;
+    }
+
+    if (toolId) {
+      const prunedTool = findRuntimeTool(runtime, hostId, toolId);
+      runtime.tools = runtime.tools.filter((item) => item !== prunedTool);
+      delete runtime.connectingToolIds[toolId];
+      clearToolConnectTimer(runtime, toolId);
+    }
+    addLog(`已自动移出长期缺失的工具 (${host ? host.displayName : hostId}): ${toolId || "--"}`, {
+      ...logMeta,
+      outcome: "success",
+    });
+  }
+
+  function handleToolLabelUpdated(hostId, payload, eventMeta = {}) {
+    const runtime = ensureRuntime(hostId);
+    if (!runtime) return;
+
+    const host = hostById(hostId);
+    const toolId = String(payload.toolId || "");
+    const ok = asBool(payload.ok);
+    const changed = asBool(payload.changed);
+    const reason = String(payload.reason || "");
+    const logMeta = {
+      scope: "tool_label",
+      action: "set_tool_label",
+      traceId: String(eventMeta.traceId || ""),
+      eventId: String(eventMeta.eventId || ""),
+      eventType: String(eventMeta.eventType || ""),
+      hostId,
+      hostName: host ? host.displayName : "",
+      toolId,
+      detail: reason,
+    };
+
+    if (!ok) {
+      addLog(`工具显示名更新失败 (${host ? host.displayName : hostId}): ${toolId || "--"} ${reason}`, {
+        ...logMeta,
+        level: "warn",
+        outcome: "failed",
+      });
+      openHostNoticeModal("显示名更新失败", reason || "请稍后重试。");
+      return;
+    }
+
+    // 成功后 sidecar 会重新下发 tools_snapshot，卡片显示名随快照更新。
+    addLog(`工具显示名已更新 (${host ? host.displayName : hostId}): ${toolId || "--"}`, {
+      ...logMeta,
+      outcome: changed ? "success" : "noop",
+    });
+  }
+
   function applyMetricsSnapshot(hostId, payload) {
     const runtime = ensureRuntime(hostId);
     if (!runtime) {
@@ -24,6 +24,7 @@ export function createConnectionSendOps({
    * @param {string} hostId 宿主机标识。
    * @param {string} type 事件类型。
    * @param {object} payload 事件载荷。
+   * @param {object} [options] 发送选项；`ackRequired` 为 true 时要求 sidecar 回 `ack`，relay 超时未收到会重发。
    * @returns {boolean} 是否发送成功。
    */
   function sendSocketEvent(hostId, type, payload, options = {}) {
@@ -61,6 +62,9 @@ export function createConnectionSendOps({
       ts: new Date().toISOString(),
       payload: payloadMap,
     };
+    if (options.ackRequired === true) {
+      event.ackRequired = true;
+    }
     const encoded = JSON.stringify(event);
 
     try {
@@ -108,6 +112,14 @@ export function createConnectionSendOps({
     sendSocketEvent(hostId, "tools_refresh_request", {}, { action: "refresh_tools" });
   }
 
+  /**
+   * 主动断开前告知 sidecar 本设备离开，sidecar 据此停止为其补采详情。
+   * @param {string} hostId 宿主机标识。
+   */
+  function notifyAppDisconnect(hostId) {
+    sendSocketEvent(hostId, "app_disconnect", {}, { action: "app_disconnect" });
+  }
+
   /**
    * 请求 sidecar 刷新工具详情。
    * @param {string} hostId 宿主机标识。
@@ -217,6 +229,7 @@ export function createConnectionSendOps({
   return {
     sendSocketEvent,
     requestToolsRefresh,
+    notifyAppDisconnect,
     requestToolDetailsRefresh,
     requestControllerRebind,
     requestToolLaunch,
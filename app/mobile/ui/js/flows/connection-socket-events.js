@@ -4,6 +4,7 @@
 
 import { state } from "../state/store.js";
 import { extractWireMeta } from "../utils/log.js";
+import { inflateWireText, isCompressedWireText } from "../utils/wire-compression.js";
 
 /**
  * 创建 socket 事件处理器。
@@ -96,7 +97,45 @@ export function createSocketEventHandlers({
     const host = hostById(hostId);
     if (!current || !host || current.connectionEpoch !== connectionEpoch || current.socket !== socket) return;
 
-    const text = String(event.data || "");
+    const raw = String(event.data || "");
+    if (!current.inboundChain && !isCompressedWireText(raw)) {
+      handleIncomingText(hostId, host, raw);
+      return;
+    }
+
+    // 压缩帧需异步解压；解压期间到达的后续帧排在其后，保证按到达顺序处理。
+    const chain = (current.inboundChain || Promise.resolve())
+      .then(() => inflateWireText(raw))
+      .then((text) => {
+        if (current.connectionEpoch !== connectionEpoch || current.socket !== socket) return;
+        handleIncomingText(hostId, host, text);
+      })
+      .catch((error) => {
+        addLog(`drop compressed frame (${host.displayName}): ${error}`, {
+          level: "warn",
+          scope: "ws_in",
+          action: "inflate_frame",
+          outcome: "failed",
+          hostId,
+          hostName: host.displayName,
+          detail: String(error || ""),
+        });
+      })
+      .finally(() => {
+        if (current.inboundChain === chain) {
+          current.inboundChain = null;
+        }
+      });
+    current.inboundChain = chain;
+  }
+
+  /**
+   * 记录并分发一条（已解压的）下行事件。
+   * @param {string} hostId 宿主机标识。
+   * @param {object} host 宿主机配置。
+   * @param {string} text envelope 文本。
+   */
+  function handleIncomingText(hostId, host, text) {
     const wireMeta = extractWireMeta(text);
     state.eventIn += 1;
     addLog(formatWireLog("IN", host.displayName, text), {
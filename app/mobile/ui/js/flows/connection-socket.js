@@ -22,6 +22,7 @@ export function createConnectionSocketOps({
   auth,
   events,
   requestToolsRefresh,
+  notifyAppDisconnect,
 }) {
   const socketEvents = createSocketEventHandlers({
     hostById,
@@ -236,6 +237,7 @@ export function createConnectionSocketOps({
     const triggerReconnect = options.triggerReconnect !== false;
     runtime.connectionEpoch += 1;
     clearReconnectTimer(runtime);
+    if (runtime.socket && runtime.connected) notifyAppDisconnect(hostId);
 
     const socket = runtime.socket;
     runtime.socket = null;
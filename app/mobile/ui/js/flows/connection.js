@@ -113,6 +113,7 @@ export function createConnectionFlow({
     auth,
     events,
     requestToolsRefresh: sendOps.requestToolsRefresh,
+    notifyAppDisconnect: sendOps.notifyAppDisconnect,
   });
 
   return {
@@ -192,13 +192,15 @@ export function createHostManageFlow({
     const hasSystemMetrics = Object.keys(system).length > 0;
     const hasSidecarMetrics = Object.keys(sidecar).length > 0;
 
+    // sidecar 开启平滑时附带 EMA 均值，读数跳动时以均值作参考。
+    const avgSuffix = (value) => (value == null ? "" : `，均值 ${fmt2(value)}%`);
     const systemRows = hasSystemMetrics
       ? [
         ["状态", hostStatusLabel(host.hostId)],
-        ["CPU", `${fmt2(system.cpuPercent)}%`],
+        ["CPU", `${fmt2(system.cpuPercent)}%${avgSuffix(system.cpuPercentAvg)}`],
         [
           "内存",
-          `${formatGbFromMb(system.memoryUsedMb)} / ${formatGbFromMb(system.memoryTotalMb)} GB (${fmt2(system.memoryUsedPercent)}%)`,
+          `${formatGbFromMb(system.memoryUsedMb)} / ${formatGbFromMb(system.memoryTotalMb)} GB (${fmt2(system.memoryUsedPercent)}%${avgSuffix(system.memoryUsedPercentAvg)})`,
         ],
         [
           "磁盘",
@@ -1,6 +1,6 @@
 // 文件职责：
 // 1. 将后端/本地错误码映射为面向用户的配对失败提示。
-// 2. 统一主按钮动作建议（扫码/粘贴/手动）。
+// 2. 统一主按钮动作建议（扫码/粘贴/手动/重试）。
 
 /**
  * 将配对错误码映射为用户可理解的失败弹窗内容。
@@ -97,6 +97,14 @@ export function mapPairFailure(code, message, suggestion, primaryAction) {
       primaryAction: "scan",
     };
   }
+  if (normalizedCode === "USER_PRESENCE_FAILED") {
+    return {
+      reason: "身份验证未通过",
+      suggestion: "配对需要验证面容/指纹或设备密码，请重新验证后继续。",
+      primaryLabel: "重新验证",
+      primaryAction: "retry",
+    };
+  }
   if (normalizedCode === "ACCESS_TOKEN_EXPIRED" || normalizedCode === "ACCESS_TOKEN_INVALID") {
     return {
       reason: "设备凭证失效",
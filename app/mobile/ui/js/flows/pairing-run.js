@@ -4,6 +4,7 @@
 
 import { parsePairingLink } from "../services/pairing-link.js";
 import { parseRelayWsUrl, relayRequestJson } from "../services/relay-api.js";
+import { tauriErrorCode } from "../services/tauri.js";
 import { normalizedDeviceName } from "../utils/platform.js";
 import { asMap } from "../utils/type.js";
 
@@ -28,6 +29,9 @@ export function createPairingRunner({
   notifyIfDuplicateDisplayName,
   tauriInvoke,
 }) {
+  // 最近一次配对入参：用户在场校验失败后可原样重试。
+  let lastPairingAttempt = null;
+
   async function runPairingFromLink(rawValue, source = "paste") {
     const parsed = parsePairingLink(rawValue);
     if (!parsed) {
@@ -73,6 +77,7 @@ export function createPairingRunner({
   async function runPairing(parsed, source) {
     if (state.pairingBusy) return;
     state.pairingBusy = true;
+    lastPairingAttempt = { parsed, source };
 
     try {
       const relayUrl = String(parsed.relayUrl || "").trim();
@@ -138,6 +143,7 @@ export function createPairingRunner({
       const keyId = String(binding.keyId || "");
       const devicePubKey = String(binding.publicKey || "");
       const proofPayload = `pair-exchange\n${systemId}\n${state.deviceId}\n${keyId}`;
+      // 签名需用户在场（生物识别/设备密码），校验失败时抛出 USER_PRESENCE_FAILED。
       const proofSigned = await tauriInvoke("auth_sign_payload", {
         deviceId: state.deviceId,
         payload: proofPayload,
@@ -231,7 +237,7 @@ export function createPairingRunner({
       await connectHost(hostId, { manual: true, resetRetry: true });
       notifyIfDuplicateDisplayName(hostId);
     } catch (error) {
-      const code = String(error && error.code ? error.code : "").trim();
+      const code = String(error && error.code ? error.code : tauriErrorCode(error)).trim();
       showPairFailure(
         mapPairFailure(
           code || "RELAY_UNREACHABLE",
@@ -260,6 +266,11 @@ export function createPairingRunner({
     });
   }
 
+  async function retryLastPairing() {
+    if (!lastPairingAttempt) return;
+    await runPairing(lastPairingAttempt.parsed, lastPairingAttempt.source);
+  }
+
   function tryApplyLaunchPairingLink() {
     try {
       const launchUrl = new URL(window.location.href);
@@ -290,6 +301,7 @@ export function createPairingRunner({
   return {
     runPairingFromLink,
     runPairingFromManual,
+    retryLastPairing,
     bindPairingLinkBridge,
     tryApplyLaunchPairingLink,
   };
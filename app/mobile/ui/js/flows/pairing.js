@@ -124,7 +124,9 @@ export function createPairingFlow({
 
   function bindFailureActionHandler() {
     return (action) => {
-      if (action === "scan") {
+      if (action === "retry") {
+        void runner.retryLastPairing();
+      } else if (action === "scan") {
         openPairFlow("scan");
       } else if (action === "manual") {
         openPairFlow("manual");
@@ -225,6 +225,7 @@ export function createToolManageFlow({
       action: "connect_tool",
       traceId,
       toolId: id,
+      ackRequired: true,
     });
     if (!sent) {
       delete runtime.connectingToolIds[id];
@@ -308,6 +309,7 @@ export function createToolManageFlow({
       action: "disconnect_tool",
       traceId,
       toolId: runtimeToolId,
+      ackRequired: true,
     });
     if (!sent) {
       setToolHidden(hostId, logicalToolId, false);
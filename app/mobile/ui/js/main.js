@@ -330,6 +330,138 @@ function guardUiHandler(scope, handler) {
   };
 }
 
+/**
+ * 解析深链查询参数；无法解析时返回空参数。
+ * @param {string} rawUrl 原始深链。
+ * @returns {URLSearchParams}
+ */
+function deepLinkParams(rawUrl) {
+  try {
+    return new URL(String(rawUrl || "")).searchParams;
+  } catch (_error) {
+    return new URLSearchParams();
+  }
+}
+
+/**
+ * 查找上报了指定工具的宿主机。
+ * @param {string} toolId 工具标识。
+ * @returns {string}
+ */
+function hostIdForTool(toolId) {
+  const host = hostState.visibleHosts().find((item) => hostReportsTool(item.hostId, toolId));
+  return host ? String(host.hostId || "") : "";
+}
+
+/**
+ * 判断宿主机是否已上报指定工具。
+ * @param {string} hostId 宿主机标识。
+ * @param {string} toolId 工具标识。
+ * @returns {boolean}
+ */
+function hostReportsTool(hostId, toolId) {
+  const runtime = state.runtimes[hostId];
+  const tools = runtime && Array.isArray(runtime.tools) ? runtime.tools : [];
+  return tools.some((tool) => String(tool.toolId || "") === toolId);
+}
+
+/** 冷启动时暂存尚无法打开的导航深链的最长时间（毫秒）。 */
+const PENDING_NAVIGATION_LINK_TTL_MS = 30_000;
+
+/** 等待会话水合或工具上报后再打开的导航深链。 */
+let pendingNavigationLink = null;
+
+/**
+ * 打开 `yc://open?conversation=...` 指向的会话；会话尚未水合时返回 false。
+ * @param {string} rawUrl 原始深链。
+ * @returns {boolean}
+ */
+function applyOpenLink(rawUrl) {
+  const key = String(deepLinkParams(rawUrl).get("conversation") || "").trim();
+  if (!key) return true;
+  if (!state.chat.hydrated || !state.chat.conversationsByKey[key]) return false;
+  switchTab("chat");
+  chatFlow.openConversation(key);
+  return true;
+}
+
+/**
+ * 打开 `yc://tool?toolId=...&hostId=...` 指向的工具详情（缺少 hostId 时按工具归属查找）；
+ * 工具尚未上报时返回 false。
+ * @param {string} rawUrl 原始深链。
+ * @returns {boolean}
+ */
+function applyToolLink(rawUrl) {
+  const params = deepLinkParams(rawUrl);
+  const toolId = String(params.get("toolId") || "").trim();
+  if (!toolId) return true;
+  const hostId = String(params.get("hostId") || "").trim() || hostIdForTool(toolId);
+  if (!hostId || !hostReportsTool(hostId, toolId)) return false;
+  switchTab("ops");
+  openToolDetailGuard(hostId, toolId);
+  return true;
+}
+
+/**
+ * 立即处理导航深链；目标尚未就绪时暂存，待水合或下一次渲染时重试（新链接覆盖旧链接）。
+ * @param {(rawUrl: string) => boolean} apply 深链处理函数。
+ * @param {string} rawUrl 原始深链。
+ */
+function handleNavigationLink(apply, rawUrl) {
+  pendingNavigationLink = null;
+  if (apply(rawUrl)) return;
+  pendingNavigationLink = { apply, rawUrl, expiresAt: Date.now() + PENDING_NAVIGATION_LINK_TTL_MS };
+}
+
+/** 重试暂存的导航深链；超时后丢弃。 */
+function flushPendingNavigationLink() {
+  const pending = pendingNavigationLink;
+  if (!pending) return;
+  pendingNavigationLink = null;
+  if (Date.now() > pending.expiresAt) return;
+  try {
+    if (!pending.apply(pending.rawUrl)) pendingNavigationLink = pending;
+  } catch (error) {
+    reportUiError("deep_link_pending", error);
+  }
+}
+
+/**
+ * 绑定通知深链：`yc://open?conversation=...` 打开会话，
+ * `yc://tool?toolId=...&hostId=...` 打开工具详情；冷启动时目标未就绪的链接会暂存重试。
+ */
+function bindNavigationLinkBridge() {
+  window.__YC_HANDLE_OPEN_LINK__ = guardUiHandler("deep_link_open", (rawUrl) => {
+    handleNavigationLink(applyOpenLink, rawUrl);
+  });
+  window.__YC_HANDLE_TOOL_LINK__ = guardUiHandler("deep_link_tool", (rawUrl) => {
+    handleNavigationLink(applyToolLink, rawUrl);
+  });
+}
+
+/**
+ * 冷启动时若以 `yc://open` / `yc://tool` 拉起，交给对应深链处理（与配对启动链接一致）。
+ * @returns {boolean} 是否识别为导航深链。
+ */
+function tryApplyLaunchNavigationLink() {
+  let launchUrl;
+  try {
+    launchUrl = new URL(window.location.href);
+  } catch (_) {
+    return false;
+  }
+  if (launchUrl.protocol !== "yc:") return false;
+  if (launchUrl.hostname === "open") {
+    handleNavigationLink(applyOpenLink, launchUrl.toString());
+    return true;
+  }
+  if (launchUrl.hostname === "tool") {
+    handleNavigationLink(applyToolLink, launchUrl.toString());
+    return true;
+  }
+  return false;
+}
+
 function switchTab(tab) {
   // 切页前先释放输入焦点，避免 iOS 底部输入附件条遮挡交互。
   if (document.activeElement instanceof HTMLElement) {
@@ -362,6 +494,7 @@ function render() {
   } catch (error) {
     reportUiError("render", error);
   }
+  flushPendingNavigationLink();
 }
 
 function bindEvents() {
@@ -444,8 +577,10 @@ function init() {
     reportUiError("unhandled_rejection", event.reason || "unknown rejection");
   });
   pairingFlow.bindPairingLinkBridge();
+  bindNavigationLinkBridge();
   pairingFlow.tryApplyLaunchPairingLink();
-  void chatFlow.hydrateChatState();
+  tryApplyLaunchNavigationLink();
+  void chatFlow.hydrateChatState().then(flushPendingNavigationLink);
 
   bindEvents();
 
@@ -1344,6 +1344,17 @@ export function createToolDetailModal({
       summaryRows.push(["原因", reason]);
     }
 
+    if (isOpenCode) {
+      const gitBranch = String(detailData.gitBranch || "").trim();
+      let gitDirtyText = "";
+      if (detailData.gitDirty === true) {
+        gitDirtyText = "（有未提交改动）";
+      } else if (detailData.gitDirty === false) {
+        gitDirtyText = "（无改动）";
+      }
+      summaryRows.push(["Git 分支", gitBranch ? `${gitBranch}${gitDirtyText}` : "--"]);
+    }
+
     const detailsRows = [
       ["App Link", runtime.connected ? "Connected" : "Disconnected"],
       [
@@ -1,6 +1,7 @@
 // 文件职责：
 // 1. 封装 Tauri invoke 的兼容调用入口。
 // 2. 屏蔽 v1/v2 API 差异，避免业务层分散处理。
+// 3. 从原生命令的字符串错误中提取错误码（形如 `CODE: detail`）。
 
 /**
  * 调用 Tauri 命令（兼容 v2 与历史 API）。
@@ -19,3 +20,13 @@ export async function tauriInvoke(command, payload = {}) {
   }
   throw new Error("Tauri invoke 不可用");
 }
+
+/**
+ * 提取原生命令错误码；Rust 侧以 `CODE: detail` 形式返回的错误才有码。
+ * @param {unknown} error invoke 抛出的错误。
+ * @returns {string} 错误码，无法识别时返回空串。
+ */
+export function tauriErrorCode(error) {
+  const match = /^([A-Z][A-Z0-9_]+):/.exec(String(error || "").trim());
+  return match ? match[1] : "";
+}
@@ -2,6 +2,9 @@
 // 1. 统一 App 侧 WS 连接 URL 组装逻辑。
 // 2. 将协议字段拼装从业务流程中抽离，减少重复代码。
 
+/** App 期望的协议版本，relay 在 server_presence 中回传协商结果。 */
+const APP_PROTOCOL_VERSION = 1;
+
 /** 组装 App 连接 Relay 的 WS URL（含鉴权 query）。 */
 export function buildAppWsUrl({
   relayUrl,
@@ -22,5 +25,6 @@ export function buildAppWsUrl({
   url.searchParams.set("ts", ts);
   url.searchParams.set("nonce", nonce);
   url.searchParams.set("sig", sig);
+  url.searchParams.set("protocolVersion", String(APP_PROTOCOL_VERSION));
   return url;
 }
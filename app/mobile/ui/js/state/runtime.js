@@ -267,6 +267,10 @@ export function createRuntimeState({ persistConfig }) {
     if (alias) {
       return alias;
     }
+    const label = String((tool && tool.displayName) || "").trim();
+    if (label) {
+      return label;
+    }
     const rawName = String((tool && tool.name) || "").trim();
     return rawName || "Unknown Tool";
   }
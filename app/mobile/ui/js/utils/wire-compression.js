@@ -0,0 +1,50 @@
+// 文件职责：
+// 1. 识别 sidecar 下发的 `type=compressed` 大帧包装（gzip + base64）并解压为内层 envelope 文本。
+// 2. 解压后以外层（relay 注入的可信字段）覆盖内层同名字段，与 Rust 协议库行为一致。
+
+const COMPRESSED_EVENT_TYPE = "compressed";
+const TRUSTED_OUTER_FIELDS = ["systemId", "sourceClientType", "sourceDeviceId", "peerId"];
+
+/**
+ * 粗略判断文本是否可能为压缩包装（避免对每帧做 JSON 解析）。
+ * @param {string} text 原始文本。
+ * @returns {boolean}
+ */
+export function isCompressedWireText(text) {
+  return String(text || "").includes(`"type":"${COMPRESSED_EVENT_TYPE}"`);
+}
+
+/**
+ * 解压压缩包装；非压缩帧原样返回。
+ * @param {string} text 原始文本。
+ * @returns {Promise<string>} 内层 envelope 文本。
+ */
+export async function inflateWireText(text) {
+  const raw = String(text || "");
+  if (!isCompressedWireText(raw)) {
+    return raw;
+  }
+  const outer = JSON.parse(raw);
+  if (!outer || outer.type !== COMPRESSED_EVENT_TYPE) {
+    return raw;
+  }
+  const payload = outer.payload || {};
+  if (payload.enc !== "gzip") {
+    throw new Error(`unsupported compressed encoding: ${payload.enc}`);
+  }
+  const binary = atob(String(payload.b64 || ""));
+  const bytes = new Uint8Array(binary.length);
+  for (let i = 0; i < binary.length; i += 1) {
+    bytes[i] = binary.charCodeAt(i);
+  }
+  const stream = new Blob([bytes]).stream().pipeThrough(new DecompressionStream("gzip"));
+  const inner = JSON.parse(await new Response(stream).text());
+  TRUSTED_OUTER_FIELDS.forEach((field) => {
+    if (field in outer) {
+      inner[field] = outer[field];
+    } else {
+      delete inner[field];
+    }
+  });
+  return JSON.stringify(inner);
+}
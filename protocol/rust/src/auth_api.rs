@@ -0,0 +1,340 @@
+// 文件职责：
+// 1) 定义 relay 配对换发、配对令牌作废、刷新、吊销、批量吊销、改名、设备列表接口的请求/响应结构，relay 与 Rust 客户端共用同一份类型。
+// 2) 字段统一按 camelCase 序列化，保持与 relay 现有 HTTP 响应逐字段一致，避免各端手写镜像时字段名漂移。
+
+use serde::{Deserialize, Serialize};
+
+/// 配对鉴权方式。
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PairAuthMode {
+    PairTicket,
+}
+
+/// 配对换发请求（`POST /v1/pair/exchange`）。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PairExchangeRequest {
+    pub system_id: String,
+    pub device_id: String,
+    #[serde(default)]
+    pub device_name: String,
+    /// 兼容字段：旧客户端可能仍上传 pairToken，新版将显式拒绝。
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pair_token: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pair_ticket: Option<String>,
+    pub device_pub_key: String,
+    pub key_id: String,
+    pub proof: String,
+}
+
+/// 配对换发数据。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PairExchangeData {
+    pub auth_mode: PairAuthMode,
+    pub access_token: String,
+    pub refresh_token: String,
+    pub key_id: String,
+    pub credential_id: String,
+    pub access_expires_in_sec: u64,
+    pub refresh_expires_in_sec: u64,
+}
+
+/// 刷新请求（`POST /v1/auth/refresh`）。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuthRefreshRequest {
+    pub system_id: String,
+    pub device_id: String,
+    pub refresh_token: String,
+    pub key_id: String,
+    pub ts: String,
+    pub nonce: String,
+    pub sig: String,
+}
+
+/// 刷新返回。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuthRefreshData {
+    pub access_token: String,
+    pub refresh_token: String,
+    pub key_id: String,
+    pub credential_id: String,
+    pub access_expires_in_sec: u64,
+    pub refresh_expires_in_sec: u64,
+}
+
+/// 吊销请求（`POST /v1/auth/revoke-device`）。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuthRevokeDeviceRequest {
+    pub system_id: String,
+    pub device_id: String,
+    pub target_device_id: String,
+    pub access_token: String,
+    pub key_id: String,
+    pub ts: String,
+    pub nonce: String,
+    pub sig: String,
+}
+
+/// 吊销结果。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuthRevokeDeviceData {
+    pub target_device_id: String,
+}
+
+/// 批量吊销请求（`POST /v1/auth/revoke-all`）：由仍有效的设备 PoP 签名发起，
+/// 或在设备全部失效时携带 sidecar 当前 pairToken 兜底（此时 PoP 字段可留空）。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuthRevokeAllRequest {
+    pub system_id: String,
+    #[serde(default)]
+    pub device_id: String,
+    #[serde(default)]
+    pub access_token: String,
+    #[serde(default)]
+    pub key_id: String,
+    #[serde(default)]
+    pub ts: String,
+    #[serde(default)]
+    pub nonce: String,
+    #[serde(default)]
+    pub sig: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pair_token: Option<String>,
+    // 为 true 时连同发起方设备一起吊销；默认保留发起方。
+    #[serde(default)]
+    pub include_self: bool,
+}
+
+/// 批量吊销结果。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuthRevokeAllData {
+    pub revoked_count: usize,
+    pub revoked_device_ids: Vec<String>,
+}
+
+/// 配对令牌作废请求（`POST /v1/pair/revoke-token`），由已绑定设备签名发起。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PairRevokeTokenRequest {
+    pub system_id: String,
+    pub device_id: String,
+    pub access_token: String,
+    pub key_id: String,
+    pub ts: String,
+    pub nonce: String,
+    pub sig: String,
+}
+
+/// 配对令牌作废结果。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PairRevokeTokenData {
+    /// 新令牌生效时间（RFC3339）。
+    pub pair_token_updated_at: String,
+}
+
+/// 设备改名请求（`POST /v1/auth/rename-device`），成功时返回更新后的 `DeviceEntry`。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuthRenameDeviceRequest {
+    pub system_id: String,
+    pub device_id: String,
+    pub target_device_id: String,
+    pub new_name: String,
+    pub access_token: String,
+    pub key_id: String,
+    pub ts: String,
+    pub nonce: String,
+    pub sig: String,
+}
+
+/// 设备列表查询参数（`GET /v1/auth/devices`）。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuthDevicesQuery {
+    pub system_id: String,
+    pub device_id: String,
+    pub access_token: String,
+    pub key_id: String,
+    pub ts: String,
+    pub nonce: String,
+    pub sig: String,
+    /// 单页条数上限（可选，超出 relay 上限时截断）。
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limit: Option<usize>,
+    /// 分页游标：上一页最后一个 deviceId（可选）。
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cursor: Option<String>,
+}
+
+/// 设备最近一次鉴权失败记录。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuthFailureRecord {
+    /// 失败错误码（如 `ACCESS_TOKEN_EXPIRED`）。
+    pub code: String,
+    /// 失败时间（RFC3339）。
+    pub at: String,
+}
+
+/// 设备列表项。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeviceEntry {
+    pub device_id: String,
+    pub device_name: String,
+    pub key_id: String,
+    pub status: String,
+    pub created_at: String,
+    pub last_seen_at: String,
+    pub revoked_at: Option<String>,
+    /// 最近一次连接鉴权失败（成功连接后清除）。
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_auth_failure: Option<AuthFailureRecord>,
+}
+
+/// 设备列表返回。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuthDevicesData {
+    pub devices: Vec<DeviceEntry>,
+    /// 仍有后续设备时返回下一页游标。
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{
+        AuthDevicesData, AuthFailureRecord, AuthRefreshData, AuthRevokeDeviceData,
+        AuthRevokeDeviceRequest, DeviceEntry, PairAuthMode, PairExchangeData, PairExchangeRequest,
+    };
+
+    #[test]
+    fn responses_keep_relay_wire_shape() {
+        let exchange = PairExchangeData {
+            auth_mode: PairAuthMode::PairTicket,
+            access_token: "yat_v1.a.b".to_string(),
+            refresh_token: "yrt_v1.c".to_string(),
+            key_id: "kid_a".to_string(),
+            credential_id: "cred_a".to_string(),
+            access_expires_in_sec: 600,
+            refresh_expires_in_sec: 2_592_000,
+        };
+        assert_eq!(
+            serde_json::to_value(&exchange).expect("serialize exchange"),
+            json!({
+                "authMode": "pairTicket",
+                "accessToken": "yat_v1.a.b",
+                "refreshToken": "yrt_v1.c",
+                "keyId": "kid_a",
+                "credentialId": "cred_a",
+                "accessExpiresInSec": 600,
+                "refreshExpiresInSec": 2_592_000,
+            })
+        );
+
+        let refresh = AuthRefreshData {
+            access_token: "yat_v1.a.b".to_string(),
+            refresh_token: "yrt_v1.c".to_string(),
+            key_id: "kid_a".to_string(),
+            credential_id: "cred_a".to_string(),
+            access_expires_in_sec: 600,
+            refresh_expires_in_sec: 60,
+        };
+        let value = serde_json::to_value(&refresh).expect("serialize refresh");
+        assert_eq!(value["accessExpiresInSec"], 600);
+        assert_eq!(value["credentialId"], "cred_a");
+        assert_eq!(value.as_object().map(|v| v.len()), Some(6));
+
+        let revoke = AuthRevokeDeviceData {
+            target_device_id: "ios_b".to_string(),
+        };
+        assert_eq!(
+            serde_json::to_value(&revoke).expect("serialize revoke"),
+            json!({ "targetDeviceId": "ios_b" })
+        );
+    }
+
+    #[test]
+    fn device_list_omits_absent_optional_fields() {
+        let mut entry = DeviceEntry {
+            device_id: "ios_a".to_string(),
+            device_name: "iPhone".to_string(),
+            key_id: "kid_a".to_string(),
+            status: "ACTIVE".to_string(),
+            created_at: "2026-01-01T00:00:00Z".to_string(),
+            last_seen_at: "2026-01-02T00:00:00Z".to_string(),
+            revoked_at: None,
+            last_auth_failure: None,
+        };
+        let page = AuthDevicesData {
+            devices: vec![entry.clone()],
+            next_cursor: None,
+        };
+        assert_eq!(
+            serde_json::to_value(&page).expect("serialize devices"),
+            json!({
+                "devices": [{
+                    "deviceId": "ios_a",
+                    "deviceName": "iPhone",
+                    "keyId": "kid_a",
+                    "status": "ACTIVE",
+                    "createdAt": "2026-01-01T00:00:00Z",
+                    "lastSeenAt": "2026-01-02T00:00:00Z",
+                    "revokedAt": null,
+                }],
+            })
+        );
+
+        entry.last_auth_failure = Some(AuthFailureRecord {
+            code: "ACCESS_TOKEN_EXPIRED".to_string(),
+            at: "2026-01-03T00:00:00Z".to_string(),
+        });
+        let value = serde_json::to_value(&entry).expect("serialize entry");
+        assert_eq!(value["lastAuthFailure"]["code"], "ACCESS_TOKEN_EXPIRED");
+        let parsed: DeviceEntry = serde_json::from_value(value).expect("entry roundtrip");
+        assert_eq!(parsed, entry);
+    }
+
+    #[test]
+    fn requests_accept_legacy_client_payloads() {
+        let exchange: PairExchangeRequest = serde_json::from_value(json!({
+            "systemId": "sys_a",
+            "deviceId": "ios_a",
+            "pairTicket": "ticket",
+            "devicePubKey": "pub",
+            "keyId": "kid_a",
+            "proof": "sig",
+        }))
+        .expect("deviceName and pairToken are optional");
+        assert_eq!(exchange.device_name, "");
+        assert_eq!(exchange.pair_token, None);
+        let value = serde_json::to_value(&exchange).expect("serialize exchange request");
+        assert!(value.get("pairToken").is_none());
+
+        let revoke: AuthRevokeDeviceRequest = serde_json::from_value(json!({
+            "systemId": "sys_a",
+            "deviceId": "ios_a",
+            "targetDeviceId": "ios_b",
+            "accessToken": "yat_v1.a.b",
+            "keyId": "kid_a",
+            "ts": "1",
+            "nonce": "n",
+            "sig": "s",
+        }))
+        .expect("revoke request");
+        assert_eq!(revoke.target_device_id, "ios_b");
+    }
+}
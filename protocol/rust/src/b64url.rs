@@ -0,0 +1,88 @@
+// 文件职责：
+// 1) 统一 base64url（无填充）解码，供密钥、签名、token 段等字段复用。
+// 2) 区分“编码无效”与“长度不符”，调用方按需映射为各自的错误码与提示。
+
+use std::fmt;
+
+use base64::{Engine as _, engine::general_purpose::URL_SAFE_NO_PAD};
+
+/// base64url 解码错误。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum B64DecodeError {
+    /// 不是合法的 base64url（无填充）文本。
+    InvalidEncoding,
+    /// 解码成功但字节长度与预期不符。
+    InvalidLength { expected: usize, actual: usize },
+}
+
+impl fmt::Display for B64DecodeError {
+    /// 输出便于排障的错误说明。
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidEncoding => write!(f, "base64url 编码无效"),
+            Self::InvalidLength { expected, actual } => {
+                write!(f, "解码长度无效：期望 {expected} 字节，实际 {actual} 字节")
+            }
+        }
+    }
+}
+
+impl std::error::Error for B64DecodeError {}
+
+/// 解码 base64url（无填充）文本。
+pub fn decode_b64url(value: &str) -> Result<Vec<u8>, B64DecodeError> {
+    URL_SAFE_NO_PAD
+        .decode(value.as_bytes())
+        .map_err(|_| B64DecodeError::InvalidEncoding)
+}
+
+/// 解码 base64url（无填充）文本并要求恰好 `N` 字节（如 32 字节公钥、64 字节签名）。
+pub fn decode_b64url_exact<const N: usize>(value: &str) -> Result<[u8; N], B64DecodeError> {
+    let raw = decode_b64url(value)?;
+    let actual = raw.len();
+    raw.try_into().map_err(|_| B64DecodeError::InvalidLength {
+        expected: N,
+        actual,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use base64::{Engine as _, engine::general_purpose::URL_SAFE_NO_PAD};
+
+    use super::{B64DecodeError, decode_b64url, decode_b64url_exact};
+
+    #[test]
+    fn decodes_exact_length_values() {
+        let key = [7_u8; 32];
+        let encoded = URL_SAFE_NO_PAD.encode(key);
+        assert_eq!(decode_b64url_exact::<32>(&encoded), Ok(key));
+        assert_eq!(decode_b64url(&encoded), Ok(key.to_vec()));
+        assert_eq!(decode_b64url(""), Ok(Vec::new()));
+    }
+
+    #[test]
+    fn distinguishes_bad_alphabet_from_wrong_length() {
+        // 标准 base64 字符与填充均不属于 base64url（无填充）字母表。
+        assert_eq!(decode_b64url("ab+/"), Err(B64DecodeError::InvalidEncoding));
+        assert_eq!(decode_b64url("YQ=="), Err(B64DecodeError::InvalidEncoding));
+        assert_eq!(
+            decode_b64url_exact::<32>("***"),
+            Err(B64DecodeError::InvalidEncoding)
+        );
+
+        let short = URL_SAFE_NO_PAD.encode([1_u8; 31]);
+        assert_eq!(
+            decode_b64url_exact::<32>(&short),
+            Err(B64DecodeError::InvalidLength {
+                expected: 32,
+                actual: 31,
+            })
+        );
+        let long = URL_SAFE_NO_PAD.encode([1_u8; 65]);
+        assert!(matches!(
+            decode_b64url_exact::<64>(&long),
+            Err(B64DecodeError::InvalidLength { actual: 65, .. })
+        ));
+    }
+}
@@ -0,0 +1,231 @@
+// 文件职责：
+// 1) relay/sidecar 共用的用户目录解析：按 HOME →（Windows）USERPROFILE → YC_HOME_DIR →
+//    系统账户目录（getpwuid）→ XDG 基础目录的顺序解析。
+// 2) 全部失败时返回明确错误，不再静默回落到当前工作目录。
+// 3) Windows 额外解析 `%APPDATA%`，供主目录不可用时定位工具状态目录。
+
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+};
+
+/// 显式指定用户目录的环境变量（HOME 缺失时的兜底，适用于服务化部署）。
+pub const HOME_DIR_ENV: &str = "YC_HOME_DIR";
+
+/// 已解析的用户基础目录。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BaseDirs {
+    /// 用户主目录。
+    home: Option<PathBuf>,
+    /// 配置根目录（默认 `~/.config`，无主目录时取 XDG_CONFIG_HOME）。
+    config_home: Option<PathBuf>,
+    /// 数据根目录（默认 `~/.local/share`，无主目录时取 XDG_DATA_HOME）。
+    data_home: Option<PathBuf>,
+    /// Windows 漫游应用数据目录（`%APPDATA%`，其他平台恒为 None）。
+    app_data: Option<PathBuf>,
+}
+
+/// 目录解析所按的平台约定。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DirsPlatform {
+    /// Linux/macOS 等类 Unix 平台。
+    Unix,
+    /// Windows：HOME 通常未设置，回退 USERPROFILE 与 APPDATA。
+    Windows,
+}
+
+impl DirsPlatform {
+    /// 当前编译目标平台。
+    #[cfg(windows)]
+    pub const CURRENT: Self = Self::Windows;
+    /// 当前编译目标平台。
+    #[cfg(not(windows))]
+    pub const CURRENT: Self = Self::Unix;
+}
+
+impl BaseDirs {
+    /// 从当前进程环境解析基础目录。
+    pub fn from_env() -> Self {
+        Self::resolve(
+            |key| std::env::var(key).ok(),
+            std::env::home_dir,
+            DirsPlatform::CURRENT,
+        )
+    }
+
+    /// 按给定环境读取函数、账户目录查询与平台约定解析基础目录。
+    pub fn resolve<F, A>(lookup: F, account_home: A, platform: DirsPlatform) -> Self
+    where
+        F: Fn(&str) -> Option<String>,
+        A: FnOnce() -> Option<PathBuf>,
+    {
+        let non_empty = |key: &str| {
+            lookup(key)
+                .map(|value| value.trim().to_string())
+                .filter(|value| !value.is_empty())
+                .map(PathBuf::from)
+        };
+        // XDG 规范要求绝对路径，相对路径视为未设置。
+        let xdg_dir = |key: &str| non_empty(key).filter(|path| path.is_absolute());
+
+        let is_windows = platform == DirsPlatform::Windows;
+
+        let home = non_empty("HOME")
+            .or_else(|| is_windows.then(|| non_empty("USERPROFILE")).flatten())
+            .or_else(|| non_empty(HOME_DIR_ENV))
+            .or_else(|| account_home().filter(|path| !path.as_os_str().is_empty()));
+        let config_home = home
+            .as_ref()
+            .map(|home| home.join(".config"))
+            .or_else(|| xdg_dir("XDG_CONFIG_HOME"));
+        let data_home = home
+            .as_ref()
+            .map(|home| home.join(".local").join("share"))
+            .or_else(|| xdg_dir("XDG_DATA_HOME"));
+        let app_data = is_windows
+            .then(|| non_empty("APPDATA").filter(|path| path.is_absolute()))
+            .flatten();
+        Self {
+            home,
+            config_home,
+            data_home,
+            app_data,
+        }
+    }
+
+    /// 用户主目录。
+    pub fn home_dir(&self) -> Result<&Path, BaseDirsError> {
+        self.home.as_deref().ok_or(BaseDirsError {
+            what: "用户主目录"
+        })
+    }
+
+    /// 配置根目录。
+    pub fn config_dir(&self) -> Result<&Path, BaseDirsError> {
+        self.config_home.as_deref().ok_or(BaseDirsError {
+            what: "配置目录"
+        })
+    }
+
+    /// 数据根目录。
+    pub fn data_dir(&self) -> Result<&Path, BaseDirsError> {
+        self.data_home.as_deref().ok_or(BaseDirsError {
+            what: "数据目录"
+        })
+    }
+
+    /// Windows 漫游应用数据目录（`%APPDATA%`）；非 Windows 或未设置时为 None。
+    pub fn app_data_dir(&self) -> Option<&Path> {
+        self.app_data.as_deref()
+    }
+}
+
+/// 目录无法解析错误，`Display` 给出需设置的环境变量。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BaseDirsError {
+    /// 未能解析的目录名称。
+    what: &'static str,
+}
+
+impl fmt::Display for BaseDirsError {
+    /// 输出便于排障的错误说明。
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "无法解析{}：HOME 未设置且系统账户目录不可用，请设置 HOME 或 {HOME_DIR_ENV}",
+            self.what
+        )
+    }
+}
+
+impl std::error::Error for BaseDirsError {}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, path::PathBuf};
+
+    use super::{BaseDirs, DirsPlatform};
+
+    fn resolve(vars: &[(&str, &str)], account_home: Option<&str>) -> BaseDirs {
+        resolve_on(DirsPlatform::Unix, vars, account_home)
+    }
+
+    fn resolve_on(
+        platform: DirsPlatform,
+        vars: &[(&str, &str)],
+        account_home: Option<&str>,
+    ) -> BaseDirs {
+        let vars = vars
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect::<HashMap<_, _>>();
+        BaseDirs::resolve(
+            |key| vars.get(key).cloned(),
+            || account_home.map(PathBuf::from),
+            platform,
+        )
+    }
+
+    #[test]
+    fn home_env_takes_priority() {
+        let dirs = resolve(
+            &[("HOME", "/home/a"), ("XDG_CONFIG_HOME", "/xdg/config")],
+            Some("/home/passwd"),
+        );
+        assert_eq!(dirs.home_dir().unwrap(), PathBuf::from("/home/a"));
+        assert_eq!(dirs.config_dir().unwrap(), PathBuf::from("/home/a/.config"));
+        assert_eq!(
+            dirs.data_dir().unwrap(),
+            PathBuf::from("/home/a/.local/share")
+        );
+    }
+
+    #[test]
+    fn unset_home_falls_back_to_explicit_env_then_account() {
+        let dirs = resolve(&[("YC_HOME_DIR", "/srv/yc")], Some("/home/passwd"));
+        assert_eq!(dirs.home_dir().unwrap(), PathBuf::from("/srv/yc"));
+
+        let dirs = resolve(&[], Some("/home/passwd"));
+        assert_eq!(dirs.home_dir().unwrap(), PathBuf::from("/home/passwd"));
+    }
+
+    #[test]
+    fn windows_falls_back_to_userprofile_and_appdata() {
+        let vars = [
+            ("USERPROFILE", "/users/a"),
+            ("APPDATA", "/users/a/appdata/roaming"),
+        ];
+        let dirs = resolve_on(DirsPlatform::Windows, &vars, None);
+        assert_eq!(dirs.home_dir().unwrap(), PathBuf::from("/users/a"));
+        assert_eq!(
+            dirs.app_data_dir(),
+            Some(PathBuf::from("/users/a/appdata/roaming").as_path())
+        );
+
+        let dirs = resolve_on(DirsPlatform::Unix, &vars, None);
+        assert!(dirs.home_dir().is_err());
+        assert_eq!(dirs.app_data_dir(), None);
+    }
+
+    #[test]
+    fn unset_home_uses_xdg_base_dirs() {
+        let dirs = resolve(
+            &[
+                ("XDG_CONFIG_HOME", "/xdg/config"),
+                ("XDG_DATA_HOME", "/xdg/data"),
+            ],
+            None,
+        );
+        assert!(dirs.home_dir().is_err());
+        assert_eq!(dirs.config_dir().unwrap(), PathBuf::from("/xdg/config"));
+        assert_eq!(dirs.data_dir().unwrap(), PathBuf::from("/xdg/data"));
+    }
+
+    #[test]
+    fn nothing_resolvable_returns_error_instead_of_cwd() {
+        let dirs = resolve(&[("HOME", " "), ("XDG_CONFIG_HOME", "relative")], None);
+        let err = dirs.config_dir().expect_err("should not fall back to cwd");
+        assert!(err.to_string().contains("YC_HOME_DIR"));
+        assert!(dirs.data_dir().is_err());
+    }
+}
@@ -0,0 +1,113 @@
+// 文件职责：
+// 1) 将 relay API 错误码归类为客户端可直接处理的类别（重试/刷新/重新配对/限流/终止）。
+// 2) 仅提供分类，不替换原始错误码；调用方仍应保留并展示 `code`。
+
+use serde::{Deserialize, Serialize};
+
+/// 错误码的客户端处理类别。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ErrorCategory {
+    /// 暂时性错误，稍后原样重试即可。
+    Retryable,
+    /// 访问凭证失效，需用 refresh token 换发后重试。
+    NeedsRefresh,
+    /// 设备凭证或配对关系失效，需重新配对。
+    NeedsRepair,
+    /// 请求被限流，应退避后再试。
+    RateLimited,
+    /// 不可自动恢复（配置/权限/请求本身问题），直接提示用户。
+    Fatal,
+}
+
+impl ErrorCategory {
+    /// 返回类别的稳定字符串表示（与序列化结果一致）。
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Retryable => "retryable",
+            Self::NeedsRefresh => "needsRefresh",
+            Self::NeedsRepair => "needsRepair",
+            Self::RateLimited => "rateLimited",
+            Self::Fatal => "fatal",
+        }
+    }
+}
+
+/// 按错误码返回处理类别；未知错误码归为 `Fatal`，避免客户端对未知错误盲目重试。
+pub fn category_of(code: &str) -> ErrorCategory {
+    match code {
+        "INTERNAL_ERROR"
+        | "SYSTEM_NOT_REGISTERED"
+        | "ACCESS_SIGNATURE_EXPIRED"
+        | "ACCESS_SIGNATURE_REPLAYED" => ErrorCategory::Retryable,
+        "ACCESS_TOKEN_EXPIRED" | "ACCESS_TOKEN_INVALID" | "ACCESS_TOKEN_MISMATCH" => {
+            ErrorCategory::NeedsRefresh
+        }
+        "REFRESH_TOKEN_INVALID"
+        | "REFRESH_TOKEN_EXPIRED"
+        | "REFRESH_TOKEN_REUSED"
+        | "DEVICE_REVOKED"
+        | "DEVICE_NOT_FOUND"
+        | "KEY_ALREADY_BOUND"
+        | "PAIR_PROOF_INVALID"
+        | "PAIR_TICKET_INVALID"
+        | "PAIR_TICKET_EXPIRED"
+        | "PAIR_TICKET_REPLAYED"
+        | "PAIR_TOKEN_MISMATCH"
+        | "PAIR_TOKEN_NOT_SUPPORTED" => ErrorCategory::NeedsRepair,
+        "RATE_LIMITED" | "PAIR_RATE_LIMITED" => ErrorCategory::RateLimited,
+        _ => ErrorCategory::Fatal,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ErrorCategory, category_of};
+
+    #[test]
+    fn known_codes_map_to_expected_categories() {
+        let cases = [
+            ("INTERNAL_ERROR", ErrorCategory::Retryable),
+            ("SYSTEM_NOT_REGISTERED", ErrorCategory::Retryable),
+            ("ACCESS_SIGNATURE_EXPIRED", ErrorCategory::Retryable),
+            ("ACCESS_SIGNATURE_REPLAYED", ErrorCategory::Retryable),
+            ("ACCESS_TOKEN_EXPIRED", ErrorCategory::NeedsRefresh),
+            ("ACCESS_TOKEN_INVALID", ErrorCategory::NeedsRefresh),
+            ("ACCESS_TOKEN_MISMATCH", ErrorCategory::NeedsRefresh),
+            ("REFRESH_TOKEN_INVALID", ErrorCategory::NeedsRepair),
+            ("REFRESH_TOKEN_EXPIRED", ErrorCategory::NeedsRepair),
+            ("REFRESH_TOKEN_REUSED", ErrorCategory::NeedsRepair),
+            ("DEVICE_REVOKED", ErrorCategory::NeedsRepair),
+            ("DEVICE_NOT_FOUND", ErrorCategory::NeedsRepair),
+            ("KEY_ALREADY_BOUND", ErrorCategory::NeedsRepair),
+            ("PAIR_PROOF_INVALID", ErrorCategory::NeedsRepair),
+            ("PAIR_TICKET_INVALID", ErrorCategory::NeedsRepair),
+            ("PAIR_TICKET_EXPIRED", ErrorCategory::NeedsRepair),
+            ("PAIR_TICKET_REPLAYED", ErrorCategory::NeedsRepair),
+            ("PAIR_TOKEN_MISMATCH", ErrorCategory::NeedsRepair),
+            ("PAIR_TOKEN_NOT_SUPPORTED", ErrorCategory::NeedsRepair),
+            ("RATE_LIMITED", ErrorCategory::RateLimited),
+            ("PAIR_RATE_LIMITED", ErrorCategory::RateLimited),
+            ("MISSING_CREDENTIALS", ErrorCategory::Fatal),
+            ("TICKET_PAIRING_DISABLED", ErrorCategory::Fatal),
+            ("ADMIN_DISABLED", ErrorCategory::Fatal),
+            ("ADMIN_TOKEN_INVALID", ErrorCategory::Fatal),
+            ("DEBUG_ENDPOINTS_DISABLED", ErrorCategory::Fatal),
+            ("EXPORT_VERSION_UNSUPPORTED", ErrorCategory::Fatal),
+        ];
+        for (code, expected) in cases {
+            assert_eq!(category_of(code), expected, "code={code}");
+        }
+    }
+
+    #[test]
+    fn unknown_code_defaults_to_fatal() {
+        assert_eq!(category_of("SOMETHING_NEW"), ErrorCategory::Fatal);
+        assert_eq!(category_of(""), ErrorCategory::Fatal);
+        assert_eq!(
+            serde_json::to_string(&ErrorCategory::NeedsRefresh).unwrap(),
+            "\"needsRefresh\""
+        );
+        assert_eq!(ErrorCategory::RateLimited.as_str(), "rateLimited");
+    }
+}
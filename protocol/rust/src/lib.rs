@@ -1,13 +1,50 @@
 // 文件职责：
 // 1) 定义 relay/sidecar/mobile 共用的协议数据结构。
 // 2) 提供时间戳、clientType 归一化等跨端一致的基础函数。
-// 3) 作为 Rust 侧协议唯一代码源，供其他服务复用。
+// 3) 作为 Rust 侧协议唯一代码源，供其他服务复用（含 relay/sidecar 共用的日志格式与用户目录解析）。
 
 use chrono::Utc;
 use serde::{Deserialize, Serialize};
-use serde_json::Value;
+use serde_json::{Map, Value};
 use uuid::Uuid;
 
+mod auth_api;
+mod b64url;
+mod base_dirs;
+mod error_category;
+mod log_format;
+mod pairing_link;
+mod snapshot_hash;
+mod token_claims;
+mod wire_compression;
+
+pub use auth_api::{
+    AuthDevicesData, AuthDevicesQuery, AuthFailureRecord, AuthRefreshData, AuthRefreshRequest,
+    AuthRenameDeviceRequest, AuthRevokeAllData, AuthRevokeAllRequest, AuthRevokeDeviceData,
+    AuthRevokeDeviceRequest, DeviceEntry, PairAuthMode, PairExchangeData, PairExchangeRequest,
+    PairRevokeTokenData, PairRevokeTokenRequest,
+};
+pub use b64url::{B64DecodeError, decode_b64url, decode_b64url_exact};
+pub use base_dirs::{BaseDirs, BaseDirsError, DirsPlatform, HOME_DIR_ENV};
+pub use error_category::{ErrorCategory, category_of};
+pub use log_format::{LOG_FORMAT_ENV, LogFormat, json_log_layer};
+pub use pairing_link::{PAIRING_LINK_HOST, PAIRING_LINK_SCHEME, PairingLink, PairingLinkError};
+pub use snapshot_hash::{StableSnapshotHash, stable_snapshot_hash};
+pub use token_claims::{
+    ACCESS_TOKEN_PREFIX, PublicAccessClaims, TokenClaimsError, decode_access_token_claims,
+};
+pub use wire_compression::{
+    COMPRESSED_EVENT_TYPE, COMPRESSED_INNER_TYPE_FIELD, COMPRESSION_ENCODING_GZIP,
+    MAX_INFLATED_BYTES, WireCompressionError, encode_envelope_text, inflate_envelope_text,
+};
+
+/// 当前协议版本号（对应 envelope 的 `v` 字段）。
+pub const PROTOCOL_VERSION: u8 = 1;
+/// relay 仍接受的最低协议版本号；握手协商区间为 `MIN_PROTOCOL_VERSION..=PROTOCOL_VERSION`。
+pub const MIN_PROTOCOL_VERSION: u8 = 1;
+/// ACK 事件类型：接收方确认收到 `ackRequired=true` 的事件。
+pub const ACK_EVENT_TYPE: &str = "ack";
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EventEnvelope {
     // 协议版本号。
@@ -59,7 +96,7 @@ impl EventEnvelope {
         payload: Value,
     ) -> Self {
         Self {
-            v: 1,
+            v: PROTOCOL_VERSION,
             event_id: format!("evt_{}", Uuid::new_v4()),
             trace_id: Some(format!("trc_{}", Uuid::new_v4())),
             event_type: event_type.into(),
@@ -77,6 +114,16 @@ impl EventEnvelope {
     }
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct AckPayload {
+    // 被确认事件的 eventId。
+    pub event_id: String,
+    #[serde(rename = "type", default)]
+    // 被确认事件的类型（便于排查）。
+    pub event_type: String,
+}
+
 /// 生成纳秒精度 UTC 时间戳（RFC3339）。
 pub fn now_rfc3339_nanos() -> String {
     Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Nanos, true)
@@ -129,8 +176,11 @@ pub struct ModelUsagePayload {
 pub struct ToolRuntimePayload {
     // 工具唯一 ID。
     pub tool_id: String,
-    // 工具显示名称。
+    // 工具显示名称（适配器识别结果，参与工具匹配）。
     pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    // 用户自定义显示名（可选，仅用于展示，不参与工具匹配）。
+    pub display_name: Option<String>,
     // 工具业务类别（assistant|code）。
     #[serde(default)]
     pub tool_class: String,
@@ -194,6 +244,10 @@ pub struct ToolRuntimePayload {
     #[serde(skip_serializing_if = "Option::is_none")]
     // 采集时间（可选）。
     pub collected_at: Option<String>,
+    #[serde(flatten, default)]
+    // 未识别字段兜底（尽力而为）：新版本适配器增加的字段在旧版本解码时保留于此，
+    // 重新编码时原样展开回顶层；已知字段不会进入该表。
+    pub extra: Map<String, Value>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -226,6 +280,36 @@ pub struct SystemMetricsPayload {
     pub disk_used_percent: f64,
     // sidecar 启动后运行秒数。
     pub uptime_sec: u64,
+    #[serde(default)]
+    // 非回环网卡接收速率（字节/秒，相邻两次采样的平均值）。
+    pub net_rx_bytes_per_sec: f64,
+    #[serde(default)]
+    // 非回环网卡发送速率（字节/秒）。
+    pub net_tx_bytes_per_sec: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    // CPU 百分比的指数移动平均（sidecar 开启 `METRICS_SMOOTHING_ALPHA` 时才有值）。
+    pub cpu_percent_avg: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    // 内存使用率的指数移动平均（同上）。
+    pub memory_used_percent_avg: Option<f64>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    // 各 GPU 指标（sidecar 启用 `gpu-nvml` 且加载到 NVML 时才有数据，否则为空）。
+    pub gpu: Vec<GpuMetricsPayload>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct GpuMetricsPayload {
+    // 设备序号（NVML index）。
+    pub index: u32,
+    // 设备名称。
+    pub name: String,
+    // GPU 利用率百分比（最近一个采样周期）。
+    pub utilization_percent: f64,
+    // 已用显存（MB）。
+    pub memory_used_mb: f64,
+    // 总显存（MB）。
+    pub memory_total_mb: f64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -233,6 +317,35 @@ pub struct SystemMetricsPayload {
 pub struct ToolsSnapshotPayload {
     // 当前工具列表（connected 或 candidates）。
     pub tools: Vec<ToolRuntimePayload>,
+    // 是否因数量上限被截断。
+    #[serde(default)]
+    pub truncated: bool,
+    // 截断前的工具总数。
+    #[serde(default)]
+    pub total_count: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct AdapterErrorSummaryPayload {
+    // 适配器名称（openclaw/opencode/codex/claude_code/unknown）。
+    pub adapter: String,
+    // 统计窗口内的失败次数。
+    pub count: u32,
+    // 最近一次失败原因。
+    pub last_error: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    // 最近一次失败时间。
+    pub last_error_at: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct CollectionErrorsPayload {
+    // 统计窗口长度（秒）。
+    pub window_sec: u64,
+    // 按适配器聚合的失败摘要（为空表示窗口内已恢复正常）。
+    pub adapters: Vec<AdapterErrorSummaryPayload>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -246,6 +359,121 @@ pub struct MetricsSnapshotPayload {
     pub tool: Value,
     // 所有工具指标。
     pub tools: Vec<ToolRuntimePayload>,
+    #[serde(default)]
+    // 按挂载点拆分的磁盘用量（已排除 tmpfs/overlay 等伪文件系统）。
+    pub disks: Vec<DiskUsagePayload>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    // 本次指标采集时间（RFC3339）。
+    pub collected_at: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct MetricsHistoryPayload {
+    // 缓冲容量上限。
+    pub capacity: usize,
+    // 历史样本（旧 -> 新）。
+    pub samples: Vec<MetricsSnapshotPayload>,
+    // 拒绝原因（仅请求被拒绝时出现，此时 samples 为空）。
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum SidecarDiagnosticsTrigger {
+    #[default]
+    Periodic,
+    Request,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct QueueDiagnosticsPayload {
+    // 所属链路（details=详情刷新队列，outbound=relay 发送队列）。
+    pub pipeline: String,
+    // 队列键（如 tool_details / metrics / control）。
+    pub key: String,
+    // 当前待处理深度。
+    pub depth: usize,
+    // 会话内累计被覆盖或丢弃的次数。
+    pub dropped_total: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct DetailsWorkerDiagnosticsPayload {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    // 最近一次详情采集耗时（毫秒）。
+    pub last_collect_ms: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    // 最近一次详情请求排队等待耗时（毫秒）。
+    pub last_queue_wait_ms: Option<u64>,
+    // 最近一次详情快照合并掉的刷新次数。
+    pub last_dropped_refreshes: u32,
+    // 会话内已下发的详情快照数。
+    pub snapshots_sent: u64,
+    // 会话内 worker 重启次数。
+    pub restarts: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct SidecarDiagnosticsPayload {
+    // 触发来源（周期上报或按需请求）。
+    pub trigger: SidecarDiagnosticsTrigger,
+    // 当前会话已持续秒数。
+    pub session_uptime_sec: u64,
+    // 各链路各队列键的深度与累计丢弃。
+    pub queues: Vec<QueueDiagnosticsPayload>,
+    // 详情 worker 最近一轮耗时。
+    pub details_worker: DetailsWorkerDiagnosticsPayload,
+    // 拒绝原因（仅按需请求被拒绝时出现，此时其余字段为空）。
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolLogsPayload {
+    // 工具 ID。
+    pub tool_id: String,
+    // 是否读取成功。
+    pub ok: bool,
+    // 日志文件名（不含目录）。
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub log_file: Option<String>,
+    // 末尾日志行（旧 -> 新）。
+    #[serde(default)]
+    pub lines: Vec<String>,
+    // 失败原因。
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ControllerBindRequestPayload {
+    // 等待审批的来源设备 ID。
+    pub device_id: String,
+    // 触发审批的命令事件名。
+    pub command_event: String,
+    // 当前待审批设备 ID 列表（含本设备）。
+    #[serde(default)]
+    pub pending_device_ids: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct DiskUsagePayload {
+    // 挂载点（Windows 为盘符）。
+    pub mount_point: String,
+    // 总容量（GB）。
+    pub total_gb: f64,
+    // 已用容量（GB）。
+    pub used_gb: f64,
+    // 已用百分比。
+    pub used_percent: f64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -335,3 +563,174 @@ pub struct ToolDetailsRefreshRequestPayload {
     // 刷新优先级。
     pub priority: ToolDetailsRefreshPriority,
 }
+
+/// 命令回执错误码：工具不在候选列表。
+pub const COMMAND_ERROR_TOOL_NOT_FOUND: &str = "TOOL_NOT_FOUND";
+/// 命令回执错误码：fallback 占位工具不可操作。
+pub const COMMAND_ERROR_FALLBACK_TOOL: &str = "FALLBACK_TOOL";
+/// 命令回执错误码：本地存储（白名单/控制端）写入失败。
+pub const COMMAND_ERROR_STORE_FAILED: &str = "STORE_FAILED";
+/// 命令回执错误码：来源设备未被授权为控制端。
+pub const COMMAND_ERROR_UNAUTHORIZED: &str = "CONTROLLER_UNAUTHORIZED";
+/// 命令回执错误码：来源设备等待已绑定控制端审批（`manual_approve` 策略）。
+pub const COMMAND_ERROR_PENDING_APPROVAL: &str = "CONTROLLER_PENDING_APPROVAL";
+/// 命令回执错误码：命令参数不合法。
+pub const COMMAND_ERROR_INVALID_REQUEST: &str = "INVALID_REQUEST";
+/// 命令回执错误码：目标进程信号发送失败或停止超时。
+pub const COMMAND_ERROR_PROCESS_FAILED: &str = "PROCESS_FAILED";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct CommandFeedbackPayload {
+    // 命令动作（connect/disconnect/reset/rebind-controller 等）。
+    pub action: String,
+    #[serde(rename = "toolId", default)]
+    // 命令目标工具 ID；无工具目标时为空串（沿用历史字段名 toolId）。
+    pub target: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    // 命令目标设备 ID（控制端重绑时使用）。
+    pub device_id: Option<String>,
+    // 命令是否执行成功。
+    pub ok: bool,
+    // 是否发生状态变更（成功但无变化时为 false）。
+    pub changed: bool,
+    #[serde(default)]
+    // 失败原因（面向用户展示）；成功时为空串。
+    pub reason: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    // 稳定错误码（仅失败时携带），供客户端分支处理。
+    pub error_code: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    // 批量操作影响条数（如清空白名单）。
+    pub removed_count: Option<usize>,
+}
+
+impl CommandFeedbackPayload {
+    /// 以动作与目标构造回执，默认视为失败且无变更，需再调用 succeeded/failed 补全结果。
+    pub fn new(action: impl Into<String>, target: impl Into<String>) -> Self {
+        Self {
+            action: action.into(),
+            target: target.into(),
+            ..Self::default()
+        }
+    }
+
+    /// 标记命令成功，并记录是否发生状态变更。
+    pub fn succeeded(mut self, changed: bool) -> Self {
+        self.ok = true;
+        self.changed = changed;
+        self.reason.clear();
+        self.error_code = None;
+        self
+    }
+
+    /// 标记命令失败，附带稳定错误码与原因。
+    pub fn failed(mut self, error_code: &str, reason: impl Into<String>) -> Self {
+        self.ok = false;
+        self.changed = false;
+        self.reason = reason.into();
+        self.error_code = Some(error_code.to_string());
+        self
+    }
+
+    /// 附带目标设备 ID。
+    pub fn with_device_id(mut self, device_id: impl Into<String>) -> Self {
+        self.device_id = Some(device_id.into());
+        self
+    }
+
+    /// 附带批量影响条数。
+    pub fn with_removed_count(mut self, removed_count: usize) -> Self {
+        self.removed_count = Some(removed_count);
+        self
+    }
+
+    /// 序列化为事件 payload。
+    pub fn to_value(&self) -> Value {
+        serde_json::to_value(self).unwrap_or(Value::Null)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{COMMAND_ERROR_STORE_FAILED, CommandFeedbackPayload, ToolRuntimePayload};
+
+    #[test]
+    fn command_feedback_serializes_legacy_field_names() {
+        let payload = CommandFeedbackPayload::new("connect", "openclaw_a").succeeded(true);
+        assert_eq!(
+            payload.to_value(),
+            json!({
+                "action": "connect",
+                "toolId": "openclaw_a",
+                "ok": true,
+                "changed": true,
+                "reason": "",
+            })
+        );
+    }
+
+    #[test]
+    fn command_feedback_represents_unchanged_success() {
+        let payload = CommandFeedbackPayload::new("disconnect", "openclaw_a").succeeded(false);
+        assert!(payload.ok);
+        assert!(!payload.changed);
+        assert!(payload.error_code.is_none());
+    }
+
+    #[test]
+    fn command_feedback_represents_failure_with_code() {
+        let payload = CommandFeedbackPayload::new("reset", "")
+            .with_removed_count(0)
+            .failed(COMMAND_ERROR_STORE_FAILED, "清空白名单失败");
+        let value = payload.to_value();
+        assert_eq!(value["ok"], false);
+        assert_eq!(value["changed"], false);
+        assert_eq!(value["errorCode"], "STORE_FAILED");
+        assert_eq!(value["reason"], "清空白名单失败");
+        assert_eq!(value["removedCount"], 0);
+
+        let parsed: CommandFeedbackPayload =
+            serde_json::from_value(value).expect("payload should roundtrip");
+        assert_eq!(parsed, payload);
+    }
+
+    #[test]
+    fn command_feedback_carries_device_id_for_rebind() {
+        let value = CommandFeedbackPayload::new("rebind-controller", "")
+            .with_device_id("ios_a")
+            .succeeded(true)
+            .to_value();
+        assert_eq!(value["deviceId"], "ios_a");
+        assert_eq!(value["toolId"], "");
+    }
+
+    #[test]
+    fn tool_runtime_unknown_fields_land_in_extra_and_roundtrip() {
+        let raw = json!({
+            "toolId": "codex_a",
+            "name": "Codex",
+            "category": "CODE_AGENT",
+            "vendor": "OpenAI",
+            "mode": "CLI",
+            "status": "RUNNING",
+            "connected": true,
+            "endpoint": "",
+            "gitBranch": "main",
+            "instanceCount": 2,
+        });
+        let tool: ToolRuntimePayload =
+            serde_json::from_value(raw.clone()).expect("payload should decode");
+        assert_eq!(tool.name, "Codex");
+        assert_eq!(tool.extra.len(), 2);
+        assert_eq!(tool.extra["gitBranch"], "main");
+        assert!(!tool.extra.contains_key("toolId"));
+
+        let encoded = serde_json::to_value(&tool).expect("payload should encode");
+        assert_eq!(encoded["gitBranch"], "main");
+        assert_eq!(encoded["instanceCount"], 2);
+        assert!(encoded.get("extra").is_none());
+    }
+}
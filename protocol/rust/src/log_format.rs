@@ -0,0 +1,110 @@
+// 文件职责：
+// 1) 统一 relay/sidecar 的日志输出格式选择（`LOG_FORMAT=json` 时输出 JSON 行）。
+// 2) 提供两端共用的 JSON 行日志层，保证字段结构一致、便于统一采集。
+
+use tracing::Subscriber;
+use tracing_subscriber::{Layer, fmt::MakeWriter, registry::LookupSpan};
+
+/// 日志格式环境变量。
+pub const LOG_FORMAT_ENV: &str = "LOG_FORMAT";
+
+/// 日志输出格式。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogFormat {
+    /// 人类可读文本（默认）。
+    Text,
+    /// 每行一个 JSON 对象。
+    Json,
+}
+
+impl LogFormat {
+    /// 读取日志格式；未设置或无法识别时为文本。
+    pub fn from_env() -> Self {
+        Self::parse(std::env::var(LOG_FORMAT_ENV).ok().as_deref())
+    }
+
+    /// 解析日志格式取值。
+    pub fn parse(raw: Option<&str>) -> Self {
+        match raw
+            .map(|value| value.trim().to_ascii_lowercase())
+            .as_deref()
+        {
+            Some("json") => Self::Json,
+            _ => Self::Text,
+        }
+    }
+}
+
+/// 构造 JSON 行日志层：事件字段位于 `fields`，当前 span 与 span 链字段（如 `trace_id`）
+/// 分别位于 `span` 与 `spans`。
+pub fn json_log_layer<S, W>(writer: W) -> impl Layer<S> + Send + Sync + 'static
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    W: for<'w> MakeWriter<'w> + Send + Sync + 'static,
+{
+    tracing_subscriber::fmt::layer()
+        .json()
+        .with_current_span(true)
+        .with_span_list(true)
+        .with_writer(writer)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io,
+        sync::{Arc, Mutex},
+    };
+
+    use serde_json::Value;
+    use tracing::{info, info_span};
+    use tracing_subscriber::layer::SubscriberExt;
+
+    use super::{LogFormat, json_log_layer};
+
+    /// 测试用共享缓冲写入器。
+    #[derive(Clone, Default)]
+    struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+    impl io::Write for Buffer {
+        /// 追加到缓冲。
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().expect("buffer lock").extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        /// 无需刷新。
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn json_format_emits_structured_event_and_span_fields() {
+        assert_eq!(LogFormat::parse(None), LogFormat::Text);
+        assert_eq!(LogFormat::parse(Some("text")), LogFormat::Text);
+        assert_eq!(LogFormat::parse(Some(" JSON ")), LogFormat::Json);
+
+        let buffer = Buffer::default();
+        let writer = buffer.clone();
+        let subscriber =
+            tracing_subscriber::registry().with(json_log_layer(move || writer.clone()));
+        tracing::subscriber::with_default(subscriber, || {
+            let span = info_span!("ws", trace_id = "tr_1", device_id = tracing::field::Empty);
+            span.record("device_id", "ios_a");
+            let _entered = span.enter();
+            info!(system_id = "sys_a", count = 3, "auth ok");
+        });
+
+        let raw = String::from_utf8(buffer.0.lock().expect("buffer lock").clone()).expect("utf8");
+        let line: Value = serde_json::from_str(raw.trim()).expect("single json line");
+        assert_eq!(line["level"], "INFO");
+        assert_eq!(line["fields"]["message"], "auth ok");
+        assert_eq!(line["fields"]["system_id"], "sys_a");
+        assert_eq!(line["fields"]["count"], 3);
+        assert_eq!(line["span"]["name"], "ws");
+        assert_eq!(line["span"]["trace_id"], "tr_1");
+        assert_eq!(line["span"]["device_id"], "ios_a");
+        assert_eq!(line["spans"].as_array().map(Vec::len), Some(1));
+    }
+}
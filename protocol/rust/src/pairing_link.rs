@@ -0,0 +1,167 @@
+// 文件职责：
+// 1) 定义 `yc://pair?...` 配对链接的统一结构（relay/sid/ticket/name/code）。
+// 2) 提供构建与解析校验，relay 签发与各端导入共用同一套规则。
+
+use std::fmt;
+
+use url::Url;
+
+/// 配对链接 scheme。
+pub const PAIRING_LINK_SCHEME: &str = "yc";
+/// 配对链接 host。
+pub const PAIRING_LINK_HOST: &str = "pair";
+
+/// 配对链接内容。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PairingLink {
+    // Relay WS 地址。
+    pub relay: String,
+    // 宿主机 systemId。
+    pub sid: String,
+    // 短时配对票据。
+    pub ticket: String,
+    // 宿主机显示名（可选）。
+    pub name: Option<String>,
+    // 兼容配对码（可选）。
+    pub code: Option<String>,
+}
+
+/// 配对链接解析失败原因。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PairingLinkError {
+    /// 不是合法 URL。
+    InvalidUrl(String),
+    /// scheme/host 不是 `yc://pair`。
+    UnsupportedTarget(String),
+    /// 缺少必填参数。
+    MissingParam(&'static str),
+}
+
+impl fmt::Display for PairingLinkError {
+    /// 输出可直接展示的错误说明。
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidUrl(err) => write!(f, "配对链接格式无效：{err}"),
+            Self::UnsupportedTarget(target) => write!(f, "不支持的配对链接：{target}"),
+            Self::MissingParam(name) => write!(f, "配对链接缺少参数：{name}"),
+        }
+    }
+}
+
+impl std::error::Error for PairingLinkError {}
+
+impl PairingLink {
+    /// 以必填参数构建配对链接；`name` 为空白时忽略。
+    pub fn build(relay: &str, sid: &str, ticket: &str, name: Option<&str>) -> Self {
+        Self {
+            relay: relay.to_string(),
+            sid: sid.to_string(),
+            ticket: ticket.to_string(),
+            name: name
+                .map(str::trim)
+                .filter(|value| !value.is_empty())
+                .map(ToString::to_string),
+            code: None,
+        }
+    }
+
+    /// 附带兼容配对码。
+    pub fn with_code(mut self, code: impl Into<String>) -> Self {
+        self.code = Some(code.into());
+        self
+    }
+
+    /// 解析并校验配对链接（scheme/host 与必填参数）。
+    pub fn parse(raw: &str) -> Result<Self, PairingLinkError> {
+        let url =
+            Url::parse(raw.trim()).map_err(|err| PairingLinkError::InvalidUrl(err.to_string()))?;
+        if url.scheme() != PAIRING_LINK_SCHEME || url.host_str() != Some(PAIRING_LINK_HOST) {
+            return Err(PairingLinkError::UnsupportedTarget(format!(
+                "{}://{}",
+                url.scheme(),
+                url.host_str().unwrap_or_default()
+            )));
+        }
+
+        let param = |key: &str| {
+            url.query_pairs()
+                .find(|(name, _)| name == key)
+                .map(|(_, value)| value.trim().to_string())
+                .filter(|value| !value.is_empty())
+        };
+        Ok(Self {
+            relay: param("relay").ok_or(PairingLinkError::MissingParam("relay"))?,
+            sid: param("sid").ok_or(PairingLinkError::MissingParam("sid"))?,
+            ticket: param("ticket").ok_or(PairingLinkError::MissingParam("ticket"))?,
+            name: param("name"),
+            code: param("code"),
+        })
+    }
+
+    /// 输出 `yc://pair?...` 链接文本。
+    pub fn to_link(&self) -> String {
+        let mut link = Url::parse(&format!("{PAIRING_LINK_SCHEME}://{PAIRING_LINK_HOST}"))
+            .expect("pairing link base must be valid");
+        {
+            let mut pairs = link.query_pairs_mut();
+            pairs.append_pair("relay", &self.relay);
+            pairs.append_pair("sid", &self.sid);
+            pairs.append_pair("ticket", &self.ticket);
+            if let Some(name) = self.name.as_deref() {
+                pairs.append_pair("name", name);
+            }
+            if let Some(code) = self.code.as_deref() {
+                pairs.append_pair("code", code);
+            }
+        }
+        link.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PairingLink, PairingLinkError};
+
+    #[test]
+    fn build_then_parse_round_trips() {
+        let link = PairingLink::build(
+            "wss://relay.example.com/v1/ws",
+            "sys_demo",
+            "pct_v1.abc",
+            Some("My Mac"),
+        )
+        .with_code("sys_demo.ptk_demo");
+        let text = link.to_link();
+        assert!(text.starts_with("yc://pair?"));
+        assert!(text.contains("name=My+Mac"));
+        assert_eq!(PairingLink::parse(&text), Ok(link));
+
+        let unnamed = PairingLink::build("ws://127.0.0.1:18080/v1/ws", "sys_a", "t", Some("  "));
+        assert_eq!(unnamed.name, None);
+        assert_eq!(PairingLink::parse(&unnamed.to_link()), Ok(unnamed));
+    }
+
+    #[test]
+    fn parse_rejects_missing_params_and_foreign_targets() {
+        assert_eq!(
+            PairingLink::parse("yc://pair?relay=ws%3A%2F%2Fr&sid=sys_a"),
+            Err(PairingLinkError::MissingParam("ticket"))
+        );
+        assert_eq!(
+            PairingLink::parse("yc://pair?sid=sys_a&ticket=t"),
+            Err(PairingLinkError::MissingParam("relay"))
+        );
+        assert_eq!(
+            PairingLink::parse("yc://pair?relay=ws%3A%2F%2Fr&sid=+&ticket=t"),
+            Err(PairingLinkError::MissingParam("sid"))
+        );
+        assert!(matches!(
+            PairingLink::parse("https://pair?relay=r&sid=s&ticket=t"),
+            Err(PairingLinkError::UnsupportedTarget(_))
+        ));
+        assert!(matches!(
+            PairingLink::parse("not a link"),
+            Err(PairingLinkError::InvalidUrl(_))
+        ));
+    }
+}
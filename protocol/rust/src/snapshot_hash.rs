@@ -0,0 +1,155 @@
+// 文件职责：
+// 1) 为工具/指标快照计算稳定内容哈希，忽略采集时间等易变字段。
+// 2) 作为快照去重与测试共用的唯一哈希定义。
+
+use serde_json::Value;
+
+use crate::{MetricsSnapshotPayload, ToolsSnapshotPayload};
+
+/// 计算哈希时忽略的易变字段（任意层级）。
+const VOLATILE_FIELDS: &[&str] = &["collectedAt"];
+/// 指标快照额外忽略的字段（随时间单调变化）。
+const METRICS_VOLATILE_FIELDS: &[&str] = &["uptimeSec"];
+
+/// 可计算稳定内容哈希的快照。
+pub trait StableSnapshotHash {
+    /// 返回去除易变字段后的规范 JSON 值。
+    fn canonical_value(&self) -> Value;
+}
+
+impl StableSnapshotHash for ToolsSnapshotPayload {
+    /// 工具快照仅剔除采集时间。
+    fn canonical_value(&self) -> Value {
+        let mut value = serde_json::to_value(self).unwrap_or(Value::Null);
+        strip_fields(&mut value, VOLATILE_FIELDS);
+        value
+    }
+}
+
+impl StableSnapshotHash for MetricsSnapshotPayload {
+    /// 指标快照剔除采集时间与运行时长。
+    fn canonical_value(&self) -> Value {
+        let mut value = serde_json::to_value(self).unwrap_or(Value::Null);
+        strip_fields(&mut value, VOLATILE_FIELDS);
+        strip_fields(&mut value, METRICS_VOLATILE_FIELDS);
+        value
+    }
+}
+
+/// 计算快照稳定哈希（FNV-1a 64，对象键按字典序）。
+pub fn stable_snapshot_hash<T: StableSnapshotHash + ?Sized>(snapshot: &T) -> u64 {
+    let mut canonical = String::new();
+    write_canonical(&snapshot.canonical_value(), &mut canonical);
+    fnv1a64(canonical.as_bytes())
+}
+
+/// 递归移除指定字段。
+fn strip_fields(value: &mut Value, fields: &[&str]) {
+    match value {
+        Value::Object(map) => {
+            for field in fields {
+                map.remove(*field);
+            }
+            for child in map.values_mut() {
+                strip_fields(child, fields);
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                strip_fields(item, fields);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// 以规范顺序输出 JSON 文本，不依赖 serde_json 的 Map 实现顺序。
+fn write_canonical(value: &Value, out: &mut String) {
+    match value {
+        Value::Object(map) => {
+            let mut keys = map.keys().collect::<Vec<_>>();
+            keys.sort();
+            out.push('{');
+            for (index, key) in keys.into_iter().enumerate() {
+                if index > 0 {
+                    out.push(',');
+                }
+                out.push_str(&Value::String(key.clone()).to_string());
+                out.push(':');
+                write_canonical(&map[key], out);
+            }
+            out.push('}');
+        }
+        Value::Array(items) => {
+            out.push('[');
+            for (index, item) in items.iter().enumerate() {
+                if index > 0 {
+                    out.push(',');
+                }
+                write_canonical(item, out);
+            }
+            out.push(']');
+        }
+        other => out.push_str(&other.to_string()),
+    }
+}
+
+/// FNV-1a 64 位哈希。
+fn fnv1a64(bytes: &[u8]) -> u64 {
+    let mut hash = 0xcbf29ce484222325_u64;
+    for byte in bytes {
+        hash ^= u64::from(*byte);
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
+
+#[cfg(test)]
+mod tests {
+    use super::stable_snapshot_hash;
+    use crate::{MetricsSnapshotPayload, ToolRuntimePayload, ToolsSnapshotPayload};
+
+    fn tool(status: &str, collected_at: &str) -> ToolRuntimePayload {
+        ToolRuntimePayload {
+            tool_id: "openclaw_a".to_string(),
+            status: status.to_string(),
+            collected_at: Some(collected_at.to_string()),
+            ..ToolRuntimePayload::default()
+        }
+    }
+
+    #[test]
+    fn tools_snapshot_hash_ignores_collected_at() {
+        let first = ToolsSnapshotPayload {
+            tools: vec![tool("RUNNING", "2026-01-01T00:00:00Z")],
+            ..ToolsSnapshotPayload::default()
+        };
+        let second = ToolsSnapshotPayload {
+            tools: vec![tool("RUNNING", "2026-01-01T00:00:05Z")],
+            ..ToolsSnapshotPayload::default()
+        };
+        assert_eq!(stable_snapshot_hash(&first), stable_snapshot_hash(&second));
+
+        let changed = ToolsSnapshotPayload {
+            tools: vec![tool("OFFLINE", "2026-01-01T00:00:00Z")],
+            ..ToolsSnapshotPayload::default()
+        };
+        assert_ne!(stable_snapshot_hash(&first), stable_snapshot_hash(&changed));
+    }
+
+    #[test]
+    fn metrics_snapshot_hash_ignores_timestamps_but_not_values() {
+        let mut first = MetricsSnapshotPayload {
+            tools: vec![tool("RUNNING", "2026-01-01T00:00:00Z")],
+            ..MetricsSnapshotPayload::default()
+        };
+        first.system.uptime_sec = 10;
+        let mut second = first.clone();
+        second.system.uptime_sec = 20;
+        second.tools[0].collected_at = Some("2026-01-01T00:00:09Z".to_string());
+        assert_eq!(stable_snapshot_hash(&first), stable_snapshot_hash(&second));
+
+        second.system.cpu_percent = 42.5;
+        assert_ne!(stable_snapshot_hash(&first), stable_snapshot_hash(&second));
+    }
+}
@@ -0,0 +1,130 @@
+// 文件职责：
+// 1) 解析 relay 签发的 `yat_v1` access token 中可公开的 claims（sid/did/kid/iat/exp）。
+// 2) 仅用于展示、日志与过期提示：不校验签名，结果不可作为鉴权依据。
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::b64url::decode_b64url;
+
+/// access token 版本前缀。
+pub const ACCESS_TOKEN_PREFIX: &str = "yat_v1";
+
+/// access token 中可公开的 claims（未校验签名）。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PublicAccessClaims {
+    /// 宿主系统标识。
+    pub sid: String,
+    /// 设备标识。
+    pub did: String,
+    /// 设备密钥标识。
+    pub kid: String,
+    /// 签发时间（unix 秒）。
+    pub iat: u64,
+    /// 过期时间（unix 秒）。
+    pub exp: u64,
+}
+
+impl PublicAccessClaims {
+    /// 判断在 `now`（unix 秒）时 token 是否已过期。
+    pub fn is_expired_at(&self, now: u64) -> bool {
+        self.exp <= now
+    }
+}
+
+/// access token claims 解析错误。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TokenClaimsError {
+    /// 不是 `yat_v1.<payload>.<sig>` 三段结构。
+    Malformed,
+    /// payload 段不是合法 base64url。
+    InvalidEncoding,
+    /// payload 不是预期的 claims JSON。
+    InvalidClaims(String),
+}
+
+impl fmt::Display for TokenClaimsError {
+    /// 输出可直接展示的错误说明。
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Malformed => write!(f, "accessToken 格式无效"),
+            Self::InvalidEncoding => write!(f, "accessToken payload 编码无效"),
+            Self::InvalidClaims(err) => write!(f, "accessToken claims 无效：{err}"),
+        }
+    }
+}
+
+impl std::error::Error for TokenClaimsError {}
+
+/// 解析 access token 的公开 claims。
+///
+/// 注意：不校验签名（签名密钥只在 relay 侧），返回值仅可用于展示与诊断。
+pub fn decode_access_token_claims(token: &str) -> Result<PublicAccessClaims, TokenClaimsError> {
+    let mut parts = token.trim().split('.');
+    let version = parts.next().unwrap_or_default();
+    let payload_b64 = parts.next().unwrap_or_default();
+    let sig_b64 = parts.next().unwrap_or_default();
+    if version != ACCESS_TOKEN_PREFIX
+        || payload_b64.is_empty()
+        || sig_b64.is_empty()
+        || parts.next().is_some()
+    {
+        return Err(TokenClaimsError::Malformed);
+    }
+    let payload = decode_b64url(payload_b64).map_err(|_| TokenClaimsError::InvalidEncoding)?;
+    serde_json::from_slice(&payload).map_err(|err| TokenClaimsError::InvalidClaims(err.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use base64::{Engine as _, engine::general_purpose::URL_SAFE_NO_PAD};
+    use serde_json::json;
+
+    use super::{TokenClaimsError, decode_access_token_claims};
+
+    fn token_with_payload(payload: &str) -> String {
+        format!("yat_v1.{}.c2ln", URL_SAFE_NO_PAD.encode(payload))
+    }
+
+    #[test]
+    fn yat_v1_public_claims_decode() {
+        let payload = json!({
+            "sid": "sys_a",
+            "did": "ios_a",
+            "kid": "kid_a",
+            "iat": 1_700_000_000_u64,
+            "exp": 1_700_000_600_u64,
+            "jti": "secret-ish-id",
+        });
+        let claims = decode_access_token_claims(&token_with_payload(&payload.to_string()))
+            .expect("claims should decode");
+        assert_eq!(claims.sid, "sys_a");
+        assert_eq!(claims.did, "ios_a");
+        assert_eq!(claims.kid, "kid_a");
+        assert_eq!(claims.iat, 1_700_000_000);
+        assert_eq!(claims.exp, 1_700_000_600);
+        assert!(!claims.is_expired_at(1_700_000_599));
+        assert!(claims.is_expired_at(1_700_000_600));
+    }
+
+    #[test]
+    fn malformed_token_errors() {
+        assert_eq!(
+            decode_access_token_claims("not-a-token"),
+            Err(TokenClaimsError::Malformed)
+        );
+        assert_eq!(
+            decode_access_token_claims("yat_v2.e30.c2ln"),
+            Err(TokenClaimsError::Malformed)
+        );
+        assert_eq!(
+            decode_access_token_claims("yat_v1.***.c2ln"),
+            Err(TokenClaimsError::InvalidEncoding)
+        );
+        assert!(matches!(
+            decode_access_token_claims(&token_with_payload("{\"sid\":1}")),
+            Err(TokenClaimsError::InvalidClaims(_))
+        ));
+    }
+}
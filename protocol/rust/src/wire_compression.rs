@@ -0,0 +1,226 @@
+// 文件职责：
+// 1) 大帧应用层压缩：序列化后超过阈值的 envelope 以 gzip + base64 包装为 `type=compressed` 事件，
+//    外层保留路由字段与内层类型（`payload.innerType`），relay 无需解压即可路由与合并快照。
+// 2) 接收端透明解压，并以外层（relay 注入的可信字段）覆盖内层同名字段，防止伪造来源。
+
+use std::{
+    fmt,
+    io::{Read, Write},
+};
+
+use base64::{Engine as _, engine::general_purpose::STANDARD};
+use flate2::{Compression, read::GzDecoder, write::GzEncoder};
+use serde_json::{Value, json};
+
+use crate::EventEnvelope;
+
+/// 压缩包装事件类型。
+pub const COMPRESSED_EVENT_TYPE: &str = "compressed";
+/// 压缩包装 payload 中携带内层事件类型的字段名。
+pub const COMPRESSED_INNER_TYPE_FIELD: &str = "innerType";
+/// 当前唯一支持的压缩编码。
+pub const COMPRESSION_ENCODING_GZIP: &str = "gzip";
+/// 解压后文本上限，防止压缩炸弹。
+pub const MAX_INFLATED_BYTES: usize = 16 * 1024 * 1024;
+/// 解压时以外层为准的可信字段（relay 在外层注入）。
+const TRUSTED_OUTER_FIELDS: [&str; 4] =
+    ["systemId", "sourceClientType", "sourceDeviceId", "peerId"];
+
+/// 压缩包装解析错误。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WireCompressionError {
+    /// 包装结构或内层 envelope 无效。
+    Malformed(String),
+    /// 不支持的压缩编码。
+    UnsupportedEncoding(String),
+    /// 解压结果超过 `MAX_INFLATED_BYTES`。
+    TooLarge,
+}
+
+impl fmt::Display for WireCompressionError {
+    /// 输出便于排障的错误说明。
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Malformed(reason) => write!(f, "压缩帧无效：{reason}"),
+            Self::UnsupportedEncoding(enc) => write!(f, "不支持的压缩编码：{enc}"),
+            Self::TooLarge => write!(f, "解压后超过 {MAX_INFLATED_BYTES} 字节上限"),
+        }
+    }
+}
+
+impl std::error::Error for WireCompressionError {}
+
+/// 序列化 envelope；`min_bytes > 0` 且文本不小于阈值时尝试压缩，压缩后不更小则发送原文。
+pub fn encode_envelope_text(
+    env: &EventEnvelope,
+    min_bytes: usize,
+) -> Result<String, serde_json::Error> {
+    let raw = serde_json::to_string(env)?;
+    if min_bytes == 0 || raw.len() < min_bytes {
+        return Ok(raw);
+    }
+
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    let compressed = encoder
+        .write_all(raw.as_bytes())
+        .and_then(|_| encoder.finish());
+    let Ok(compressed) = compressed else {
+        return Ok(raw);
+    };
+
+    let mut wrapper = EventEnvelope::new(
+        COMPRESSED_EVENT_TYPE,
+        env.system_id.clone(),
+        json!({
+            "enc": COMPRESSION_ENCODING_GZIP,
+            "b64": STANDARD.encode(compressed),
+            COMPRESSED_INNER_TYPE_FIELD: env.event_type,
+        }),
+    );
+    wrapper.v = env.v;
+    wrapper.event_id = env.event_id.clone();
+    wrapper.trace_id = env.trace_id.clone();
+    wrapper.tool_id = env.tool_id.clone();
+    wrapper.peer_id = env.peer_id.clone();
+    wrapper.session_id = env.session_id.clone();
+    wrapper.ts = env.ts.clone();
+    wrapper.ack_required = env.ack_required;
+    // 外层保留 seq，relay 无需解压即可做断档检测。
+    wrapper.seq = env.seq;
+    let wrapped = serde_json::to_string(&wrapper)?;
+    Ok(if wrapped.len() < raw.len() {
+        wrapped
+    } else {
+        raw
+    })
+}
+
+/// 若文本为压缩包装则解压出内层 envelope 文本，否则返回 `Ok(None)`。
+pub fn inflate_envelope_text(raw: &str) -> Result<Option<String>, WireCompressionError> {
+    // 快速路径：绝大多数帧不是压缩包装，避免重复解析 JSON。
+    if !raw.contains(COMPRESSED_EVENT_TYPE) {
+        return Ok(None);
+    }
+    let Ok(Value::Object(outer)) = serde_json::from_str::<Value>(raw) else {
+        return Ok(None);
+    };
+    if outer.get("type").and_then(Value::as_str) != Some(COMPRESSED_EVENT_TYPE) {
+        return Ok(None);
+    }
+
+    let payload = outer
+        .get("payload")
+        .and_then(Value::as_object)
+        .ok_or_else(|| WireCompressionError::Malformed("缺少 payload".to_string()))?;
+    let enc = payload
+        .get("enc")
+        .and_then(Value::as_str)
+        .unwrap_or_default();
+    if enc != COMPRESSION_ENCODING_GZIP {
+        return Err(WireCompressionError::UnsupportedEncoding(enc.to_string()));
+    }
+    let compressed = payload
+        .get("b64")
+        .and_then(Value::as_str)
+        .and_then(|b64| STANDARD.decode(b64).ok())
+        .ok_or_else(|| WireCompressionError::Malformed("b64 无效".to_string()))?;
+
+    let mut inflated = Vec::new();
+    GzDecoder::new(compressed.as_slice())
+        .take(MAX_INFLATED_BYTES as u64 + 1)
+        .read_to_end(&mut inflated)
+        .map_err(|err| WireCompressionError::Malformed(err.to_string()))?;
+    if inflated.len() > MAX_INFLATED_BYTES {
+        return Err(WireCompressionError::TooLarge);
+    }
+
+    let mut inner = match serde_json::from_slice::<Value>(&inflated) {
+        Ok(Value::Object(inner)) => inner,
+        _ => {
+            return Err(WireCompressionError::Malformed(
+                "内层不是 envelope 对象".to_string(),
+            ));
+        }
+    };
+    if inner.get("type").and_then(Value::as_str) == Some(COMPRESSED_EVENT_TYPE) {
+        return Err(WireCompressionError::Malformed(
+            "不允许嵌套压缩".to_string(),
+        ));
+    }
+    for field in TRUSTED_OUTER_FIELDS {
+        match outer.get(field) {
+            Some(value) => {
+                inner.insert(field.to_string(), value.clone());
+            }
+            None => {
+                inner.remove(field);
+            }
+        }
+    }
+    serde_json::to_string(&inner)
+        .map(Some)
+        .map_err(|err| WireCompressionError::Malformed(err.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{Value, json};
+
+    use super::{
+        COMPRESSED_EVENT_TYPE, COMPRESSED_INNER_TYPE_FIELD, WireCompressionError,
+        encode_envelope_text, inflate_envelope_text,
+    };
+    use crate::EventEnvelope;
+
+    #[test]
+    fn large_envelope_round_trips_through_gzip_wrapper() {
+        let big = "x".repeat(64 * 1024);
+        let mut env = EventEnvelope::new("tool_details_snapshot", "sys_a", json!({ "blob": big }));
+        env.seq = Some(42);
+        env.tool_id = Some("codex_a".to_string());
+        env.peer_id = Some("ios_a".to_string());
+        env.session_id = Some("sess_a".to_string());
+
+        let small = encode_envelope_text(&env, 0).expect("encode");
+        assert!(inflate_envelope_text(&small).expect("plain").is_none());
+
+        let wrapped = encode_envelope_text(&env, 1024).expect("encode");
+        assert!(wrapped.len() < small.len() / 10);
+        let mut outer: Value = serde_json::from_str(&wrapped).expect("wrapper json");
+        assert_eq!(outer["type"], COMPRESSED_EVENT_TYPE);
+        assert_eq!(outer["eventId"], env.event_id.as_str());
+        assert_eq!(outer["seq"], 42);
+        assert_eq!(outer["toolId"], "codex_a");
+        assert_eq!(outer["peerId"], "ios_a");
+        assert_eq!(outer["sessionId"], "sess_a");
+        assert_eq!(outer["ts"], env.ts.as_str());
+        assert_eq!(
+            outer["payload"][COMPRESSED_INNER_TYPE_FIELD],
+            "tool_details_snapshot"
+        );
+
+        // relay 在外层注入可信来源，解压后以外层为准。
+        outer["sourceClientType"] = json!("sidecar");
+        outer["sourceDeviceId"] = json!("sc_a");
+        let inflated = inflate_envelope_text(&outer.to_string())
+            .expect("inflate")
+            .expect("compressed");
+        let inner: Value = serde_json::from_str(&inflated).expect("inner json");
+        assert_eq!(inner["type"], "tool_details_snapshot");
+        assert_eq!(
+            inner["payload"]["blob"].as_str().map(str::len),
+            Some(64 * 1024)
+        );
+        assert_eq!(inner["sourceDeviceId"], "sc_a");
+        assert_eq!(inner["sourceClientType"], "sidecar");
+    }
+
+    #[test]
+    fn unsupported_encoding_is_rejected() {
+        let raw = r#"{"v":1,"type":"compressed","payload":{"enc":"br","b64":""}}"#;
+        assert_eq!(
+            inflate_envelope_text(raw),
+            Err(WireCompressionError::UnsupportedEncoding("br".to_string()))
+        );
+    }
+}
@@ -3,7 +3,7 @@
 use axum::{Json, http::StatusCode};
 use serde_json::Value;
 
-use super::response::ApiEnvelope;
+use super::{response::ApiEnvelope, trace::current_trace_id};
 
 /// 认证与接口错误。
 #[derive(Debug)]
@@ -40,6 +40,7 @@ impl ApiError {
                 message: self.message,
                 suggestion: self.suggestion.to_string(),
                 data: None,
+                trace_id: current_trace_id(),
             }),
         )
     }
@@ -1,5 +1,6 @@
-//! API 公共模块：错误、响应包裹与请求类型。
+//! API 公共模块：错误、响应包裹、trace id 与请求类型。
 
 pub(crate) mod error;
 pub(crate) mod response;
+pub(crate) mod trace;
 pub(crate) mod types;
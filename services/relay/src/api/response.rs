@@ -3,6 +3,8 @@
 use axum::{Json, http::StatusCode};
 use serde::Serialize;
 
+use super::trace::current_trace_id;
+
 /// 通用 API 成功/失败包裹结构。
 #[derive(Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -16,6 +18,9 @@ where
     pub(crate) suggestion: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub(crate) data: Option<T>,
+    /// 本次请求的 trace id（见 `X-Trace-Id`），便于跨 app/relay/sidecar 关联日志。
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) trace_id: Option<String>,
 }
 
 /// 构造成功响应。
@@ -33,6 +38,7 @@ pub(crate) fn ok_response<T: Serialize>(
             message: message.into(),
             suggestion: suggestion.into(),
             data,
+            trace_id: current_trace_id(),
         }),
     )
 }
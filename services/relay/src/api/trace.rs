@@ -0,0 +1,137 @@
+//! 跨服务排障的 trace id：`/v1/*` 请求读取 `X-Trace-Id`（缺省或非法时生成），
+//! 挂到 tracing span、响应头与 `ApiEnvelope.traceId`，WS 连接再写入 `server_presence`。
+
+use axum::{
+    extract::Request,
+    http::{HeaderName, HeaderValue},
+    middleware::Next,
+    response::Response,
+};
+use tracing::{Instrument, info_span};
+use uuid::Uuid;
+
+/// 请求/响应共用的 trace id 头。
+pub(crate) const TRACE_ID_HEADER: HeaderName = HeaderName::from_static("x-trace-id");
+/// 需要注入 trace id 的路由前缀。
+const TRACED_PATH_PREFIX: &str = "/v1/";
+/// 外部传入 trace id 的最大长度。
+const MAX_TRACE_ID_LEN: usize = 128;
+
+tokio::task_local! {
+    /// 当前请求的 trace id，供响应包裹与 WS 握手读取。
+    static CURRENT_TRACE_ID: String;
+}
+
+/// 当前请求的 trace id；不在 `/v1/*` 请求作用域内时为空。
+pub(crate) fn current_trace_id() -> Option<String> {
+    CURRENT_TRACE_ID.try_with(Clone::clone).ok()
+}
+
+/// 生成与协议库 envelope 同格式的 trace id。
+fn generate_trace_id() -> String {
+    format!("trc_{}", Uuid::new_v4())
+}
+
+/// 规范化外部 trace id：仅接受不超过 128 字节的 `[A-Za-z0-9_.:-]`，否则重新生成。
+fn normalize_trace_id(raw: Option<&str>) -> String {
+    raw.map(str::trim)
+        .filter(|value| !value.is_empty() && value.len() <= MAX_TRACE_ID_LEN)
+        .filter(|value| {
+            value
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | ':' | '-'))
+        })
+        .map(ToString::to_string)
+        .unwrap_or_else(generate_trace_id)
+}
+
+/// trace id 中间件：为 `/v1/*` 请求建立 span 与 task-local 作用域，并回写响应头。
+pub(crate) async fn trace_id_layer(req: Request, next: Next) -> Response {
+    if !req.uri().path().starts_with(TRACED_PATH_PREFIX) {
+        return next.run(req).await;
+    }
+    let trace_id = normalize_trace_id(
+        req.headers()
+            .get(&TRACE_ID_HEADER)
+            .and_then(|value| value.to_str().ok()),
+    );
+    let span = info_span!(
+        "http",
+        trace_id = %trace_id,
+        method = %req.method(),
+        path = %req.uri().path()
+    );
+    let mut response = CURRENT_TRACE_ID
+        .scope(trace_id.clone(), next.run(req))
+        .instrument(span)
+        .await;
+    if let Ok(value) = HeaderValue::from_str(&trace_id) {
+        response.headers_mut().insert(TRACE_ID_HEADER, value);
+    }
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_util::StreamExt;
+    use serde_json::Value;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio_tungstenite::{connect_async, tungstenite::client::IntoClientRequest};
+
+    use super::normalize_trace_id;
+    use crate::{app::router, state::AppState};
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn trace_id_is_echoed_in_api_envelope_header_and_server_presence() {
+        assert_eq!(normalize_trace_id(Some(" trc_abc-1 ")), "trc_abc-1");
+        assert!(normalize_trace_id(Some("bad id\n")).starts_with("trc_"));
+        assert!(normalize_trace_id(Some(&"x".repeat(129))).starts_with("trc_"));
+
+        let state = AppState::for_test();
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("bind");
+        let addr = listener.local_addr().expect("local addr");
+        let server = tokio::spawn(async move {
+            axum::serve(listener, router(state)).await.expect("serve");
+        });
+
+        let body = r#"{"systemId":"","pairToken":"","deviceId":""}"#;
+        let mut stream = tokio::net::TcpStream::connect(addr).await.expect("connect");
+        let request = format!(
+            concat!(
+                "POST /v1/pair/preflight HTTP/1.1\r\nHost: {}\r\n",
+                "Content-Type: application/json\r\nX-Trace-Id: trc_pair_1\r\n",
+                "Content-Length: {}\r\nConnection: close\r\n\r\n{}"
+            ),
+            addr,
+            body.len(),
+            body
+        );
+        stream.write_all(request.as_bytes()).await.expect("write");
+        let mut raw = String::new();
+        stream.read_to_string(&mut raw).await.expect("read");
+        let (head, json) = raw.split_once("\r\n\r\n").expect("http response");
+        assert!(head.to_ascii_lowercase().contains("x-trace-id: trc_pair_1"));
+        let envelope: Value = serde_json::from_str(json).expect("envelope json");
+        assert_eq!(envelope["ok"], false);
+        assert_eq!(envelope["traceId"], "trc_pair_1");
+
+        let mut ws_request = format!(
+            "ws://{addr}/v1/ws?systemId=sys_trace&clientType=sidecar&deviceId=sc_t&pairToken=pt_t"
+        )
+        .into_client_request()
+        .expect("ws request");
+        ws_request
+            .headers_mut()
+            .insert("x-trace-id", "trc_ws_1".parse().expect("header"));
+        let (mut ws, response) = connect_async(ws_request).await.expect("ws handshake");
+        assert_eq!(response.headers()["x-trace-id"], "trc_ws_1");
+        let presence = ws.next().await.expect("presence").expect("presence frame");
+        let presence: Value =
+            serde_json::from_str(presence.to_text().expect("text")).expect("presence json");
+        assert_eq!(presence["type"], "server_presence");
+        assert_eq!(presence["traceId"], "trc_ws_1");
+        server.abort();
+    }
+}
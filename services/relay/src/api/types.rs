@@ -4,6 +4,15 @@ use std::collections::HashMap;
 
 use serde::{Deserialize, Serialize};
 
+use crate::auth::signing_keys::SigningKeyRing;
+
+pub(crate) use yc_shared_protocol::{
+    AuthDevicesData, AuthDevicesQuery, AuthFailureRecord, AuthRefreshData, AuthRefreshRequest,
+    AuthRenameDeviceRequest, AuthRevokeAllData, AuthRevokeAllRequest, AuthRevokeDeviceData,
+    AuthRevokeDeviceRequest, DeviceEntry, PairAuthMode, PairExchangeData, PairExchangeRequest,
+    PairRevokeTokenData, PairRevokeTokenRequest,
+};
+
 /// WS 握手 query 参数。
 #[derive(Debug, Deserialize)]
 pub(crate) struct WsQuery {
@@ -34,13 +43,56 @@ pub(crate) struct WsQuery {
     /// PoP 签名。
     #[serde(rename = "sig", default)]
     pub(crate) sig: Option<String>,
+    /// 客户端期望的协议版本；缺省按 v1 处理。
+    #[serde(rename = "protocolVersion", default)]
+    pub(crate) protocol_version: Option<String>,
 }
 
-/// 配对鉴权方式。
-#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize)]
-#[serde(rename_all = "camelCase")]
-pub(crate) enum PairAuthMode {
-    PairTicket,
+/// WS 连接端角色：握手时由 clientType 解析，后续鉴权与路由统一按角色分派。
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub(crate) enum ClientRole {
+    /// 移动端/桌面端控制 App（兼容历史别名 `mobile`）。
+    App,
+    /// 宿主机 sidecar。
+    Sidecar,
+}
+
+impl ClientRole {
+    /// 从 clientType 解析角色；先做历史别名归一化，未知角色返回 None。
+    pub(crate) fn parse(raw: &str) -> Option<Self> {
+        match yc_shared_protocol::normalize_client_type(raw.trim()).as_str() {
+            "app" => Some(Self::App),
+            "sidecar" => Some(Self::Sidecar),
+            _ => None,
+        }
+    }
+
+    /// 返回协议层 clientType 字符串。
+    pub(crate) fn as_str(self) -> &'static str {
+        match self {
+            Self::App => "app",
+            Self::Sidecar => "sidecar",
+        }
+    }
+}
+
+/// WS 连接协商出的鉴权方式（写入连接审计日志与 server_presence）。
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub(crate) enum ConnectionAuthMode {
+    /// App 生产链路：accessToken + PoP 签名。
+    AccessToken,
+    /// sidecar 建房，或已关闭 `requireAccessToken` 的 system 上 App 的历史直连。
+    PairToken,
+}
+
+impl ConnectionAuthMode {
+    /// 返回协议层鉴权方式字符串。
+    pub(crate) fn as_str(self) -> &'static str {
+        match self {
+            Self::AccessToken => "accessToken",
+            Self::PairToken => "pairToken",
+        }
+    }
 }
 
 /// 配对预检请求。
@@ -63,35 +115,24 @@ pub(crate) struct PairPreflightData {
     pub(crate) auth_mode: PairAuthMode,
 }
 
-/// 配对换发请求。
+/// 配对票据试校验请求（不消费票据）。
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
-pub(crate) struct PairExchangeRequest {
+pub(crate) struct PairTicketVerifyRequest {
     pub(crate) system_id: String,
-    pub(crate) device_id: String,
-    #[serde(default)]
-    pub(crate) device_name: String,
-    /// 兼容字段：旧客户端可能仍上传 pairToken，新版将显式拒绝。
-    #[serde(default)]
-    pub(crate) pair_token: Option<String>,
     #[serde(default)]
-    pub(crate) pair_ticket: Option<String>,
-    pub(crate) device_pub_key: String,
-    pub(crate) key_id: String,
-    pub(crate) proof: String,
+    pub(crate) pair_ticket: String,
 }
 
-/// 配对换发数据。
+/// 配对票据试校验返回数据。
 #[derive(Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
-pub(crate) struct PairExchangeData {
-    pub(crate) auth_mode: PairAuthMode,
-    pub(crate) access_token: String,
-    pub(crate) refresh_token: String,
-    pub(crate) key_id: String,
-    pub(crate) credential_id: String,
-    pub(crate) access_expires_in_sec: u64,
-    pub(crate) refresh_expires_in_sec: u64,
+pub(crate) struct PairTicketVerifyData {
+    pub(crate) valid: bool,
+    /// 票据过期时间（unix 秒）。
+    pub(crate) exp: u64,
+    /// 距过期剩余秒数。
+    pub(crate) remaining_sec: u64,
 }
 
 /// 配对签发请求（供 sidecar/脚本统一拿链接）。
@@ -124,91 +165,74 @@ pub(crate) struct PairBootstrapData {
     pub(crate) simctl_command: String,
 }
 
-/// 刷新请求。
+/// system 策略更新请求（以 sidecar pairToken 证明宿主机归属）。
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
-pub(crate) struct AuthRefreshRequest {
+pub(crate) struct AdminSystemFeaturesRequest {
     pub(crate) system_id: String,
-    pub(crate) device_id: String,
-    pub(crate) refresh_token: String,
-    pub(crate) key_id: String,
-    pub(crate) ts: String,
-    pub(crate) nonce: String,
-    pub(crate) sig: String,
+    pub(crate) pair_token: String,
+    #[serde(default)]
+    pub(crate) require_access_token: Option<bool>,
+    #[serde(default)]
+    pub(crate) allow_ticket_pairing: Option<bool>,
 }
 
-/// 刷新返回。
+/// system 策略更新结果。
 #[derive(Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
-pub(crate) struct AuthRefreshData {
-    pub(crate) access_token: String,
-    pub(crate) refresh_token: String,
-    pub(crate) key_id: String,
-    pub(crate) credential_id: String,
-    pub(crate) access_expires_in_sec: u64,
-    pub(crate) refresh_expires_in_sec: u64,
-}
-
-/// 吊销请求。
-#[derive(Debug, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub(crate) struct AuthRevokeDeviceRequest {
+pub(crate) struct AdminSystemFeaturesData {
     pub(crate) system_id: String,
-    pub(crate) device_id: String,
-    pub(crate) target_device_id: String,
-    pub(crate) access_token: String,
-    pub(crate) key_id: String,
-    pub(crate) ts: String,
-    pub(crate) nonce: String,
-    pub(crate) sig: String,
+    pub(crate) features: SystemFeatures,
 }
 
-/// 吊销结果。
-#[derive(Debug, Serialize)]
+/// 认证状态只读导出：仅包含哈希与非敏感元数据，不含签名种子与 refresh 会话。
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
-pub(crate) struct AuthRevokeDeviceData {
-    pub(crate) target_device_id: String,
+pub(crate) struct AuthStoreExport {
+    pub(crate) version: u32,
+    #[serde(default)]
+    pub(crate) exported_at: String,
+    pub(crate) systems: Vec<SystemExportEntry>,
 }
 
-/// 设备列表查询参数。
-#[derive(Debug, Deserialize)]
+/// 单 system 导出条目。
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
-pub(crate) struct AuthDevicesQuery {
+pub(crate) struct SystemExportEntry {
     pub(crate) system_id: String,
-    pub(crate) device_id: String,
-    pub(crate) access_token: String,
-    pub(crate) key_id: String,
-    pub(crate) ts: String,
-    pub(crate) nonce: String,
-    pub(crate) sig: String,
+    #[serde(default)]
+    pub(crate) pair_token_hash: Option<String>,
+    #[serde(default)]
+    pub(crate) pair_token_updated_at: Option<String>,
+    #[serde(default)]
+    pub(crate) features: SystemFeatures,
+    #[serde(default)]
+    pub(crate) devices: Vec<DeviceCredential>,
 }
 
-/// 设备列表项。
-#[derive(Debug, Serialize)]
+/// 认证状态导入合并结果。
+#[derive(Debug, Default, PartialEq, Eq, Serialize)]
 #[serde(rename_all = "camelCase")]
-pub(crate) struct DeviceEntry {
-    pub(crate) device_id: String,
-    pub(crate) device_name: String,
-    pub(crate) key_id: String,
-    pub(crate) status: String,
-    pub(crate) created_at: String,
-    pub(crate) last_seen_at: String,
-    pub(crate) revoked_at: Option<String>,
+pub(crate) struct AdminImportData {
+    pub(crate) systems_added: usize,
+    pub(crate) devices_added: usize,
+    pub(crate) devices_skipped: usize,
 }
 
-/// 设备列表返回。
-#[derive(Debug, Serialize)]
-#[serde(rename_all = "camelCase")]
-pub(crate) struct AuthDevicesData {
-    pub(crate) devices: Vec<DeviceEntry>,
-}
+/// 认证存储格式版本：`2` 起签名密钥保存在密钥环中。
+pub(crate) const AUTH_STORE_VERSION: u32 = 2;
 
 /// 持久化认证元数据。
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub(crate) struct AuthStore {
     pub(crate) version: u32,
+    /// 旧版单签名种子：读取时迁移进密钥环；写回时保存当前签发密钥，
+    /// 保证回滚到只认识该字段的旧版 relay 后仍能验签（保留一个版本后移除）。
+    #[serde(default)]
     pub(crate) signing_key: String,
+    #[serde(default)]
+    pub(crate) signing_keys: SigningKeyRing,
     pub(crate) systems: HashMap<String, SystemAuthState>,
 }
 
@@ -216,7 +240,8 @@ impl AuthStore {
     /// 使用签名种子初始化认证存储。
     pub(crate) fn new(signing_key: String) -> Self {
         Self {
-            version: 1,
+            version: AUTH_STORE_VERSION,
+            signing_keys: SigningKeyRing::new(signing_key.clone()),
             signing_key,
             systems: HashMap::new(),
         }
@@ -239,8 +264,37 @@ impl AuthStore {
 pub(crate) struct SystemAuthState {
     pub(crate) pair_token_hash: Option<String>,
     pub(crate) pair_token_updated_at: Option<String>,
+    #[serde(default)]
+    pub(crate) previous_pair_token_hash: Option<String>,
+    #[serde(default)]
+    pub(crate) previous_pair_token_expires_at: Option<u64>,
+    /// 已作废的配对令牌哈希：sidecar 持这些令牌连接时拒绝，不再轮换回去。
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) revoked_pair_token_hashes: Vec<String>,
     pub(crate) devices: HashMap<String, DeviceCredential>,
     pub(crate) refresh_sessions: HashMap<String, RefreshSession>,
+    #[serde(default)]
+    pub(crate) features: SystemFeatures,
+}
+
+/// 单 system 策略开关，默认值与全局行为一致。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub(crate) struct SystemFeatures {
+    /// app 连接是否必须使用 accessToken + PoP；关闭后允许 pairToken 直连。
+    pub(crate) require_access_token: bool,
+    /// 是否允许通过 pairTicket 配对新设备。
+    pub(crate) allow_ticket_pairing: bool,
+}
+
+impl Default for SystemFeatures {
+    /// 默认：app 必须使用 accessToken，允许票据配对。
+    fn default() -> Self {
+        Self {
+            require_access_token: true,
+            allow_ticket_pairing: true,
+        }
+    }
 }
 
 /// 设备凭证记录。
@@ -255,6 +309,25 @@ pub(crate) struct DeviceCredential {
     pub(crate) created_at: String,
     pub(crate) last_seen_at: String,
     pub(crate) revoked_at: Option<String>,
+    /// 最近一次连接鉴权失败，仅保留最新一条。
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) last_auth_failure: Option<AuthFailureRecord>,
+}
+
+impl From<DeviceCredential> for DeviceEntry {
+    /// 转为对外设备列表项（不含公钥）。
+    fn from(item: DeviceCredential) -> Self {
+        Self {
+            device_id: item.device_id,
+            device_name: item.device_name,
+            key_id: item.key_id,
+            status: item.status,
+            created_at: item.created_at,
+            last_seen_at: item.last_seen_at,
+            revoked_at: item.revoked_at,
+            last_auth_failure: item.last_auth_failure,
+        }
+    }
 }
 
 /// refresh 会话记录。
@@ -283,6 +356,9 @@ pub(crate) struct AccessTokenClaims {
     pub(crate) iat: u64,
     pub(crate) exp: u64,
     pub(crate) jti: String,
+    /// 签名密钥 kid；旧版 token 未携带时按迁移后的旧种子验签。
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub(crate) skid: String,
 }
 
 /// 短时票据 claims。
@@ -327,11 +403,41 @@ pub(crate) const ANSI_CYAN: &str = "\x1b[36m";
 /// 终端高亮样式：亮白。
 pub(crate) const ANSI_WHITE: &str = "\x1b[97m";
 
-/// 预检与换发接口默认使用的 access token TTL（秒）。
+/// 预检与换发接口默认使用的 access token TTL（秒），可由 `RELAY_ACCESS_TTL_SEC` 覆盖。
 pub(crate) const ACCESS_TOKEN_TTL_SEC: u64 = 600;
-/// refresh token 有效期（秒）。
+/// refresh token 默认有效期（秒），可由 `RELAY_REFRESH_TTL_SEC` 覆盖。
 pub(crate) const REFRESH_TOKEN_TTL_SEC: u64 = 30 * 24 * 3600;
-/// PoP 签名请求时间窗（秒）。
+/// PoP 签名请求默认时间窗（秒），可由 `RELAY_POP_SKEW_SEC` 覆盖。
 pub(crate) const POP_MAX_SKEW_SEC: u64 = 120;
 /// 配对票据默认有效期（秒）。
 pub(crate) const DEFAULT_PAIR_TICKET_TTL_SEC: u64 = 300;
+/// 设备列表单页最大条数（未指定 limit 时同样生效）。
+pub(crate) const DEVICES_PAGE_MAX: usize = 200;
+/// 同一设备相同错误码的鉴权失败最短落盘间隔（秒），期间只更新内存。
+pub(crate) const AUTH_FAILURE_PERSIST_INTERVAL_SEC: i64 = 60;
+
+#[cfg(test)]
+mod tests {
+    use super::ClientRole;
+
+    #[test]
+    fn client_role_parses_known_roles() {
+        assert_eq!(ClientRole::parse("app"), Some(ClientRole::App));
+        assert_eq!(ClientRole::parse("sidecar"), Some(ClientRole::Sidecar));
+        assert_eq!(ClientRole::parse(" sidecar "), Some(ClientRole::Sidecar));
+    }
+
+    #[test]
+    fn client_role_maps_legacy_mobile_alias_to_app() {
+        let role = ClientRole::parse("mobile");
+        assert_eq!(role, Some(ClientRole::App));
+        assert_eq!(role.map(ClientRole::as_str), Some("app"));
+    }
+
+    #[test]
+    fn client_role_rejects_unknown_roles() {
+        assert_eq!(ClientRole::parse(""), None);
+        assert_eq!(ClientRole::parse("observer"), None);
+        assert_eq!(ClientRole::parse("SIDECAR"), None);
+    }
+}
@@ -1,52 +1,145 @@
-//! Relay 应用装配：路由、CORS 与监听。
+//! Relay 应用装配：路由、CORS 与监听（可选原生 TLS，支持优雅停机）。
 
-use std::collections::HashMap;
+use std::net::SocketAddr;
 
 use axum::{
     Json, Router,
-    extract::State,
-    http::{
-        Method,
-        header::{AUTHORIZATION, CONTENT_TYPE},
-    },
+    extract::{State, connect_info::Connected},
+    http::{HeaderMap, StatusCode, header::CONTENT_TYPE},
+    middleware,
+    response::{IntoResponse, Response},
     routing::{get, post},
+    serve::IncomingStream,
 };
-use tower_http::cors::{Any, CorsLayer};
+use serde::Serialize;
 use tracing::info;
 
 use crate::{
-    auth::handlers::{auth_devices_handler, auth_refresh_handler, auth_revoke_device_handler},
-    pairing::handlers::{pair_bootstrap_handler, pair_exchange_handler, pair_preflight_handler},
+    api::{
+        response::{ApiEnvelope, ok_response},
+        trace::trace_id_layer,
+    },
+    auth::{
+        expiry::spawn_system_expiry_sweeper,
+        handlers::{
+            admin_export_handler, admin_import_handler, admin_system_features_handler,
+            auth_devices_handler, auth_refresh_handler, auth_rename_device_handler,
+            auth_revoke_all_handler, auth_revoke_device_handler,
+        },
+    },
+    cors::cors_layer_from_env,
+    debug::{DebugSystemsData, debug_gate_from_env},
+    metrics::{METRICS_CONTENT_TYPE, metrics_enabled},
+    pairing::{
+        handlers::{
+            pair_bootstrap_handler, pair_exchange_handler, pair_preflight_handler,
+            pair_revoke_token_handler, pair_ticket_verify_handler,
+        },
+        rate_limit::spawn_pair_rate_pruner,
+    },
     state::AppState,
-    ws::handlers::ws_handler,
+    tls::{TlsListener, load_server_config, tls_paths_from_env},
+    ws::{
+        ack::spawn_ack_retry_sweeper,
+        event_types::init_event_allowlist,
+        handlers::ws_handler,
+        shutdown::{drain_after, shutdown_grace_from_env, wait_for_shutdown_signal},
+    },
 };
 
+/// 连接对端地址，经 `ConnectInfo<PeerAddr>` 暴露给需要识别来源的处理函数（如配对限流）。
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct PeerAddr(pub(crate) SocketAddr);
+
+impl Connected<IncomingStream<'_, tokio::net::TcpListener>> for PeerAddr {
+    /// 明文监听的对端地址。
+    fn connect_info(stream: IncomingStream<'_, tokio::net::TcpListener>) -> Self {
+        Self(*stream.remote_addr())
+    }
+}
+
+impl Connected<IncomingStream<'_, TlsListener>> for PeerAddr {
+    /// 原生 TLS 监听的对端地址。
+    fn connect_info(stream: IncomingStream<'_, TlsListener>) -> Self {
+        Self(*stream.remote_addr())
+    }
+}
+
 /// Relay 入口：启动 HTTP/WS 路由。
 pub(crate) async fn run() -> anyhow::Result<()> {
     let addr = std::env::var("RELAY_ADDR").unwrap_or_else(|_| "0.0.0.0:18080".to_string());
-    let state = AppState::default();
-    let cors = CorsLayer::new()
-        .allow_origin(Any)
-        .allow_methods([Method::GET, Method::POST, Method::OPTIONS])
-        .allow_headers([CONTENT_TYPE, AUTHORIZATION]);
-
-    let app = Router::new()
-        .route("/healthz", get(healthz))
+    // 证书配置不完整时在加载状态前直接失败，避免以明文误启动。
+    let tls_paths = tls_paths_from_env()?;
+    let state = AppState::load()?;
+    init_event_allowlist();
+    spawn_system_expiry_sweeper(state.clone());
+    spawn_ack_retry_sweeper(state.clone());
+    spawn_pair_rate_pruner(state.clone());
+    let app = router(state.clone());
+    let shutdown = drain_after(state, shutdown_grace_from_env(), wait_for_shutdown_signal());
+
+    let listener = tokio::net::TcpListener::bind(&addr).await?;
+    match tls_paths {
+        Some(paths) => {
+            let config = load_server_config(&paths)?;
+            info!(
+                "relay-rs listening on {addr} (tls cert={})",
+                paths.cert.display()
+            );
+            axum::serve(
+                TlsListener::new(listener, config)?,
+                app.into_make_service_with_connect_info::<PeerAddr>(),
+            )
+            .with_graceful_shutdown(shutdown)
+            .await?;
+        }
+        None => {
+            info!("relay-rs listening on {addr}");
+            axum::serve(
+                listener,
+                app.into_make_service_with_connect_info::<PeerAddr>(),
+            )
+            .with_graceful_shutdown(shutdown)
+            .await?;
+        }
+    }
+    info!("relay-rs stopped");
+    Ok(())
+}
+
+/// 装配全部 HTTP/WS 路由、trace id 中间件与 CORS。
+pub(crate) fn router(state: AppState) -> Router {
+    let cors = cors_layer_from_env();
+
+    let mut app = Router::new();
+    // 指标接口默认不注册，避免对外暴露 system 维度信息。
+    if metrics_enabled() {
+        info!("relay metrics endpoint enabled path=/metrics");
+        app = app.route("/metrics", get(metrics));
+    }
+    app.route("/healthz", get(healthz))
+        .route("/v1/version", get(version))
         .route("/v1/debug/systems", get(debug_systems))
         .route("/v1/pair/preflight", post(pair_preflight_handler))
+        .route("/v1/pair/ticket/verify", post(pair_ticket_verify_handler))
         .route("/v1/pair/exchange", post(pair_exchange_handler))
         .route("/v1/pair/bootstrap", post(pair_bootstrap_handler))
+        .route("/v1/pair/revoke-token", post(pair_revoke_token_handler))
         .route("/v1/auth/refresh", post(auth_refresh_handler))
         .route("/v1/auth/revoke-device", post(auth_revoke_device_handler))
+        .route("/v1/auth/revoke-all", post(auth_revoke_all_handler))
+        .route("/v1/auth/rename-device", post(auth_rename_device_handler))
         .route("/v1/auth/devices", get(auth_devices_handler))
+        .route(
+            "/v1/admin/system-features",
+            post(admin_system_features_handler),
+        )
+        .route("/v1/admin/export", get(admin_export_handler))
+        .route("/v1/admin/import", post(admin_import_handler))
         .route("/v1/ws", get(ws_handler))
+        .layer(middleware::from_fn(trace_id_layer))
         .layer(cors)
-        .with_state(state);
-
-    let listener = tokio::net::TcpListener::bind(&addr).await?;
-    info!("relay-rs listening on {addr}");
-    axum::serve(listener, app).await?;
-    Ok(())
+        .with_state(state)
 }
 
 /// 健康检查接口。
@@ -54,7 +147,91 @@ async fn healthz() -> &'static str {
     "ok"
 }
 
-/// 调试接口：查看每个 system 当前连接数。
-async fn debug_systems(State(state): State<AppState>) -> Json<HashMap<String, usize>> {
-    Json(state.snapshot().await)
+/// Prometheus 指标接口（需 `RELAY_METRICS_ENABLED=1` 开启）。
+async fn metrics(State(state): State<AppState>, headers: HeaderMap) -> Response {
+    match state.render_metrics(&headers).await {
+        Ok(text) => ([(CONTENT_TYPE, METRICS_CONTENT_TYPE)], text).into_response(),
+        Err(err) => err.into_response().into_response(),
+    }
+}
+
+/// 构建信息：git sha 优先取编译期注入，其次取运行时环境变量。
+const GIT_SHA_ENV: &str = "YC_GIT_SHA";
+
+/// 版本接口返回数据。
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct VersionData {
+    name: &'static str,
+    version: &'static str,
+    protocol_version: u8,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    git_sha: Option<String>,
+}
+
+/// 版本接口：返回服务名、包版本、协议版本与可选 git sha。
+async fn version() -> Json<VersionData> {
+    Json(version_data())
+}
+
+/// 组装版本信息。
+fn version_data() -> VersionData {
+    let git_sha = option_env!("YC_GIT_SHA")
+        .map(ToString::to_string)
+        .or_else(|| std::env::var(GIT_SHA_ENV).ok())
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty());
+    VersionData {
+        name: env!("CARGO_PKG_NAME"),
+        version: env!("CARGO_PKG_VERSION"),
+        protocol_version: yc_shared_protocol::PROTOCOL_VERSION,
+        git_sha,
+    }
+}
+
+/// 调试接口：查看每个 system 当前连接数（需 `RELAY_DEBUG_ENDPOINTS=1` 开启）。
+async fn debug_systems(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> (StatusCode, Json<ApiEnvelope<DebugSystemsData>>) {
+    let (enabled, admin_token) = debug_gate_from_env();
+    match state.debug_systems(&headers, enabled, &admin_token).await {
+        Ok(data) => ok_response(
+            StatusCode::OK,
+            "连接快照获取成功",
+            "快照最多缓存 2 秒",
+            Some(data),
+        ),
+        Err(err) => {
+            let (status, body) = err.into_response();
+            (
+                status,
+                Json(ApiEnvelope {
+                    ok: body.0.ok,
+                    code: body.0.code,
+                    message: body.0.message,
+                    suggestion: body.0.suggestion,
+                    data: None,
+                    trace_id: body.0.trace_id,
+                }),
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::version;
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn version_endpoint_returns_package_and_protocol_version() {
+        let body = version().await.0;
+        let value = serde_json::to_value(&body).expect("version should serialize");
+        assert_eq!(value["name"], "yc-relay");
+        assert_eq!(value["version"], env!("CARGO_PKG_VERSION"));
+        assert_eq!(
+            value["protocolVersion"],
+            u64::from(yc_shared_protocol::PROTOCOL_VERSION)
+        );
+    }
 }
@@ -0,0 +1,75 @@
+//! 运维管理接口鉴权：以 `RELAY_ADMIN_TOKEN` 配置的共享令牌校验 `Authorization: Bearer`。
+
+use axum::http::{HeaderMap, StatusCode, header::AUTHORIZATION};
+
+use crate::{api::error::ApiError, auth::token_crypto::sha256_hex};
+
+/// 运维管理令牌环境变量（未设置时管理接口整体关闭）。
+pub(crate) const RELAY_ADMIN_TOKEN_ENV: &str = "RELAY_ADMIN_TOKEN";
+
+/// 读取已配置的管理令牌（去除首尾空白，未设置时为空串）。
+pub(crate) fn admin_token_from_env() -> String {
+    std::env::var(RELAY_ADMIN_TOKEN_ENV)
+        .map(|value| value.trim().to_string())
+        .unwrap_or_default()
+}
+
+/// 校验请求头中的管理令牌。
+pub(crate) fn verify_admin_token(headers: &HeaderMap) -> Result<(), ApiError> {
+    verify_admin_token_with(headers, &admin_token_from_env())
+}
+
+/// 按给定管理令牌校验请求头；`expected` 为空表示未启用管理接口。
+pub(crate) fn verify_admin_token_with(headers: &HeaderMap, expected: &str) -> Result<(), ApiError> {
+    if expected.is_empty() {
+        return Err(ApiError::new(
+            StatusCode::FORBIDDEN,
+            "ADMIN_DISABLED",
+            "relay 未启用运维管理接口",
+            "请配置 RELAY_ADMIN_TOKEN 后重启 relay",
+        ));
+    }
+    let provided = headers
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(str::trim)
+        .unwrap_or_default();
+    // 比较哈希而非原文，避免按前缀逐字节泄露时序信息。
+    if provided.is_empty() || sha256_hex(provided) != sha256_hex(expected) {
+        return Err(ApiError::new(
+            StatusCode::UNAUTHORIZED,
+            "ADMIN_TOKEN_INVALID",
+            "管理令牌无效",
+            "请在 Authorization 头携带 Bearer RELAY_ADMIN_TOKEN",
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::{HeaderMap, HeaderValue, header::AUTHORIZATION};
+
+    use super::verify_admin_token_with;
+
+    #[test]
+    fn admin_token_requires_matching_bearer() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(
+            verify_admin_token_with(&headers, "").unwrap_err().code,
+            "ADMIN_DISABLED"
+        );
+        assert_eq!(
+            verify_admin_token_with(&headers, "secret")
+                .unwrap_err()
+                .code,
+            "ADMIN_TOKEN_INVALID"
+        );
+
+        headers.insert(AUTHORIZATION, HeaderValue::from_static("Bearer other"));
+        assert!(verify_admin_token_with(&headers, "secret").is_err());
+        headers.insert(AUTHORIZATION, HeaderValue::from_static("Bearer secret"));
+        assert!(verify_admin_token_with(&headers, "secret").is_ok());
+    }
+}
@@ -0,0 +1,228 @@
+//! 鉴权审计日志：配对换发、配对令牌作废、刷新、吊销、批量吊销、改名与 WS 鉴权失败逐条追加为 JSONL。
+//! 每行携带上一行的 SHA-256（`prevHash`），任意行被改动或删除都会使后续链路校验失败。
+//! 写入失败只告警，不影响请求结果；`RELAY_AUDIT_LOG_PATH` 未设置时不记录。
+
+use std::{
+    fs::{self, File, OpenOptions},
+    io::Write as _,
+    path::PathBuf,
+    sync::Mutex,
+};
+
+use serde::Serialize;
+use tracing::warn;
+
+use crate::{api::error::ApiError, auth::token_crypto::sha256_hex};
+
+/// 审计日志路径环境变量。
+const RELAY_AUDIT_LOG_PATH_ENV: &str = "RELAY_AUDIT_LOG_PATH";
+/// 成功结果的 outcome 码。
+const AUDIT_OUTCOME_OK: &str = "OK";
+
+/// 审计事件类型。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum AuditAction {
+    /// 配对换发。
+    PairExchange,
+    /// 配对令牌作废。
+    PairRevokeToken,
+    /// refresh 轮换。
+    Refresh,
+    /// 设备吊销。
+    Revoke,
+    /// 批量吊销。
+    RevokeAll,
+    /// 设备改名。
+    Rename,
+    /// WS 握手鉴权（仅记录失败）。
+    WsAuth,
+}
+
+/// 单条审计记录的业务字段。
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct AuditRecord<'a> {
+    /// 事件类型。
+    pub(crate) action: AuditAction,
+    /// system ID。
+    pub(crate) system_id: &'a str,
+    /// 发起方设备 ID。
+    pub(crate) device_id: &'a str,
+    /// 发起方 keyId（未携带时为空）。
+    pub(crate) key_id: &'a str,
+    /// 被操作设备（吊销/改名）。
+    pub(crate) target_device_id: Option<&'a str>,
+}
+
+/// 落盘行结构。
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct AuditLine<'a> {
+    ts: String,
+    action: AuditAction,
+    system_id: &'a str,
+    device_id: &'a str,
+    key_id: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    target_device_id: Option<&'a str>,
+    outcome: &'a str,
+    prev_hash: &'a str,
+}
+
+/// 审计日志写入器；未配置路径时所有记录被忽略。
+#[derive(Debug, Default)]
+pub(crate) struct AuditLog {
+    /// 写入状态；None 表示未启用。
+    writer: Option<Mutex<AuditWriter>>,
+}
+
+/// 已启用时的写入状态。
+#[derive(Debug)]
+struct AuditWriter {
+    /// 日志路径。
+    path: PathBuf,
+    /// 打开的文件句柄（写失败后置空，下次重新打开）。
+    file: Option<File>,
+    /// 上一行的 SHA-256（空文件为空串）。
+    prev_hash: String,
+}
+
+impl AuditLog {
+    /// 读取 `RELAY_AUDIT_LOG_PATH`；未设置时返回未启用的写入器。
+    pub(crate) fn from_env() -> Self {
+        std::env::var(RELAY_AUDIT_LOG_PATH_ENV)
+            .ok()
+            .map(|raw| raw.trim().to_string())
+            .filter(|raw| !raw.is_empty())
+            .map(|raw| Self::open(PathBuf::from(raw)))
+            .unwrap_or_default()
+    }
+
+    /// 以指定路径启用审计日志，并从已有文件末行续接哈希链。
+    pub(crate) fn open(path: PathBuf) -> Self {
+        let prev_hash = fs::read_to_string(&path)
+            .ok()
+            .and_then(|raw| raw.lines().last().map(sha256_hex))
+            .unwrap_or_default();
+        Self {
+            writer: Some(Mutex::new(AuditWriter {
+                path,
+                file: None,
+                prev_hash,
+            })),
+        }
+    }
+
+    /// 按请求结果记录一条审计日志。
+    pub(crate) fn record_result<T>(&self, record: AuditRecord<'_>, result: &Result<T, ApiError>) {
+        let outcome = match result {
+            Ok(_) => AUDIT_OUTCOME_OK,
+            Err(err) => err.code,
+        };
+        self.record(record, outcome);
+    }
+
+    /// 追加一条审计日志并立即 flush；`outcome` 成功为 `OK`，失败为错误码。写入失败只告警。
+    pub(crate) fn record(&self, record: AuditRecord<'_>, outcome: &str) {
+        let Some(writer) = self.writer.as_ref() else {
+            return;
+        };
+        let mut writer = writer.lock().unwrap_or_else(|err| err.into_inner());
+        if let Err(err) = writer.append(record, outcome) {
+            warn!(
+                "append audit log failed path={} err={err}",
+                writer.path.display()
+            );
+            writer.file = None;
+        }
+    }
+}
+
+impl AuditWriter {
+    /// 序列化并写入一行，成功后推进哈希链。
+    fn append(&mut self, record: AuditRecord<'_>, outcome: &str) -> Result<(), String> {
+        let line = serde_json::to_string(&AuditLine {
+            ts: yc_shared_protocol::now_rfc3339_nanos(),
+            action: record.action,
+            system_id: record.system_id,
+            device_id: record.device_id,
+            key_id: record.key_id,
+            target_device_id: record.target_device_id,
+            outcome,
+            prev_hash: &self.prev_hash,
+        })
+        .map_err(|err| format!("encode audit line failed: {err}"))?;
+        if self.file.is_none() {
+            if let Some(parent) = self.path.parent() {
+                fs::create_dir_all(parent).map_err(|err| err.to_string())?;
+            }
+            let file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&self.path)
+                .map_err(|err| err.to_string())?;
+            self.file = Some(file);
+        }
+        let Some(file) = self.file.as_mut() else {
+            return Err("audit log file unavailable".to_string());
+        };
+        file.write_all(format!("{line}\n").as_bytes())
+            .and_then(|_| file.flush())
+            .map_err(|err| err.to_string())?;
+        self.prev_hash = sha256_hex(&line);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::Value;
+    use uuid::Uuid;
+
+    use super::{AuditAction, AuditLog, AuditRecord};
+    use crate::auth::token_crypto::sha256_hex;
+
+    /// 构造测试记录。
+    fn record(action: AuditAction) -> AuditRecord<'static> {
+        AuditRecord {
+            action,
+            system_id: "sys_a",
+            device_id: "ios_a",
+            key_id: "kid_a",
+            target_device_id: None,
+        }
+    }
+
+    #[test]
+    fn lines_form_hash_chain_across_reopen() {
+        let dir = std::env::temp_dir().join(format!("yc-relay-audit-{}", Uuid::new_v4()));
+        let path = dir.join("audit.jsonl");
+        let log = AuditLog::open(path.clone());
+        log.record(record(AuditAction::PairExchange), "OK");
+        log.record(record(AuditAction::Refresh), "REFRESH_TOKEN_REUSED");
+        drop(log);
+        AuditLog::open(path.clone()).record(
+            AuditRecord {
+                target_device_id: Some("ios_b"),
+                ..record(AuditAction::Revoke)
+            },
+            "OK",
+        );
+
+        let raw = std::fs::read_to_string(&path).expect("read audit log");
+        let lines = raw.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 3);
+        let parsed = lines
+            .iter()
+            .map(|line| serde_json::from_str::<Value>(line).expect("json line"))
+            .collect::<Vec<_>>();
+        assert_eq!(parsed[0]["prevHash"], "");
+        assert_eq!(parsed[0]["action"], "pair_exchange");
+        assert_eq!(parsed[1]["outcome"], "REFRESH_TOKEN_REUSED");
+        assert_eq!(parsed[1]["prevHash"], sha256_hex(lines[0]));
+        assert_eq!(parsed[2]["prevHash"], sha256_hex(lines[1]));
+        assert_eq!(parsed[2]["targetDeviceId"], "ios_b");
+        assert!(parsed[0].get("targetDeviceId").is_none());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,218 @@
+//! 认证存储后端：`AuthStoreBackend` 抽象加载、持久化与原子读改写，默认实现沿用单 JSON 文件；
+//! `RELAY_AUTH_STORE_BACKEND` 选择后端（当前仅 `file`），为多副本共享的 Redis/Postgres 后端预留扩展点。
+
+use std::{
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+use crate::{
+    api::types::AuthStore,
+    auth::store::{auth_store_path, load_auth_store, persist_auth_store},
+};
+
+/// 后端选择环境变量。
+const AUTH_STORE_BACKEND_ENV: &str = "RELAY_AUTH_STORE_BACKEND";
+/// 默认后端名称。
+const FILE_BACKEND: &str = "file";
+
+/// 认证存储后端；relay 运行期以内存副本为准，每次变更后整体持久化。
+pub(crate) trait AuthStoreBackend: Send + Sync {
+    /// 后端名称（日志用）。
+    fn name(&self) -> &'static str;
+
+    /// 后端描述（路径或连接目标，日志用）。
+    fn describe(&self) -> String;
+
+    /// 本地落盘路径；非文件后端为 `None`，nonce 日志与过期备份不再落在其旁边。
+    fn local_path(&self) -> Option<&Path> {
+        None
+    }
+
+    /// 加载认证存储；尚无数据时返回带新签名种子的空存储。
+    fn load(&self) -> Result<AuthStore, String>;
+
+    /// 整体持久化认证存储。
+    fn persist(&self, store: &AuthStore) -> Result<(), String>;
+
+    /// 原子读改写：以后端最新数据为基础应用修改并持久化，返回修改后的存储。
+    /// 多副本后端须保证同一时刻只有一个写入方（事务、乐观锁等）。
+    fn update(&self, apply: &mut dyn FnMut(&mut AuthStore)) -> Result<AuthStore, String>;
+}
+
+/// 按环境变量选择后端；未设置时使用默认文件后端。
+pub(crate) fn auth_store_backend_from_env() -> Result<Arc<dyn AuthStoreBackend>, String> {
+    let kind = std::env::var(AUTH_STORE_BACKEND_ENV).unwrap_or_default();
+    match kind.trim().to_ascii_lowercase().as_str() {
+        "" | FILE_BACKEND => Ok(Arc::new(FileAuthStoreBackend::new(auth_store_path()?))),
+        other => Err(format!(
+            "unsupported {AUTH_STORE_BACKEND_ENV}={other}, supported: {FILE_BACKEND}"
+        )),
+    }
+}
+
+/// 单 JSON 文件后端（默认），只适用于单副本部署。
+#[derive(Debug)]
+pub(crate) struct FileAuthStoreBackend {
+    /// 认证存储文件路径。
+    path: PathBuf,
+    /// 串行化进程内的读改写。
+    update_lock: Mutex<()>,
+}
+
+impl FileAuthStoreBackend {
+    /// 以文件路径创建后端。
+    pub(crate) fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            update_lock: Mutex::new(()),
+        }
+    }
+}
+
+impl AuthStoreBackend for FileAuthStoreBackend {
+    /// 后端名称。
+    fn name(&self) -> &'static str {
+        FILE_BACKEND
+    }
+
+    /// 文件路径。
+    fn describe(&self) -> String {
+        self.path.display().to_string()
+    }
+
+    /// 文件路径。
+    fn local_path(&self) -> Option<&Path> {
+        Some(&self.path)
+    }
+
+    /// 读取文件。
+    fn load(&self) -> Result<AuthStore, String> {
+        load_auth_store(&self.path)
+    }
+
+    /// 写入文件。
+    fn persist(&self, store: &AuthStore) -> Result<(), String> {
+        persist_auth_store(&self.path, store)
+    }
+
+    /// 持锁读取、修改并写回文件。
+    fn update(&self, apply: &mut dyn FnMut(&mut AuthStore)) -> Result<AuthStore, String> {
+        let _guard = self
+            .update_lock
+            .lock()
+            .unwrap_or_else(|err| err.into_inner());
+        let mut store = self.load()?;
+        apply(&mut store);
+        self.persist(&store)?;
+        Ok(store)
+    }
+}
+
+/// 纯内存后端：测试使用，不触碰磁盘。
+#[cfg(test)]
+#[derive(Debug, Default)]
+pub(crate) struct MemoryAuthStoreBackend {
+    /// 最近一次持久化的存储。
+    store: Mutex<Option<AuthStore>>,
+}
+
+#[cfg(test)]
+impl MemoryAuthStoreBackend {
+    /// 最近一次持久化的存储副本。
+    pub(crate) fn persisted(&self) -> Option<AuthStore> {
+        self.store
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .clone()
+    }
+}
+
+#[cfg(test)]
+impl AuthStoreBackend for MemoryAuthStoreBackend {
+    /// 后端名称。
+    fn name(&self) -> &'static str {
+        "memory"
+    }
+
+    /// 内存后端无外部位置。
+    fn describe(&self) -> String {
+        "in-memory".to_string()
+    }
+
+    /// 返回最近一次持久化的副本。
+    fn load(&self) -> Result<AuthStore, String> {
+        Ok(self
+            .persisted()
+            .unwrap_or_else(|| AuthStore::new(crate::auth::store::generate_signing_key_seed())))
+    }
+
+    /// 保存副本。
+    fn persist(&self, store: &AuthStore) -> Result<(), String> {
+        *self.store.lock().unwrap_or_else(|err| err.into_inner()) = Some(store.clone());
+        Ok(())
+    }
+
+    /// 持锁修改副本。
+    fn update(&self, apply: &mut dyn FnMut(&mut AuthStore)) -> Result<AuthStore, String> {
+        let mut guard = self.store.lock().unwrap_or_else(|err| err.into_inner());
+        let mut store = guard
+            .take()
+            .unwrap_or_else(|| AuthStore::new(crate::auth::store::generate_signing_key_seed()));
+        apply(&mut store);
+        *guard = Some(store.clone());
+        Ok(store)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use uuid::Uuid;
+
+    use super::{AuthStoreBackend, FileAuthStoreBackend, MemoryAuthStoreBackend};
+
+    /// 对同一后端依次执行持久化、加载与读改写。
+    fn exercise(backend: &dyn AuthStoreBackend) {
+        let mut store = backend.load().expect("load empty");
+        let seed = store
+            .signing_keys
+            .active()
+            .expect("active key")
+            .secret
+            .clone();
+        assert!(!seed.is_empty());
+        store.system_mut("sys_a");
+        backend.persist(&store).expect("persist");
+        let loaded = backend.load().expect("reload");
+        assert_eq!(
+            loaded.signing_keys.active().map(|key| key.secret.as_str()),
+            Some(seed.as_str())
+        );
+        assert!(loaded.systems.contains_key("sys_a"));
+
+        let updated = backend
+            .update(&mut |store| {
+                store.system_mut("sys_b");
+            })
+            .expect("update");
+        assert_eq!(updated.systems.len(), 2);
+        assert_eq!(backend.load().expect("reload").systems.len(), 2);
+    }
+
+    #[test]
+    fn file_and_memory_backends_share_load_persist_update_semantics() {
+        let dir = std::env::temp_dir().join(format!("yc-relay-backend-{}", Uuid::new_v4()));
+        let file = FileAuthStoreBackend::new(dir.join("auth-store.json"));
+        assert_eq!(
+            file.local_path(),
+            Some(dir.join("auth-store.json").as_path())
+        );
+        exercise(&file);
+        assert!(dir.join("auth-store.json").exists());
+        let _ = std::fs::remove_dir_all(dir);
+
+        let memory = MemoryAuthStoreBackend::default();
+        assert!(memory.local_path().is_none());
+        exercise(&memory);
+    }
+}
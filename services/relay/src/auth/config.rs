@@ -0,0 +1,143 @@
+//! 鉴权时效配置：启动时从环境变量读取 access/refresh TTL、PoP 时间窗与 pairToken 轮换宽限期，
+//! 并限制在安全区间内。
+
+use serde::Serialize;
+use tracing::warn;
+
+use crate::api::types::{ACCESS_TOKEN_TTL_SEC, POP_MAX_SKEW_SEC, REFRESH_TOKEN_TTL_SEC};
+
+/// access token TTL 环境变量（秒）。
+const RELAY_ACCESS_TTL_ENV: &str = "RELAY_ACCESS_TTL_SEC";
+/// refresh token TTL 环境变量（秒）。
+const RELAY_REFRESH_TTL_ENV: &str = "RELAY_REFRESH_TTL_SEC";
+/// PoP 签名时间窗环境变量（秒）。
+const RELAY_POP_SKEW_ENV: &str = "RELAY_POP_SKEW_SEC";
+/// pairToken 轮换宽限期环境变量（秒）。
+const RELAY_PAIR_TOKEN_GRACE_ENV: &str = "RELAY_PAIR_TOKEN_GRACE_SEC";
+
+/// access token TTL 允许区间：1 分钟 ~ 1 小时。
+const ACCESS_TTL_RANGE: (u64, u64) = (60, 3600);
+/// refresh token TTL 允许区间：1 小时 ~ 90 天。
+const REFRESH_TTL_RANGE: (u64, u64) = (3600, 90 * 24 * 3600);
+/// PoP 时间窗允许区间：30 秒 ~ 10 分钟。
+const POP_SKEW_RANGE: (u64, u64) = (30, 600);
+/// pairToken 轮换宽限期允许区间：0（不保留旧令牌）~ 1 天。
+const PAIR_TOKEN_GRACE_RANGE: (u64, u64) = (0, 24 * 3600);
+/// pairToken 轮换宽限期默认值：10 分钟。
+const PAIR_TOKEN_GRACE_SEC: u64 = 600;
+
+/// 鉴权时效配置（进程内只读）。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct AuthConfig {
+    /// access token 有效期（秒）。
+    pub(crate) access_ttl_sec: u64,
+    /// refresh token 有效期（秒）。
+    pub(crate) refresh_ttl_sec: u64,
+    /// PoP 签名允许的时钟偏差（秒），同时作为 nonce 防重放窗口。
+    pub(crate) pop_skew_sec: u64,
+    /// pairToken 轮换后旧令牌仍被接受的宽限期（秒）。
+    pub(crate) pair_token_grace_sec: u64,
+}
+
+impl Default for AuthConfig {
+    /// 未配置时沿用内置默认值。
+    fn default() -> Self {
+        Self {
+            access_ttl_sec: ACCESS_TOKEN_TTL_SEC,
+            refresh_ttl_sec: REFRESH_TOKEN_TTL_SEC,
+            pop_skew_sec: POP_MAX_SKEW_SEC,
+            pair_token_grace_sec: PAIR_TOKEN_GRACE_SEC,
+        }
+    }
+}
+
+impl AuthConfig {
+    /// 从环境变量读取配置；非法值回退默认，越界值截断到允许区间。
+    pub(crate) fn from_env() -> Self {
+        Self::from_lookup(|key| std::env::var(key).ok())
+    }
+
+    /// 按给定读取函数解析配置，便于测试。
+    fn from_lookup(lookup: impl Fn(&str) -> Option<String>) -> Self {
+        let defaults = Self::default();
+        let read = |key: &str, fallback: u64, (min, max): (u64, u64)| {
+            let Some(raw) = lookup(key) else {
+                return fallback;
+            };
+            let Ok(value) = raw.trim().parse::<u64>() else {
+                warn!("invalid {key}={raw}, fallback to {fallback}");
+                return fallback;
+            };
+            let clamped = value.clamp(min, max);
+            if clamped != value {
+                warn!("{key}={value} out of range [{min}, {max}], clamped to {clamped}");
+            }
+            clamped
+        };
+        Self {
+            access_ttl_sec: read(
+                RELAY_ACCESS_TTL_ENV,
+                defaults.access_ttl_sec,
+                ACCESS_TTL_RANGE,
+            ),
+            refresh_ttl_sec: read(
+                RELAY_REFRESH_TTL_ENV,
+                defaults.refresh_ttl_sec,
+                REFRESH_TTL_RANGE,
+            ),
+            pop_skew_sec: read(RELAY_POP_SKEW_ENV, defaults.pop_skew_sec, POP_SKEW_RANGE),
+            pair_token_grace_sec: read(
+                RELAY_PAIR_TOKEN_GRACE_ENV,
+                defaults.pair_token_grace_sec,
+                PAIR_TOKEN_GRACE_RANGE,
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::AuthConfig;
+
+    /// 以固定键值表解析配置。
+    fn parse(pairs: &[(&str, &str)]) -> AuthConfig {
+        let env = pairs
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect::<HashMap<_, _>>();
+        AuthConfig::from_lookup(|key| env.get(key).cloned())
+    }
+
+    #[test]
+    fn unset_or_invalid_values_use_defaults() {
+        assert_eq!(parse(&[]), AuthConfig::default());
+        assert_eq!(
+            parse(&[("RELAY_ACCESS_TTL_SEC", "ten minutes")]),
+            AuthConfig::default()
+        );
+    }
+
+    #[test]
+    fn configured_values_are_clamped_to_safe_ranges() {
+        let config = parse(&[
+            ("RELAY_ACCESS_TTL_SEC", "5"),
+            ("RELAY_REFRESH_TTL_SEC", "999999999"),
+            ("RELAY_POP_SKEW_SEC", "45"),
+            ("RELAY_PAIR_TOKEN_GRACE_SEC", "999999"),
+        ]);
+        assert_eq!(config.access_ttl_sec, 60);
+        assert_eq!(config.refresh_ttl_sec, 90 * 24 * 3600);
+        assert_eq!(config.pop_skew_sec, 45);
+        assert_eq!(config.pair_token_grace_sec, 24 * 3600);
+
+        let config = parse(&[
+            ("RELAY_ACCESS_TTL_SEC", "900"),
+            ("RELAY_REFRESH_TTL_SEC", "60"),
+        ]);
+        assert_eq!(config.access_ttl_sec, 900);
+        assert_eq!(config.refresh_ttl_sec, 3600);
+    }
+}
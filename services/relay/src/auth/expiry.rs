@@ -0,0 +1,234 @@
+//! system 自动过期清理（默认关闭）：移除超过保留期无任何活跃记录、且当前无在线连接的 system，
+//! 删除前先把被清理条目备份到认证存储同目录（非文件后端不备份），避免长期运行的 relay 累积废弃注册。
+
+use std::{
+    collections::{BTreeMap, HashSet},
+    fs,
+    path::Path,
+    time::Duration,
+};
+
+use chrono::{DateTime, Utc};
+use tokio::time::MissedTickBehavior;
+use tracing::{info, warn};
+
+use crate::{
+    api::types::{AuthStore, SystemAuthState},
+    state::AppState,
+};
+
+/// 过期清理开关环境变量（`1`/`true` 开启）。
+const RELAY_SYSTEM_EXPIRY_ENV: &str = "RELAY_SYSTEM_EXPIRY";
+/// 保留天数环境变量。
+const RELAY_SYSTEM_RETENTION_DAYS_ENV: &str = "RELAY_SYSTEM_RETENTION_DAYS";
+/// 默认保留天数（刻意宽松）。
+const DEFAULT_RETENTION_DAYS: u64 = 180;
+/// 清理周期。
+const SWEEP_INTERVAL: Duration = Duration::from_secs(3600);
+
+/// 读取保留期；未开启时返回 None。
+fn retention_from_env() -> Option<Duration> {
+    let enabled = std::env::var(RELAY_SYSTEM_EXPIRY_ENV)
+        .map(|raw| matches!(raw.trim().to_ascii_lowercase().as_str(), "1" | "true"))
+        .unwrap_or(false);
+    if !enabled {
+        return None;
+    }
+    let days = std::env::var(RELAY_SYSTEM_RETENTION_DAYS_ENV)
+        .ok()
+        .and_then(|raw| raw.trim().parse::<u64>().ok())
+        .filter(|days| *days > 0)
+        .unwrap_or(DEFAULT_RETENTION_DAYS);
+    Some(Duration::from_secs(days * 24 * 3600))
+}
+
+/// 按配置启动后台清理任务；未开启时不启动。
+pub(crate) fn spawn_system_expiry_sweeper(state: AppState) {
+    let Some(retention) = retention_from_env() else {
+        return;
+    };
+    info!(
+        "system expiry sweeper enabled retention_days={}",
+        retention.as_secs() / (24 * 3600)
+    );
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(SWEEP_INTERVAL);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Skip);
+        loop {
+            ticker.tick().await;
+            if let Err(err) = state.sweep_expired_systems(Utc::now(), retention).await {
+                warn!("sweep expired systems failed: {err}");
+            }
+        }
+    });
+}
+
+impl AppState {
+    /// 执行一次过期清理，返回被移除的 system 数量；备份失败时不做任何删除。
+    pub(crate) async fn sweep_expired_systems(
+        &self,
+        now: DateTime<Utc>,
+        retention: Duration,
+    ) -> Result<usize, String> {
+        let online = {
+            let rooms = self.systems.read().await;
+            rooms
+                .iter()
+                .filter(|(_, room)| !room.clients.is_empty())
+                .map(|(system_id, _)| system_id.clone())
+                .collect::<HashSet<_>>()
+        };
+
+        let mut store = self.auth_store.write().await;
+        let expired = select_expired_systems(&store, &online, now, retention);
+        if expired.is_empty() {
+            return Ok(0);
+        }
+
+        let removed = expired
+            .iter()
+            .filter_map(|system_id| {
+                store
+                    .system_ref(system_id)
+                    .map(|system| (system_id.clone(), system.clone()))
+            })
+            .collect::<BTreeMap<_, _>>();
+        match self.auth_backend.local_path() {
+            Some(path) => backup_expired_systems(path, now, &removed)?,
+            None => info!(
+                "auth store backend={} keeps no local copy, expired systems not backed up",
+                self.auth_backend.name()
+            ),
+        }
+        for system_id in &expired {
+            store.systems.remove(system_id);
+        }
+        self.auth_backend.persist(&store)?;
+        info!(
+            "expired systems removed count={} ids={expired:?}",
+            expired.len()
+        );
+        Ok(expired.len())
+    }
+}
+
+/// 挑选过期 system：不在线，且最近活跃时间早于保留期；无法判断活跃时间的条目一律保留。
+fn select_expired_systems(
+    store: &AuthStore,
+    online: &HashSet<String>,
+    now: DateTime<Utc>,
+    retention: Duration,
+) -> Vec<String> {
+    let Ok(retention) = chrono::Duration::from_std(retention) else {
+        return Vec::new();
+    };
+    let cutoff = now - retention;
+    let mut expired = store
+        .systems
+        .iter()
+        .filter(|(system_id, _)| !online.contains(*system_id))
+        .filter(|(_, system)| last_activity(system).is_some_and(|last| last < cutoff))
+        .map(|(system_id, _)| system_id.clone())
+        .collect::<Vec<_>>();
+    expired.sort();
+    expired
+}
+
+/// system 最近活跃时间：pairToken 更新时间与各设备最后活跃时间取最大值。
+fn last_activity(system: &SystemAuthState) -> Option<DateTime<Utc>> {
+    system
+        .devices
+        .values()
+        .map(|device| device.last_seen_at.as_str())
+        .chain(system.pair_token_updated_at.as_deref())
+        .filter_map(|raw| DateTime::parse_from_rfc3339(raw).ok())
+        .map(|value| value.with_timezone(&Utc))
+        .max()
+}
+
+/// 把待清理 system 写入 `auth-store.expired-<时间>.json` 备份。
+fn backup_expired_systems(
+    store_path: &Path,
+    now: DateTime<Utc>,
+    removed: &BTreeMap<String, SystemAuthState>,
+) -> Result<(), String> {
+    let backup = store_path.with_file_name(format!(
+        "auth-store.expired-{}.json",
+        now.format("%Y%m%dT%H%M%SZ")
+    ));
+    if let Some(parent) = backup.parent() {
+        fs::create_dir_all(parent).map_err(|err| format!("create backup dir failed: {err}"))?;
+    }
+    let encoded = serde_json::to_vec_pretty(removed)
+        .map_err(|err| format!("encode expired systems failed: {err}"))?;
+    fs::write(&backup, encoded).map_err(|err| format!("write expired systems backup failed: {err}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashSet, time::Duration};
+
+    use chrono::{DateTime, Utc};
+
+    use super::select_expired_systems;
+    use crate::{
+        api::types::{AuthStore, DeviceCredential},
+        auth::{backend::FileAuthStoreBackend, handlers::test_support::device},
+    };
+
+    #[test]
+    fn stale_system_is_expired_while_recent_and_online_ones_are_kept() {
+        let now = DateTime::parse_from_rfc3339("2026-06-01T00:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let retention = Duration::from_secs(30 * 24 * 3600);
+        let mut store = AuthStore::new("seed".to_string());
+        store.system_mut("sys_stale").pair_token_updated_at =
+            Some("2026-01-01T00:00:00Z".to_string());
+        let recent = store.system_mut("sys_recent");
+        recent.pair_token_updated_at = Some("2026-01-01T00:00:00Z".to_string());
+        recent.devices.insert(
+            "ios_1".to_string(),
+            DeviceCredential {
+                last_seen_at: "2026-05-20T00:00:00Z".to_string(),
+                ..device("ios_1", "kid_1", "pk", "ACTIVE")
+            },
+        );
+        store.system_mut("sys_online").pair_token_updated_at =
+            Some("2025-01-01T00:00:00Z".to_string());
+        store.system_mut("sys_unknown");
+
+        let online = HashSet::from(["sys_online".to_string()]);
+        let expired = select_expired_systems(&store, &online, now, retention);
+        assert_eq!(expired, vec!["sys_stale".to_string()]);
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn sweep_backs_up_then_removes_stale_systems() {
+        let mut dir = std::env::temp_dir();
+        dir.push(format!("yc_relay_expiry_test_{}", uuid::Uuid::new_v4()));
+        let mut state = crate::state::AppState::for_test();
+        state.auth_backend =
+            std::sync::Arc::new(FileAuthStoreBackend::new(dir.join("auth-store.json")));
+        state
+            .auth_store
+            .write()
+            .await
+            .system_mut("sys_stale")
+            .pair_token_updated_at = Some("2020-01-01T00:00:00Z".to_string());
+
+        let removed = state
+            .sweep_expired_systems(Utc::now(), Duration::from_secs(24 * 3600))
+            .await
+            .expect("sweep should succeed");
+        assert_eq!(removed, 1);
+        assert!(state.auth_store.read().await.systems.is_empty());
+        let backups = std::fs::read_dir(&dir)
+            .expect("read store dir")
+            .filter_map(Result::ok)
+            .filter(|entry| entry.file_name().to_string_lossy().contains("expired"))
+            .count();
+        assert_eq!(backups, 1);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
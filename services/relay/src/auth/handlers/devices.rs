@@ -5,7 +5,7 @@ use axum::http::StatusCode;
 use crate::{
     api::{
         error::ApiError,
-        types::{AuthDevicesData, AuthDevicesQuery, DeviceEntry},
+        types::{AuthDevicesData, AuthDevicesQuery, DEVICES_PAGE_MAX, DeviceEntry},
     },
     auth::pop::{auth_list_payload, parse_ts, verify_ts_window},
     state::AppState,
@@ -30,10 +30,18 @@ impl AppState {
         }
 
         let ts = parse_ts(&req.ts, "ACCESS_SIGNATURE_EXPIRED", "签名时间戳无效")?;
-        verify_ts_window(ts, "ACCESS_SIGNATURE_EXPIRED", "签名时间窗已过期")?;
+        verify_ts_window(
+            ts,
+            self.auth_config.pop_skew_sec,
+            "ACCESS_SIGNATURE_EXPIRED",
+            "签名时间窗已过期",
+        )?;
         self.consume_auth_nonce("devices", &req.nonce, ts).await?;
 
-        let payload = auth_list_payload(system_id, device_id, key_id, ts, &req.nonce);
+        let cursor = req.cursor.as_deref().map(str::trim);
+        let payload = auth_list_payload(
+            system_id, device_id, key_id, ts, &req.nonce, req.limit, cursor,
+        );
         self.verify_access_http(
             system_id,
             device_id,
@@ -58,17 +66,85 @@ impl AppState {
             .devices
             .values()
             .cloned()
-            .map(|item| DeviceEntry {
-                device_id: item.device_id,
-                device_name: item.device_name,
-                key_id: item.key_id,
-                status: item.status,
-                created_at: item.created_at,
-                last_seen_at: item.last_seen_at,
-                revoked_at: item.revoked_at,
-            })
+            .map(DeviceEntry::from)
             .collect::<Vec<_>>();
         devices.sort_by(|a, b| a.device_id.cmp(&b.device_id));
-        Ok(AuthDevicesData { devices })
+        Ok(paginate_devices(devices, req.limit, cursor))
+    }
+}
+
+/// 按 deviceId 游标分页：返回游标之后的至多 `limit` 条，仍有剩余时给出下一页游标。
+fn paginate_devices(
+    sorted_devices: Vec<DeviceEntry>,
+    limit: Option<usize>,
+    cursor: Option<&str>,
+) -> AuthDevicesData {
+    let limit = limit.unwrap_or(DEVICES_PAGE_MAX).clamp(1, DEVICES_PAGE_MAX);
+    let mut remaining = sorted_devices
+        .into_iter()
+        .filter(|item| cursor.is_none_or(|cursor| item.device_id.as_str() > cursor))
+        .peekable();
+    let devices = remaining.by_ref().take(limit).collect::<Vec<_>>();
+    let next_cursor = remaining
+        .peek()
+        .and(devices.last())
+        .map(|item| item.device_id.clone());
+    AuthDevicesData {
+        devices,
+        next_cursor,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::paginate_devices;
+    use crate::api::types::{DEVICES_PAGE_MAX, DeviceEntry};
+
+    fn devices(count: usize) -> Vec<DeviceEntry> {
+        (0..count)
+            .map(|index| DeviceEntry {
+                device_id: format!("ios_{index:03}"),
+                device_name: String::new(),
+                key_id: String::new(),
+                status: "ACTIVE".to_string(),
+                created_at: String::new(),
+                last_seen_at: String::new(),
+                revoked_at: None,
+                last_auth_failure: None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn paging_walks_whole_device_set() {
+        let mut seen = Vec::new();
+        let mut cursor = None::<String>;
+        loop {
+            let page = paginate_devices(devices(5), Some(2), cursor.as_deref());
+            assert!(page.devices.len() <= 2);
+            seen.extend(page.devices.into_iter().map(|item| item.device_id));
+            match page.next_cursor {
+                Some(next) => cursor = Some(next),
+                None => break,
+            }
+        }
+        assert_eq!(
+            seen,
+            ["ios_000", "ios_001", "ios_002", "ios_003", "ios_004"]
+        );
+    }
+
+    #[test]
+    fn default_page_is_capped_without_cursor_on_last_page() {
+        let page = paginate_devices(devices(3), None, None);
+        assert_eq!(page.devices.len(), 3);
+        assert!(page.next_cursor.is_none());
+
+        let page = paginate_devices(devices(DEVICES_PAGE_MAX + 1), Some(usize::MAX), None);
+        assert_eq!(page.devices.len(), DEVICES_PAGE_MAX);
+        assert_eq!(
+            page.next_cursor.as_deref(),
+            Some(format!("ios_{:03}", DEVICES_PAGE_MAX - 1).as_str())
+        );
     }
 }
@@ -0,0 +1,205 @@
+//! 认证状态导出/导入：供多 relay 同步与审计，只交换哈希与非敏感元数据。
+
+use axum::http::StatusCode;
+use tracing::info;
+
+use crate::{
+    api::{
+        error::ApiError,
+        types::{AdminImportData, AuthStore, AuthStoreExport, SystemAuthState, SystemExportEntry},
+    },
+    state::AppState,
+};
+
+/// 当前导出格式版本。
+const AUTH_EXPORT_VERSION: u32 = 1;
+
+impl AppState {
+    /// 导出认证状态快照。
+    pub(crate) async fn export_auth_store(&self) -> AuthStoreExport {
+        let store = self.auth_store.read().await;
+        build_auth_export(&store, yc_shared_protocol::now_rfc3339_nanos())
+    }
+
+    /// 导入认证状态快照：只补充缺失的 system 与设备，已有记录保持不变。
+    pub(crate) async fn import_auth_store(
+        &self,
+        snapshot: &AuthStoreExport,
+    ) -> Result<AdminImportData, ApiError> {
+        if snapshot.version != AUTH_EXPORT_VERSION {
+            return Err(ApiError::new(
+                StatusCode::BAD_REQUEST,
+                "EXPORT_VERSION_UNSUPPORTED",
+                format!("不支持的导出版本：{}", snapshot.version),
+                "请使用相同版本 relay 导出的快照",
+            ));
+        }
+
+        let mut store = self.auth_store.write().await;
+        let result = merge_auth_export(&mut store, snapshot);
+        if result.systems_added > 0 || result.devices_added > 0 {
+            self.auth_backend.persist(&store).map_err(|err| {
+                ApiError::new(
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "INTERNAL_ERROR",
+                    err,
+                    "请稍后重试",
+                )
+            })?;
+        }
+        info!(
+            "auth store imported systems_added={} devices_added={} devices_skipped={}",
+            result.systems_added, result.devices_added, result.devices_skipped
+        );
+        Ok(result)
+    }
+}
+
+/// 由认证存储构造导出快照；system 与设备按 id 排序，便于比对。
+fn build_auth_export(store: &AuthStore, exported_at: String) -> AuthStoreExport {
+    let mut systems = store
+        .systems
+        .iter()
+        .map(|(system_id, system)| {
+            let mut devices = system.devices.values().cloned().collect::<Vec<_>>();
+            devices.sort_by(|a, b| a.device_id.cmp(&b.device_id));
+            SystemExportEntry {
+                system_id: system_id.clone(),
+                pair_token_hash: system.pair_token_hash.clone(),
+                pair_token_updated_at: system.pair_token_updated_at.clone(),
+                features: system.features,
+                devices,
+            }
+        })
+        .collect::<Vec<_>>();
+    systems.sort_by(|a, b| a.system_id.cmp(&b.system_id));
+    AuthStoreExport {
+        version: AUTH_EXPORT_VERSION,
+        exported_at,
+        systems,
+    }
+}
+
+/// 合并导出快照：缺失的 system 连同策略与 pairToken 哈希一并补充；
+/// 已存在的设备不覆盖（保留本地公钥与吊销状态），重复导入结果不变。
+fn merge_auth_export(store: &mut AuthStore, snapshot: &AuthStoreExport) -> AdminImportData {
+    let mut result = AdminImportData::default();
+    for entry in &snapshot.systems {
+        let system_id = entry.system_id.trim();
+        if system_id.is_empty() {
+            continue;
+        }
+        let system = store
+            .systems
+            .entry(system_id.to_string())
+            .or_insert_with(|| {
+                result.systems_added += 1;
+                SystemAuthState {
+                    pair_token_hash: entry.pair_token_hash.clone(),
+                    pair_token_updated_at: entry.pair_token_updated_at.clone(),
+                    features: entry.features,
+                    ..SystemAuthState::default()
+                }
+            });
+        for device in &entry.devices {
+            let device_id = device.device_id.trim();
+            if device_id.is_empty() || system.devices.contains_key(device_id) {
+                result.devices_skipped += 1;
+                continue;
+            }
+            system.devices.insert(device_id.to_string(), device.clone());
+            result.devices_added += 1;
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{build_auth_export, merge_auth_export};
+    use crate::{
+        api::types::{AuthStore, RefreshSession},
+        auth::handlers::test_support::device,
+    };
+
+    fn source_store() -> AuthStore {
+        let mut store = AuthStore::new("signing_seed_secret".to_string());
+        let system = store.system_mut("sys_a");
+        system.pair_token_hash = Some("pair_hash".to_string());
+        system.devices.insert(
+            "ios_1".to_string(),
+            device("ios_1", "kid_ios_1", "pk_remote", "ACTIVE"),
+        );
+        system.refresh_sessions.insert(
+            "rs_1".to_string(),
+            RefreshSession {
+                session_id: "rs_1".to_string(),
+                system_id: "sys_a".to_string(),
+                device_id: "ios_1".to_string(),
+                key_id: "kid_ios_1".to_string(),
+                credential_id: "cred_1".to_string(),
+                refresh_secret_hash: "refresh_hash_secret".to_string(),
+                expires_at: 0,
+                created_at: String::new(),
+                revoked_at: None,
+                rotated_from: None,
+            },
+        );
+        store
+    }
+
+    #[test]
+    fn export_contains_no_secret_material() {
+        let export = build_auth_export(&source_store(), "now".to_string());
+        let raw = serde_json::to_string(&export).expect("encode export");
+        assert!(raw.contains("pair_hash"));
+        assert!(raw.contains("pk_remote"));
+        assert!(!raw.contains("signing_seed_secret"));
+        assert!(!raw.contains("signingKey"));
+        assert!(!raw.contains("refresh_hash_secret"));
+        assert!(!raw.contains("refreshSessions"));
+    }
+
+    #[test]
+    fn import_merges_devices_idempotently_without_overwriting_keys() {
+        let mut snapshot = build_auth_export(&source_store(), "now".to_string());
+        snapshot.systems[0]
+            .devices
+            .push(device("android_2", "kid_android_2", "pk_new", "ACTIVE"));
+
+        let mut target = AuthStore::new("target_seed".to_string());
+        target.system_mut("sys_a").devices.insert(
+            "ios_1".to_string(),
+            device("ios_1", "kid_ios_1", "pk_local", "ACTIVE"),
+        );
+
+        let first = merge_auth_export(&mut target, &snapshot);
+        assert_eq!(first.systems_added, 0);
+        assert_eq!(first.devices_added, 1);
+        assert_eq!(first.devices_skipped, 1);
+        let second = merge_auth_export(&mut target, &snapshot);
+        assert_eq!(second.devices_added, 0);
+        assert_eq!(second.devices_skipped, 2);
+
+        let system = target.system_ref("sys_a").expect("system kept");
+        assert_eq!(system.devices["ios_1"].public_key, "pk_local");
+        assert_eq!(system.devices["android_2"].public_key, "pk_new");
+        assert_eq!(
+            target.signing_keys.active().map(|key| key.secret.as_str()),
+            Some("target_seed")
+        );
+        assert!(system.pair_token_hash.is_none());
+
+        let mut empty = AuthStore::new("other_seed".to_string());
+        let added = merge_auth_export(&mut empty, &snapshot);
+        assert_eq!(added.systems_added, 1);
+        assert_eq!(
+            empty
+                .system_ref("sys_a")
+                .unwrap()
+                .pair_token_hash
+                .as_deref(),
+            Some("pair_hash")
+        );
+    }
+}
@@ -0,0 +1,127 @@
+//! system 策略开关管理逻辑。
+
+use axum::http::StatusCode;
+use tracing::info;
+
+use crate::{
+    api::{
+        error::ApiError,
+        types::{AdminSystemFeaturesData, AdminSystemFeaturesRequest},
+    },
+    auth::token::pair_token_hash_matches,
+    pairing::rate_limit::PairClientIp,
+    state::AppState,
+};
+
+impl AppState {
+    /// 更新 system 策略开关：调用方需持有该 system 当前的 sidecar pairToken，
+    /// 与其他 pairToken 接口共用配对限流桶。
+    pub(crate) async fn update_system_features(
+        &self,
+        req: &AdminSystemFeaturesRequest,
+        client_ip: PairClientIp,
+    ) -> Result<AdminSystemFeaturesData, ApiError> {
+        let system_id = req.system_id.trim();
+        let pair_token = req.pair_token.trim();
+        if system_id.is_empty() || pair_token.is_empty() {
+            return Err(ApiError::new(
+                StatusCode::BAD_REQUEST,
+                "MISSING_CREDENTIALS",
+                "systemId/pairToken 不能为空",
+                "请检查输入后重试",
+            ));
+        }
+        self.check_pair_rate(client_ip, system_id)?;
+
+        let mut store = self.auth_store.write().await;
+        let Some(system) = store.systems.get_mut(system_id) else {
+            return Err(ApiError::new(
+                StatusCode::NOT_FOUND,
+                "SYSTEM_NOT_REGISTERED",
+                "system 不存在",
+                "请先启动 sidecar",
+            ));
+        };
+        if !pair_token_hash_matches(system.pair_token_hash.as_deref(), pair_token) {
+            return Err(ApiError::new(
+                StatusCode::UNAUTHORIZED,
+                "PAIR_TOKEN_MISMATCH",
+                "pairToken 不匹配",
+                "请使用宿主机当前 pairToken",
+            ));
+        }
+
+        if let Some(value) = req.require_access_token {
+            system.features.require_access_token = value;
+        }
+        if let Some(value) = req.allow_ticket_pairing {
+            system.features.allow_ticket_pairing = value;
+        }
+        let features = system.features;
+        self.auth_backend.persist(&store).map_err(|err| {
+            ApiError::new(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "INTERNAL_ERROR",
+                err,
+                "请稍后重试",
+            )
+        })?;
+        info!(
+            "system features updated system_id={system_id} require_access_token={} allow_ticket_pairing={}",
+            features.require_access_token, features.allow_ticket_pairing
+        );
+
+        Ok(AdminSystemFeaturesData {
+            system_id: system_id.to_string(),
+            features,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::IpAddr;
+
+    use ed25519_dalek::SigningKey;
+
+    use crate::{
+        api::types::AdminSystemFeaturesRequest,
+        auth::{handlers::test_support::state_with_devices, token::sha256_hex},
+        pairing::rate_limit::PairClientIp,
+    };
+
+    /// 构造关闭票据配对的策略更新请求。
+    fn features_request(pair_token: &str) -> AdminSystemFeaturesRequest {
+        AdminSystemFeaturesRequest {
+            system_id: "sys_a".to_string(),
+            pair_token: pair_token.to_string(),
+            require_access_token: None,
+            allow_ticket_pairing: Some(false),
+        }
+    }
+
+    #[tokio::test]
+    async fn wrong_pair_token_is_rejected_then_rate_limited() {
+        let state = state_with_devices(&SigningKey::from_bytes(&[7_u8; 32]), &[], |system| {
+            system.pair_token_hash = Some(sha256_hex("pt_a"));
+        });
+        let owner = PairClientIp(Some(IpAddr::from([198, 51, 100, 1])));
+        let data = state
+            .update_system_features(&features_request("pt_a"), owner)
+            .await
+            .expect("current pairToken updates features");
+        assert!(!data.features.allow_ticket_pairing);
+
+        let attacker = PairClientIp(Some(IpAddr::from([203, 0, 113, 7])));
+        let mut codes = Vec::new();
+        for _ in 0..6 {
+            let err = state
+                .update_system_features(&features_request("pt_guess"), attacker)
+                .await
+                .expect_err("wrong pairToken is rejected");
+            codes.push(err.code);
+        }
+        assert!(codes[..5].iter().all(|code| *code == "PAIR_TOKEN_MISMATCH"));
+        assert_eq!(codes[5], "PAIR_RATE_LIMITED");
+    }
+}
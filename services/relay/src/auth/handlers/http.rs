@@ -3,17 +3,21 @@
 use axum::{
     Json,
     extract::{Query, State},
-    http::StatusCode,
+    http::{HeaderMap, StatusCode},
 };
 
 use crate::{
     api::{
         response::{ApiEnvelope, ok_response},
         types::{
-            AuthDevicesData, AuthDevicesQuery, AuthRefreshData, AuthRefreshRequest,
-            AuthRevokeDeviceData, AuthRevokeDeviceRequest,
+            AdminImportData, AdminSystemFeaturesData, AdminSystemFeaturesRequest, AuthDevicesData,
+            AuthDevicesQuery, AuthRefreshData, AuthRefreshRequest, AuthRenameDeviceRequest,
+            AuthRevokeAllData, AuthRevokeAllRequest, AuthRevokeDeviceData, AuthRevokeDeviceRequest,
+            AuthStoreExport, DeviceEntry,
         },
     },
+    auth::admin::verify_admin_token,
+    pairing::rate_limit::PairClientIp,
     state::AppState,
 };
 
@@ -39,6 +43,7 @@ pub(crate) async fn auth_refresh_handler(
                     message: body.0.message,
                     suggestion: body.0.suggestion,
                     data: None,
+                    trace_id: body.0.trace_id,
                 }),
             )
         }
@@ -67,6 +72,65 @@ pub(crate) async fn auth_revoke_device_handler(
                     message: body.0.message,
                     suggestion: body.0.suggestion,
                     data: None,
+                    trace_id: body.0.trace_id,
+                }),
+            )
+        }
+    }
+}
+
+/// 批量吊销接口。
+pub(crate) async fn auth_revoke_all_handler(
+    State(state): State<AppState>,
+    Json(req): Json<AuthRevokeAllRequest>,
+) -> (StatusCode, Json<ApiEnvelope<AuthRevokeAllData>>) {
+    match state.revoke_all_devices(&req).await {
+        Ok(data) => ok_response(
+            StatusCode::OK,
+            "设备已批量吊销",
+            "被吊销设备需重新配对",
+            Some(data),
+        ),
+        Err(err) => {
+            let (status, body) = err.into_response();
+            (
+                status,
+                Json(ApiEnvelope {
+                    ok: body.0.ok,
+                    code: body.0.code,
+                    message: body.0.message,
+                    suggestion: body.0.suggestion,
+                    data: None,
+                    trace_id: body.0.trace_id,
+                }),
+            )
+        }
+    }
+}
+
+/// 设备改名接口。
+pub(crate) async fn auth_rename_device_handler(
+    State(state): State<AppState>,
+    Json(req): Json<AuthRenameDeviceRequest>,
+) -> (StatusCode, Json<ApiEnvelope<DeviceEntry>>) {
+    match state.rename_device(&req).await {
+        Ok(data) => ok_response(
+            StatusCode::OK,
+            "设备已改名",
+            "设备列表将展示新名称",
+            Some(data),
+        ),
+        Err(err) => {
+            let (status, body) = err.into_response();
+            (
+                status,
+                Json(ApiEnvelope {
+                    ok: body.0.ok,
+                    code: body.0.code,
+                    message: body.0.message,
+                    suggestion: body.0.suggestion,
+                    data: None,
+                    trace_id: body.0.trace_id,
                 }),
             )
         }
@@ -95,6 +159,100 @@ pub(crate) async fn auth_devices_handler(
                     message: body.0.message,
                     suggestion: body.0.suggestion,
                     data: None,
+                    trace_id: body.0.trace_id,
+                }),
+            )
+        }
+    }
+}
+
+/// system 策略开关管理接口（按来源 IP 与 system 限流，防止暴力猜测 pairToken）。
+pub(crate) async fn admin_system_features_handler(
+    State(state): State<AppState>,
+    client_ip: PairClientIp,
+    Json(req): Json<AdminSystemFeaturesRequest>,
+) -> (StatusCode, Json<ApiEnvelope<AdminSystemFeaturesData>>) {
+    match state.update_system_features(&req, client_ip).await {
+        Ok(data) => ok_response(
+            StatusCode::OK,
+            "system 策略已更新",
+            "新策略对后续连接与配对生效",
+            Some(data),
+        ),
+        Err(err) => {
+            let (status, body) = err.into_response();
+            (
+                status,
+                Json(ApiEnvelope {
+                    ok: body.0.ok,
+                    code: body.0.code,
+                    message: body.0.message,
+                    suggestion: body.0.suggestion,
+                    data: None,
+                    trace_id: body.0.trace_id,
+                }),
+            )
+        }
+    }
+}
+
+/// 认证状态导出接口（运维管理令牌鉴权）。
+pub(crate) async fn admin_export_handler(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> (StatusCode, Json<ApiEnvelope<AuthStoreExport>>) {
+    match verify_admin_token(&headers) {
+        Ok(()) => ok_response(
+            StatusCode::OK,
+            "认证状态导出成功",
+            "快照不含签名种子与 refresh 会话",
+            Some(state.export_auth_store().await),
+        ),
+        Err(err) => {
+            let (status, body) = err.into_response();
+            (
+                status,
+                Json(ApiEnvelope {
+                    ok: body.0.ok,
+                    code: body.0.code,
+                    message: body.0.message,
+                    suggestion: body.0.suggestion,
+                    data: None,
+                    trace_id: body.0.trace_id,
+                }),
+            )
+        }
+    }
+}
+
+/// 认证状态导入接口（运维管理令牌鉴权）。
+pub(crate) async fn admin_import_handler(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(req): Json<AuthStoreExport>,
+) -> (StatusCode, Json<ApiEnvelope<AdminImportData>>) {
+    let result = match verify_admin_token(&headers) {
+        Ok(()) => state.import_auth_store(&req).await,
+        Err(err) => Err(err),
+    };
+    match result {
+        Ok(data) => ok_response(
+            StatusCode::OK,
+            "认证状态导入成功",
+            "已有设备保持不变，仅补充缺失记录",
+            Some(data),
+        ),
+        Err(err) => {
+            let (status, body) = err.into_response();
+            (
+                status,
+                Json(ApiEnvelope {
+                    ok: body.0.ok,
+                    code: body.0.code,
+                    message: body.0.message,
+                    suggestion: body.0.suggestion,
+                    data: None,
+                    trace_id: body.0.trace_id,
                 }),
             )
         }
@@ -1,9 +1,19 @@
 //! 鉴权 HTTP 接口处理模块。
 
 mod devices;
+mod export;
+mod features;
 mod http;
 mod refresh;
+mod rename;
 mod revoke;
+mod revoke_all;
+#[cfg(test)]
+pub(crate) mod test_support;
 mod verify;
 
-pub(crate) use http::{auth_devices_handler, auth_refresh_handler, auth_revoke_device_handler};
+pub(crate) use http::{
+    admin_export_handler, admin_import_handler, admin_system_features_handler,
+    auth_devices_handler, auth_refresh_handler, auth_rename_device_handler,
+    auth_revoke_all_handler, auth_revoke_device_handler,
+};
@@ -1,15 +1,16 @@
 //! 设备凭证刷新逻辑。
 
 use axum::http::StatusCode;
+use tracing::warn;
 
 use crate::{
     api::{
         error::ApiError,
-        types::{AuthRefreshData, AuthRefreshRequest},
+        types::{AuthRefreshData, AuthRefreshRequest, SystemAuthState},
     },
     auth::{
+        audit::{AuditAction, AuditRecord},
         pop::{auth_refresh_payload, parse_ts, verify_ts_window},
-        store::persist_auth_store,
         token::{
             issue_access_token, issue_refresh_session, parse_refresh_token, sha256_hex,
             verify_pop_signature,
@@ -19,11 +20,27 @@ use crate::{
 };
 
 impl AppState {
-    /// 刷新设备凭证（轮换 refresh）。
+    /// 刷新设备凭证（轮换 refresh），结果写入审计日志。
     pub(crate) async fn refresh_device_credential(
         &self,
         req: &AuthRefreshRequest,
     ) -> Result<AuthRefreshData, ApiError> {
+        let result = self.apply_refresh(req).await;
+        self.audit.record_result(
+            AuditRecord {
+                action: AuditAction::Refresh,
+                system_id: req.system_id.trim(),
+                device_id: req.device_id.trim(),
+                key_id: req.key_id.trim(),
+                target_device_id: None,
+            },
+            &result,
+        );
+        result
+    }
+
+    /// 刷新主流程。
+    async fn apply_refresh(&self, req: &AuthRefreshRequest) -> Result<AuthRefreshData, ApiError> {
         let system_id = req.system_id.trim();
         let device_id = req.device_id.trim();
         let key_id = req.key_id.trim();
@@ -37,13 +54,18 @@ impl AppState {
         }
 
         let ts = parse_ts(&req.ts, "ACCESS_SIGNATURE_EXPIRED", "签名时间戳无效")?;
-        verify_ts_window(ts, "ACCESS_SIGNATURE_EXPIRED", "签名时间窗已过期")?;
+        verify_ts_window(
+            ts,
+            self.auth_config.pop_skew_sec,
+            "ACCESS_SIGNATURE_EXPIRED",
+            "签名时间窗已过期",
+        )?;
         self.consume_auth_nonce("refresh", &req.nonce, ts).await?;
 
         let payload = auth_refresh_payload(system_id, device_id, key_id, ts, &req.nonce);
         let (session_id, refresh_secret) = parse_refresh_token(&req.refresh_token)?;
         let mut store = self.auth_store.write().await;
-        let signing_key = store.signing_key.clone();
+        let signing_keys = store.signing_keys.clone();
         let Some(system) = store.systems.get_mut(system_id) else {
             return Err(ApiError::new(
                 StatusCode::UNAUTHORIZED,
@@ -62,29 +84,43 @@ impl AppState {
             ));
         };
 
-        if old_session.revoked_at.is_some() {
+        // 先校验密钥：仅凭 sessionId 无法触发下方的整链吊销。
+        let hash = sha256_hex(&refresh_secret);
+        if hash != old_session.refresh_secret_hash {
             return Err(ApiError::new(
                 StatusCode::UNAUTHORIZED,
                 "REFRESH_TOKEN_INVALID",
-                "refreshToken 已失效",
+                "refreshToken 校验失败",
                 "请重新配对",
             ));
         }
-        if old_session.expires_at <= crate::auth::store::unix_now() {
+        if old_session.revoked_at.is_some() {
+            // 已轮换的 refreshToken 被再次出示：视为泄露，吊销其后整条轮换链并标记设备。
+            let owner_device_id = old_session.device_id.clone();
+            let revoked = revoke_refresh_family(system, &session_id);
+            let compromised = mark_device_compromised(system, &owner_device_id);
+            warn!(
+                concat!(
+                    "refresh token reuse detected system={} device={} session={} ",
+                    "revoked_sessions={} device_compromised={}"
+                ),
+                system_id, owner_device_id, session_id, revoked, compromised
+            );
+            if let Err(err) = self.auth_backend.persist(&store) {
+                warn!("persist auth store after refresh reuse failed: {err}");
+            }
             return Err(ApiError::new(
                 StatusCode::UNAUTHORIZED,
-                "REFRESH_TOKEN_EXPIRED",
-                "refreshToken 已过期",
-                "请重新配对",
+                "REFRESH_TOKEN_REUSED",
+                "refreshToken 被重复使用，该设备凭证已全部失效",
+                "凭证可能已泄露，请重新配对",
             ));
         }
-
-        let hash = sha256_hex(&refresh_secret);
-        if hash != old_session.refresh_secret_hash {
+        if old_session.expires_at <= crate::auth::store::unix_now() {
             return Err(ApiError::new(
                 StatusCode::UNAUTHORIZED,
-                "REFRESH_TOKEN_INVALID",
-                "refreshToken 校验失败",
+                "REFRESH_TOKEN_EXPIRED",
+                "refreshToken 已过期",
                 "请重新配对",
             ));
         }
@@ -120,20 +156,25 @@ impl AppState {
         let rotated_from = Some(old_session.session_id.clone());
 
         let access_token = issue_access_token(
-            &signing_key,
+            &signing_keys,
             system_id,
             device_id,
             key_id,
-            crate::api::types::ACCESS_TOKEN_TTL_SEC,
+            self.auth_config.access_ttl_sec,
         )?;
-        let (refresh_token, mut new_session) =
-            issue_refresh_session(system_id, device_id, key_id, &credential_id);
+        let (refresh_token, mut new_session) = issue_refresh_session(
+            system_id,
+            device_id,
+            key_id,
+            &credential_id,
+            self.auth_config.refresh_ttl_sec,
+        );
         new_session.rotated_from = rotated_from;
         system
             .refresh_sessions
             .insert(new_session.session_id.clone(), new_session);
 
-        persist_auth_store(&self.auth_store_path, &store).map_err(|err| {
+        self.auth_backend.persist(&store).map_err(|err| {
             ApiError::new(
                 StatusCode::INTERNAL_SERVER_ERROR,
                 "INTERNAL_ERROR",
@@ -142,13 +183,136 @@ impl AppState {
             )
         })?;
 
+        self.metrics.record_refresh_rotation();
         Ok(AuthRefreshData {
             access_token,
             refresh_token,
             key_id: key_id.to_string(),
             credential_id,
-            access_expires_in_sec: crate::api::types::ACCESS_TOKEN_TTL_SEC,
-            refresh_expires_in_sec: crate::api::types::REFRESH_TOKEN_TTL_SEC,
+            access_expires_in_sec: self.auth_config.access_ttl_sec,
+            refresh_expires_in_sec: self.auth_config.refresh_ttl_sec,
         })
     }
 }
+
+/// 沿 `rotated_from` 链吊销 `root` 之后轮换出的全部会话，返回本次新吊销的数量。
+fn revoke_refresh_family(system: &mut SystemAuthState, root: &str) -> usize {
+    let now = yc_shared_protocol::now_rfc3339_nanos();
+    let mut revoked = 0;
+    let mut frontier = vec![root.to_string()];
+    while let Some(parent) = frontier.pop() {
+        for session in system.refresh_sessions.values_mut() {
+            if session.rotated_from.as_deref() != Some(parent.as_str()) {
+                continue;
+            }
+            frontier.push(session.session_id.clone());
+            if session.revoked_at.is_none() {
+                session.revoked_at = Some(now.clone());
+                revoked += 1;
+            }
+        }
+    }
+    revoked
+}
+
+/// 将仍处于 ACTIVE 的设备标记为 COMPROMISED（已吊销设备保持原状态），返回是否发生变更。
+fn mark_device_compromised(system: &mut SystemAuthState, device_id: &str) -> bool {
+    let Some(device) = system.devices.get_mut(device_id) else {
+        return false;
+    };
+    if device.status != "ACTIVE" {
+        return false;
+    }
+    device.status = "COMPROMISED".to_string();
+    device.revoked_at = Some(yc_shared_protocol::now_rfc3339_nanos());
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use base64::{Engine as _, engine::general_purpose::URL_SAFE_NO_PAD};
+    use ed25519_dalek::{Signer, SigningKey};
+
+    use crate::{
+        api::types::AuthRefreshRequest,
+        auth::{
+            handlers::test_support::state_with_devices, pop::auth_refresh_payload, store::unix_now,
+            token::issue_refresh_session,
+        },
+        state::AppState,
+    };
+
+    /// 构造带已登记设备与首个 refresh 会话的状态，返回状态与首个 refreshToken。
+    fn state_with_session(device_key: &SigningKey) -> (AppState, String) {
+        let mut token = String::new();
+        let state = state_with_devices(device_key, &[("ios_a", "kid_a")], |system| {
+            let (issued, session) = issue_refresh_session("sys_a", "ios_a", "kid_a", "crd_a", 3600);
+            token = issued;
+            system
+                .refresh_sessions
+                .insert(session.session_id.clone(), session);
+        });
+        (state, token)
+    }
+
+    /// 构造带 PoP 签名的刷新请求。
+    fn refresh_request(
+        device_key: &SigningKey,
+        refresh_token: &str,
+        nonce: &str,
+    ) -> AuthRefreshRequest {
+        let ts = unix_now();
+        let payload = auth_refresh_payload("sys_a", "ios_a", "kid_a", ts, nonce);
+        AuthRefreshRequest {
+            system_id: "sys_a".to_string(),
+            device_id: "ios_a".to_string(),
+            refresh_token: refresh_token.to_string(),
+            key_id: "kid_a".to_string(),
+            ts: ts.to_string(),
+            nonce: nonce.to_string(),
+            sig: URL_SAFE_NO_PAD.encode(device_key.sign(payload.as_bytes()).to_bytes()),
+        }
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn replayed_rotated_token_revokes_family_and_device() {
+        let device_key = SigningKey::from_bytes(&[9u8; 32]);
+        let (state, first_token) = state_with_session(&device_key);
+
+        let mut token = first_token.clone();
+        for round in 0..3 {
+            let nonce = format!("nonce_{round}");
+            token = state
+                .refresh_device_credential(&refresh_request(&device_key, &token, &nonce))
+                .await
+                .expect("rotation should succeed")
+                .refresh_token;
+        }
+
+        let err = state
+            .refresh_device_credential(&refresh_request(&device_key, &first_token, "nonce_replay"))
+            .await
+            .expect_err("replayed token should be rejected");
+        assert_eq!(err.code, "REFRESH_TOKEN_REUSED");
+
+        {
+            let store = state.auth_store.read().await;
+            let system = &store.systems["sys_a"];
+            assert_eq!(system.refresh_sessions.len(), 4);
+            assert!(
+                system
+                    .refresh_sessions
+                    .values()
+                    .all(|session| session.revoked_at.is_some())
+            );
+            assert_eq!(system.devices["ios_a"].status, "COMPROMISED");
+        }
+
+        // 最新一代 token 也随整链失效。
+        let err = state
+            .refresh_device_credential(&refresh_request(&device_key, &token, "nonce_latest"))
+            .await
+            .expect_err("latest token should be revoked");
+        assert_eq!(err.code, "REFRESH_TOKEN_REUSED");
+    }
+}
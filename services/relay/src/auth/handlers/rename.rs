@@ -0,0 +1,236 @@
+//! 设备改名逻辑。
+
+use axum::http::StatusCode;
+
+use crate::{
+    api::{
+        error::ApiError,
+        types::{AuthRenameDeviceRequest, DeviceEntry},
+    },
+    auth::{
+        audit::{AuditAction, AuditRecord},
+        pop::{auth_rename_payload, parse_ts, verify_ts_window},
+    },
+    pairing::handlers::normalize_device_name,
+    state::AppState,
+};
+
+impl AppState {
+    /// 修改指定设备的展示名称；新名称为空时回退为目标 deviceId，结果写入审计日志。
+    pub(crate) async fn rename_device(
+        &self,
+        req: &AuthRenameDeviceRequest,
+    ) -> Result<DeviceEntry, ApiError> {
+        let result = self.apply_rename(req).await;
+        self.audit.record_result(
+            AuditRecord {
+                action: AuditAction::Rename,
+                system_id: req.system_id.trim(),
+                device_id: req.device_id.trim(),
+                key_id: req.key_id.trim(),
+                target_device_id: Some(req.target_device_id.trim()),
+            },
+            &result,
+        );
+        result
+    }
+
+    /// 改名主流程。
+    async fn apply_rename(&self, req: &AuthRenameDeviceRequest) -> Result<DeviceEntry, ApiError> {
+        let system_id = req.system_id.trim();
+        let device_id = req.device_id.trim();
+        let key_id = req.key_id.trim();
+        let target_device_id = req.target_device_id.trim();
+        let new_name = req.new_name.trim();
+        if system_id.is_empty()
+            || device_id.is_empty()
+            || key_id.is_empty()
+            || target_device_id.is_empty()
+        {
+            return Err(ApiError::new(
+                StatusCode::BAD_REQUEST,
+                "MISSING_CREDENTIALS",
+                "改名参数不完整",
+                "请检查输入后重试",
+            ));
+        }
+
+        let ts = parse_ts(&req.ts, "ACCESS_SIGNATURE_EXPIRED", "签名时间戳无效")?;
+        verify_ts_window(
+            ts,
+            self.auth_config.pop_skew_sec,
+            "ACCESS_SIGNATURE_EXPIRED",
+            "签名时间窗已过期",
+        )?;
+        self.consume_auth_nonce("rename", &req.nonce, ts).await?;
+
+        let payload = auth_rename_payload(
+            system_id,
+            device_id,
+            target_device_id,
+            new_name,
+            key_id,
+            ts,
+            &req.nonce,
+        );
+        self.verify_access_http(
+            system_id,
+            device_id,
+            key_id,
+            &req.access_token,
+            &payload,
+            &req.sig,
+        )
+        .await?;
+
+        let mut store = self.auth_store.write().await;
+        let Some(system) = store.systems.get_mut(system_id) else {
+            return Err(ApiError::new(
+                StatusCode::NOT_FOUND,
+                "SYSTEM_NOT_REGISTERED",
+                "system 不存在",
+                "请先完成配对",
+            ));
+        };
+
+        let Some(target) = system.devices.get_mut(target_device_id) else {
+            return Err(ApiError::new(
+                StatusCode::NOT_FOUND,
+                "DEVICE_NOT_FOUND",
+                "目标设备不存在",
+                "请刷新后重试",
+            ));
+        };
+        // 与吊销同样拒绝因 refresh 重放被标记为 COMPROMISED 的设备。
+        if target.status != "ACTIVE" {
+            return Err(ApiError::new(
+                StatusCode::CONFLICT,
+                "DEVICE_REVOKED",
+                "目标设备已吊销，无法改名",
+                "请刷新设备列表",
+            ));
+        }
+
+        target.device_name = normalize_device_name(new_name, target_device_id);
+        let entry = DeviceEntry::from(target.clone());
+
+        self.auth_backend.persist(&store).map_err(|err| {
+            ApiError::new(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "INTERNAL_ERROR",
+                err,
+                "请稍后重试",
+            )
+        })?;
+
+        Ok(entry)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use base64::{Engine as _, engine::general_purpose::URL_SAFE_NO_PAD};
+    use ed25519_dalek::{Signer, SigningKey};
+
+    use crate::{
+        api::types::AuthRenameDeviceRequest,
+        auth::{
+            handlers::test_support::{access_token, state_with_devices},
+            pop::auth_rename_payload,
+            store::unix_now,
+        },
+    };
+
+    /// 构造带 PoP 签名的改名请求。
+    fn rename_request(
+        device_key: &SigningKey,
+        access_token: &str,
+        new_name: &str,
+        nonce: &str,
+    ) -> AuthRenameDeviceRequest {
+        let ts = unix_now();
+        let payload = auth_rename_payload(
+            "sys_a",
+            "ios_a",
+            "ios_b",
+            new_name.trim(),
+            "kid_a",
+            ts,
+            nonce,
+        );
+        AuthRenameDeviceRequest {
+            system_id: "sys_a".to_string(),
+            device_id: "ios_a".to_string(),
+            target_device_id: "ios_b".to_string(),
+            new_name: new_name.to_string(),
+            access_token: access_token.to_string(),
+            key_id: "kid_a".to_string(),
+            ts: ts.to_string(),
+            nonce: nonce.to_string(),
+            sig: URL_SAFE_NO_PAD.encode(device_key.sign(payload.as_bytes()).to_bytes()),
+        }
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn renames_active_device_and_rejects_revoked_or_compromised() {
+        let device_key = SigningKey::from_bytes(&[7u8; 32]);
+        let state = state_with_devices(
+            &device_key,
+            &[("ios_a", "kid_a"), ("ios_b", "kid_b")],
+            |_| {},
+        );
+        let access_token = access_token(&state, "ios_a", "kid_a").await;
+
+        let entry = state
+            .rename_device(&rename_request(
+                &device_key,
+                &access_token,
+                "  Work Phone ",
+                "n1",
+            ))
+            .await
+            .expect("rename should succeed");
+        assert_eq!(entry.device_id, "ios_b");
+        assert_eq!(entry.device_name, "Work Phone");
+        assert_eq!(
+            state.auth_store.read().await.systems["sys_a"].devices["ios_b"].device_name,
+            "Work Phone"
+        );
+
+        let entry = state
+            .rename_device(&rename_request(&device_key, &access_token, " ", "n2"))
+            .await
+            .expect("blank name falls back to deviceId");
+        assert_eq!(entry.device_name, "ios_b");
+
+        state
+            .auth_store
+            .write()
+            .await
+            .system_mut("sys_a")
+            .devices
+            .get_mut("ios_b")
+            .expect("target device")
+            .status = "REVOKED".to_string();
+        let err = state
+            .rename_device(&rename_request(&device_key, &access_token, "Old", "n3"))
+            .await
+            .expect_err("revoked device cannot be renamed");
+        assert_eq!(err.code, "DEVICE_REVOKED");
+
+        state
+            .auth_store
+            .write()
+            .await
+            .system_mut("sys_a")
+            .devices
+            .get_mut("ios_b")
+            .expect("target device")
+            .status = "COMPROMISED".to_string();
+        let err = state
+            .rename_device(&rename_request(&device_key, &access_token, "Old", "n4"))
+            .await
+            .expect_err("compromised device cannot be renamed");
+        assert_eq!(err.code, "DEVICE_REVOKED");
+    }
+}
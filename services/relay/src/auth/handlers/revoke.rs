@@ -8,17 +8,36 @@ use crate::{
         types::{AuthRevokeDeviceData, AuthRevokeDeviceRequest},
     },
     auth::{
+        audit::{AuditAction, AuditRecord},
         pop::{auth_revoke_payload, parse_ts, verify_ts_window},
-        store::persist_auth_store,
     },
     state::AppState,
 };
 
 impl AppState {
-    /// 吊销指定设备。
+    /// 吊销指定设备，结果写入审计日志。
     pub(crate) async fn revoke_device(
         &self,
         req: &AuthRevokeDeviceRequest,
+    ) -> Result<AuthRevokeDeviceData, ApiError> {
+        let result = self.apply_revoke(req).await;
+        self.audit.record_result(
+            AuditRecord {
+                action: AuditAction::Revoke,
+                system_id: req.system_id.trim(),
+                device_id: req.device_id.trim(),
+                key_id: req.key_id.trim(),
+                target_device_id: Some(req.target_device_id.trim()),
+            },
+            &result,
+        );
+        result
+    }
+
+    /// 吊销主流程。
+    async fn apply_revoke(
+        &self,
+        req: &AuthRevokeDeviceRequest,
     ) -> Result<AuthRevokeDeviceData, ApiError> {
         let system_id = req.system_id.trim();
         let device_id = req.device_id.trim();
@@ -38,7 +57,12 @@ impl AppState {
         }
 
         let ts = parse_ts(&req.ts, "ACCESS_SIGNATURE_EXPIRED", "签名时间戳无效")?;
-        verify_ts_window(ts, "ACCESS_SIGNATURE_EXPIRED", "签名时间窗已过期")?;
+        verify_ts_window(
+            ts,
+            self.auth_config.pop_skew_sec,
+            "ACCESS_SIGNATURE_EXPIRED",
+            "签名时间窗已过期",
+        )?;
         self.consume_auth_nonce("revoke", &req.nonce, ts).await?;
 
         let payload = auth_revoke_payload(
@@ -86,7 +110,7 @@ impl AppState {
             }
         }
 
-        persist_auth_store(&self.auth_store_path, &store).map_err(|err| {
+        self.auth_backend.persist(&store).map_err(|err| {
             ApiError::new(
                 StatusCode::INTERNAL_SERVER_ERROR,
                 "INTERNAL_ERROR",
@@ -95,6 +119,11 @@ impl AppState {
             )
         })?;
 
+        drop(store);
+        // 鉴权只在握手时校验，已建立的连接需主动断开。
+        self.disconnect_revoked_device(system_id, target_device_id)
+            .await;
+        self.metrics.record_revocation();
         Ok(AuthRevokeDeviceData {
             target_device_id: target_device_id.to_string(),
         })
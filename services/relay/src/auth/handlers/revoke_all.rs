@@ -0,0 +1,270 @@
+//! 批量吊销逻辑：PoP 签名设备或 sidecar pairToken（兜底）一次吊销 system 下全部设备。
+
+use axum::http::StatusCode;
+
+use crate::{
+    api::{
+        error::ApiError,
+        types::{AuthRevokeAllData, AuthRevokeAllRequest},
+    },
+    auth::{
+        audit::{AuditAction, AuditRecord},
+        pop::{auth_revoke_all_payload, parse_ts, verify_ts_window},
+        token::pair_token_hash_matches,
+    },
+    state::AppState,
+};
+
+impl AppState {
+    /// 吊销 system 下除发起方外的全部设备，结果写入审计日志。
+    pub(crate) async fn revoke_all_devices(
+        &self,
+        req: &AuthRevokeAllRequest,
+    ) -> Result<AuthRevokeAllData, ApiError> {
+        let result = self.apply_revoke_all(req).await;
+        self.audit.record_result(
+            AuditRecord {
+                action: AuditAction::RevokeAll,
+                system_id: req.system_id.trim(),
+                device_id: req.device_id.trim(),
+                key_id: req.key_id.trim(),
+                target_device_id: None,
+            },
+            &result,
+        );
+        result
+    }
+
+    /// 批量吊销主流程。
+    async fn apply_revoke_all(
+        &self,
+        req: &AuthRevokeAllRequest,
+    ) -> Result<AuthRevokeAllData, ApiError> {
+        let system_id = req.system_id.trim();
+        let pair_token = req.pair_token.as_deref().map(str::trim).unwrap_or_default();
+        // pairToken 兜底不代表任何设备，全部吊销；PoP 发起方默认保留。
+        let keep_device_id = if pair_token.is_empty() {
+            self.verify_revoke_all_pop(req).await?;
+            (!req.include_self).then(|| req.device_id.trim())
+        } else {
+            None
+        };
+
+        let mut store = self.auth_store.write().await;
+        let Some(system) = store.systems.get_mut(system_id) else {
+            return Err(ApiError::new(
+                StatusCode::NOT_FOUND,
+                "SYSTEM_NOT_REGISTERED",
+                "system 不存在",
+                "请先完成配对",
+            ));
+        };
+        if !pair_token.is_empty()
+            && !pair_token_hash_matches(system.pair_token_hash.as_deref(), pair_token)
+        {
+            return Err(ApiError::new(
+                StatusCode::UNAUTHORIZED,
+                "PAIR_TOKEN_MISMATCH",
+                "pairToken 不匹配",
+                "请使用宿主机当前 pairToken",
+            ));
+        }
+
+        let now = yc_shared_protocol::now_rfc3339_nanos();
+        let mut targets = system
+            .devices
+            .keys()
+            .filter(|device_id| Some(device_id.as_str()) != keep_device_id)
+            .cloned()
+            .collect::<Vec<_>>();
+        targets.sort();
+        let mut revoked_device_ids = Vec::new();
+        for device_id in &targets {
+            let Some(device) = system.devices.get_mut(device_id) else {
+                continue;
+            };
+            // 已吊销或已判定泄露的设备保留原状态，不重复计数。
+            if device.revoked_at.is_none() {
+                device.status = "REVOKED".to_string();
+                device.revoked_at = Some(now.clone());
+                revoked_device_ids.push(device_id.clone());
+            }
+        }
+        for session in system.refresh_sessions.values_mut() {
+            if session.revoked_at.is_none() && targets.contains(&session.device_id) {
+                session.revoked_at = Some(now.clone());
+            }
+        }
+
+        self.auth_backend.persist(&store).map_err(|err| {
+            ApiError::new(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "INTERNAL_ERROR",
+                err,
+                "请稍后重试",
+            )
+        })?;
+
+        drop(store);
+        // 已失效设备也可能残留连接，按全部目标逐一断开。
+        for device_id in &targets {
+            self.disconnect_revoked_device(system_id, device_id).await;
+        }
+        for _ in &revoked_device_ids {
+            self.metrics.record_revocation();
+        }
+        Ok(AuthRevokeAllData {
+            revoked_count: revoked_device_ids.len(),
+            revoked_device_ids,
+        })
+    }
+
+    /// 校验发起方设备的 access token 与 PoP 签名。
+    async fn verify_revoke_all_pop(&self, req: &AuthRevokeAllRequest) -> Result<(), ApiError> {
+        let system_id = req.system_id.trim();
+        let device_id = req.device_id.trim();
+        let key_id = req.key_id.trim();
+        if system_id.is_empty() || device_id.is_empty() || key_id.is_empty() {
+            return Err(ApiError::new(
+                StatusCode::BAD_REQUEST,
+                "MISSING_CREDENTIALS",
+                "批量吊销参数不完整",
+                "请携带设备签名或宿主机 pairToken 后重试",
+            ));
+        }
+
+        let ts = parse_ts(&req.ts, "ACCESS_SIGNATURE_EXPIRED", "签名时间戳无效")?;
+        verify_ts_window(
+            ts,
+            self.auth_config.pop_skew_sec,
+            "ACCESS_SIGNATURE_EXPIRED",
+            "签名时间窗已过期",
+        )?;
+        self.consume_auth_nonce("revoke-all", &req.nonce, ts)
+            .await?;
+
+        let payload = auth_revoke_all_payload(
+            system_id,
+            device_id,
+            key_id,
+            ts,
+            &req.nonce,
+            req.include_self,
+        );
+        self.verify_access_http(
+            system_id,
+            device_id,
+            key_id,
+            &req.access_token,
+            &payload,
+            &req.sig,
+        )
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use base64::{Engine as _, engine::general_purpose::URL_SAFE_NO_PAD};
+    use ed25519_dalek::{Signer, SigningKey};
+
+    use crate::{
+        api::types::AuthRevokeAllRequest,
+        auth::{
+            handlers::test_support::{access_token, state_with_devices},
+            pop::auth_revoke_all_payload,
+            store::unix_now,
+            token::{issue_refresh_session, sha256_hex},
+        },
+        state::AppState,
+    };
+
+    /// 构造登记了三台设备（各带 refresh 会话）与 pairToken 的状态。
+    fn state_with_sessions(device_key: &SigningKey) -> AppState {
+        let devices = [("ios_a", "kid_a"), ("ios_b", "kid_b"), ("mac_c", "kid_c")];
+        state_with_devices(device_key, &devices, |system| {
+            system.pair_token_hash = Some(sha256_hex("pt_a"));
+            for (device_id, key_id) in devices {
+                let (_, session) = issue_refresh_session("sys_a", device_id, key_id, "crd", 3600);
+                system
+                    .refresh_sessions
+                    .insert(session.session_id.clone(), session);
+            }
+        })
+    }
+
+    /// 构造 ios_a 发起的 PoP 签名请求。
+    async fn pop_request(
+        state: &AppState,
+        device_key: &SigningKey,
+        nonce: &str,
+        include_self: bool,
+    ) -> AuthRevokeAllRequest {
+        let access_token = access_token(state, "ios_a", "kid_a").await;
+        let ts = unix_now();
+        let payload = auth_revoke_all_payload("sys_a", "ios_a", "kid_a", ts, nonce, include_self);
+        AuthRevokeAllRequest {
+            system_id: "sys_a".to_string(),
+            device_id: "ios_a".to_string(),
+            access_token,
+            key_id: "kid_a".to_string(),
+            ts: ts.to_string(),
+            nonce: nonce.to_string(),
+            sig: URL_SAFE_NO_PAD.encode(device_key.sign(payload.as_bytes()).to_bytes()),
+            pair_token: None,
+            include_self,
+        }
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn revoke_all_keeps_caller_unless_include_self_and_accepts_pair_token() {
+        let device_key = SigningKey::from_bytes(&[9u8; 32]);
+        let state = state_with_sessions(&device_key);
+
+        // 篡改 includeSelf 会使签名失效。
+        let mut tampered = pop_request(&state, &device_key, "n_tamper", false).await;
+        tampered.include_self = true;
+        assert!(state.revoke_all_devices(&tampered).await.is_err());
+
+        let data = state
+            .revoke_all_devices(&pop_request(&state, &device_key, "n1", false).await)
+            .await
+            .expect("revoke all should succeed");
+        assert_eq!(data.revoked_count, 2);
+        assert_eq!(data.revoked_device_ids, vec!["ios_b", "mac_c"]);
+        {
+            let store = state.auth_store.read().await;
+            let system = &store.systems["sys_a"];
+            assert_eq!(system.devices["ios_a"].status, "ACTIVE");
+            assert_eq!(system.devices["ios_b"].status, "REVOKED");
+            for session in system.refresh_sessions.values() {
+                assert_eq!(session.revoked_at.is_some(), session.device_id != "ios_a");
+            }
+        }
+
+        let err = state
+            .revoke_all_devices(&AuthRevokeAllRequest {
+                system_id: "sys_a".to_string(),
+                device_id: String::new(),
+                access_token: String::new(),
+                key_id: String::new(),
+                ts: String::new(),
+                nonce: String::new(),
+                sig: String::new(),
+                pair_token: Some("pt_wrong".to_string()),
+                include_self: false,
+            })
+            .await
+            .expect_err("wrong pair token should be rejected");
+        assert_eq!(err.code, "PAIR_TOKEN_MISMATCH");
+
+        let data = state
+            .revoke_all_devices(&AuthRevokeAllRequest {
+                pair_token: Some("pt_a".to_string()),
+                ..pop_request(&state, &device_key, "n2", false).await
+            })
+            .await
+            .expect("break-glass revoke should succeed");
+        assert_eq!(data.revoked_device_ids, vec!["ios_a"]);
+    }
+}
@@ -0,0 +1,63 @@
+//! 鉴权相关测试共用夹具：构造设备凭证，在 `sys_a` 下登记设备并构造测试状态。
+
+use std::sync::Arc;
+
+use base64::{Engine as _, engine::general_purpose::URL_SAFE_NO_PAD};
+use ed25519_dalek::SigningKey;
+use tokio::sync::RwLock;
+
+use crate::{
+    api::types::{AuthStore, DeviceCredential, SystemAuthState},
+    auth::token::issue_access_token,
+    state::AppState,
+};
+
+/// 构造时间戳为空的设备凭证；`DeviceCredential` 新增字段时只需在此补默认值。
+pub(crate) fn device(
+    device_id: &str,
+    key_id: &str,
+    public_key: &str,
+    status: &str,
+) -> DeviceCredential {
+    DeviceCredential {
+        device_id: device_id.to_string(),
+        device_name: "iPhone".to_string(),
+        key_id: key_id.to_string(),
+        public_key: public_key.to_string(),
+        status: status.to_string(),
+        created_at: String::new(),
+        last_seen_at: String::new(),
+        revoked_at: None,
+        last_auth_failure: None,
+    }
+}
+
+/// 构造 `sys_a` 下登记了给定 `(deviceId, keyId)` 设备（共用 `device_key`、状态 ACTIVE）的状态；
+/// `customize` 可继续补充会话、pairToken 等系统数据。
+pub(super) fn state_with_devices(
+    device_key: &SigningKey,
+    devices: &[(&str, &str)],
+    customize: impl FnOnce(&mut SystemAuthState),
+) -> AppState {
+    let mut store = AuthStore::new("seed".to_string());
+    let system = store.system_mut("sys_a");
+    let public_key = URL_SAFE_NO_PAD.encode(device_key.verifying_key().to_bytes());
+    for (device_id, key_id) in devices {
+        system.devices.insert(
+            device_id.to_string(),
+            device(device_id, key_id, &public_key, "ACTIVE"),
+        );
+    }
+    customize(system);
+    AppState {
+        auth_store: Arc::new(RwLock::new(store)),
+        ..AppState::for_test()
+    }
+}
+
+/// 以当前签名密钥为 `sys_a` 下的设备签发 accessToken。
+pub(super) async fn access_token(state: &AppState, device_id: &str, key_id: &str) -> String {
+    let store = state.auth_store.read().await;
+    issue_access_token(&store.signing_keys, "sys_a", device_id, key_id, 600)
+        .expect("issue access token")
+}
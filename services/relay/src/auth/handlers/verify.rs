@@ -22,7 +22,7 @@ impl AppState {
         let store = self.auth_store.read().await;
         verify_access_token(
             access_token,
-            &store.signing_key,
+            &store.signing_keys,
             system_id,
             device_id,
             key_id,
@@ -1,7 +1,16 @@
 //! 鉴权模块：token/签名/认证存储与接口处理。
 
+pub(crate) mod admin;
+pub(crate) mod audit;
+pub(crate) mod backend;
+pub(crate) mod config;
+pub(crate) mod expiry;
 pub(crate) mod handlers;
+pub(crate) mod nonce_log;
+pub(crate) mod pair_tokens;
 pub(crate) mod pop;
+pub(crate) mod sidecar_secret;
+pub(crate) mod signing_keys;
 pub(crate) mod store;
 pub(crate) mod token;
 pub(crate) mod token_crypto;
@@ -0,0 +1,215 @@
+//! HTTP nonce 持久化：以追加写 JSON 行记录 `scope:nonce` 与过期时间，重启后恢复防重放窗口。
+//! 持久化为尽力而为：读写失败只告警，不影响 relay 启动与请求处理。
+//! 写入分两步：持 nonce 表锁时只生成待写内容（`prepare`），释放锁后再在阻塞线程池落盘（`write`）。
+
+use std::{
+    collections::HashMap,
+    fs::{self, OpenOptions},
+    io::Write as _,
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+/// nonce 日志文件名（与认证存储同目录）。
+const NONCE_LOG_FILE_NAME: &str = "auth-nonces.jsonl";
+/// 累计追加多少条后按内存快照重写文件，避免日志无限增长。
+const NONCE_LOG_COMPACT_THRESHOLD: usize = 1024;
+
+/// 单条 nonce 记录。
+#[derive(Debug, Serialize, Deserialize)]
+struct NonceLogEntry {
+    /// `scope:nonce` 键。
+    key: String,
+    /// 过期 unix 秒。
+    exp: u64,
+}
+
+/// 一次待落盘的 nonce 日志写入。
+#[derive(Debug)]
+pub(crate) enum NonceLogWrite {
+    /// 追加单条记录。
+    Append { key: String, exp: u64 },
+    /// 按内存快照整体重写。
+    Rewrite(HashMap<String, u64>),
+}
+
+/// 追加写 nonce 日志；`path` 为空时不落盘（测试场景）。
+#[derive(Debug, Default)]
+pub(crate) struct NonceLog {
+    /// 日志文件路径。
+    path: Option<PathBuf>,
+    /// 上次重写后追加的条数。
+    appended: AtomicUsize,
+}
+
+impl NonceLog {
+    /// 以认证存储路径推导日志位置。
+    pub(crate) fn beside(auth_store_path: &Path) -> Self {
+        let path = auth_store_path
+            .parent()
+            .map(|dir| dir.join(NONCE_LOG_FILE_NAME))
+            .unwrap_or_else(|| PathBuf::from(NONCE_LOG_FILE_NAME));
+        Self {
+            path: Some(path),
+            appended: AtomicUsize::new(0),
+        }
+    }
+
+    /// 读取未过期记录，并把裁剪后的结果重写回文件。
+    pub(crate) fn load(&self, now: u64) -> HashMap<String, u64> {
+        let Some(path) = self.path.as_deref() else {
+            return HashMap::new();
+        };
+        let raw = match fs::read_to_string(path) {
+            Ok(raw) => raw,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return HashMap::new(),
+            Err(err) => {
+                warn!("read nonce log failed path={} err={err}", path.display());
+                return HashMap::new();
+            }
+        };
+        let nonces = parse_entries(&raw, now);
+        self.compact(&nonces);
+        nonces
+    }
+
+    /// 生成一条记录的待写内容；累计过多时改为按当前内存快照重写。不落盘时返回 `None`。
+    pub(crate) fn prepare(
+        &self,
+        key: &str,
+        exp: u64,
+        live: &HashMap<String, u64>,
+    ) -> Option<NonceLogWrite> {
+        self.path.as_ref()?;
+        if self.appended.fetch_add(1, Ordering::Relaxed) + 1 >= NONCE_LOG_COMPACT_THRESHOLD {
+            self.appended.store(0, Ordering::Relaxed);
+            return Some(NonceLogWrite::Rewrite(live.clone()));
+        }
+        Some(NonceLogWrite::Append {
+            key: key.to_string(),
+            exp,
+        })
+    }
+
+    /// 执行 `prepare` 生成的写入（阻塞文件 I/O，调用方应放到阻塞线程池）。
+    pub(crate) fn write(&self, write: NonceLogWrite) {
+        let Some(path) = self.path.as_deref() else {
+            return;
+        };
+        match write {
+            NonceLogWrite::Append { key, exp } => {
+                if let Err(err) = append_entry(path, &key, exp) {
+                    warn!("append nonce log failed path={} err={err}", path.display());
+                }
+            }
+            NonceLogWrite::Rewrite(live) => self.compact(&live),
+        }
+    }
+
+    /// 以给定记录整体重写日志。
+    fn compact(&self, live: &HashMap<String, u64>) {
+        let Some(path) = self.path.as_deref() else {
+            return;
+        };
+        self.appended.store(0, Ordering::Relaxed);
+        if let Err(err) = write_entries(path, live) {
+            warn!("rewrite nonce log failed path={} err={err}", path.display());
+        }
+    }
+}
+
+/// 解析 JSON 行，跳过损坏行与已过期记录；同键保留最晚过期时间。
+fn parse_entries(raw: &str, now: u64) -> HashMap<String, u64> {
+    let mut nonces = HashMap::new();
+    for entry in raw
+        .lines()
+        .filter_map(|line| serde_json::from_str::<NonceLogEntry>(line).ok())
+        .filter(|entry| entry.exp > now)
+    {
+        let exp = nonces.entry(entry.key).or_insert(entry.exp);
+        *exp = (*exp).max(entry.exp);
+    }
+    nonces
+}
+
+/// 追加单条记录。
+fn append_entry(path: &Path, key: &str, exp: u64) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|err| format!("create nonce log dir failed: {err}"))?;
+    }
+    let mut line = serde_json::to_string(&NonceLogEntry {
+        key: key.to_string(),
+        exp,
+    })
+    .map_err(|err| format!("encode nonce entry failed: {err}"))?;
+    line.push('\n');
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .and_then(|mut file| file.write_all(line.as_bytes()))
+        .map_err(|err| err.to_string())
+}
+
+/// 整体重写日志文件。
+fn write_entries(path: &Path, live: &HashMap<String, u64>) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|err| format!("create nonce log dir failed: {err}"))?;
+    }
+    let mut out = String::new();
+    for (key, exp) in live {
+        let line = serde_json::to_string(&NonceLogEntry {
+            key: key.clone(),
+            exp: *exp,
+        })
+        .map_err(|err| format!("encode nonce entry failed: {err}"))?;
+        out.push_str(&line);
+        out.push('\n');
+    }
+    fs::write(path, out).map_err(|err| err.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use uuid::Uuid;
+
+    use super::{NonceLog, parse_entries};
+
+    #[test]
+    fn parse_skips_expired_and_corrupt_lines() {
+        let raw = concat!(
+            "{\"key\":\"refresh:a\",\"exp\":200}\n",
+            "{\"key\":\"refresh:b\",\"exp\":50}\n",
+            "not json\n",
+            "{\"key\":\"refresh:a\",\"exp\":150}\n",
+        );
+        let nonces = parse_entries(raw, 100);
+        assert_eq!(nonces, HashMap::from([("refresh:a".to_string(), 200)]));
+    }
+
+    #[test]
+    fn recorded_nonces_survive_reload() {
+        let dir = std::env::temp_dir().join(format!("yc-relay-nonce-{}", Uuid::new_v4()));
+        let log = NonceLog::beside(&dir.join("auth-store.json"));
+        for (key, exp) in [("revoke:n1", 1_000), ("devices:n2", 10)] {
+            let write = log
+                .prepare(key, exp, &HashMap::new())
+                .expect("file-backed log");
+            log.write(write);
+        }
+        assert!(
+            NonceLog::default()
+                .prepare("revoke:n3", 1_000, &HashMap::new())
+                .is_none()
+        );
+
+        let reloaded = NonceLog::beside(&dir.join("auth-store.json")).load(100);
+        assert_eq!(reloaded, HashMap::from([("revoke:n1".to_string(), 1_000)]));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
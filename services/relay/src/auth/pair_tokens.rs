@@ -0,0 +1,85 @@
+//! system 配对令牌集合：当前令牌 + 轮换前的上一个令牌（带宽限期），
+//! 轮换后已展示的二维码/配对链接在宽限期内仍可使用。
+
+/// 轮换下线、仍在宽限期内的旧令牌。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct RetiredPairToken {
+    /// 旧令牌明文（仅内存持有）。
+    pub(crate) token: String,
+    /// 失效时间（Unix 秒）。
+    pub(crate) expires_at: u64,
+}
+
+/// 单个 system 的有效配对令牌集合。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct PairTokenSet {
+    /// 当前令牌（不过期，直到下次轮换）。
+    current: String,
+    /// 上一个令牌。
+    previous: Option<RetiredPairToken>,
+}
+
+impl PairTokenSet {
+    /// 以单个当前令牌初始化。
+    pub(crate) fn new(current: impl Into<String>) -> Self {
+        Self {
+            current: current.into(),
+            previous: None,
+        }
+    }
+
+    /// 当前令牌。
+    pub(crate) fn current(&self) -> &str {
+        &self.current
+    }
+
+    /// 上一个令牌（可能已过期，调用方按需判断）。
+    pub(crate) fn previous(&self) -> Option<&RetiredPairToken> {
+        self.previous.as_ref()
+    }
+
+    /// 轮换为新令牌；旧令牌保留到 `now + grace_sec`，宽限期为 0 时直接丢弃。
+    pub(crate) fn rotate(&mut self, next: impl Into<String>, grace_sec: u64, now: u64) {
+        let retired = std::mem::replace(&mut self.current, next.into());
+        self.previous = (grace_sec > 0 && retired != self.current).then(|| RetiredPairToken {
+            token: retired,
+            expires_at: now.saturating_add(grace_sec),
+        });
+    }
+
+    /// 在 `now` 时刻仍有效的令牌（当前令牌在前）。
+    pub(crate) fn valid_tokens(&self, now: u64) -> impl Iterator<Item = &str> {
+        std::iter::once(self.current.as_str()).chain(
+            self.previous
+                .as_ref()
+                .filter(|previous| previous.expires_at > now)
+                .map(|previous| previous.token.as_str()),
+        )
+    }
+
+    /// 判断令牌在 `now` 时刻是否有效。
+    pub(crate) fn accepts(&self, token: &str, now: u64) -> bool {
+        self.valid_tokens(now).any(|valid| valid == token)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PairTokenSet;
+
+    #[test]
+    fn previous_token_is_accepted_only_within_grace_window() {
+        let mut tokens = PairTokenSet::new("pt_old");
+        tokens.rotate("pt_new", 600, 1_000);
+
+        assert_eq!(tokens.current(), "pt_new");
+        assert!(tokens.accepts("pt_new", 1_000));
+        assert!(tokens.accepts("pt_old", 1_599));
+        assert!(!tokens.accepts("pt_old", 1_600));
+        assert!(tokens.accepts("pt_new", 1_600));
+
+        tokens.rotate("pt_next", 0, 2_000);
+        assert!(tokens.previous().is_none());
+        assert!(!tokens.accepts("pt_new", 2_000));
+    }
+}
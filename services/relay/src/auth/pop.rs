@@ -2,10 +2,7 @@
 
 use axum::http::StatusCode;
 
-use crate::{
-    api::{error::ApiError, types::POP_MAX_SKEW_SEC},
-    auth::store::unix_now,
-};
+use crate::{api::error::ApiError, auth::store::unix_now};
 
 /// 解析秒级时间戳。
 pub(crate) fn parse_ts(
@@ -18,14 +15,15 @@ pub(crate) fn parse_ts(
         .map_err(|_| ApiError::new(StatusCode::BAD_REQUEST, code, message, "请刷新后重试"))
 }
 
-/// 校验时间窗。
+/// 校验时间窗（允许 `max_skew_sec` 秒的时钟偏差）。
 pub(crate) fn verify_ts_window(
     ts: u64,
+    max_skew_sec: u64,
     code: &'static str,
     message: &'static str,
 ) -> Result<(), ApiError> {
     let now = unix_now();
-    if ts.saturating_add(POP_MAX_SKEW_SEC) < now || ts > now.saturating_add(POP_MAX_SKEW_SEC) {
+    if ts.saturating_add(max_skew_sec) < now || ts > now.saturating_add(max_skew_sec) {
         return Err(ApiError::new(
             StatusCode::UNAUTHORIZED,
             code,
@@ -75,22 +73,68 @@ pub(crate) fn auth_revoke_payload(
     format!("auth-revoke\n{system_id}\n{device_id}\n{target_device_id}\n{key_id}\n{ts}\n{nonce}")
 }
 
-/// 组装 list-devices 签名 payload。
+/// 组装 auth revoke-all 签名 payload；`includeSelf` 参与签名，防止被篡改成连同发起方一起吊销。
+pub(crate) fn auth_revoke_all_payload(
+    system_id: &str,
+    device_id: &str,
+    key_id: &str,
+    ts: u64,
+    nonce: &str,
+    include_self: bool,
+) -> String {
+    format!("auth-revoke-all\n{system_id}\n{device_id}\n{key_id}\n{ts}\n{nonce}\n{include_self}")
+}
+
+/// 组装 pair revoke-token 签名 payload。
+pub(crate) fn pair_revoke_token_payload(
+    system_id: &str,
+    device_id: &str,
+    key_id: &str,
+    ts: u64,
+    nonce: &str,
+) -> String {
+    format!("pair-revoke-token\n{system_id}\n{device_id}\n{key_id}\n{ts}\n{nonce}")
+}
+
+/// 组装 rename-device 签名 payload；新名称按原文（trim 后）参与签名。
+pub(crate) fn auth_rename_payload(
+    system_id: &str,
+    device_id: &str,
+    target_device_id: &str,
+    new_name: &str,
+    key_id: &str,
+    ts: u64,
+    nonce: &str,
+) -> String {
+    format!(
+        "auth-rename\n{system_id}\n{device_id}\n{target_device_id}\n{new_name}\n{key_id}\n{ts}\n{nonce}"
+    )
+}
+
+/// 组装 list-devices 签名 payload；携带分页参数时追加 `limit`/`cursor`（缺省为空串），防止篡改分页。
 pub(crate) fn auth_list_payload(
     system_id: &str,
     device_id: &str,
     key_id: &str,
     ts: u64,
     nonce: &str,
+    limit: Option<usize>,
+    cursor: Option<&str>,
 ) -> String {
-    format!("auth-list-devices\n{system_id}\n{device_id}\n{key_id}\n{ts}\n{nonce}")
+    let base = format!("auth-list-devices\n{system_id}\n{device_id}\n{key_id}\n{ts}\n{nonce}");
+    if limit.is_none() && cursor.is_none() {
+        return base;
+    }
+    let limit = limit.map(|value| value.to_string()).unwrap_or_default();
+    let cursor = cursor.unwrap_or_default();
+    format!("{base}\n{limit}\n{cursor}")
 }
 
 #[cfg(test)]
 mod tests {
     use super::{
-        auth_list_payload, auth_refresh_payload, auth_revoke_payload, pair_exchange_payload,
-        ws_pop_payload,
+        auth_list_payload, auth_refresh_payload, auth_rename_payload, auth_revoke_payload,
+        pair_exchange_payload, pair_revoke_token_payload, ws_pop_payload,
     };
 
     #[test]
@@ -99,11 +143,30 @@ mod tests {
         let exchange = pair_exchange_payload("sid", "did", "kid");
         let refresh = auth_refresh_payload("sid", "did", "kid", 123, "nonce");
         let revoke = auth_revoke_payload("sid", "did", "target", "kid", 123, "nonce");
-        let list = auth_list_payload("sid", "did", "kid", 123, "nonce");
+        let list = auth_list_payload("sid", "did", "kid", 123, "nonce", None, None);
+        let rename = auth_rename_payload("sid", "did", "target", "name", "kid", 123, "nonce");
+        let revoke_token = pair_revoke_token_payload("sid", "did", "kid", 123, "nonce");
 
-        for payload in [ws, exchange, refresh, revoke, list] {
+        for payload in [ws, exchange, refresh, revoke, list, rename, revoke_token] {
             assert!(payload.contains('\n'));
             assert!(!payload.contains("\\n"));
         }
     }
+
+    #[test]
+    fn list_payload_covers_pagination_params() {
+        let legacy = auth_list_payload("sid", "did", "kid", 123, "nonce", None, None);
+        assert_eq!(legacy, "auth-list-devices\nsid\ndid\nkid\n123\nnonce");
+
+        let paged = auth_list_payload("sid", "did", "kid", 123, "nonce", Some(20), Some("ios_b"));
+        assert_eq!(paged, format!("{legacy}\n20\nios_b"));
+        assert_ne!(
+            paged,
+            auth_list_payload("sid", "did", "kid", 123, "nonce", Some(20), Some("ios_c"))
+        );
+        assert_ne!(
+            auth_list_payload("sid", "did", "kid", 123, "nonce", Some(20), None),
+            legacy
+        );
+    }
 }
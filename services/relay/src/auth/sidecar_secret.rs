@@ -0,0 +1,75 @@
+//! sidecar 注册共享密钥：配置 `RELAY_SIDECAR_SHARED_SECRET` 后，sidecar WS 握手必须携带匹配的
+//! `X-Sidecar-Secret` 头才允许建房/轮换 pairToken；App 连接不受影响。
+
+use axum::http::{HeaderMap, StatusCode};
+
+use crate::{api::error::ApiError, auth::token_crypto::sha256_hex};
+
+/// sidecar 注册共享密钥环境变量（未设置时不校验）。
+pub(crate) const RELAY_SIDECAR_SHARED_SECRET_ENV: &str = "RELAY_SIDECAR_SHARED_SECRET";
+/// sidecar 携带共享密钥的请求头。
+pub(crate) const SIDECAR_SECRET_HEADER: &str = "x-sidecar-secret";
+
+/// 读取已配置的共享密钥（去除首尾空白，未设置时为空串）。
+pub(crate) fn sidecar_secret_from_env() -> String {
+    std::env::var(RELAY_SIDECAR_SHARED_SECRET_ENV)
+        .map(|value| value.trim().to_string())
+        .unwrap_or_default()
+}
+
+/// 校验 sidecar 握手请求头中的共享密钥。
+pub(crate) fn verify_sidecar_secret(headers: &HeaderMap) -> Result<(), ApiError> {
+    verify_sidecar_secret_with(headers, &sidecar_secret_from_env())
+}
+
+/// 按给定共享密钥校验请求头；`expected` 为空表示未启用校验。
+pub(crate) fn verify_sidecar_secret_with(
+    headers: &HeaderMap,
+    expected: &str,
+) -> Result<(), ApiError> {
+    if expected.is_empty() {
+        return Ok(());
+    }
+    let provided = headers
+        .get(SIDECAR_SECRET_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .map(str::trim)
+        .unwrap_or_default();
+    // 与管理令牌一致：比较哈希而非原文。
+    if provided.is_empty() || sha256_hex(provided) != sha256_hex(expected) {
+        return Err(ApiError::new(
+            StatusCode::UNAUTHORIZED,
+            "SIDECAR_SECRET_INVALID",
+            "sidecar 注册密钥缺失或不匹配",
+            "请为 sidecar 配置与 relay 一致的 SIDECAR_SHARED_SECRET",
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::{HeaderMap, HeaderValue};
+
+    use super::{SIDECAR_SECRET_HEADER, verify_sidecar_secret_with};
+
+    #[test]
+    fn configured_secret_requires_matching_header() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(
+            verify_sidecar_secret_with(&headers, "s3cret")
+                .unwrap_err()
+                .code,
+            "SIDECAR_SECRET_INVALID"
+        );
+        headers.insert(SIDECAR_SECRET_HEADER, HeaderValue::from_static("other"));
+        assert!(verify_sidecar_secret_with(&headers, "s3cret").is_err());
+        headers.insert(SIDECAR_SECRET_HEADER, HeaderValue::from_static("s3cret"));
+        assert!(verify_sidecar_secret_with(&headers, "s3cret").is_ok());
+    }
+
+    #[test]
+    fn unset_secret_accepts_any_sidecar() {
+        assert!(verify_sidecar_secret_with(&HeaderMap::new(), "").is_ok());
+    }
+}
@@ -0,0 +1,190 @@
+//! access token 签名密钥环：按 kid 保存多把 HMAC 密钥，只有一把负责签发；
+//! 轮换后旧密钥在 access token 最长有效期内继续验签，到期后移除。
+
+use serde::{Deserialize, Serialize};
+use tracing::info;
+
+use crate::{
+    api::types::{AUTH_STORE_VERSION, AuthStore},
+    auth::store::{generate_signing_key_seed, unix_now},
+};
+
+/// 旧版单签名种子迁移后的 kid；未携带 kid 的历史 token 按它验签。
+pub(crate) const LEGACY_SIGNING_KEY_ID: &str = "legacy";
+
+/// 单把签名密钥。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SigningKeyEntry {
+    pub(crate) kid: String,
+    pub(crate) secret: String,
+    pub(crate) created_at: u64,
+    /// 退役时间（Unix 秒）：此后不再验签；当前签发密钥为空。
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) retires_at: Option<u64>,
+}
+
+/// 签名密钥环。
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SigningKeyRing {
+    #[serde(default)]
+    active_kid: String,
+    #[serde(default)]
+    keys: Vec<SigningKeyEntry>,
+}
+
+impl SigningKeyRing {
+    /// 以给定签名种子创建只含一把签发密钥的密钥环。
+    pub(crate) fn new(secret: String) -> Self {
+        let mut ring = Self::default();
+        ring.push_active(new_signing_key_id(), secret, unix_now());
+        ring
+    }
+
+    /// 当前签发密钥。
+    pub(crate) fn active(&self) -> Option<&SigningKeyEntry> {
+        self.keys.iter().find(|key| key.kid == self.active_kid)
+    }
+
+    /// 按 kid 选择验签密钥；不存在或已退役时返回 `None`。
+    pub(crate) fn verification_secret(&self, kid: &str, now: u64) -> Option<&str> {
+        self.keys
+            .iter()
+            .find(|key| key.kid == kid)
+            .filter(|key| key.retires_at.is_none_or(|retires_at| retires_at > now))
+            .map(|key| key.secret.as_str())
+    }
+
+    /// 新增签发密钥，原签发密钥在 `overlap_sec` 秒后退役；同时移除已退役的密钥。
+    pub(crate) fn rotate(&mut self, now: u64, overlap_sec: u64) -> &SigningKeyEntry {
+        self.prune_retired(now);
+        let active_kid = self.active_kid.clone();
+        if let Some(previous) = self.keys.iter_mut().find(|key| key.kid == active_kid) {
+            previous.retires_at = Some(now.saturating_add(overlap_sec));
+        }
+        self.push_active(new_signing_key_id(), generate_signing_key_seed(), now)
+    }
+
+    /// 移除已退役的密钥，返回移除数量。
+    pub(crate) fn prune_retired(&mut self, now: u64) -> usize {
+        let before = self.keys.len();
+        self.keys
+            .retain(|key| key.retires_at.is_none_or(|retires_at| retires_at > now));
+        before - self.keys.len()
+    }
+
+    /// 追加密钥并设为签发密钥。
+    fn push_active(&mut self, kid: String, secret: String, now: u64) -> &SigningKeyEntry {
+        self.active_kid = kid.clone();
+        self.keys.push(SigningKeyEntry {
+            kid,
+            secret,
+            created_at: now,
+            retires_at: None,
+        });
+        &self.keys[self.keys.len() - 1]
+    }
+}
+
+/// 生成签名密钥 kid。
+fn new_signing_key_id() -> String {
+    let id = uuid::Uuid::new_v4().simple().to_string();
+    format!("sk_{}", &id[..12])
+}
+
+/// 启动时整理签名密钥：迁移旧版单种子、补齐签发密钥并清理已退役密钥。
+pub(crate) fn prepare_signing_keys(store: &mut AuthStore, now: u64) {
+    let legacy = std::mem::take(&mut store.signing_key);
+    if store.signing_keys.active().is_none() {
+        if legacy.trim().is_empty() {
+            store
+                .signing_keys
+                .push_active(new_signing_key_id(), generate_signing_key_seed(), now);
+        } else {
+            store
+                .signing_keys
+                .push_active(LEGACY_SIGNING_KEY_ID.to_string(), legacy, now);
+            info!("relay signing key migrated kid={LEGACY_SIGNING_KEY_ID}");
+        }
+    }
+    let pruned = store.signing_keys.prune_retired(now);
+    if pruned > 0 {
+        info!("relay retired signing keys removed count={pruned}");
+    }
+    sync_store_format(store);
+}
+
+/// 轮换签名密钥（`yc-relay keys rotate` 调用）：新增签发密钥，原签发密钥在 `overlap_sec`
+/// 秒后退役。`overlap_sec` 取 access token 有效期，保证轮换前签发的 token 到期前仍可验签。
+/// 返回新签发密钥的 kid。
+pub(crate) fn rotate_signing_keys(store: &mut AuthStore, overlap_sec: u64, now: u64) -> String {
+    prepare_signing_keys(store, now);
+    let previous = store.signing_keys.active_kid.clone();
+    let active = store.signing_keys.rotate(now, overlap_sec).kid.clone();
+    info!(
+        "relay signing key rotated active_kid={active} retiring_kid={previous} retires_at={}",
+        now.saturating_add(overlap_sec)
+    );
+    sync_store_format(store);
+    active
+}
+
+/// 写回前对齐存储格式：记录当前版本，并把签发密钥同步到旧版 `signingKey` 字段。
+fn sync_store_format(store: &mut AuthStore) {
+    store.version = AUTH_STORE_VERSION;
+    store.signing_key = store
+        .signing_keys
+        .active()
+        .map(|key| key.secret.clone())
+        .unwrap_or_default();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LEGACY_SIGNING_KEY_ID, SigningKeyRing, prepare_signing_keys, rotate_signing_keys};
+    use crate::api::types::{AUTH_STORE_VERSION, AuthStore};
+
+    #[test]
+    fn legacy_seed_migrates_and_rotation_retires_previous_key_after_overlap() {
+        let mut store: AuthStore =
+            serde_json::from_str(r#"{"version":1,"signingKey":"seed","systems":{}}"#)
+                .expect("decode legacy store");
+        prepare_signing_keys(&mut store, 1_000);
+        let active = store.signing_keys.active().expect("active key");
+        assert_eq!(active.kid, LEGACY_SIGNING_KEY_ID);
+        assert_eq!(active.secret, "seed");
+        let json = serde_json::to_value(&store).expect("encode store");
+        assert_eq!(json["version"], AUTH_STORE_VERSION);
+        assert_eq!(json["signingKey"], "seed");
+
+        prepare_signing_keys(&mut store, 1_500);
+        assert_eq!(
+            store.signing_keys.active().expect("active key").kid,
+            LEGACY_SIGNING_KEY_ID
+        );
+
+        let rotated = rotate_signing_keys(&mut store, 600, 2_000);
+        assert_ne!(rotated, LEGACY_SIGNING_KEY_ID);
+        let active_secret = store
+            .signing_keys
+            .active()
+            .expect("rotated key")
+            .secret
+            .clone();
+        let json = serde_json::to_value(&store).expect("encode store");
+        assert_eq!(json["signingKey"], active_secret.as_str());
+        let ring = &store.signing_keys;
+        assert_eq!(
+            ring.verification_secret(LEGACY_SIGNING_KEY_ID, 2_599),
+            Some("seed")
+        );
+        assert_eq!(ring.verification_secret(LEGACY_SIGNING_KEY_ID, 2_600), None);
+        assert!(ring.verification_secret(&rotated, 9_999).is_some());
+
+        let mut ring: SigningKeyRing = ring.clone();
+        ring.rotate(2_700, 600);
+        assert_eq!(ring.keys.len(), 2);
+        assert!(ring.keys.iter().all(|key| key.kid != LEGACY_SIGNING_KEY_ID));
+    }
+}
@@ -1,10 +1,15 @@
-//! 认证存储读写。
+//! 认证存储读写：临时文件 + `rename` 原子替换，保留一份滚动备份，主文件损坏时从备份恢复。
 
 use std::{
-    fs,
+    fs::{self, File},
+    io::Write as _,
     path::{Path, PathBuf},
 };
 
+use chrono::Utc;
+use tracing::warn;
+use yc_shared_protocol::BaseDirs;
+
 use crate::api::types::AuthStore;
 
 /// 当前 unix 秒。
@@ -15,45 +20,129 @@ pub(crate) fn unix_now() -> u64 {
         .as_secs()
 }
 
-/// 认证存储路径。
-pub(crate) fn auth_store_path() -> PathBuf {
-    if let Ok(path) = std::env::var("RELAY_AUTH_STORE_PATH") {
+/// 认证存储路径覆盖环境变量。
+const AUTH_STORE_PATH_ENV: &str = "RELAY_AUTH_STORE_PATH";
+/// 滚动备份后缀（保留上一次成功写入前的主文件）。
+const BACKUP_SUFFIX: &str = ".bak";
+/// 原子替换前的临时文件后缀。
+const TMP_SUFFIX: &str = ".tmp";
+
+/// 认证存储路径；用户目录无法解析时返回错误，不再回落到当前目录。
+pub(crate) fn auth_store_path() -> Result<PathBuf, String> {
+    if let Ok(path) = std::env::var(AUTH_STORE_PATH_ENV) {
         let trimmed = path.trim();
         if !trimmed.is_empty() {
-            return PathBuf::from(trimmed);
+            return Ok(PathBuf::from(trimmed));
         }
     }
 
-    let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
-    PathBuf::from(home)
-        .join(".config")
+    default_auth_store_path(&BaseDirs::from_env())
+}
+
+/// 默认认证存储路径：`<配置根目录>/yourconnector/relay/auth-store.json`。
+fn default_auth_store_path(base_dirs: &BaseDirs) -> Result<PathBuf, String> {
+    let config_dir = base_dirs
+        .config_dir()
+        .map_err(|err| format!("{err}，或设置 {AUTH_STORE_PATH_ENV}"))?;
+    Ok(config_dir
         .join("yourconnector")
         .join("relay")
-        .join("auth-store.json")
+        .join("auth-store.json"))
 }
 
-/// 加载认证元数据。
+/// 加载认证元数据；文件不存在时返回带新签名种子的空存储。
+/// 主文件损坏时先移到 `.corrupt-<时间>` 留档，再尝试从 `.bak` 恢复，两者都不可用才返回错误。
 pub(crate) fn load_auth_store(path: &Path) -> Result<AuthStore, String> {
     if !path.exists() {
         return Ok(AuthStore::new(generate_signing_key_seed()));
     }
+    let err = match read_auth_store(path) {
+        Ok(store) => return Ok(store),
+        Err(err) => err,
+    };
+    // 损坏文件移走后，下一次持久化不会再把它复制成备份，覆盖仍可用的 `.bak`。
+    let quarantined = quarantine_corrupt_store(path);
+    let backup = sibling_path(path, BACKUP_SUFFIX);
+    match read_auth_store(&backup) {
+        Ok(store) => {
+            warn!(
+                "auth store corrupt ({err}), restored from backup {} corrupt_copy={quarantined}",
+                backup.display()
+            );
+            Ok(store)
+        }
+        Err(backup_err) => Err(format!(
+            "{err}; restore from backup failed: {backup_err}; corrupt_copy={quarantined}"
+        )),
+    }
+}
+
+/// 读取并解析单个认证存储文件。
+fn read_auth_store(path: &Path) -> Result<AuthStore, String> {
     let raw = fs::read(path).map_err(|err| format!("read auth store failed: {err}"))?;
-    let mut parsed: AuthStore =
-        serde_json::from_slice(&raw).map_err(|err| format!("decode auth store failed: {err}"))?;
-    if parsed.signing_key.trim().is_empty() {
-        parsed.signing_key = generate_signing_key_seed();
+    serde_json::from_slice(&raw).map_err(|err| format!("decode auth store failed: {err}"))
+}
+
+/// 把损坏的主文件改名留档，返回留档位置（失败时返回原因）。
+fn quarantine_corrupt_store(path: &Path) -> String {
+    let suffix = format!(".corrupt-{}", Utc::now().format("%Y%m%dT%H%M%SZ"));
+    let target = sibling_path(path, &suffix);
+    match fs::rename(path, &target) {
+        Ok(()) => target.display().to_string(),
+        Err(err) => format!("<move failed: {err}>"),
     }
-    Ok(parsed)
 }
 
-/// 持久化认证元数据。
+/// 持久化认证元数据：先写同目录临时文件并落盘，把当前主文件复制为 `.bak`，
+/// 再以 `rename` 原子替换主文件，写入中途崩溃不会留下半截 JSON。
 pub(crate) fn persist_auth_store(path: &Path, store: &AuthStore) -> Result<(), String> {
     if let Some(parent) = path.parent() {
         fs::create_dir_all(parent).map_err(|err| format!("create auth store dir failed: {err}"))?;
     }
     let encoded = serde_json::to_vec_pretty(store)
         .map_err(|err| format!("encode auth store failed: {err}"))?;
-    fs::write(path, encoded).map_err(|err| format!("write auth store failed: {err}"))
+    let tmp = sibling_path(path, TMP_SUFFIX);
+    let result = write_synced(&tmp, &encoded)
+        .map_err(|err| format!("write auth store failed: {err}"))
+        .and_then(|()| {
+            if path.exists() {
+                fs::copy(path, sibling_path(path, BACKUP_SUFFIX))
+                    .map_err(|err| format!("backup auth store failed: {err}"))?;
+            }
+            fs::rename(&tmp, path).map_err(|err| format!("replace auth store failed: {err}"))
+        });
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp);
+    }
+    result?;
+    sync_parent_dir(path);
+    Ok(())
+}
+
+/// 在文件名后追加后缀，得到同目录的兄弟路径。
+fn sibling_path(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(suffix);
+    path.with_file_name(name)
+}
+
+/// 写入文件并刷盘，保证 `rename` 之后读到的是完整内容。
+fn write_synced(path: &Path, bytes: &[u8]) -> std::io::Result<()> {
+    let mut file = File::create(path)?;
+    file.write_all(bytes)?;
+    file.sync_all()
+}
+
+/// 刷新目录项，使 `rename` 在掉电后仍然生效；失败不影响本次写入。
+fn sync_parent_dir(path: &Path) {
+    #[cfg(unix)]
+    if let Some(parent) = path.parent().filter(|dir| !dir.as_os_str().is_empty())
+        && let Ok(dir) = File::open(parent)
+    {
+        let _ = dir.sync_all();
+    }
+    #[cfg(not(unix))]
+    let _ = path;
 }
 
 /// 生成 relay 自身 token 签名种子。
@@ -64,3 +153,93 @@ pub(crate) fn generate_signing_key_seed() -> String {
         uuid::Uuid::new_v4().simple()
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, path::PathBuf};
+
+    use uuid::Uuid;
+    use yc_shared_protocol::{BaseDirs, DirsPlatform};
+
+    use super::{
+        BACKUP_SUFFIX, TMP_SUFFIX, default_auth_store_path, load_auth_store, persist_auth_store,
+        sibling_path,
+    };
+    use crate::api::types::AuthStore;
+
+    fn resolve(vars: &[(&str, &str)], account_home: Option<&str>) -> Result<PathBuf, String> {
+        let vars = vars
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect::<HashMap<_, _>>();
+        default_auth_store_path(&BaseDirs::resolve(
+            |key| vars.get(key).cloned(),
+            || account_home.map(PathBuf::from),
+            DirsPlatform::Unix,
+        ))
+    }
+
+    #[test]
+    fn default_path_lives_under_shared_config_dir() {
+        assert_eq!(
+            resolve(&[("HOME", "/home/a")], Some("/home/passwd")),
+            Ok(PathBuf::from(
+                "/home/a/.config/yourconnector/relay/auth-store.json"
+            ))
+        );
+        assert_eq!(
+            resolve(&[("XDG_CONFIG_HOME", "/xdg/config")], None),
+            Ok(PathBuf::from(
+                "/xdg/config/yourconnector/relay/auth-store.json"
+            ))
+        );
+    }
+
+    #[test]
+    fn default_path_errors_when_nothing_resolvable() {
+        let err = resolve(&[("XDG_CONFIG_HOME", "relative")], None)
+            .expect_err("should not fall back to cwd");
+        assert!(err.contains("YC_HOME_DIR"));
+        assert!(err.contains("RELAY_AUTH_STORE_PATH"));
+    }
+
+    #[test]
+    fn persist_keeps_rolling_backup_and_load_recovers_from_corruption() {
+        let dir = std::env::temp_dir().join(format!("yc-relay-store-{}", Uuid::new_v4()));
+        let path = dir.join("auth-store.json");
+        let backup = sibling_path(&path, BACKUP_SUFFIX);
+        let mut store = AuthStore::new("seed".to_string());
+        store.system_mut("sys_a");
+        persist_auth_store(&path, &store).expect("first persist");
+        assert!(!backup.exists(), "nothing to back up on first write");
+        store.system_mut("sys_b");
+        persist_auth_store(&path, &store).expect("second persist");
+        assert!(!sibling_path(&path, TMP_SUFFIX).exists());
+        assert_eq!(load_auth_store(&backup).expect("backup").systems.len(), 1);
+
+        // 模拟写入中途崩溃留下的半截主文件：回退到上一版备份，损坏文件留档。
+        std::fs::write(&path, b"{\"version\":1,\"signingKey\":").expect("corrupt");
+        let restored = load_auth_store(&path).expect("restore from backup");
+        assert_eq!(
+            restored
+                .signing_keys
+                .active()
+                .map(|key| key.secret.as_str()),
+            Some("seed")
+        );
+        assert_eq!(restored.systems.len(), 1);
+        let corrupt_copies = std::fs::read_dir(&dir)
+            .expect("read dir")
+            .filter_map(Result::ok)
+            .filter(|entry| entry.file_name().to_string_lossy().contains(".corrupt-"))
+            .count();
+        assert_eq!(corrupt_copies, 1);
+
+        // 主文件与备份都不可用时才报错。
+        std::fs::write(&path, b"not json").expect("corrupt primary");
+        std::fs::write(&backup, b"not json").expect("corrupt backup");
+        let err = load_auth_store(&path).expect_err("both copies unusable");
+        assert!(err.contains("restore from backup failed"));
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
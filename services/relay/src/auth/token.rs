@@ -5,56 +5,91 @@ use base64::{Engine as _, engine::general_purpose::URL_SAFE_NO_PAD};
 use hmac::{Hmac, Mac};
 use sha2::Sha256;
 use uuid::Uuid;
+use yc_shared_protocol::{ACCESS_TOKEN_PREFIX, decode_b64url};
 
 use crate::{
     api::{
         error::ApiError,
-        types::{AccessTokenClaims, PairTokenAuthDecision, REFRESH_TOKEN_TTL_SEC, RefreshSession},
+        types::{AccessTokenClaims, ClientRole, PairTokenAuthDecision, RefreshSession},
+    },
+    auth::{
+        pair_tokens::PairTokenSet,
+        signing_keys::{LEGACY_SIGNING_KEY_ID, SigningKeyRing},
+        store::unix_now,
+        token_crypto::hmac_b64url,
     },
-    auth::{store::unix_now, token_crypto::hmac_b64url},
 };
 
 pub(crate) use crate::auth::token_crypto::{
-    key_id_for_public_key, sha256_hex, verify_pop_signature,
+    key_id_for_public_key, pair_token_hash_matches, sha256_hex, verify_pop_signature,
 };
 
-/// pairToken 鉴权决策。
+/// sidecar 持已作废令牌连接时的拒绝原因。
+pub(crate) const PAIR_TOKEN_REVOKED_REASON: &str = "pairToken 已作废";
+
+/// pairToken 鉴权决策：app 接受当前令牌与宽限期内的上一个令牌；
+/// sidecar 只认当前令牌，持旧令牌重连时走轮换判定，但不会初始化或轮换为已作废的令牌。
 pub(crate) fn authorize_pair_token(
-    existing_pair_token: Option<&str>,
+    existing_pair_tokens: Option<&PairTokenSet>,
     active_client_count: usize,
-    client_type: &str,
+    role: ClientRole,
     incoming_pair_token: &str,
+    revoked_pair_token_hashes: &[String],
+    now: u64,
 ) -> Result<PairTokenAuthDecision, String> {
     if incoming_pair_token.trim().is_empty() {
         return Err("pairToken 不能为空".to_string());
     }
+    let revoked = || {
+        let hash = sha256_hex(incoming_pair_token);
+        revoked_pair_token_hashes.contains(&hash)
+    };
 
-    let Some(existing) = existing_pair_token else {
-        if client_type == "sidecar" {
+    let Some(existing) = existing_pair_tokens else {
+        if role == ClientRole::Sidecar {
+            if revoked() {
+                return Err(PAIR_TOKEN_REVOKED_REASON.to_string());
+            }
             return Ok(PairTokenAuthDecision::Initialize);
         }
         return Err("system 未注册，请先启动 sidecar 完成配对".to_string());
     };
 
-    if existing == incoming_pair_token {
+    let matched = if role == ClientRole::Sidecar {
+        existing.current() == incoming_pair_token
+    } else {
+        existing.accepts(incoming_pair_token, now)
+    };
+    if matched {
         return Ok(PairTokenAuthDecision::Allow);
     }
 
-    if client_type == "sidecar" && active_client_count == 0 {
+    if role == ClientRole::Sidecar && active_client_count == 0 {
+        if revoked() {
+            return Err(PAIR_TOKEN_REVOKED_REASON.to_string());
+        }
         return Ok(PairTokenAuthDecision::Rotate);
     }
 
     Err("pairToken 不匹配".to_string())
 }
 
-/// 生成 access token。
+/// 用当前签发密钥生成 access token，claims 记录签名密钥 kid。
 pub(crate) fn issue_access_token(
-    signing_key: &str,
+    signing_keys: &SigningKeyRing,
     system_id: &str,
     device_id: &str,
     key_id: &str,
     ttl_sec: u64,
 ) -> Result<String, ApiError> {
+    let Some(signing_key) = signing_keys.active() else {
+        return Err(ApiError::new(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "INTERNAL_ERROR",
+            "relay 签名密钥未初始化",
+            "请稍后重试",
+        ));
+    };
     let now = unix_now();
     let claims = AccessTokenClaims {
         sid: system_id.to_string(),
@@ -63,6 +98,7 @@ pub(crate) fn issue_access_token(
         iat: now,
         exp: now.saturating_add(ttl_sec),
         jti: Uuid::new_v4().simple().to_string(),
+        skid: signing_key.kid.clone(),
     };
     let payload = serde_json::to_string(&claims).map_err(|err| {
         ApiError::new(
@@ -73,14 +109,14 @@ pub(crate) fn issue_access_token(
         )
     })?;
     let payload_b64 = URL_SAFE_NO_PAD.encode(payload.as_bytes());
-    let sig_b64 = hmac_b64url(signing_key, payload_b64.as_bytes())?;
-    Ok(format!("yat_v1.{payload_b64}.{sig_b64}"))
+    let sig_b64 = hmac_b64url(&signing_key.secret, payload_b64.as_bytes())?;
+    Ok(format!("{ACCESS_TOKEN_PREFIX}.{payload_b64}.{sig_b64}"))
 }
 
-/// 校验 access token。
+/// 校验 access token：按 claims 中的签名密钥 kid 选择验签密钥，轮换重叠期内旧密钥签发的 token 仍有效。
 pub(crate) fn verify_access_token(
     token: &str,
-    signing_key: &str,
+    signing_keys: &SigningKeyRing,
     expected_system: &str,
     expected_device: &str,
     expected_key_id: &str,
@@ -89,7 +125,10 @@ pub(crate) fn verify_access_token(
     let version = parts.next().unwrap_or_default();
     let payload_b64 = parts.next().unwrap_or_default();
     let sig_b64 = parts.next().unwrap_or_default();
-    if version != "yat_v1" || payload_b64.is_empty() || sig_b64.is_empty() || parts.next().is_some()
+    if version != ACCESS_TOKEN_PREFIX
+        || payload_b64.is_empty()
+        || sig_b64.is_empty()
+        || parts.next().is_some()
     {
         return Err(ApiError::new(
             StatusCode::UNAUTHORIZED,
@@ -99,7 +138,7 @@ pub(crate) fn verify_access_token(
         ));
     }
 
-    let sig = URL_SAFE_NO_PAD.decode(sig_b64.as_bytes()).map_err(|_| {
+    let sig = decode_b64url(sig_b64).map_err(|_| {
         ApiError::new(
             StatusCode::UNAUTHORIZED,
             "ACCESS_TOKEN_INVALID",
@@ -108,40 +147,53 @@ pub(crate) fn verify_access_token(
         )
     })?;
 
-    type HmacSha256 = Hmac<Sha256>;
-    let mut mac = HmacSha256::new_from_slice(signing_key.as_bytes()).map_err(|_| {
+    // 先解出 claims 只为取签名密钥 kid，验签通过前不信任其余字段。
+    let payload_raw = decode_b64url(payload_b64).map_err(|_| {
         ApiError::new(
             StatusCode::UNAUTHORIZED,
             "ACCESS_TOKEN_INVALID",
-            "accessToken 签名器无效",
+            "accessToken payload 无效",
             "请重新配对",
         )
     })?;
-    mac.update(payload_b64.as_bytes());
-    mac.verify_slice(&sig).map_err(|_| {
+    let claims: AccessTokenClaims = serde_json::from_slice(&payload_raw).map_err(|_| {
         ApiError::new(
             StatusCode::UNAUTHORIZED,
             "ACCESS_TOKEN_INVALID",
-            "accessToken 签名校验失败",
+            "accessToken claims 无效",
             "请重新配对",
         )
     })?;
+    let signing_kid = if claims.skid.is_empty() {
+        LEGACY_SIGNING_KEY_ID
+    } else {
+        claims.skid.as_str()
+    };
+    let now = unix_now();
+    let Some(signing_key) = signing_keys.verification_secret(signing_kid, now) else {
+        return Err(ApiError::new(
+            StatusCode::UNAUTHORIZED,
+            "ACCESS_TOKEN_INVALID",
+            "accessToken 签名密钥已失效",
+            "请刷新凭证或重新配对",
+        ));
+    };
 
-    let payload_raw = URL_SAFE_NO_PAD
-        .decode(payload_b64.as_bytes())
-        .map_err(|_| {
-            ApiError::new(
-                StatusCode::UNAUTHORIZED,
-                "ACCESS_TOKEN_INVALID",
-                "accessToken payload 无效",
-                "请重新配对",
-            )
-        })?;
-    let claims: AccessTokenClaims = serde_json::from_slice(&payload_raw).map_err(|_| {
+    type HmacSha256 = Hmac<Sha256>;
+    let mut mac = HmacSha256::new_from_slice(signing_key.as_bytes()).map_err(|_| {
         ApiError::new(
             StatusCode::UNAUTHORIZED,
             "ACCESS_TOKEN_INVALID",
-            "accessToken claims 无效",
+            "accessToken 签名器无效",
+            "请重新配对",
+        )
+    })?;
+    mac.update(payload_b64.as_bytes());
+    mac.verify_slice(&sig).map_err(|_| {
+        ApiError::new(
+            StatusCode::UNAUTHORIZED,
+            "ACCESS_TOKEN_INVALID",
+            "accessToken 签名校验失败",
             "请重新配对",
         )
     })?;
@@ -158,7 +210,6 @@ pub(crate) fn verify_access_token(
         ));
     }
 
-    let now = unix_now();
     if claims.exp <= now {
         return Err(ApiError::new(
             StatusCode::UNAUTHORIZED,
@@ -171,12 +222,13 @@ pub(crate) fn verify_access_token(
     Ok(claims)
 }
 
-/// 生成 refresh 会话。
+/// 生成 refresh 会话（有效期 `ttl_sec` 秒）。
 pub(crate) fn issue_refresh_session(
     system_id: &str,
     device_id: &str,
     key_id: &str,
     credential_id: &str,
+    ttl_sec: u64,
 ) -> (String, RefreshSession) {
     let session_id = format!("rs_{}", Uuid::new_v4().simple());
     let secret = format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple());
@@ -191,7 +243,7 @@ pub(crate) fn issue_refresh_session(
             key_id: key_id.to_string(),
             credential_id: credential_id.to_string(),
             refresh_secret_hash: sha256_hex(&secret),
-            expires_at: unix_now().saturating_add(REFRESH_TOKEN_TTL_SEC),
+            expires_at: unix_now().saturating_add(ttl_sec),
             created_at: now,
             revoked_at: None,
             rotated_from: None,
@@ -215,3 +267,33 @@ pub(crate) fn parse_refresh_token(token: &str) -> Result<(String, String), ApiEr
     }
     Ok((session.to_string(), secret.to_string()))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{issue_access_token, verify_access_token};
+    use crate::auth::{signing_keys::SigningKeyRing, store::unix_now};
+
+    #[test]
+    fn tokens_signed_before_rotation_verify_until_previous_key_retires() {
+        let mut ring = SigningKeyRing::new("seed_old".to_string());
+        let old_token =
+            issue_access_token(&ring, "sys_a", "ios_a", "kid_a", 600).expect("issue old");
+        let verify = |ring: &SigningKeyRing, token: &str| {
+            verify_access_token(token, ring, "sys_a", "ios_a", "kid_a")
+        };
+
+        ring.rotate(unix_now(), 600);
+        let new_token =
+            issue_access_token(&ring, "sys_a", "ios_a", "kid_a", 600).expect("issue new");
+        assert!(verify(&ring, &old_token).is_ok());
+        let claims = verify(&ring, &new_token).expect("new token verifies");
+        assert_eq!(claims.skid, ring.active().expect("active").kid);
+
+        // 重叠期为 0 时上一把密钥立即退役，其签发的 token 不再有效。
+        let mut retired = ring.clone();
+        retired.rotate(unix_now(), 0);
+        let err = verify(&retired, &new_token).expect_err("retired key rejected");
+        assert_eq!(err.code, "ACCESS_TOKEN_INVALID");
+        assert!(verify(&SigningKeyRing::new("other".to_string()), &old_token).is_err());
+    }
+}
@@ -5,6 +5,8 @@ use base64::{Engine as _, engine::general_purpose::URL_SAFE_NO_PAD};
 use ed25519_dalek::{Signature, Verifier, VerifyingKey};
 use hmac::{Hmac, Mac};
 use sha2::{Digest, Sha256};
+use subtle::ConstantTimeEq;
+use yc_shared_protocol::{B64DecodeError, decode_b64url, decode_b64url_exact};
 
 use crate::api::error::ApiError;
 
@@ -14,40 +16,27 @@ pub(crate) fn verify_pop_signature(
     payload: &str,
     signature_b64: &str,
 ) -> Result<(), ApiError> {
-    let pk_raw = URL_SAFE_NO_PAD
-        .decode(public_key_b64.as_bytes())
-        .map_err(|_| {
-            ApiError::new(
-                StatusCode::UNAUTHORIZED,
-                "PAIR_PROOF_INVALID",
-                "设备公钥格式无效",
-                "请重新生成设备绑定信息",
-            )
-        })?;
-    let pk_bytes: [u8; 32] = pk_raw.try_into().map_err(|_| {
+    let pk_bytes = decode_b64url_exact::<32>(public_key_b64).map_err(|err| {
+        let message = match err {
+            B64DecodeError::InvalidEncoding => "设备公钥格式无效",
+            B64DecodeError::InvalidLength { .. } => "设备公钥长度无效",
+        };
         ApiError::new(
             StatusCode::UNAUTHORIZED,
             "PAIR_PROOF_INVALID",
-            "设备公钥长度无效",
+            message,
             "请重新生成设备绑定信息",
         )
     })?;
-
-    let sig_raw = URL_SAFE_NO_PAD
-        .decode(signature_b64.as_bytes())
-        .map_err(|_| {
-            ApiError::new(
-                StatusCode::UNAUTHORIZED,
-                "PAIR_PROOF_INVALID",
-                "签名格式无效",
-                "请重试",
-            )
-        })?;
-    let sig_bytes: [u8; 64] = sig_raw.try_into().map_err(|_| {
+    let sig_bytes = decode_b64url_exact::<64>(signature_b64).map_err(|err| {
+        let message = match err {
+            B64DecodeError::InvalidEncoding => "签名格式无效",
+            B64DecodeError::InvalidLength { .. } => "签名长度无效",
+        };
         ApiError::new(
             StatusCode::UNAUTHORIZED,
             "PAIR_PROOF_INVALID",
-            "签名长度无效",
+            message,
             "请重试",
         )
     })?;
@@ -76,16 +65,14 @@ pub(crate) fn verify_pop_signature(
 
 /// 计算 keyId。
 pub(crate) fn key_id_for_public_key(public_key_b64: &str) -> Result<String, ApiError> {
-    let pk_raw = URL_SAFE_NO_PAD
-        .decode(public_key_b64.as_bytes())
-        .map_err(|_| {
-            ApiError::new(
-                StatusCode::UNAUTHORIZED,
-                "PAIR_PROOF_INVALID",
-                "设备公钥格式无效",
-                "请重新生成设备绑定信息",
-            )
-        })?;
+    let pk_raw = decode_b64url(public_key_b64).map_err(|_| {
+        ApiError::new(
+            StatusCode::UNAUTHORIZED,
+            "PAIR_PROOF_INVALID",
+            "设备公钥格式无效",
+            "请重新生成设备绑定信息",
+        )
+    })?;
     let digest = Sha256::digest(pk_raw);
     Ok(format!("kid_{}", URL_SAFE_NO_PAD.encode(&digest[..10])))
 }
@@ -115,3 +102,10 @@ pub(crate) fn sha256_hex(value: &str) -> String {
     }
     out
 }
+
+/// 常量时间比较 pairToken 与已存哈希，避免比较耗时泄露匹配前缀。
+pub(crate) fn pair_token_hash_matches(stored_hash: Option<&str>, pair_token: &str) -> bool {
+    stored_hash.is_some_and(|stored| {
+        bool::from(stored.as_bytes().ct_eq(sha256_hex(pair_token).as_bytes()))
+    })
+}
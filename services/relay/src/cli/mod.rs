@@ -1,10 +1,15 @@
-//! relay CLI 分发：`run`、`status`、`doctor`、`service`、`version`。
+//! relay CLI 分发：`run`、`status`、`doctor`、`service`、`keys`、`version`。
 
 use std::process::Command;
 
 use anyhow::{anyhow, bail};
 use serde_json::json;
 
+use crate::auth::{
+    backend::auth_store_backend_from_env, config::AuthConfig, signing_keys::rotate_signing_keys,
+    store::unix_now,
+};
+
 /// CLI 分发结果。
 pub(crate) enum CliDispatch {
     /// 继续进入 relay 主循环。
@@ -48,6 +53,11 @@ pub(crate) fn dispatch(args: &[String]) -> anyhow::Result<CliDispatch> {
             run_service_action(action)?;
             Ok(CliDispatch::Exit)
         }
+        "keys" => {
+            let action = args.get(1).map(String::as_str).unwrap_or("");
+            run_keys_action(action)?;
+            Ok(CliDispatch::Exit)
+        }
         "version" => {
             println!("{}", env!("CARGO_PKG_VERSION"));
             Ok(CliDispatch::Exit)
@@ -85,6 +95,7 @@ fn run_doctor(format: DoctorFormat) {
     let active = service_active();
     let relay_addr = std::env::var("RELAY_ADDR").unwrap_or_else(|_| "0.0.0.0:18080".to_string());
     let public_ws = std::env::var("RELAY_PUBLIC_WS_URL").unwrap_or_default();
+    let auth_config = AuthConfig::from_env();
 
     match format {
         DoctorFormat::Text => {
@@ -92,6 +103,10 @@ fn run_doctor(format: DoctorFormat) {
             println!("service-active: {}", if active { "yes" } else { "no" });
             println!("relay-addr: {}", relay_addr);
             println!("relay-public-ws: {}", public_ws);
+            println!("access-ttl-sec: {}", auth_config.access_ttl_sec);
+            println!("refresh-ttl-sec: {}", auth_config.refresh_ttl_sec);
+            println!("pop-skew-sec: {}", auth_config.pop_skew_sec);
+            println!("pair-token-grace-sec: {}", auth_config.pair_token_grace_sec);
         }
         DoctorFormat::Json => {
             let payload = json!({
@@ -99,6 +114,7 @@ fn run_doctor(format: DoctorFormat) {
                 "serviceActive": active,
                 "relayAddr": relay_addr,
                 "relayPublicWsUrl": public_ws,
+                "authConfig": auth_config,
             });
             println!(
                 "{}",
@@ -132,6 +148,35 @@ fn run_service_action(action: &str) -> anyhow::Result<()> {
     }
 }
 
+/// 执行 keys rotate。
+fn run_keys_action(action: &str) -> anyhow::Result<()> {
+    match action {
+        "rotate" => rotate_keys(),
+        _ => Err(anyhow!("usage: yc-relay keys rotate")),
+    }
+}
+
+/// 轮换 access token 签名密钥；运行中的 relay 会以内存副本整体写回存储，覆盖本次轮换，
+/// 因此要求先停止服务，轮换后再启动。
+fn rotate_keys() -> anyhow::Result<()> {
+    if service_active() {
+        bail!("yc-relay is running; stop it first (`yc-relay service stop`) and rotate again");
+    }
+    let backend = auth_store_backend_from_env().map_err(anyhow::Error::msg)?;
+    let overlap_sec = AuthConfig::from_env().access_ttl_sec;
+    let now = unix_now();
+    let mut active_kid = String::new();
+    backend
+        .update(&mut |store| active_kid = rotate_signing_keys(store, overlap_sec, now))
+        .map_err(|err| anyhow!("rotate signing key failed: {err}"))?;
+    println!("signing key rotated: active kid {active_kid}");
+    println!(
+        "previous key retires at {} (after {overlap_sec}s)",
+        now.saturating_add(overlap_sec)
+    );
+    Ok(())
+}
+
 /// 服务管理器标识。
 fn service_manager() -> &'static str {
     if cfg!(target_os = "linux") {
@@ -227,5 +272,6 @@ fn print_root_help() {
     println!("  yc-relay status");
     println!("  yc-relay doctor [--format text|json]");
     println!("  yc-relay service <start|stop|restart|status>");
+    println!("  yc-relay keys rotate");
     println!("  yc-relay version");
 }
@@ -0,0 +1,135 @@
+//! CORS 配置：`RELAY_CORS_ORIGINS`（逗号分隔）限定允许的来源，未设置时沿用 `Any` 便于本地调试；
+//! 非法来源告警后跳过，允许的方法与请求头保持不变。
+
+use axum::http::{
+    HeaderValue, Method,
+    header::{AUTHORIZATION, CONTENT_TYPE},
+};
+use tower_http::cors::{AllowOrigin, Any, CorsLayer};
+use tracing::{info, warn};
+use url::Url;
+
+use crate::api::trace::TRACE_ID_HEADER;
+
+/// 允许的跨域来源列表环境变量。
+const RELAY_CORS_ORIGINS_ENV: &str = "RELAY_CORS_ORIGINS";
+
+/// 按环境变量构建 CORS 层。
+pub(crate) fn cors_layer_from_env() -> CorsLayer {
+    cors_layer(std::env::var(RELAY_CORS_ORIGINS_ENV).ok().as_deref())
+}
+
+/// 构建 CORS 层：配置了来源列表时只放行列表内来源，否则放行任意来源。
+fn cors_layer(raw_origins: Option<&str>) -> CorsLayer {
+    let allow_origin = match parse_cors_origins(raw_origins) {
+        Some(origins) => {
+            if origins.is_empty() {
+                warn!("{RELAY_CORS_ORIGINS_ENV} has no valid origin, cross-origin requests denied");
+            } else {
+                info!("cors origins restricted count={}", origins.len());
+            }
+            AllowOrigin::list(origins)
+        }
+        None => AllowOrigin::from(Any),
+    };
+    CorsLayer::new()
+        .allow_origin(allow_origin)
+        .allow_methods([Method::GET, Method::POST, Method::OPTIONS])
+        .allow_headers([CONTENT_TYPE, AUTHORIZATION, TRACE_ID_HEADER])
+        .expose_headers([TRACE_ID_HEADER])
+}
+
+/// 解析来源列表；未设置或全空白时返回 `None`（放行任意来源）。
+fn parse_cors_origins(raw: Option<&str>) -> Option<Vec<HeaderValue>> {
+    let raw = raw.map(str::trim).filter(|value| !value.is_empty())?;
+    let origins = raw
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .filter_map(|item| match normalize_origin(item) {
+            Some(origin) => Some(origin),
+            None => {
+                warn!("invalid {RELAY_CORS_ORIGINS_ENV} entry skipped: {item}");
+                None
+            }
+        })
+        .collect();
+    Some(origins)
+}
+
+/// 规范化单个来源：须为不带路径、查询与片段的 http(s) 地址，按浏览器 `Origin` 格式输出。
+fn normalize_origin(item: &str) -> Option<HeaderValue> {
+    let url = Url::parse(item).ok()?;
+    if !matches!(url.scheme(), "http" | "https")
+        || url.host_str().is_none()
+        || url.path() != "/"
+        || url.query().is_some()
+        || url.fragment().is_some()
+    {
+        return None;
+    }
+    HeaderValue::from_str(&url.origin().ascii_serialization()).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{Router, routing::get};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    use super::{cors_layer, parse_cors_origins};
+
+    /// 以给定 Origin 请求并返回响应头中的 `access-control-allow-origin`。
+    async fn allow_origin_for(addr: std::net::SocketAddr, origin: &str) -> Option<String> {
+        let mut stream = tokio::net::TcpStream::connect(addr).await.expect("connect");
+        let request = format!(
+            "GET /healthz HTTP/1.1\r\nHost: {addr}\r\nOrigin: {origin}\r\nConnection: close\r\n\r\n"
+        );
+        stream.write_all(request.as_bytes()).await.expect("write");
+        let mut raw = String::new();
+        stream.read_to_string(&mut raw).await.expect("read");
+        raw.split("\r\n\r\n")
+            .next()
+            .unwrap_or_default()
+            .lines()
+            .find_map(|line| {
+                let (name, value) = line.split_once(':')?;
+                name.eq_ignore_ascii_case("access-control-allow-origin")
+                    .then(|| value.trim().to_string())
+            })
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn disallowed_origin_is_rejected_while_allowlisted_one_passes() {
+        let origins = parse_cors_origins(Some(
+            " https://Panel.example.com/ , not a url, https://x.test/path, http://localhost:5173",
+        ))
+        .expect("restricted list");
+        assert_eq!(
+            origins,
+            vec!["https://panel.example.com", "http://localhost:5173"]
+        );
+        assert!(parse_cors_origins(Some("  ")).is_none());
+        assert!(parse_cors_origins(None).is_none());
+
+        let app = Router::new()
+            .route("/healthz", get(|| async { "ok" }))
+            .layer(cors_layer(Some("https://panel.example.com")));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("bind");
+        let addr = listener.local_addr().expect("local addr");
+        let server = tokio::spawn(async move {
+            axum::serve(listener, app).await.expect("serve");
+        });
+
+        assert_eq!(
+            allow_origin_for(addr, "https://panel.example.com").await,
+            Some("https://panel.example.com".to_string())
+        );
+        assert_eq!(
+            allow_origin_for(addr, "https://evil.example.com").await,
+            None
+        );
+        server.abort();
+    }
+}
@@ -0,0 +1,249 @@
+//! 调试接口：默认关闭，开启后可选管理令牌鉴权，并对连接快照做短缓存与条目上限，
+//! 避免高频轮询反复持有房间读锁、与广播路径争用。
+
+use std::{
+    collections::BTreeMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use axum::http::{HeaderMap, StatusCode};
+use serde::Serialize;
+
+use crate::{
+    api::error::ApiError,
+    auth::admin::{admin_token_from_env, verify_admin_token_with},
+    state::{AppState, RoomStats},
+};
+
+/// 调试接口开关环境变量（`1`/`true` 开启，默认关闭）。
+const RELAY_DEBUG_ENDPOINTS_ENV: &str = "RELAY_DEBUG_ENDPOINTS";
+/// 连接快照缓存时长。
+const DEBUG_SYSTEMS_CACHE_TTL: Duration = Duration::from_secs(2);
+/// 单次返回的 system 条目上限（按连接数降序保留）。
+const DEBUG_SYSTEMS_MAX_ENTRIES: usize = 200;
+
+/// 读取调试接口开关。
+pub(crate) fn debug_endpoints_enabled() -> bool {
+    std::env::var(RELAY_DEBUG_ENDPOINTS_ENV)
+        .map(|raw| matches!(raw.trim().to_ascii_lowercase().as_str(), "1" | "true"))
+        .unwrap_or(false)
+}
+
+/// 调试接口返回：各 system 在线连接数与超限帧丢弃数。
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct DebugSystemsData {
+    pub(crate) systems: BTreeMap<String, usize>,
+    /// 出现过超限帧丢弃的 system（仅包含已返回的条目）。
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub(crate) oversized_frames: BTreeMap<String, u64>,
+    pub(crate) total_systems: usize,
+    pub(crate) truncated: bool,
+}
+
+/// 连接快照缓存。
+#[derive(Debug, Default)]
+pub(crate) struct DebugSystemsCache {
+    /// 最近一次快照及生成时间。
+    entry: Mutex<Option<(Instant, DebugSystemsData)>>,
+}
+
+impl DebugSystemsCache {
+    /// 读取未过期的缓存快照。
+    fn get(&self, now: Instant) -> Option<DebugSystemsData> {
+        let guard = self.entry.lock().unwrap_or_else(|err| err.into_inner());
+        guard
+            .as_ref()
+            .filter(|(cached_at, _)| {
+                now.saturating_duration_since(*cached_at) < DEBUG_SYSTEMS_CACHE_TTL
+            })
+            .map(|(_, data)| data.clone())
+    }
+
+    /// 写入新快照。
+    fn put(&self, now: Instant, data: DebugSystemsData) {
+        let mut guard = self.entry.lock().unwrap_or_else(|err| err.into_inner());
+        *guard = Some((now, data));
+    }
+}
+
+impl AppState {
+    /// 校验调试接口开关与鉴权后返回连接快照。
+    pub(crate) async fn debug_systems(
+        &self,
+        headers: &HeaderMap,
+        enabled: bool,
+        admin_token: &str,
+    ) -> Result<DebugSystemsData, ApiError> {
+        if !enabled {
+            return Err(ApiError::new(
+                StatusCode::NOT_FOUND,
+                "DEBUG_ENDPOINTS_DISABLED",
+                "调试接口未开启",
+                "如需排障请设置 RELAY_DEBUG_ENDPOINTS=1 后重启 relay",
+            ));
+        }
+        // 配置了管理令牌时调试接口同样要求鉴权。
+        if !admin_token.is_empty() {
+            verify_admin_token_with(headers, admin_token)?;
+        }
+        Ok(self.debug_systems_at(Instant::now()).await)
+    }
+
+    /// 返回连接快照：缓存窗口内直接复用，不再加锁扫描房间。
+    async fn debug_systems_at(&self, now: Instant) -> DebugSystemsData {
+        if let Some(cached) = self.debug_cache.get(now) {
+            return cached;
+        }
+        let data = cap_debug_systems(self.snapshot().await, DEBUG_SYSTEMS_MAX_ENTRIES);
+        self.debug_cache.put(now, data.clone());
+        data
+    }
+}
+
+/// 按连接数降序保留前 `limit` 个 system。
+fn cap_debug_systems(
+    snapshot: impl IntoIterator<Item = (String, RoomStats)>,
+    limit: usize,
+) -> DebugSystemsData {
+    let mut entries = snapshot.into_iter().collect::<Vec<_>>();
+    let total_systems = entries.len();
+    entries.sort_by(|a, b| b.1.clients.cmp(&a.1.clients).then_with(|| a.0.cmp(&b.0)));
+    entries.truncate(limit);
+    let oversized_frames = entries
+        .iter()
+        .filter(|(_, stats)| stats.oversized_frames > 0)
+        .map(|(system_id, stats)| (system_id.clone(), stats.oversized_frames))
+        .collect();
+    DebugSystemsData {
+        systems: entries
+            .into_iter()
+            .map(|(system_id, stats)| (system_id, stats.clients))
+            .collect(),
+        oversized_frames,
+        total_systems,
+        truncated: total_systems > limit,
+    }
+}
+
+/// 调试接口入口参数：从环境读取开关与管理令牌。
+pub(crate) fn debug_gate_from_env() -> (bool, String) {
+    (debug_endpoints_enabled(), admin_token_from_env())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::HashMap,
+        time::{Duration, Instant},
+    };
+
+    use axum::http::HeaderMap;
+
+    use super::{DEBUG_SYSTEMS_CACHE_TTL, cap_debug_systems};
+    use crate::{
+        auth::pair_tokens::PairTokenSet,
+        state::{AppState, RoomStats, SystemRoom},
+    };
+
+    fn stats(clients: usize, oversized_frames: u64) -> RoomStats {
+        RoomStats {
+            clients,
+            oversized_frames,
+        }
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn disabled_debug_endpoint_is_gated() {
+        let state = AppState::for_test();
+        let err = state
+            .debug_systems(&HeaderMap::new(), false, "")
+            .await
+            .expect_err("disabled endpoint should reject");
+        assert_eq!(err.code, "DEBUG_ENDPOINTS_DISABLED");
+
+        let err = state
+            .debug_systems(&HeaderMap::new(), true, "admin_secret")
+            .await
+            .expect_err("admin token should be required when configured");
+        assert_eq!(err.code, "ADMIN_TOKEN_INVALID");
+        assert!(
+            state
+                .debug_systems(&HeaderMap::new(), true, "")
+                .await
+                .is_ok()
+        );
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn repeated_calls_within_window_use_cached_snapshot() {
+        let state = AppState::for_test();
+        let start = Instant::now();
+        let first = state.debug_systems_at(start).await;
+        assert!(first.systems.is_empty());
+
+        state.systems.write().await.insert(
+            "sys_a".to_string(),
+            SystemRoom {
+                pair_tokens: PairTokenSet::new(""),
+                ticket_nonces: HashMap::new(),
+                app_nonces: HashMap::new(),
+                clients: HashMap::new(),
+                oversized_frames: 0,
+            },
+        );
+        let cached = state
+            .debug_systems_at(start + Duration::from_millis(500))
+            .await;
+        assert!(cached.systems.is_empty());
+
+        let refreshed = state
+            .debug_systems_at(start + DEBUG_SYSTEMS_CACHE_TTL)
+            .await;
+        assert_eq!(refreshed.systems.get("sys_a"), Some(&0));
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn oversized_frames_are_counted_per_system() {
+        let state = AppState::for_test();
+        assert_eq!(state.record_oversized_frame("sys_missing").await, 0);
+        state.systems.write().await.insert(
+            "sys_a".to_string(),
+            SystemRoom {
+                pair_tokens: PairTokenSet::new(""),
+                ticket_nonces: HashMap::new(),
+                app_nonces: HashMap::new(),
+                clients: HashMap::new(),
+                oversized_frames: 0,
+            },
+        );
+        state.record_oversized_frame("sys_a").await;
+        assert_eq!(state.record_oversized_frame("sys_a").await, 2);
+
+        let data = state.debug_systems_at(Instant::now()).await;
+        assert_eq!(data.oversized_frames.get("sys_a"), Some(&2));
+        let value = serde_json::to_value(&data).expect("serialize debug data");
+        assert_eq!(value["oversizedFrames"]["sys_a"], 2);
+    }
+
+    #[test]
+    fn snapshot_is_capped_by_connection_count() {
+        let data = cap_debug_systems(
+            [
+                ("sys_a".to_string(), stats(1, 4)),
+                ("sys_b".to_string(), stats(5, 0)),
+                ("sys_c".to_string(), stats(3, 2)),
+            ],
+            2,
+        );
+        assert!(data.truncated);
+        assert_eq!(data.total_systems, 3);
+        assert_eq!(data.systems.keys().collect::<Vec<_>>(), ["sys_b", "sys_c"]);
+        // 被截断的 system 不出现在丢弃计数中，未丢弃的 system 也不输出。
+        assert_eq!(
+            data.oversized_frames.into_iter().collect::<Vec<_>>(),
+            [("sys_c".to_string(), 2)]
+        );
+    }
+}
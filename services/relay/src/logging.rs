@@ -1,5 +1,5 @@
 //! 日志系统模块职责：
-//! 1. 初始化 stdout + 文件双通道 tracing 日志。
+//! 1. 初始化 stdout + 文件双通道 tracing 日志（`LOG_FORMAT=json` 时两路均输出 JSON 行）。
 //! 2. 将运行日志按天落在 `logs/raw` 目录。
 //! 3. 将历史日期日志自动归档到 `logs/archive/<YYYY-MM-DD>.7z`。
 
@@ -20,6 +20,7 @@ use tracing_appender::non_blocking::WorkerGuard;
 use tracing_subscriber::{
     EnvFilter, Layer, filter::LevelFilter, layer::SubscriberExt, util::SubscriberInitExt,
 };
+use yc_shared_protocol::{LogFormat, json_log_layer};
 
 /// 默认日志根目录（相对当前工作目录）。
 const DEFAULT_LOG_DIR: &str = "logs";
@@ -63,17 +64,29 @@ pub(crate) fn init(service_name: &str) -> Result<LogRuntime> {
     let stdout_filter = resolve_stdout_env_filter();
     let file_filter = resolve_file_level_filter();
 
-    let stdout_layer = tracing_subscriber::fmt::layer()
-        .with_writer(stdout_writer)
-        .with_ansi(true)
-        .with_target(false)
-        .compact()
-        .with_filter(stdout_filter);
-    let file_layer = tracing_subscriber::fmt::layer()
-        .with_writer(file_writer)
-        .with_ansi(false)
-        .with_target(true)
-        .with_filter(file_filter);
+    let (stdout_layer, file_layer) = match LogFormat::from_env() {
+        LogFormat::Text => (
+            tracing_subscriber::fmt::layer()
+                .with_writer(stdout_writer)
+                .with_ansi(true)
+                .with_target(false)
+                .compact()
+                .with_filter(stdout_filter)
+                .boxed(),
+            tracing_subscriber::fmt::layer()
+                .with_writer(file_writer)
+                .with_ansi(false)
+                .with_target(true)
+                .with_filter(file_filter)
+                .boxed(),
+        ),
+        LogFormat::Json => (
+            json_log_layer(stdout_writer)
+                .with_filter(stdout_filter)
+                .boxed(),
+            json_log_layer(file_writer).with_filter(file_filter).boxed(),
+        ),
+    };
 
     tracing_subscriber::registry()
         .with(stdout_layer)
@@ -4,9 +4,13 @@ mod api;
 mod app;
 mod auth;
 mod cli;
+mod cors;
+mod debug;
 mod logging;
+mod metrics;
 mod pairing;
 mod state;
+mod tls;
 mod ws;
 
 #[tokio::main]
@@ -0,0 +1,233 @@
+//! Prometheus 指标：进程内计数器 + 手写文本格式输出（不引入额外依赖）。
+//! 默认不暴露，`RELAY_METRICS_ENABLED=1` 时注册 `GET /metrics`；配置管理令牌时同样要求 Bearer 鉴权。
+
+use std::{
+    collections::BTreeMap,
+    fmt::Write as _,
+    sync::{
+        Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+};
+
+use axum::http::HeaderMap;
+
+use crate::{
+    api::error::ApiError,
+    auth::admin::{admin_token_from_env, verify_admin_token_with},
+    state::{AppState, RoomStats},
+};
+
+/// 指标接口开关环境变量（`1`/`true` 开启，默认关闭）。
+const RELAY_METRICS_ENABLED_ENV: &str = "RELAY_METRICS_ENABLED";
+/// Prometheus 文本格式 Content-Type。
+pub(crate) const METRICS_CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// 读取指标接口开关。
+pub(crate) fn metrics_enabled() -> bool {
+    std::env::var(RELAY_METRICS_ENABLED_ENV)
+        .map(|raw| matches!(raw.trim().to_ascii_lowercase().as_str(), "1" | "true"))
+        .unwrap_or(false)
+}
+
+/// relay 进程级计数器（重启后归零）。
+#[derive(Debug, Default)]
+pub(crate) struct RelayMetrics {
+    /// WS 握手鉴权失败数（按错误码）；错误码为静态常量，基数有限。
+    auth_failures: Mutex<BTreeMap<&'static str, u64>>,
+    /// 配对换发成功数。
+    pair_exchanges: AtomicU64,
+    /// refresh 轮换成功数。
+    refresh_rotations: AtomicU64,
+    /// 设备吊销成功数。
+    revocations: AtomicU64,
+}
+
+impl RelayMetrics {
+    /// 记录一次 WS 握手鉴权失败。
+    pub(crate) fn record_auth_failure(&self, code: &'static str) {
+        let mut guard = self
+            .auth_failures
+            .lock()
+            .unwrap_or_else(|err| err.into_inner());
+        let count = guard.entry(code).or_default();
+        *count = count.saturating_add(1);
+    }
+
+    /// 记录一次配对换发成功。
+    pub(crate) fn record_pair_exchange(&self) {
+        self.pair_exchanges.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// 记录一次 refresh 轮换成功。
+    pub(crate) fn record_refresh_rotation(&self) {
+        self.refresh_rotations.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// 记录一次设备吊销成功。
+    pub(crate) fn record_revocation(&self) {
+        self.revocations.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// 按 Prometheus 文本格式输出计数器与房间快照。
+    fn render(&self, rooms: &BTreeMap<String, RoomStats>) -> String {
+        let mut out = String::new();
+        let total_clients = rooms.values().map(|stats| stats.clients).sum::<usize>();
+
+        write_header(
+            &mut out,
+            "yc_relay_connected_clients",
+            "gauge",
+            "在线 WS 连接总数",
+        );
+        let _ = writeln!(out, "yc_relay_connected_clients {total_clients}");
+
+        write_header(&mut out, "yc_relay_rooms", "gauge", "在线 system 房间数");
+        let _ = writeln!(out, "yc_relay_rooms {}", rooms.len());
+
+        write_header(
+            &mut out,
+            "yc_relay_room_clients",
+            "gauge",
+            "各 system 房间在线连接数",
+        );
+        for (system_id, stats) in rooms {
+            let _ = writeln!(
+                out,
+                "yc_relay_room_clients{{system_id=\"{}\"}} {}",
+                escape_label(system_id),
+                stats.clients
+            );
+        }
+
+        write_header(
+            &mut out,
+            "yc_relay_auth_failures_total",
+            "counter",
+            "WS 握手鉴权失败数（按错误码）",
+        );
+        let failures = self
+            .auth_failures
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .clone();
+        for (code, count) in failures {
+            let _ = writeln!(
+                out,
+                "yc_relay_auth_failures_total{{code=\"{}\"}} {count}",
+                escape_label(code)
+            );
+        }
+
+        for (name, help, counter) in [
+            (
+                "yc_relay_pair_exchanges_total",
+                "配对换发成功数",
+                &self.pair_exchanges,
+            ),
+            (
+                "yc_relay_refresh_rotations_total",
+                "refresh 轮换成功数",
+                &self.refresh_rotations,
+            ),
+            (
+                "yc_relay_device_revocations_total",
+                "设备吊销成功数",
+                &self.revocations,
+            ),
+        ] {
+            write_header(&mut out, name, "counter", help);
+            let _ = writeln!(out, "{name} {}", counter.load(Ordering::Relaxed));
+        }
+        out
+    }
+}
+
+/// 输出指标的 HELP/TYPE 行。
+fn write_header(out: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {name} {help}");
+    let _ = writeln!(out, "# TYPE {name} {kind}");
+}
+
+/// 转义标签值中的反斜杠、双引号与换行。
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+impl AppState {
+    /// 校验鉴权后输出 Prometheus 文本。
+    pub(crate) async fn render_metrics(&self, headers: &HeaderMap) -> Result<String, ApiError> {
+        let admin_token = admin_token_from_env();
+        if !admin_token.is_empty() {
+            verify_admin_token_with(headers, &admin_token)?;
+        }
+        let rooms = self
+            .snapshot()
+            .await
+            .into_iter()
+            .collect::<BTreeMap<_, _>>();
+        Ok(self.metrics.render(&rooms))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::{RelayMetrics, escape_label};
+    use crate::state::RoomStats;
+
+    #[test]
+    fn renders_counters_in_prometheus_text_format() {
+        let metrics = RelayMetrics::default();
+        metrics.record_auth_failure("ACCESS_TOKEN_EXPIRED");
+        metrics.record_auth_failure("ACCESS_TOKEN_EXPIRED");
+        metrics.record_auth_failure("PAIR_TOKEN_INVALID");
+        metrics.record_pair_exchange();
+        metrics.record_refresh_rotation();
+        metrics.record_refresh_rotation();
+        metrics.record_revocation();
+
+        let mut rooms = BTreeMap::new();
+        rooms.insert(
+            "sys_a".to_string(),
+            RoomStats {
+                clients: 2,
+                oversized_frames: 0,
+            },
+        );
+        rooms.insert(
+            "sys_b".to_string(),
+            RoomStats {
+                clients: 1,
+                oversized_frames: 0,
+            },
+        );
+        let text = metrics.render(&rooms);
+        for line in [
+            "# TYPE yc_relay_connected_clients gauge",
+            "yc_relay_connected_clients 3",
+            "yc_relay_rooms 2",
+            "yc_relay_room_clients{system_id=\"sys_a\"} 2",
+            "yc_relay_auth_failures_total{code=\"ACCESS_TOKEN_EXPIRED\"} 2",
+            "yc_relay_auth_failures_total{code=\"PAIR_TOKEN_INVALID\"} 1",
+            "# TYPE yc_relay_pair_exchanges_total counter",
+            "yc_relay_pair_exchanges_total 1",
+            "yc_relay_refresh_rotations_total 2",
+            "yc_relay_device_revocations_total 1",
+        ] {
+            assert!(
+                text.lines().any(|l| l == line),
+                "missing line: {line}\n{text}"
+            );
+        }
+    }
+
+    #[test]
+    fn label_values_are_escaped() {
+        assert_eq!(escape_label("a\"b\\c\nd"), "a\\\"b\\\\c\\nd");
+    }
+}
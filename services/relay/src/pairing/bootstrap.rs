@@ -1,7 +1,5 @@
 //! 配对链接签发与 banner 输出。
 
-use url::Url;
-
 use crate::{
     api::types::{
         ANSI_BOLD, ANSI_CYAN, ANSI_RESET, ANSI_WHITE, DEFAULT_PAIR_TICKET_TTL_SEC,
@@ -9,14 +7,25 @@ use crate::{
     },
     pairing::ticket::generate_pairing_ticket,
 };
+use yc_shared_protocol::PairingLink;
 
 /// Relay 用于展示配对链接的公开 WS 地址。
 pub(crate) fn relay_public_ws_url() -> String {
-    let from_env = std::env::var("RELAY_PUBLIC_WS_URL")
-        .ok()
+    resolve_public_ws_url(
+        std::env::var("RELAY_PUBLIC_WS_URL").ok(),
+        crate::tls::tls_enabled(),
+    )
+}
+
+/// 显式配置（`ws://` 或 `wss://`）原样使用；未配置时按是否启用原生 TLS 选择默认 scheme。
+fn resolve_public_ws_url(from_env: Option<String>, tls_enabled: bool) -> String {
+    let from_env = from_env
         .map(|v| v.trim().to_string())
         .filter(|v| !v.is_empty());
-    from_env.unwrap_or_else(|| "ws://127.0.0.1:18080/v1/ws".to_string())
+    from_env.unwrap_or_else(|| {
+        let scheme = if tls_enabled { "wss" } else { "ws" };
+        format!("{scheme}://127.0.0.1:18080/v1/ws")
+    })
 }
 
 /// 归一化宿主机名称。
@@ -45,20 +54,11 @@ pub(crate) fn build_pair_bootstrap_data(
     let pair_ticket = generate_pairing_ticket(system_id, pair_token, ttl_sec);
     let pair_code = format!("{system_id}.{pair_token}");
 
-    let mut link = Url::parse("yc://pair").expect("pairing link base must be valid");
-    {
-        let mut pairs = link.query_pairs_mut();
-        pairs.append_pair("relay", relay_ws_url);
-        pairs.append_pair("sid", system_id);
-        pairs.append_pair("ticket", &pair_ticket);
-        if !host_name.trim().is_empty() {
-            pairs.append_pair("name", host_name.trim());
-        }
-        if include_code {
-            pairs.append_pair("code", &pair_code);
-        }
+    let mut link = PairingLink::build(relay_ws_url, system_id, &pair_ticket, Some(host_name));
+    if include_code {
+        link = link.with_code(pair_code.clone());
     }
-    let pair_link = link.to_string();
+    let pair_link = link.to_link();
 
     PairBootstrapData {
         pair_link: pair_link.clone(),
@@ -128,7 +128,7 @@ pub(crate) fn print_pairing_banner_from_relay(data: &PairBootstrapData) {
 
 #[cfg(test)]
 mod tests {
-    use super::{build_pair_bootstrap_data, normalize_ttl_sec};
+    use super::{build_pair_bootstrap_data, normalize_ttl_sec, resolve_public_ws_url};
 
     #[test]
     fn bootstrap_data_contains_ticket_and_link() {
@@ -158,4 +158,20 @@ mod tests {
         assert_eq!(normalize_ttl_sec(Some(6000)), 3600);
         assert_eq!(normalize_ttl_sec(Some(180)), 180);
     }
+
+    #[test]
+    fn public_ws_url_follows_tls_when_not_configured() {
+        assert_eq!(
+            resolve_public_ws_url(None, false),
+            "ws://127.0.0.1:18080/v1/ws"
+        );
+        assert_eq!(
+            resolve_public_ws_url(Some(" ".to_string()), true),
+            "wss://127.0.0.1:18080/v1/ws"
+        );
+        assert_eq!(
+            resolve_public_ws_url(Some("wss://relay.example.com/v1/ws".to_string()), false),
+            "wss://relay.example.com/v1/ws"
+        );
+    }
 }
@@ -7,6 +7,7 @@ use crate::{
         error::ApiError,
         types::{PairBootstrapData, PairBootstrapRequest},
     },
+    auth::store::unix_now,
     pairing::bootstrap::{
         build_pair_bootstrap_data, normalize_host_name, normalize_ttl_sec, relay_public_ws_url,
     },
@@ -47,7 +48,7 @@ impl AppState {
                 "请先启动 sidecar",
             ));
         }
-        if room.pair_token != pair_token {
+        if !room.pair_tokens.accepts(pair_token, unix_now()) {
             return Err(ApiError::new(
                 StatusCode::UNAUTHORIZED,
                 "PAIR_TOKEN_MISMATCH",
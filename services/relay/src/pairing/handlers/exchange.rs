@@ -5,23 +5,47 @@ use axum::http::StatusCode;
 use crate::{
     api::{
         error::ApiError,
-        types::{PairExchangeData, PairExchangeRequest},
+        types::{PairExchangeData, PairExchangeRequest, SystemAuthState},
     },
     auth::{
+        audit::{AuditAction, AuditRecord},
         pop::pair_exchange_payload,
-        store::persist_auth_store,
         token::{
             issue_access_token, issue_refresh_session, key_id_for_public_key, verify_pop_signature,
         },
     },
+    pairing::rate_limit::PairClientIp,
     state::AppState,
 };
 
 impl AppState {
-    /// 配对换发设备凭证（消费票据）。
+    /// 配对换发设备凭证（消费票据，按来源 IP 与 system 限流），结果写入审计日志。
     pub(crate) async fn exchange_device_credential(
         &self,
         req: &PairExchangeRequest,
+        client_ip: PairClientIp,
+    ) -> Result<PairExchangeData, ApiError> {
+        let result = match self.check_pair_rate(client_ip, &req.system_id) {
+            Ok(()) => self.apply_pair_exchange(req).await,
+            Err(err) => Err(err),
+        };
+        self.audit.record_result(
+            AuditRecord {
+                action: AuditAction::PairExchange,
+                system_id: req.system_id.trim(),
+                device_id: req.device_id.trim(),
+                key_id: req.key_id.trim(),
+                target_device_id: None,
+            },
+            &result,
+        );
+        result
+    }
+
+    /// 配对换发主流程。
+    async fn apply_pair_exchange(
+        &self,
+        req: &PairExchangeRequest,
     ) -> Result<PairExchangeData, ApiError> {
         let system_id = req.system_id.trim();
         let device_id = req.device_id.trim();
@@ -73,9 +97,22 @@ impl AppState {
         }
 
         let mut store = self.auth_store.write().await;
-        let signing_key = store.signing_key.clone();
+        let signing_keys = store.signing_keys.clone();
         let system = store.system_mut(system_id);
 
+        // 同一公钥只允许绑定一个有效设备，拦截凭证克隆/共享。
+        if let Some(bound_device_id) = find_key_bound_device(system, device_id, key_id, pubkey) {
+            tracing::warn!(
+                "pair exchange rejected: key already bound system_id={system_id} device_id={device_id} bound_device_id={bound_device_id}"
+            );
+            return Err(ApiError::new(
+                StatusCode::CONFLICT,
+                "KEY_ALREADY_BOUND",
+                "该设备公钥已绑定到其他设备",
+                "请在新设备上重新生成设备密钥后再配对",
+            ));
+        }
+
         let now_text = yc_shared_protocol::now_rfc3339_nanos();
         let device_name = normalize_device_name(&req.device_name, device_id);
         let credential_id = format!("crd_{}", uuid::Uuid::new_v4().simple());
@@ -91,23 +128,29 @@ impl AppState {
                 created_at: now_text.clone(),
                 last_seen_at: now_text,
                 revoked_at: None,
+                last_auth_failure: None,
             },
         );
 
         let access_token = issue_access_token(
-            &signing_key,
+            &signing_keys,
             system_id,
             device_id,
             key_id,
-            crate::api::types::ACCESS_TOKEN_TTL_SEC,
+            self.auth_config.access_ttl_sec,
         )?;
-        let (refresh_token, refresh_session) =
-            issue_refresh_session(system_id, device_id, key_id, &credential_id);
+        let (refresh_token, refresh_session) = issue_refresh_session(
+            system_id,
+            device_id,
+            key_id,
+            &credential_id,
+            self.auth_config.refresh_ttl_sec,
+        );
         system
             .refresh_sessions
             .insert(refresh_session.session_id.clone(), refresh_session);
 
-        persist_auth_store(&self.auth_store_path, &store).map_err(|err| {
+        self.auth_backend.persist(&store).map_err(|err| {
             ApiError::new(
                 StatusCode::INTERNAL_SERVER_ERROR,
                 "INTERNAL_ERROR",
@@ -116,23 +159,102 @@ impl AppState {
             )
         })?;
 
+        self.metrics.record_pair_exchange();
         Ok(PairExchangeData {
             auth_mode,
             access_token,
             refresh_token,
             key_id: key_id.to_string(),
             credential_id,
-            access_expires_in_sec: crate::api::types::ACCESS_TOKEN_TTL_SEC,
-            refresh_expires_in_sec: crate::api::types::REFRESH_TOKEN_TTL_SEC,
+            access_expires_in_sec: self.auth_config.access_ttl_sec,
+            refresh_expires_in_sec: self.auth_config.refresh_ttl_sec,
         })
     }
 }
 
-/// 归一化设备名称。
-fn normalize_device_name(raw: &str, fallback: &str) -> String {
+/// 归一化设备名称：去除首尾空白、截断到 64 字符，空名称回退为 `fallback`。
+pub(crate) fn normalize_device_name(raw: &str, fallback: &str) -> String {
     let normalized = raw.trim();
     if normalized.is_empty() {
         return fallback.to_string();
     }
     normalized.chars().take(64).collect()
 }
+
+/// 查找已绑定同一公钥（或 keyId）的其他未吊销设备；被标记为 COMPROMISED 的公钥同样不可复用。
+fn find_key_bound_device<'a>(
+    system: &'a SystemAuthState,
+    device_id: &str,
+    key_id: &str,
+    public_key: &str,
+) -> Option<&'a str> {
+    system
+        .devices
+        .values()
+        .find(|device| {
+            device.device_id != device_id
+                && device.status != "REVOKED"
+                && (device.key_id == key_id || device.public_key == public_key)
+        })
+        .map(|device| device.device_id.as_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::find_key_bound_device;
+    use crate::{api::types::SystemAuthState, auth::handlers::test_support::device};
+
+    #[test]
+    fn key_bound_to_other_active_device_is_detected() {
+        let mut system = SystemAuthState::default();
+        system.devices.insert(
+            "ios_a".to_string(),
+            device("ios_a", "kid_a", "pub_a", "ACTIVE"),
+        );
+
+        let conflict = find_key_bound_device(&system, "ios_b", "kid_a", "pub_a");
+        assert_eq!(conflict, Some("ios_a"));
+    }
+
+    #[test]
+    fn repairing_same_device_with_own_key_is_allowed() {
+        let mut system = SystemAuthState::default();
+        system.devices.insert(
+            "ios_a".to_string(),
+            device("ios_a", "kid_a", "pub_a", "ACTIVE"),
+        );
+
+        assert_eq!(
+            find_key_bound_device(&system, "ios_a", "kid_a", "pub_a"),
+            None
+        );
+    }
+
+    #[test]
+    fn key_of_compromised_device_cannot_be_rebound() {
+        let mut system = SystemAuthState::default();
+        system.devices.insert(
+            "ios_a".to_string(),
+            device("ios_a", "kid_a", "pub_a", "COMPROMISED"),
+        );
+
+        assert_eq!(
+            find_key_bound_device(&system, "ios_b", "kid_a", "pub_a"),
+            Some("ios_a")
+        );
+    }
+
+    #[test]
+    fn key_of_revoked_device_can_be_rebound() {
+        let mut system = SystemAuthState::default();
+        system.devices.insert(
+            "ios_a".to_string(),
+            device("ios_a", "kid_a", "pub_a", "REVOKED"),
+        );
+
+        assert_eq!(
+            find_key_bound_device(&system, "ios_b", "kid_a", "pub_a"),
+            None
+        );
+    }
+}
@@ -7,18 +7,21 @@ use crate::{
         response::{ApiEnvelope, ok_response},
         types::{
             PairBootstrapData, PairBootstrapRequest, PairExchangeData, PairExchangeRequest,
-            PairPreflightData, PairPreflightRequest,
+            PairPreflightData, PairPreflightRequest, PairRevokeTokenData, PairRevokeTokenRequest,
+            PairTicketVerifyData, PairTicketVerifyRequest,
         },
     },
+    pairing::rate_limit::PairClientIp,
     state::AppState,
 };
 
 /// 配对预检接口：用于移动端精确映射失败弹窗。
 pub(crate) async fn pair_preflight_handler(
     State(state): State<AppState>,
+    client_ip: PairClientIp,
     Json(req): Json<PairPreflightRequest>,
 ) -> (StatusCode, Json<ApiEnvelope<PairPreflightData>>) {
-    match state.preflight_pair_credentials(&req).await {
+    match state.preflight_pair_credentials(&req, client_ip).await {
         Ok(mode) => ok_response(
             StatusCode::OK,
             "配对信息可用",
@@ -35,6 +38,37 @@ pub(crate) async fn pair_preflight_handler(
                     message: body.0.message,
                     suggestion: body.0.suggestion,
                     data: None,
+                    trace_id: body.0.trace_id,
+                }),
+            )
+        }
+    }
+}
+
+/// 配对票据试校验接口：不消费票据，返回剩余有效秒数供 App 倒计时。
+pub(crate) async fn pair_ticket_verify_handler(
+    State(state): State<AppState>,
+    client_ip: PairClientIp,
+    Json(req): Json<PairTicketVerifyRequest>,
+) -> (StatusCode, Json<ApiEnvelope<PairTicketVerifyData>>) {
+    match state.dry_run_pair_ticket(&req, client_ip).await {
+        Ok(data) => ok_response(
+            StatusCode::OK,
+            "配对票据有效",
+            "请在票据过期前完成配对",
+            Some(data),
+        ),
+        Err(err) => {
+            let (status, body) = err.into_response();
+            (
+                status,
+                Json(ApiEnvelope {
+                    ok: body.0.ok,
+                    code: body.0.code,
+                    message: body.0.message,
+                    suggestion: body.0.suggestion,
+                    data: None,
+                    trace_id: body.0.trace_id,
                 }),
             )
         }
@@ -44,9 +78,10 @@ pub(crate) async fn pair_preflight_handler(
 /// 配对换发接口：绑定设备公钥并签发 access/refresh。
 pub(crate) async fn pair_exchange_handler(
     State(state): State<AppState>,
+    client_ip: PairClientIp,
     Json(req): Json<PairExchangeRequest>,
 ) -> (StatusCode, Json<ApiEnvelope<PairExchangeData>>) {
-    let result = state.exchange_device_credential(&req).await;
+    let result = state.exchange_device_credential(&req, client_ip).await;
     match result {
         Ok(data) => ok_response(
             StatusCode::OK,
@@ -64,18 +99,25 @@ pub(crate) async fn pair_exchange_handler(
                     message: body.0.message,
                     suggestion: body.0.suggestion,
                     data: None,
+                    trace_id: body.0.trace_id,
                 }),
             )
         }
     }
 }
 
-/// 配对签发接口：统一 sidecar 与脚本的配对链接来源。
+/// 配对签发接口：统一 sidecar 与脚本的配对链接来源；对外接口与配对尝试共用限流桶，
+/// 防止借此暴力猜测 pairToken（sidecar 接入时的内部签发不受限）。
 pub(crate) async fn pair_bootstrap_handler(
     State(state): State<AppState>,
+    client_ip: PairClientIp,
     Json(req): Json<PairBootstrapRequest>,
 ) -> (StatusCode, Json<ApiEnvelope<PairBootstrapData>>) {
-    match state.issue_pair_bootstrap(&req).await {
+    let result = match state.check_pair_rate(client_ip, &req.system_id) {
+        Ok(()) => state.issue_pair_bootstrap(&req).await,
+        Err(err) => Err(err),
+    };
+    match result {
         Ok(data) => ok_response(
             StatusCode::OK,
             "配对信息已签发",
@@ -92,6 +134,36 @@ pub(crate) async fn pair_bootstrap_handler(
                     message: body.0.message,
                     suggestion: body.0.suggestion,
                     data: None,
+                    trace_id: body.0.trace_id,
+                }),
+            )
+        }
+    }
+}
+
+/// 配对令牌作废接口：已绑定设备远程作废泄露的配对码。
+pub(crate) async fn pair_revoke_token_handler(
+    State(state): State<AppState>,
+    Json(req): Json<PairRevokeTokenRequest>,
+) -> (StatusCode, Json<ApiEnvelope<PairRevokeTokenData>>) {
+    match state.revoke_pair_token(&req).await {
+        Ok(data) => ok_response(
+            StatusCode::OK,
+            "配对令牌已作废",
+            "已展示的配对码与二维码均已失效",
+            Some(data),
+        ),
+        Err(err) => {
+            let (status, body) = err.into_response();
+            (
+                status,
+                Json(ApiEnvelope {
+                    ok: body.0.ok,
+                    code: body.0.code,
+                    message: body.0.message,
+                    suggestion: body.0.suggestion,
+                    data: None,
+                    trace_id: body.0.trace_id,
                 }),
             )
         }
@@ -4,6 +4,11 @@ mod bootstrap;
 mod exchange;
 mod http;
 mod preflight;
+mod revoke_token;
 mod ticket;
 
-pub(crate) use http::{pair_bootstrap_handler, pair_exchange_handler, pair_preflight_handler};
+pub(crate) use exchange::normalize_device_name;
+pub(crate) use http::{
+    pair_bootstrap_handler, pair_exchange_handler, pair_preflight_handler,
+    pair_revoke_token_handler, pair_ticket_verify_handler,
+};
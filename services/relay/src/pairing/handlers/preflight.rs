@@ -7,14 +7,16 @@ use crate::{
         error::ApiError,
         types::{PairAuthMode, PairPreflightRequest},
     },
+    pairing::rate_limit::PairClientIp,
     state::AppState,
 };
 
 impl AppState {
-    /// 配对预检（不消费票据）。
+    /// 配对预检（不消费票据，与换发共用限流桶）。
     pub(crate) async fn preflight_pair_credentials(
         &self,
         req: &PairPreflightRequest,
+        client_ip: PairClientIp,
     ) -> Result<PairAuthMode, ApiError> {
         let system_id = req.system_id.trim();
         let device_id = req.device_id.trim();
@@ -26,6 +28,7 @@ impl AppState {
                 "请检查配对信息",
             ));
         }
+        self.check_pair_rate(client_ip, system_id)?;
 
         let pair_token = req.pair_token.as_deref().unwrap_or_default().trim();
         if !pair_token.is_empty() {
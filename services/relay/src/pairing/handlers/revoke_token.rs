@@ -0,0 +1,318 @@
+//! 配对令牌作废逻辑：已绑定设备远程烧毁泄露的配对码。
+
+use axum::http::StatusCode;
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::{
+    api::{
+        error::ApiError,
+        types::{PairRevokeTokenData, PairRevokeTokenRequest},
+    },
+    auth::{
+        audit::{AuditAction, AuditRecord},
+        pop::{pair_revoke_token_payload, parse_ts, verify_ts_window},
+        store::unix_now,
+        token::sha256_hex,
+    },
+    state::AppState,
+};
+
+/// 每个 system 保留的已作废令牌哈希数量上限。
+const MAX_REVOKED_PAIR_TOKEN_HASHES: usize = 16;
+
+impl AppState {
+    /// 作废 system 当前配对令牌，结果写入审计日志。
+    pub(crate) async fn revoke_pair_token(
+        &self,
+        req: &PairRevokeTokenRequest,
+    ) -> Result<PairRevokeTokenData, ApiError> {
+        let result = self.apply_pair_token_revoke(req).await;
+        self.audit.record_result(
+            AuditRecord {
+                action: AuditAction::PairRevokeToken,
+                system_id: req.system_id.trim(),
+                device_id: req.device_id.trim(),
+                key_id: req.key_id.trim(),
+                target_device_id: None,
+            },
+            &result,
+        );
+        result
+    }
+
+    /// 作废主流程：轮换为随机新令牌（不保留宽限期）、清空票据 nonce、登记被作废令牌的哈希，
+    /// 并通知在线 sidecar 换新令牌；sidecar 持被作废令牌重连时不会再被轮换回去。
+    async fn apply_pair_token_revoke(
+        &self,
+        req: &PairRevokeTokenRequest,
+    ) -> Result<PairRevokeTokenData, ApiError> {
+        let system_id = req.system_id.trim();
+        let device_id = req.device_id.trim();
+        let key_id = req.key_id.trim();
+        if system_id.is_empty() || device_id.is_empty() || key_id.is_empty() {
+            return Err(ApiError::new(
+                StatusCode::BAD_REQUEST,
+                "MISSING_CREDENTIALS",
+                "作废参数不完整",
+                "请检查输入后重试",
+            ));
+        }
+
+        let ts = parse_ts(&req.ts, "ACCESS_SIGNATURE_EXPIRED", "签名时间戳无效")?;
+        verify_ts_window(
+            ts,
+            self.auth_config.pop_skew_sec,
+            "ACCESS_SIGNATURE_EXPIRED",
+            "签名时间窗已过期",
+        )?;
+        self.consume_auth_nonce("pair-revoke-token", &req.nonce, ts)
+            .await?;
+
+        let payload = pair_revoke_token_payload(system_id, device_id, key_id, ts, &req.nonce);
+        self.verify_access_http(
+            system_id,
+            device_id,
+            key_id,
+            &req.access_token,
+            &payload,
+            &req.sig,
+        )
+        .await?;
+
+        let mut guard = self.systems.write().await;
+        let Some(room) = guard.get_mut(system_id) else {
+            return Err(ApiError::new(
+                StatusCode::UNAUTHORIZED,
+                "SYSTEM_NOT_REGISTERED",
+                "宿主机未在线",
+                "请先启动 sidecar",
+            ));
+        };
+        let now = unix_now();
+        let revoked_hashes = room
+            .pair_tokens
+            .valid_tokens(now)
+            .map(sha256_hex)
+            .collect::<Vec<_>>();
+        room.pair_tokens
+            .rotate(format!("ptk_{}", Uuid::new_v4().simple()), 0, now);
+        room.ticket_nonces.clear();
+        let pair_tokens = room.pair_tokens.clone();
+        drop(guard);
+        self.persist_pair_token_meta(system_id, &pair_tokens).await;
+        let pair_token_updated_at = self
+            .record_revoked_pair_tokens(system_id, revoked_hashes)
+            .await;
+        self.notify_pair_token_revoked(system_id).await;
+        Ok(PairRevokeTokenData {
+            pair_token_updated_at,
+        })
+    }
+
+    /// 登记被作废令牌的哈希（只保留最近若干个）并落盘，返回令牌更新时间。
+    async fn record_revoked_pair_tokens(&self, system_id: &str, hashes: Vec<String>) -> String {
+        let mut store = self.auth_store.write().await;
+        let system = store.system_mut(system_id);
+        for hash in hashes {
+            if !system.revoked_pair_token_hashes.contains(&hash) {
+                system.revoked_pair_token_hashes.push(hash);
+            }
+        }
+        let overflow = system
+            .revoked_pair_token_hashes
+            .len()
+            .saturating_sub(MAX_REVOKED_PAIR_TOKEN_HASHES);
+        system.revoked_pair_token_hashes.drain(..overflow);
+        let pair_token_updated_at = system.pair_token_updated_at.clone().unwrap_or_default();
+        if let Err(err) = self.auth_backend.persist(&store) {
+            warn!("persist revoked pair tokens failed: {err}");
+        }
+        pair_token_updated_at
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::HashMap,
+        sync::{Arc, atomic::AtomicU64},
+    };
+
+    use axum::extract::ws::Message;
+    use base64::{Engine as _, engine::general_purpose::URL_SAFE_NO_PAD};
+    use ed25519_dalek::{Signer, SigningKey};
+    use tokio::sync::{RwLock, mpsc};
+    use uuid::Uuid;
+
+    use crate::{
+        api::types::{AuthStore, ClientRole, PairRevokeTokenRequest, WsQuery},
+        auth::{
+            handlers::test_support::device,
+            pair_tokens::PairTokenSet,
+            pop::pair_revoke_token_payload,
+            store::unix_now,
+            token::{issue_access_token, sha256_hex},
+        },
+        state::{AppState, ClientHandle, RelayWriteCommand, SystemRoom},
+        ws::revocation::PAIR_TOKEN_REVOKED_EVENT,
+    };
+
+    /// 构造 sidecar 以给定 pairToken 连接的握手参数。
+    fn sidecar_query(pair_token: &str) -> WsQuery {
+        WsQuery {
+            system_id: "sys_a".to_string(),
+            client_type: "sidecar".to_string(),
+            device_id: "sc_a".to_string(),
+            pair_token: pair_token.to_string(),
+            pair_ticket: None,
+            host_name: None,
+            access_token: None,
+            key_id: None,
+            ts: None,
+            nonce: None,
+            sig: None,
+            protocol_version: None,
+        }
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn revoke_rotates_token_and_clears_ticket_nonces() {
+        let device_key = SigningKey::from_bytes(&[9u8; 32]);
+        let mut store = AuthStore::new("seed".to_string());
+        store.system_mut("sys_a").devices.insert(
+            "ios_a".to_string(),
+            device(
+                "ios_a",
+                "kid_a",
+                &URL_SAFE_NO_PAD.encode(device_key.verifying_key().to_bytes()),
+                "ACTIVE",
+            ),
+        );
+        let access_token = issue_access_token(&store.signing_keys, "sys_a", "ios_a", "kid_a", 600)
+            .expect("issue access token");
+        let mut pair_tokens = PairTokenSet::new("ptk_before");
+        pair_tokens.rotate("ptk_leaked", 600, unix_now());
+        let (sidecar_tx, mut sidecar_rx) = mpsc::channel(4);
+        let sidecar_id = Uuid::new_v4();
+        let sidecar = ClientHandle {
+            role: ClientRole::Sidecar,
+            device_id: "sc_a".to_string(),
+            seq: 0,
+            sender: sidecar_tx,
+            drop_count: Arc::new(AtomicU64::new(0)),
+            disconnect: Default::default(),
+        };
+        let mut rooms = HashMap::new();
+        rooms.insert(
+            "sys_a".to_string(),
+            SystemRoom {
+                pair_tokens,
+                ticket_nonces: HashMap::from([("nonce_a".to_string(), u64::MAX)]),
+                app_nonces: HashMap::new(),
+                clients: HashMap::from([(sidecar_id, sidecar)]),
+                oversized_frames: 0,
+            },
+        );
+        let state = AppState {
+            systems: Arc::new(RwLock::new(rooms)),
+            auth_store: Arc::new(RwLock::new(store)),
+            ..AppState::for_test()
+        };
+
+        let ts = unix_now();
+        let payload = pair_revoke_token_payload("sys_a", "ios_a", "kid_a", ts, "n1");
+        let req = PairRevokeTokenRequest {
+            system_id: "sys_a".to_string(),
+            device_id: "ios_a".to_string(),
+            access_token,
+            key_id: "kid_a".to_string(),
+            ts: ts.to_string(),
+            nonce: "n1".to_string(),
+            sig: URL_SAFE_NO_PAD.encode(device_key.sign(payload.as_bytes()).to_bytes()),
+        };
+        let data = state.revoke_pair_token(&req).await.expect("revoke token");
+
+        let rooms = state.systems.read().await;
+        let room = rooms.get("sys_a").expect("room kept");
+        let now = unix_now();
+        assert!(!room.pair_tokens.accepts("ptk_leaked", now));
+        assert!(!room.pair_tokens.accepts("ptk_before", now));
+        assert!(room.pair_tokens.current().starts_with("ptk_"));
+        assert!(room.ticket_nonces.is_empty());
+        let store = state.auth_store.read().await;
+        let system = store.system_ref("sys_a").expect("system");
+        assert_eq!(
+            system.pair_token_hash.as_deref(),
+            Some(sha256_hex(room.pair_tokens.current()).as_str())
+        );
+        assert!(system.previous_pair_token_hash.is_none());
+        assert_eq!(
+            system.pair_token_updated_at.as_deref(),
+            Some(data.pair_token_updated_at.as_str())
+        );
+        assert!(
+            system
+                .revoked_pair_token_hashes
+                .contains(&sha256_hex("ptk_leaked"))
+        );
+        drop((rooms, store));
+
+        let replay = state.revoke_pair_token(&req).await;
+        assert!(replay.is_err());
+
+        let Some(RelayWriteCommand::Direct(Message::Text(notice))) = sidecar_rx.recv().await else {
+            panic!("sidecar should be told to rotate its pair token");
+        };
+        assert!(notice.contains(PAIR_TOKEN_REVOKED_EVENT));
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn sidecar_reconnect_with_revoked_token_is_refused_until_it_rotates() {
+        let mut store = AuthStore::new("seed".to_string());
+        store.system_mut("sys_a").revoked_pair_token_hashes = vec![sha256_hex("ptk_leaked")];
+        let mut rooms = HashMap::new();
+        rooms.insert(
+            "sys_a".to_string(),
+            SystemRoom {
+                pair_tokens: PairTokenSet::new("ptk_random"),
+                ticket_nonces: HashMap::new(),
+                app_nonces: HashMap::new(),
+                clients: HashMap::new(),
+                oversized_frames: 0,
+            },
+        );
+        let state = AppState {
+            systems: Arc::new(RwLock::new(rooms)),
+            auth_store: Arc::new(RwLock::new(store)),
+            ..AppState::for_test()
+        };
+
+        let refused = state
+            .authorize_connection(ClientRole::Sidecar, &sidecar_query("ptk_leaked"))
+            .await
+            .expect_err("revoked token must not be rotated back");
+        assert_eq!(refused.code, "PAIR_TOKEN_REVOKED");
+        assert_eq!(
+            state.systems.read().await["sys_a"].pair_tokens.current(),
+            "ptk_random"
+        );
+
+        // relay 重启后房间不在内存中，同样不能以被作废令牌重新建房。
+        state.systems.write().await.clear();
+        let refused = state
+            .authorize_connection(ClientRole::Sidecar, &sidecar_query("ptk_leaked"))
+            .await
+            .expect_err("revoked token must not initialize a room");
+        assert_eq!(refused.code, "PAIR_TOKEN_REVOKED");
+
+        state
+            .authorize_connection(ClientRole::Sidecar, &sidecar_query("ptk_fresh"))
+            .await
+            .expect("rotated token connects");
+        assert_eq!(
+            state.systems.read().await["sys_a"].pair_tokens.current(),
+            "ptk_fresh"
+        );
+    }
+}
@@ -3,8 +3,18 @@
 use axum::http::StatusCode;
 
 use crate::{
-    api::{error::ApiError, types::PairAuthMode},
-    pairing::ticket::{pair_ticket_error_to_api, verify_pairing_ticket},
+    api::{
+        error::ApiError,
+        types::{
+            PairAuthMode, PairTicketClaims, PairTicketError, PairTicketVerifyData,
+            PairTicketVerifyRequest,
+        },
+    },
+    auth::store::unix_now,
+    pairing::{
+        rate_limit::PairClientIp,
+        ticket::{pair_ticket_error_to_api, verify_pairing_ticket},
+    },
     state::AppState,
 };
 
@@ -16,6 +26,54 @@ impl AppState {
         pair_ticket: &str,
         consume_ticket: bool,
     ) -> Result<PairAuthMode, ApiError> {
+        self.verify_pair_ticket_claims(system_id, pair_ticket, consume_ticket)
+            .await
+            .map(|_| PairAuthMode::PairTicket)
+    }
+
+    /// pairTicket 试校验：不消费票据，返回过期时间与剩余秒数，供 App 展示倒计时；
+    /// 与预检、换发共用限流桶。
+    pub(crate) async fn dry_run_pair_ticket(
+        &self,
+        req: &PairTicketVerifyRequest,
+        client_ip: PairClientIp,
+    ) -> Result<PairTicketVerifyData, ApiError> {
+        let system_id = req.system_id.trim();
+        if system_id.is_empty() {
+            return Err(ApiError::new(
+                StatusCode::BAD_REQUEST,
+                "MISSING_CREDENTIALS",
+                "systemId 不能为空",
+                "请检查配对信息",
+            ));
+        }
+        self.check_pair_rate(client_ip, system_id)?;
+        let claims = self
+            .verify_pair_ticket_claims(system_id, req.pair_ticket.trim(), false)
+            .await?;
+        Ok(PairTicketVerifyData {
+            valid: true,
+            exp: claims.exp,
+            remaining_sec: claims.exp.saturating_sub(unix_now()),
+        })
+    }
+
+    /// 校验 pairTicket 并返回声明；`consume_ticket=false` 时不登记 nonce。
+    async fn verify_pair_ticket_claims(
+        &self,
+        system_id: &str,
+        pair_ticket: &str,
+        consume_ticket: bool,
+    ) -> Result<PairTicketClaims, ApiError> {
+        if !self.system_features(system_id).await.allow_ticket_pairing {
+            return Err(ApiError::new(
+                StatusCode::FORBIDDEN,
+                "TICKET_PAIRING_DISABLED",
+                "该宿主机已关闭票据配对",
+                "请联系宿主机管理员开启配对后重试",
+            ));
+        }
+
         let mut guard = self.systems.write().await;
         let Some(room) = guard.get_mut(system_id) else {
             return Err(ApiError::new(
@@ -43,15 +101,20 @@ impl AppState {
             ));
         }
 
-        match verify_pairing_ticket(
-            pair_ticket,
-            system_id,
-            &room.pair_token,
-            &mut room.ticket_nonces,
-            consume_ticket,
-        ) {
-            Ok(_) => Ok(PairAuthMode::PairTicket),
-            Err(err) => Err(pair_ticket_error_to_api(err)),
+        // 票据可能由轮换前的令牌签发：依次尝试当前令牌与宽限期内的上一个令牌。
+        let mut result = Err(PairTicketError::SignatureVerify);
+        for pair_token in room.pair_tokens.valid_tokens(unix_now()) {
+            result = verify_pairing_ticket(
+                pair_ticket,
+                system_id,
+                pair_token,
+                &mut room.ticket_nonces,
+                consume_ticket,
+            );
+            if !matches!(result, Err(PairTicketError::SignatureVerify)) {
+                break;
+            }
         }
+        result.map_err(pair_ticket_error_to_api)
     }
 }
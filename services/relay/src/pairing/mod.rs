@@ -2,4 +2,5 @@
 
 pub(crate) mod bootstrap;
 pub(crate) mod handlers;
+pub(crate) mod rate_limit;
 pub(crate) mod ticket;
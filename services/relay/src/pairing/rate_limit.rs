@@ -0,0 +1,325 @@
+//! 配对尝试限流：按（客户端 IP, `systemId`）维护令牌桶，配对换发、预检、票据试校验与配对签发
+//! 共用同一个桶，超出突发上限返回 `429 PAIR_RATE_LIMITED`，阻止已知 systemId 时高速暴力猜测
+//! pairTicket/pairToken，也避免单一来源耗尽桶后把同一 system 的正常设备一并挡在门外；
+//! 空闲桶定期清理。
+
+use std::{
+    collections::HashMap,
+    convert::Infallible,
+    net::IpAddr,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use axum::{
+    extract::{ConnectInfo, FromRequestParts},
+    http::{HeaderMap, StatusCode, request::Parts},
+};
+use tokio::time::MissedTickBehavior;
+use tracing::{info, warn};
+
+use crate::{api::error::ApiError, app::PeerAddr, state::AppState};
+
+/// 每个来源每分钟补充的配对尝试次数环境变量（0 表示关闭限流）。
+const RELAY_PAIR_RATE_PER_MIN_ENV: &str = "RELAY_PAIR_RATE_PER_MIN";
+/// 每个来源允许的突发尝试次数环境变量。
+const RELAY_PAIR_RATE_BURST_ENV: &str = "RELAY_PAIR_RATE_BURST";
+/// 默认每分钟补充次数。
+const DEFAULT_PAIR_RATE_PER_MIN: u32 = 10;
+/// 默认突发次数。
+const DEFAULT_PAIR_RATE_BURST: u32 = 5;
+/// 突发次数上限。
+const MAX_PAIR_RATE_BURST: u32 = 1000;
+/// 空闲桶清理周期。
+const PAIR_RATE_PRUNE_INTERVAL: Duration = Duration::from_secs(60);
+/// 本机反向代理追加真实客户端地址的请求头。
+const FORWARDED_FOR_HEADER: &str = "x-forwarded-for";
+
+/// 配对限流使用的客户端地址：直连对端为回环地址（本机 nginx 反向代理）时取 `X-Forwarded-For`
+/// 最后一跳（由代理追加，客户端无法伪造），否则取对端地址；未挂载连接信息时为 `None`。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct PairClientIp(pub(crate) Option<IpAddr>);
+
+impl<S: Send + Sync> FromRequestParts<S> for PairClientIp {
+    type Rejection = Infallible;
+
+    /// 从连接信息与代理请求头解析客户端地址，解析不到时不拒绝请求。
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let peer = parts
+            .extensions
+            .get::<ConnectInfo<PeerAddr>>()
+            .map(|ConnectInfo(PeerAddr(addr))| addr.ip());
+        Ok(Self(resolve_client_ip(peer, &parts.headers)))
+    }
+}
+
+/// 按对端地址与代理请求头确定客户端地址。
+fn resolve_client_ip(peer: Option<IpAddr>, headers: &HeaderMap) -> Option<IpAddr> {
+    match peer {
+        Some(ip) if ip.is_loopback() => forwarded_client_ip(headers).or(Some(ip)),
+        other => other,
+    }
+}
+
+/// 取 `X-Forwarded-For` 最后一跳。
+fn forwarded_client_ip(headers: &HeaderMap) -> Option<IpAddr> {
+    headers
+        .get_all(FORWARDED_FOR_HEADER)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .next_back()?
+        .trim()
+        .parse()
+        .ok()
+}
+
+/// 配对限流配置。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct PairRateConfig {
+    /// 每分钟补充的令牌数。
+    pub(crate) per_minute: u32,
+    /// 桶容量（突发次数）。
+    pub(crate) burst: u32,
+}
+
+impl Default for PairRateConfig {
+    /// 默认每分钟 10 次、突发 5 次。
+    fn default() -> Self {
+        Self {
+            per_minute: DEFAULT_PAIR_RATE_PER_MIN,
+            burst: DEFAULT_PAIR_RATE_BURST,
+        }
+    }
+}
+
+impl PairRateConfig {
+    /// 读取环境变量。
+    pub(crate) fn from_env() -> Self {
+        Self::from_values(
+            std::env::var(RELAY_PAIR_RATE_PER_MIN_ENV).ok().as_deref(),
+            std::env::var(RELAY_PAIR_RATE_BURST_ENV).ok().as_deref(),
+        )
+    }
+
+    /// 按原始文本解析配置；无法解析时使用默认值，突发次数限制在 `1..=1000`。
+    fn from_values(per_minute: Option<&str>, burst: Option<&str>) -> Self {
+        let parse = |raw: Option<&str>, env: &str, default: u32| match raw
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+        {
+            Some(value) => value.parse::<u32>().unwrap_or_else(|_| {
+                warn!("invalid {env}={value}, fallback to {default}");
+                default
+            }),
+            None => default,
+        };
+        Self {
+            per_minute: parse(
+                per_minute,
+                RELAY_PAIR_RATE_PER_MIN_ENV,
+                DEFAULT_PAIR_RATE_PER_MIN,
+            ),
+            burst: parse(burst, RELAY_PAIR_RATE_BURST_ENV, DEFAULT_PAIR_RATE_BURST)
+                .clamp(1, MAX_PAIR_RATE_BURST),
+        }
+    }
+
+    /// 是否启用限流。
+    pub(crate) fn enabled(&self) -> bool {
+        self.per_minute > 0
+    }
+
+    /// 补满一个令牌所需时间。
+    fn refill_period(&self) -> Duration {
+        Duration::from_secs(60) / self.per_minute.max(1)
+    }
+}
+
+/// 单个来源的令牌桶。
+#[derive(Debug, Clone, Copy)]
+struct TokenBucket {
+    /// 当前可用令牌（允许小数，按流逝时间连续补充）。
+    tokens: f64,
+    /// 上次补充时间。
+    refilled_at: Instant,
+}
+
+/// 令牌桶键：（客户端 IP, systemId），IP 未知时退化为仅按 systemId。
+type PairRateKey = (Option<IpAddr>, String);
+
+/// 配对限流表；只在同步临界区内加锁，不跨 await 持有。
+#[derive(Debug, Default)]
+pub(crate) struct PairRateLimiter {
+    /// 限流配置。
+    config: PairRateConfig,
+    /// 各来源的令牌桶。
+    buckets: Mutex<HashMap<PairRateKey, TokenBucket>>,
+}
+
+impl PairRateLimiter {
+    /// 按配置创建限流表。
+    pub(crate) fn new(config: PairRateConfig) -> Self {
+        Self {
+            config,
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// 限流配置。
+    pub(crate) fn config(&self) -> PairRateConfig {
+        self.config
+    }
+
+    /// 消耗一次配对尝试；桶内令牌不足时返回需要等待的时长。
+    pub(crate) fn try_acquire(
+        &self,
+        client_ip: Option<IpAddr>,
+        system_id: &str,
+        now: Instant,
+    ) -> Result<(), Duration> {
+        if !self.config.enabled() || system_id.is_empty() {
+            return Ok(());
+        }
+        let capacity = f64::from(self.config.burst);
+        let per_sec = f64::from(self.config.per_minute) / 60.0;
+        let mut guard = self.buckets.lock().unwrap_or_else(|err| err.into_inner());
+        let key = (client_ip, system_id.to_string());
+        let bucket = guard.entry(key).or_insert(TokenBucket {
+            tokens: capacity,
+            refilled_at: now,
+        });
+        let elapsed = now.saturating_duration_since(bucket.refilled_at);
+        bucket.tokens = (bucket.tokens + elapsed.as_secs_f64() * per_sec).min(capacity);
+        bucket.refilled_at = now;
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            return Ok(());
+        }
+        Err(Duration::from_secs_f64((1.0 - bucket.tokens) / per_sec))
+    }
+
+    /// 移除已补满的桶（等价于从未访问），返回移除数量。
+    pub(crate) fn prune_idle(&self, now: Instant) -> usize {
+        let full_after = self.config.refill_period() * self.config.burst;
+        let mut guard = self.buckets.lock().unwrap_or_else(|err| err.into_inner());
+        let before = guard.len();
+        guard.retain(|_, bucket| now.saturating_duration_since(bucket.refilled_at) < full_after);
+        before - guard.len()
+    }
+
+    /// 当前追踪的桶数量。
+    #[cfg(test)]
+    fn len(&self) -> usize {
+        self.buckets
+            .lock()
+            .map(|guard| guard.len())
+            .unwrap_or_default()
+    }
+}
+
+impl AppState {
+    /// 配对类接口入口限流：超限返回 `429 PAIR_RATE_LIMITED`，message 带建议等待秒数。
+    pub(crate) fn check_pair_rate(
+        &self,
+        client_ip: PairClientIp,
+        system_id: &str,
+    ) -> Result<(), ApiError> {
+        self.pair_rate
+            .try_acquire(client_ip.0, system_id.trim(), Instant::now())
+            .map_err(|retry_after| {
+                let retry_after_sec = retry_after.as_secs().max(1);
+                let ip = client_ip.0.map(|ip| ip.to_string()).unwrap_or_default();
+                warn!(
+                    "pair attempt rate limited system={system_id} ip={ip} \
+                     retry_after_sec={retry_after_sec}"
+                );
+                ApiError::new(
+                    StatusCode::TOO_MANY_REQUESTS,
+                    "PAIR_RATE_LIMITED",
+                    format!("配对尝试过于频繁，请 {retry_after_sec} 秒后重试"),
+                    "请稍后重新扫码或输入配对码",
+                )
+            })
+    }
+}
+
+/// 启动空闲桶清理任务；未启用限流时不启动。
+pub(crate) fn spawn_pair_rate_pruner(state: AppState) {
+    let config = state.pair_rate.config();
+    if !config.enabled() {
+        return;
+    }
+    info!(
+        "pair rate limiter enabled per_minute={} burst={}",
+        config.per_minute, config.burst
+    );
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(PAIR_RATE_PRUNE_INTERVAL);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Skip);
+        loop {
+            ticker.tick().await;
+            state.pair_rate.prune_idle(Instant::now());
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        net::IpAddr,
+        time::{Duration, Instant},
+    };
+
+    use axum::http::{HeaderMap, HeaderValue};
+
+    use super::{PairRateConfig, PairRateLimiter, resolve_client_ip};
+
+    #[test]
+    fn burst_is_rejected_then_refilled_and_idle_buckets_pruned() {
+        let limiter = PairRateLimiter::new(PairRateConfig::from_values(Some("6"), Some("2")));
+        let start = Instant::now();
+        let attacker = Some(IpAddr::from([203, 0, 113, 7]));
+        assert!(limiter.try_acquire(attacker, "sys_a", start).is_ok());
+        assert!(limiter.try_acquire(attacker, "sys_a", start).is_ok());
+        let retry_after = limiter
+            .try_acquire(attacker, "sys_a", start)
+            .expect_err("burst exhausted");
+        assert_eq!(retry_after, Duration::from_secs(10));
+        // 其它 system 与同 system 的其它来源互不影响。
+        assert!(limiter.try_acquire(attacker, "sys_b", start).is_ok());
+        let owner = Some(IpAddr::from([198, 51, 100, 1]));
+        assert!(limiter.try_acquire(owner, "sys_a", start).is_ok());
+
+        assert!(
+            limiter
+                .try_acquire(attacker, "sys_a", start + Duration::from_secs(10))
+                .is_ok()
+        );
+        assert_eq!(limiter.prune_idle(start + Duration::from_secs(15)), 0);
+        assert_eq!(limiter.prune_idle(start + Duration::from_secs(30)), 3);
+        assert_eq!(limiter.len(), 0);
+
+        let disabled = PairRateLimiter::new(PairRateConfig::from_values(Some("0"), None));
+        assert!((0..100).all(|_| disabled.try_acquire(attacker, "sys_a", start).is_ok()));
+        assert_eq!(PairRateConfig::from_values(None, Some("0")).burst, 1);
+    }
+
+    #[test]
+    fn client_ip_trusts_forwarded_for_only_behind_local_proxy() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "x-forwarded-for",
+            HeaderValue::from_static("10.0.0.9, 203.0.113.7"),
+        );
+        let loopback = Some(IpAddr::from([127, 0, 0, 1]));
+        let direct = Some(IpAddr::from([198, 51, 100, 1]));
+        assert_eq!(
+            resolve_client_ip(loopback, &headers),
+            Some(IpAddr::from([203, 0, 113, 7]))
+        );
+        assert_eq!(resolve_client_ip(direct, &headers), direct);
+        assert_eq!(resolve_client_ip(loopback, &HeaderMap::new()), loopback);
+        assert_eq!(resolve_client_ip(None, &headers), None);
+    }
+}
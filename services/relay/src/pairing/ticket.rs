@@ -7,6 +7,7 @@ use base64::{Engine as _, engine::general_purpose::URL_SAFE_NO_PAD};
 use hmac::{Hmac, Mac};
 use serde_json::json;
 use sha2::Sha256;
+use yc_shared_protocol::decode_b64url;
 
 use crate::{
     api::{
@@ -40,14 +41,14 @@ pub(crate) fn generate_pairing_ticket(system_id: &str, pair_token: &str, ttl_sec
     format!("pct_v1.{payload_b64}.{sig_b64}")
 }
 
-/// 校验短时配对票据。
+/// 校验短时配对票据，通过时返回解码后的声明。
 pub(crate) fn verify_pairing_ticket(
     ticket: &str,
     expected_system_id: &str,
     pair_token: &str,
     used_nonces: &mut HashMap<String, u64>,
     consume: bool,
-) -> Result<(), PairTicketError> {
+) -> Result<PairTicketClaims, PairTicketError> {
     if ticket.is_empty() {
         return Err(PairTicketError::Empty);
     }
@@ -61,9 +62,7 @@ pub(crate) fn verify_pairing_ticket(
         return Err(PairTicketError::Format);
     }
 
-    let sig = URL_SAFE_NO_PAD
-        .decode(sig_b64.as_bytes())
-        .map_err(|_| PairTicketError::SignatureFormat)?;
+    let sig = decode_b64url(sig_b64).map_err(|_| PairTicketError::SignatureFormat)?;
 
     type HmacSha256 = Hmac<Sha256>;
     let mut mac = HmacSha256::new_from_slice(pair_token.as_bytes())
@@ -72,9 +71,7 @@ pub(crate) fn verify_pairing_ticket(
     mac.verify_slice(&sig)
         .map_err(|_| PairTicketError::SignatureVerify)?;
 
-    let payload_raw = URL_SAFE_NO_PAD
-        .decode(payload_b64.as_bytes())
-        .map_err(|_| PairTicketError::Payload)?;
+    let payload_raw = decode_b64url(payload_b64).map_err(|_| PairTicketError::Payload)?;
     let claims: PairTicketClaims =
         serde_json::from_slice(&payload_raw).map_err(|_| PairTicketError::Claims)?;
 
@@ -101,10 +98,10 @@ pub(crate) fn verify_pairing_ticket(
     }
 
     if consume {
-        used_nonces.insert(claims.nonce, claims.exp);
+        used_nonces.insert(claims.nonce.clone(), claims.exp);
     }
 
-    Ok(())
+    Ok(claims)
 }
 
 /// pairTicket 错误映射到 API 错误。
@@ -169,4 +166,18 @@ mod tests {
             Err(crate::api::types::PairTicketError::Replay)
         ));
     }
+
+    #[test]
+    fn dry_run_returns_claims_without_burning_nonce() {
+        let mut used = std::collections::HashMap::new();
+        let ticket = generate_pairing_ticket("sys_demo", "ptk_demo", 300);
+
+        for _ in 0..2 {
+            let claims = verify_pairing_ticket(&ticket, "sys_demo", "ptk_demo", &mut used, false)
+                .expect("dry run");
+            assert_eq!(claims.exp, claims.iat + 300);
+        }
+        assert!(used.is_empty());
+        assert!(verify_pairing_ticket(&ticket, "sys_demo", "ptk_demo", &mut used, true).is_ok());
+    }
 }
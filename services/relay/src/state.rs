@@ -1,17 +1,39 @@
 //! Relay 状态：在线连接房间与认证存储句柄。
 
 use std::sync::atomic::{AtomicU64, Ordering};
-use std::{collections::HashMap, path::PathBuf, sync::Arc};
+use std::{collections::HashMap, sync::Arc};
 
 use axum::extract::ws::Message;
+use chrono::{DateTime, Utc};
 use tokio::sync::mpsc::error::TrySendError;
-use tokio::sync::{RwLock, mpsc};
-use tracing::warn;
+use tokio::sync::{Notify, RwLock, mpsc};
+use tracing::{info, warn};
 use uuid::Uuid;
 
 use crate::{
-    api::{error::ApiError, types::AuthStore},
-    auth::store::{auth_store_path, load_auth_store, persist_auth_store, unix_now},
+    api::{
+        error::ApiError,
+        types::{
+            AUTH_FAILURE_PERSIST_INTERVAL_SEC, AuthFailureRecord, AuthStore, ClientRole,
+            SystemFeatures,
+        },
+    },
+    auth::{
+        audit::AuditLog,
+        backend::{AuthStoreBackend, auth_store_backend_from_env},
+        config::AuthConfig,
+        nonce_log::NonceLog,
+        pair_tokens::PairTokenSet,
+        signing_keys::prepare_signing_keys,
+        store::{generate_signing_key_seed, unix_now},
+    },
+    debug::DebugSystemsCache,
+    metrics::RelayMetrics,
+    pairing::rate_limit::{PairRateConfig, PairRateLimiter},
+    ws::{
+        ack::{AckConfig, AckTracker},
+        seq_gap::SeqGapDetector,
+    },
 };
 
 /// Relay 共享状态。
@@ -21,25 +43,104 @@ pub(crate) struct AppState {
     pub(crate) systems: Arc<RwLock<HashMap<String, SystemRoom>>>,
     /// 认证元数据（持久化）。
     pub(crate) auth_store: Arc<RwLock<AuthStore>>,
-    /// 认证元数据文件路径。
-    pub(crate) auth_store_path: Arc<PathBuf>,
-    /// HTTP 鉴权接口 nonce（内存防重放）。
+    /// 认证元数据持久化后端。
+    pub(crate) auth_backend: Arc<dyn AuthStoreBackend>,
+    /// HTTP 鉴权接口 nonce（内存防重放，启动时从 nonce 日志恢复）。
     pub(crate) auth_nonces: Arc<RwLock<HashMap<String, u64>>>,
+    /// HTTP nonce 追加日志（尽力持久化）。
+    pub(crate) nonce_log: Arc<NonceLog>,
+    /// 鉴权审计日志（`RELAY_AUDIT_LOG_PATH` 未设置时不记录）。
+    pub(crate) audit: Arc<AuditLog>,
+    /// 调试接口连接快照缓存。
+    pub(crate) debug_cache: Arc<DebugSystemsCache>,
+    /// Prometheus 指标计数器。
+    pub(crate) metrics: Arc<RelayMetrics>,
+    /// 鉴权时效配置（TTL 与 PoP 时间窗）。
+    pub(crate) auth_config: AuthConfig,
+    /// 关键事件 ACK 追踪表。
+    pub(crate) acks: Arc<AckTracker>,
+    /// sidecar 上行 seq 断档检测（默认关闭）。
+    pub(crate) seq_gaps: Arc<SeqGapDetector>,
+    /// 配对换发/预检按 system 限流的令牌桶。
+    pub(crate) pair_rate: Arc<PairRateLimiter>,
 }
 
-impl Default for AppState {
-    /// 初始化内存状态并加载持久化认证元数据。
-    fn default() -> Self {
-        let path = auth_store_path();
-        let store = load_auth_store(&path).unwrap_or_else(|err| {
-            warn!("load auth store failed: {err}");
-            AuthStore::new(crate::auth::store::generate_signing_key_seed())
-        });
-        Self {
+impl AppState {
+    /// 初始化内存状态并加载持久化认证元数据；认证存储后端无法初始化时返回错误。
+    pub(crate) fn load() -> anyhow::Result<Self> {
+        let backend = auth_store_backend_from_env().map_err(anyhow::Error::msg)?;
+        info!(
+            "relay auth store backend={} location={}",
+            backend.name(),
+            backend.describe()
+        );
+        let auth_config = AuthConfig::from_env();
+        info!(
+            concat!(
+                "relay auth config access_ttl_sec={} refresh_ttl_sec={} pop_skew_sec={} ",
+                "pair_token_grace_sec={}"
+            ),
+            auth_config.access_ttl_sec,
+            auth_config.refresh_ttl_sec,
+            auth_config.pop_skew_sec,
+            auth_config.pair_token_grace_sec
+        );
+        // 启动即写回签名密钥环：共享后端的多个副本据此使用同一把密钥签发 token。
+        let store = backend
+            .update(&mut |store| prepare_signing_keys(store, unix_now()))
+            .unwrap_or_else(|err| {
+                warn!("load auth store failed: {err}");
+                AuthStore::new(generate_signing_key_seed())
+            });
+        let nonce_log = backend
+            .local_path()
+            .map(NonceLog::beside)
+            .unwrap_or_default();
+        let nonces = nonce_log.load(unix_now());
+        if !nonces.is_empty() {
+            info!(
+                "relay restored unexpired auth nonces count={}",
+                nonces.len()
+            );
+        }
+        let seq_gaps = SeqGapDetector::from_env();
+        if seq_gaps.enabled() {
+            info!("relay seq gap detection enabled");
+        }
+        Ok(Self {
             systems: Arc::new(RwLock::new(HashMap::new())),
             auth_store: Arc::new(RwLock::new(store)),
-            auth_store_path: Arc::new(path),
+            auth_backend: backend,
+            auth_nonces: Arc::new(RwLock::new(nonces)),
+            nonce_log: Arc::new(nonce_log),
+            audit: Arc::new(AuditLog::from_env()),
+            debug_cache: Arc::new(DebugSystemsCache::default()),
+            metrics: Arc::new(RelayMetrics::default()),
+            auth_config,
+            acks: Arc::new(AckTracker::new(AckConfig::from_env())),
+            seq_gaps: Arc::new(seq_gaps),
+            pair_rate: Arc::new(PairRateLimiter::new(PairRateConfig::from_env())),
+        })
+    }
+
+    #[cfg(test)]
+    /// 测试辅助：构造空房间与内存认证存储（不落盘）。
+    pub(crate) fn for_test() -> Self {
+        use crate::auth::backend::MemoryAuthStoreBackend;
+
+        Self {
+            systems: Arc::new(RwLock::new(HashMap::new())),
+            auth_store: Arc::new(RwLock::new(AuthStore::new("test_seed".to_string()))),
+            auth_backend: Arc::new(MemoryAuthStoreBackend::default()),
             auth_nonces: Arc::new(RwLock::new(HashMap::new())),
+            nonce_log: Default::default(),
+            audit: Default::default(),
+            debug_cache: Arc::new(DebugSystemsCache::default()),
+            metrics: Arc::new(RelayMetrics::default()),
+            auth_config: AuthConfig::default(),
+            acks: Arc::new(AckTracker::default()),
+            seq_gaps: Default::default(),
+            pair_rate: Default::default(),
         }
     }
 }
@@ -53,34 +154,51 @@ fn is_snapshot_event(event_type: &str) -> bool {
             | "tools_snapshot"
             | "tools_candidates"
             | "tool_details_snapshot"
+            | "collection_errors"
     )
 }
 
 /// 生成快照队列覆盖键：同键仅保留最后一条。
+/// 压缩包装看不到内层 `targetToolId`，退回外层 `toolId`，再退回 eventId（单独成键，不与其他工具互相覆盖）。
 fn snapshot_queue_key(event_type: &str, raw: &str) -> String {
     if event_type != "tool_details_snapshot" {
         return event_type.to_string();
     }
 
-    let target_tool_id = serde_json::from_str::<serde_json::Value>(raw)
-        .ok()
-        .and_then(|value| value.get("payload").cloned())
-        .and_then(|payload| payload.get("targetToolId").cloned())
-        .and_then(|value| value.as_str().map(ToString::to_string))
-        .unwrap_or_default();
-    format!("{event_type}:{target_tool_id}")
+    let value = serde_json::from_str::<serde_json::Value>(raw).unwrap_or_default();
+    let field = |pointer: &str| {
+        value
+            .pointer(pointer)
+            .and_then(|value| value.as_str())
+            .filter(|value| !value.is_empty())
+    };
+    match field("/payload/targetToolId").or_else(|| field("/toolId")) {
+        Some(target_tool_id) => format!("{event_type}:{target_tool_id}"),
+        None => format!("{event_type}:evt:{}", field("/eventId").unwrap_or_default()),
+    }
 }
 
 /// 单个 system 房间状态。
 pub(crate) struct SystemRoom {
-    /// 当前 system 配对令牌（sidecar 注册）。
-    pub(crate) pair_token: String,
+    /// system 有效配对令牌（sidecar 注册的当前令牌 + 宽限期内的上一个令牌）。
+    pub(crate) pair_tokens: PairTokenSet,
     /// 已使用短时票据 nonce。
     pub(crate) ticket_nonces: HashMap<String, u64>,
     /// WS PoP nonce（app）防重放。
     pub(crate) app_nonces: HashMap<String, u64>,
     /// 当前连接客户端集合。
     pub(crate) clients: HashMap<Uuid, ClientHandle>,
+    /// 因超过帧大小上限被丢弃的消息数（随房间生命周期累计）。
+    pub(crate) oversized_frames: u64,
+}
+
+/// 调试快照中的单个房间统计。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct RoomStats {
+    /// 在线连接数。
+    pub(crate) clients: usize,
+    /// 超限丢弃帧数。
+    pub(crate) oversized_frames: u64,
 }
 
 impl SystemRoom {
@@ -88,18 +206,32 @@ impl SystemRoom {
     pub(crate) fn has_online_sidecar(&self) -> bool {
         self.clients
             .values()
-            .any(|client| client.client_type == "sidecar")
+            .any(|client| client.role == ClientRole::Sidecar)
     }
 }
 
+/// 进程内连接接入序号，用于判定同设备连接的先后。
+static CONNECTION_SEQ: AtomicU64 = AtomicU64::new(0);
+
+/// 分配下一个连接接入序号。
+pub(crate) fn next_connection_seq() -> u64 {
+    CONNECTION_SEQ.fetch_add(1, Ordering::Relaxed)
+}
+
 /// 单个连接发送句柄。
 #[derive(Clone)]
 pub(crate) struct ClientHandle {
-    /// 连接端类型（`app` / `sidecar`），用于在线 sidecar 判定。
-    pub(crate) client_type: String,
+    /// 连接端角色，用于在线 sidecar 判定。
+    pub(crate) role: ClientRole,
+    /// 连接端设备 ID，用于单设备并发上限。
+    pub(crate) device_id: String,
+    /// 接入序号（越小越早）。
+    pub(crate) seq: u64,
     pub(crate) sender: mpsc::Sender<RelayWriteCommand>,
     /// 慢客户端累计丢弃计数（仅快照类消息）。
     pub(crate) drop_count: Arc<AtomicU64>,
+    /// 服务端主动断开信号（如设备吊销），连接循环收到后退出。
+    pub(crate) disconnect: Arc<Notify>,
 }
 
 /// Relay -> WS writer 命令。
@@ -109,26 +241,54 @@ pub(crate) enum RelayWriteCommand {
     Snapshot { key: String, msg: Message },
 }
 
-/// 单连接写队列上限。
-pub(crate) const WS_WRITE_QUEUE_CAPACITY: usize = 256;
-
 impl AppState {
-    /// 注册 system 房间连接。
+    /// 注册 system 房间连接；同设备连接数超过 `max_per_device` 时淘汰最早的连接并通知其关闭。
     pub(crate) async fn insert(
         &self,
         system_id: String,
         pair_token: String,
         client_id: Uuid,
         handle: ClientHandle,
+        max_per_device: usize,
     ) {
         let mut guard = self.systems.write().await;
-        let room = guard.entry(system_id).or_insert_with(|| SystemRoom {
-            pair_token,
-            ticket_nonces: HashMap::new(),
-            app_nonces: HashMap::new(),
-            clients: HashMap::new(),
-        });
+        let room = guard
+            .entry(system_id.clone())
+            .or_insert_with(|| SystemRoom {
+                pair_tokens: PairTokenSet::new(pair_token),
+                ticket_nonces: HashMap::new(),
+                app_nonces: HashMap::new(),
+                clients: HashMap::new(),
+                oversized_frames: 0,
+            });
+        let device_id = handle.device_id.clone();
         room.clients.insert(client_id, handle);
+
+        let mut same_device = room
+            .clients
+            .iter()
+            .filter(|(_, client)| client.device_id == device_id)
+            .map(|(id, client)| (client.seq, *id))
+            .collect::<Vec<_>>();
+        let excess = same_device.len().saturating_sub(max_per_device.max(1));
+        if excess == 0 {
+            return;
+        }
+        same_device.sort_unstable();
+        for (_, evicted_id) in same_device.into_iter().take(excess) {
+            if let Some(evicted) = room.clients.remove(&evicted_id) {
+                let _ = evicted
+                    .sender
+                    .try_send(RelayWriteCommand::Direct(Message::Close(None)));
+            }
+        }
+        warn!(
+            concat!(
+                "device connection limit exceeded system={} device={} ",
+                "evicted={} limit={}"
+            ),
+            system_id, device_id, excess, max_per_device
+        );
     }
 
     /// 移除 system 房间连接。
@@ -151,7 +311,8 @@ impl AppState {
         }
     }
 
-    /// 广播到同 system 其他连接。
+    /// 广播到同 system 其他连接；普通事件写队列已满的慢客户端被移出房间并通知断开，
+    /// 不阻塞其余连接。
     pub(crate) async fn broadcast(
         &self,
         system_id: &str,
@@ -190,8 +351,10 @@ impl AppState {
                             stale.push(*client_id);
                         }
                         Err(TrySendError::Full(_)) => {
-                            let queue_depth =
-                                WS_WRITE_QUEUE_CAPACITY.saturating_sub(handle.sender.capacity());
+                            let queue_depth = handle
+                                .sender
+                                .max_capacity()
+                                .saturating_sub(handle.sender.capacity());
                             if snapshot_event {
                                 let drop_count =
                                     handle.drop_count.fetch_add(1, Ordering::Relaxed) + 1;
@@ -231,7 +394,10 @@ impl AppState {
         let mut close_senders = Vec::new();
         if let Some(room) = guard.get_mut(system_id) {
             for client_id in stale {
-                room.clients.remove(&client_id);
+                // 写队列已满时关闭帧无法入队，直接唤醒连接循环退出。
+                if let Some(handle) = room.clients.remove(&client_id) {
+                    handle.disconnect.notify_one();
+                }
             }
             should_drop_room = room.clients.is_empty() || !room.has_online_sidecar();
             if should_drop_room {
@@ -247,25 +413,59 @@ impl AppState {
     }
 
     /// system 连接数快照。
-    pub(crate) async fn snapshot(&self) -> HashMap<String, usize> {
+    pub(crate) async fn snapshot(&self) -> HashMap<String, RoomStats> {
         let guard = self.systems.read().await;
         guard
             .iter()
-            .map(|(system_id, room)| (system_id.clone(), room.clients.len()))
+            .map(|(system_id, room)| {
+                let stats = RoomStats {
+                    clients: room.clients.len(),
+                    oversized_frames: room.oversized_frames,
+                };
+                (system_id.clone(), stats)
+            })
             .collect()
     }
 
-    /// 记录 pair token 元数据（仅 hash，不存明文）。
-    pub(crate) async fn persist_pair_token_meta(&self, system_id: &str, pair_token: &str) {
+    /// 累计一次超限帧丢弃，返回该房间的累计丢弃数。
+    pub(crate) async fn record_oversized_frame(&self, system_id: &str) -> u64 {
+        let mut guard = self.systems.write().await;
+        let Some(room) = guard.get_mut(system_id) else {
+            return 0;
+        };
+        room.oversized_frames = room.oversized_frames.saturating_add(1);
+        room.oversized_frames
+    }
+
+    /// 记录 pair token 元数据（当前与上一个令牌均仅存 hash，不存明文）。
+    pub(crate) async fn persist_pair_token_meta(
+        &self,
+        system_id: &str,
+        pair_tokens: &PairTokenSet,
+    ) {
         let mut store = self.auth_store.write().await;
         let system = store.system_mut(system_id);
-        system.pair_token_hash = Some(crate::auth::token::sha256_hex(pair_token));
+        system.pair_token_hash = Some(crate::auth::token::sha256_hex(pair_tokens.current()));
+        system.previous_pair_token_hash = pair_tokens
+            .previous()
+            .map(|previous| crate::auth::token::sha256_hex(&previous.token));
+        system.previous_pair_token_expires_at =
+            pair_tokens.previous().map(|previous| previous.expires_at);
         system.pair_token_updated_at = Some(yc_shared_protocol::now_rfc3339_nanos());
-        if let Err(err) = persist_auth_store(&self.auth_store_path, &store) {
+        if let Err(err) = self.auth_backend.persist(&store) {
             warn!("persist pair token meta failed: {err}");
         }
     }
 
+    /// 读取 system 策略开关；未登记的 system 使用默认策略。
+    pub(crate) async fn system_features(&self, system_id: &str) -> SystemFeatures {
+        let store = self.auth_store.read().await;
+        store
+            .system_ref(system_id)
+            .map(|system| system.features)
+            .unwrap_or_default()
+    }
+
     /// 更新设备最后活跃时间。
     pub(crate) async fn touch_device_last_seen(&self, system_id: &str, device_id: &str) {
         let mut store = self.auth_store.write().await;
@@ -276,12 +476,48 @@ impl AppState {
             return;
         };
         device.last_seen_at = yc_shared_protocol::now_rfc3339_nanos();
-        if let Err(err) = persist_auth_store(&self.auth_store_path, &store) {
+        device.last_auth_failure = None;
+        if let Err(err) = self.auth_backend.persist(&store) {
             warn!("persist device last_seen failed: {err}");
         }
     }
 
-    /// 消费 HTTP nonce（防重放）。
+    /// 记录已登记设备最近一次连接鉴权失败；未登记的设备忽略，避免任意 deviceId 撑大存储。
+    /// 同一错误码在持久化间隔内重复失败时只更新内存，避免失败风暴放大磁盘写入。
+    pub(crate) async fn record_device_auth_failure(
+        &self,
+        system_id: &str,
+        device_id: &str,
+        code: &str,
+    ) {
+        let mut store = self.auth_store.write().await;
+        let Some(device) = store
+            .systems
+            .get_mut(system_id)
+            .and_then(|system| system.devices.get_mut(device_id))
+        else {
+            return;
+        };
+        let now = Utc::now();
+        let persist = device.last_auth_failure.as_ref().is_none_or(|previous| {
+            previous.code != code
+                || DateTime::parse_from_rfc3339(&previous.at)
+                    .ok()
+                    .is_none_or(|at| {
+                        now.signed_duration_since(at).num_seconds()
+                            >= AUTH_FAILURE_PERSIST_INTERVAL_SEC
+                    })
+        });
+        device.last_auth_failure = Some(AuthFailureRecord {
+            code: code.to_string(),
+            at: yc_shared_protocol::now_rfc3339_nanos(),
+        });
+        if persist && let Err(err) = self.auth_backend.persist(&store) {
+            warn!("persist device auth failure failed: {err}");
+        }
+    }
+
+    /// 消费 HTTP nonce（防重放）；新 nonce 同时追加到 nonce 日志，重启后仍可拒绝重放。
     pub(crate) async fn consume_auth_nonce(
         &self,
         scope: &str,
@@ -298,7 +534,7 @@ impl AppState {
             ));
         }
         let now = unix_now();
-        if ts > now.saturating_add(crate::api::types::POP_MAX_SKEW_SEC) {
+        if ts > now.saturating_add(self.auth_config.pop_skew_sec) {
             return Err(ApiError::new(
                 axum::http::StatusCode::UNAUTHORIZED,
                 "ACCESS_SIGNATURE_EXPIRED",
@@ -320,7 +556,168 @@ impl AppState {
                 "请重新发起请求",
             ));
         }
-        guard.insert(key, now.saturating_add(crate::api::types::POP_MAX_SKEW_SEC));
+        let exp = now.saturating_add(self.auth_config.pop_skew_sec);
+        guard.insert(key.clone(), exp);
+        let write = self.nonce_log.prepare(&key, exp, &guard);
+        drop(guard);
+        if let Some(write) = write {
+            let nonce_log = self.nonce_log.clone();
+            if let Err(err) = tokio::task::spawn_blocking(move || nonce_log.write(write)).await {
+                warn!("nonce log write task failed: {err}");
+            }
+        }
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::{Arc, atomic::AtomicU64},
+        time::Duration,
+    };
+
+    use axum::extract::ws::Message;
+    use tokio::sync::mpsc;
+    use uuid::Uuid;
+
+    use super::{
+        AppState, ClientHandle, RelayWriteCommand, next_connection_seq, snapshot_queue_key,
+    };
+    use crate::api::types::ClientRole;
+
+    /// 构造指定设备的连接句柄，返回句柄与其写队列接收端。
+    fn handle(
+        role: ClientRole,
+        device_id: &str,
+    ) -> (ClientHandle, mpsc::Receiver<RelayWriteCommand>) {
+        let (sender, receiver) = mpsc::channel(4);
+        let handle = ClientHandle {
+            role,
+            device_id: device_id.to_string(),
+            seq: next_connection_seq(),
+            sender,
+            drop_count: Arc::new(AtomicU64::new(0)),
+            disconnect: Default::default(),
+        };
+        (handle, receiver)
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn oldest_connection_is_evicted_when_device_limit_exceeded() {
+        let state = AppState::for_test();
+        let (sidecar, _sidecar_rx) = handle(ClientRole::Sidecar, "sc_a");
+        state
+            .insert(
+                "sys_a".to_string(),
+                "pt_a".to_string(),
+                Uuid::new_v4(),
+                sidecar,
+                3,
+            )
+            .await;
+
+        let mut connections = Vec::new();
+        for _ in 0..4 {
+            let client_id = Uuid::new_v4();
+            let (app, receiver) = handle(ClientRole::App, "ios_a");
+            state
+                .insert("sys_a".to_string(), "pt_a".to_string(), client_id, app, 3)
+                .await;
+            connections.push((client_id, receiver));
+        }
+
+        let guard = state.systems.read().await;
+        let room = &guard["sys_a"];
+        assert_eq!(room.clients.len(), 4, "sidecar + 3 app connections");
+        assert!(!room.clients.contains_key(&connections[0].0));
+        assert!(
+            connections[1..]
+                .iter()
+                .all(|(client_id, _)| room.clients.contains_key(client_id))
+        );
+        drop(guard);
+
+        let (_, first_rx) = &mut connections[0];
+        assert!(matches!(
+            first_rx.try_recv(),
+            Ok(RelayWriteCommand::Direct(Message::Close(None)))
+        ));
+        let (_, second_rx) = &mut connections[1];
+        assert!(second_rx.try_recv().is_err());
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn slow_client_with_full_queue_is_evicted_without_stalling_others() {
+        let state = AppState::for_test();
+        let sidecar_id = Uuid::new_v4();
+        let slow_id = Uuid::new_v4();
+        let fast_id = Uuid::new_v4();
+        let (sidecar, _sidecar_rx) = handle(ClientRole::Sidecar, "sc_a");
+        let (slow, _slow_rx) = handle(ClientRole::App, "ios_slow");
+        let (fast, mut fast_rx) = handle(ClientRole::App, "ios_fast");
+        let slow_sender = slow.sender.clone();
+        let slow_disconnect = slow.disconnect.clone();
+        for (client_id, client) in [(sidecar_id, sidecar), (slow_id, slow), (fast_id, fast)] {
+            state
+                .insert(
+                    "sys_a".to_string(),
+                    "pt_a".to_string(),
+                    client_id,
+                    client,
+                    3,
+                )
+                .await;
+        }
+        // 慢客户端不消费，写队列被占满。
+        while slow_sender
+            .try_send(RelayWriteCommand::Direct(Message::Text("backlog".into())))
+            .is_ok()
+        {}
+
+        state
+            .broadcast(
+                "sys_a",
+                sidecar_id,
+                r#"{"type":"tool_chat_chunk"}"#.to_string(),
+                "tool_chat_chunk",
+            )
+            .await;
+
+        let guard = state.systems.read().await;
+        let room = &guard["sys_a"];
+        assert!(!room.clients.contains_key(&slow_id));
+        assert!(room.clients.contains_key(&fast_id));
+        drop(guard);
+        assert!(matches!(
+            fast_rx.try_recv(),
+            Ok(RelayWriteCommand::Direct(Message::Text(_)))
+        ));
+        tokio::time::timeout(Duration::from_millis(100), slow_disconnect.notified())
+            .await
+            .expect("slow client notified to disconnect");
+    }
+
+    #[test]
+    fn compressed_details_snapshots_do_not_share_a_queue_key() {
+        let plain = r#"{"type":"tool_details_snapshot","payload":{"targetToolId":"codex_a"}}"#;
+        assert_eq!(
+            snapshot_queue_key("tool_details_snapshot", plain),
+            "tool_details_snapshot:codex_a"
+        );
+        let wrapped = r#"{"type":"compressed","toolId":"codex_b","payload":{"b64":""}}"#;
+        assert_eq!(
+            snapshot_queue_key("tool_details_snapshot", wrapped),
+            "tool_details_snapshot:codex_b"
+        );
+        let anonymous = r#"{"type":"compressed","eventId":"evt_1","payload":{"b64":""}}"#;
+        assert_eq!(
+            snapshot_queue_key("tool_details_snapshot", anonymous),
+            "tool_details_snapshot:evt:evt_1"
+        );
+        assert_eq!(
+            snapshot_queue_key("metrics_snapshot", wrapped),
+            "metrics_snapshot"
+        );
+    }
+}
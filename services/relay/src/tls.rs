@@ -0,0 +1,256 @@
+//! 原生 TLS 终止：`RELAY_TLS_CERT` 与 `RELAY_TLS_KEY` 同时指向 PEM 文件时以 `wss://` 对外服务。
+//! 握手在独立任务中完成并带超时，慢客户端不会阻塞后续连接的接入。
+
+use std::{
+    io,
+    net::SocketAddr,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+
+use anyhow::{Context as _, anyhow, bail};
+use rustls_pki_types::{CertificateDer, PrivateKeyDer, pem::PemObject};
+use tokio::{
+    net::{TcpListener, TcpStream},
+    sync::mpsc,
+};
+use tokio_rustls::{
+    TlsAcceptor,
+    rustls::{ServerConfig, crypto::ring},
+    server::TlsStream,
+};
+use tracing::{debug, warn};
+
+/// 证书链 PEM 路径环境变量。
+const RELAY_TLS_CERT_ENV: &str = "RELAY_TLS_CERT";
+/// 私钥 PEM 路径环境变量。
+const RELAY_TLS_KEY_ENV: &str = "RELAY_TLS_KEY";
+/// 单连接 TLS 握手超时。
+const TLS_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+/// 已完成握手、等待 axum 接管的连接队列上限。
+const TLS_ACCEPT_BACKLOG: usize = 128;
+
+/// TLS 证书与私钥路径。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct TlsPaths {
+    /// 证书链 PEM。
+    pub(crate) cert: PathBuf,
+    /// 私钥 PEM（PKCS#8 / PKCS#1 / SEC1）。
+    pub(crate) key: PathBuf,
+}
+
+/// 读取 TLS 配置；两者都未设置时返回 None，仅设置其一时报错。
+pub(crate) fn tls_paths_from_env() -> anyhow::Result<Option<TlsPaths>> {
+    parse_tls_paths(
+        std::env::var(RELAY_TLS_CERT_ENV).ok(),
+        std::env::var(RELAY_TLS_KEY_ENV).ok(),
+    )
+}
+
+/// 是否启用了原生 TLS（配置不完整时视为未启用）。
+pub(crate) fn tls_enabled() -> bool {
+    matches!(tls_paths_from_env(), Ok(Some(_)))
+}
+
+/// 校验证书/私钥路径是否成对出现。
+fn parse_tls_paths(cert: Option<String>, key: Option<String>) -> anyhow::Result<Option<TlsPaths>> {
+    let non_empty = |raw: Option<String>| {
+        raw.map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty())
+            .map(PathBuf::from)
+    };
+    match (non_empty(cert), non_empty(key)) {
+        (Some(cert), Some(key)) => Ok(Some(TlsPaths { cert, key })),
+        (None, None) => Ok(None),
+        (Some(_), None) => Err(incomplete_pair(RELAY_TLS_CERT_ENV, RELAY_TLS_KEY_ENV)),
+        (None, Some(_)) => Err(incomplete_pair(RELAY_TLS_KEY_ENV, RELAY_TLS_CERT_ENV)),
+    }
+}
+
+/// 证书/私钥只配置其一时的错误。
+fn incomplete_pair(set: &str, missing: &str) -> anyhow::Error {
+    anyhow!("{set} is set but {missing} is missing; both are required for TLS")
+}
+
+/// 从 PEM 文件构造 rustls 服务端配置（仅协商 HTTP/1.1，保证 WS 升级可用）。
+pub(crate) fn load_server_config(paths: &TlsPaths) -> anyhow::Result<Arc<ServerConfig>> {
+    let certs = read_cert_chain(&paths.cert)?;
+    let key = PrivateKeyDer::from_pem_file(&paths.key)
+        .map_err(|err| anyhow!("read TLS key {} failed: {err}", paths.key.display()))?;
+    let mut config = ServerConfig::builder_with_provider(Arc::new(ring::default_provider()))
+        .with_safe_default_protocol_versions()
+        .context("init TLS protocol versions failed")?
+        .with_no_client_auth()
+        .with_single_cert(certs, key)
+        .context("TLS cert and key do not match")?;
+    config.alpn_protocols = vec![b"http/1.1".to_vec()];
+    Ok(Arc::new(config))
+}
+
+/// 读取证书链；文件中没有证书时报错。
+fn read_cert_chain(path: &Path) -> anyhow::Result<Vec<CertificateDer<'static>>> {
+    let certs = CertificateDer::pem_file_iter(path)
+        .and_then(|iter| iter.collect::<Result<Vec<_>, _>>())
+        .map_err(|err| anyhow!("read TLS cert {} failed: {err}", path.display()))?;
+    if certs.is_empty() {
+        bail!("TLS cert {} contains no certificate", path.display());
+    }
+    Ok(certs)
+}
+
+/// 供 `axum::serve` 使用的 TLS 监听器：后台接入 TCP 并完成握手，按完成顺序交付连接。
+pub(crate) struct TlsListener {
+    /// 实际监听地址。
+    local_addr: SocketAddr,
+    /// 已完成握手的连接。
+    incoming: mpsc::Receiver<(TlsStream<TcpStream>, SocketAddr)>,
+}
+
+impl TlsListener {
+    /// 包装已绑定的 TCP 监听器并启动后台接入任务。
+    pub(crate) fn new(tcp: TcpListener, config: Arc<ServerConfig>) -> io::Result<Self> {
+        let local_addr = tcp.local_addr()?;
+        let (tx, incoming) = mpsc::channel(TLS_ACCEPT_BACKLOG);
+        tokio::spawn(accept_loop(tcp, TlsAcceptor::from(config), tx));
+        Ok(Self {
+            local_addr,
+            incoming,
+        })
+    }
+}
+
+/// 接入循环：每个 TCP 连接独立握手，失败或超时只记录日志。
+async fn accept_loop(
+    tcp: TcpListener,
+    acceptor: TlsAcceptor,
+    tx: mpsc::Sender<(TlsStream<TcpStream>, SocketAddr)>,
+) {
+    while !tx.is_closed() {
+        let (stream, peer) = match tcp.accept().await {
+            Ok(accepted) => accepted,
+            Err(err) => {
+                warn!("relay tcp accept failed: {err}");
+                tokio::time::sleep(Duration::from_millis(100)).await;
+                continue;
+            }
+        };
+        let acceptor = acceptor.clone();
+        let tx = tx.clone();
+        tokio::spawn(async move {
+            match tokio::time::timeout(TLS_HANDSHAKE_TIMEOUT, acceptor.accept(stream)).await {
+                Ok(Ok(tls)) => {
+                    let _ = tx.send((tls, peer)).await;
+                }
+                Ok(Err(err)) => debug!("tls handshake failed peer={peer} err={err}"),
+                Err(_) => debug!("tls handshake timed out peer={peer}"),
+            }
+        });
+    }
+}
+
+impl axum::serve::Listener for TlsListener {
+    type Io = TlsStream<TcpStream>;
+    type Addr = SocketAddr;
+
+    async fn accept(&mut self) -> (Self::Io, Self::Addr) {
+        match self.incoming.recv().await {
+            Some(accepted) => accepted,
+            // 接入任务只会随进程退出，通道关闭后不再交付连接。
+            None => std::future::pending().await,
+        }
+    }
+
+    fn local_addr(&self) -> io::Result<Self::Addr> {
+        Ok(self.local_addr)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use futures_util::StreamExt;
+    use tokio_rustls::rustls::{ClientConfig, RootCertStore, crypto::ring};
+    use tokio_tungstenite::{Connector, connect_async_tls_with_config};
+    use uuid::Uuid;
+
+    use super::{TlsListener, TlsPaths, load_server_config, parse_tls_paths, read_cert_chain};
+    use crate::{app::router, state::AppState};
+
+    const TEST_CERT_PEM: &str = include_str!("../testdata/tls/localhost-cert.pem");
+    const TEST_KEY_PEM: &str = include_str!("../testdata/tls/localhost-key.pem");
+
+    #[test]
+    fn cert_and_key_must_be_configured_together() {
+        assert_eq!(
+            parse_tls_paths(None, Some("  ".to_string())).ok(),
+            Some(None)
+        );
+        let paths = parse_tls_paths(Some("a.pem".to_string()), Some("b.pem".to_string()))
+            .expect("both set");
+        assert_eq!(
+            paths,
+            Some(TlsPaths {
+                cert: "a.pem".into(),
+                key: "b.pem".into(),
+            })
+        );
+        let err = parse_tls_paths(Some("a.pem".to_string()), None).expect_err("key missing");
+        assert!(err.to_string().contains("RELAY_TLS_KEY is missing"));
+        let err = parse_tls_paths(None, Some("b.pem".to_string())).expect_err("cert missing");
+        assert!(err.to_string().contains("RELAY_TLS_CERT is missing"));
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn serves_ws_handshake_over_tls() {
+        let dir = std::env::temp_dir().join(format!("yc-relay-tls-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).expect("create temp dir");
+        let paths = TlsPaths {
+            cert: dir.join("cert.pem"),
+            key: dir.join("key.pem"),
+        };
+        std::fs::write(&paths.cert, TEST_CERT_PEM).expect("write cert");
+        std::fs::write(&paths.key, TEST_KEY_PEM).expect("write key");
+
+        let config = load_server_config(&paths).expect("load tls config");
+        let tcp = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("bind");
+        let listener = TlsListener::new(tcp, config).expect("tls listener");
+        let addr = axum::serve::Listener::local_addr(&listener).expect("local addr");
+        let app = router(AppState::for_test());
+        let server = tokio::spawn(async move { axum::serve(listener, app).await });
+
+        let mut roots = RootCertStore::empty();
+        for cert in read_cert_chain(&paths.cert).expect("read cert") {
+            roots.add(cert).expect("trust test cert");
+        }
+        let client = ClientConfig::builder_with_provider(Arc::new(ring::default_provider()))
+            .with_safe_default_protocol_versions()
+            .expect("client protocol versions")
+            .with_root_certificates(roots)
+            .with_no_client_auth();
+        let url = format!(
+            concat!(
+                "wss://localhost:{}/v1/ws",
+                "?systemId=sys_tls&clientType=sidecar&deviceId=sc_tls&pairToken=pt_tls"
+            ),
+            addr.port()
+        );
+        let (mut ws, response) = connect_async_tls_with_config(
+            url,
+            None,
+            false,
+            Some(Connector::Rustls(Arc::new(client))),
+        )
+        .await
+        .expect("wss handshake");
+        assert_eq!(response.status(), 101);
+
+        ws.close(None).await.expect("close ws");
+        while ws.next().await.is_some() {}
+        server.abort();
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
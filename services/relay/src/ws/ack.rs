@@ -0,0 +1,292 @@
+//! 关键事件 ACK 追踪：记录带 `ackRequired=true` 转发的事件，超时未收到引用其 eventId 的
+//! `ack` 时按原来源重新广播，重试次数有界，用尽后放弃并告警。
+
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use tokio::time::MissedTickBehavior;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::state::AppState;
+
+/// ACK 超时环境变量（毫秒）。
+const RELAY_ACK_TIMEOUT_MS_ENV: &str = "RELAY_ACK_TIMEOUT_MS";
+/// ACK 超时后的最大重发次数环境变量（0 表示关闭追踪）。
+const RELAY_ACK_MAX_RETRIES_ENV: &str = "RELAY_ACK_MAX_RETRIES";
+/// 默认 ACK 超时（毫秒）。
+const DEFAULT_ACK_TIMEOUT_MS: u64 = 5_000;
+/// ACK 超时允许区间（毫秒）。
+const ACK_TIMEOUT_RANGE_MS: (u64, u64) = (500, 60_000);
+/// 默认重发次数。
+const DEFAULT_ACK_MAX_RETRIES: u32 = 1;
+/// 重发次数上限。
+const MAX_ACK_RETRIES: u32 = 5;
+/// 同时追踪的事件数上限，超出后新事件不再追踪。
+const MAX_PENDING_ACKS: usize = 1024;
+
+/// ACK 追踪配置。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct AckConfig {
+    /// 等待 ACK 的超时。
+    pub(crate) timeout: Duration,
+    /// 超时后的最大重发次数。
+    pub(crate) max_retries: u32,
+}
+
+impl Default for AckConfig {
+    /// 默认 5 秒超时、重发一次。
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_millis(DEFAULT_ACK_TIMEOUT_MS),
+            max_retries: DEFAULT_ACK_MAX_RETRIES,
+        }
+    }
+}
+
+impl AckConfig {
+    /// 读取环境变量，超时限制在允许区间、重发次数不超过上限。
+    pub(crate) fn from_env() -> Self {
+        Self::from_values(
+            std::env::var(RELAY_ACK_TIMEOUT_MS_ENV).ok().as_deref(),
+            std::env::var(RELAY_ACK_MAX_RETRIES_ENV).ok().as_deref(),
+        )
+    }
+
+    /// 按原始文本解析配置；无法解析时使用默认值。
+    fn from_values(timeout_ms: Option<&str>, max_retries: Option<&str>) -> Self {
+        let (min_ms, max_ms) = ACK_TIMEOUT_RANGE_MS;
+        let timeout_ms = timeout_ms
+            .and_then(|raw| raw.trim().parse::<u64>().ok())
+            .unwrap_or(DEFAULT_ACK_TIMEOUT_MS)
+            .clamp(min_ms, max_ms);
+        let max_retries = max_retries
+            .and_then(|raw| raw.trim().parse::<u32>().ok())
+            .unwrap_or(DEFAULT_ACK_MAX_RETRIES)
+            .min(MAX_ACK_RETRIES);
+        Self {
+            timeout: Duration::from_millis(timeout_ms),
+            max_retries,
+        }
+    }
+
+    /// 是否启用追踪。
+    pub(crate) fn enabled(&self) -> bool {
+        self.max_retries > 0
+    }
+}
+
+/// 等待 ACK 的事件。
+#[derive(Debug, Clone)]
+struct PendingAck {
+    /// 原始发送连接（重发时同样跳过）。
+    origin_id: Uuid,
+    /// 净化后的原始消息。
+    msg: String,
+    /// 事件类型。
+    event_type: String,
+    /// 下一次超时时间。
+    deadline: Instant,
+    /// 剩余重发次数。
+    retries_left: u32,
+}
+
+/// 超时待重发的事件。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct AckRetry {
+    /// 所属 system。
+    pub(crate) system_id: String,
+    /// 事件 ID。
+    pub(crate) event_id: String,
+    /// 原始发送连接。
+    pub(crate) origin_id: Uuid,
+    /// 原始消息。
+    pub(crate) msg: String,
+    /// 事件类型。
+    pub(crate) event_type: String,
+}
+
+/// ACK 追踪表，键为 (systemId, eventId)。
+#[derive(Debug, Default)]
+pub(crate) struct AckTracker {
+    /// 追踪配置。
+    config: AckConfig,
+    /// 等待中的事件。
+    pending: Mutex<HashMap<(String, String), PendingAck>>,
+}
+
+impl AckTracker {
+    /// 按配置创建追踪表。
+    pub(crate) fn new(config: AckConfig) -> Self {
+        Self {
+            config,
+            pending: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// 追踪配置。
+    pub(crate) fn config(&self) -> AckConfig {
+        self.config
+    }
+
+    /// 登记一条要求 ACK 的事件；未启用、缺少 eventId 或追踪表已满时忽略。
+    pub(crate) fn track(
+        &self,
+        system_id: &str,
+        event_id: &str,
+        origin_id: Uuid,
+        msg: &str,
+        event_type: &str,
+        now: Instant,
+    ) {
+        if !self.config.enabled() || event_id.is_empty() {
+            return;
+        }
+        let mut guard = self.pending.lock().unwrap_or_else(|err| err.into_inner());
+        if guard.len() >= MAX_PENDING_ACKS {
+            warn!("ack tracker full, skip system={system_id} event_id={event_id}");
+            return;
+        }
+        guard.insert(
+            (system_id.to_string(), event_id.to_string()),
+            PendingAck {
+                origin_id,
+                msg: msg.to_string(),
+                event_type: event_type.to_string(),
+                deadline: now + self.config.timeout,
+                retries_left: self.config.max_retries,
+            },
+        );
+    }
+
+    /// 收到 ACK 后移除追踪，返回是否命中。
+    pub(crate) fn acknowledge(&self, system_id: &str, event_id: &str) -> bool {
+        let mut guard = self.pending.lock().unwrap_or_else(|err| err.into_inner());
+        guard
+            .remove(&(system_id.to_string(), event_id.to_string()))
+            .is_some()
+    }
+
+    /// 取出已超时的事件：仍有重发次数的返回待重发并顺延超时，次数用尽的移除并告警。
+    pub(crate) fn take_due(&self, now: Instant) -> Vec<AckRetry> {
+        let mut guard = self.pending.lock().unwrap_or_else(|err| err.into_inner());
+        let mut retries = Vec::new();
+        guard.retain(|(system_id, event_id), pending| {
+            if pending.deadline > now {
+                return true;
+            }
+            if pending.retries_left == 0 {
+                warn!(
+                    "ack timeout, give up system={system_id} type={} event_id={event_id}",
+                    pending.event_type
+                );
+                return false;
+            }
+            pending.retries_left -= 1;
+            pending.deadline = now + self.config.timeout;
+            retries.push(AckRetry {
+                system_id: system_id.clone(),
+                event_id: event_id.clone(),
+                origin_id: pending.origin_id,
+                msg: pending.msg.clone(),
+                event_type: pending.event_type.clone(),
+            });
+            true
+        });
+        retries
+    }
+}
+
+/// 检查周期：取超时的 1/4，并限制在 [100ms, 1s]。
+fn ack_check_interval(timeout: Duration) -> Duration {
+    (timeout / 4).clamp(Duration::from_millis(100), Duration::from_secs(1))
+}
+
+/// 启动 ACK 重发任务；未启用追踪时不启动。
+pub(crate) fn spawn_ack_retry_sweeper(state: AppState) {
+    let config = state.acks.config();
+    if !config.enabled() {
+        return;
+    }
+    info!(
+        "ack tracker enabled timeout_ms={} max_retries={}",
+        config.timeout.as_millis(),
+        config.max_retries
+    );
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(ack_check_interval(config.timeout));
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Skip);
+        loop {
+            ticker.tick().await;
+            for retry in state.acks.take_due(Instant::now()) {
+                info!(
+                    "ack timeout, rebroadcast system={} type={} event_id={}",
+                    retry.system_id, retry.event_type, retry.event_id
+                );
+                state
+                    .broadcast(
+                        &retry.system_id,
+                        retry.origin_id,
+                        retry.msg,
+                        &retry.event_type,
+                    )
+                    .await;
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use uuid::Uuid;
+
+    use super::{AckConfig, AckTracker};
+
+    #[test]
+    fn config_is_bounded() {
+        let config = AckConfig::from_values(Some("10"), Some("99"));
+        assert_eq!(config.timeout, Duration::from_millis(500));
+        assert_eq!(config.max_retries, 5);
+        assert!(!AckConfig::from_values(None, Some("0")).enabled());
+        assert_eq!(AckConfig::from_values(None, None), AckConfig::default());
+    }
+
+    #[test]
+    fn unacked_event_is_retried_once_then_dropped() {
+        let tracker = AckTracker::new(AckConfig::default());
+        let origin = Uuid::new_v4();
+        let start = Instant::now();
+        tracker.track(
+            "sys_a",
+            "evt_1",
+            origin,
+            "{}",
+            "tool_connect_request",
+            start,
+        );
+        tracker.track(
+            "sys_a",
+            "evt_2",
+            origin,
+            "{}",
+            "tool_connect_request",
+            start,
+        );
+        assert!(tracker.acknowledge("sys_a", "evt_2"));
+        assert!(!tracker.acknowledge("sys_b", "evt_1"));
+
+        assert!(tracker.take_due(start + Duration::from_secs(4)).is_empty());
+        let retries = tracker.take_due(start + Duration::from_secs(5));
+        assert_eq!(retries.len(), 1);
+        assert_eq!(retries[0].event_id, "evt_1");
+        assert_eq!(retries[0].origin_id, origin);
+
+        assert!(tracker.take_due(start + Duration::from_secs(10)).is_empty());
+        assert!(!tracker.acknowledge("sys_a", "evt_1"));
+    }
+}
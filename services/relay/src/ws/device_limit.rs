@@ -0,0 +1,50 @@
+//! 单设备并发连接上限：同一 `(systemId, deviceId)` 超出上限时淘汰最早的连接，防止重连风暴堆积句柄。
+
+use tracing::warn;
+
+/// 单设备并发连接上限环境变量。
+const RELAY_MAX_CONNECTIONS_PER_DEVICE_ENV: &str = "RELAY_MAX_CONNECTIONS_PER_DEVICE";
+/// 单设备默认并发连接上限。
+pub(crate) const DEFAULT_MAX_CONNECTIONS_PER_DEVICE: usize = 3;
+
+/// 读取单设备并发连接上限；未设置、非法或为 0 时使用默认值。
+pub(crate) fn max_connections_per_device_from_env() -> usize {
+    parse_max_connections_per_device(
+        std::env::var(RELAY_MAX_CONNECTIONS_PER_DEVICE_ENV)
+            .ok()
+            .as_deref(),
+    )
+}
+
+/// 解析单设备并发连接上限配置。
+fn parse_max_connections_per_device(raw: Option<&str>) -> usize {
+    match raw.map(|value| value.trim().parse::<usize>()) {
+        Some(Ok(limit)) if limit > 0 => limit,
+        Some(_) => {
+            warn!(
+                "invalid {}, fallback to {}",
+                RELAY_MAX_CONNECTIONS_PER_DEVICE_ENV, DEFAULT_MAX_CONNECTIONS_PER_DEVICE
+            );
+            DEFAULT_MAX_CONNECTIONS_PER_DEVICE
+        }
+        None => DEFAULT_MAX_CONNECTIONS_PER_DEVICE,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DEFAULT_MAX_CONNECTIONS_PER_DEVICE, parse_max_connections_per_device};
+
+    #[test]
+    fn limit_falls_back_to_default_when_unset_or_invalid() {
+        assert_eq!(
+            parse_max_connections_per_device(None),
+            DEFAULT_MAX_CONNECTIONS_PER_DEVICE
+        );
+        assert_eq!(
+            parse_max_connections_per_device(Some("0")),
+            DEFAULT_MAX_CONNECTIONS_PER_DEVICE
+        );
+        assert_eq!(parse_max_connections_per_device(Some(" 5 ")), 5);
+    }
+}
@@ -1,32 +1,49 @@
-//! WebSocket 消息净化与 server_presence 发送。
+//! WebSocket 消息净化与 server_presence 发送；事件类型按白名单校验，
+//! `compressed` 包装的 payload 原样透传，其声明的内层类型同样按白名单校验并用于转发判定。
 
 use axum::extract::ws::Message;
 use serde_json::{Value, json};
 use tokio::sync::mpsc;
 use uuid::Uuid;
-use yc_shared_protocol::{EventEnvelope, now_rfc3339_nanos};
+use yc_shared_protocol::{
+    COMPRESSED_EVENT_TYPE, COMPRESSED_INNER_TYPE_FIELD, EventEnvelope, now_rfc3339_nanos,
+};
 
-use crate::state::RelayWriteCommand;
+use crate::{
+    api::types::ConnectionAuthMode,
+    state::RelayWriteCommand,
+    ws::event_types::{allow_unknown_events, check_event_type},
+};
 
 /// 事件摘要：用于日志追踪，避免打印完整 payload。
 #[derive(Debug, Clone, Default)]
 pub(crate) struct EnvelopeSummary {
     /// 事件类型。
     pub(crate) event_type: String,
+    /// 转发判定用的类型：`compressed` 包装取其声明的内层类型，其余同 `event_type`。
+    pub(crate) routing_type: String,
     /// 事件 ID。
     pub(crate) event_id: String,
     /// 链路追踪 ID。
     pub(crate) trace_id: String,
     /// 目标工具 ID（可选）。
     pub(crate) tool_id: String,
+    /// 是否要求接收方 ACK。
+    pub(crate) ack_required: bool,
+    /// `ack` 事件确认的原始 eventId（仅 `ack` 事件）。
+    pub(crate) acked_event_id: String,
+    /// 来源连接内递增序号（可选）。
+    pub(crate) seq: Option<u64>,
 }
 
-/// 校验并修正上行 envelope。
+/// 校验并修正上行 envelope；缺省 `v` 补为连接协商的协议版本，
+/// 不在来源角色白名单内的事件类型返回错误。
 pub(crate) fn sanitize_envelope(
     raw: &str,
     system_id: &str,
     source_client_type: &str,
     source_device_id: &str,
+    protocol_version: u8,
 ) -> Result<String, String> {
     let mut env: Value = serde_json::from_str(raw).map_err(|err| err.to_string())?;
     let obj = env
@@ -34,7 +51,7 @@ pub(crate) fn sanitize_envelope(
         .ok_or_else(|| "envelope must be an object".to_string())?;
 
     if !obj.contains_key("v") {
-        obj.insert("v".to_string(), json!(1));
+        obj.insert("v".to_string(), json!(protocol_version));
     }
 
     let event_id_empty = obj
@@ -71,6 +88,15 @@ pub(crate) fn sanitize_envelope(
     if event_type.is_empty() {
         return Err("missing type".to_string());
     }
+    check_event_type(source_client_type, event_type, allow_unknown_events())?;
+    if event_type == COMPRESSED_EVENT_TYPE
+        && let Some(inner_type) = obj
+            .get("payload")
+            .and_then(|payload| payload.get(COMPRESSED_INNER_TYPE_FIELD))
+            .and_then(Value::as_str)
+    {
+        check_event_type(source_client_type, inner_type, allow_unknown_events())?;
+    }
 
     if let Some(sid) = obj.get("systemId").and_then(Value::as_str)
         && sid != system_id
@@ -120,12 +146,23 @@ pub(crate) fn summarize_envelope(raw: &str) -> EnvelopeSummary {
         .and_then(Value::as_object)
         .cloned()
         .unwrap_or_default();
-    EnvelopeSummary {
-        event_type: value
-            .get("type")
+    let event_type = value
+        .get("type")
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+        .to_string();
+    let routing_type = if event_type == COMPRESSED_EVENT_TYPE {
+        payload
+            .get(COMPRESSED_INNER_TYPE_FIELD)
             .and_then(Value::as_str)
-            .unwrap_or_default()
-            .to_string(),
+            .unwrap_or(COMPRESSED_EVENT_TYPE)
+            .to_string()
+    } else {
+        event_type.clone()
+    };
+    EnvelopeSummary {
+        event_type,
+        routing_type,
         event_id: value
             .get("eventId")
             .and_then(Value::as_str)
@@ -141,27 +178,106 @@ pub(crate) fn summarize_envelope(raw: &str) -> EnvelopeSummary {
             .and_then(Value::as_str)
             .unwrap_or_default()
             .to_string(),
+        ack_required: value
+            .get("ackRequired")
+            .and_then(Value::as_bool)
+            .unwrap_or(false),
+        acked_event_id: payload
+            .get("eventId")
+            .and_then(Value::as_str)
+            .map(str::trim)
+            .unwrap_or_default()
+            .to_string(),
+        seq: value.get("seq").and_then(Value::as_u64),
     }
 }
 
-/// 连接成功后回推 server_presence。
+/// 连接成功后回推 server_presence，附带连接建立时间与房间当前在线连接数；
+/// 握手请求带 trace id 时沿用，便于跨服务关联。
+#[allow(clippy::too_many_arguments)]
 pub(crate) fn send_server_presence(
     tx: &mpsc::Sender<RelayWriteCommand>,
     system_id: &str,
     client_type: &str,
     device_id: &str,
+    auth_mode: ConnectionAuthMode,
+    protocol_version: u8,
+    connected_at: &str,
+    client_count: usize,
+    trace_id: Option<&str>,
 ) {
-    let env = EventEnvelope::new(
+    let mut env = EventEnvelope::new(
         "server_presence",
         system_id,
         json!({
             "status": "connected",
             "clientType": client_type,
             "deviceId": device_id,
+            "authMode": auth_mode.as_str(),
+            "protocolVersion": protocol_version,
+            "connectedAt": connected_at,
+            "clientCount": client_count,
         }),
     );
+    env.v = protocol_version;
+    if let Some(trace_id) = trace_id {
+        env.trace_id = Some(trace_id.to_string());
+    }
 
     if let Ok(raw) = serde_json::to_string(&env) {
         let _ = tx.try_send(RelayWriteCommand::Direct(Message::Text(raw.into())));
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{Value, json};
+    use yc_shared_protocol::{EventEnvelope, encode_envelope_text, inflate_envelope_text};
+
+    use super::{sanitize_envelope, summarize_envelope};
+
+    #[test]
+    fn compressed_wrapper_payload_passes_through_unchanged() {
+        let mut env = EventEnvelope::new(
+            "tool_details_snapshot",
+            "sys_a",
+            json!({ "blob": "y".repeat(32 * 1024) }),
+        );
+        // 内层伪造的来源必须被外层 relay 注入值覆盖。
+        env.source_device_id = Some("forged".to_string());
+        let wrapped = encode_envelope_text(&env, 1024).expect("encode");
+
+        let sanitized =
+            sanitize_envelope(&wrapped, "sys_a", "sidecar", "sc_a", 1).expect("sanitize wrapper");
+        let before: Value = serde_json::from_str(&wrapped).expect("wrapper json");
+        let after: Value = serde_json::from_str(&sanitized).expect("sanitized json");
+        assert_eq!(after["type"], "compressed");
+        assert_eq!(after["payload"], before["payload"]);
+        let summary = summarize_envelope(&sanitized);
+        assert_eq!(summary.event_type, "compressed");
+        assert_eq!(summary.routing_type, "tool_details_snapshot");
+
+        // 声明的内层类型不在来源角色白名单内时与明文帧一样被拒绝。
+        let mut forged: Value = serde_json::from_str(&wrapped).expect("wrapper json");
+        forged["payload"]["innerType"] = json!("tool_chat_request");
+        assert!(sanitize_envelope(&forged.to_string(), "sys_a", "sidecar", "sc_a", 1).is_err());
+
+        let inflated = inflate_envelope_text(&sanitized)
+            .expect("inflate")
+            .expect("compressed");
+        let inner: Value = serde_json::from_str(&inflated).expect("inner json");
+        assert_eq!(inner["type"], "tool_details_snapshot");
+        assert_eq!(inner["sourceDeviceId"], "sc_a");
+        assert_eq!(inner["sourceClientType"], "sidecar");
+    }
+
+    #[test]
+    fn unknown_event_type_is_dropped_during_sanitize() {
+        let raw = r#"{"v":1,"type":"tools_snapshto","payload":{}}"#;
+        let err = sanitize_envelope(raw, "sys_a", "sidecar", "sc_a", 1).expect_err("unknown type");
+        assert!(err.contains("unknown event type"));
+
+        let raw = r#"{"v":1,"type":"tools_snapshot","payload":{}}"#;
+        assert!(sanitize_envelope(raw, "sys_a", "sidecar", "sc_a", 1).is_ok());
+    }
+}
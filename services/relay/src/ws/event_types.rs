@@ -0,0 +1,174 @@
+//! 事件类型白名单：按来源角色校验上行 envelope 的 `type`，未知类型在净化阶段丢弃并告警；
+//! 设置 `RELAY_ALLOW_UNKNOWN_EVENTS` 后放行未知类型便于协议试验，relay 自有事件始终拒绝。
+
+use std::sync::OnceLock;
+
+use tracing::{info, warn};
+use yc_shared_protocol::{ACK_EVENT_TYPE, COMPRESSED_EVENT_TYPE};
+
+use crate::ws::{
+    presence::{PEER_JOINED_EVENT, PEER_LEFT_EVENT},
+    revocation::{PAIR_TOKEN_REVOKED_EVENT, SESSION_REVOKED_EVENT},
+    seq_gap::RESYNC_HINT_EVENT,
+    shutdown::SERVER_SHUTDOWN_EVENT,
+};
+
+/// 放行未知事件类型的环境变量。
+const RELAY_ALLOW_UNKNOWN_EVENTS_ENV: &str = "RELAY_ALLOW_UNKNOWN_EVENTS";
+/// relay 自身下发的事件类型，任何客户端都不得上行伪造。
+const RELAY_ONLY_EVENT_TYPES: &[&str] = &[
+    "server_presence",
+    SERVER_SHUTDOWN_EVENT,
+    SESSION_REVOKED_EVENT,
+    PAIR_TOKEN_REVOKED_EVENT,
+    RESYNC_HINT_EVENT,
+    PEER_JOINED_EVENT,
+    PEER_LEFT_EVENT,
+];
+/// 双向通用的事件类型。
+const SHARED_EVENT_TYPES: &[&str] = &[ACK_EVENT_TYPE, COMPRESSED_EVENT_TYPE];
+/// sidecar 上行事件类型。
+const SIDECAR_EVENT_TYPES: &[&str] = &[
+    "heartbeat",
+    "session_ready",
+    "tools_snapshot",
+    "tools_candidates",
+    "metrics_snapshot",
+    "metrics_history",
+    "collection_errors",
+    "tool_details_snapshot",
+    "tool_details_now_result",
+    "tool_whitelist_updated",
+    "tool_label_updated",
+    "tool_process_control_updated",
+    "tool_kill_updated",
+    "tool_logs",
+    "controller_bind_updated",
+    "controller_bind_request",
+    "sidecar_log_level_updated",
+    "sidecar_diagnostics",
+    "tool_chat_started",
+    "tool_chat_chunk",
+    "tool_chat_finished",
+    "tool_report_fetch_started",
+    "tool_report_fetch_chunk",
+    "tool_report_fetch_finished",
+    "tool_launch_started",
+    "tool_launch_finished",
+    "tool_launch_failed",
+    "tool_media_stage_progress",
+    "tool_media_stage_finished",
+    "tool_media_stage_failed",
+];
+/// app 上行事件类型（控制命令）。
+const APP_EVENT_TYPES: &[&str] = &[
+    "tools_refresh_request",
+    "tool_connect_request",
+    "tool_disconnect_request",
+    "tool_whitelist_reset_request",
+    "tool_details_refresh_request",
+    "tool_details_now_request",
+    "tool_process_control_request",
+    "tool_kill_request",
+    "tool_logs_request",
+    "tool_label_set_request",
+    "tool_focus_request",
+    "tool_unfocus_request",
+    "tool_chat_request",
+    "tool_chat_cancel_request",
+    "tool_report_fetch_request",
+    "tool_launch_request",
+    "tool_media_stage_request",
+    "controller_rebind_request",
+    "sidecar_log_level_request",
+    "sidecar_diagnostics_request",
+    "metrics_history_request",
+    "app_disconnect",
+];
+
+/// 启动时读取的放行开关。
+static ALLOW_UNKNOWN_EVENTS: OnceLock<bool> = OnceLock::new();
+
+/// 启动时加载放行开关并打印白名单规模。
+pub(crate) fn init_event_allowlist() {
+    let allow_unknown = allow_unknown_events();
+    info!(
+        "event allowlist loaded sidecar={} app={} shared={} allow_unknown={allow_unknown}",
+        SIDECAR_EVENT_TYPES.len(),
+        APP_EVENT_TYPES.len(),
+        SHARED_EVENT_TYPES.len()
+    );
+}
+
+/// 是否放行未知事件类型（首次调用时读取环境变量）。
+pub(crate) fn allow_unknown_events() -> bool {
+    *ALLOW_UNKNOWN_EVENTS.get_or_init(|| {
+        parse_allow_unknown(
+            std::env::var(RELAY_ALLOW_UNKNOWN_EVENTS_ENV)
+                .ok()
+                .as_deref(),
+        )
+    })
+}
+
+/// 解析放行开关：`1/true/yes/on` 为开启，其它非空值告警后按关闭处理。
+fn parse_allow_unknown(raw: Option<&str>) -> bool {
+    let Some(raw) = raw.map(str::trim).filter(|value| !value.is_empty()) else {
+        return false;
+    };
+    match raw.to_ascii_lowercase().as_str() {
+        "1" | "true" | "yes" | "on" => true,
+        "0" | "false" | "no" | "off" => false,
+        _ => {
+            warn!("invalid {RELAY_ALLOW_UNKNOWN_EVENTS_ENV}={raw}, fallback to false");
+            false
+        }
+    }
+}
+
+/// 校验来源角色能否上行该事件类型。
+pub(crate) fn check_event_type(
+    source_client_type: &str,
+    event_type: &str,
+    allow_unknown: bool,
+) -> Result<(), String> {
+    if RELAY_ONLY_EVENT_TYPES.contains(&event_type) {
+        return Err(format!("reserved event type: {event_type}"));
+    }
+    let role_types = match source_client_type {
+        "sidecar" => SIDECAR_EVENT_TYPES,
+        "app" => APP_EVENT_TYPES,
+        _ => &[],
+    };
+    if allow_unknown || SHARED_EVENT_TYPES.contains(&event_type) || role_types.contains(&event_type)
+    {
+        return Ok(());
+    }
+    Err(format!(
+        "unknown event type: {event_type} (source={source_client_type})"
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check_event_type, parse_allow_unknown};
+
+    #[test]
+    fn known_types_pass_for_their_source_role() {
+        assert!(check_event_type("sidecar", "tools_snapshot", false).is_ok());
+        assert!(check_event_type("app", "tool_connect_request", false).is_ok());
+        assert!(check_event_type("app", "ack", false).is_ok());
+    }
+
+    #[test]
+    fn unknown_misdirected_and_reserved_types_are_rejected() {
+        assert!(check_event_type("sidecar", "tools_snapshto", false).is_err());
+        assert!(check_event_type("app", "tools_snapshot", false).is_err());
+        assert!(check_event_type("app", "server_shutdown", true).is_err());
+        assert!(check_event_type("sidecar", "experimental_probe", true).is_ok());
+
+        assert!(parse_allow_unknown(Some(" TRUE ")));
+        assert!(!parse_allow_unknown(Some("maybe")));
+        assert!(!parse_allow_unknown(None));
+    }
+}
@@ -0,0 +1,38 @@
+//! WebSocket 帧大小上限：超限文本帧在净化前直接丢弃并计数，避免大包在房间内被逐连接复制广播。
+
+use tracing::warn;
+
+/// 单帧大小上限环境变量（字节）。
+const RELAY_MAX_FRAME_BYTES_ENV: &str = "RELAY_MAX_FRAME_BYTES";
+/// 单帧大小默认上限：256 KiB。
+pub(crate) const DEFAULT_MAX_FRAME_BYTES: usize = 256 * 1024;
+
+/// 读取单帧大小上限；未设置、非法或为 0 时使用默认值。
+pub(crate) fn max_frame_bytes_from_env() -> usize {
+    parse_max_frame_bytes(std::env::var(RELAY_MAX_FRAME_BYTES_ENV).ok().as_deref())
+}
+
+/// 解析单帧大小上限配置。
+fn parse_max_frame_bytes(raw: Option<&str>) -> usize {
+    match raw.map(|value| value.trim().parse::<usize>()) {
+        Some(Ok(limit)) if limit > 0 => limit,
+        Some(_) => {
+            warn!("invalid {RELAY_MAX_FRAME_BYTES_ENV}, fallback to {DEFAULT_MAX_FRAME_BYTES}");
+            DEFAULT_MAX_FRAME_BYTES
+        }
+        None => DEFAULT_MAX_FRAME_BYTES,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DEFAULT_MAX_FRAME_BYTES, parse_max_frame_bytes};
+
+    #[test]
+    fn limit_falls_back_to_default_when_unset_or_invalid() {
+        assert_eq!(parse_max_frame_bytes(None), DEFAULT_MAX_FRAME_BYTES);
+        assert_eq!(parse_max_frame_bytes(Some("0")), DEFAULT_MAX_FRAME_BYTES);
+        assert_eq!(parse_max_frame_bytes(Some("1MB")), DEFAULT_MAX_FRAME_BYTES);
+        assert_eq!(parse_max_frame_bytes(Some(" 1048576 ")), 1_048_576);
+    }
+}
@@ -3,33 +3,96 @@
 use axum::http::StatusCode;
 
 use crate::{
-    api::{error::ApiError, types::WsQuery},
+    api::{
+        error::ApiError,
+        types::{ClientRole, ConnectionAuthMode, PairTokenAuthDecision, WsQuery},
+    },
     auth::{
+        audit::{AuditAction, AuditRecord},
+        pair_tokens::PairTokenSet,
         pop::{parse_ts, verify_ts_window, ws_pop_payload},
-        token::{authorize_pair_token, verify_access_token, verify_pop_signature},
+        store::unix_now,
+        token::{
+            PAIR_TOKEN_REVOKED_REASON, authorize_pair_token, verify_access_token,
+            verify_pop_signature,
+        },
     },
     state::{AppState, SystemRoom},
 };
 
 impl AppState {
-    /// 连接鉴权入口：sidecar 走 pairToken；app 仅允许 accessToken + PoP。
-    pub(crate) async fn authorize_connection(&self, q: &WsQuery) -> Result<(), ApiError> {
-        if q.client_type == "sidecar" {
-            if q.pair_token.trim().is_empty() {
-                return Err(ApiError::new(
-                    StatusCode::BAD_REQUEST,
-                    "MISSING_CREDENTIALS",
-                    "sidecar 缺少 pairToken",
-                    "请重启 sidecar 并检查配置",
-                ));
+    /// 连接鉴权入口：按角色分派，sidecar 走 pairToken；app 仅允许 accessToken + PoP。
+    /// 返回本次连接实际使用的鉴权方式，供连接审计与 server_presence 使用；
+    /// app 鉴权失败时为已登记设备记录最近失败原因；任一角色失败均按错误码计入指标。
+    pub(crate) async fn authorize_connection(
+        &self,
+        role: ClientRole,
+        q: &WsQuery,
+    ) -> Result<ConnectionAuthMode, ApiError> {
+        let result = match role {
+            ClientRole::Sidecar => self
+                .authorize_sidecar_connection(q)
+                .await
+                .map(|_| ConnectionAuthMode::PairToken),
+            ClientRole::App => {
+                let result = self.authorize_app_connection(q).await;
+                if let Err(err) = &result {
+                    self.record_device_auth_failure(&q.system_id, &q.device_id, err.code)
+                        .await;
+                }
+                result
             }
-            return self.authorize_sidecar(q).await;
+        };
+        if let Err(err) = &result {
+            self.metrics.record_auth_failure(err.code);
+            self.audit.record(
+                AuditRecord {
+                    action: AuditAction::WsAuth,
+                    system_id: &q.system_id,
+                    device_id: &q.device_id,
+                    key_id: q.key_id.as_deref().unwrap_or_default(),
+                    target_device_id: None,
+                },
+                err.code,
+            );
         }
+        result
+    }
+
+    /// sidecar 连接鉴权：必须携带 pairToken。
+    async fn authorize_sidecar_connection(&self, q: &WsQuery) -> Result<(), ApiError> {
+        if q.pair_token.trim().is_empty() {
+            return Err(ApiError::new(
+                StatusCode::BAD_REQUEST,
+                "MISSING_CREDENTIALS",
+                "sidecar 缺少 pairToken",
+                "请重启 sidecar 并检查配置",
+            ));
+        }
+        self.authorize_sidecar(q).await
+    }
 
+    /// app 连接鉴权：默认仅接受 accessToken + PoP；system 关闭 `requireAccessToken` 时允许 pairToken 直连。
+    async fn authorize_app_connection(&self, q: &WsQuery) -> Result<ConnectionAuthMode, ApiError> {
         if let Some(access_token) = q.access_token.as_deref().map(str::trim)
             && !access_token.is_empty()
         {
-            return self.authorize_app_with_access(q).await;
+            return self
+                .authorize_app_with_access(q)
+                .await
+                .map(|_| ConnectionAuthMode::AccessToken);
+        }
+
+        if !q.pair_token.trim().is_empty()
+            && !self
+                .system_features(&q.system_id)
+                .await
+                .require_access_token
+        {
+            return self
+                .authorize_app_with_pair(q)
+                .await
+                .map(|_| ConnectionAuthMode::PairToken);
         }
 
         let has_legacy_pair = !q.pair_token.trim().is_empty()
@@ -56,54 +119,119 @@ impl AppState {
         ))
     }
 
-    /// sidecar 鉴权并建房。
+    /// sidecar 鉴权并建房；持已作废令牌时以 `PAIR_TOKEN_REVOKED` 拒绝，提示 sidecar 换新令牌。
     async fn authorize_sidecar(&self, q: &WsQuery) -> Result<(), ApiError> {
         let incoming_pair_token = q.pair_token.trim();
+        let revoked_pair_token_hashes = self
+            .auth_store
+            .read()
+            .await
+            .system_ref(&q.system_id)
+            .map(|system| system.revoked_pair_token_hashes.clone())
+            .unwrap_or_default();
+        let now = unix_now();
         let mut guard = self.systems.write().await;
-        let Some(room) = guard.get_mut(&q.system_id) else {
-            guard.insert(
-                q.system_id.clone(),
-                SystemRoom {
-                    pair_token: incoming_pair_token.to_string(),
-                    ticket_nonces: std::collections::HashMap::new(),
-                    app_nonces: std::collections::HashMap::new(),
-                    clients: std::collections::HashMap::new(),
-                },
-            );
-            self.persist_pair_token_meta(&q.system_id, incoming_pair_token)
-                .await;
-            return Ok(());
-        };
-
-        let sidecar_clients = room
-            .clients
-            .values()
-            .filter(|client| client.client_type == "sidecar")
-            .count();
-        match authorize_pair_token(
-            Some(room.pair_token.as_str()),
+        let room = guard.get_mut(&q.system_id);
+        let sidecar_clients = room.as_ref().map_or(0, |room| {
+            room.clients
+                .values()
+                .filter(|client| client.role == ClientRole::Sidecar)
+                .count()
+        });
+        let decision = authorize_pair_token(
+            room.as_ref().map(|room| &room.pair_tokens),
             sidecar_clients,
-            &q.client_type,
+            ClientRole::Sidecar,
             incoming_pair_token,
+            &revoked_pair_token_hashes,
+            now,
+        )
+        .map_err(|reason| {
+            if reason == PAIR_TOKEN_REVOKED_REASON {
+                ApiError::new(
+                    StatusCode::UNAUTHORIZED,
+                    "PAIR_TOKEN_REVOKED",
+                    "pairToken 已作废",
+                    "sidecar 将生成新的配对令牌后重连",
+                )
+            } else {
+                ApiError::new(
+                    StatusCode::UNAUTHORIZED,
+                    "PAIR_TOKEN_MISMATCH",
+                    "pairToken 不匹配",
+                    "请重新生成配对信息后再试",
+                )
+            }
+        })?;
+        match (decision, room) {
+            (PairTokenAuthDecision::Allow, _) => Ok(()),
+            (PairTokenAuthDecision::Rotate, Some(room)) => {
+                room.pair_tokens.rotate(
+                    incoming_pair_token,
+                    self.auth_config.pair_token_grace_sec,
+                    now,
+                );
+                let pair_tokens = room.pair_tokens.clone();
+                drop(guard);
+                self.persist_pair_token_meta(&q.system_id, &pair_tokens)
+                    .await;
+                Ok(())
+            }
+            (PairTokenAuthDecision::Initialize | PairTokenAuthDecision::Rotate, _) => {
+                guard.insert(
+                    q.system_id.clone(),
+                    SystemRoom {
+                        pair_tokens: PairTokenSet::new(incoming_pair_token),
+                        ticket_nonces: std::collections::HashMap::new(),
+                        app_nonces: std::collections::HashMap::new(),
+                        clients: std::collections::HashMap::new(),
+                        oversized_frames: 0,
+                    },
+                );
+                drop(guard);
+                self.persist_pair_token_meta(&q.system_id, &PairTokenSet::new(incoming_pair_token))
+                    .await;
+                Ok(())
+            }
+        }
+    }
+
+    /// app 使用 pairToken 直连（仅限已关闭 `requireAccessToken` 的 system）。
+    async fn authorize_app_with_pair(&self, q: &WsQuery) -> Result<(), ApiError> {
+        let guard = self.systems.read().await;
+        let Some(room) = guard.get(&q.system_id) else {
+            return Err(ApiError::new(
+                StatusCode::UNAUTHORIZED,
+                "SYSTEM_NOT_REGISTERED",
+                "宿主机未在线",
+                "请先启动 sidecar",
+            ));
+        };
+        if !room.has_online_sidecar() {
+            return Err(ApiError::new(
+                StatusCode::UNAUTHORIZED,
+                "SYSTEM_NOT_REGISTERED",
+                "宿主机 sidecar 未在线",
+                "请先启动 sidecar",
+            ));
+        }
+        authorize_pair_token(
+            Some(&room.pair_tokens),
+            room.clients.len(),
+            ClientRole::App,
+            q.pair_token.trim(),
+            &[],
+            unix_now(),
         )
+        .map(|_| ())
         .map_err(|_| {
             ApiError::new(
                 StatusCode::UNAUTHORIZED,
                 "PAIR_TOKEN_MISMATCH",
                 "pairToken 不匹配",
-                "请重新生成配对信息后再试",
+                "请使用最新配对信息",
             )
-        })? {
-            crate::api::types::PairTokenAuthDecision::Allow => Ok(()),
-            crate::api::types::PairTokenAuthDecision::Rotate => {
-                room.pair_token = incoming_pair_token.to_string();
-                drop(guard);
-                self.persist_pair_token_meta(&q.system_id, incoming_pair_token)
-                    .await;
-                Ok(())
-            }
-            crate::api::types::PairTokenAuthDecision::Initialize => Ok(()),
-        }
+        })
     }
 
     /// app 使用 access token + PoP 的生产鉴权。
@@ -141,7 +269,12 @@ impl AppState {
             "ACCESS_SIGNATURE_EXPIRED",
             "签名时间戳无效",
         )?;
-        verify_ts_window(ts, "ACCESS_SIGNATURE_EXPIRED", "签名时间窗已过期")?;
+        verify_ts_window(
+            ts,
+            self.auth_config.pop_skew_sec,
+            "ACCESS_SIGNATURE_EXPIRED",
+            "签名时间窗已过期",
+        )?;
 
         let nonce = q
             .nonce
@@ -175,7 +308,7 @@ impl AppState {
             let guard = self.auth_store.read().await;
             verify_access_token(
                 access_token,
-                &guard.signing_key,
+                &guard.signing_keys,
                 &q.system_id,
                 &q.device_id,
                 key_id,
@@ -242,7 +375,7 @@ impl AppState {
         }
         room.app_nonces.insert(
             nonce.to_string(),
-            now.saturating_add(crate::api::types::POP_MAX_SKEW_SEC),
+            now.saturating_add(self.auth_config.pop_skew_sec),
         );
 
         drop(guard);
@@ -251,3 +384,263 @@ impl AppState {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::HashMap,
+        sync::{Arc, atomic::AtomicU64},
+    };
+
+    use base64::{Engine as _, engine::general_purpose::URL_SAFE_NO_PAD};
+    use ed25519_dalek::{Signer, SigningKey};
+    use tokio::sync::{RwLock, mpsc};
+    use uuid::Uuid;
+
+    use crate::{
+        api::types::{AuthStore, ClientRole, ConnectionAuthMode, WsQuery},
+        auth::{
+            handlers::test_support::device, pair_tokens::PairTokenSet, pop::ws_pop_payload,
+            signing_keys::SigningKeyRing, store::unix_now, token::issue_access_token,
+        },
+        state::{AppState, ClientHandle, SystemRoom},
+    };
+
+    /// 以给定房间与认证存储构造状态（存储只写入内存后端）。
+    fn state_with(rooms: HashMap<String, SystemRoom>, store: AuthStore) -> AppState {
+        AppState {
+            systems: Arc::new(RwLock::new(rooms)),
+            auth_store: Arc::new(RwLock::new(store)),
+            ..AppState::for_test()
+        }
+    }
+
+    /// 构造一个 sidecar 在线的房间。
+    fn online_room(pair_token: &str) -> SystemRoom {
+        let (sender, _receiver) = mpsc::channel(1);
+        let mut clients = HashMap::new();
+        clients.insert(
+            Uuid::new_v4(),
+            ClientHandle {
+                role: ClientRole::Sidecar,
+                device_id: "sc_a".to_string(),
+                seq: 0,
+                sender,
+                drop_count: Arc::new(AtomicU64::new(0)),
+                disconnect: Default::default(),
+            },
+        );
+        SystemRoom {
+            pair_tokens: PairTokenSet::new(pair_token),
+            ticket_nonces: HashMap::new(),
+            app_nonces: HashMap::new(),
+            clients,
+            oversized_frames: 0,
+        }
+    }
+
+    /// 构造仅携带 pairToken 的 app 握手参数。
+    fn pair_only_query(system_id: &str, pair_token: &str) -> WsQuery {
+        WsQuery {
+            system_id: system_id.to_string(),
+            client_type: "app".to_string(),
+            device_id: "ios_a".to_string(),
+            pair_token: pair_token.to_string(),
+            pair_ticket: None,
+            host_name: None,
+            access_token: None,
+            key_id: None,
+            ts: None,
+            nonce: None,
+            sig: None,
+            protocol_version: None,
+        }
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn pair_token_app_connect_follows_per_system_features() {
+        let mut store = AuthStore::new("seed".to_string());
+        store.system_mut("sys_strict");
+        store.system_mut("sys_legacy").features.require_access_token = false;
+        let mut rooms = HashMap::new();
+        rooms.insert("sys_strict".to_string(), online_room("pt_strict"));
+        rooms.insert("sys_legacy".to_string(), online_room("pt_legacy"));
+        let state = AppState {
+            systems: Arc::new(RwLock::new(rooms)),
+            auth_store: Arc::new(RwLock::new(store)),
+            ..AppState::for_test()
+        };
+
+        let strict = state
+            .authorize_connection(ClientRole::App, &pair_only_query("sys_strict", "pt_strict"))
+            .await;
+        assert_eq!(
+            strict.expect_err("strict system should reject").code,
+            "PAIR_TOKEN_NOT_SUPPORTED"
+        );
+
+        let legacy = state
+            .authorize_connection(ClientRole::App, &pair_only_query("sys_legacy", "pt_legacy"))
+            .await
+            .expect("legacy system should allow pairToken");
+        assert_eq!(legacy, ConnectionAuthMode::PairToken);
+
+        let mismatch = state
+            .authorize_connection(ClientRole::App, &pair_only_query("sys_legacy", "pt_other"))
+            .await;
+        assert_eq!(
+            mismatch
+                .expect_err("wrong pairToken should be rejected")
+                .code,
+            "PAIR_TOKEN_MISMATCH"
+        );
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn previous_pair_token_is_accepted_only_within_grace_window() {
+        let mut store = AuthStore::new("seed".to_string());
+        store.system_mut("sys_grace").features.require_access_token = false;
+        store
+            .system_mut("sys_expired")
+            .features
+            .require_access_token = false;
+        let now = unix_now();
+        let mut grace = online_room("pt_old");
+        grace.pair_tokens.rotate("pt_new", 600, now);
+        let mut expired = online_room("pt_old");
+        expired
+            .pair_tokens
+            .rotate("pt_new", 600, now.saturating_sub(601));
+        let mut rooms = HashMap::new();
+        rooms.insert("sys_grace".to_string(), grace);
+        rooms.insert("sys_expired".to_string(), expired);
+        let state = state_with(rooms, store);
+
+        for token in ["pt_new", "pt_old"] {
+            let mode = state
+                .authorize_connection(ClientRole::App, &pair_only_query("sys_grace", token))
+                .await
+                .expect("current and previous token should be accepted in grace window");
+            assert_eq!(mode, ConnectionAuthMode::PairToken);
+        }
+        let stale = state
+            .authorize_connection(ClientRole::App, &pair_only_query("sys_expired", "pt_old"))
+            .await;
+        assert_eq!(
+            stale.expect_err("previous token should expire").code,
+            "PAIR_TOKEN_MISMATCH"
+        );
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn sidecar_connect_is_tagged_pair_token() {
+        let state = state_with(HashMap::new(), AuthStore::new("seed".to_string()));
+        let mut query = pair_only_query("sys_new", "pt_new");
+        query.client_type = "sidecar".to_string();
+        let mode = state
+            .authorize_connection(ClientRole::Sidecar, &query)
+            .await
+            .expect("sidecar should register system");
+        assert_eq!(mode, ConnectionAuthMode::PairToken);
+    }
+
+    /// 构造已登记 `sys_a/ios_a` 设备且 sidecar 在线的状态，返回状态与有效 accessToken。
+    fn registered_device_state(device_key: &SigningKey) -> (AppState, String) {
+        let mut store = AuthStore::new("seed".to_string());
+        store.system_mut("sys_a").devices.insert(
+            "ios_a".to_string(),
+            device(
+                "ios_a",
+                "kid_a",
+                &URL_SAFE_NO_PAD.encode(device_key.verifying_key().to_bytes()),
+                "ACTIVE",
+            ),
+        );
+        let access_token = issue_access_token(&store.signing_keys, "sys_a", "ios_a", "kid_a", 600)
+            .expect("issue access token");
+        let mut rooms = HashMap::new();
+        rooms.insert("sys_a".to_string(), online_room("pt_a"));
+        (state_with(rooms, store), access_token)
+    }
+
+    /// 构造携带 accessToken + PoP 签名的 app 握手参数。
+    fn access_query(access_token: &str, device_key: &SigningKey, nonce: &str) -> WsQuery {
+        let ts = unix_now();
+        let payload = ws_pop_payload("sys_a", "ios_a", "kid_a", ts, nonce);
+        let mut query = pair_only_query("sys_a", "");
+        query.access_token = Some(access_token.to_string());
+        query.key_id = Some("kid_a".to_string());
+        query.ts = Some(ts.to_string());
+        query.nonce = Some(nonce.to_string());
+        query.sig = Some(URL_SAFE_NO_PAD.encode(device_key.sign(payload.as_bytes()).to_bytes()));
+        query
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn access_token_connect_is_tagged_access_token() {
+        let device_key = SigningKey::from_bytes(&[7u8; 32]);
+        let (state, access_token) = registered_device_state(&device_key);
+        let claims = yc_shared_protocol::decode_access_token_claims(&access_token)
+            .expect("relay token should expose public claims");
+        assert_eq!(
+            (claims.sid.as_str(), claims.kid.as_str()),
+            ("sys_a", "kid_a")
+        );
+
+        let mode = state
+            .authorize_connection(
+                ClientRole::App,
+                &access_query(&access_token, &device_key, "nonce_a"),
+            )
+            .await
+            .expect("access token should be accepted");
+        assert_eq!(mode, ConnectionAuthMode::AccessToken);
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn failed_connect_records_reason_until_next_success() {
+        let device_key = SigningKey::from_bytes(&[7u8; 32]);
+        let (state, access_token) = registered_device_state(&device_key);
+        let last_failure = |state: &AppState| {
+            let state = state.clone();
+            async move {
+                state.auth_store.read().await.systems["sys_a"].devices["ios_a"]
+                    .last_auth_failure
+                    .clone()
+            }
+        };
+
+        let forged_keys = SigningKeyRing::new("other_seed".to_string());
+        let forged = issue_access_token(&forged_keys, "sys_a", "ios_a", "kid_a", 600)
+            .expect("issue forged token");
+        let err = state
+            .authorize_connection(
+                ClientRole::App,
+                &access_query(&forged, &device_key, "nonce_1"),
+            )
+            .await
+            .expect_err("forged token should be rejected");
+        let failure = last_failure(&state).await.expect("failure recorded");
+        assert_eq!(failure.code, err.code);
+        assert!(!failure.at.is_empty());
+
+        // 未登记设备的失败不落盘。
+        let mut unknown = access_query(&forged, &device_key, "nonce_2");
+        unknown.device_id = "ios_unknown".to_string();
+        let _ = state.authorize_connection(ClientRole::App, &unknown).await;
+        assert!(
+            !state.auth_store.read().await.systems["sys_a"]
+                .devices
+                .contains_key("ios_unknown")
+        );
+
+        state
+            .authorize_connection(
+                ClientRole::App,
+                &access_query(&access_token, &device_key, "nonce_3"),
+            )
+            .await
+            .expect("valid token should be accepted");
+        assert_eq!(last_failure(&state).await, None);
+    }
+}
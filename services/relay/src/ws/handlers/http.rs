@@ -5,28 +5,51 @@ use axum::{
         Query, State,
         ws::{Message, WebSocket, WebSocketUpgrade},
     },
-    http::StatusCode,
+    http::{HeaderMap, StatusCode},
     response::IntoResponse,
 };
-use futures_util::{SinkExt, StreamExt};
+use futures_util::{Sink, SinkExt, Stream, StreamExt};
 use std::collections::HashMap;
 use std::sync::Arc;
 use std::sync::atomic::AtomicU64;
-use tokio::sync::mpsc;
-use tracing::{debug, info, warn};
+use std::time::{Duration, Instant};
+use tokio::sync::{Notify, mpsc};
+use tokio::time::MissedTickBehavior;
+use tracing::{Instrument, debug, info, info_span, warn};
 use uuid::Uuid;
+use yc_shared_protocol::{ACK_EVENT_TYPE, now_rfc3339_nanos};
 
 use crate::{
-    api::types::{PairBootstrapRequest, WsQuery},
+    api::{
+        trace::current_trace_id,
+        types::{ClientRole, ConnectionAuthMode, PairBootstrapRequest, WsQuery},
+    },
+    auth::sidecar_secret::verify_sidecar_secret,
     pairing::bootstrap::print_pairing_banner_from_relay,
-    state::{AppState, ClientHandle, RelayWriteCommand, WS_WRITE_QUEUE_CAPACITY},
-    ws::envelope::{sanitize_envelope, send_server_presence, summarize_envelope},
+    state::{AppState, ClientHandle, RelayWriteCommand, next_connection_seq},
+    ws::{
+        device_limit::max_connections_per_device_from_env,
+        envelope::{sanitize_envelope, send_server_presence, summarize_envelope},
+        frame_limit::max_frame_bytes_from_env,
+        idle::{
+            IdleTracker, WsKeepalive, idle_check_interval, idle_close_message,
+            keepalive_ping_message,
+        },
+        presence::{PEER_JOINED_EVENT, PEER_LEFT_EVENT, PeerChange},
+        protocol_version::{negotiate_protocol_version, unsupported_protocol_close_message},
+        seq_gap::{RESYNC_HINT_EVENT, resync_hint_message},
+        write_queue::write_queue_capacity_from_env,
+    },
 };
 
+/// 连接结束后等待写任务冲刷剩余消息（如关闭帧）的上限。
+const WRITER_DRAIN_TIMEOUT: Duration = Duration::from_secs(1);
+
 /// WS 握手入口：校验 query 并升级连接。
 pub(crate) async fn ws_handler(
     ws: WebSocketUpgrade,
     State(state): State<AppState>,
+    headers: HeaderMap,
     Query(mut q): Query<WsQuery>,
 ) -> Result<impl IntoResponse, (StatusCode, String)> {
     if q.system_id.trim().is_empty()
@@ -39,25 +62,91 @@ pub(crate) async fn ws_handler(
         ));
     }
 
-    q.client_type = yc_shared_protocol::normalize_client_type(&q.client_type);
-    if q.client_type != "app" && q.client_type != "sidecar" {
+    let Some(role) = ClientRole::parse(&q.client_type) else {
         return Err((StatusCode::BAD_REQUEST, "invalid clientType".to_string()));
-    }
+    };
+    q.client_type = role.as_str().to_string();
+
+    // 版本不受支持时仍完成升级，以关闭帧携带原因（浏览器拿不到握手失败的响应体）。
+    let protocol_version = match negotiate_protocol_version(q.protocol_version.as_deref()) {
+        Ok(version) => version,
+        Err(reason) => {
+            warn!(
+                "ws protocol version rejected system={} type={} device={}: {}",
+                q.system_id, q.client_type, q.device_id, reason
+            );
+            return Ok(ws.on_upgrade(move |mut socket: WebSocket| async move {
+                let _ = socket
+                    .send(unsupported_protocol_close_message(&reason))
+                    .await;
+            }));
+        }
+    };
 
-    let auth_result = state.authorize_connection(&q).await;
-    if let Err(err) = auth_result {
+    // 共享密钥须在 sidecar 鉴权（可能建房/轮换 pairToken）之前校验。
+    if role == ClientRole::Sidecar
+        && let Err(err) = verify_sidecar_secret(&headers)
+    {
+        warn!(
+            "ws sidecar rejected system={} device={} code={}",
+            q.system_id, q.device_id, err.code
+        );
         return Err((err.status, format!("{}: {}", err.code, err.message)));
     }
 
-    Ok(ws.on_upgrade(move |socket| handle_socket(state, socket, q)))
+    let auth_mode = match state.authorize_connection(role, &q).await {
+        Ok(mode) => mode,
+        Err(err) => return Err((err.status, format!("{}: {}", err.code, err.message))),
+    };
+
+    // 升级后的连接任务脱离请求作用域，trace id 需显式带入并挂到连接 span 上。
+    let trace_id = current_trace_id();
+    let span = info_span!("ws", trace_id = trace_id.as_deref().unwrap_or_default());
+    Ok(ws.on_upgrade(move |socket: WebSocket| {
+        let (ws_sender, ws_reader) = socket.split();
+        handle_socket(
+            state,
+            ws_sender,
+            ws_reader,
+            role,
+            auth_mode,
+            q,
+            protocol_version,
+            WsKeepalive::from_env(),
+            trace_id,
+        )
+        .instrument(span)
+    }))
 }
 
 /// 单连接处理：注册连接、转发消息、连接断开清理。
-async fn handle_socket(state: AppState, socket: WebSocket, q: WsQuery) {
+///
+/// 读写两端以 trait 约束传入，便于测试用静默的模拟连接验证空闲回收。
+#[allow(clippy::too_many_arguments)]
+async fn handle_socket<W, R>(
+    state: AppState,
+    mut ws_sender: W,
+    mut ws_reader: R,
+    role: ClientRole,
+    auth_mode: ConnectionAuthMode,
+    q: WsQuery,
+    protocol_version: u8,
+    keepalive: WsKeepalive,
+    trace_id: Option<String>,
+) where
+    W: Sink<Message> + Unpin + Send + 'static,
+    R: Stream<Item = Result<Message, axum::Error>> + Unpin,
+{
     let client_id = Uuid::new_v4();
-    let (mut ws_sender, mut ws_reader) = socket.split();
-    let (tx, mut rx) = mpsc::channel::<RelayWriteCommand>(WS_WRITE_QUEUE_CAPACITY);
+    let (tx, mut rx) = mpsc::channel::<RelayWriteCommand>(write_queue_capacity_from_env());
     let drop_count = Arc::new(AtomicU64::new(0));
+    let disconnect = Arc::new(Notify::new());
+    let connected_at = now_rfc3339_nanos();
+    let peer = PeerChange {
+        client_type: &q.client_type,
+        device_id: &q.device_id,
+        connected_at: &connected_at,
+    };
 
     state
         .insert(
@@ -65,14 +154,18 @@ async fn handle_socket(state: AppState, socket: WebSocket, q: WsQuery) {
             q.pair_token.clone(),
             client_id,
             ClientHandle {
-                client_type: q.client_type.clone(),
+                role,
+                device_id: q.device_id.clone(),
+                seq: next_connection_seq(),
                 sender: tx.clone(),
                 drop_count: drop_count.clone(),
+                disconnect: disconnect.clone(),
             },
+            max_connections_per_device_from_env(),
         )
         .await;
 
-    if q.client_type == "sidecar" {
+    if role == ClientRole::Sidecar {
         match state
             .issue_pair_bootstrap(&PairBootstrapRequest {
                 system_id: q.system_id.clone(),
@@ -90,12 +183,32 @@ async fn handle_socket(state: AppState, socket: WebSocket, q: WsQuery) {
     }
 
     info!(
-        "ws connected system={} type={} device={}",
-        q.system_id, q.client_type, q.device_id
+        "ws connected system={} type={} device={} auth={}",
+        q.system_id,
+        q.client_type,
+        q.device_id,
+        auth_mode.as_str()
     );
-    send_server_presence(&tx, &q.system_id, &q.client_type, &q.device_id);
+    send_server_presence(
+        &tx,
+        &q.system_id,
+        &q.client_type,
+        &q.device_id,
+        auth_mode,
+        protocol_version,
+        &connected_at,
+        state.room_client_count(&q.system_id).await,
+        trace_id.as_deref(),
+    );
+    state
+        .announce_peer(PEER_JOINED_EVENT, &q.system_id, client_id, peer)
+        .await;
 
-    let writer = tokio::spawn(async move {
+    let idle_timeout = keepalive.idle_timeout;
+    let max_frame_bytes = max_frame_bytes_from_env();
+    // 仅入站帧刷新计时：半开连接上写 socket 仍可能成功，不能视为存活。
+    let activity = IdleTracker::new();
+    let mut writer = tokio::spawn(async move {
         let mut snapshot_latest: HashMap<String, Message> = HashMap::new();
         while let Some(command) = rx.recv().await {
             match command {
@@ -135,7 +248,48 @@ async fn handle_socket(state: AppState, socket: WebSocket, q: WsQuery) {
         }
     });
 
-    while let Some(next) = ws_reader.next().await {
+    let mut idle_ticker =
+        tokio::time::interval(idle_check_interval(idle_timeout.unwrap_or_default()));
+    idle_ticker.set_missed_tick_behavior(MissedTickBehavior::Skip);
+    let ping_period = keepalive.ping_interval.unwrap_or(Duration::from_secs(1));
+    let mut ping_ticker =
+        tokio::time::interval_at(tokio::time::Instant::now() + ping_period, ping_period);
+    ping_ticker.set_missed_tick_behavior(MissedTickBehavior::Skip);
+
+    loop {
+        let next = tokio::select! {
+            next = ws_reader.next() => next,
+            _ = disconnect.notified() => {
+                info!(
+                    "ws disconnected by server system={} type={} device={}",
+                    q.system_id, q.client_type, q.device_id
+                );
+                break;
+            }
+            _ = ping_ticker.tick(), if keepalive.ping_interval.is_some() => {
+                // 存活客户端会自动回 pong 并刷新空闲计时；写队列满时跳过本轮。
+                let _ = tx.try_send(RelayWriteCommand::Direct(keepalive_ping_message()));
+                continue;
+            }
+            _ = idle_ticker.tick(), if idle_timeout.is_some() => {
+                let timeout = idle_timeout.unwrap_or_default();
+                if activity.is_idle_at(tokio::time::Instant::now(), timeout) {
+                    info!(
+                        "ws idle timeout system={} type={} device={} timeout_sec={}",
+                        q.system_id,
+                        q.client_type,
+                        q.device_id,
+                        timeout.as_secs()
+                    );
+                    let _ = tx.try_send(RelayWriteCommand::Direct(idle_close_message()));
+                    break;
+                }
+                continue;
+            }
+        };
+        let Some(next) = next else {
+            break;
+        };
         let msg = match next {
             Ok(m) => m,
             Err(err) => {
@@ -147,11 +301,32 @@ async fn handle_socket(state: AppState, socket: WebSocket, q: WsQuery) {
             }
         };
 
+        activity.touch();
         let Message::Text(text) = msg else {
             continue;
         };
+        // 超限帧在净化前丢弃：连接与房间保持不变，仅计数告警。
+        if text.len() > max_frame_bytes {
+            let dropped = state.record_oversized_frame(&q.system_id).await;
+            warn!(
+                "drop oversized frame system={} type={} device={} bytes={} limit={} dropped_total={}",
+                q.system_id,
+                q.client_type,
+                q.device_id,
+                text.len(),
+                max_frame_bytes,
+                dropped
+            );
+            continue;
+        }
 
-        let sanitized = match sanitize_envelope(&text, &q.system_id, &q.client_type, &q.device_id) {
+        let sanitized = match sanitize_envelope(
+            &text,
+            &q.system_id,
+            &q.client_type,
+            &q.device_id,
+            protocol_version,
+        ) {
             Ok(v) => v,
             Err(err) => {
                 warn!(
@@ -174,15 +349,248 @@ async fn handle_socket(state: AppState, socket: WebSocket, q: WsQuery) {
             summary.tool_id
         );
 
+        if summary.event_type == ACK_EVENT_TYPE {
+            if !summary.acked_event_id.is_empty() {
+                state
+                    .acks
+                    .acknowledge(&q.system_id, &summary.acked_event_id);
+            }
+        } else if summary.ack_required {
+            // 先登记再转发，避免接收方回 ACK 早于登记。
+            state.acks.track(
+                &q.system_id,
+                &summary.event_id,
+                client_id,
+                &sanitized,
+                &summary.event_type,
+                Instant::now(),
+            );
+        }
+
+        if role == ClientRole::Sidecar
+            && let Some(seq) = summary.seq
+            && let Some(gap) = state.seq_gaps.observe(&q.system_id, &q.device_id, seq)
+        {
+            warn!(
+                "ws seq gap system={} device={} type={} expected={} received={}",
+                q.system_id, q.device_id, summary.event_type, gap.expected, gap.received
+            );
+            // 先提示断档再转发当前帧，App 收到后请求最新快照补齐。
+            if let Some(hint) = resync_hint_message(&q.system_id, &q.device_id, gap) {
+                state
+                    .broadcast(&q.system_id, client_id, hint, RESYNC_HINT_EVENT)
+                    .await;
+            }
+        }
+
         state
-            .broadcast(&q.system_id, client_id, sanitized, &summary.event_type)
+            .broadcast(&q.system_id, client_id, sanitized, &summary.routing_type)
             .await;
     }
 
     state.remove(&q.system_id, client_id).await;
-    writer.abort();
+    state
+        .announce_peer(PEER_LEFT_EVENT, &q.system_id, client_id, peer)
+        .await;
+    if role == ClientRole::Sidecar {
+        state.seq_gaps.forget(&q.system_id, &q.device_id);
+    }
+    // 连接已从房间移除，释放最后一个发送端后写任务会在冲刷完剩余消息后自然退出。
+    drop(tx);
+    if tokio::time::timeout(WRITER_DRAIN_TIMEOUT, &mut writer)
+        .await
+        .is_err()
+    {
+        writer.abort();
+    }
     info!(
-        "ws disconnected system={} type={} device={}",
-        q.system_id, q.client_type, q.device_id
+        "ws disconnected system={} type={} device={} auth={}",
+        q.system_id,
+        q.client_type,
+        q.device_id,
+        auth_mode.as_str()
     );
 }
+
+#[cfg(test)]
+mod tests {
+    use std::pin::Pin;
+    use std::sync::{Arc, Mutex, atomic::AtomicU64};
+    use std::time::Duration;
+
+    use axum::extract::ws::Message;
+    use base64::{Engine as _, engine::general_purpose::URL_SAFE_NO_PAD};
+    use ed25519_dalek::{Signer, SigningKey};
+    use futures_util::{Sink, sink, stream};
+    use serde_json::json;
+    use tokio::sync::{RwLock, mpsc};
+    use uuid::Uuid;
+    use yc_shared_protocol::PROTOCOL_VERSION;
+
+    use super::handle_socket;
+    use crate::{
+        api::types::{AuthRevokeDeviceRequest, AuthStore, ClientRole, ConnectionAuthMode, WsQuery},
+        auth::{
+            handlers::test_support::device, pop::auth_revoke_payload, store::unix_now,
+            token::issue_access_token,
+        },
+        state::{AppState, ClientHandle, next_connection_seq},
+        ws::{
+            idle::{IDLE_CLOSE_CODE, WsKeepalive},
+            revocation::{SESSION_REVOKED_CLOSE_CODE, SESSION_REVOKED_EVENT},
+        },
+    };
+
+    /// 已发出帧的记录。
+    type SentFrames = Arc<Mutex<Vec<Message>>>;
+
+    /// 构造记录所有发出帧的模拟写端。
+    fn recording_sink() -> (
+        SentFrames,
+        Pin<Box<impl Sink<Message, Error = axum::Error> + Send + 'static>>,
+    ) {
+        let sent = SentFrames::default();
+        let sink = Box::pin(sink::unfold(
+            sent.clone(),
+            |sent, msg: Message| async move {
+                sent.lock().expect("sent").push(msg);
+                Ok::<_, axum::Error>(sent)
+            },
+        ));
+        (sent, sink)
+    }
+
+    /// 构造 App 连接参数。
+    fn app_query(device_id: &str) -> WsQuery {
+        serde_json::from_value(json!({
+            "systemId": "sys_a",
+            "clientType": "app",
+            "deviceId": device_id,
+        }))
+        .expect("query")
+    }
+
+    #[tokio::test(flavor = "current_thread", start_paused = true)]
+    async fn silent_half_open_client_is_pinged_then_closed_and_removed() {
+        let state = AppState::for_test();
+        // 预置在线 sidecar，使 App 断开后房间仍在，便于确认只移除了该连接。
+        let (sidecar_tx, _sidecar_rx) = mpsc::channel(8);
+        state
+            .insert(
+                "sys_a".to_string(),
+                "pt_a".to_string(),
+                Uuid::new_v4(),
+                ClientHandle {
+                    role: ClientRole::Sidecar,
+                    device_id: "sc_a".to_string(),
+                    seq: next_connection_seq(),
+                    sender: sidecar_tx,
+                    drop_count: Arc::new(AtomicU64::new(0)),
+                    disconnect: Default::default(),
+                },
+                3,
+            )
+            .await;
+
+        let (sent, recording_sink) = recording_sink();
+        // 半开连接：读端永远不产生帧，也不会结束。
+        let silent_reader = stream::pending::<Result<Message, axum::Error>>();
+        let keepalive = WsKeepalive {
+            idle_timeout: Some(Duration::from_secs(3)),
+            ping_interval: Some(Duration::from_secs(1)),
+        };
+
+        handle_socket(
+            state.clone(),
+            recording_sink,
+            silent_reader,
+            ClientRole::App,
+            ConnectionAuthMode::AccessToken,
+            app_query("ios_a"),
+            PROTOCOL_VERSION,
+            keepalive,
+            None,
+        )
+        .await;
+
+        let guard = state.systems.read().await;
+        assert_eq!(guard["sys_a"].clients.len(), 1, "only the sidecar remains");
+        drop(guard);
+        let sent = sent.lock().expect("sent");
+        assert!(sent.iter().any(|msg| matches!(msg, Message::Ping(_))));
+        let Some(Message::Close(Some(frame))) = sent.last() else {
+            panic!("expected idle close frame, got {:?}", sent.last());
+        };
+        assert_eq!(frame.code, IDLE_CLOSE_CODE);
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn revoking_device_tears_down_its_live_connection() {
+        let device_key = SigningKey::from_bytes(&[9u8; 32]);
+        let mut store = AuthStore::new("seed".to_string());
+        let system = store.system_mut("sys_a");
+        let public_key = URL_SAFE_NO_PAD.encode(device_key.verifying_key().to_bytes());
+        for (device_id, key_id) in [("ios_a", "kid_a"), ("ios_b", "kid_b")] {
+            system.devices.insert(
+                device_id.to_string(),
+                device(device_id, key_id, &public_key, "ACTIVE"),
+            );
+        }
+        let access_token = issue_access_token(&store.signing_keys, "sys_a", "ios_a", "kid_a", 600)
+            .expect("issue access token");
+        let state = AppState {
+            auth_store: Arc::new(RwLock::new(store)),
+            ..AppState::for_test()
+        };
+
+        let (sent, recording_sink) = recording_sink();
+        let connection = tokio::spawn(handle_socket(
+            state.clone(),
+            recording_sink,
+            stream::pending::<Result<Message, axum::Error>>(),
+            ClientRole::App,
+            ConnectionAuthMode::AccessToken,
+            app_query("ios_b"),
+            PROTOCOL_VERSION,
+            WsKeepalive {
+                idle_timeout: None,
+                ping_interval: None,
+            },
+            None,
+        ));
+        while state.snapshot().await.get("sys_a").map(|room| room.clients) != Some(1) {
+            tokio::task::yield_now().await;
+        }
+
+        let ts = unix_now();
+        let payload = auth_revoke_payload("sys_a", "ios_a", "ios_b", "kid_a", ts, "n1");
+        state
+            .revoke_device(&AuthRevokeDeviceRequest {
+                system_id: "sys_a".to_string(),
+                device_id: "ios_a".to_string(),
+                target_device_id: "ios_b".to_string(),
+                access_token,
+                key_id: "kid_a".to_string(),
+                ts: ts.to_string(),
+                nonce: "n1".to_string(),
+                sig: URL_SAFE_NO_PAD.encode(device_key.sign(payload.as_bytes()).to_bytes()),
+            })
+            .await
+            .expect("revoke should succeed");
+
+        tokio::time::timeout(Duration::from_secs(2), connection)
+            .await
+            .expect("connection loop should exit")
+            .expect("join");
+        assert!(!state.systems.read().await.contains_key("sys_a"));
+        let sent = sent.lock().expect("sent");
+        assert!(sent.iter().any(|msg| matches!(
+            msg,
+            Message::Text(text) if text.contains(SESSION_REVOKED_EVENT)
+        )));
+        let Some(Message::Close(Some(frame))) = sent.last() else {
+            panic!("expected revoke close frame, got {:?}", sent.last());
+        };
+        assert_eq!(frame.code, SESSION_REVOKED_CLOSE_CODE);
+    }
+}
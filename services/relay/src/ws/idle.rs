@@ -0,0 +1,178 @@
+//! WebSocket 空闲连接回收：按连接记录最近一次收到的帧，超时后以结构化关闭原因断开；
+//! relay 按独立周期主动下发 ping，存活客户端回 pong 即刷新计时，半开连接得以及时清理。
+
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::Duration,
+};
+
+use axum::extract::ws::{CloseFrame, Message};
+use tokio::time::Instant;
+
+/// 空闲超时配置环境变量（秒，0 表示关闭回收）。
+const RELAY_WS_IDLE_ENV: &str = "RELAY_WS_IDLE_SEC";
+/// 兼容旧名称的空闲超时环境变量，仅在未设置 `RELAY_WS_IDLE_SEC` 时读取。
+const LEGACY_RELAY_IDLE_TIMEOUT_ENV: &str = "RELAY_IDLE_TIMEOUT_SEC";
+/// 空闲超时默认值（秒）。
+const DEFAULT_IDLE_TIMEOUT_SEC: u64 = 300;
+/// 主动 ping 周期环境变量（秒，0 表示不主动 ping）。
+const RELAY_WS_PING_ENV: &str = "RELAY_WS_PING_SEC";
+/// 主动 ping 周期默认值（秒）。
+const DEFAULT_PING_INTERVAL_SEC: u64 = 30;
+/// 空闲关闭使用的 WebSocket close code（应用自定义区间）。
+pub(crate) const IDLE_CLOSE_CODE: u16 = 4408;
+/// 空闲关闭原因。
+pub(crate) const IDLE_CLOSE_REASON: &str = "idle_timeout";
+
+/// 单连接保活配置。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct WsKeepalive {
+    /// 空闲超时；None 表示不回收空闲连接。
+    pub(crate) idle_timeout: Option<Duration>,
+    /// 主动 ping 周期；None 表示不主动 ping。
+    pub(crate) ping_interval: Option<Duration>,
+}
+
+impl WsKeepalive {
+    /// 读取空闲超时与 ping 周期配置。
+    pub(crate) fn from_env() -> Self {
+        let idle_raw = std::env::var(RELAY_WS_IDLE_ENV)
+            .or_else(|_| std::env::var(LEGACY_RELAY_IDLE_TIMEOUT_ENV))
+            .ok();
+        Self {
+            idle_timeout: seconds_or_disabled(idle_raw.as_deref(), DEFAULT_IDLE_TIMEOUT_SEC),
+            ping_interval: seconds_or_disabled(
+                std::env::var(RELAY_WS_PING_ENV).ok().as_deref(),
+                DEFAULT_PING_INTERVAL_SEC,
+            ),
+        }
+    }
+}
+
+/// 解析秒数配置：无法解析时取默认值，0 表示关闭。
+fn seconds_or_disabled(raw: Option<&str>, default_sec: u64) -> Option<Duration> {
+    let seconds = raw
+        .and_then(|raw| raw.trim().parse::<u64>().ok())
+        .unwrap_or(default_sec);
+    (seconds > 0).then(|| Duration::from_secs(seconds))
+}
+
+/// 空闲检查周期：取超时的 1/4，并限制在 [1s, 30s]。
+pub(crate) fn idle_check_interval(timeout: Duration) -> Duration {
+    (timeout / 4).clamp(Duration::from_secs(1), Duration::from_secs(30))
+}
+
+/// 构造主动保活 ping 帧。
+pub(crate) fn keepalive_ping_message() -> Message {
+    Message::Ping(Default::default())
+}
+
+/// 构造空闲关闭帧。
+pub(crate) fn idle_close_message() -> Message {
+    Message::Close(Some(CloseFrame {
+        code: IDLE_CLOSE_CODE,
+        reason: IDLE_CLOSE_REASON.into(),
+    }))
+}
+
+/// 连接活动追踪器：记录最近一次收到客户端帧（含 ping/pong）的时间。
+#[derive(Debug, Clone)]
+pub(crate) struct IdleTracker {
+    /// 计时基准。
+    started_at: Instant,
+    /// 最近活动时间（相对基准的毫秒数）。
+    last_activity_ms: Arc<AtomicU64>,
+}
+
+impl IdleTracker {
+    /// 以当前时间为基准创建追踪器。
+    pub(crate) fn new() -> Self {
+        Self::new_at(Instant::now())
+    }
+
+    /// 以指定时间为基准创建追踪器。
+    fn new_at(started_at: Instant) -> Self {
+        Self {
+            started_at,
+            last_activity_ms: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// 记录一次入站活动。
+    pub(crate) fn touch(&self) {
+        self.touch_at(Instant::now());
+    }
+
+    /// 在指定时间记录一次入站活动。
+    fn touch_at(&self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.started_at).as_millis();
+        self.last_activity_ms
+            .fetch_max(elapsed.min(u64::MAX as u128) as u64, Ordering::Relaxed);
+    }
+
+    /// 判断在 `now` 时刻连接是否已空闲超过 `timeout`。
+    pub(crate) fn is_idle_at(&self, now: Instant, timeout: Duration) -> bool {
+        let last =
+            self.started_at + Duration::from_millis(self.last_activity_ms.load(Ordering::Relaxed));
+        now.saturating_duration_since(last) >= timeout
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use axum::extract::ws::Message;
+    use tokio::time::Instant;
+
+    use super::{
+        IDLE_CLOSE_CODE, IdleTracker, idle_check_interval, idle_close_message, seconds_or_disabled,
+    };
+
+    #[test]
+    fn silent_connection_past_timeout_is_idle() {
+        let start = Instant::now();
+        let tracker = IdleTracker::new_at(start);
+        let timeout = Duration::from_secs(60);
+
+        assert!(!tracker.is_idle_at(start + Duration::from_secs(59), timeout));
+        assert!(tracker.is_idle_at(start + Duration::from_secs(61), timeout));
+    }
+
+    #[test]
+    fn active_connection_survives_timeout() {
+        let start = Instant::now();
+        let tracker = IdleTracker::new_at(start);
+        let timeout = Duration::from_secs(60);
+
+        // 收到任意帧都会刷新计时（如 sidecar 心跳或 App 回复的 pong）。
+        for offset in [20, 40, 60, 80, 100] {
+            tracker
+                .clone()
+                .touch_at(start + Duration::from_secs(offset));
+            assert!(!tracker.is_idle_at(start + Duration::from_secs(offset + 30), timeout));
+        }
+        assert!(tracker.is_idle_at(start + Duration::from_secs(161), timeout));
+    }
+
+    #[test]
+    fn idle_close_frame_carries_structured_reason() {
+        let Message::Close(Some(frame)) = idle_close_message() else {
+            panic!("expected close frame");
+        };
+        assert_eq!(frame.code, IDLE_CLOSE_CODE);
+        assert_eq!(frame.reason.as_str(), "idle_timeout");
+        assert_eq!(
+            idle_check_interval(Duration::from_secs(2)),
+            Duration::from_secs(1)
+        );
+        assert_eq!(seconds_or_disabled(Some("0"), 30), None);
+        assert_eq!(
+            seconds_or_disabled(Some("x"), 30),
+            Some(Duration::from_secs(30))
+        );
+    }
+}
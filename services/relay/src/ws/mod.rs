@@ -1,4 +1,15 @@
 //! WebSocket 模块：握手鉴权、消息净化与路由转发。
 
+pub(crate) mod ack;
+pub(crate) mod device_limit;
 pub(crate) mod envelope;
+pub(crate) mod event_types;
+pub(crate) mod frame_limit;
 pub(crate) mod handlers;
+pub(crate) mod idle;
+pub(crate) mod presence;
+pub(crate) mod protocol_version;
+pub(crate) mod revocation;
+pub(crate) mod seq_gap;
+pub(crate) mod shutdown;
+pub(crate) mod write_queue;
@@ -0,0 +1,145 @@
+//! 房间在线感知：连接注册后向同房间其他连接推送 `peer_joined`，断开移出后推送 `peer_left`，
+//! 携带变化后的在线连接数，App 无需再靠心跳推断对端是否在线。
+
+use serde_json::json;
+use uuid::Uuid;
+use yc_shared_protocol::EventEnvelope;
+
+use crate::state::AppState;
+
+/// 对端接入事件类型。
+pub(crate) const PEER_JOINED_EVENT: &str = "peer_joined";
+/// 对端断开事件类型。
+pub(crate) const PEER_LEFT_EVENT: &str = "peer_left";
+
+/// 房间内发生变化的连接。
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct PeerChange<'a> {
+    /// 连接端类型（`sidecar`/`app`）。
+    pub(crate) client_type: &'a str,
+    /// 连接端设备 ID。
+    pub(crate) device_id: &'a str,
+    /// 该连接建立时间（RFC3339）。
+    pub(crate) connected_at: &'a str,
+}
+
+/// 构造对端变化事件文本。
+fn peer_presence_message(
+    event_type: &str,
+    system_id: &str,
+    peer: PeerChange<'_>,
+    client_count: usize,
+) -> Option<String> {
+    let env = EventEnvelope::new(
+        event_type,
+        system_id,
+        json!({
+            "clientType": peer.client_type,
+            "deviceId": peer.device_id,
+            "connectedAt": peer.connected_at,
+            "clientCount": client_count,
+        }),
+    );
+    serde_json::to_string(&env).ok()
+}
+
+impl AppState {
+    /// 房间当前在线连接数；房间不存在时为 0。
+    pub(crate) async fn room_client_count(&self, system_id: &str) -> usize {
+        self.systems
+            .read()
+            .await
+            .get(system_id)
+            .map_or(0, |room| room.clients.len())
+    }
+
+    /// 向同房间其他连接推送对端变化；房间已解散时不再推送。
+    pub(crate) async fn announce_peer(
+        &self,
+        event_type: &str,
+        system_id: &str,
+        origin_id: Uuid,
+        peer: PeerChange<'_>,
+    ) {
+        let client_count = self.room_client_count(system_id).await;
+        if client_count == 0 {
+            return;
+        }
+        if let Some(msg) = peer_presence_message(event_type, system_id, peer, client_count) {
+            self.broadcast(system_id, origin_id, msg, event_type).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use futures_util::{SinkExt, StreamExt};
+    use serde_json::Value;
+    use tokio_tungstenite::{MaybeTlsStream, WebSocketStream, connect_async, tungstenite::Message};
+
+    use super::{PEER_JOINED_EVENT, PEER_LEFT_EVENT};
+    use crate::{app::router, state::AppState};
+
+    type Client = WebSocketStream<MaybeTlsStream<tokio::net::TcpStream>>;
+
+    /// 以 sidecar 身份接入测试房间。
+    async fn connect(addr: std::net::SocketAddr, device_id: &str) -> Client {
+        let url = format!(
+            concat!(
+                "ws://{}/v1/ws",
+                "?systemId=sys_peer&clientType=sidecar&deviceId={}&pairToken=pt_peer"
+            ),
+            addr, device_id
+        );
+        connect_async(url).await.expect("ws handshake").0
+    }
+
+    /// 读取下一条 JSON 文本帧。
+    async fn next_json(ws: &mut Client) -> Value {
+        let frame = tokio::time::timeout(Duration::from_secs(5), ws.next())
+            .await
+            .expect("frame in time")
+            .expect("frame")
+            .expect("frame ok");
+        serde_json::from_str(frame.to_text().expect("text")).expect("json")
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn room_peers_receive_join_and_leave_with_client_count() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("bind");
+        let addr = listener.local_addr().expect("local addr");
+        tokio::spawn(async move { axum::serve(listener, router(AppState::for_test())).await });
+
+        let mut first = connect(addr, "sc_first").await;
+        let presence = next_json(&mut first).await;
+        assert_eq!(presence["type"], "server_presence");
+        assert_eq!(presence["payload"]["clientCount"], 1);
+        let connected_at = presence["payload"]["connectedAt"]
+            .as_str()
+            .expect("connectedAt");
+        assert!(chrono::DateTime::parse_from_rfc3339(connected_at).is_ok());
+
+        let mut second = connect(addr, "sc_second").await;
+        let presence = next_json(&mut second).await;
+        assert_eq!(presence["payload"]["clientCount"], 2);
+        let joined = next_json(&mut first).await;
+        assert_eq!(joined["type"], PEER_JOINED_EVENT);
+        assert_eq!(joined["payload"]["deviceId"], "sc_second");
+        assert_eq!(joined["payload"]["clientType"], "sidecar");
+        assert_eq!(
+            joined["payload"]["connectedAt"],
+            presence["payload"]["connectedAt"]
+        );
+        assert_eq!(joined["payload"]["clientCount"], 2);
+
+        second.send(Message::Close(None)).await.expect("close");
+        let left = next_json(&mut first).await;
+        assert_eq!(left["type"], PEER_LEFT_EVENT);
+        assert_eq!(left["payload"]["deviceId"], "sc_second");
+        assert_eq!(left["payload"]["clientCount"], 1);
+    }
+}
@@ -0,0 +1,61 @@
+//! 协议版本协商：握手 query 的 `protocolVersion` 须落在 relay 支持区间内，
+//! 协商结果写入 `server_presence` 与缺省 envelope `v`；不支持的版本以关闭帧说明原因。
+
+use axum::extract::ws::{CloseFrame, Message};
+use yc_shared_protocol::{MIN_PROTOCOL_VERSION, PROTOCOL_VERSION};
+
+/// 未携带 `protocolVersion` 的历史客户端按 v1 处理。
+const LEGACY_PROTOCOL_VERSION: u8 = 1;
+/// 协议版本不受支持时的 WebSocket close code（应用自定义区间，对应 HTTP 426）。
+pub(crate) const UNSUPPORTED_PROTOCOL_CLOSE_CODE: u16 = 4426;
+
+/// 协商连接使用的协议版本；缺省按历史版本处理，非法或超出支持区间返回原因。
+pub(crate) fn negotiate_protocol_version(raw: Option<&str>) -> Result<u8, String> {
+    let Some(raw) = raw.map(str::trim).filter(|value| !value.is_empty()) else {
+        return Ok(LEGACY_PROTOCOL_VERSION);
+    };
+    let requested = raw
+        .parse::<u32>()
+        .map_err(|_| format!("invalid protocolVersion: {raw}"))?;
+    match u8::try_from(requested) {
+        Ok(version) if (MIN_PROTOCOL_VERSION..=PROTOCOL_VERSION).contains(&version) => Ok(version),
+        _ => Err(format!(
+            "unsupported protocolVersion {requested}, supported {MIN_PROTOCOL_VERSION}-{}",
+            PROTOCOL_VERSION
+        )),
+    }
+}
+
+/// 构造协议版本不受支持的关闭帧，reason 携带支持区间。
+pub(crate) fn unsupported_protocol_close_message(reason: &str) -> Message {
+    Message::Close(Some(CloseFrame {
+        code: UNSUPPORTED_PROTOCOL_CLOSE_CODE,
+        reason: reason.to_string().into(),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use yc_shared_protocol::PROTOCOL_VERSION;
+
+    use super::negotiate_protocol_version;
+
+    #[test]
+    fn missing_version_falls_back_to_v1_and_out_of_range_is_rejected() {
+        assert_eq!(negotiate_protocol_version(None), Ok(1));
+        assert_eq!(negotiate_protocol_version(Some(" ")), Ok(1));
+        assert_eq!(
+            negotiate_protocol_version(Some(&PROTOCOL_VERSION.to_string())),
+            Ok(PROTOCOL_VERSION)
+        );
+
+        let too_new = negotiate_protocol_version(Some("99")).expect_err("too new");
+        assert!(too_new.contains("unsupported protocolVersion 99"));
+        assert!(negotiate_protocol_version(Some("0")).is_err());
+        assert!(negotiate_protocol_version(Some("4096")).is_err());
+        assert_eq!(
+            negotiate_protocol_version(Some("v2")),
+            Err("invalid protocolVersion: v2".to_string())
+        );
+    }
+}
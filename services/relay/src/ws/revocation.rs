@@ -0,0 +1,123 @@
+//! 设备吊销断连：吊销生效后向该设备的在线连接推送 `session_revoked`，
+//! 发送关闭帧并移出房间，连接循环随即退出，不再等待客户端自行断开。
+//! 配对令牌作废后向在线 sidecar 推送 `pair_token_revoked`，由 sidecar 换新令牌并重连。
+
+use axum::extract::ws::{CloseFrame, Message};
+use serde_json::json;
+use tracing::info;
+use yc_shared_protocol::EventEnvelope;
+
+use crate::{
+    api::types::ClientRole,
+    state::{AppState, RelayWriteCommand},
+};
+
+/// 吊销通知事件类型。
+pub(crate) const SESSION_REVOKED_EVENT: &str = "session_revoked";
+/// 配对令牌作废通知事件类型（仅发往 sidecar）。
+pub(crate) const PAIR_TOKEN_REVOKED_EVENT: &str = "pair_token_revoked";
+/// 吊销关闭使用的 WebSocket close code（应用自定义区间）。
+pub(crate) const SESSION_REVOKED_CLOSE_CODE: u16 = 4401;
+
+/// 构造吊销通知事件文本。
+fn session_revoked_message(system_id: &str, device_id: &str) -> Option<Message> {
+    let env = EventEnvelope::new(
+        SESSION_REVOKED_EVENT,
+        system_id,
+        json!({
+            "reason": "device_revoked",
+            "deviceId": device_id,
+        }),
+    );
+    serde_json::to_string(&env)
+        .ok()
+        .map(|raw| Message::Text(raw.into()))
+}
+
+/// 构造配对令牌作废通知事件文本。
+fn pair_token_revoked_message(system_id: &str) -> Option<Message> {
+    let env = EventEnvelope::new(
+        PAIR_TOKEN_REVOKED_EVENT,
+        system_id,
+        json!({ "reason": "pair_token_revoked" }),
+    );
+    serde_json::to_string(&env)
+        .ok()
+        .map(|raw| Message::Text(raw.into()))
+}
+
+impl AppState {
+    /// 通知房间内在线 sidecar 配对令牌已作废，返回通知的连接数。
+    pub(crate) async fn notify_pair_token_revoked(&self, system_id: &str) -> usize {
+        let Some(notice) = pair_token_revoked_message(system_id) else {
+            return 0;
+        };
+        let guard = self.systems.read().await;
+        let Some(room) = guard.get(system_id) else {
+            return 0;
+        };
+        let mut notified = 0;
+        for handle in room
+            .clients
+            .values()
+            .filter(|handle| handle.role == ClientRole::Sidecar)
+        {
+            if handle
+                .sender
+                .try_send(RelayWriteCommand::Direct(notice.clone()))
+                .is_ok()
+            {
+                notified += 1;
+            }
+        }
+        info!("pair token revoke pushed to sidecar system={system_id} connections={notified}");
+        notified
+    }
+
+    /// 断开指定设备的全部在线连接，返回断开的连接数。
+    pub(crate) async fn disconnect_revoked_device(
+        &self,
+        system_id: &str,
+        device_id: &str,
+    ) -> usize {
+        let close = Message::Close(Some(CloseFrame {
+            code: SESSION_REVOKED_CLOSE_CODE,
+            reason: SESSION_REVOKED_EVENT.into(),
+        }));
+        let notice = session_revoked_message(system_id, device_id);
+        let revoked = {
+            let mut guard = self.systems.write().await;
+            let Some(room) = guard.get_mut(system_id) else {
+                return 0;
+            };
+            let client_ids = room
+                .clients
+                .iter()
+                .filter(|(_, handle)| handle.device_id == device_id)
+                .map(|(client_id, _)| *client_id)
+                .collect::<Vec<_>>();
+            client_ids
+                .into_iter()
+                .filter_map(|client_id| room.clients.remove(&client_id))
+                .collect::<Vec<_>>()
+        };
+        for handle in &revoked {
+            if let Some(msg) = notice.clone() {
+                let _ = handle.sender.try_send(RelayWriteCommand::Direct(msg));
+            }
+            let _ = handle
+                .sender
+                .try_send(RelayWriteCommand::Direct(close.clone()));
+            handle.disconnect.notify_one();
+        }
+        if !revoked.is_empty() {
+            info!(
+                "revoked device disconnected system={} device={} connections={}",
+                system_id,
+                device_id,
+                revoked.len()
+            );
+        }
+        revoked.len()
+    }
+}
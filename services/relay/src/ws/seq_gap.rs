@@ -0,0 +1,141 @@
+//! 上行 seq 断档检测（`RELAY_SEQ_GAP_DETECT` 开启）：按 `(systemId, sourceDeviceId)` 记录
+//! sidecar 最近转发的 seq，跳号时向房间推送 `resync_hint`，App 据此请求最新快照补齐状态。
+
+use std::{collections::HashMap, sync::Mutex};
+
+use serde_json::json;
+use tracing::warn;
+use yc_shared_protocol::EventEnvelope;
+
+/// 断档检测开关环境变量。
+const RELAY_SEQ_GAP_DETECT_ENV: &str = "RELAY_SEQ_GAP_DETECT";
+/// 断档提示事件类型。
+pub(crate) const RESYNC_HINT_EVENT: &str = "resync_hint";
+
+/// 一次检测到的 seq 断档。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct SeqGap {
+    /// 期望收到的 seq（上一帧 + 1）。
+    pub(crate) expected: u64,
+    /// 实际收到的 seq。
+    pub(crate) received: u64,
+}
+
+/// seq 断档检测表，键为 (systemId, sourceDeviceId)。
+#[derive(Debug, Default)]
+pub(crate) struct SeqGapDetector {
+    /// 是否开启检测；关闭时不保存任何状态。
+    enabled: bool,
+    /// 各来源最近转发的 seq。
+    last_seq: Mutex<HashMap<(String, String), u64>>,
+}
+
+impl SeqGapDetector {
+    /// 按开关构造检测表。
+    pub(crate) fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            last_seq: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// 读取环境变量构造检测表（默认关闭）。
+    pub(crate) fn from_env() -> Self {
+        Self::new(parse_enabled(
+            std::env::var(RELAY_SEQ_GAP_DETECT_ENV).ok().as_deref(),
+        ))
+    }
+
+    /// 是否开启检测。
+    pub(crate) fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// 记录一帧 seq；跳号时返回断档。seq 回退视为来源重连后重新计数，只重置基线。
+    pub(crate) fn observe(&self, system_id: &str, device_id: &str, seq: u64) -> Option<SeqGap> {
+        if !self.enabled {
+            return None;
+        }
+        let mut last_seq = self.lock();
+        let previous = last_seq.insert((system_id.to_string(), device_id.to_string()), seq)?;
+        let expected = previous.saturating_add(1);
+        (seq > expected).then_some(SeqGap {
+            expected,
+            received: seq,
+        })
+    }
+
+    /// 来源连接断开后清除其基线。
+    pub(crate) fn forget(&self, system_id: &str, device_id: &str) {
+        if !self.enabled {
+            return;
+        }
+        self.lock()
+            .remove(&(system_id.to_string(), device_id.to_string()));
+    }
+
+    /// 加锁读取检测表（锁中毒时继续使用内部数据）。
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<(String, String), u64>> {
+        self.last_seq.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+/// 解析开关：`1/true/yes/on` 为开启，其它非空值告警后按关闭处理。
+fn parse_enabled(raw: Option<&str>) -> bool {
+    let Some(raw) = raw.map(str::trim).filter(|value| !value.is_empty()) else {
+        return false;
+    };
+    match raw.to_ascii_lowercase().as_str() {
+        "1" | "true" | "yes" | "on" => true,
+        "0" | "false" | "no" | "off" => false,
+        _ => {
+            warn!("invalid {RELAY_SEQ_GAP_DETECT_ENV}={raw}, fallback to false");
+            false
+        }
+    }
+}
+
+/// 构造断档提示事件文本。
+pub(crate) fn resync_hint_message(system_id: &str, device_id: &str, gap: SeqGap) -> Option<String> {
+    let env = EventEnvelope::new(
+        RESYNC_HINT_EVENT,
+        system_id,
+        json!({
+            "sourceDeviceId": device_id,
+            "expectedSeq": gap.expected,
+            "receivedSeq": gap.received,
+            "missed": gap.received - gap.expected,
+        }),
+    );
+    serde_json::to_string(&env).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SeqGap, SeqGapDetector, parse_enabled};
+
+    #[test]
+    fn skipped_seq_reports_gap_and_regression_resets_baseline() {
+        let detector = SeqGapDetector::new(true);
+        assert_eq!(detector.observe("sys_a", "sc_a", 1), None);
+        assert_eq!(detector.observe("sys_a", "sc_a", 2), None);
+        assert_eq!(
+            detector.observe("sys_a", "sc_a", 5),
+            Some(SeqGap {
+                expected: 3,
+                received: 5
+            })
+        );
+        // 来源独立计数，重连后从 1 重新开始不算断档。
+        assert_eq!(detector.observe("sys_a", "sc_b", 9), None);
+        assert_eq!(detector.observe("sys_a", "sc_a", 1), None);
+        detector.forget("sys_a", "sc_a");
+        assert_eq!(detector.observe("sys_a", "sc_a", 7), None);
+
+        let disabled = SeqGapDetector::new(false);
+        disabled.observe("sys_a", "sc_a", 1);
+        assert_eq!(disabled.observe("sys_a", "sc_a", 9), None);
+        assert!(parse_enabled(Some(" on ")));
+        assert!(!parse_enabled(Some("maybe")));
+    }
+}
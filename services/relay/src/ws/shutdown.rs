@@ -0,0 +1,213 @@
+//! 优雅停机：收到 Ctrl-C / SIGTERM 后向所有在线连接推送 `server_shutdown` 并发送关闭帧，
+//! 在宽限期内等待写任务冲刷、房间清空后再让 `axum::serve` 退出。
+
+use std::{future::Future, time::Duration};
+
+use axum::extract::ws::{CloseFrame, Message};
+use serde_json::json;
+use tracing::{info, warn};
+use yc_shared_protocol::EventEnvelope;
+
+use crate::state::{AppState, RelayWriteCommand};
+
+/// 停机宽限期环境变量（秒，0 表示不等待）。
+const RELAY_SHUTDOWN_GRACE_ENV: &str = "RELAY_SHUTDOWN_GRACE_SEC";
+/// 停机宽限期默认值（秒）。
+const DEFAULT_SHUTDOWN_GRACE_SEC: u64 = 5;
+/// 建议客户端重连前等待的时间（秒）。
+const SHUTDOWN_RETRY_AFTER_SEC: u64 = 10;
+/// 停机通知事件类型。
+pub(crate) const SERVER_SHUTDOWN_EVENT: &str = "server_shutdown";
+/// 停机关闭帧使用的 close code（RFC 6455 Going Away）。
+const SHUTDOWN_CLOSE_CODE: u16 = 1001;
+/// 宽限期内检查房间是否清空的周期。
+const DRAIN_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// 读取停机宽限期；未设置或非法时使用默认值。
+pub(crate) fn shutdown_grace_from_env() -> Duration {
+    let seconds = std::env::var(RELAY_SHUTDOWN_GRACE_ENV)
+        .ok()
+        .and_then(|raw| raw.trim().parse::<u64>().ok())
+        .unwrap_or(DEFAULT_SHUTDOWN_GRACE_SEC);
+    Duration::from_secs(seconds)
+}
+
+/// 等待 Ctrl-C 或 SIGTERM（仅 unix）。
+pub(crate) async fn wait_for_shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(err) = tokio::signal::ctrl_c().await {
+            warn!("listen ctrl_c failed: {err}");
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut signal) => {
+                signal.recv().await;
+            }
+            Err(err) => {
+                warn!("listen SIGTERM failed: {err}");
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {}
+        _ = terminate => {}
+    }
+}
+
+/// `axum::serve` 的停机 future：`trigger` 完成后先排空房间再返回。
+pub(crate) async fn drain_after(
+    state: AppState,
+    grace: Duration,
+    trigger: impl Future<Output = ()>,
+) {
+    trigger.await;
+    state.drain_for_shutdown(grace).await;
+}
+
+/// 构造指定 system 的停机通知事件文本。
+fn server_shutdown_message(system_id: &str, grace: Duration) -> Option<Message> {
+    let env = EventEnvelope::new(
+        SERVER_SHUTDOWN_EVENT,
+        system_id,
+        json!({
+            "reason": "shutdown",
+            "graceSec": grace.as_secs(),
+            "retryAfterSec": SHUTDOWN_RETRY_AFTER_SEC,
+        }),
+    );
+    serde_json::to_string(&env)
+        .ok()
+        .map(|raw| Message::Text(raw.into()))
+}
+
+impl AppState {
+    /// 通知所有在线连接即将停机并发送关闭帧，等待房间清空或宽限期结束。
+    pub(crate) async fn drain_for_shutdown(&self, grace: Duration) {
+        let close = Message::Close(Some(CloseFrame {
+            code: SHUTDOWN_CLOSE_CODE,
+            reason: SERVER_SHUTDOWN_EVENT.into(),
+        }));
+        let (notified, skipped) = {
+            let guard = self.systems.read().await;
+            let mut notified = 0usize;
+            let mut skipped = 0usize;
+            for (system_id, room) in guard.iter() {
+                let notice = server_shutdown_message(system_id, grace);
+                for handle in room.clients.values() {
+                    // 写队列已满或已关闭的连接收不到通知，仍尝试发送关闭帧。
+                    let queued = notice.clone().map_or(Ok(()), |msg| {
+                        handle.sender.try_send(RelayWriteCommand::Direct(msg))
+                    });
+                    if queued.is_ok() {
+                        notified += 1;
+                    } else {
+                        skipped += 1;
+                    }
+                    let _ = handle
+                        .sender
+                        .try_send(RelayWriteCommand::Direct(close.clone()));
+                }
+            }
+            (notified, skipped)
+        };
+        info!(
+            "relay shutdown draining notified={} skipped={} grace_sec={}",
+            notified,
+            skipped,
+            grace.as_secs()
+        );
+
+        let drained = tokio::time::timeout(grace, async {
+            while !self.systems.read().await.is_empty() {
+                tokio::time::sleep(DRAIN_POLL_INTERVAL).await;
+            }
+        })
+        .await
+        .is_ok();
+        if drained {
+            info!("relay shutdown drained all rooms");
+        } else {
+            let remaining = self
+                .systems
+                .read()
+                .await
+                .values()
+                .map(|room| room.clients.len())
+                .sum::<usize>();
+            warn!("relay shutdown grace elapsed remaining_clients={remaining}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use futures_util::StreamExt;
+    use serde_json::Value;
+    use tokio::sync::oneshot;
+    use tokio_tungstenite::{connect_async, tungstenite::Message};
+
+    use super::{SERVER_SHUTDOWN_EVENT, drain_after};
+    use crate::{app::router, state::AppState};
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn connected_client_receives_shutdown_notice_before_close() {
+        let state = AppState::for_test();
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("bind");
+        let addr = listener.local_addr().expect("local addr");
+        let (trigger_tx, trigger_rx) = oneshot::channel::<()>();
+        let shutdown = drain_after(state.clone(), Duration::from_secs(2), async move {
+            let _ = trigger_rx.await;
+        });
+        let server = tokio::spawn(async move {
+            axum::serve(listener, router(state))
+                .with_graceful_shutdown(shutdown)
+                .await
+        });
+
+        let url = format!(
+            concat!(
+                "ws://{}/v1/ws",
+                "?systemId=sys_down&clientType=sidecar&deviceId=sc_down&pairToken=pt_down"
+            ),
+            addr
+        );
+        let (mut ws, _) = connect_async(url).await.expect("ws handshake");
+        let presence = ws.next().await.expect("presence").expect("presence frame");
+        assert!(
+            presence
+                .to_text()
+                .expect("text")
+                .contains("server_presence")
+        );
+
+        trigger_tx.send(()).expect("trigger shutdown");
+        let notice = ws.next().await.expect("notice").expect("notice frame");
+        let notice: Value =
+            serde_json::from_str(notice.to_text().expect("text")).expect("notice json");
+        assert_eq!(notice["type"], SERVER_SHUTDOWN_EVENT);
+        assert_eq!(notice["payload"]["graceSec"], 2);
+        match ws.next().await {
+            Some(Ok(Message::Close(Some(frame)))) => assert_eq!(u16::from(frame.code), 1001),
+            other => panic!("expected close frame, got {other:?}"),
+        }
+        while ws.next().await.is_some() {}
+
+        tokio::time::timeout(Duration::from_secs(5), server)
+            .await
+            .expect("server stops within grace")
+            .expect("server task")
+            .expect("serve result");
+    }
+}
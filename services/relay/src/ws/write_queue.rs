@@ -0,0 +1,50 @@
+//! 单连接写队列容量：广播以 `try_send` 入队，队列满的慢客户端被判定为 stale 并断开，
+//! 避免广播风暴中 relay 为跟不上的连接无限缓冲或阻塞整个房间。
+
+use tracing::warn;
+
+/// 单连接写队列容量环境变量（帧）。
+const RELAY_WS_WRITE_QUEUE_ENV: &str = "RELAY_WS_WRITE_QUEUE";
+/// 单连接写队列默认容量。
+pub(crate) const DEFAULT_WS_WRITE_QUEUE_CAPACITY: usize = 256;
+
+/// 读取单连接写队列容量；未设置、非法或为 0 时使用默认值。
+pub(crate) fn write_queue_capacity_from_env() -> usize {
+    parse_write_queue_capacity(std::env::var(RELAY_WS_WRITE_QUEUE_ENV).ok().as_deref())
+}
+
+/// 解析单连接写队列容量配置。
+fn parse_write_queue_capacity(raw: Option<&str>) -> usize {
+    match raw.map(|value| value.trim().parse::<usize>()) {
+        Some(Ok(capacity)) if capacity > 0 => capacity,
+        Some(_) => {
+            warn!(
+                "invalid {RELAY_WS_WRITE_QUEUE_ENV}, fallback to {DEFAULT_WS_WRITE_QUEUE_CAPACITY}"
+            );
+            DEFAULT_WS_WRITE_QUEUE_CAPACITY
+        }
+        None => DEFAULT_WS_WRITE_QUEUE_CAPACITY,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DEFAULT_WS_WRITE_QUEUE_CAPACITY, parse_write_queue_capacity};
+
+    #[test]
+    fn capacity_falls_back_to_default_when_unset_or_invalid() {
+        assert_eq!(
+            parse_write_queue_capacity(None),
+            DEFAULT_WS_WRITE_QUEUE_CAPACITY
+        );
+        assert_eq!(
+            parse_write_queue_capacity(Some("0")),
+            DEFAULT_WS_WRITE_QUEUE_CAPACITY
+        );
+        assert_eq!(
+            parse_write_queue_capacity(Some("big")),
+            DEFAULT_WS_WRITE_QUEUE_CAPACITY
+        );
+        assert_eq!(parse_write_queue_capacity(Some(" 1024 ")), 1024);
+    }
+}
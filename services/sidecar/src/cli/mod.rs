@@ -1,15 +1,22 @@
-//! sidecar CLI 分发：`run`、`relay`、`pairing show`、`status`、`doctor`、`service`、`version`。
+//! sidecar CLI 分发：`run`、`relay`、`pairing show`、`tools list`、`status`、`doctor`、`service`、
+//! `version`。
 
-use std::process::Command;
+use std::{process::Command, time::Duration};
 
 use anyhow::{Context, anyhow, bail};
 use serde_json::json;
 
 mod pairing;
 mod relay;
+mod tools;
 
 use pairing::{PairingOutputFormat, PairingShowCommand};
-use relay::RelayCommand;
+use relay::{RelayCommand, probe_relay_ws};
+
+use crate::{config::Config, profile::SidecarProfile};
+
+/// doctor 探测 relay 的超时上限。
+const DOCTOR_RELAY_PROBE_TIMEOUT: Duration = Duration::from_secs(2);
 
 /// CLI 处理结果。
 pub(crate) enum CliDispatch {
@@ -61,17 +68,26 @@ pub(crate) async fn dispatch(args: &[String]) -> anyhow::Result<CliDispatch> {
             pairing::execute_show(pairing_cmd).await?;
             Ok(CliDispatch::Exit)
         }
-        "status" => {
-            let active = service_active();
-            println!("yc-sidecar: {}", if active { "active" } else { "inactive" });
-            if !active {
-                std::process::exit(1);
+        "tools" => {
+            if args[1..]
+                .iter()
+                .any(|value| matches!(value.as_str(), "-h" | "--help" | "help"))
+            {
+                print_tools_help();
+                return Ok(CliDispatch::Exit);
             }
+            let format = parse_tools_command(&args[1..])?;
+            tools::execute_list(format)?;
+            Ok(CliDispatch::Exit)
+        }
+        "status" => {
+            let format = parse_report_format("status", &args[1..])?;
+            run_status(format);
             Ok(CliDispatch::Exit)
         }
         "doctor" => {
-            let format = parse_doctor_format(&args[1..])?;
-            run_doctor(format);
+            let format = parse_report_format("doctor", &args[1..])?;
+            run_doctor(format).await;
             Ok(CliDispatch::Exit)
         }
         "service" => {
@@ -197,6 +213,14 @@ fn parse_pairing_command(args: &[String]) -> anyhow::Result<PairingShowCommand>
     })
 }
 
+/// 解析 `tools list` 子命令，返回输出格式。
+fn parse_tools_command(args: &[String]) -> anyhow::Result<ReportFormat> {
+    if args.first().map(String::as_str) != Some("list") {
+        return Err(anyhow!("usage: yc-sidecar tools list [--format text|json]"));
+    }
+    parse_report_format("tools list", &args[1..])
+}
+
 /// 提取 `--allow-insecure-ws`，返回剩余位置参数。
 fn strip_allow_insecure_flag(args: &[String]) -> (bool, Vec<String>) {
     let mut allow_insecure_ws = false;
@@ -217,7 +241,8 @@ fn print_root_help() {
     println!("  yc-sidecar run");
     println!("  yc-sidecar relay [set|-change|test|reset]");
     println!("  yc-sidecar pairing show [--format text|json|link|qr]");
-    println!("  yc-sidecar status");
+    println!("  yc-sidecar tools list [--format text|json]");
+    println!("  yc-sidecar status [--format text|json]");
     println!("  yc-sidecar doctor [--format text|json]");
     println!("  yc-sidecar service <start|stop|restart|status>");
     println!("  yc-sidecar version");
@@ -240,47 +265,102 @@ fn print_pairing_help() {
     );
 }
 
-/// doctor 输出格式。
-enum DoctorFormat {
+/// 打印 tools help。
+fn print_tools_help() {
+    println!(
+        "yc-sidecar tools list usage:\n  yc-sidecar tools list [--format text|json]\n  本地执行一次工具发现，不连接 relay。"
+    );
+}
+
+/// status/doctor/tools 输出格式。
+enum ReportFormat {
     Text,
     Json,
 }
 
-/// 解析 doctor 的 `--format` 参数。
-fn parse_doctor_format(args: &[String]) -> anyhow::Result<DoctorFormat> {
+/// 解析 status/doctor/tools list 的 `--format` 参数。
+fn parse_report_format(command: &str, args: &[String]) -> anyhow::Result<ReportFormat> {
     if args.is_empty() {
-        return Ok(DoctorFormat::Text);
+        return Ok(ReportFormat::Text);
     }
     if args.len() == 2 && args[0] == "--format" {
         return match args[1].as_str() {
-            "text" => Ok(DoctorFormat::Text),
-            "json" => Ok(DoctorFormat::Json),
-            other => Err(anyhow!("unsupported doctor format: {other}")),
+            "text" => Ok(ReportFormat::Text),
+            "json" => Ok(ReportFormat::Json),
+            other => Err(anyhow!("unsupported {command} format: {other}")),
         };
     }
-    Err(anyhow!("usage: yc-sidecar doctor [--format text|json]"))
+    Err(anyhow!("usage: yc-sidecar {command} [--format text|json]"))
+}
+
+/// 输出 sidecar 服务状态，未活跃时以退出码 1 结束。
+fn run_status(format: ReportFormat) {
+    let active = service_active();
+    match format {
+        ReportFormat::Text => {
+            println!("yc-sidecar: {}", if active { "active" } else { "inactive" });
+        }
+        ReportFormat::Json => {
+            let payload = json!({
+                "active": active,
+                "serviceManager": service_manager(),
+            });
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&payload).unwrap_or_else(|_| "{}".to_string())
+            );
+        }
+    }
+
+    if !active {
+        std::process::exit(1);
+    }
 }
 
 /// 输出 sidecar 诊断信息，并按健康度设置退出码。
-fn run_doctor(format: DoctorFormat) {
+async fn run_doctor(format: ReportFormat) {
     let manager = service_manager();
     let active = service_active();
     let health_addr = std::env::var("SIDECAR_ADDR").unwrap_or_else(|_| "0.0.0.0:18081".to_string());
-    let relay_ws = std::env::var("RELAY_WS_URL").unwrap_or_default();
+    // 探测实际生效的地址（含持久化配置）；配置无效时回退到原始环境变量。
+    let relay_ws = Config::from_env()
+        .map(|cfg| cfg.relay_ws_url)
+        .unwrap_or_else(|_| std::env::var("RELAY_WS_URL").unwrap_or_default());
+    let probe = probe_relay_ws(&relay_ws, DOCTOR_RELAY_PROBE_TIMEOUT).await;
+    let profile = SidecarProfile::from_env().as_str();
+    let config_problems = Config::validate();
 
     match format {
-        DoctorFormat::Text => {
+        ReportFormat::Text => {
+            println!("profile: {profile}");
+            if config_problems.is_empty() {
+                println!("config: ok");
+            } else {
+                println!("config: {} problem(s)", config_problems.len());
+                for problem in &config_problems {
+                    println!("  - {problem}");
+                }
+            }
             println!("service-manager: {manager}");
             println!("service-active: {}", if active { "yes" } else { "no" });
             println!("sidecar-addr: {health_addr}");
             println!("relay-ws-url: {relay_ws}");
+            match (probe.latency_ms, probe.error.as_deref()) {
+                (Some(latency_ms), _) => println!("relay-reachable: yes ({latency_ms} ms)"),
+                (None, error) => println!("relay-reachable: no ({})", error.unwrap_or("unknown")),
+            }
         }
-        DoctorFormat::Json => {
+        ReportFormat::Json => {
             let payload = json!({
+                "profile": profile,
+                "configProblems": config_problems,
                 "serviceManager": manager,
                 "serviceActive": active,
                 "sidecarAddr": health_addr,
                 "relayWsUrl": relay_ws,
+                "relayReachable": probe.reachable,
+                "relayLatencyMs": probe.latency_ms,
+                "relayError": probe.error,
             });
             println!(
                 "{}",
@@ -289,7 +369,7 @@ fn run_doctor(format: DoctorFormat) {
         }
     }
 
-    if !active {
+    if !config_problems.is_empty() || !active || !probe.reachable {
         std::process::exit(1);
     }
 }
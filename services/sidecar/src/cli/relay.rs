@@ -1,14 +1,29 @@
 //! relay 子命令：查看/测试/修改 sidecar 的 relay 地址。
 
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use anyhow::{Context, anyhow};
+use tokio_tungstenite::{
+    connect_async,
+    tungstenite::{Error as WsError, http::StatusCode},
+};
 
 use crate::config::{
     Config, DEFAULT_RELAY_WS_URL, load_sidecar_persisted_config, relay_health_url,
     save_sidecar_persisted_config, validate_user_relay_ws_url,
 };
 
+/// relay 连通性探测结果。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct RelayProbe {
+    /// relay 是否在超时内响应了 WS 握手。
+    pub(crate) reachable: bool,
+    /// 握手往返耗时（毫秒），不可达时为 None。
+    pub(crate) latency_ms: Option<u64>,
+    /// 不可达原因（可选）。
+    pub(crate) error: Option<String>,
+}
+
 /// relay 子命令动作。
 #[derive(Debug, Clone)]
 pub(crate) enum RelayCommand {
@@ -145,3 +160,84 @@ async fn relay_health_check(relay_ws_url: &str) -> anyhow::Result<()> {
     }
     Ok(())
 }
+
+/// 在 `timeout` 内尝试与 relay 完成 WS 握手并计时。
+///
+/// 未携带鉴权参数时 relay 会以 400/401/403 拒绝升级，这类拒绝已能证明 relay 本身可达；
+/// 其余 HTTP 状态（如反向代理返回的 404/502）、连接失败、TLS 失败或超时都视为不可达。
+pub(crate) async fn probe_relay_ws(relay_ws_url: &str, timeout: Duration) -> RelayProbe {
+    let started = Instant::now();
+    let result = tokio::time::timeout(timeout, connect_async(relay_ws_url)).await;
+    let latency_ms = Some(started.elapsed().as_millis().min(u64::MAX as u128) as u64);
+    let error = match result {
+        Ok(Ok(_)) => None,
+        Ok(Err(WsError::Http(response))) if is_relay_rejection(response.status()) => None,
+        Ok(Err(WsError::Http(response))) => Some(format!(
+            "relay upgrade answered with HTTP {}",
+            response.status()
+        )),
+        Ok(Err(err)) => Some(err.to_string()),
+        Err(_) => Some(format!("timed out after {}ms", timeout.as_millis())),
+    };
+    RelayProbe {
+        reachable: error.is_none(),
+        latency_ms: latency_ms.filter(|_| error.is_none()),
+        error,
+    }
+}
+
+/// 判断握手拒绝状态是否来自 relay 鉴权校验（而非代理或路由错误）。
+fn is_relay_rejection(status: StatusCode) -> bool {
+    matches!(
+        status,
+        StatusCode::BAD_REQUEST | StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use tokio::{io::AsyncWriteExt, net::TcpListener};
+
+    use super::probe_relay_ws;
+
+    /// 启动只应答一次给定状态行的本地 HTTP 服务，返回其地址。
+    async fn answer_once(status_line: &'static str) -> std::net::SocketAddr {
+        let listener = TcpListener::bind("127.0.0.1:0").await.expect("bind");
+        let addr = listener.local_addr().expect("addr");
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.expect("accept");
+            let response = format!("{status_line}\r\ncontent-length: 0\r\n\r\n");
+            let _ = stream.write_all(response.as_bytes()).await;
+        });
+        addr
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn rejected_handshake_counts_as_reachable_but_closed_port_does_not() {
+        let addr = answer_once("HTTP/1.1 400 Bad Request").await;
+        let probe = probe_relay_ws(&format!("ws://{addr}/v1/ws"), Duration::from_secs(2)).await;
+        assert!(probe.reachable, "{probe:?}");
+        assert!(probe.latency_ms.is_some());
+
+        let addr = answer_once("HTTP/1.1 502 Bad Gateway").await;
+        let probe = probe_relay_ws(&format!("ws://{addr}/v1/ws"), Duration::from_secs(2)).await;
+        assert!(!probe.reachable);
+        assert!(
+            probe
+                .error
+                .as_deref()
+                .is_some_and(|err| err.contains("502"))
+        );
+
+        let closed = TcpListener::bind("127.0.0.1:0").await.expect("bind");
+        let closed_addr = closed.local_addr().expect("addr");
+        drop(closed);
+        let probe =
+            probe_relay_ws(&format!("ws://{closed_addr}/v1/ws"), Duration::from_secs(2)).await;
+        assert!(!probe.reachable);
+        assert_eq!(probe.latency_ms, None);
+        assert!(probe.error.is_some());
+    }
+}
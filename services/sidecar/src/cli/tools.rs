@@ -0,0 +1,84 @@
+//! tools 子命令：本地执行一次工具发现并输出结果，不连接 relay，用于配对前确认进程可被识别。
+
+use anyhow::Context;
+use sysinfo::System;
+use yc_shared_protocol::ToolRuntimePayload;
+
+use super::ReportFormat;
+use crate::{config::Config, tooling::core::ToolAdapterCore};
+
+/// 表格列标题。
+const TABLE_HEADERS: [&str; 6] = ["TOOL ID", "NAME", "MODE", "CONNECTED", "PID", "WORKSPACE"];
+
+/// 执行 `tools list`。
+pub(crate) fn execute_list(format: ReportFormat) -> anyhow::Result<()> {
+    let cfg = Config::from_env()?;
+    let mut core = ToolAdapterCore::new(
+        cfg.fallback_tool,
+        cfg.details_interval,
+        cfg.details_command_timeout,
+        cfg.details_max_parallel,
+        cfg.details_refresh_debounce,
+    );
+    let tools = core.discover_tools(&mut System::new());
+
+    match format {
+        ReportFormat::Text => print!("{}", render_tools_table(&tools)),
+        ReportFormat::Json => println!(
+            "{}",
+            serde_json::to_string_pretty(&tools).context("encode tools json failed")?
+        ),
+    }
+    Ok(())
+}
+
+/// 渲染工具表格；无工具时输出提示行。
+fn render_tools_table(tools: &[ToolRuntimePayload]) -> String {
+    if tools.is_empty() {
+        return "no tools discovered\n".to_string();
+    }
+
+    let rows = tools
+        .iter()
+        .map(|tool| {
+            [
+                tool.tool_id.clone(),
+                tool.name.clone(),
+                tool.mode.clone(),
+                if tool.connected { "yes" } else { "no" }.to_string(),
+                tool.pid
+                    .map(|pid| pid.to_string())
+                    .unwrap_or_else(|| "-".to_string()),
+                tool.workspace_dir
+                    .clone()
+                    .unwrap_or_else(|| "-".to_string()),
+            ]
+        })
+        .collect::<Vec<_>>();
+    let mut widths = TABLE_HEADERS.map(|header| header.chars().count());
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let mut out = String::new();
+    push_row(&mut out, &TABLE_HEADERS.map(str::to_string), &widths);
+    for row in &rows {
+        push_row(&mut out, row, &widths);
+    }
+    out
+}
+
+/// 按列宽左对齐追加一行（末列不补空格）。
+fn push_row(out: &mut String, cells: &[String; 6], widths: &[usize; 6]) {
+    let last = cells.len() - 1;
+    for (index, (cell, width)) in cells.iter().zip(widths).enumerate() {
+        if index == last {
+            out.push_str(cell);
+        } else {
+            out.push_str(&format!("{cell:<width$}  "));
+        }
+    }
+    out.push('\n');
+}
@@ -14,18 +14,30 @@ use std::{
 
 use anyhow::{Context, anyhow};
 use serde::{Deserialize, Serialize};
+use tracing::warn;
 use url::Url;
 use uuid::Uuid;
 
+use crate::home::sidecar_config_dir;
+use crate::profile::{ProfileSettings, SidecarProfile};
+use crate::session::diagnostics::DEFAULT_DIAGNOSTICS_INTERVAL_SEC;
+use crate::session::metrics_cadence::AdaptiveMetricsConfig;
+use crate::session::metrics_history::DEFAULT_METRICS_HISTORY_LEN;
+use crate::session::metrics_smoothing::metrics_smoothing_alpha_from_env;
+use crate::session::whitelist_prune::{
+    DEFAULT_WHITELIST_PRUNE_GRACE_SEC, WHITELIST_PRUNE_GRACE_ENV, WhitelistPrunePolicy,
+};
+use crate::stores::{CONTROLLER_BIND_POLICY_ENV, ControllerBindPolicy};
+use crate::tooling::core::errors::DEFAULT_COLLECTION_ERRORS_WINDOW_SEC;
 use crate::tooling::core::scheduler::{
-    DEFAULT_DETAILS_COMMAND_TIMEOUT_MS, DEFAULT_DETAILS_DEBOUNCE_SEC, DEFAULT_DETAILS_INTERVAL_SEC,
-    DEFAULT_DETAILS_MAX_PARALLEL,
+    DEFAULT_DETAILS_COMMAND_TIMEOUT_MS, DEFAULT_DETAILS_DEBOUNCE_SEC,
+    DEFAULT_DETAILS_FOCUS_INTERVAL_MS, DEFAULT_DETAILS_MAX_PARALLEL, clamp_focus_interval,
 };
 
 /// sidecar 默认 relay 地址（开发态默认本机）。
 pub(crate) const DEFAULT_RELAY_WS_URL: &str = "ws://127.0.0.1:18080/v1/ws";
 /// 允许不安全 ws 的环境变量开关。
-const ALLOW_INSECURE_WS_ENV: &str = "YC_ALLOW_INSECURE_WS";
+pub(crate) const ALLOW_INSECURE_WS_ENV: &str = "YC_ALLOW_INSECURE_WS";
 /// 标记 research 构建渠道的环境变量。
 const BUILD_CHANNEL_ENV: &str = "YC_BUILD_CHANNEL";
 /// 持久化配置版本。
@@ -77,6 +89,8 @@ fn default_config_version() -> u8 {
 /// Sidecar 运行时配置。
 #[derive(Debug, Clone)]
 pub(crate) struct Config {
+    /// 生效的运行 profile。
+    pub(crate) profile: SidecarProfile,
     /// Relay WebSocket 地址。
     pub(crate) relay_ws_url: String,
     /// 宿主系统标识。
@@ -89,14 +103,22 @@ pub(crate) struct Config {
     pub(crate) host_name: String,
     /// 预授权控制端设备 ID 列表。
     pub(crate) controller_device_ids: Vec<String>,
-    /// 当未配置控制端白名单时，是否允许首个 app 自动绑定。
-    pub(crate) allow_first_controller_bind: bool,
+    /// 未绑定设备发控制命令时的绑定策略。
+    pub(crate) controller_bind_policy: ControllerBindPolicy,
     /// Sidecar 健康检查监听地址。
     pub(crate) health_addr: String,
     /// 心跳推送周期。
     pub(crate) heartbeat_interval: Duration,
     /// 指标快照推送周期。
     pub(crate) metrics_interval: Duration,
+    /// 工具发现周期（tools_snapshot / tools_candidates），未配置时与指标周期一致。
+    pub(crate) discovery_interval: Duration,
+    /// 指标历史缓冲容量（条）。
+    pub(crate) metrics_history_len: usize,
+    /// 指标快照自适应周期参数，None 表示固定周期。
+    pub(crate) metrics_adaptive: Option<AdaptiveMetricsConfig>,
+    /// 系统 CPU/内存 EMA 平滑系数（`[0, 1]`），0 表示关闭。
+    pub(crate) metrics_smoothing_alpha: f64,
     /// 配对 banner 刷新周期（自动重新签发短时链接）。
     pub(crate) pairing_banner_refresh_interval: Duration,
     /// 工具详情补采周期。
@@ -107,14 +129,27 @@ pub(crate) struct Config {
     pub(crate) details_command_timeout: Duration,
     /// 工具详情采集并发上限。
     pub(crate) details_max_parallel: usize,
+    /// 聚焦工具详情补采周期（已限制在允许区间内）。
+    pub(crate) details_focus_interval: Duration,
     /// 是否启用 fallback 工具占位。
     pub(crate) fallback_tool: bool,
+    /// 单次快照上报的工具数量上限（connected 优先），None 表示不限制。
+    pub(crate) max_tools: Option<usize>,
+    /// 采集错误汇总窗口（同时为 `collection_errors` 下发周期）。
+    pub(crate) collection_errors_window: Duration,
+    /// `sidecar_diagnostics` 周期下发间隔。
+    pub(crate) diagnostics_interval: Duration,
+    /// 白名单工具持续缺失时的处理策略。
+    pub(crate) whitelist_prune: WhitelistPrunePolicy,
+    /// 白名单工具缺失宽限期。
+    pub(crate) whitelist_prune_grace: Duration,
 }
 
 impl Config {
     /// 从环境变量与配置文件构建配置，并做 relay URL 安全校验。
     pub(crate) fn from_env() -> anyhow::Result<Self> {
         let persisted = load_sidecar_persisted_config().unwrap_or_default();
+        let profile_settings = ProfileSettings::from_env();
 
         let raw_relay = std::env::var("RELAY_WS_URL")
             .ok()
@@ -123,9 +158,9 @@ impl Config {
             .or_else(|| persisted.relay_ws_url.clone())
             .unwrap_or_else(|| DEFAULT_RELAY_WS_URL.to_string());
 
-        let allow_insecure_ws = bool_from_env(ALLOW_INSECURE_WS_ENV, false);
-        let relay_ws_url = validate_relay_ws_url_with_mode(&raw_relay, allow_insecure_ws)
-            .with_context(|| format!("invalid relay ws url: {raw_relay}"))?;
+        let relay_ws_url =
+            validate_relay_ws_url_with_mode(&raw_relay, profile_settings.allow_insecure_ws)
+                .with_context(|| format!("invalid relay ws url: {raw_relay}"))?;
 
         let system_id = std::env::var("SYSTEM_ID")
             .ok()
@@ -171,23 +206,32 @@ impl Config {
         let allow_first_controller_bind = bool_from_env_optional("ALLOW_FIRST_CONTROLLER_BIND")
             .or(persisted.allow_first_controller_bind)
             .unwrap_or_else(|| relay_is_local(&relay_ws_url));
+        let controller_bind_policy = ControllerBindPolicy::resolve(
+            std::env::var(CONTROLLER_BIND_POLICY_ENV).ok().as_deref(),
+            allow_first_controller_bind,
+        );
 
         Ok(Self {
+            profile: profile_settings.profile,
             relay_ws_url,
             system_id,
             device_id,
             pair_token,
             host_name,
             controller_device_ids,
-            allow_first_controller_bind,
+            controller_bind_policy,
             health_addr: env_or_default("SIDECAR_ADDR", "0.0.0.0:18081"),
-            heartbeat_interval: duration_from_env("HEARTBEAT_INTERVAL_SEC", 5),
-            metrics_interval: duration_from_env("METRICS_INTERVAL_SEC", 10),
-            pairing_banner_refresh_interval: duration_from_env("PAIRING_BANNER_REFRESH_SEC", 120),
-            details_interval: duration_from_env(
-                "DETAILS_INTERVAL_SEC",
-                DEFAULT_DETAILS_INTERVAL_SEC,
+            heartbeat_interval: profile_settings.heartbeat_interval,
+            metrics_interval: profile_settings.metrics_interval,
+            discovery_interval: duration_from_env(
+                "DISCOVERY_INTERVAL_SEC",
+                profile_settings.metrics_interval.as_secs(),
             ),
+            metrics_history_len: usize_from_env("METRICS_HISTORY_LEN", DEFAULT_METRICS_HISTORY_LEN),
+            metrics_adaptive: AdaptiveMetricsConfig::from_env(),
+            metrics_smoothing_alpha: metrics_smoothing_alpha_from_env(),
+            pairing_banner_refresh_interval: duration_from_env("PAIRING_BANNER_REFRESH_SEC", 120),
+            details_interval: profile_settings.details_interval,
             details_refresh_debounce: duration_from_env(
                 "DETAILS_REFRESH_DEBOUNCE_SEC",
                 DEFAULT_DETAILS_DEBOUNCE_SEC,
@@ -200,7 +244,25 @@ impl Config {
                 "DETAILS_MAX_PARALLEL",
                 DEFAULT_DETAILS_MAX_PARALLEL,
             ),
+            details_focus_interval: clamp_focus_interval(duration_from_env_millis(
+                "DETAILS_FOCUS_INTERVAL_MS",
+                DEFAULT_DETAILS_FOCUS_INTERVAL_MS,
+            )),
             fallback_tool: bool_from_env("FALLBACK_TOOL_ENABLED", false),
+            max_tools: usize_from_env_optional("SIDECAR_MAX_TOOLS"),
+            collection_errors_window: duration_from_env(
+                "SIDECAR_COLLECTION_ERRORS_WINDOW_SEC",
+                DEFAULT_COLLECTION_ERRORS_WINDOW_SEC,
+            ),
+            diagnostics_interval: duration_from_env(
+                "SIDECAR_DIAGNOSTICS_INTERVAL_SEC",
+                DEFAULT_DIAGNOSTICS_INTERVAL_SEC,
+            ),
+            whitelist_prune: WhitelistPrunePolicy::from_env(),
+            whitelist_prune_grace: duration_from_env(
+                WHITELIST_PRUNE_GRACE_ENV,
+                DEFAULT_WHITELIST_PRUNE_GRACE_SEC,
+            ),
         })
     }
 
@@ -249,17 +311,7 @@ pub(crate) fn save_sidecar_persisted_config(config: &SidecarPersistedConfig) ->
 
 /// 返回 sidecar 配置文件路径。
 pub(crate) fn sidecar_config_file_path() -> Option<PathBuf> {
-    let home = std::env::var("HOME").ok()?;
-    if home.trim().is_empty() {
-        return None;
-    }
-    Some(
-        Path::new(&home)
-            .join(".config")
-            .join("yourconnector")
-            .join("sidecar")
-            .join("config.json"),
-    )
+    sidecar_config_dir().map(|dir| dir.join("config.json"))
 }
 
 /// 对外暴露：校验并规范化用户输入的 relay WS URL。
@@ -498,7 +550,7 @@ fn csv_list_from_env_optional(key: &str) -> Option<Vec<String>> {
 fn duration_from_env(key: &str, fallback_sec: u64) -> Duration {
     std::env::var(key)
         .ok()
-        .and_then(|raw| raw.parse::<u64>().ok())
+        .and_then(|raw| raw.trim().parse::<u64>().ok())
         .filter(|v| *v > 0)
         .map(Duration::from_secs)
         .unwrap_or_else(|| Duration::from_secs(fallback_sec))
@@ -508,7 +560,7 @@ fn duration_from_env(key: &str, fallback_sec: u64) -> Duration {
 fn duration_from_env_millis(key: &str, fallback_ms: u64) -> Duration {
     std::env::var(key)
         .ok()
-        .and_then(|raw| raw.parse::<u64>().ok())
+        .and_then(|raw| raw.trim().parse::<u64>().ok())
         .filter(|v| *v > 0)
         .map(Duration::from_millis)
         .unwrap_or_else(|| Duration::from_millis(fallback_ms))
@@ -518,11 +570,19 @@ fn duration_from_env_millis(key: &str, fallback_ms: u64) -> Duration {
 fn usize_from_env(key: &str, fallback: usize) -> usize {
     std::env::var(key)
         .ok()
-        .and_then(|raw| raw.parse::<usize>().ok())
+        .and_then(|raw| raw.trim().parse::<usize>().ok())
         .filter(|value| *value > 0)
         .unwrap_or(fallback)
 }
 
+/// 读取可选 usize 配置，未设置、非法或 0 视为未配置。
+fn usize_from_env_optional(key: &str) -> Option<usize> {
+    std::env::var(key)
+        .ok()
+        .and_then(|raw| raw.trim().parse::<usize>().ok())
+        .filter(|value| *value > 0)
+}
+
 /// 解析布尔环境变量，支持常见 true/false 文本。
 fn bool_from_env(key: &str, fallback: bool) -> bool {
     match std::env::var(key) {
@@ -571,10 +631,28 @@ fn load_or_create_system_id() -> String {
 
 /// 读取或生成宿主机持久化 `pairToken`。
 fn load_or_create_pair_token() -> String {
-    load_or_create_identity_value("pair-token", || {
-        let hex = Uuid::new_v4().simple().to_string();
-        format!("ptk_{hex}")
-    })
+    load_or_create_identity_value("pair-token", new_pair_token)
+}
+
+/// 生成新的 `pairToken` 并覆盖持久化文件（relay 通知令牌已作废时调用）；
+/// 写盘失败时新令牌仅对本进程生效。
+pub(crate) fn rotate_pair_token() -> String {
+    let value = new_pair_token();
+    match identity_file_path("pair-token") {
+        Some(path) => {
+            if let Err(err) = write_identity_file(&path, &value) {
+                warn!("persist rotated pair token failed: {err}");
+            }
+        }
+        None => warn!("sidecar config dir unresolved, rotated pair token is not persisted"),
+    }
+    value
+}
+
+/// 生成随机 `pairToken`。
+fn new_pair_token() -> String {
+    let hex = Uuid::new_v4().simple().to_string();
+    format!("ptk_{hex}")
 }
 
 /// 身份值通用持久化逻辑：存在则读取，不存在则生成并写盘。
@@ -597,17 +675,7 @@ where
 
 /// sidecar 身份文件路径：`~/.config/yourconnector/sidecar/<name>.txt`。
 fn identity_file_path(name: &str) -> Option<PathBuf> {
-    let home = std::env::var("HOME").ok()?;
-    if home.trim().is_empty() {
-        return None;
-    }
-    Some(
-        Path::new(&home)
-            .join(".config")
-            .join("yourconnector")
-            .join("sidecar")
-            .join(format!("{name}.txt")),
-    )
+    sidecar_config_dir().map(|dir| dir.join(format!("{name}.txt")))
 }
 
 /// 读取文本并去掉首尾空白；空字符串视为无效。
@@ -0,0 +1,241 @@
+//! 启动配置校验职责：
+//! 1. 在进入 relay 循环前检查 relay 地址（scheme、路径、非回环 ws 开关）与周期、数量类环境变量，
+//!    把运行期才暴露的连接失败或被静默回退的非法取值提前为可读的问题列表。
+//! 2. `main` 与 `doctor` 共用同一份校验：前者打印后以非零码退出，后者并入诊断输出。
+//! 3. 只读取环境与持久化配置，不生成身份文件、不发起网络请求。
+
+use std::fmt;
+
+use serde::Serialize;
+
+use crate::{
+    config::{
+        ALLOW_INSECURE_WS_ENV, Config, DEFAULT_RELAY_WS_URL, load_sidecar_persisted_config,
+        validate_user_relay_ws_url,
+    },
+    profile::ProfileSettings,
+    session::{
+        metrics_cadence::{METRICS_ADAPTIVE_MAX_ENV, METRICS_ADAPTIVE_MIN_ENV},
+        whitelist_prune::WHITELIST_PRUNE_GRACE_ENV,
+    },
+};
+
+/// relay 地址环境变量。
+const RELAY_WS_URL_ENV: &str = "RELAY_WS_URL";
+/// 以秒为单位、必须为正整数的周期变量。
+const SECOND_INTERVAL_KEYS: &[&str] = &[
+    "HEARTBEAT_INTERVAL_SEC",
+    "METRICS_INTERVAL_SEC",
+    "DETAILS_INTERVAL_SEC",
+    "DISCOVERY_INTERVAL_SEC",
+    "PAIRING_BANNER_REFRESH_SEC",
+    "DETAILS_REFRESH_DEBOUNCE_SEC",
+    "SIDECAR_COLLECTION_ERRORS_WINDOW_SEC",
+    "SIDECAR_DIAGNOSTICS_INTERVAL_SEC",
+    METRICS_ADAPTIVE_MIN_ENV,
+    METRICS_ADAPTIVE_MAX_ENV,
+    WHITELIST_PRUNE_GRACE_ENV,
+];
+/// 以毫秒为单位、必须为正整数的周期变量。
+const MILLI_INTERVAL_KEYS: &[&str] = &["DETAILS_COMMAND_TIMEOUT_MS", "DETAILS_FOCUS_INTERVAL_MS"];
+/// 必须为正整数的数量变量。
+const POSITIVE_COUNT_KEYS: &[&str] = &["METRICS_HISTORY_LEN", "DETAILS_MAX_PARALLEL"];
+/// 允许 `0`（表示不限制）的数量变量。
+const OPTIONAL_COUNT_KEYS: &[&str] = &["SIDECAR_MAX_TOOLS"];
+/// 布尔开关变量。
+const BOOL_KEYS: &[&str] = &[
+    ALLOW_INSECURE_WS_ENV,
+    "ALLOW_FIRST_CONTROLLER_BIND",
+    "FALLBACK_TOOL_ENABLED",
+];
+
+/// 单条配置问题。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ConfigProblem {
+    /// 出问题的配置项（环境变量名）。
+    pub(crate) key: String,
+    /// 实际取值。
+    pub(crate) value: String,
+    /// 问题描述。
+    pub(crate) message: String,
+    /// 修正建议。
+    pub(crate) hint: String,
+}
+
+impl fmt::Display for ConfigProblem {
+    /// 单行输出：`KEY="value": message (hint)`。
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}={:?}: {} ({})",
+            self.key, self.value, self.message, self.hint
+        )
+    }
+}
+
+impl Config {
+    /// 校验当前进程环境与持久化配置，返回全部问题；为空表示可以启动。
+    pub(crate) fn validate() -> Vec<ConfigProblem> {
+        let persisted_relay = load_sidecar_persisted_config()
+            .ok()
+            .and_then(|persisted| persisted.relay_ws_url);
+        validate_with(|key| std::env::var(key).ok(), persisted_relay)
+    }
+}
+
+/// 按给定环境读取函数校验；relay 地址取值顺序与 `Config::from_env` 一致。
+fn validate_with<F>(lookup: F, persisted_relay: Option<String>) -> Vec<ConfigProblem>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    let mut problems = Vec::new();
+    let set_value = |key: &str| lookup(key).filter(|raw| !raw.trim().is_empty());
+
+    let relay_from_env = set_value(RELAY_WS_URL_ENV);
+    let relay_key = if relay_from_env.is_some() {
+        RELAY_WS_URL_ENV
+    } else {
+        "config.json relayWsUrl"
+    };
+    let raw_relay = relay_from_env
+        .or(persisted_relay)
+        .unwrap_or_else(|| DEFAULT_RELAY_WS_URL.to_string());
+    let allow_insecure_ws = ProfileSettings::resolve(&lookup).allow_insecure_ws;
+    if let Err(err) = validate_user_relay_ws_url(&raw_relay, allow_insecure_ws) {
+        let hint = if raw_relay.trim().to_ascii_lowercase().starts_with("ws://") {
+            format!(
+                "use wss://<host>/v1/ws, or set {ALLOW_INSECURE_WS_ENV}=1 \
+                 (relay set --allow-insecure-ws) in a debug/research build"
+            )
+        } else {
+            "expected wss://<host>/v1/ws; fix it with `yc-sidecar relay set <url>`".to_string()
+        };
+        problems.push(ConfigProblem {
+            key: relay_key.to_string(),
+            value: raw_relay.trim().to_string(),
+            message: format!("{err:#}"),
+            hint,
+        });
+    }
+
+    let mut check = |keys: &[&str], allow_zero: bool, hint: &str| {
+        for key in keys {
+            let Some(raw) = set_value(key) else {
+                continue;
+            };
+            let message = match raw.trim().parse::<u64>() {
+                Ok(0) if !allow_zero => "must be greater than 0",
+                Ok(_) => continue,
+                Err(_) => "not an unsigned integer",
+            };
+            problems.push(ConfigProblem {
+                key: (*key).to_string(),
+                value: raw,
+                message: message.to_string(),
+                hint: hint.to_string(),
+            });
+        }
+    };
+    check(
+        SECOND_INTERVAL_KEYS,
+        false,
+        "use whole seconds, e.g. 30; unset to use the default",
+    );
+    check(
+        MILLI_INTERVAL_KEYS,
+        false,
+        "use whole milliseconds, e.g. 3000; unset to use the default",
+    );
+    check(
+        POSITIVE_COUNT_KEYS,
+        false,
+        "use a positive integer; unset to use the default",
+    );
+    check(
+        OPTIONAL_COUNT_KEYS,
+        true,
+        "use a non-negative integer, 0 means unlimited",
+    );
+
+    for key in BOOL_KEYS {
+        let Some(raw) = set_value(key) else {
+            continue;
+        };
+        if !matches!(
+            raw.trim().to_ascii_lowercase().as_str(),
+            "1" | "true" | "yes" | "y" | "on" | "0" | "false" | "no" | "n" | "off"
+        ) {
+            problems.push(ConfigProblem {
+                key: (*key).to_string(),
+                value: raw,
+                message: "not a boolean".to_string(),
+                hint: "use true/false (1/0, yes/no, on/off)".to_string(),
+            });
+        }
+    }
+    problems
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::validate_with;
+
+    /// 以给定变量与持久化 relay 地址执行校验，返回出问题的配置项。
+    fn problem_keys(vars: &[(&str, &str)], persisted_relay: Option<&str>) -> Vec<String> {
+        let vars = vars
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect::<HashMap<_, _>>();
+        validate_with(
+            |key| vars.get(key).cloned(),
+            persisted_relay.map(ToString::to_string),
+        )
+        .into_iter()
+        .map(|problem| problem.key)
+        .collect()
+    }
+
+    #[test]
+    fn validate_reports_bad_relay_url_and_unparseable_values() {
+        assert!(problem_keys(&[], None).is_empty());
+        assert!(
+            problem_keys(&[("RELAY_WS_URL", "wss://relay.example.com/v1/ws")], None).is_empty()
+        );
+
+        assert_eq!(
+            problem_keys(&[("RELAY_WS_URL", "https://relay.example.com/v1/ws")], None),
+            vec!["RELAY_WS_URL"]
+        );
+        assert_eq!(
+            problem_keys(&[("RELAY_WS_URL", "ws://relay.example.com/v1/ws")], None),
+            vec!["RELAY_WS_URL"]
+        );
+        assert_eq!(
+            problem_keys(&[], Some("wss://relay.example.com/other")),
+            vec!["config.json relayWsUrl"]
+        );
+
+        let keys = problem_keys(
+            &[
+                ("METRICS_INTERVAL_SEC", "10s"),
+                ("DETAILS_COMMAND_TIMEOUT_MS", "0"),
+                ("DETAILS_MAX_PARALLEL", " 4 "),
+                ("SIDECAR_MAX_TOOLS", "0"),
+                ("FALLBACK_TOOL_ENABLED", "maybe"),
+                ("HEARTBEAT_INTERVAL_SEC", ""),
+            ],
+            None,
+        );
+        assert_eq!(
+            keys,
+            vec![
+                "METRICS_INTERVAL_SEC",
+                "DETAILS_COMMAND_TIMEOUT_MS",
+                "FALLBACK_TOOL_ENABLED"
+            ]
+        );
+    }
+}
@@ -5,7 +5,9 @@
 
 use serde_json::Value;
 use uuid::Uuid;
-use yc_shared_protocol::ToolDetailsRefreshPriority;
+use yc_shared_protocol::{CommandFeedbackPayload, ToolDetailsRefreshPriority};
+
+use crate::tooling::tool_logs::{DEFAULT_TOOL_LOG_LINES, MAX_TOOL_LOG_LINES};
 
 /// 请求接入某个候选工具。
 pub(crate) const TOOL_CONNECT_REQUEST_EVENT: &str = "tool_connect_request";
@@ -17,16 +19,50 @@ pub(crate) const TOOLS_REFRESH_REQUEST_EVENT: &str = "tools_refresh_request";
 pub(crate) const TOOL_WHITELIST_RESET_REQUEST_EVENT: &str = "tool_whitelist_reset_request";
 /// 请求 sidecar 立即刷新工具详情（支持指定 toolId）。
 pub(crate) const TOOL_DETAILS_REFRESH_REQUEST_EVENT: &str = "tool_details_refresh_request";
+/// 请求 sidecar 绕过详情队列立即采集单个工具详情，并以 requestId 回执。
+pub(crate) const TOOL_DETAILS_NOW_REQUEST_EVENT: &str = "tool_details_now_request";
+/// sidecar 返回同步详情采集结果。
+pub(crate) const TOOL_DETAILS_NOW_RESULT_EVENT: &str = "tool_details_now_result";
+/// 请求 sidecar 对单个工具启用聚焦高频详情补采。
+pub(crate) const TOOL_FOCUS_REQUEST_EVENT: &str = "tool_focus_request";
+/// 请求 sidecar 取消工具聚焦，恢复常规详情周期。
+pub(crate) const TOOL_UNFOCUS_REQUEST_EVENT: &str = "tool_unfocus_request";
+/// App 主动断开前告知 sidecar 本设备离开。
+pub(crate) const APP_DISCONNECT_EVENT: &str = "app_disconnect";
+/// 请求设置工具自定义显示名（空白表示清除）。
+pub(crate) const TOOL_LABEL_SET_REQUEST_EVENT: &str = "tool_label_set_request";
+/// sidecar 返回工具显示名设置结果。
+pub(crate) const TOOL_LABEL_UPDATED_EVENT: &str = "tool_label_updated";
+/// 请求 sidecar 运行时调整某个模块的日志级别。
+pub(crate) const SIDECAR_LOG_LEVEL_REQUEST_EVENT: &str = "sidecar_log_level_request";
+/// sidecar 返回日志级别调整结果。
+pub(crate) const SIDECAR_LOG_LEVEL_UPDATED_EVENT: &str = "sidecar_log_level_updated";
 /// sidecar 返回工具白名单更新结果。
 pub(crate) const TOOL_WHITELIST_UPDATED_EVENT: &str = "tool_whitelist_updated";
 /// 请求 sidecar 控制工具进程（停止/重启）。
 pub(crate) const TOOL_PROCESS_CONTROL_REQUEST_EVENT: &str = "tool_process_control_request";
 /// sidecar 返回工具进程控制结果。
 pub(crate) const TOOL_PROCESS_CONTROL_UPDATED_EVENT: &str = "tool_process_control_updated";
+/// 请求 sidecar 结束某个工具进程（TERM，超时后 KILL）。
+pub(crate) const TOOL_KILL_REQUEST_EVENT: &str = "tool_kill_request";
+/// sidecar 返回工具进程结束结果。
+pub(crate) const TOOL_KILL_UPDATED_EVENT: &str = "tool_kill_updated";
+/// 请求 sidecar 回放最近的指标历史样本。
+pub(crate) const METRICS_HISTORY_REQUEST_EVENT: &str = "metrics_history_request";
+/// sidecar 返回指标历史样本。
+pub(crate) const METRICS_HISTORY_EVENT: &str = "metrics_history";
+/// 请求 sidecar 立即下发一次自诊断（队列深度、丢弃计数、详情耗时）。
+pub(crate) const SIDECAR_DIAGNOSTICS_REQUEST_EVENT: &str = "sidecar_diagnostics_request";
+/// 请求 sidecar 回放工具最近的日志行。
+pub(crate) const TOOL_LOGS_REQUEST_EVENT: &str = "tool_logs_request";
+/// sidecar 返回工具日志行。
+pub(crate) const TOOL_LOGS_EVENT: &str = "tool_logs";
 /// 请求把当前（或指定）设备重绑为控制端。
 pub(crate) const CONTROLLER_REBIND_REQUEST_EVENT: &str = "controller_rebind_request";
 /// sidecar 返回控制端绑定更新结果。
 pub(crate) const CONTROLLER_BIND_UPDATED_EVENT: &str = "controller_bind_updated";
+/// sidecar 请求已绑定控制端审批未绑定设备（`manual_approve` 策略）。
+pub(crate) const CONTROLLER_BIND_REQUEST_EVENT: &str = "controller_bind_request";
 /// 请求执行工具聊天（单条消息）。
 pub(crate) const TOOL_CHAT_REQUEST_EVENT: &str = "tool_chat_request";
 /// 请求取消当前工具聊天执行。
@@ -72,6 +108,8 @@ const EVENT_TYPE_FIELD: &str = "type";
 const EVENT_ID_FIELD: &str = "eventId";
 /// 统一事件字段：链路追踪 ID。
 const TRACE_ID_FIELD: &str = "traceId";
+/// 统一事件字段：是否要求接收方 ACK。
+const ACK_REQUIRED_FIELD: &str = "ackRequired";
 /// 兼容字段：旧链路通过 peerId 携带来源设备 ID。
 const PEER_ID_FIELD: &str = "peerId";
 
@@ -86,6 +124,8 @@ pub(crate) enum SidecarCommand {
     DisconnectTool { tool_id: String },
     /// 清空全部工具白名单，断开全部已接入工具。
     ResetToolWhitelist,
+    /// 设置工具自定义显示名；空白 label 表示清除。
+    SetToolLabel { tool_id: String, label: String },
     /// 刷新工具详情（可指定单工具）。
     RefreshToolDetails {
         refresh_id: String,
@@ -93,13 +133,31 @@ pub(crate) enum SidecarCommand {
         force: bool,
         priority: ToolDetailsRefreshPriority,
     },
+    /// 立即强制采集单个工具详情并同步回执（不经过 latest-wins 队列）。
+    GetToolDetailsNow { request_id: String, tool_id: String },
+    /// 聚焦单个工具：该工具按短周期补采详情，其他工具保持常规周期。
+    FocusTool { tool_id: String },
+    /// 取消当前工具聚焦。
+    UnfocusTool,
+    /// 来源 App 设备即将断开，重连前不再为其补采详情。
+    AppDisconnect,
     /// 控制工具进程：当前仅支持 OpenClaw 的停止/重启。
     ControlToolProcess {
         tool_id: String,
         action: ToolProcessAction,
     },
+    /// 结束任意已发现工具的进程（不含 fallback 工具与 sidecar 自身）。
+    KillTool { tool_id: String },
+    /// 回放最近的指标历史样本；limit 为空时返回全部缓冲。
+    MetricsHistoryRequest { limit: Option<usize> },
+    /// 立即下发一次 `sidecar_diagnostics`。
+    DiagnosticsRequest,
+    /// 读取工具日志目录中最近日志文件的末尾行数（已按上限截断）。
+    ToolLogsRequest { tool_id: String, lines: usize },
     /// 将控制端设备重绑为指定 deviceId。
     RebindController { device_id: String },
+    /// 运行时调整模块日志级别；`level=reset` 恢复启动时的级别。
+    SetLogLevel { target: String, level: String },
     /// 发起工具聊天请求。
     ToolChatRequest {
         tool_id: String,
@@ -203,6 +261,8 @@ pub(crate) struct SidecarCommandEnvelope {
     pub(crate) source_client_type: String,
     /// 来源设备 ID。
     pub(crate) source_device_id: String,
+    /// 是否要求回发 `ack`。
+    pub(crate) ack_required: bool,
 }
 
 fn parse_u64_field(value: Option<&Value>) -> u64 {
@@ -355,6 +415,10 @@ pub(crate) fn parse_sidecar_command(raw: &str) -> Option<SidecarCommandEnvelope>
         .map(str::trim)
         .unwrap_or_default()
         .to_string();
+    let ack_required = event
+        .get(ACK_REQUIRED_FIELD)
+        .and_then(Value::as_bool)
+        .unwrap_or(false);
 
     let command = match event_type {
         TOOLS_REFRESH_REQUEST_EVENT => Some(SidecarCommand::Refresh),
@@ -375,6 +439,36 @@ pub(crate) fn parse_sidecar_command(raw: &str) -> Option<SidecarCommandEnvelope>
                 tool_id: tool_id.to_string(),
             }),
         TOOL_WHITELIST_RESET_REQUEST_EVENT => Some(SidecarCommand::ResetToolWhitelist),
+        TOOL_LABEL_SET_REQUEST_EVENT => {
+            let tool_id = payload
+                .get("toolId")
+                .and_then(Value::as_str)
+                .map(str::trim)
+                .filter(|value| !value.is_empty())
+                .map(ToString::to_string)?;
+            let label = payload
+                .get("label")
+                .and_then(Value::as_str)
+                .map(str::trim)
+                .unwrap_or_default()
+                .to_string();
+            Some(SidecarCommand::SetToolLabel { tool_id, label })
+        }
+        SIDECAR_LOG_LEVEL_REQUEST_EVENT => {
+            let target = payload
+                .get("target")
+                .and_then(Value::as_str)
+                .map(str::trim)
+                .filter(|value| !value.is_empty())
+                .map(ToString::to_string)?;
+            let level = payload
+                .get("level")
+                .and_then(Value::as_str)
+                .map(str::trim)
+                .unwrap_or_default()
+                .to_string();
+            Some(SidecarCommand::SetLogLevel { target, level })
+        }
         TOOL_DETAILS_REFRESH_REQUEST_EVENT => {
             let refresh_id = payload
                 .get("refreshId")
@@ -410,6 +504,35 @@ pub(crate) fn parse_sidecar_command(raw: &str) -> Option<SidecarCommandEnvelope>
                 priority,
             })
         }
+        TOOL_DETAILS_NOW_REQUEST_EVENT => {
+            let tool_id = payload
+                .get("toolId")
+                .and_then(Value::as_str)
+                .map(str::trim)
+                .filter(|value| !value.is_empty())
+                .map(ToString::to_string)?;
+            let request_id = payload
+                .get("requestId")
+                .and_then(Value::as_str)
+                .map(str::trim)
+                .filter(|value| !value.is_empty())
+                .map(ToString::to_string)
+                .unwrap_or_else(|| format!("dnr_{}", Uuid::new_v4()));
+            Some(SidecarCommand::GetToolDetailsNow {
+                request_id,
+                tool_id,
+            })
+        }
+        TOOL_FOCUS_REQUEST_EVENT => payload
+            .get("toolId")
+            .and_then(Value::as_str)
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .map(|tool_id| SidecarCommand::FocusTool {
+                tool_id: tool_id.to_string(),
+            }),
+        TOOL_UNFOCUS_REQUEST_EVENT => Some(SidecarCommand::UnfocusTool),
+        APP_DISCONNECT_EVENT => Some(SidecarCommand::AppDisconnect),
         TOOL_PROCESS_CONTROL_REQUEST_EVENT => {
             let tool_id = payload
                 .get("toolId")
@@ -429,6 +552,39 @@ pub(crate) fn parse_sidecar_command(raw: &str) -> Option<SidecarCommandEnvelope>
                 })?;
             Some(SidecarCommand::ControlToolProcess { tool_id, action })
         }
+        TOOL_KILL_REQUEST_EVENT => payload
+            .get("toolId")
+            .and_then(Value::as_str)
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .map(|tool_id| SidecarCommand::KillTool {
+                tool_id: tool_id.to_string(),
+            }),
+        METRICS_HISTORY_REQUEST_EVENT => {
+            let limit = payload
+                .get("limit")
+                .and_then(Value::as_u64)
+                .filter(|value| *value > 0)
+                .map(|value| usize::try_from(value).unwrap_or(usize::MAX));
+            Some(SidecarCommand::MetricsHistoryRequest { limit })
+        }
+        SIDECAR_DIAGNOSTICS_REQUEST_EVENT => Some(SidecarCommand::DiagnosticsRequest),
+        TOOL_LOGS_REQUEST_EVENT => {
+            let tool_id = payload
+                .get("toolId")
+                .and_then(Value::as_str)
+                .map(str::trim)
+                .filter(|value| !value.is_empty())
+                .map(ToString::to_string)?;
+            let lines = payload
+                .get("lines")
+                .and_then(Value::as_u64)
+                .filter(|value| *value > 0)
+                .map(|value| usize::try_from(value).unwrap_or(usize::MAX))
+                .unwrap_or(DEFAULT_TOOL_LOG_LINES)
+                .min(MAX_TOOL_LOG_LINES);
+            Some(SidecarCommand::ToolLogsRequest { tool_id, lines })
+        }
         CONTROLLER_REBIND_REQUEST_EVENT => payload
             .get("deviceId")
             .and_then(Value::as_str)
@@ -653,37 +809,53 @@ pub(crate) fn parse_sidecar_command(raw: &str) -> Option<SidecarCommandEnvelope>
         command,
         source_client_type,
         source_device_id,
+        ack_required,
     })
 }
 
-/// 构造命令回执所需的 action/toolId 字段。
-pub(crate) fn command_feedback_parts(command: &SidecarCommand) -> (&'static str, String) {
-    match command {
+/// 按命令类型构造回执骨架（动作 + 目标），结果由调用方补全。
+pub(crate) fn command_feedback_payload(command: &SidecarCommand) -> CommandFeedbackPayload {
+    let (action, target) = match command {
         SidecarCommand::Refresh => ("refresh", String::new()),
         SidecarCommand::ConnectTool { tool_id } => ("connect", tool_id.clone()),
         SidecarCommand::DisconnectTool { tool_id } => ("disconnect", tool_id.clone()),
         SidecarCommand::ResetToolWhitelist => ("reset", String::new()),
+        SidecarCommand::SetToolLabel { tool_id, .. } => ("set-label", tool_id.clone()),
         SidecarCommand::RefreshToolDetails { tool_id, .. } => {
             ("refresh-details", tool_id.clone().unwrap_or_default())
         }
+        SidecarCommand::GetToolDetailsNow { tool_id, .. } => ("details-now", tool_id.clone()),
+        SidecarCommand::FocusTool { tool_id } => ("focus", tool_id.clone()),
+        SidecarCommand::UnfocusTool => ("unfocus", String::new()),
+        SidecarCommand::AppDisconnect => ("app-disconnect", String::new()),
         SidecarCommand::ControlToolProcess { tool_id, action } => {
             (action.as_str(), tool_id.clone())
         }
+        SidecarCommand::KillTool { tool_id } => ("kill", tool_id.clone()),
+        SidecarCommand::MetricsHistoryRequest { .. } => ("metrics-history", String::new()),
+        SidecarCommand::DiagnosticsRequest => ("diagnostics", String::new()),
+        SidecarCommand::ToolLogsRequest { tool_id, .. } => ("tool-logs", tool_id.clone()),
         SidecarCommand::RebindController { device_id } => {
             ("rebind-controller", device_id.to_string())
         }
+        SidecarCommand::SetLogLevel { target, .. } => ("set-log-level", target.clone()),
         SidecarCommand::ToolChatRequest { tool_id, .. } => ("chat-request", tool_id.clone()),
         SidecarCommand::ToolChatCancel { tool_id, .. } => ("chat-cancel", tool_id.clone()),
         SidecarCommand::ToolReportFetchRequest { tool_id, .. } => ("report-fetch", tool_id.clone()),
         SidecarCommand::ToolMediaStageRequest { tool_id, .. } => ("media-stage", tool_id.clone()),
         SidecarCommand::ToolLaunchRequest { tool_name, .. } => ("launch", tool_name.clone()),
-    }
+    };
+    CommandFeedbackPayload::new(action, target)
 }
 
 /// 根据命令类型返回回执事件名。
 pub(crate) fn command_feedback_event(command: &SidecarCommand) -> &'static str {
     match command {
         SidecarCommand::ControlToolProcess { .. } => TOOL_PROCESS_CONTROL_UPDATED_EVENT,
+        SidecarCommand::KillTool { .. } => TOOL_KILL_UPDATED_EVENT,
+        SidecarCommand::GetToolDetailsNow { .. } => TOOL_DETAILS_NOW_RESULT_EVENT,
+        SidecarCommand::SetToolLabel { .. } => TOOL_LABEL_UPDATED_EVENT,
+        SidecarCommand::SetLogLevel { .. } => SIDECAR_LOG_LEVEL_UPDATED_EVENT,
         SidecarCommand::ToolChatRequest { .. } => TOOL_CHAT_FINISHED_EVENT,
         SidecarCommand::ToolChatCancel { .. } => TOOL_CHAT_FINISHED_EVENT,
         SidecarCommand::ToolReportFetchRequest { .. } => TOOL_REPORT_FETCH_FINISHED_EVENT,
@@ -695,7 +867,10 @@ pub(crate) fn command_feedback_event(command: &SidecarCommand) -> &'static str {
 
 #[cfg(test)]
 mod tests {
-    use super::{SidecarCommand, ToolProcessAction, parse_sidecar_command};
+    use super::{
+        SidecarCommand, ToolProcessAction, command_feedback_event, command_feedback_payload,
+        parse_sidecar_command,
+    };
     use yc_shared_protocol::ToolDetailsRefreshPriority;
 
     #[test]
@@ -716,6 +891,23 @@ mod tests {
         }
     }
 
+    #[test]
+    fn parse_connect_command_reads_ack_required() {
+        let raw = r#"{
+            "type":"tool_connect_request",
+            "eventId":"evt_1",
+            "ackRequired":true,
+            "payload":{"toolId":"codex_a"}
+        }"#;
+
+        let env = parse_sidecar_command(raw).expect("command should parse");
+        assert!(env.ack_required);
+        assert_eq!(env.event_id, "evt_1");
+        let plain = parse_sidecar_command(r#"{"type":"tools_refresh_request","payload":{}}"#)
+            .expect("command should parse");
+        assert!(!plain.ack_required);
+    }
+
     #[test]
     fn parse_rebind_command_falls_back_to_source_device_id() {
         let raw = r#"{
@@ -760,6 +952,72 @@ mod tests {
         }
     }
 
+    #[test]
+    fn command_feedback_payload_uses_action_and_target() {
+        let command = SidecarCommand::ConnectTool {
+            tool_id: "openclaw_xxx".to_string(),
+        };
+        let feedback = command_feedback_payload(&command);
+        assert_eq!(feedback.action, "connect");
+        assert_eq!(feedback.target, "openclaw_xxx");
+        assert!(!feedback.ok);
+
+        let feedback = command_feedback_payload(&SidecarCommand::ResetToolWhitelist);
+        assert_eq!(feedback.action, "reset");
+        assert_eq!(feedback.target, "");
+    }
+
+    #[test]
+    fn parse_tool_focus_and_unfocus_commands() {
+        let raw = r#"{
+            "type":"tool_focus_request",
+            "sourceClientType":"app",
+            "sourceDeviceId":"ios_source",
+            "payload":{"toolId":"openclaw_xxx"}
+        }"#;
+        let env = parse_sidecar_command(raw).expect("command should parse");
+        match env.command {
+            SidecarCommand::FocusTool { tool_id } => assert_eq!(tool_id, "openclaw_xxx"),
+            _ => panic!("unexpected command"),
+        }
+
+        let raw = r#"{"type":"tool_unfocus_request","payload":{}}"#;
+        let env = parse_sidecar_command(raw).expect("command should parse");
+        assert!(matches!(env.command, SidecarCommand::UnfocusTool));
+
+        let raw = r#"{"type":"tool_focus_request","payload":{"toolId":" "}}"#;
+        assert!(parse_sidecar_command(raw).is_none());
+
+        let raw = r#"{"type":"app_disconnect","sourceClientType":"app","sourceDeviceId":"ios_a"}"#;
+        let env = parse_sidecar_command(raw).expect("command should parse");
+        assert!(matches!(env.command, SidecarCommand::AppDisconnect));
+        assert_eq!(env.source_device_id, "ios_a");
+    }
+
+    #[test]
+    fn parse_tool_details_now_command_keeps_request_id() {
+        let raw = r#"{
+            "type":"tool_details_now_request",
+            "sourceClientType":"app",
+            "sourceDeviceId":"ios_source",
+            "payload":{"toolId":"openclaw_xxx","requestId":"dnr_app_1"}
+        }"#;
+        let env = parse_sidecar_command(raw).expect("command should parse");
+        match env.command {
+            SidecarCommand::GetToolDetailsNow {
+                request_id,
+                tool_id,
+            } => {
+                assert_eq!(request_id, "dnr_app_1");
+                assert_eq!(tool_id, "openclaw_xxx");
+            }
+            _ => panic!("unexpected command"),
+        }
+
+        let raw = r#"{"type":"tool_details_now_request","payload":{}}"#;
+        assert!(parse_sidecar_command(raw).is_none());
+    }
+
     #[test]
     fn parse_tool_process_control_command_restart() {
         let raw = r#"{
@@ -795,6 +1053,26 @@ mod tests {
         }
     }
 
+    #[test]
+    fn parse_tool_label_set_command() {
+        let raw = r#"{
+            "type":"tool_label_set_request",
+            "sourceClientType":"app",
+            "sourceDeviceId":"ios_source",
+            "payload":{"toolId":"codex_abcd","label":" Prod deploy agent "}
+        }"#;
+
+        let env = parse_sidecar_command(raw).expect("command should parse");
+        assert_eq!(command_feedback_event(&env.command), "tool_label_updated");
+        match env.command {
+            SidecarCommand::SetToolLabel { tool_id, label } => {
+                assert_eq!(tool_id, "codex_abcd");
+                assert_eq!(label, "Prod deploy agent");
+            }
+            _ => panic!("unexpected command"),
+        }
+    }
+
     #[test]
     fn parse_tool_chat_request_command() {
         let raw = r#"{
@@ -961,4 +1239,92 @@ mod tests {
             _ => panic!("unexpected command"),
         }
     }
+
+    #[test]
+    fn parse_log_level_request_requires_target() {
+        let raw = r#"{
+            "type":"sidecar_log_level_request",
+            "sourceClientType":"app",
+            "sourceDeviceId":"ios_source",
+            "payload":{"target":" yc_sidecar::tooling ","level":"debug"}
+        }"#;
+        let env = parse_sidecar_command(raw).expect("command should parse");
+        match &env.command {
+            SidecarCommand::SetLogLevel { target, level } => {
+                assert_eq!(target, "yc_sidecar::tooling");
+                assert_eq!(level, "debug");
+            }
+            _ => panic!("unexpected command"),
+        }
+        assert_eq!(
+            command_feedback_event(&env.command),
+            "sidecar_log_level_updated"
+        );
+
+        let missing_target = r#"{"type":"sidecar_log_level_request","payload":{"level":"debug"}}"#;
+        assert!(parse_sidecar_command(missing_target).is_none());
+    }
+
+    #[test]
+    fn parse_tool_kill_request_maps_to_kill_feedback() {
+        let raw = r#"{
+            "type":"tool_kill_request",
+            "sourceClientType":"app",
+            "sourceDeviceId":"ios_source",
+            "payload":{"toolId":" opencode_xxx "}
+        }"#;
+        let env = parse_sidecar_command(raw).expect("command should parse");
+        match &env.command {
+            SidecarCommand::KillTool { tool_id } => assert_eq!(tool_id, "opencode_xxx"),
+            _ => panic!("unexpected command"),
+        }
+        assert_eq!(command_feedback_event(&env.command), "tool_kill_updated");
+        let feedback = command_feedback_payload(&env.command);
+        assert_eq!(feedback.action, "kill");
+        assert_eq!(feedback.target, "opencode_xxx");
+
+        let missing_tool = r#"{"type":"tool_kill_request","payload":{"toolId":""}}"#;
+        assert!(parse_sidecar_command(missing_tool).is_none());
+    }
+
+    #[test]
+    fn parse_metrics_history_request_reads_optional_limit() {
+        let raw = r#"{"type":"metrics_history_request","payload":{"limit":30}}"#;
+        let env = parse_sidecar_command(raw).expect("command should parse");
+        assert!(matches!(
+            env.command,
+            SidecarCommand::MetricsHistoryRequest { limit: Some(30) }
+        ));
+
+        let raw = r#"{"type":"metrics_history_request","payload":{"limit":0}}"#;
+        let env = parse_sidecar_command(raw).expect("command should parse");
+        assert!(matches!(
+            env.command,
+            SidecarCommand::MetricsHistoryRequest { limit: None }
+        ));
+    }
+
+    #[test]
+    fn parse_tool_logs_request_defaults_and_caps_lines() {
+        let raw = r#"{"type":"tool_logs_request","payload":{"toolId":"openclaw_a"}}"#;
+        let env = parse_sidecar_command(raw).expect("command should parse");
+        match &env.command {
+            SidecarCommand::ToolLogsRequest { tool_id, lines } => {
+                assert_eq!(tool_id, "openclaw_a");
+                assert_eq!(*lines, 100);
+            }
+            _ => panic!("unexpected command"),
+        }
+
+        let raw = r#"{"type":"tool_logs_request","payload":{"toolId":"openclaw_a","lines":9999}}"#;
+        let env = parse_sidecar_command(raw).expect("command should parse");
+        assert!(matches!(
+            env.command,
+            SidecarCommand::ToolLogsRequest { lines: 500, .. }
+        ));
+        assert_eq!(command_feedback_payload(&env.command).action, "tool-logs");
+
+        let missing_tool = r#"{"type":"tool_logs_request","payload":{"lines":10}}"#;
+        assert!(parse_sidecar_command(missing_tool).is_none());
+    }
 }
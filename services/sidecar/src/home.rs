@@ -0,0 +1,14 @@
+//! 用户目录解析：复用 `yc_shared_protocol::BaseDirs`（与 relay 同一套解析顺序），
+//! 并给出 sidecar 本地配置目录。
+
+use std::path::PathBuf;
+
+pub(crate) use yc_shared_protocol::BaseDirs;
+
+/// sidecar 本地配置目录：`<配置根目录>/yourconnector/sidecar`，无法解析时返回 None。
+pub(crate) fn sidecar_config_dir() -> Option<PathBuf> {
+    BaseDirs::from_env()
+        .config_dir()
+        .ok()
+        .map(|dir| dir.join("yourconnector").join("sidecar"))
+}
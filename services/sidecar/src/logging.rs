@@ -1,13 +1,15 @@
 //! 日志系统模块职责：
-//! 1. 初始化 stdout + 文件双通道 tracing 日志。
+//! 1. 初始化 stdout + 文件双通道 tracing 日志（`LOG_FORMAT=json` 时两路均输出 JSON 行）。
 //! 2. 将运行日志按天落在 `logs/raw` 目录。
 //! 3. 将历史日期日志自动归档到 `logs/archive/<YYYY-MM-DD>.7z`。
+//! 4. 通过可重载过滤器支持运行时按模块调整日志级别（无需重启）。
 
 use std::{
     collections::BTreeMap,
-    fs,
+    fmt, fs,
     io::ErrorKind,
     path::{Path, PathBuf},
+    sync::{Mutex, OnceLock},
     time::Duration,
 };
 
@@ -18,8 +20,11 @@ use tokio::{task::JoinHandle, time::MissedTickBehavior};
 use tracing::warn;
 use tracing_appender::non_blocking::WorkerGuard;
 use tracing_subscriber::{
-    EnvFilter, Layer, filter::LevelFilter, layer::SubscriberExt, util::SubscriberInitExt,
+    EnvFilter, Layer, filter::LevelFilter, layer::SubscriberExt, reload, util::SubscriberInitExt,
 };
+use yc_shared_protocol::{LogFormat, json_log_layer};
+
+use crate::profile::SidecarProfile;
 
 /// 默认日志根目录（相对当前工作目录）。
 const DEFAULT_LOG_DIR: &str = "logs";
@@ -35,8 +40,11 @@ const ARCHIVE_LOCK_DIR_NAME: &str = ".archive-lock";
 const DEFAULT_ARCHIVE_INTERVAL_SEC: u64 = 3600;
 /// 文件日志级别环境变量（独立于 `RUST_LOG`）。
 const FILE_LOG_LEVEL_ENV: &str = "YC_FILE_LOG_LEVEL";
-/// stdout 默认日志过滤（人类可读摘要）。
-const DEFAULT_STDOUT_FILTER: &str = "info";
+/// 运行时日志级别覆盖的目标路径最大长度。
+const MAX_LOG_TARGET_LEN: usize = 128;
+
+/// 进程级运行时日志级别控制（日志初始化后设置）。
+static LOG_LEVEL_CONTROL: OnceLock<LogLevelControl> = OnceLock::new();
 
 /// 日志运行时守卫，防止 non-blocking writer 提前析构。
 pub(crate) struct LogRuntime {
@@ -60,25 +68,43 @@ pub(crate) fn init(service_name: &str) -> Result<LogRuntime> {
     let file_appender = tracing_appender::rolling::daily(&raw_dir, format!("{service_name}.log"));
     let (file_writer, file_guard) = tracing_appender::non_blocking(file_appender);
     let (stdout_writer, stdout_guard) = tracing_appender::non_blocking(std::io::stdout());
-    let stdout_filter = resolve_stdout_env_filter();
-    let file_filter = resolve_file_level_filter();
-
-    let stdout_layer = tracing_subscriber::fmt::layer()
-        .with_writer(stdout_writer)
-        .with_ansi(true)
-        .with_target(false)
-        .compact()
-        .with_filter(stdout_filter);
-    let file_layer = tracing_subscriber::fmt::layer()
-        .with_writer(file_writer)
-        .with_ansi(false)
-        .with_target(true)
-        .with_filter(file_filter);
+    let stdout_base = resolve_stdout_filter_directives();
+    let file_base = resolve_file_level_filter().to_string();
+    let (stdout_filter, stdout_reload) = reload::Layer::new(EnvFilter::new(&stdout_base));
+    let (file_filter, file_reload) = reload::Layer::new(EnvFilter::new(&file_base));
+
+    let (stdout_layer, file_layer) = match LogFormat::from_env() {
+        LogFormat::Text => (
+            tracing_subscriber::fmt::layer()
+                .with_writer(stdout_writer)
+                .with_ansi(true)
+                .with_target(false)
+                .compact()
+                .with_filter(stdout_filter)
+                .boxed(),
+            tracing_subscriber::fmt::layer()
+                .with_writer(file_writer)
+                .with_ansi(false)
+                .with_target(true)
+                .with_filter(file_filter)
+                .boxed(),
+        ),
+        LogFormat::Json => (
+            json_log_layer(stdout_writer)
+                .with_filter(stdout_filter)
+                .boxed(),
+            json_log_layer(file_writer).with_filter(file_filter).boxed(),
+        ),
+    };
 
     tracing_subscriber::registry()
         .with(stdout_layer)
         .with(file_layer)
         .init();
+    let _ = LOG_LEVEL_CONTROL.set(LogLevelControl::new(vec![
+        ReloadableFilter::new(stdout_base, stdout_reload),
+        ReloadableFilter::new(file_base, file_reload),
+    ]));
 
     let archiver = spawn_archive_task(root_dir);
     Ok(LogRuntime {
@@ -88,9 +114,12 @@ pub(crate) fn init(service_name: &str) -> Result<LogRuntime> {
     })
 }
 
-/// 解析 stdout 日志过滤规则：优先 `RUST_LOG`，回退默认摘要级别。
-fn resolve_stdout_env_filter() -> EnvFilter {
-    EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(DEFAULT_STDOUT_FILTER))
+/// 解析 stdout 日志过滤规则：优先合法的 `RUST_LOG`，回退当前 profile 的默认级别。
+fn resolve_stdout_filter_directives() -> String {
+    std::env::var(EnvFilter::DEFAULT_ENV)
+        .ok()
+        .filter(|raw| EnvFilter::try_new(raw).is_ok())
+        .unwrap_or_else(|| SidecarProfile::from_env().stdout_log_filter().to_string())
 }
 
 /// 解析文件日志级别；默认保留 `debug` 级别，确保日志文件可完整回放。
@@ -101,6 +130,145 @@ fn resolve_file_level_filter() -> LevelFilter {
         .unwrap_or(LevelFilter::DEBUG)
 }
 
+/// 返回运行时日志级别控制；日志系统未初始化时为 None。
+pub(crate) fn log_level_control() -> Option<&'static LogLevelControl> {
+    LOG_LEVEL_CONTROL.get()
+}
+
+/// 运行时日志级别调整错误。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum LogLevelError {
+    /// 目标不是合法的模块路径。
+    InvalidTarget(String),
+    /// 级别不是 trace/debug/info/warn/error/off/reset 之一。
+    InvalidLevel(String),
+    /// 过滤器重载失败。
+    Reload(String),
+}
+
+impl fmt::Display for LogLevelError {
+    /// 输出可直接回执给控制端的错误说明。
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidTarget(target) => write!(f, "日志目标无效：{target}（需为模块路径）"),
+            Self::InvalidLevel(level) => write!(
+                f,
+                "日志级别无效：{level}（可选 trace/debug/info/warn/error/off/reset）"
+            ),
+            Self::Reload(err) => write!(f, "日志过滤器重载失败：{err}"),
+        }
+    }
+}
+
+/// 单路可重载过滤器：基础规则 + 重载入口。
+struct ReloadableFilter {
+    /// 启动时的基础过滤规则。
+    base: String,
+    /// 替换当前过滤器。
+    reload: Box<dyn Fn(EnvFilter) -> Result<(), reload::Error> + Send + Sync>,
+}
+
+impl ReloadableFilter {
+    /// 以基础规则与 reload 句柄构造。
+    fn new<S: 'static>(base: impl Into<String>, handle: reload::Handle<EnvFilter, S>) -> Self {
+        Self {
+            base: base.into(),
+            reload: Box::new(move |filter| handle.reload(filter)),
+        }
+    }
+}
+
+/// 运行时日志级别控制：各路过滤器共用同一组按模块的级别覆盖。
+pub(crate) struct LogLevelControl {
+    /// 需要同步重载的过滤器（stdout、文件）。
+    channels: Vec<ReloadableFilter>,
+    /// 当前生效的模块级别覆盖（target -> level）。
+    overrides: Mutex<BTreeMap<String, LevelFilter>>,
+}
+
+impl LogLevelControl {
+    /// 以给定过滤器集合构造，初始无覆盖。
+    fn new(channels: Vec<ReloadableFilter>) -> Self {
+        Self {
+            channels,
+            overrides: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    /// 设置（或以 `reset` 清除）某个模块的日志级别并立即重载；返回覆盖是否发生变更。
+    /// 所有过滤器重载成功后才记录新的覆盖，失败时保持原覆盖不变。
+    pub(crate) fn set(&self, target: &str, level: &str) -> Result<bool, LogLevelError> {
+        let target = validate_log_target(target)?;
+        let level = parse_log_level(level)?;
+
+        let mut overrides = self
+            .overrides
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        if overrides.get(&target).copied() == level {
+            return Ok(false);
+        }
+        let mut next = overrides.clone();
+        match level {
+            Some(level) => next.insert(target, level),
+            None => next.remove(&target),
+        };
+
+        let filters = self
+            .channels
+            .iter()
+            .map(|channel| EnvFilter::try_new(filter_directives(&channel.base, &next)))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|err| LogLevelError::Reload(err.to_string()))?;
+        for (channel, filter) in self.channels.iter().zip(filters) {
+            (channel.reload)(filter).map_err(|err| LogLevelError::Reload(err.to_string()))?;
+        }
+        *overrides = next;
+        Ok(true)
+    }
+}
+
+/// 拼接过滤规则：基础规则后追加各模块级别覆盖。
+fn filter_directives(base: &str, overrides: &BTreeMap<String, LevelFilter>) -> String {
+    let mut directives = base.to_string();
+    for (target, level) in overrides {
+        directives.push_str(&format!(",{target}={level}"));
+    }
+    directives
+}
+
+/// 校验日志目标：由 `::` 分隔的模块路径，每段仅含字母、数字与下划线（允许 `r#` 原始标识符前缀）。
+fn validate_log_target(raw: &str) -> Result<String, LogLevelError> {
+    let target = raw.trim();
+    let valid = !target.is_empty()
+        && target.len() <= MAX_LOG_TARGET_LEN
+        && target.split("::").all(|segment| {
+            let ident = segment.strip_prefix("r#").unwrap_or(segment);
+            !ident.is_empty()
+                && ident
+                    .chars()
+                    .all(|ch| ch.is_ascii_alphanumeric() || ch == '_')
+        });
+    if !valid {
+        return Err(LogLevelError::InvalidTarget(raw.to_string()));
+    }
+    Ok(target.to_string())
+}
+
+/// 解析日志级别；`reset`/`default` 表示清除覆盖（返回 None）。
+fn parse_log_level(raw: &str) -> Result<Option<LevelFilter>, LogLevelError> {
+    match raw.trim().to_ascii_lowercase().as_str() {
+        "trace" => Ok(Some(LevelFilter::TRACE)),
+        "debug" => Ok(Some(LevelFilter::DEBUG)),
+        "info" => Ok(Some(LevelFilter::INFO)),
+        "warn" => Ok(Some(LevelFilter::WARN)),
+        "error" => Ok(Some(LevelFilter::ERROR)),
+        "off" => Ok(Some(LevelFilter::OFF)),
+        "reset" | "default" => Ok(None),
+        _ => Err(LogLevelError::InvalidLevel(raw.to_string())),
+    }
+}
+
 /// 启动后台归档任务，定期将历史日志打包为 `.7z`。
 fn spawn_archive_task(root_dir: PathBuf) -> JoinHandle<()> {
     let interval = archive_interval();
@@ -262,3 +430,101 @@ impl Drop for ArchiveLockGuard {
         let _ = fs::remove_dir(&self.lock_dir);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        fmt,
+        sync::{Arc, Mutex},
+    };
+
+    use tracing::{
+        Event, Subscriber,
+        field::{Field, Visit},
+    };
+    use tracing_subscriber::{EnvFilter, Layer, layer::Context, layer::SubscriberExt, reload};
+
+    use super::{LogLevelControl, LogLevelError, ReloadableFilter};
+
+    const WORKER_TARGET: &str = "yc_sidecar::session::r#loop::details_worker";
+
+    /// 记录经过滤后实际输出的日志消息。
+    #[derive(Clone, Default)]
+    struct CaptureLayer(Arc<Mutex<Vec<String>>>);
+
+    /// 提取 `message` 字段。
+    struct MessageVisitor(String);
+
+    impl Visit for MessageVisitor {
+        fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+            if field.name() == "message" {
+                self.0 = format!("{value:?}");
+            }
+        }
+    }
+
+    impl<S: Subscriber> Layer<S> for CaptureLayer {
+        fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+            let mut visitor = MessageVisitor(String::new());
+            event.record(&mut visitor);
+            self.0.lock().unwrap().push(visitor.0);
+        }
+    }
+
+    #[test]
+    fn level_change_applies_to_subsequent_events() {
+        let capture = CaptureLayer::default();
+        let (filter, handle) = reload::Layer::new(EnvFilter::new("info"));
+        let subscriber = tracing_subscriber::registry().with(capture.clone().with_filter(filter));
+        let control = LogLevelControl::new(vec![ReloadableFilter::new("info", handle)]);
+
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::debug!(target: WORKER_TARGET, "before");
+            assert_eq!(control.set(WORKER_TARGET, "debug"), Ok(true));
+            assert_eq!(control.set(WORKER_TARGET, "DEBUG"), Ok(false));
+            tracing::debug!(target: WORKER_TARGET, "enabled");
+            tracing::debug!(target: "yc_sidecar::other", "other module");
+            assert_eq!(control.set(WORKER_TARGET, "reset"), Ok(true));
+            tracing::debug!(target: WORKER_TARGET, "after reset");
+            tracing::info!(target: WORKER_TARGET, "info");
+        });
+
+        assert_eq!(*capture.0.lock().unwrap(), vec!["enabled", "info"]);
+    }
+
+    #[test]
+    fn invalid_target_or_level_is_rejected() {
+        let (_filter, handle) =
+            reload::Layer::<EnvFilter, tracing_subscriber::Registry>::new(EnvFilter::new("info"));
+        let control = LogLevelControl::new(vec![ReloadableFilter::new("info", handle)]);
+        assert!(matches!(
+            control.set("yc_sidecar=debug", "debug"),
+            Err(LogLevelError::InvalidTarget(_))
+        ));
+        assert!(matches!(
+            control.set("", "debug"),
+            Err(LogLevelError::InvalidTarget(_))
+        ));
+        assert!(matches!(
+            control.set("yc_sidecar", "verbose"),
+            Err(LogLevelError::InvalidLevel(_))
+        ));
+    }
+
+    #[test]
+    fn failed_reload_keeps_previous_overrides() {
+        let (filter, handle) =
+            reload::Layer::<EnvFilter, tracing_subscriber::Registry>::new(EnvFilter::new("info"));
+        drop(filter);
+        let control = LogLevelControl::new(vec![ReloadableFilter::new("info", handle)]);
+        assert!(matches!(
+            control.set(WORKER_TARGET, "debug"),
+            Err(LogLevelError::Reload(_))
+        ));
+        assert!(control.overrides.lock().unwrap().is_empty());
+        assert!(matches!(
+            control.set(WORKER_TARGET, "debug"),
+            Err(LogLevelError::Reload(_))
+        ));
+    }
+}
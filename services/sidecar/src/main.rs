@@ -5,13 +5,16 @@
 
 use anyhow::Result;
 use axum::{Router, routing::get};
-use tracing::{error, info};
+use tracing::{error, info, warn};
 
 mod cli;
 mod config;
+mod config_check;
 mod control;
+mod home;
 mod logging;
 mod pairing;
+mod profile;
 mod runtime;
 mod session;
 mod stores;
@@ -21,13 +24,13 @@ use config::Config;
 
 pub(crate) use runtime::{ProcInfo, fallback_tools_or_empty};
 pub(crate) use tooling::{
-    build_claude_code_tool_id, build_codex_tool_id, build_openclaw_tool_id, build_opencode_tool_id,
-    bytes_to_gb, bytes_to_mb, collect_opencode_session_state, detect_openclaw_mode,
-    detect_opencode_mode, evaluate_openclaw_connection, evaluate_opencode_connection,
-    first_non_empty, is_claude_code_candidate_command, is_codex_candidate_command,
-    is_openclaw_candidate_command, is_opencode_candidate_command, is_opencode_wrapper_command,
-    normalize_path, normalize_probe_host, option_non_empty, parse_cli_flag_value,
-    parse_serve_address, pick_runtime_pid, round2,
+    build_claude_code_tool_id, build_codex_tool_id, build_custom_tool_id, build_openclaw_tool_id,
+    build_opencode_tool_id, bytes_to_gb, bytes_to_mb, collect_opencode_session_state,
+    detect_openclaw_mode, detect_opencode_mode, evaluate_openclaw_connection,
+    evaluate_opencode_connection, first_non_empty, is_claude_code_candidate_command,
+    is_codex_candidate_command, is_openclaw_candidate_command, is_opencode_candidate_command,
+    is_opencode_wrapper_command, normalize_path, normalize_probe_host, option_non_empty,
+    parse_cli_flag_value, parse_serve_address, pick_runtime_pid, round2,
 };
 
 /// Sidecar 入口：初始化日志、启动 health server、进入 relay 会话循环。
@@ -40,8 +43,43 @@ async fn main() -> Result<()> {
     }
 
     let _log_runtime = logging::init("sidecar")?;
+    match home::BaseDirs::from_env().config_dir() {
+        Ok(dir) => info!("sidecar base dir resolved config_dir={}", dir.display()),
+        Err(err) => warn!("sidecar base dir unresolved, local state will not persist: {err}"),
+    }
 
+    let problems = Config::validate();
+    if !problems.is_empty() {
+        error!("sidecar config invalid problems={}", problems.len());
+        eprintln!("yc-sidecar: invalid configuration, fix the following and restart:");
+        for problem in &problems {
+            eprintln!("  - {problem}");
+        }
+        std::process::exit(2);
+    }
     let cfg = Config::from_env()?;
+    info!(
+        "sidecar profile={} whitelist_prune={} grace_sec={} controller_bind_policy={}",
+        cfg.profile.as_str(),
+        cfg.whitelist_prune.as_str(),
+        cfg.whitelist_prune_grace.as_secs(),
+        cfg.controller_bind_policy.as_str()
+    );
+    if let Some(adaptive) = cfg.metrics_adaptive {
+        info!(
+            "sidecar metrics interval adaptive base_sec={} min_sec={} max_sec={} threshold={}",
+            cfg.metrics_interval.as_secs(),
+            adaptive.min.as_secs(),
+            adaptive.max.as_secs(),
+            adaptive.threshold_percent
+        );
+    }
+    if cfg.metrics_smoothing_alpha > 0.0 {
+        info!(
+            "sidecar metrics smoothing enabled alpha={}",
+            cfg.metrics_smoothing_alpha
+        );
+    }
     info!(
         "sidecar identity ready system_id={} device_id={} host_name={} pairing_code={}",
         cfg.system_id,
@@ -0,0 +1,163 @@
+//! sidecar 运行 profile（dev/prod）：按 `SIDECAR_PROFILE` 选择一组默认值，
+//! 显式环境变量始终覆盖 profile 默认值；未设置或无法识别时按 prod 处理。
+
+use std::time::Duration;
+
+use crate::{
+    config::ALLOW_INSECURE_WS_ENV, tooling::core::scheduler::DEFAULT_DETAILS_INTERVAL_SEC,
+};
+
+/// profile 选择环境变量。
+pub(crate) const SIDECAR_PROFILE_ENV: &str = "SIDECAR_PROFILE";
+
+/// sidecar 运行 profile。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub(crate) enum SidecarProfile {
+    /// 本地开发：详细日志、更短采集周期、允许不安全 ws（仍受构建渠道限制）。
+    Dev,
+    /// 生产（默认）：摘要日志、常规周期、仅允许 wss。
+    #[default]
+    Prod,
+}
+
+impl SidecarProfile {
+    /// 解析 profile 文本（大小写不敏感）。
+    fn parse(raw: &str) -> Option<Self> {
+        match raw.trim().to_ascii_lowercase().as_str() {
+            "dev" | "development" => Some(Self::Dev),
+            "prod" | "production" => Some(Self::Prod),
+            _ => None,
+        }
+    }
+
+    /// 读取当前进程的 profile。
+    pub(crate) fn from_env() -> Self {
+        std::env::var(SIDECAR_PROFILE_ENV)
+            .ok()
+            .and_then(|raw| Self::parse(&raw))
+            .unwrap_or_default()
+    }
+
+    /// profile 名称。
+    pub(crate) fn as_str(self) -> &'static str {
+        match self {
+            Self::Dev => "dev",
+            Self::Prod => "prod",
+        }
+    }
+
+    /// 未设置 `RUST_LOG` 时的 stdout 日志过滤。
+    pub(crate) fn stdout_log_filter(self) -> &'static str {
+        match self {
+            Self::Dev => "debug",
+            Self::Prod => "info",
+        }
+    }
+}
+
+/// profile 默认值叠加显式环境变量后的结果。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ProfileSettings {
+    /// 生效的 profile。
+    pub(crate) profile: SidecarProfile,
+    /// 心跳推送周期。
+    pub(crate) heartbeat_interval: Duration,
+    /// 指标快照推送周期。
+    pub(crate) metrics_interval: Duration,
+    /// 工具详情补采周期。
+    pub(crate) details_interval: Duration,
+    /// 是否请求放开不安全 ws。
+    pub(crate) allow_insecure_ws: bool,
+}
+
+impl ProfileSettings {
+    /// 从当前进程环境解析。
+    pub(crate) fn from_env() -> Self {
+        Self::resolve(|key| std::env::var(key).ok())
+    }
+
+    /// 按给定环境读取函数解析：先取 profile 默认值，再由显式变量覆盖。
+    pub(crate) fn resolve<F>(lookup: F) -> Self
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        let profile = lookup(SIDECAR_PROFILE_ENV)
+            .and_then(|raw| SidecarProfile::parse(&raw))
+            .unwrap_or_default();
+        let (heartbeat_sec, metrics_sec, details_sec, allow_insecure_ws) = match profile {
+            SidecarProfile::Dev => (3, 5, 15, true),
+            SidecarProfile::Prod => (5, 10, DEFAULT_DETAILS_INTERVAL_SEC, false),
+        };
+        let secs = |key: &str, fallback: u64| {
+            Duration::from_secs(
+                lookup(key)
+                    .and_then(|raw| raw.trim().parse::<u64>().ok())
+                    .filter(|value| *value > 0)
+                    .unwrap_or(fallback),
+            )
+        };
+        let allow_insecure_ws = lookup(ALLOW_INSECURE_WS_ENV)
+            .and_then(|raw| match raw.trim().to_ascii_lowercase().as_str() {
+                "1" | "true" | "yes" | "y" | "on" => Some(true),
+                "0" | "false" | "no" | "n" | "off" => Some(false),
+                _ => None,
+            })
+            .unwrap_or(allow_insecure_ws);
+
+        Self {
+            profile,
+            heartbeat_interval: secs("HEARTBEAT_INTERVAL_SEC", heartbeat_sec),
+            metrics_interval: secs("METRICS_INTERVAL_SEC", metrics_sec),
+            details_interval: secs("DETAILS_INTERVAL_SEC", details_sec),
+            allow_insecure_ws,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, time::Duration};
+
+    use super::{ProfileSettings, SidecarProfile};
+
+    fn resolve(vars: &[(&str, &str)]) -> ProfileSettings {
+        let vars = vars
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect::<HashMap<_, _>>();
+        ProfileSettings::resolve(|key| vars.get(key).cloned())
+    }
+
+    #[test]
+    fn profile_defaults_apply() {
+        let prod = resolve(&[]);
+        assert_eq!(prod.profile, SidecarProfile::Prod);
+        assert_eq!(prod.metrics_interval, Duration::from_secs(10));
+        assert!(!prod.allow_insecure_ws);
+
+        let dev = resolve(&[("SIDECAR_PROFILE", "Dev")]);
+        assert_eq!(dev.profile, SidecarProfile::Dev);
+        assert_eq!(dev.heartbeat_interval, Duration::from_secs(3));
+        assert_eq!(dev.details_interval, Duration::from_secs(15));
+        assert!(dev.allow_insecure_ws);
+        assert_eq!(dev.profile.stdout_log_filter(), "debug");
+
+        assert_eq!(
+            resolve(&[("SIDECAR_PROFILE", "staging")]).profile,
+            SidecarProfile::Prod
+        );
+    }
+
+    #[test]
+    fn explicit_env_overrides_profile_defaults() {
+        let dev = resolve(&[
+            ("SIDECAR_PROFILE", "dev"),
+            ("METRICS_INTERVAL_SEC", "30"),
+            ("YC_ALLOW_INSECURE_WS", "0"),
+            ("HEARTBEAT_INTERVAL_SEC", "invalid"),
+        ]);
+        assert_eq!(dev.metrics_interval, Duration::from_secs(30));
+        assert!(!dev.allow_insecure_ws);
+        assert_eq!(dev.heartbeat_interval, Duration::from_secs(3));
+    }
+}
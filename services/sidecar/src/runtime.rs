@@ -30,6 +30,7 @@ pub(crate) fn fallback_tools_or_empty(fallback_tool: bool) -> Vec<ToolRuntimePay
     vec![ToolRuntimePayload {
         tool_id: "tool_local".to_string(),
         name: "Local Tool".to_string(),
+        display_name: None,
         tool_class: "assistant".to_string(),
         category: "DEV_WORKER".to_string(),
         vendor: "yourconnector".to_string(),
@@ -55,5 +56,6 @@ pub(crate) fn fallback_tools_or_empty(fallback_tool: bool) -> Vec<ToolRuntimePay
         latest_tokens: Some(LatestTokensPayload::default()),
         model_usage: Vec::new(),
         collected_at: Some(now_rfc3339_nanos()),
+        extra: Default::default(),
     }]
 }
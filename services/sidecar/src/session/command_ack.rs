@@ -0,0 +1,55 @@
+//! 控制命令 ACK 职责：
+//! 1. 对 `ackRequired=true` 的控制命令回发 `ack`（引用原 eventId），relay 据此停止重发。
+//! 2. 记录近期已执行的需 ACK 命令，relay 超时重发的重复命令只回 ACK、不再重复执行。
+
+use std::collections::{HashSet, VecDeque};
+
+/// 记录的近期 eventId 数量上限。
+const RECENT_ACKED_CAPACITY: usize = 256;
+
+/// 近期已执行的需 ACK 命令。
+#[derive(Debug, Default)]
+pub(crate) struct CommandAckTracker {
+    /// eventId（旧 -> 新），用于淘汰。
+    order: VecDeque<String>,
+    /// eventId 集合，用于查重。
+    seen: HashSet<String>,
+}
+
+impl CommandAckTracker {
+    /// 记录 eventId，首次出现时返回 true；空 eventId 无法查重，始终视为首次。
+    pub(crate) fn first_seen(&mut self, event_id: &str) -> bool {
+        if event_id.is_empty() {
+            return true;
+        }
+        if !self.seen.insert(event_id.to_string()) {
+            return false;
+        }
+        self.order.push_back(event_id.to_string());
+        if self.order.len() > RECENT_ACKED_CAPACITY
+            && let Some(evicted) = self.order.pop_front()
+        {
+            self.seen.remove(&evicted);
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CommandAckTracker, RECENT_ACKED_CAPACITY};
+
+    #[test]
+    fn duplicate_event_ids_are_detected_within_capacity() {
+        let mut tracker = CommandAckTracker::default();
+        assert!(tracker.first_seen("evt_1"));
+        assert!(!tracker.first_seen("evt_1"));
+        assert!(tracker.first_seen(""));
+        assert!(tracker.first_seen(""));
+
+        for idx in 0..RECENT_ACKED_CAPACITY {
+            assert!(tracker.first_seen(&format!("evt_fill_{idx}")));
+        }
+        assert!(tracker.first_seen("evt_1"));
+    }
+}
@@ -0,0 +1,145 @@
+//! Sidecar 自诊断职责：
+//! 1. 记录详情 worker 最近一轮的排队/采集耗时与会话内重启次数。
+//! 2. 汇总详情刷新队列与 relay 发送队列的各键深度、累计丢弃，组装 `sidecar_diagnostics`，
+//!    用于判断 latest-wins 合并是否掩盖了积压。
+
+use std::time::Instant;
+
+use yc_shared_protocol::{
+    DetailsWorkerDiagnosticsPayload, QueueDiagnosticsPayload, SidecarDiagnosticsPayload,
+    SidecarDiagnosticsTrigger,
+};
+
+use crate::session::queue::QueueKeyStats;
+
+/// 周期诊断事件类型。
+pub(crate) const SIDECAR_DIAGNOSTICS_EVENT: &str = "sidecar_diagnostics";
+/// 默认诊断下发周期（秒）。
+pub(crate) const DEFAULT_DIAGNOSTICS_INTERVAL_SEC: u64 = 30;
+/// 详情刷新队列链路名。
+const DETAILS_PIPELINE: &str = "details";
+/// relay 发送队列链路名。
+const OUTBOUND_PIPELINE: &str = "outbound";
+
+/// 单会话内的诊断计数。
+#[derive(Debug)]
+pub(crate) struct SessionDiagnostics {
+    /// 会话开始时间。
+    started_at: Instant,
+    /// 详情 worker 最近一轮统计。
+    details_worker: DetailsWorkerDiagnosticsPayload,
+}
+
+impl SessionDiagnostics {
+    /// 以会话开始时间创建计数。
+    pub(crate) fn new(started_at: Instant) -> Self {
+        Self {
+            started_at,
+            details_worker: DetailsWorkerDiagnosticsPayload::default(),
+        }
+    }
+
+    /// 记录一次已下发的详情快照。
+    pub(crate) fn record_details_snapshot(
+        &mut self,
+        queue_wait_ms: u64,
+        collect_ms: u64,
+        dropped_refreshes: u32,
+    ) {
+        let worker = &mut self.details_worker;
+        worker.last_queue_wait_ms = Some(queue_wait_ms);
+        worker.last_collect_ms = Some(collect_ms);
+        worker.last_dropped_refreshes = dropped_refreshes;
+        worker.snapshots_sent = worker.snapshots_sent.saturating_add(1);
+    }
+
+    /// 记录一次详情 worker 重启。
+    pub(crate) fn record_details_worker_restart(&mut self) {
+        self.details_worker.restarts = self.details_worker.restarts.saturating_add(1);
+    }
+
+    /// 组装诊断载荷。
+    pub(crate) fn payload(
+        &self,
+        trigger: SidecarDiagnosticsTrigger,
+        details_queue: &[QueueKeyStats],
+        outbound_queue: &[QueueKeyStats],
+    ) -> SidecarDiagnosticsPayload {
+        let queues = details_queue
+            .iter()
+            .map(|stats| queue_payload(DETAILS_PIPELINE, stats))
+            .chain(
+                outbound_queue
+                    .iter()
+                    .map(|stats| queue_payload(OUTBOUND_PIPELINE, stats)),
+            )
+            .collect();
+        SidecarDiagnosticsPayload {
+            trigger,
+            session_uptime_sec: self.started_at.elapsed().as_secs(),
+            queues,
+            details_worker: self.details_worker.clone(),
+            reason: None,
+        }
+    }
+}
+
+/// 组装按需诊断请求被拒绝时的回执（不含队列与耗时数据）。
+pub(crate) fn diagnostics_denied_payload(reason: String) -> SidecarDiagnosticsPayload {
+    SidecarDiagnosticsPayload {
+        trigger: SidecarDiagnosticsTrigger::Request,
+        reason: Some(reason),
+        ..SidecarDiagnosticsPayload::default()
+    }
+}
+
+/// 把单键统计转换为协议载荷。
+fn queue_payload(pipeline: &str, stats: &QueueKeyStats) -> QueueDiagnosticsPayload {
+    QueueDiagnosticsPayload {
+        pipeline: pipeline.to_string(),
+        key: stats.key.as_str().to_string(),
+        depth: stats.depth,
+        dropped_total: stats.dropped_total,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, time::Instant};
+
+    use yc_shared_protocol::SidecarDiagnosticsTrigger;
+
+    use super::SessionDiagnostics;
+    use crate::session::queue::{QueueKey, QueuePolicy, QueueScheduler};
+
+    #[test]
+    fn payload_reports_depth_and_cumulative_drops_per_key() {
+        let mut details = QueueScheduler::new(
+            QueuePolicy::fifo(0),
+            HashMap::from([(QueueKey::ToolDetails, QueuePolicy::latest_wins())]),
+        );
+        for refresh in 0..3 {
+            details.enqueue(QueueKey::ToolDetails, refresh);
+        }
+        details.pop_next();
+        details.enqueue(QueueKey::ToolDetails, 3);
+
+        let mut diagnostics = SessionDiagnostics::new(Instant::now());
+        diagnostics.record_details_snapshot(12, 340, 2);
+        diagnostics.record_details_worker_restart();
+        let payload =
+            diagnostics.payload(SidecarDiagnosticsTrigger::Request, &details.stats(), &[]);
+
+        assert_eq!(payload.queues.len(), 1);
+        assert_eq!(payload.queues[0].pipeline, "details");
+        assert_eq!(payload.queues[0].key, "tool_details");
+        assert_eq!(payload.queues[0].depth, 1);
+        assert_eq!(payload.queues[0].dropped_total, 2);
+        assert_eq!(payload.details_worker.last_collect_ms, Some(340));
+        assert_eq!(payload.details_worker.snapshots_sent, 1);
+        assert_eq!(payload.details_worker.restarts, 1);
+        let json = serde_json::to_value(&payload).expect("payload json");
+        assert_eq!(json["trigger"], "request");
+        assert_eq!(json["queues"][0]["droppedTotal"], 2);
+    }
+}
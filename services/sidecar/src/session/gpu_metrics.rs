@@ -0,0 +1,203 @@
+//! GPU 指标采集职责：
+//! 1. 启用 `gpu-nvml` 特性的 Linux 构建在首次采集时动态加载 `libnvidia-ml.so.1` 并初始化 NVML。
+//! 2. 逐卡读取名称、利用率与显存；库缺失或初始化失败时返回空列表，单卡读取失败只跳过该卡，
+//!    都不影响整份指标快照。
+//! 3. 未启用特性或非 Linux 构建始终返回空列表。
+
+use yc_shared_protocol::GpuMetricsPayload;
+
+use crate::{bytes_to_mb, round2};
+
+/// 采集全部 GPU 指标；NVML 不可用时返回空列表。
+pub(crate) fn collect_gpu_metrics() -> Vec<GpuMetricsPayload> {
+    #[cfg(all(feature = "gpu-nvml", target_os = "linux"))]
+    {
+        nvml::collect()
+    }
+    #[cfg(not(all(feature = "gpu-nvml", target_os = "linux")))]
+    {
+        Vec::new()
+    }
+}
+
+/// 把 NVML 原始读数换算为协议载荷（显存字节转 MB）。
+#[cfg_attr(not(all(feature = "gpu-nvml", target_os = "linux")), allow(dead_code))]
+fn gpu_payload(
+    index: u32,
+    name: &str,
+    utilization_percent: u32,
+    memory_used_bytes: u64,
+    memory_total_bytes: u64,
+) -> GpuMetricsPayload {
+    GpuMetricsPayload {
+        index,
+        name: name.trim().to_string(),
+        utilization_percent: f64::from(utilization_percent.min(100)),
+        memory_used_mb: round2(bytes_to_mb(memory_used_bytes.min(memory_total_bytes))),
+        memory_total_mb: round2(bytes_to_mb(memory_total_bytes)),
+    }
+}
+
+#[cfg(all(feature = "gpu-nvml", target_os = "linux"))]
+mod nvml {
+    use std::{
+        ffi::{CStr, c_char, c_uint, c_ulonglong, c_void},
+        sync::OnceLock,
+    };
+
+    use libloading::Library;
+    use tracing::{debug, info};
+    use yc_shared_protocol::GpuMetricsPayload;
+
+    use super::gpu_payload;
+
+    /// NVIDIA 驱动随附的 NVML 动态库。
+    const NVML_LIBRARY: &str = "libnvidia-ml.so.1";
+    /// `NVML_SUCCESS`。
+    const NVML_SUCCESS: c_uint = 0;
+    /// `NVML_DEVICE_NAME_V2_BUFFER_SIZE`。
+    const NVML_DEVICE_NAME_BUFFER_SIZE: usize = 96;
+
+    /// `nvmlDevice_t`。
+    type NvmlDevice = *mut c_void;
+
+    /// `nvmlUtilization_t`。
+    #[repr(C)]
+    #[derive(Default)]
+    struct NvmlUtilization {
+        gpu: c_uint,
+        memory: c_uint,
+    }
+
+    /// `nvmlMemory_t`。
+    #[repr(C)]
+    #[derive(Default)]
+    struct NvmlMemory {
+        total: c_ulonglong,
+        free: c_ulonglong,
+        used: c_ulonglong,
+    }
+
+    /// 已初始化的 NVML 函数表。
+    struct Nvml {
+        device_get_count: unsafe extern "C" fn(*mut c_uint) -> c_uint,
+        device_get_handle_by_index: unsafe extern "C" fn(c_uint, *mut NvmlDevice) -> c_uint,
+        device_get_name: unsafe extern "C" fn(NvmlDevice, *mut c_char, c_uint) -> c_uint,
+        device_get_utilization_rates:
+            unsafe extern "C" fn(NvmlDevice, *mut NvmlUtilization) -> c_uint,
+        device_get_memory_info: unsafe extern "C" fn(NvmlDevice, *mut NvmlMemory) -> c_uint,
+        /// 持有动态库，保证上面的函数指针在进程生命周期内有效。
+        _library: Library,
+    }
+
+    /// 进程内只加载一次；加载失败缓存为 `None`，不在每个采集周期重试。
+    static NVML: OnceLock<Option<Nvml>> = OnceLock::new();
+
+    /// 读取 NVML 导出函数。
+    fn symbol<T: Copy>(library: &Library, name: &[u8]) -> Result<T, String> {
+        // SAFETY: 调用方声明的函数签名与 nvml.h 一致。
+        unsafe { library.get::<T>(name) }
+            .map(|symbol| *symbol)
+            .map_err(|err| err.to_string())
+    }
+
+    impl Nvml {
+        /// 加载动态库、解析函数并初始化 NVML。
+        fn load() -> Result<Self, String> {
+            // SAFETY: 加载系统驱动库，其初始化例程没有额外前置条件。
+            let library = unsafe { Library::new(NVML_LIBRARY) }.map_err(|err| err.to_string())?;
+            let init: unsafe extern "C" fn() -> c_uint = symbol(&library, b"nvmlInit_v2\0")?;
+            // SAFETY: `nvmlInit_v2` 无参数，可重复调用。
+            let code = unsafe { init() };
+            if code != NVML_SUCCESS {
+                return Err(format!("nvmlInit_v2 returned {code}"));
+            }
+            Ok(Self {
+                device_get_count: symbol(&library, b"nvmlDeviceGetCount_v2\0")?,
+                device_get_handle_by_index: symbol(&library, b"nvmlDeviceGetHandleByIndex_v2\0")?,
+                device_get_name: symbol(&library, b"nvmlDeviceGetName\0")?,
+                device_get_utilization_rates: symbol(&library, b"nvmlDeviceGetUtilizationRates\0")?,
+                device_get_memory_info: symbol(&library, b"nvmlDeviceGetMemoryInfo\0")?,
+                _library: library,
+            })
+        }
+
+        /// 读取全部设备；读取失败的设备被跳过。
+        fn devices(&self) -> Vec<GpuMetricsPayload> {
+            let mut count: c_uint = 0;
+            // SAFETY: 出参指向有效的栈上变量。
+            if unsafe { (self.device_get_count)(&mut count) } != NVML_SUCCESS {
+                return Vec::new();
+            }
+            (0..count).filter_map(|index| self.device(index)).collect()
+        }
+
+        /// 读取单个设备的名称、利用率与显存。
+        fn device(&self, index: c_uint) -> Option<GpuMetricsPayload> {
+            let mut handle: NvmlDevice = std::ptr::null_mut();
+            let mut name = [0u8; NVML_DEVICE_NAME_BUFFER_SIZE];
+            let mut utilization = NvmlUtilization::default();
+            let mut memory = NvmlMemory::default();
+            // SAFETY: 出参均指向本函数内的有效内存，名称缓冲区长度与传入值一致。
+            let ok = unsafe {
+                (self.device_get_handle_by_index)(index, &mut handle) == NVML_SUCCESS
+                    && (self.device_get_name)(
+                        handle,
+                        name.as_mut_ptr().cast::<c_char>(),
+                        NVML_DEVICE_NAME_BUFFER_SIZE as c_uint,
+                    ) == NVML_SUCCESS
+                    && (self.device_get_utilization_rates)(handle, &mut utilization) == NVML_SUCCESS
+                    && (self.device_get_memory_info)(handle, &mut memory) == NVML_SUCCESS
+            };
+            if !ok {
+                debug!("nvml device index={index} unreadable, skipped");
+                return None;
+            }
+            let name = CStr::from_bytes_until_nul(&name)
+                .map(|value| value.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            Some(gpu_payload(
+                index,
+                &name,
+                utilization.gpu,
+                memory.used,
+                memory.total,
+            ))
+        }
+    }
+
+    /// 首次调用时加载 NVML，之后复用；不可用时返回空列表。
+    pub(super) fn collect() -> Vec<GpuMetricsPayload> {
+        let nvml = NVML.get_or_init(|| match Nvml::load() {
+            Ok(nvml) => {
+                info!("nvml loaded, gpu metrics enabled");
+                Some(nvml)
+            }
+            Err(err) => {
+                info!("nvml unavailable, gpu metrics disabled: {err}");
+                None
+            }
+        });
+        nvml.as_ref().map(Nvml::devices).unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{collect_gpu_metrics, gpu_payload};
+
+    #[test]
+    fn raw_readings_convert_to_megabytes_and_disabled_build_reports_none() {
+        let payload = gpu_payload(1, " NVIDIA A10 ", 73, 3 << 30, 24 << 30);
+        assert_eq!(payload.index, 1);
+        assert_eq!(payload.name, "NVIDIA A10");
+        assert_eq!(payload.utilization_percent, 73.0);
+        assert_eq!(payload.memory_used_mb, 3072.0);
+        assert_eq!(payload.memory_total_mb, 24576.0);
+
+        // 未启用特性（或非 Linux）时恒为空列表，快照照常生成。
+        if cfg!(not(all(feature = "gpu-nvml", target_os = "linux"))) {
+            assert!(collect_gpu_metrics().is_empty());
+        }
+    }
+}
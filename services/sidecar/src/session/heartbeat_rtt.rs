@@ -0,0 +1,117 @@
+//! 心跳时延测量职责：
+//! 1. 每次心跳后发出一个带序号的 WebSocket Ping，relay 自动回 Pong，读端按序号算出往返时延。
+//! 2. Ping 经发送队列出站，测得的时延包含本端积压，能反映链路劣化而不只是网络 RTT。
+//! 3. 上一探测迟迟未回时以已等待时长作为下限上报，避免链路卡死时仍显示旧的低时延。
+
+use std::{
+    sync::{Arc, Mutex, MutexGuard},
+    time::{Duration, Instant},
+};
+
+use tokio_tungstenite::tungstenite::Message;
+
+/// 探测 Ping 载荷前缀，用于区分其它来源的 Pong。
+const RTT_PROBE_PREFIX: &[u8] = b"yc-rtt:";
+
+/// 探测状态。
+#[derive(Debug, Default)]
+struct RttState {
+    /// 下一次探测序号。
+    next_probe_id: u64,
+    /// 未回 Pong 的探测（序号、发出时间）；新探测覆盖旧探测。
+    pending: Option<(u64, Instant)>,
+    /// 最近一次测得的往返时延。
+    last_rtt: Option<Duration>,
+}
+
+/// 会话循环与读端共享的时延测量器。
+#[derive(Debug, Clone, Default)]
+pub(crate) struct HeartbeatRtt {
+    state: Arc<Mutex<RttState>>,
+}
+
+impl HeartbeatRtt {
+    /// 加锁读取状态（锁中毒时继续使用内部数据）。
+    fn lock(&self) -> MutexGuard<'_, RttState> {
+        self.state.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    /// 登记一次新探测并返回待发送的 Ping 帧。
+    pub(crate) fn start_probe(&self, now: Instant) -> Message {
+        let mut state = self.lock();
+        let probe_id = state.next_probe_id;
+        state.next_probe_id = probe_id.wrapping_add(1);
+        state.pending = Some((probe_id, now));
+        let mut payload = RTT_PROBE_PREFIX.to_vec();
+        payload.extend_from_slice(&probe_id.to_be_bytes());
+        Message::Ping(payload.into())
+    }
+
+    /// 处理 Pong；匹配当前探测时记录时延并返回 true。
+    pub(crate) fn on_pong(&self, payload: &[u8], now: Instant) -> bool {
+        let Some(id_bytes) = payload
+            .strip_prefix(RTT_PROBE_PREFIX)
+            .and_then(|rest| <[u8; 8]>::try_from(rest).ok())
+        else {
+            return false;
+        };
+        let probe_id = u64::from_be_bytes(id_bytes);
+        let mut state = self.lock();
+        match state.pending {
+            Some((pending_id, sent_at)) if pending_id == probe_id => {
+                state.last_rtt = Some(now.saturating_duration_since(sent_at));
+                state.pending = None;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// 当前应上报的时延（毫秒）；尚无测量时为 0。
+    pub(crate) fn latency_ms(&self, now: Instant) -> u64 {
+        let state = self.lock();
+        let waiting = state
+            .pending
+            .map(|(_, sent_at)| now.saturating_duration_since(sent_at))
+            .unwrap_or_default();
+        let latency = state.last_rtt.unwrap_or_default().max(waiting);
+        u64::try_from(latency.as_millis()).unwrap_or(u64::MAX)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use tokio_tungstenite::tungstenite::Message;
+
+    use super::HeartbeatRtt;
+
+    #[test]
+    fn pong_matching_latest_probe_sets_latency_and_stalls_raise_floor() {
+        let rtt = HeartbeatRtt::default();
+        let t0 = Instant::now();
+        assert_eq!(rtt.latency_ms(t0), 0);
+
+        let Message::Ping(first) = rtt.start_probe(t0) else {
+            panic!("expected ping frame");
+        };
+        assert!(!rtt.on_pong(b"relay-keepalive", t0 + Duration::from_millis(5)));
+        assert!(rtt.on_pong(&first, t0 + Duration::from_millis(42)));
+        assert_eq!(rtt.latency_ms(t0 + Duration::from_secs(1)), 42);
+        // 重复 Pong 不再改写时延。
+        assert!(!rtt.on_pong(&first, t0 + Duration::from_millis(900)));
+
+        let t1 = t0 + Duration::from_secs(10);
+        let Message::Ping(second) = rtt.start_probe(t1) else {
+            panic!("expected ping frame");
+        };
+        assert_ne!(first, second);
+        // 探测未回：以已等待时长为下限。
+        assert_eq!(rtt.latency_ms(t1 + Duration::from_millis(20)), 42);
+        assert_eq!(rtt.latency_ms(t1 + Duration::from_secs(3)), 3_000);
+        // 被新探测覆盖的旧 Pong 迟到后忽略。
+        rtt.start_probe(t1 + Duration::from_secs(5));
+        assert!(!rtt.on_pong(&second, t1 + Duration::from_secs(6)));
+    }
+}
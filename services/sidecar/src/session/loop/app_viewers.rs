@@ -0,0 +1,246 @@
+//! App 观看者跟踪职责：
+//! 1. 以 relay 下发的 `server_presence`（房间在线数）、`peer_joined`/`peer_left` 维护在线 App 连接，
+//!    App 命令与 `app_disconnect` 作为补充信号：发出命令即视为在看，主动断开提示后不再计入。
+//! 2. 尚未收到任何信号（如旧版 relay 不带 `clientCount`）时保守地视为有人观看，保持原有周期采集。
+//! 3. 在线观看者从有到无、从无到有时返回状态切换，供会话循环暂停/恢复周期详情与指标采集。
+//! 4. 跟踪状态随会话重建，relay 重连后以新的 `server_presence` 重新开始统计。
+
+use std::collections::{HashMap, HashSet};
+
+use serde_json::Value;
+
+/// relay 回推本连接在线状态的事件类型。
+const SERVER_PRESENCE_EVENT: &str = "server_presence";
+/// relay 推送的对端接入事件类型。
+const PEER_JOINED_EVENT: &str = "peer_joined";
+/// relay 推送的对端断开事件类型。
+const PEER_LEFT_EVENT: &str = "peer_left";
+/// App 连接端类型。
+const APP_CLIENT_TYPE: &str = "app";
+
+/// 影响观看者集合的信号。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) enum ViewerSignal {
+    /// 本连接建立时房间内的其他连接数（身份未知，按 App 计）。
+    Presence { others: usize },
+    /// App 连接接入。
+    Joined { device_id: String },
+    /// App 连接断开。
+    Left { device_id: String },
+    /// App 发出控制命令。
+    Command { device_id: String },
+    /// App 主动断开提示。
+    Disconnect { device_id: String },
+}
+
+/// 一次信号引起的观看状态切换。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum ViewerTransition {
+    /// 是否有人观看未变化。
+    Unchanged,
+    /// 最后一个观看者离开，周期采集应暂停。
+    Suspended,
+    /// 重新出现观看者，周期采集应恢复。
+    Resumed,
+}
+
+/// 当前会话已知的 App 观看者。
+#[derive(Debug, Default)]
+pub(super) struct AppViewers {
+    /// 各 App 设备的在线连接数（同设备可能并存多个连接）。
+    connections: HashMap<String, usize>,
+    /// 已发出 `app_disconnect`、但连接尚未断开的设备。
+    departed: HashSet<String>,
+    /// 接入时已在房间、身份未知的连接数。
+    anonymous: usize,
+    /// 是否已收到过任何信号；为 false 时无法判断是否有人观看。
+    tracking: bool,
+}
+
+impl AppViewers {
+    /// 应用一条信号，返回观看状态是否发生切换。
+    pub(super) fn apply(&mut self, signal: ViewerSignal) -> ViewerTransition {
+        let before = self.has_viewers();
+        match signal {
+            ViewerSignal::Presence { others } => {
+                self.connections.clear();
+                self.departed.clear();
+                self.anonymous = others;
+            }
+            ViewerSignal::Joined { device_id } => {
+                self.departed.remove(&device_id);
+                *self.connections.entry(device_id).or_default() += 1;
+            }
+            ViewerSignal::Left { device_id } => match self.connections.get_mut(&device_id) {
+                Some(count) if *count > 1 => *count -= 1,
+                Some(_) => {
+                    self.connections.remove(&device_id);
+                    self.departed.remove(&device_id);
+                }
+                None => self.anonymous = self.anonymous.saturating_sub(1),
+            },
+            ViewerSignal::Command { device_id } => {
+                self.departed.remove(&device_id);
+                if !self.connections.contains_key(&device_id) {
+                    // 身份未知的既有连接多半就是它，认领后不再重复计数。
+                    self.anonymous = self.anonymous.saturating_sub(1);
+                    self.connections.insert(device_id, 1);
+                }
+            }
+            ViewerSignal::Disconnect { device_id } => {
+                if !self.connections.contains_key(&device_id) {
+                    // 认领身份未知的既有连接并标记已离开，随后的断开只移除该条目，不再重复扣减。
+                    self.anonymous = self.anonymous.saturating_sub(1);
+                    self.connections.insert(device_id.clone(), 1);
+                }
+                self.departed.insert(device_id);
+            }
+        }
+        self.tracking = true;
+        match (before, self.has_viewers()) {
+            (true, false) => ViewerTransition::Suspended,
+            (false, true) => ViewerTransition::Resumed,
+            _ => ViewerTransition::Unchanged,
+        }
+    }
+
+    /// 是否可能有 App 在观看：未收到任何信号时保守地视为有。
+    pub(super) fn has_viewers(&self) -> bool {
+        !self.tracking || self.count() > 0
+    }
+
+    /// 当前计入的观看者数（身份未知的连接 + 未主动离开的设备）。
+    pub(super) fn count(&self) -> usize {
+        let known = self
+            .connections
+            .keys()
+            .filter(|device_id| !self.departed.contains(*device_id))
+            .count();
+        self.anonymous + known
+    }
+}
+
+/// 从 relay 下行事件解析观看者信号；非 App 对端与其他事件返回 None。
+pub(super) fn parse_viewer_signal(raw: &str) -> Option<ViewerSignal> {
+    let event: Value = serde_json::from_str(raw).ok()?;
+    let payload = event.get("payload")?;
+    match event.get("type").and_then(Value::as_str)? {
+        SERVER_PRESENCE_EVENT => {
+            let client_count = payload.get("clientCount").and_then(Value::as_u64)?;
+            let others = usize::try_from(client_count.saturating_sub(1)).unwrap_or(usize::MAX);
+            Some(ViewerSignal::Presence { others })
+        }
+        event_type @ (PEER_JOINED_EVENT | PEER_LEFT_EVENT) => {
+            if payload.get("clientType").and_then(Value::as_str) != Some(APP_CLIENT_TYPE) {
+                return None;
+            }
+            let device_id = payload
+                .get("deviceId")
+                .and_then(Value::as_str)
+                .map(str::trim)
+                .filter(|value| !value.is_empty())?
+                .to_string();
+            if event_type == PEER_JOINED_EVENT {
+                Some(ViewerSignal::Joined { device_id })
+            } else {
+                Some(ViewerSignal::Left { device_id })
+            }
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AppViewers, ViewerSignal, ViewerTransition, parse_viewer_signal};
+
+    /// 按设备构造接入信号。
+    fn joined(device_id: &str) -> ViewerSignal {
+        ViewerSignal::Joined {
+            device_id: device_id.to_string(),
+        }
+    }
+
+    /// 按设备构造断开信号。
+    fn left(device_id: &str) -> ViewerSignal {
+        ViewerSignal::Left {
+            device_id: device_id.to_string(),
+        }
+    }
+
+    #[test]
+    fn periodic_collection_suspends_without_viewers_and_resumes_on_join() {
+        let mut viewers = AppViewers::default();
+        assert!(viewers.has_viewers());
+
+        // sidecar 独自在房间：立即暂停。
+        assert_eq!(
+            viewers.apply(ViewerSignal::Presence { others: 0 }),
+            ViewerTransition::Suspended
+        );
+        assert_eq!(viewers.apply(joined("ios_a")), ViewerTransition::Resumed);
+        assert_eq!(viewers.apply(joined("ios_a")), ViewerTransition::Unchanged);
+        assert_eq!(viewers.apply(left("ios_a")), ViewerTransition::Unchanged);
+        assert_eq!(viewers.apply(left("ios_a")), ViewerTransition::Suspended);
+
+        // 主动断开提示先于连接断开到达：提示即暂停，真正断开不再重复切换。
+        assert_eq!(viewers.apply(joined("ios_b")), ViewerTransition::Resumed);
+        assert_eq!(
+            viewers.apply(ViewerSignal::Disconnect {
+                device_id: "ios_b".to_string()
+            }),
+            ViewerTransition::Suspended
+        );
+        assert_eq!(viewers.apply(left("ios_b")), ViewerTransition::Unchanged);
+        assert_eq!(viewers.count(), 0);
+
+        // 接入前已在房间的连接按身份未知计入，发出命令后认领，不重复计数。
+        let mut viewers = AppViewers::default();
+        assert_eq!(
+            viewers.apply(ViewerSignal::Presence { others: 1 }),
+            ViewerTransition::Unchanged
+        );
+        viewers.apply(ViewerSignal::Command {
+            device_id: "ios_c".to_string(),
+        });
+        assert_eq!(viewers.count(), 1);
+        assert_eq!(viewers.apply(left("ios_c")), ViewerTransition::Suspended);
+
+        // 身份未知的连接先发主动断开提示再断开：只扣减一次，另一条未知连接仍计入。
+        let mut viewers = AppViewers::default();
+        viewers.apply(ViewerSignal::Presence { others: 2 });
+        assert_eq!(
+            viewers.apply(ViewerSignal::Disconnect {
+                device_id: "ios_d".to_string()
+            }),
+            ViewerTransition::Unchanged
+        );
+        assert_eq!(viewers.count(), 1);
+        assert_eq!(viewers.apply(left("ios_d")), ViewerTransition::Unchanged);
+        assert_eq!(viewers.count(), 1);
+        assert_eq!(viewers.apply(left("ios_e")), ViewerTransition::Suspended);
+    }
+
+    #[test]
+    fn relay_presence_events_parse_into_app_viewer_signals() {
+        let raw = r#"{"type":"server_presence","payload":{"status":"connected","clientCount":3}}"#;
+        assert_eq!(
+            parse_viewer_signal(raw),
+            Some(ViewerSignal::Presence { others: 2 })
+        );
+        let raw = r#"{"type":"peer_joined","payload":{"clientType":"app","deviceId":"ios_a"}}"#;
+        assert_eq!(parse_viewer_signal(raw), Some(joined("ios_a")));
+        let raw = r#"{"type":"peer_left","payload":{"clientType":"app","deviceId":"ios_a"}}"#;
+        assert_eq!(parse_viewer_signal(raw), Some(left("ios_a")));
+
+        // 旧版 relay 不带 clientCount、sidecar 对端与其他事件都不影响跟踪。
+        let raw = r#"{"type":"server_presence","payload":{"status":"connected"}}"#;
+        assert_eq!(parse_viewer_signal(raw), None);
+        let raw = r#"{"type":"peer_joined","payload":{"clientType":"sidecar","deviceId":"sc"}}"#;
+        assert_eq!(parse_viewer_signal(raw), None);
+        assert_eq!(
+            parse_viewer_signal(r#"{"type":"heartbeat","payload":{}}"#),
+            None
+        );
+    }
+}
@@ -0,0 +1,75 @@
+//! 重连退避：指数增长（1s 起、15s 封顶）并叠加 full jitter，
+//! 避免 relay 恢复时所有 sidecar 在同一时刻集中重连。
+
+use std::time::Duration;
+
+use rand::Rng;
+
+/// 初始退避时长。
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+/// 退避时长上限。
+const MAX_BACKOFF: Duration = Duration::from_secs(15);
+
+/// 带 full jitter 的指数退避状态。
+#[derive(Debug, Clone)]
+pub(super) struct ReconnectBackoff {
+    /// 当前退避上界。
+    current: Duration,
+}
+
+impl Default for ReconnectBackoff {
+    /// 从初始退避开始。
+    fn default() -> Self {
+        Self {
+            current: INITIAL_BACKOFF,
+        }
+    }
+}
+
+impl ReconnectBackoff {
+    /// 会话成功后回到初始退避。
+    pub(super) fn reset(&mut self) {
+        self.current = INITIAL_BACKOFF;
+    }
+
+    /// 返回本次等待时长（`[0, 当前上界]` 内均匀随机），并把上界翻倍（封顶）。
+    pub(super) fn next_delay(&mut self) -> Duration {
+        let delay = full_jitter(self.current, &mut rand::rng());
+        self.current = (self.current * 2).min(MAX_BACKOFF);
+        delay
+    }
+}
+
+/// 在 `[0, upper]` 内按毫秒均匀取随机时长。
+fn full_jitter(upper: Duration, rng: &mut impl Rng) -> Duration {
+    let upper_ms = u64::try_from(upper.as_millis()).unwrap_or(u64::MAX);
+    Duration::from_millis(rng.random_range(0..=upper_ms))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{MAX_BACKOFF, ReconnectBackoff, full_jitter};
+
+    #[test]
+    fn delays_stay_within_doubling_capped_bound_and_reset() {
+        let mut backoff = ReconnectBackoff::default();
+        for upper_sec in [1, 2, 4, 8, 15, 15] {
+            assert!(backoff.next_delay() <= Duration::from_secs(upper_sec));
+        }
+        assert_eq!(backoff.current, MAX_BACKOFF);
+
+        backoff.reset();
+        assert_eq!(backoff.current, Duration::from_secs(1));
+        assert!(backoff.next_delay() <= Duration::from_secs(1));
+    }
+
+    #[test]
+    fn jitter_of_zero_bound_is_zero() {
+        assert_eq!(
+            full_jitter(Duration::ZERO, &mut rand::rng()),
+            Duration::ZERO
+        );
+    }
+}
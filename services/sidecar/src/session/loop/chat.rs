@@ -373,6 +373,7 @@ fn merge_attachment_delivery_meta(meta: Value, attachment_delivery: Value) -> Va
     }
 }
 
+#[allow(clippy::result_large_err)]
 fn resolve_media_attachment_for_prompt(
     request: &ChatRequestInput,
     tool: &ToolRuntimePayload,
@@ -1663,7 +1664,7 @@ fn collect_markdown_paths_from_text(raw: &str, output: &mut Vec<String>) {
             text.len()
         };
         let candidate = text[start_byte..end_byte]
-            .trim_end_matches(|char| matches!(char, '.' | ',' | ';' | ':' | '!' | '?'));
+            .trim_end_matches(['.', ',', ';', ':', '!', '?']);
         if is_markdown_report_path_candidate(candidate) {
             push_unique_path(output, candidate);
             index = end_index;
@@ -3,7 +3,6 @@
 use anyhow::Result;
 use base64::{Engine as _, engine::general_purpose};
 use chrono::{Duration as ChronoDuration, Utc};
-use futures_util::stream::SplitSink;
 use serde_json::json;
 use std::{
     env, fs,
@@ -12,50 +11,65 @@ use std::{
     time::{Duration, Instant},
 };
 use sysinfo::{Pid, ProcessesToUpdate, System};
-use tokio::net::TcpStream;
 use tokio::{process::Command, time::sleep};
-use tokio_tungstenite::{MaybeTlsStream, WebSocketStream, tungstenite::Message};
 use tracing::{debug, info};
 use yc_shared_protocol::{
-    ToolDetailsRefreshPriority, ToolDetailsSnapshotTrigger, ToolRuntimePayload,
+    COMMAND_ERROR_FALLBACK_TOOL, COMMAND_ERROR_INVALID_REQUEST, COMMAND_ERROR_PENDING_APPROVAL,
+    COMMAND_ERROR_PROCESS_FAILED, COMMAND_ERROR_STORE_FAILED, COMMAND_ERROR_TOOL_NOT_FOUND,
+    COMMAND_ERROR_UNAUTHORIZED, CommandFeedbackPayload, ControllerBindRequestPayload,
+    MetricsHistoryPayload, ToolDetailsRefreshPriority, ToolDetailsSnapshotTrigger, ToolLogsPayload,
+    ToolRuntimePayload,
 };
 
 use crate::{
     config::Config,
     control::{
-        CONTROLLER_BIND_UPDATED_EVENT, SidecarCommand, SidecarCommandEnvelope,
-        TOOL_CHAT_FINISHED_EVENT, TOOL_LAUNCH_FAILED_EVENT, TOOL_LAUNCH_FINISHED_EVENT,
-        TOOL_LAUNCH_STARTED_EVENT, TOOL_MEDIA_STAGE_FAILED_EVENT, TOOL_MEDIA_STAGE_FINISHED_EVENT,
-        TOOL_MEDIA_STAGE_PROGRESS_EVENT, TOOL_PROCESS_CONTROL_UPDATED_EVENT,
-        TOOL_REPORT_FETCH_FINISHED_EVENT, TOOL_WHITELIST_UPDATED_EVENT, ToolProcessAction,
-        command_feedback_event, command_feedback_parts,
+        CONTROLLER_BIND_REQUEST_EVENT, CONTROLLER_BIND_UPDATED_EVENT, METRICS_HISTORY_EVENT,
+        SIDECAR_LOG_LEVEL_UPDATED_EVENT, SidecarCommand, SidecarCommandEnvelope,
+        TOOL_CHAT_FINISHED_EVENT, TOOL_DETAILS_NOW_RESULT_EVENT, TOOL_KILL_UPDATED_EVENT,
+        TOOL_LABEL_UPDATED_EVENT, TOOL_LAUNCH_FAILED_EVENT, TOOL_LAUNCH_FINISHED_EVENT,
+        TOOL_LAUNCH_STARTED_EVENT, TOOL_LOGS_EVENT, TOOL_MEDIA_STAGE_FAILED_EVENT,
+        TOOL_MEDIA_STAGE_FINISHED_EVENT, TOOL_MEDIA_STAGE_PROGRESS_EVENT,
+        TOOL_PROCESS_CONTROL_UPDATED_EVENT, TOOL_REPORT_FETCH_FINISHED_EVENT,
+        TOOL_WHITELIST_UPDATED_EVENT, ToolProcessAction, command_feedback_event,
+        command_feedback_payload,
+    },
+    logging::log_level_control,
+    session::{
+        diagnostics::{SIDECAR_DIAGNOSTICS_EVENT, diagnostics_denied_payload},
+        metrics_history::MetricsHistory,
+        snapshots::is_fallback_tool,
+        transport::send_event,
+    },
+    stores::{ControllerAuthorization, ControllerDevicesStore, ToolLabelStore, ToolWhitelistStore},
+    tooling::{
+        adapters::{claude_code, codex, openclaw, opencode, registry::ToolAdapterRegistry},
+        tool_logs::read_latest_log_tail,
     },
-    session::{snapshots::is_fallback_tool, transport::send_event},
-    stores::{ControllerDevicesStore, ToolWhitelistStore},
-    tooling::adapters::{claude_code, codex, openclaw, opencode},
 };
 
 use super::chat::{
     CancelChatOutcome, ChatCancelInput, ChatEventSender, ChatRequestInput, ChatRuntime,
     StartChatOutcome,
 };
+use super::details_now::{DetailsNowRuntime, StartDetailsNowOutcome, details_now_failed_payload};
 use super::report::{ReportEventSender, ReportRequestInput, ReportRuntime, StartReportOutcome};
-
-/// Relay WebSocket 写端类型别名。
-pub(crate) type RelayWriter = SplitSink<WebSocketStream<MaybeTlsStream<TcpStream>>, Message>;
+use super::send_worker::RelayWriter;
 
 /// sidecar 命令处理上下文。
 pub(crate) struct SidecarCommandContext<'a> {
     pub(crate) ws_writer: &'a mut RelayWriter,
     pub(crate) cfg: &'a Config,
-    pub(crate) seq: &'a mut u64,
     pub(crate) discovered_tools: &'a [ToolRuntimePayload],
     pub(crate) whitelist: &'a mut ToolWhitelistStore,
+    pub(crate) labels: &'a mut ToolLabelStore,
     pub(crate) controllers: &'a mut ControllerDevicesStore,
     pub(crate) chat_runtime: &'a mut ChatRuntime,
     pub(crate) chat_event_tx: &'a ChatEventSender,
     pub(crate) report_runtime: &'a mut ReportRuntime,
     pub(crate) report_event_tx: &'a ReportEventSender,
+    pub(crate) details_now: &'a DetailsNowRuntime,
+    pub(crate) metrics_history: &'a MetricsHistory,
 }
 
 /// sidecar 命令处理结果：声明后续是否需要刷新快照/详情。
@@ -75,6 +89,21 @@ pub(crate) struct SidecarCommandOutcome {
     pub(crate) detail_priority: ToolDetailsRefreshPriority,
     /// 详情快照触发来源。
     pub(crate) detail_trigger: ToolDetailsSnapshotTrigger,
+    /// 工具聚焦状态变更（可选）。
+    pub(crate) focus_change: Option<DetailsFocusChange>,
+    /// 是否需要立即下发 `sidecar_diagnostics`。
+    pub(crate) send_diagnostics: bool,
+}
+
+/// 工具聚焦状态变更意图，由会话循环落到聚焦状态上。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum DetailsFocusChange {
+    /// 聚焦指定工具。
+    Focus(String),
+    /// 取消当前聚焦。
+    Unfocus,
+    /// 若当前聚焦的是该工具则取消（工具断开时使用）。
+    UnfocusTool(String),
 }
 
 impl SidecarCommandOutcome {
@@ -88,9 +117,17 @@ impl SidecarCommandOutcome {
             detail_refresh_id: None,
             detail_priority: ToolDetailsRefreshPriority::Background,
             detail_trigger: ToolDetailsSnapshotTrigger::Command,
+            focus_change: None,
+            send_diagnostics: false,
         }
     }
 
+    /// 附带聚焦状态变更。
+    fn with_focus_change(mut self, change: DetailsFocusChange) -> Self {
+        self.focus_change = Some(change);
+        self
+    }
+
     /// 仅刷新详情。
     fn details_only(
         detail_tool_id: Option<String>,
@@ -107,6 +144,8 @@ impl SidecarCommandOutcome {
             detail_refresh_id,
             detail_priority,
             detail_trigger,
+            focus_change: None,
+            send_diagnostics: false,
         }
     }
 }
@@ -212,14 +251,16 @@ pub(crate) async fn handle_sidecar_command(
     let SidecarCommandContext {
         ws_writer,
         cfg,
-        seq,
         discovered_tools,
         whitelist,
+        labels,
         controllers,
         chat_runtime,
         chat_event_tx,
         report_runtime,
         report_event_tx,
+        details_now,
+        metrics_history,
     } = ctx;
 
     let trace_id = if command_envelope.trace_id.trim().is_empty() {
@@ -238,50 +279,75 @@ pub(crate) async fn handle_sidecar_command(
 
     if let SidecarCommand::RebindController { device_id } = &command_envelope.command {
         let device = device_id.trim();
-        let (ok, changed, reason) = if command_envelope.source_client_type != "app" {
-            (
-                false,
-                false,
-                "仅接受 app 客户端发起控制端重绑。".to_string(),
+        let feedback = command_feedback_payload(&command_envelope.command).with_device_id(device);
+        let feedback = if command_envelope.source_client_type != "app" {
+            feedback.failed(
+                COMMAND_ERROR_UNAUTHORIZED,
+                "仅接受 app 客户端发起控制端重绑。",
             )
+        } else if let Some(reason) =
+            controllers.rebind_denied_reason(&command_envelope.source_device_id)
+        {
+            feedback.failed(COMMAND_ERROR_UNAUTHORIZED, reason)
         } else if device.is_empty() {
-            (
-                false,
-                false,
-                "缺少目标设备标识，无法重绑控制端。".to_string(),
+            feedback.failed(
+                COMMAND_ERROR_INVALID_REQUEST,
+                "缺少目标设备标识，无法重绑控制端。",
             )
         } else {
             match controllers.rebind(device) {
-                Ok(changed) => (true, changed, String::new()),
-                Err(err) => (false, false, format!("重绑控制设备失败: {err}")),
+                Ok(changed) => feedback.succeeded(changed),
+                Err(err) => feedback.failed(
+                    COMMAND_ERROR_STORE_FAILED,
+                    format!("重绑控制设备失败: {err}"),
+                ),
             }
         };
 
         send_event(
             ws_writer,
             &cfg.system_id,
-            seq,
             CONTROLLER_BIND_UPDATED_EVENT,
             trace_id.as_deref(),
-            json!({
-                "ok": ok,
-                "changed": changed,
-                "deviceId": device,
-                "reason": reason,
-            }),
+            feedback.to_value(),
         )
         .await?;
 
         return Ok(SidecarCommandOutcome::default());
     }
 
-    let (allowed, allow_reason) = match controllers.authorize_or_bind(
+    let (allowed, allow_reason, deny_code) = match controllers.authorize_or_bind(
         &command_envelope.source_client_type,
         &command_envelope.source_device_id,
-        cfg.allow_first_controller_bind,
     ) {
-        Ok(value) => value,
-        Err(err) => (false, format!("更新控制设备配置失败: {err}")),
+        Ok(ControllerAuthorization::Allowed) => (true, String::new(), COMMAND_ERROR_UNAUTHORIZED),
+        Ok(ControllerAuthorization::Denied(reason)) => (false, reason, COMMAND_ERROR_UNAUTHORIZED),
+        Ok(ControllerAuthorization::PendingApproval {
+            reason,
+            newly_requested,
+        }) => {
+            if newly_requested {
+                let payload = ControllerBindRequestPayload {
+                    device_id: command_envelope.source_device_id.trim().to_string(),
+                    command_event: command_envelope.event_type.clone(),
+                    pending_device_ids: controllers.pending_ids(),
+                };
+                send_event(
+                    ws_writer,
+                    &cfg.system_id,
+                    CONTROLLER_BIND_REQUEST_EVENT,
+                    None,
+                    serde_json::to_value(payload)?,
+                )
+                .await?;
+            }
+            (false, reason, COMMAND_ERROR_PENDING_APPROVAL)
+        }
+        Err(err) => (
+            false,
+            format!("更新控制设备配置失败: {err}"),
+            COMMAND_ERROR_STORE_FAILED,
+        ),
     };
 
     if !allowed {
@@ -302,7 +368,6 @@ pub(crate) async fn handle_sidecar_command(
                 send_event(
                     ws_writer,
                     &cfg.system_id,
-                    seq,
                     TOOL_CHAT_FINISHED_EVENT,
                     trace_id.as_deref(),
                     json!({
@@ -328,7 +393,6 @@ pub(crate) async fn handle_sidecar_command(
                 send_event(
                     ws_writer,
                     &cfg.system_id,
-                    seq,
                     TOOL_REPORT_FETCH_FINISHED_EVENT,
                     trace_id.as_deref(),
                     json!({
@@ -345,24 +409,75 @@ pub(crate) async fn handle_sidecar_command(
                 .await?;
                 return Ok(SidecarCommandOutcome::default());
             }
+            SidecarCommand::GetToolDetailsNow {
+                request_id,
+                tool_id,
+            } => {
+                send_event(
+                    ws_writer,
+                    &cfg.system_id,
+                    TOOL_DETAILS_NOW_RESULT_EVENT,
+                    trace_id.as_deref(),
+                    details_now_failed_payload(request_id, tool_id, "failed", allow_reason),
+                )
+                .await?;
+                return Ok(SidecarCommandOutcome::default());
+            }
+            SidecarCommand::MetricsHistoryRequest { .. } => {
+                let payload = MetricsHistoryPayload {
+                    capacity: metrics_history.capacity(),
+                    samples: Vec::new(),
+                    reason: Some(allow_reason),
+                };
+                send_event(
+                    ws_writer,
+                    &cfg.system_id,
+                    METRICS_HISTORY_EVENT,
+                    trace_id.as_deref(),
+                    serde_json::to_value(payload)?,
+                )
+                .await?;
+                return Ok(SidecarCommandOutcome::default());
+            }
+            SidecarCommand::ToolLogsRequest { tool_id, .. } => {
+                let payload = ToolLogsPayload {
+                    tool_id: tool_id.clone(),
+                    reason: Some(allow_reason),
+                    ..ToolLogsPayload::default()
+                };
+                send_event(
+                    ws_writer,
+                    &cfg.system_id,
+                    TOOL_LOGS_EVENT,
+                    trace_id.as_deref(),
+                    serde_json::to_value(payload)?,
+                )
+                .await?;
+                return Ok(SidecarCommandOutcome::default());
+            }
+            SidecarCommand::DiagnosticsRequest => {
+                send_event(
+                    ws_writer,
+                    &cfg.system_id,
+                    SIDECAR_DIAGNOSTICS_EVENT,
+                    trace_id.as_deref(),
+                    serde_json::to_value(diagnostics_denied_payload(allow_reason))?,
+                )
+                .await?;
+                return Ok(SidecarCommandOutcome::default());
+            }
             _ => {}
         }
 
-        let (action, tool_id) = command_feedback_parts(&command_envelope.command);
+        let feedback =
+            command_feedback_payload(&command_envelope.command).failed(deny_code, allow_reason);
         let response_event = command_feedback_event(&command_envelope.command);
         send_event(
             ws_writer,
             &cfg.system_id,
-            seq,
             response_event,
             trace_id.as_deref(),
-            json!({
-                "action": action,
-                "toolId": tool_id,
-                "ok": false,
-                "changed": false,
-                "reason": allow_reason,
-            }),
+            feedback.to_value(),
         )
         .await?;
         return Ok(SidecarCommandOutcome::default());
@@ -372,13 +487,16 @@ pub(crate) async fn handle_sidecar_command(
         SidecarCommand::Refresh => SidecarCommandOutcome::snapshots_and_details(),
         SidecarCommand::ConnectTool { tool_id } => {
             let candidate = discovered_tools.iter().find(|tool| tool.tool_id == tool_id);
-            let (ok, changed, reason) = if candidate.is_none() {
-                (false, false, "工具不在当前候选列表，无法接入。".to_string())
+            let feedback = CommandFeedbackPayload::new("connect", tool_id.clone());
+            let feedback = if candidate.is_none() {
+                feedback.failed(
+                    COMMAND_ERROR_TOOL_NOT_FOUND,
+                    "工具不在当前候选列表，无法接入。",
+                )
             } else if candidate.map(is_fallback_tool).unwrap_or(false) {
-                (
-                    false,
-                    false,
-                    "fallback 工具仅用于占位展示，不能接入。".to_string(),
+                feedback.failed(
+                    COMMAND_ERROR_FALLBACK_TOOL,
+                    "fallback 工具仅用于占位展示，不能接入。",
                 )
             } else {
                 match whitelist.add(&tool_id) {
@@ -386,78 +504,157 @@ pub(crate) async fn handle_sidecar_command(
                         if changed {
                             info!("tool whitelisted: {tool_id}");
                         }
-                        (true, changed, String::new())
+                        feedback.succeeded(changed)
                     }
-                    Err(err) => (false, false, format!("更新白名单失败: {err}")),
+                    Err(err) => feedback
+                        .failed(COMMAND_ERROR_STORE_FAILED, format!("更新白名单失败: {err}")),
                 }
             };
 
             send_event(
                 ws_writer,
                 &cfg.system_id,
-                seq,
                 TOOL_WHITELIST_UPDATED_EVENT,
                 trace_id.as_deref(),
-                json!({
-                    "action": "connect",
-                    "toolId": tool_id,
-                    "ok": ok,
-                    "changed": changed,
-                    "reason": reason,
-                }),
+                feedback.to_value(),
             )
             .await?;
 
             SidecarCommandOutcome::snapshots_and_details()
         }
         SidecarCommand::DisconnectTool { tool_id } => {
-            let (ok, changed, reason) = match whitelist.remove(&tool_id) {
-                Ok(changed) => (true, changed, String::new()),
-                Err(err) => (false, false, format!("更新白名单失败: {err}")),
+            let feedback = CommandFeedbackPayload::new("disconnect", tool_id.clone());
+            let feedback = match whitelist.remove(&tool_id) {
+                Ok(changed) => feedback.succeeded(changed),
+                Err(err) => {
+                    feedback.failed(COMMAND_ERROR_STORE_FAILED, format!("更新白名单失败: {err}"))
+                }
             };
 
             send_event(
                 ws_writer,
                 &cfg.system_id,
-                seq,
                 TOOL_WHITELIST_UPDATED_EVENT,
                 trace_id.as_deref(),
-                json!({
-                    "action": "disconnect",
-                    "toolId": tool_id,
-                    "ok": ok,
-                    "changed": changed,
-                    "reason": reason,
-                }),
+                feedback.to_value(),
             )
             .await?;
 
             SidecarCommandOutcome::snapshots_and_details()
+                .with_focus_change(DetailsFocusChange::UnfocusTool(tool_id))
         }
         SidecarCommand::ResetToolWhitelist => {
-            let (ok, changed, reason, removed_count) = match whitelist.clear() {
-                Ok(removed) => (true, removed > 0, String::new(), removed),
-                Err(err) => (false, false, format!("清空白名单失败: {err}"), 0),
+            let feedback = CommandFeedbackPayload::new("reset", "");
+            let feedback = match whitelist.clear() {
+                Ok(removed) => feedback.with_removed_count(removed).succeeded(removed > 0),
+                Err(err) => feedback
+                    .with_removed_count(0)
+                    .failed(COMMAND_ERROR_STORE_FAILED, format!("清空白名单失败: {err}")),
             };
 
             send_event(
                 ws_writer,
                 &cfg.system_id,
-                seq,
                 TOOL_WHITELIST_UPDATED_EVENT,
                 trace_id.as_deref(),
-                json!({
-                    "action": "reset",
-                    "toolId": "",
-                    "ok": ok,
-                    "changed": changed,
-                    "removedCount": removed_count,
-                    "reason": reason,
-                }),
+                feedback.to_value(),
             )
             .await?;
 
             SidecarCommandOutcome::snapshots_and_details()
+                .with_focus_change(DetailsFocusChange::Unfocus)
+        }
+        SidecarCommand::SetToolLabel { tool_id, label } => {
+            let feedback = CommandFeedbackPayload::new("set-label", tool_id.clone());
+            let feedback = match labels.set(&tool_id, &label) {
+                Ok(changed) => {
+                    if changed {
+                        info!("tool label updated: {tool_id}");
+                    }
+                    feedback.succeeded(changed)
+                }
+                Err(err) => {
+                    feedback.failed(COMMAND_ERROR_STORE_FAILED, format!("更新显示名失败: {err}"))
+                }
+            };
+
+            send_event(
+                ws_writer,
+                &cfg.system_id,
+                TOOL_LABEL_UPDATED_EVENT,
+                trace_id.as_deref(),
+                feedback.to_value(),
+            )
+            .await?;
+
+            SidecarCommandOutcome {
+                refresh_snapshots: true,
+                ..SidecarCommandOutcome::default()
+            }
+        }
+        SidecarCommand::SetLogLevel { target, level } => {
+            let feedback = CommandFeedbackPayload::new("set-log-level", target.clone());
+            let feedback = match log_level_control() {
+                None => feedback.failed(
+                    COMMAND_ERROR_INVALID_REQUEST,
+                    "日志系统未启用运行时级别调整。",
+                ),
+                Some(control) => match control.set(&target, &level) {
+                    Ok(changed) => {
+                        info!(
+                            "log level updated target={target} level={level} source_device={}",
+                            command_envelope.source_device_id
+                        );
+                        feedback.succeeded(changed)
+                    }
+                    Err(err) => feedback.failed(COMMAND_ERROR_INVALID_REQUEST, err.to_string()),
+                },
+            };
+
+            send_event(
+                ws_writer,
+                &cfg.system_id,
+                SIDECAR_LOG_LEVEL_UPDATED_EVENT,
+                trace_id.as_deref(),
+                feedback.to_value(),
+            )
+            .await?;
+
+            SidecarCommandOutcome::default()
+        }
+        SidecarCommand::MetricsHistoryRequest { limit } => {
+            let payload = MetricsHistoryPayload {
+                capacity: metrics_history.capacity(),
+                samples: metrics_history.recent(limit),
+                reason: None,
+            };
+            send_event(
+                ws_writer,
+                &cfg.system_id,
+                METRICS_HISTORY_EVENT,
+                trace_id.as_deref(),
+                serde_json::to_value(payload)?,
+            )
+            .await?;
+
+            SidecarCommandOutcome::default()
+        }
+        SidecarCommand::DiagnosticsRequest => SidecarCommandOutcome {
+            send_diagnostics: true,
+            ..SidecarCommandOutcome::default()
+        },
+        SidecarCommand::ToolLogsRequest { tool_id, lines } => {
+            let payload = collect_tool_logs(discovered_tools, &tool_id, lines);
+            send_event(
+                ws_writer,
+                &cfg.system_id,
+                TOOL_LOGS_EVENT,
+                trace_id.as_deref(),
+                serde_json::to_value(payload)?,
+            )
+            .await?;
+
+            SidecarCommandOutcome::default()
         }
         SidecarCommand::RefreshToolDetails {
             refresh_id,
@@ -471,6 +668,63 @@ pub(crate) async fn handle_sidecar_command(
             priority,
             ToolDetailsSnapshotTrigger::Request,
         ),
+        SidecarCommand::GetToolDetailsNow {
+            request_id,
+            tool_id,
+        } => {
+            let tool = discovered_tools
+                .iter()
+                .find(|item| item.tool_id == tool_id && whitelist.contains_compatible(&tool_id))
+                .cloned();
+            let rejected = match tool {
+                None => Some(details_now_failed_payload(
+                    &request_id,
+                    &tool_id,
+                    "failed",
+                    "工具未在线或未接入，无法采集详情。",
+                )),
+                Some(tool) => {
+                    match details_now.start(request_id.clone(), tool, trace_id.clone()) {
+                        StartDetailsNowOutcome::Started => None,
+                        StartDetailsNowOutcome::Busy { reason } => Some(
+                            details_now_failed_payload(&request_id, &tool_id, "busy", reason),
+                        ),
+                    }
+                }
+            };
+            if let Some(payload) = rejected {
+                send_event(
+                    ws_writer,
+                    &cfg.system_id,
+                    TOOL_DETAILS_NOW_RESULT_EVENT,
+                    trace_id.as_deref(),
+                    payload,
+                )
+                .await?;
+            }
+            SidecarCommandOutcome::default()
+        }
+        SidecarCommand::FocusTool { tool_id } => {
+            if !whitelist.contains_compatible(&tool_id) {
+                debug!("ignore focus request for unconnected tool: {tool_id}");
+                return Ok(SidecarCommandOutcome::default());
+            }
+            // 聚焦后立即补采一次，后续由会话循环按聚焦周期继续补采。
+            SidecarCommandOutcome::details_only(
+                Some(tool_id.clone()),
+                true,
+                None,
+                ToolDetailsRefreshPriority::User,
+                ToolDetailsSnapshotTrigger::Request,
+            )
+            .with_focus_change(DetailsFocusChange::Focus(tool_id))
+        }
+        SidecarCommand::UnfocusTool => SidecarCommandOutcome {
+            focus_change: Some(DetailsFocusChange::Unfocus),
+            ..SidecarCommandOutcome::default()
+        },
+        // 会话循环在授权前已消费离开提示，这里不会到达。
+        SidecarCommand::AppDisconnect => SidecarCommandOutcome::default(),
         SidecarCommand::ControlToolProcess { tool_id, action } => {
             let candidate = discovered_tools.iter().find(|tool| tool.tool_id == tool_id);
             let pid = candidate.and_then(|tool| tool.pid);
@@ -538,7 +792,6 @@ pub(crate) async fn handle_sidecar_command(
             send_event(
                 ws_writer,
                 &cfg.system_id,
-                seq,
                 TOOL_PROCESS_CONTROL_UPDATED_EVENT,
                 trace_id.as_deref(),
                 json!({
@@ -559,6 +812,58 @@ pub(crate) async fn handle_sidecar_command(
                 SidecarCommandOutcome::default()
             }
         }
+        SidecarCommand::KillTool { tool_id } => {
+            let candidate = discovered_tools.iter().find(|tool| tool.tool_id == tool_id);
+            let feedback = CommandFeedbackPayload::new("kill", tool_id.clone());
+            let feedback = match candidate {
+                None => feedback.failed(
+                    COMMAND_ERROR_TOOL_NOT_FOUND,
+                    "工具不在当前列表，无法结束进程。",
+                ),
+                Some(tool) if is_fallback_tool(tool) => feedback.failed(
+                    COMMAND_ERROR_FALLBACK_TOOL,
+                    "fallback 工具仅用于占位展示，不能结束进程。",
+                ),
+                Some(tool) => match tool.pid {
+                    None => {
+                        feedback.failed(COMMAND_ERROR_INVALID_REQUEST, "未找到可结束的进程 PID。")
+                    }
+                    Some(pid) if is_protected_pid(pid) => feedback.failed(
+                        COMMAND_ERROR_INVALID_REQUEST,
+                        format!("PID {pid} 为 sidecar 自身或系统进程，已拒绝结束。"),
+                    ),
+                    Some(pid) => {
+                        let result = stop_process(pid).await;
+                        if result.ok {
+                            info!(
+                                "tool process killed tool={} pid={} changed={} source_device={}",
+                                tool_id, pid, result.changed, command_envelope.source_device_id
+                            );
+                            feedback.succeeded(result.changed)
+                        } else {
+                            feedback.failed(COMMAND_ERROR_PROCESS_FAILED, result.reason)
+                        }
+                    }
+                },
+            };
+            let killed = feedback.changed;
+
+            send_event(
+                ws_writer,
+                &cfg.system_id,
+                TOOL_KILL_UPDATED_EVENT,
+                trace_id.as_deref(),
+                feedback.to_value(),
+            )
+            .await?;
+
+            if killed {
+                SidecarCommandOutcome::snapshots_and_details()
+                    .with_focus_change(DetailsFocusChange::UnfocusTool(tool_id))
+            } else {
+                SidecarCommandOutcome::default()
+            }
+        }
         SidecarCommand::ToolChatRequest {
             tool_id,
             conversation_key,
@@ -575,7 +880,6 @@ pub(crate) async fn handle_sidecar_command(
                 send_event(
                     ws_writer,
                     &cfg.system_id,
-                    seq,
                     TOOL_CHAT_FINISHED_EVENT,
                     trace_id.as_deref(),
                     json!({
@@ -613,7 +917,6 @@ pub(crate) async fn handle_sidecar_command(
                     send_event(
                         ws_writer,
                         &cfg.system_id,
-                        seq,
                         TOOL_CHAT_FINISHED_EVENT,
                         trace_id.as_deref(),
                         json!({
@@ -649,7 +952,6 @@ pub(crate) async fn handle_sidecar_command(
                     send_event(
                         ws_writer,
                         &cfg.system_id,
-                        seq,
                         TOOL_CHAT_FINISHED_EVENT,
                         trace_id.as_deref(),
                         json!({
@@ -682,7 +984,6 @@ pub(crate) async fn handle_sidecar_command(
                 send_event(
                     ws_writer,
                     &cfg.system_id,
-                    seq,
                     TOOL_REPORT_FETCH_FINISHED_EVENT,
                     trace_id.as_deref(),
                     json!({
@@ -718,7 +1019,6 @@ pub(crate) async fn handle_sidecar_command(
                     send_event(
                         ws_writer,
                         &cfg.system_id,
-                        seq,
                         TOOL_REPORT_FETCH_FINISHED_EVENT,
                         trace_id.as_deref(),
                         json!({
@@ -749,7 +1049,6 @@ pub(crate) async fn handle_sidecar_command(
             send_event(
                 ws_writer,
                 &cfg.system_id,
-                seq,
                 TOOL_MEDIA_STAGE_PROGRESS_EVENT,
                 trace_id.as_deref(),
                 json!({
@@ -779,7 +1078,6 @@ pub(crate) async fn handle_sidecar_command(
                     send_event(
                         ws_writer,
                         &cfg.system_id,
-                        seq,
                         TOOL_MEDIA_STAGE_FINISHED_EVENT,
                         trace_id.as_deref(),
                         json!({
@@ -803,7 +1101,6 @@ pub(crate) async fn handle_sidecar_command(
                     send_event(
                         ws_writer,
                         &cfg.system_id,
-                        seq,
                         TOOL_MEDIA_STAGE_FAILED_EVENT,
                         trace_id.as_deref(),
                         json!({
@@ -837,7 +1134,6 @@ pub(crate) async fn handle_sidecar_command(
                     send_event(
                         ws_writer,
                         &cfg.system_id,
-                        seq,
                         TOOL_LAUNCH_STARTED_EVENT,
                         trace_id.as_deref(),
                         json!({
@@ -853,7 +1149,6 @@ pub(crate) async fn handle_sidecar_command(
                             send_event(
                                 ws_writer,
                                 &cfg.system_id,
-                                seq,
                                 TOOL_LAUNCH_FINISHED_EVENT,
                                 trace_id.as_deref(),
                                 json!({
@@ -873,7 +1168,6 @@ pub(crate) async fn handle_sidecar_command(
                             send_event(
                                 ws_writer,
                                 &cfg.system_id,
-                                seq,
                                 TOOL_LAUNCH_FAILED_EVENT,
                                 trace_id.as_deref(),
                                 json!({
@@ -893,7 +1187,6 @@ pub(crate) async fn handle_sidecar_command(
                     send_event(
                         ws_writer,
                         &cfg.system_id,
-                        seq,
                         TOOL_LAUNCH_FAILED_EVENT,
                         trace_id.as_deref(),
                         json!({
@@ -915,6 +1208,35 @@ pub(crate) async fn handle_sidecar_command(
     Ok(outcome)
 }
 
+/// 读取已发现工具的最近日志行；工具未发现或不支持日志时返回失败回执。
+fn collect_tool_logs(
+    discovered_tools: &[ToolRuntimePayload],
+    tool_id: &str,
+    lines: usize,
+) -> ToolLogsPayload {
+    let failed = |reason: String| ToolLogsPayload {
+        tool_id: tool_id.to_string(),
+        reason: Some(reason),
+        ..ToolLogsPayload::default()
+    };
+    let Some(tool) = discovered_tools.iter().find(|item| item.tool_id == tool_id) else {
+        return failed("工具未在线，无法读取日志。".to_string());
+    };
+    let Some(location) = ToolAdapterRegistry::builtin().log_location(tool) else {
+        return failed("该工具不支持日志回放。".to_string());
+    };
+    match read_latest_log_tail(&location, lines) {
+        Ok(tail) => ToolLogsPayload {
+            tool_id: tool_id.to_string(),
+            ok: true,
+            log_file: Some(tail.file_name),
+            lines: tail.lines,
+            reason: None,
+        },
+        Err(reason) => failed(reason),
+    }
+}
+
 /// 尝试优雅停止进程；超时后自动升级为强制停止。
 async fn stop_process(pid: i32) -> StopResult {
     if !is_pid_running(pid) {
@@ -1011,6 +1333,11 @@ async fn stop_process(pid: i32) -> StopResult {
     }
 }
 
+/// 判断 PID 是否不可被结束：sidecar 自身、init 以及会被 kill 解释为进程组的非正值。
+fn is_protected_pid(pid: i32) -> bool {
+    pid <= 1 || u32::try_from(pid).is_ok_and(|value| value == std::process::id())
+}
+
 /// 使用同一命令与工作目录重启进程。
 async fn restart_process(pid: i32) -> std::result::Result<(bool, Option<i32>), String> {
     let launch_spec =
@@ -1085,6 +1412,7 @@ fn is_pid_running(pid: i32) -> bool {
     sys.process(Pid::from_u32(pid_u32)).is_some()
 }
 
+#[allow(clippy::too_many_arguments)]
 fn stage_media_attachment(
     tool_id: &str,
     conversation_key: &str,
@@ -1142,15 +1470,17 @@ fn stage_media_attachment(
     }
 
     let stage_root = resolve_media_stage_root(workspace_dir).map_err(|reason| {
-        StageError::new(MEDIA_STAGE_NOT_FOUND, format!("{tool_id} 暂存目录不可用: {reason}"))
+        StageError::new(
+            MEDIA_STAGE_NOT_FOUND,
+            format!("{tool_id} 暂存目录不可用: {reason}"),
+        )
     })?;
     cleanup_media_stage_dir(&stage_root);
     let conv_segment = sanitize_path_segment(conversation_key);
     let req_segment = sanitize_path_segment(request_id);
     let dir = stage_root.join(&conv_segment).join(&req_segment);
-    fs::create_dir_all(&dir).map_err(|err| {
-        StageError::new(MEDIA_PATH_FORBIDDEN, format!("创建暂存目录失败: {err}"))
-    })?;
+    fs::create_dir_all(&dir)
+        .map_err(|err| StageError::new(MEDIA_PATH_FORBIDDEN, format!("创建暂存目录失败: {err}")))?;
     let ext = mime_extension(&effective_mime);
     let file_name = format!("{}.{}", sanitize_path_segment(media_id), ext);
     let file_path = dir.join(file_name);
@@ -1200,7 +1530,10 @@ fn resolve_media_stage_root(workspace_dir: Option<&str>) -> std::result::Result<
             return Ok(candidate);
         }
     }
-    let Some(workspace) = workspace_dir.map(str::trim).filter(|value| !value.is_empty()) else {
+    let Some(workspace) = workspace_dir
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+    else {
         return Err("工具缺少工作目录。".to_string());
     };
     let canonical =
@@ -1404,3 +1737,18 @@ async fn spawn_launch_target(
         .map_err(|err| format!("启动 {} 失败: {err}", program))?;
     Ok(child.id().and_then(|pid| i32::try_from(pid).ok()))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::is_protected_pid;
+
+    #[test]
+    fn own_and_system_pids_are_protected() {
+        let own_pid = i32::try_from(std::process::id()).expect("pid fits i32");
+        assert!(is_protected_pid(own_pid));
+        assert!(is_protected_pid(1));
+        assert!(is_protected_pid(0));
+        assert!(is_protected_pid(-1));
+        assert!(!is_protected_pid(own_pid + 1));
+    }
+}
@@ -0,0 +1,309 @@
+//! 同步详情请求执行器：
+//! 1. 绕过 latest-wins 详情队列，对单个工具立即强制采集一次详情。
+//! 2. 以 requestId 关联请求与 `tool_details_now_result` 回执，便于详情页首屏加载。
+//! 3. 限制并发数与单次采集时长，超出时直接回执 busy/timeout。
+
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use serde_json::{Value, json};
+use tokio::sync::{Semaphore, mpsc};
+use yc_shared_protocol::ToolRuntimePayload;
+
+use super::{details_worker::DetailsCollector, new_details_core};
+use crate::{
+    config::Config, tooling::core::errors::SharedCollectionErrors,
+    tooling::core::types::ToolDetailsCollectRequest,
+};
+
+/// 同时进行的同步详情请求上限。
+pub(super) const DETAILS_NOW_MAX_IN_FLIGHT: usize = 2;
+/// 在详情命令超时之外额外预留的等待时间。
+const DETAILS_NOW_TIMEOUT_MARGIN: Duration = Duration::from_secs(5);
+
+/// 同步详情回执发送通道。
+pub(crate) type DetailsNowEventSender = mpsc::UnboundedSender<DetailsNowEvent>;
+
+/// 同步详情回执（由 run_session 主循环统一转发到 relay）。
+#[derive(Debug, Clone)]
+pub(crate) struct DetailsNowEvent {
+    /// traceId（可选）。
+    pub(crate) trace_id: Option<String>,
+    /// 事件 payload。
+    pub(crate) payload: Value,
+}
+
+/// 发起同步详情请求的结果。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum StartDetailsNowOutcome {
+    Started,
+    Busy { reason: String },
+}
+
+/// 会话级同步详情运行时。
+#[derive(Debug)]
+pub(crate) struct DetailsNowRuntime {
+    /// 采集核心构造所需配置。
+    cfg: Config,
+    /// 采集错误汇总（与详情 worker 共用）。
+    collection_errors: SharedCollectionErrors,
+    /// 并发许可。
+    permits: Arc<Semaphore>,
+    /// 单次请求超时。
+    timeout: Duration,
+    /// 回执发送通道。
+    event_tx: DetailsNowEventSender,
+}
+
+impl DetailsNowRuntime {
+    /// 按配置创建运行时。
+    pub(crate) fn new(
+        cfg: &Config,
+        collection_errors: SharedCollectionErrors,
+        event_tx: DetailsNowEventSender,
+    ) -> Self {
+        Self {
+            cfg: cfg.clone(),
+            collection_errors,
+            permits: Arc::new(Semaphore::new(DETAILS_NOW_MAX_IN_FLIGHT)),
+            timeout: cfg
+                .details_command_timeout
+                .saturating_add(DETAILS_NOW_TIMEOUT_MARGIN),
+            event_tx,
+        }
+    }
+
+    /// 使用独立采集核心（无缓存）为指定工具发起同步详情请求。
+    pub(crate) fn start(
+        &self,
+        request_id: String,
+        tool: ToolRuntimePayload,
+        trace_id: Option<String>,
+    ) -> StartDetailsNowOutcome {
+        let collector = new_details_core(&self.cfg, &self.collection_errors);
+        start_with(
+            &self.permits,
+            self.timeout,
+            &self.event_tx,
+            collector,
+            request_id,
+            tool,
+            trace_id,
+        )
+    }
+}
+
+/// 获取并发许可后在后台执行采集，完成后经通道回执。
+fn start_with<C: DetailsCollector>(
+    permits: &Arc<Semaphore>,
+    timeout: Duration,
+    event_tx: &DetailsNowEventSender,
+    mut collector: C,
+    request_id: String,
+    tool: ToolRuntimePayload,
+    trace_id: Option<String>,
+) -> StartDetailsNowOutcome {
+    let Ok(permit) = permits.clone().try_acquire_owned() else {
+        return StartDetailsNowOutcome::Busy {
+            reason: format!("同步详情请求过多（上限 {DETAILS_NOW_MAX_IN_FLIGHT}），请稍后重试。"),
+        };
+    };
+    let event_tx = event_tx.clone();
+    tokio::spawn(async move {
+        let payload = collect_details_now(&mut collector, &request_id, tool, timeout).await;
+        let _ = event_tx.send(DetailsNowEvent { trace_id, payload });
+        drop(permit);
+    });
+    StartDetailsNowOutcome::Started
+}
+
+/// 对单个工具强制采集详情，返回 `tool_details_now_result` payload。
+async fn collect_details_now<C: DetailsCollector>(
+    collector: &mut C,
+    request_id: &str,
+    tool: ToolRuntimePayload,
+    timeout: Duration,
+) -> Value {
+    let tool_id = tool.tool_id.clone();
+    let request = ToolDetailsCollectRequest {
+        tools: vec![tool],
+        target_tool_id: Some(tool_id.clone()),
+        force: true,
+    };
+    let started_at = Instant::now();
+    let collected = tokio::time::timeout(timeout, collector.collect(request)).await;
+    let collect_ms = started_at.elapsed().as_millis().min(u64::MAX as u128) as u64;
+
+    let Ok(details) = collected else {
+        return details_now_failed_payload(
+            request_id,
+            &tool_id,
+            "timeout",
+            format!("详情采集超时（{}ms）", timeout.as_millis()),
+        );
+    };
+    let Some(detail) = details.into_iter().find(|item| item.tool_id == tool_id) else {
+        return details_now_failed_payload(request_id, &tool_id, "failed", "未采集到工具详情");
+    };
+    json!({
+        "requestId": request_id,
+        "toolId": tool_id,
+        "status": "completed",
+        "reason": "",
+        "collectMs": collect_ms,
+        "detail": detail,
+    })
+}
+
+/// 构造失败/忙碌类的 `tool_details_now_result` payload。
+pub(crate) fn details_now_failed_payload(
+    request_id: &str,
+    tool_id: &str,
+    status: &str,
+    reason: impl Into<String>,
+) -> Value {
+    json!({
+        "requestId": request_id,
+        "toolId": tool_id,
+        "status": status,
+        "reason": reason.into(),
+        "collectMs": 0,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{future::Future, sync::Arc, time::Duration};
+
+    use serde_json::json;
+    use tokio::sync::{Notify, Semaphore, mpsc};
+    use yc_shared_protocol::{ToolDetailEnvelopePayload, ToolRuntimePayload};
+
+    use super::{
+        DETAILS_NOW_MAX_IN_FLIGHT, DetailsCollector, StartDetailsNowOutcome, collect_details_now,
+        start_with,
+    };
+    use crate::tooling::core::types::ToolDetailsCollectRequest;
+
+    /// 按请求工具返回详情的假采集器；可选在采集前等待放行。
+    #[derive(Clone, Default)]
+    struct FakeCollector {
+        gate: Option<Arc<Notify>>,
+    }
+
+    impl DetailsCollector for FakeCollector {
+        fn cached_details(
+            &mut self,
+            _tools: &[ToolRuntimePayload],
+        ) -> Vec<ToolDetailEnvelopePayload> {
+            Vec::new()
+        }
+
+        fn collect(
+            &mut self,
+            request: ToolDetailsCollectRequest,
+        ) -> impl Future<Output = Vec<ToolDetailEnvelopePayload>> + Send {
+            let gate = self.gate.clone();
+            async move {
+                if let Some(gate) = gate {
+                    gate.notified().await;
+                }
+                assert!(request.force);
+                request
+                    .tools
+                    .iter()
+                    .map(|tool| ToolDetailEnvelopePayload {
+                        tool_id: tool.tool_id.clone(),
+                        schema: "openclaw.v1".to_string(),
+                        data: json!({"ok": true}),
+                        ..ToolDetailEnvelopePayload::default()
+                    })
+                    .collect()
+            }
+        }
+    }
+
+    fn tool(tool_id: &str) -> ToolRuntimePayload {
+        ToolRuntimePayload {
+            tool_id: tool_id.to_string(),
+            ..ToolRuntimePayload::default()
+        }
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn returns_targeted_details_with_request_id() {
+        let (event_tx, mut event_rx) = mpsc::unbounded_channel();
+        let permits = Arc::new(Semaphore::new(DETAILS_NOW_MAX_IN_FLIGHT));
+        let outcome = start_with(
+            &permits,
+            Duration::from_secs(5),
+            &event_tx,
+            FakeCollector::default(),
+            "dnr_1".to_string(),
+            tool("openclaw_a"),
+            Some("trace_1".to_string()),
+        );
+        assert_eq!(outcome, StartDetailsNowOutcome::Started);
+
+        let event = event_rx.recv().await.expect("result event");
+        assert_eq!(event.trace_id.as_deref(), Some("trace_1"));
+        assert_eq!(event.payload["requestId"], "dnr_1");
+        assert_eq!(event.payload["status"], "completed");
+        assert_eq!(event.payload["detail"]["toolId"], "openclaw_a");
+        assert_eq!(event.payload["detail"]["data"]["ok"], true);
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn concurrent_requests_are_capped_and_slow_collection_times_out() {
+        let (event_tx, mut event_rx) = mpsc::unbounded_channel();
+        let permits = Arc::new(Semaphore::new(DETAILS_NOW_MAX_IN_FLIGHT));
+        let gate = Arc::new(Notify::new());
+        let blocked = FakeCollector {
+            gate: Some(gate.clone()),
+        };
+        for index in 0..DETAILS_NOW_MAX_IN_FLIGHT {
+            let outcome = start_with(
+                &permits,
+                Duration::from_secs(5),
+                &event_tx,
+                blocked.clone(),
+                format!("dnr_{index}"),
+                tool("openclaw_a"),
+                None,
+            );
+            assert_eq!(outcome, StartDetailsNowOutcome::Started);
+        }
+        let busy = start_with(
+            &permits,
+            Duration::from_secs(5),
+            &event_tx,
+            FakeCollector::default(),
+            "dnr_busy".to_string(),
+            tool("openclaw_a"),
+            None,
+        );
+        assert!(matches!(busy, StartDetailsNowOutcome::Busy { .. }));
+        // 先让后台任务进入等待，再统一放行。
+        tokio::task::yield_now().await;
+        gate.notify_waiters();
+        for _ in 0..DETAILS_NOW_MAX_IN_FLIGHT {
+            let event = event_rx.recv().await.expect("released result");
+            assert_eq!(event.payload["status"], "completed");
+        }
+
+        let mut never = FakeCollector {
+            gate: Some(Arc::new(Notify::new())),
+        };
+        let payload = collect_details_now(
+            &mut never,
+            "dnr_slow",
+            tool("openclaw_a"),
+            Duration::from_millis(20),
+        )
+        .await;
+        assert_eq!(payload["requestId"], "dnr_slow");
+        assert_eq!(payload["status"], "timeout");
+    }
+}
@@ -0,0 +1,243 @@
+//! 详情采集 worker：独立任务串行执行详情采集，异常退出后可在会话内重启。
+
+use std::{future::Future, sync::Arc, time::Instant};
+
+use tokio::{
+    sync::{Mutex, mpsc},
+    task::JoinHandle,
+};
+use yc_shared_protocol::{
+    ToolDetailEnvelopePayload, ToolDetailsSnapshotTrigger, ToolRuntimePayload,
+};
+
+use super::DetailsRefreshIntent;
+use crate::tooling::core::{ToolAdapterCore, types::ToolDetailsCollectRequest};
+
+/// 单会话内详情 worker 最多重启次数，超过后回退到整体重连。
+pub(super) const DETAILS_WORKER_MAX_RESTARTS: u32 = 3;
+
+/// 会话循环派发给 worker 的采集请求。
+#[derive(Debug)]
+pub(super) struct DetailsWorkerRequest {
+    pub(super) intent: DetailsRefreshIntent,
+    pub(super) collect_request: ToolDetailsCollectRequest,
+}
+
+/// worker 回传给会话循环的详情结果。
+#[derive(Debug)]
+pub(super) struct DetailsWorkerEvent {
+    pub(super) generation: u64,
+    pub(super) refresh_id: Option<String>,
+    pub(super) trigger: ToolDetailsSnapshotTrigger,
+    pub(super) target_tool_id: Option<String>,
+    pub(super) details: Vec<ToolDetailEnvelopePayload>,
+    pub(super) queue_wait_ms: u64,
+    pub(super) collect_ms: u64,
+    pub(super) dropped_refreshes: u32,
+    pub(super) connected_tools_count: usize,
+}
+
+/// 请求接收端：用互斥锁包裹，worker 退出（含 panic）后可交给新 worker 继续消费。
+pub(super) type DetailsRequestReceiver = Arc<Mutex<mpsc::Receiver<DetailsWorkerRequest>>>;
+
+/// worker 依赖的采集能力，便于在测试中替换为假实现。
+pub(super) trait DetailsCollector: Send + 'static {
+    /// 返回缓存中的详情快照（可为空）。
+    fn cached_details(&mut self, tools: &[ToolRuntimePayload]) -> Vec<ToolDetailEnvelopePayload>;
+
+    /// 执行一次详情采集。
+    fn collect(
+        &mut self,
+        request: ToolDetailsCollectRequest,
+    ) -> impl Future<Output = Vec<ToolDetailEnvelopePayload>> + Send;
+}
+
+impl DetailsCollector for ToolAdapterCore {
+    /// 读取适配器核心的详情缓存。
+    fn cached_details(&mut self, tools: &[ToolRuntimePayload]) -> Vec<ToolDetailEnvelopePayload> {
+        self.cached_details_snapshot(tools)
+    }
+
+    /// 调用适配器核心执行详情采集。
+    fn collect(
+        &mut self,
+        request: ToolDetailsCollectRequest,
+    ) -> impl Future<Output = Vec<ToolDetailEnvelopePayload>> + Send {
+        self.collect_details_snapshot(request)
+    }
+}
+
+/// 启动一个详情 worker 任务。
+pub(super) fn spawn_details_worker<C: DetailsCollector>(
+    mut collector: C,
+    requests: DetailsRequestReceiver,
+    events: mpsc::UnboundedSender<DetailsWorkerEvent>,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut requests = requests.lock().await;
+        while let Some(first_request) = requests.recv().await {
+            let mut active = first_request;
+            let mut dropped_refreshes = active.intent.dropped_refreshes;
+            while let Ok(next_request) = requests.try_recv() {
+                // 聚焦补采不能覆盖待执行的非聚焦刷新（后者已覆盖聚焦工具）。
+                if next_request.intent.focus && !active.intent.focus {
+                    continue;
+                }
+                dropped_refreshes = dropped_refreshes
+                    .saturating_add(1)
+                    .saturating_add(next_request.intent.dropped_refreshes);
+                active = next_request;
+            }
+            active.intent.dropped_refreshes = dropped_refreshes;
+            let queue_wait_ms = active
+                .intent
+                .queued_at
+                .elapsed()
+                .as_millis()
+                .min(u64::MAX as u128) as u64;
+            let generation = active.intent.generation;
+            let refresh_id = active.intent.refresh_id.clone();
+            let target_tool_id = active.intent.target_tool_id.clone();
+            let trigger = active.intent.trigger;
+            let connected_tools_count = active.collect_request.tools.len();
+
+            let cache_details = collector.cached_details(&active.collect_request.tools);
+            if !cache_details.is_empty() {
+                let _ = events.send(DetailsWorkerEvent {
+                    generation,
+                    refresh_id: refresh_id.clone(),
+                    trigger: ToolDetailsSnapshotTrigger::Cache,
+                    target_tool_id: target_tool_id.clone(),
+                    details: cache_details,
+                    queue_wait_ms,
+                    collect_ms: 0,
+                    dropped_refreshes,
+                    connected_tools_count,
+                });
+            }
+
+            let collect_started_at = Instant::now();
+            let details = collector.collect(active.collect_request).await;
+            let collect_ms = collect_started_at
+                .elapsed()
+                .as_millis()
+                .min(u64::MAX as u128) as u64;
+            let _ = events.send(DetailsWorkerEvent {
+                generation,
+                refresh_id,
+                trigger,
+                target_tool_id,
+                details,
+                queue_wait_ms,
+                collect_ms,
+                dropped_refreshes,
+                connected_tools_count,
+            });
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        future::Future,
+        sync::{
+            Arc,
+            atomic::{AtomicBool, Ordering},
+        },
+        time::Instant,
+    };
+
+    use tokio::sync::{Mutex, mpsc};
+    use yc_shared_protocol::{
+        ToolDetailEnvelopePayload, ToolDetailsRefreshPriority, ToolDetailsSnapshotTrigger,
+        ToolRuntimePayload,
+    };
+
+    use super::{DetailsCollector, DetailsWorkerRequest, spawn_details_worker};
+    use crate::{
+        session::r#loop::DetailsRefreshIntent, tooling::core::types::ToolDetailsCollectRequest,
+    };
+
+    /// 首次采集时 panic 的假采集器。
+    struct PanicOnceCollector {
+        panicked: Arc<AtomicBool>,
+    }
+
+    impl DetailsCollector for PanicOnceCollector {
+        fn cached_details(
+            &mut self,
+            _tools: &[ToolRuntimePayload],
+        ) -> Vec<ToolDetailEnvelopePayload> {
+            Vec::new()
+        }
+
+        fn collect(
+            &mut self,
+            request: ToolDetailsCollectRequest,
+        ) -> impl Future<Output = Vec<ToolDetailEnvelopePayload>> + Send {
+            let panicked = self.panicked.clone();
+            async move {
+                if !panicked.swap(true, Ordering::SeqCst) {
+                    panic!("simulated details worker crash");
+                }
+                vec![ToolDetailEnvelopePayload {
+                    tool_id: request.target_tool_id.unwrap_or_default(),
+                    ..ToolDetailEnvelopePayload::default()
+                }]
+            }
+        }
+    }
+
+    fn request(generation: u64) -> DetailsWorkerRequest {
+        DetailsWorkerRequest {
+            intent: DetailsRefreshIntent {
+                generation,
+                target_tool_id: Some("openclaw_a".to_string()),
+                force: true,
+                refresh_id: None,
+                priority: ToolDetailsRefreshPriority::Background,
+                trigger: ToolDetailsSnapshotTrigger::Command,
+                queued_at: Instant::now(),
+                dropped_refreshes: 0,
+                focus: false,
+            },
+            collect_request: ToolDetailsCollectRequest {
+                tools: Vec::new(),
+                target_tool_id: Some("openclaw_a".to_string()),
+                force: true,
+            },
+        }
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn panicked_worker_is_respawned_and_resumes_snapshots() {
+        let (req_tx, req_rx) = mpsc::channel(8);
+        let (event_tx, mut event_rx) = mpsc::unbounded_channel();
+        let requests = Arc::new(Mutex::new(req_rx));
+        let panicked = Arc::new(AtomicBool::new(false));
+
+        let first = spawn_details_worker(
+            PanicOnceCollector {
+                panicked: panicked.clone(),
+            },
+            requests.clone(),
+            event_tx.clone(),
+        );
+        req_tx.send(request(1)).await.expect("send first request");
+        let joined = first.await;
+        assert!(joined.is_err_and(|err| err.is_panic()));
+
+        let second =
+            spawn_details_worker(PanicOnceCollector { panicked }, requests.clone(), event_tx);
+        req_tx
+            .send(request(2))
+            .await
+            .expect("send backfill request");
+        let event = event_rx.recv().await.expect("respawned worker should emit");
+        assert_eq!(event.generation, 2);
+        assert_eq!(event.details.len(), 1);
+        assert_eq!(event.details[0].tool_id, "openclaw_a");
+        second.abort();
+    }
+}
@@ -1,49 +1,90 @@
 //! Relay 会话循环。
 
+mod app_viewers;
+mod backoff;
 mod chat;
 mod command;
+mod details_now;
+mod details_worker;
+mod pair_rotation;
+mod payload_log;
+mod ready;
+mod reload;
 mod report;
+mod send_worker;
+mod shutdown;
 mod url;
 
 use std::{
     collections::HashMap,
+    sync::Arc,
     time::{Duration, Instant},
 };
 
 use anyhow::{Result, anyhow};
-use futures_util::StreamExt;
+use futures_util::{SinkExt, StreamExt};
 use serde_json::json;
 use sysinfo::System;
-use tokio::sync::mpsc;
 use tokio::sync::mpsc::error::TrySendError;
+use tokio::sync::{Mutex, mpsc};
 use tokio::time::MissedTickBehavior;
 use tokio_tungstenite::{connect_async, tungstenite::Message};
 use tracing::{debug, error, info, warn};
 
 use self::{
+    app_viewers::{AppViewers, ViewerSignal, ViewerTransition, parse_viewer_signal},
+    backoff::ReconnectBackoff,
     chat::{ChatEventSender, ChatRuntime},
-    command::{SidecarCommandContext, handle_sidecar_command},
+    command::{DetailsFocusChange, SidecarCommandContext, handle_sidecar_command},
+    details_now::{DetailsNowEvent, DetailsNowRuntime},
+    details_worker::{
+        DETAILS_WORKER_MAX_RESTARTS, DetailsWorkerEvent, DetailsWorkerRequest, spawn_details_worker,
+    },
+    pair_rotation::{
+        PairTokenRotation, is_pair_token_revoked_notice, is_pair_token_revoked_rejection,
+    },
+    payload_log::PayloadLogMode,
+    ready::{SESSION_READY_EVENT, SESSION_READY_TIMEOUT, SessionReadyTracker},
+    reload::{ConfigReloader, apply_store_reload},
     report::{ReportEventSender, ReportRuntime},
-    url::{raw_payload_logging_enabled, sidecar_ws_url},
+    send_worker::{RelayWriter, spawn_send_worker},
+    shutdown::{ShutdownHint, server_shutdown_retry_after},
+    url::{sidecar_ws_request, sidecar_ws_url},
 };
 use crate::{
-    config::Config,
-    control::{SidecarCommand, SidecarCommandEnvelope, parse_sidecar_command},
+    config::{Config, rotate_pair_token},
+    control::{
+        SidecarCommand, SidecarCommandEnvelope, TOOL_DETAILS_NOW_RESULT_EVENT,
+        TOOL_WHITELIST_UPDATED_EVENT, parse_sidecar_command,
+    },
     pairing::{banner::print_pairing_banner, bootstrap_client::fetch_pair_bootstrap},
     session::{
+        command_ack::CommandAckTracker,
+        diagnostics::{SIDECAR_DIAGNOSTICS_EVENT, SessionDiagnostics},
+        heartbeat_rtt::HeartbeatRtt,
+        metrics_cadence::MetricsTicker,
+        metrics_history::MetricsHistory,
+        metrics_smoothing::MetricsSmoother,
+        network_rate::NetworkRateTracker,
         queue::{QueueKey, QueuePolicy, QueueScheduler},
         snapshots::{
-            ToolDetailsSnapshotMeta, send_snapshots, send_tool_details_snapshot,
-            summarize_wire_payload,
+            COLLECTION_ERRORS_EVENT, ToolDetailsSnapshotMeta, send_metrics_snapshot,
+            send_snapshots, send_tool_details_snapshot, send_tool_snapshots,
         },
-        transport::send_event,
+        transport::{OutboundFrame, send_event},
+        whitelist_prune::WhitelistPruner,
+    },
+    stores::{ControllerDevicesStore, ToolLabelStore, ToolWhitelistStore},
+    tooling::core::{
+        ToolAdapterCore,
+        errors::{CollectionErrorTracker, SharedCollectionErrors},
+        scheduler::DetailsFocusState,
+        types::ToolDetailsCollectRequest,
     },
-    stores::{ControllerDevicesStore, ToolWhitelistStore},
-    tooling::core::{ToolAdapterCore, types::ToolDetailsCollectRequest},
 };
 use yc_shared_protocol::{
-    ToolDetailEnvelopePayload, ToolDetailsRefreshPriority, ToolDetailsSnapshotTrigger,
-    ToolRuntimePayload,
+    ACK_EVENT_TYPE, AckPayload, SidecarDiagnosticsTrigger, ToolDetailsRefreshPriority,
+    ToolDetailsSnapshotTrigger, ToolRuntimePayload, inflate_envelope_text,
 };
 
 #[derive(Debug, Clone)]
@@ -56,59 +97,99 @@ struct DetailsRefreshIntent {
     trigger: ToolDetailsSnapshotTrigger,
     queued_at: Instant,
     dropped_refreshes: u32,
-}
-
-#[derive(Debug)]
-struct DetailsWorkerRequest {
-    intent: DetailsRefreshIntent,
-    collect_request: ToolDetailsCollectRequest,
-}
-
-#[derive(Debug)]
-struct DetailsWorkerEvent {
-    generation: u64,
-    refresh_id: Option<String>,
-    trigger: ToolDetailsSnapshotTrigger,
-    target_tool_id: Option<String>,
-    details: Vec<ToolDetailEnvelopePayload>,
-    queue_wait_ms: u64,
-    collect_ms: u64,
-    dropped_refreshes: u32,
-    connected_tools_count: usize,
+    focus: bool,
 }
 
 /// 处理一条控制命令，并把详情刷新意图入队。
 #[allow(clippy::too_many_arguments)]
 async fn handle_command_envelope(
-    ws_writer: &mut command::RelayWriter,
+    ws_writer: &mut RelayWriter,
     cfg: &Config,
-    seq: &mut u64,
     sys: &mut System,
     started_at: Instant,
+    net_rate: &mut NetworkRateTracker,
+    metrics_smoother: &mut MetricsSmoother,
+    metrics_history: &MetricsHistory,
     discover_core: &mut ToolAdapterCore,
     discovered_tools: &mut Vec<ToolRuntimePayload>,
     whitelist: &mut ToolWhitelistStore,
+    labels: &mut ToolLabelStore,
     controllers: &mut ControllerDevicesStore,
     chat_runtime: &mut ChatRuntime,
     chat_event_tx: &ChatEventSender,
     report_runtime: &mut ReportRuntime,
     report_event_tx: &ReportEventSender,
+    details_now: &DetailsNowRuntime,
     command_envelope: SidecarCommandEnvelope,
     details_scheduler: &mut QueueScheduler<DetailsRefreshIntent>,
     latest_details_generation: &mut u64,
+    details_focus: &mut DetailsFocusState,
+    app_viewers: &mut AppViewers,
+    command_acks: &mut CommandAckTracker,
+    session_diagnostics: &SessionDiagnostics,
 ) -> Result<bool> {
+    if command_envelope.ack_required {
+        let payload = AckPayload {
+            event_id: command_envelope.event_id.clone(),
+            event_type: command_envelope.event_type.clone(),
+        };
+        send_event(
+            ws_writer,
+            &cfg.system_id,
+            ACK_EVENT_TYPE,
+            Some(&command_envelope.trace_id),
+            serde_json::to_value(payload)?,
+        )
+        .await?;
+        // relay 超时重发的重复命令只回 ACK，不再执行。
+        if !command_acks.first_seen(&command_envelope.event_id) {
+            debug!(
+                "skip duplicate acked command type={} event_id={}",
+                command_envelope.event_type, command_envelope.event_id
+            );
+            return Ok(false);
+        }
+    }
+
+    let from_app = command_envelope.source_client_type == "app";
+    let device_id = command_envelope.source_device_id.trim().to_string();
+    if matches!(command_envelope.command, SidecarCommand::AppDisconnect) {
+        if from_app && !device_id.is_empty() {
+            apply_viewer_signal(
+                app_viewers,
+                ViewerSignal::Disconnect { device_id },
+                details_scheduler,
+                latest_details_generation,
+                details_focus,
+            );
+        }
+        return Ok(false);
+    }
+    if from_app && !device_id.is_empty() {
+        apply_viewer_signal(
+            app_viewers,
+            ViewerSignal::Command { device_id },
+            details_scheduler,
+            latest_details_generation,
+            details_focus,
+        );
+    }
+
+    let trace_id = command_envelope.trace_id.clone();
     let outcome = handle_sidecar_command(
         SidecarCommandContext {
             ws_writer,
             cfg,
-            seq,
             discovered_tools,
             whitelist,
+            labels,
             controllers,
             chat_runtime,
             chat_event_tx,
             report_runtime,
             report_event_tx,
+            details_now,
+            metrics_history,
         },
         command_envelope,
     )
@@ -119,11 +200,29 @@ async fn handle_command_envelope(
         send_snapshots(
             ws_writer,
             cfg,
-            seq,
             sys,
             started_at,
+            net_rate,
+            metrics_smoother,
             discovered_tools,
             whitelist,
+            labels,
+        )
+        .await?;
+    }
+
+    if let Some(change) = outcome.focus_change {
+        apply_focus_change(details_focus, change);
+    }
+
+    if outcome.send_diagnostics {
+        send_diagnostics(
+            ws_writer,
+            cfg,
+            Some(&trace_id),
+            SidecarDiagnosticsTrigger::Request,
+            session_diagnostics,
+            details_scheduler,
         )
         .await?;
     }
@@ -145,6 +244,86 @@ async fn handle_command_envelope(
     Ok(dispatch_now)
 }
 
+/// 下发一次 `sidecar_diagnostics`（详情队列 + 发送队列 + 详情 worker 耗时）。
+async fn send_diagnostics(
+    ws_writer: &mut RelayWriter,
+    cfg: &Config,
+    trace_id: Option<&str>,
+    trigger: SidecarDiagnosticsTrigger,
+    session_diagnostics: &SessionDiagnostics,
+    details_scheduler: &QueueScheduler<DetailsRefreshIntent>,
+) -> Result<()> {
+    let payload = session_diagnostics.payload(
+        trigger,
+        &details_scheduler.stats(),
+        &ws_writer.queue_stats(),
+    );
+    send_event(
+        ws_writer,
+        &cfg.system_id,
+        SIDECAR_DIAGNOSTICS_EVENT,
+        trace_id,
+        serde_json::to_value(payload)?,
+    )
+    .await
+}
+
+/// 应用观看者信号：最后一个 App 离开时暂停周期采集并取消聚焦，重新出现 App 时立即补采一轮详情。
+/// 返回是否需要立即派发详情刷新。
+fn apply_viewer_signal(
+    app_viewers: &mut AppViewers,
+    signal: ViewerSignal,
+    details_scheduler: &mut QueueScheduler<DetailsRefreshIntent>,
+    latest_details_generation: &mut u64,
+    details_focus: &mut DetailsFocusState,
+) -> bool {
+    debug!("app viewer signal {signal:?}");
+    match app_viewers.apply(signal) {
+        ViewerTransition::Unchanged => false,
+        ViewerTransition::Suspended => {
+            info!("no app viewers, periodic details and metrics suspended");
+            apply_focus_change(details_focus, DetailsFocusChange::Unfocus);
+            false
+        }
+        ViewerTransition::Resumed => {
+            info!(
+                "app viewers={} present, periodic details and metrics resumed",
+                app_viewers.count()
+            );
+            enqueue_details_refresh(
+                details_scheduler,
+                latest_details_generation,
+                None,
+                false,
+                None,
+                ToolDetailsRefreshPriority::Background,
+                ToolDetailsSnapshotTrigger::Periodic,
+            );
+            true
+        }
+    }
+}
+
+/// 把命令产生的聚焦变更落到会话聚焦状态上。
+fn apply_focus_change(details_focus: &mut DetailsFocusState, change: DetailsFocusChange) {
+    let changed = match &change {
+        DetailsFocusChange::Focus(tool_id) => {
+            let changed = details_focus.focus(tool_id);
+            // 聚焦命令本身已触发一次立即补采，这里记作首轮，避免紧接着重复采集。
+            let _ = details_focus.take_due(Instant::now());
+            changed
+        }
+        DetailsFocusChange::Unfocus => details_focus.unfocus(),
+        DetailsFocusChange::UnfocusTool(tool_id) => details_focus.unfocus_tool(tool_id),
+    };
+    if changed {
+        info!(
+            "details focus updated change={change:?} interval_ms={}",
+            details_focus.interval().as_millis()
+        );
+    }
+}
+
 /// 判定是否应进入高优先级控制队列。
 fn is_priority_command(command: &SidecarCommandEnvelope) -> bool {
     matches!(
@@ -152,6 +331,7 @@ fn is_priority_command(command: &SidecarCommandEnvelope) -> bool {
         SidecarCommand::ToolChatRequest { .. }
             | SidecarCommand::ToolChatCancel { .. }
             | SidecarCommand::ToolReportFetchRequest { .. }
+            | SidecarCommand::GetToolDetailsNow { .. }
     )
 }
 
@@ -175,6 +355,7 @@ fn enqueue_details_refresh(
         trigger,
         queued_at: Instant::now(),
         dropped_refreshes: 0,
+        focus: false,
     };
     let report = scheduler.enqueue(QueueKey::ToolDetails, intent);
     if report.dropped > 0
@@ -184,6 +365,35 @@ fn enqueue_details_refresh(
     }
 }
 
+/// 为聚焦工具入队一次补采。
+///
+/// 聚焦补采不递增 generation，避免把进行中的全量刷新判为过期；
+/// 队列中已有待派发请求时直接跳过，避免覆盖全量刷新。
+fn enqueue_focus_details_refresh(
+    scheduler: &mut QueueScheduler<DetailsRefreshIntent>,
+    latest_generation: u64,
+    tool_id: String,
+) -> bool {
+    if scheduler.depth_for_key(QueueKey::ToolDetails) > 0 {
+        return false;
+    }
+    scheduler.enqueue(
+        QueueKey::ToolDetails,
+        DetailsRefreshIntent {
+            generation: latest_generation,
+            target_tool_id: Some(tool_id),
+            force: true,
+            refresh_id: None,
+            priority: ToolDetailsRefreshPriority::Background,
+            trigger: ToolDetailsSnapshotTrigger::Periodic,
+            queued_at: Instant::now(),
+            dropped_refreshes: 0,
+            focus: true,
+        },
+    );
+    true
+}
+
 /// 从详情队列弹出一个请求并尝试派发给 worker。
 fn dispatch_details_refresh(
     scheduler: &mut QueueScheduler<DetailsRefreshIntent>,
@@ -247,33 +457,74 @@ fn default_queue_policies() -> HashMap<QueueKey, QueuePolicy> {
     ])
 }
 
-/// 维护 relay 会话生命周期，并在断线后执行指数退避重连。
-pub(crate) async fn run_relay_loop(cfg: Config) -> Result<()> {
-    let mut backoff = Duration::from_secs(1);
+/// 维护 relay 会话生命周期，并在断线后按带 jitter 的指数退避重连；
+/// relay 通知停机时在其建议时间之上再叠加 jitter。SIGHUP 重新读取的配置在下次重连时生效；
+/// relay 作废配对令牌后，重连前换用新生成的令牌。
+pub(crate) async fn run_relay_loop(mut cfg: Config) -> Result<()> {
+    let mut backoff = ReconnectBackoff::default();
+    let shutdown_hint = ShutdownHint::default();
+    let pair_rotation = PairTokenRotation::default();
+    let mut reloader = ConfigReloader::new();
+    // 指标历史跨重连保留，App 重连后仍可回放断线前的样本。
+    let mut metrics_history = MetricsHistory::new(cfg.metrics_history_len);
 
     loop {
+        if let Some(next) = reloader.take_pending() {
+            cfg = next;
+        }
+        if pair_rotation.take() {
+            cfg.pair_token = rotate_pair_token();
+            info!("pair token revoked by relay, rotated to a new token");
+        }
         tokio::select! {
             _ = tokio::signal::ctrl_c() => {
                 info!("sidecar-rs shutdown requested");
                 return Ok(());
             }
-            session = run_session(&cfg) => {
+            session = run_session(
+                &cfg,
+                &shutdown_hint,
+                &pair_rotation,
+                &mut metrics_history,
+                &mut reloader,
+            ) => {
                 match session {
-                    Ok(_) => info!("relay session closed"),
+                    Ok(_) => {
+                        info!("relay session closed");
+                        backoff.reset();
+                    }
                     Err(err) => warn!("relay session ended: {err}"),
                 }
             }
         }
 
-        tokio::select! {
-            _ = tokio::signal::ctrl_c() => {
-                info!("sidecar-rs shutdown requested");
-                return Ok(());
+        let jitter = backoff.next_delay();
+        let delay = match shutdown_hint.take() {
+            Some(retry_after) => {
+                let delay = retry_after + jitter;
+                info!(
+                    "relay announced shutdown, reconnect in {}s",
+                    delay.as_secs()
+                );
+                delay
             }
-            _ = tokio::time::sleep(backoff) => {}
-        }
+            None => jitter,
+        };
 
-        backoff = (backoff * 2).min(Duration::from_secs(15));
+        let reconnect = tokio::time::sleep(delay);
+        tokio::pin!(reconnect);
+        loop {
+            tokio::select! {
+                _ = tokio::signal::ctrl_c() => {
+                    info!("sidecar-rs shutdown requested");
+                    return Ok(());
+                }
+                _ = reloader.recv() => {
+                    reloader.reload(&cfg);
+                }
+                _ = &mut reconnect => break,
+            }
+        }
     }
 }
 
@@ -294,34 +545,79 @@ async fn refresh_pairing_banner(cfg: &Config) {
 }
 
 /// 单次 relay 会话：连接、收命令、推送心跳与快照，直到连接中断。
-async fn run_session(cfg: &Config) -> Result<()> {
+async fn run_session(
+    cfg: &Config,
+    shutdown_hint: &ShutdownHint,
+    pair_rotation: &PairTokenRotation,
+    metrics_history: &mut MetricsHistory,
+    reloader: &mut ConfigReloader,
+) -> Result<()> {
     let ws_url = sidecar_ws_url(cfg)?;
     info!("connecting relay {}", ws_url);
 
-    let (ws_stream, _) = connect_async(ws_url.as_str()).await?;
+    let (ws_stream, _) = match connect_async(sidecar_ws_request(&ws_url)?).await {
+        Ok(connected) => connected,
+        Err(err) => {
+            if is_pair_token_revoked_rejection(&err) {
+                pair_rotation.request();
+            }
+            return Err(err.into());
+        }
+    };
     info!("relay connected");
+    let connected_at = Instant::now();
 
     let startup_banner_cfg = cfg.clone();
     tokio::spawn(async move {
         refresh_pairing_banner(&startup_banner_cfg).await;
     });
 
-    let (mut ws_writer, mut ws_reader) = ws_stream.split();
+    let (ws_sink, mut ws_reader) = ws_stream.split();
+    // 写半边交给发送 worker：慢链路只阻塞 worker，周期快照在队列中合并为最新一帧。
+    let (mut ws_writer, mut send_worker) = spawn_send_worker(ws_sink);
     let (high_cmd_tx, mut high_cmd_rx) = mpsc::unbounded_channel::<SidecarCommandEnvelope>();
     let (normal_cmd_tx, mut normal_cmd_rx) = mpsc::unbounded_channel::<SidecarCommandEnvelope>();
     let (chat_event_tx, mut chat_event_rx) = mpsc::unbounded_channel::<chat::ChatEventEnvelope>();
     let (report_event_tx, mut report_event_rx) =
         mpsc::unbounded_channel::<report::ReportEventEnvelope>();
-    let (details_req_tx, mut details_req_rx) = mpsc::channel::<DetailsWorkerRequest>(8);
+    let (details_req_tx, details_req_rx) = mpsc::channel::<DetailsWorkerRequest>(8);
     let (details_event_tx, mut details_event_rx) = mpsc::unbounded_channel::<DetailsWorkerEvent>();
-    let log_raw_payload = raw_payload_logging_enabled();
+    let (details_now_tx, mut details_now_rx) = mpsc::unbounded_channel::<DetailsNowEvent>();
+    let (viewer_tx, mut viewer_rx) = mpsc::unbounded_channel::<ViewerSignal>();
+    let payload_log = PayloadLogMode::from_env();
+    let reader_shutdown_hint = shutdown_hint.clone();
+    let reader_pair_rotation = pair_rotation.clone();
+    let heartbeat_rtt = HeartbeatRtt::default();
+    let reader_heartbeat_rtt = heartbeat_rtt.clone();
 
     // reader_task 专门读取 relay 下行消息，并抽取 sidecar 控制命令。
     let mut reader_task = tokio::spawn(async move {
         while let Some(next) = ws_reader.next().await {
             match next {
                 Ok(Message::Text(text)) => {
-                    if let Some(command) = parse_sidecar_command(&text) {
+                    let text = match inflate_envelope_text(&text) {
+                        Ok(Some(inflated)) => inflated,
+                        Ok(None) => text.to_string(),
+                        Err(err) => {
+                            warn!("drop compressed frame: {err}");
+                            continue;
+                        }
+                    };
+                    if let Some(retry_after) = server_shutdown_retry_after(&text) {
+                        info!(
+                            "relay shutdown notice received, retry_after_sec={}",
+                            retry_after.as_secs()
+                        );
+                        reader_shutdown_hint.set(retry_after);
+                    } else if is_pair_token_revoked_notice(&text) {
+                        info!("relay revoked pair token, reconnecting with a new token");
+                        reader_pair_rotation.request();
+                        break;
+                    } else if let Some(signal) = parse_viewer_signal(&text) {
+                        if viewer_tx.send(signal).is_err() {
+                            break;
+                        }
+                    } else if let Some(command) = parse_sidecar_command(&text) {
                         debug!(
                             "incoming command type={} event_id={} trace_id={} source_type={} source_device={}",
                             command.event_type,
@@ -338,12 +634,13 @@ async fn run_session(cfg: &Config) -> Result<()> {
                         if target.send(command).is_err() {
                             break;
                         }
-                    } else if log_raw_payload {
-                        debug!("incoming raw: {text}");
                     } else {
-                        debug!("incoming event: {}", summarize_wire_payload(&text));
+                        debug!("{}", payload_log.render(&text));
                     }
                 }
+                Ok(Message::Pong(payload)) => {
+                    reader_heartbeat_rtt.on_pong(&payload, Instant::now());
+                }
                 Ok(_) => {}
                 Err(err) => {
                     warn!("relay read error: {err}");
@@ -352,78 +649,21 @@ async fn run_session(cfg: &Config) -> Result<()> {
             }
         }
     });
-    let details_worker_cfg = cfg.clone();
-    let mut details_worker = tokio::spawn(async move {
-        let mut details_core = ToolAdapterCore::new(
-            details_worker_cfg.fallback_tool,
-            details_worker_cfg.details_interval,
-            details_worker_cfg.details_command_timeout,
-            details_worker_cfg.details_max_parallel,
-            details_worker_cfg.details_refresh_debounce,
-        );
-        while let Some(first_request) = details_req_rx.recv().await {
-            let mut active = first_request;
-            let mut dropped_refreshes = active.intent.dropped_refreshes;
-            while let Ok(next_request) = details_req_rx.try_recv() {
-                dropped_refreshes = dropped_refreshes
-                    .saturating_add(1)
-                    .saturating_add(next_request.intent.dropped_refreshes);
-                active = next_request;
-            }
-            active.intent.dropped_refreshes = dropped_refreshes;
-            let queue_wait_ms = active
-                .intent
-                .queued_at
-                .elapsed()
-                .as_millis()
-                .min(u64::MAX as u128) as u64;
-            let generation = active.intent.generation;
-            let refresh_id = active.intent.refresh_id.clone();
-            let target_tool_id = active.intent.target_tool_id.clone();
-            let trigger = active.intent.trigger;
-            let connected_tools_count = active.collect_request.tools.len();
-
-            let cache_details = details_core.cached_details_snapshot(&active.collect_request.tools);
-            if !cache_details.is_empty() {
-                let _ = details_event_tx.send(DetailsWorkerEvent {
-                    generation,
-                    refresh_id: refresh_id.clone(),
-                    trigger: ToolDetailsSnapshotTrigger::Cache,
-                    target_tool_id: target_tool_id.clone(),
-                    details: cache_details,
-                    queue_wait_ms,
-                    collect_ms: 0,
-                    dropped_refreshes,
-                    connected_tools_count,
-                });
-            }
-
-            let collect_started_at = Instant::now();
-            let details = details_core
-                .collect_details_snapshot(active.collect_request)
-                .await;
-            let collect_ms = collect_started_at
-                .elapsed()
-                .as_millis()
-                .min(u64::MAX as u128) as u64;
-            let _ = details_event_tx.send(DetailsWorkerEvent {
-                generation,
-                refresh_id,
-                trigger,
-                target_tool_id,
-                details,
-                queue_wait_ms,
-                collect_ms,
-                dropped_refreshes,
-                connected_tools_count,
-            });
-        }
-    });
+    let details_req_rx = Arc::new(Mutex::new(details_req_rx));
+    let collection_errors = CollectionErrorTracker::shared(cfg.collection_errors_window);
+    let mut details_worker = spawn_details_worker(
+        new_details_core(cfg, &collection_errors),
+        details_req_rx.clone(),
+        details_event_tx.clone(),
+    );
+    let mut details_worker_restarts = 0_u32;
+    let details_now = DetailsNowRuntime::new(cfg, collection_errors.clone(), details_now_tx);
 
-    let mut seq = 0_u64;
     let mut details_snapshot_id = 0_u64;
     let started_at = Instant::now();
     let mut sys = System::new_all();
+    let mut net_rate = NetworkRateTracker::new();
+    let mut metrics_smoother = MetricsSmoother::new(cfg.metrics_smoothing_alpha);
     let mut discover_core = ToolAdapterCore::new(
         cfg.fallback_tool,
         cfg.details_interval,
@@ -432,7 +672,9 @@ async fn run_session(cfg: &Config) -> Result<()> {
         cfg.details_refresh_debounce,
     );
     let mut whitelist = ToolWhitelistStore::load();
-    let mut controllers = ControllerDevicesStore::load();
+    let mut labels = ToolLabelStore::load();
+    let mut controllers = ControllerDevicesStore::load(cfg.controller_bind_policy);
+    let mut whitelist_pruner = WhitelistPruner::new(cfg.whitelist_prune, cfg.whitelist_prune_grace);
     let mut chat_runtime = ChatRuntime::default();
     let mut report_runtime = ReportRuntime::default();
     if let Err(err) = controllers.seed(&cfg.controller_device_ids) {
@@ -442,17 +684,30 @@ async fn run_session(cfg: &Config) -> Result<()> {
     let mut details_scheduler =
         QueueScheduler::new(QueuePolicy::fifo(256), default_queue_policies());
     let mut latest_details_generation = 0_u64;
+    let mut details_focus = DetailsFocusState::new(cfg.details_focus_interval);
+    let mut app_viewers = AppViewers::default();
+    let mut command_acks = CommandAckTracker::default();
+    let mut session_diagnostics = SessionDiagnostics::new(connected_at);
 
-    send_snapshots(
+    let initial_snapshots = send_snapshots(
         &mut ws_writer,
         cfg,
-        &mut seq,
         &mut sys,
         started_at,
+        &mut net_rate,
+        &mut metrics_smoother,
         &discovered_tools,
         &whitelist,
+        &labels,
     )
     .await?;
+    let mut session_ready = SessionReadyTracker::new(
+        connected_at,
+        initial_snapshots.tool_count,
+        initial_snapshots.candidate_count,
+    );
+    let session_ready_timeout = tokio::time::sleep(SESSION_READY_TIMEOUT);
+    tokio::pin!(session_ready_timeout);
     enqueue_details_refresh(
         &mut details_scheduler,
         &mut latest_details_generation,
@@ -472,8 +727,11 @@ async fn run_session(cfg: &Config) -> Result<()> {
     let mut heartbeat_ticker = tokio::time::interval(cfg.heartbeat_interval);
     heartbeat_ticker.set_missed_tick_behavior(MissedTickBehavior::Skip);
 
-    let mut metrics_ticker = tokio::time::interval(cfg.metrics_interval);
-    metrics_ticker.set_missed_tick_behavior(MissedTickBehavior::Skip);
+    let mut metrics_ticker = MetricsTicker::new(cfg.metrics_interval, cfg.metrics_adaptive);
+    let mut discovery_ticker = tokio::time::interval(cfg.discovery_interval);
+    discovery_ticker.set_missed_tick_behavior(MissedTickBehavior::Skip);
+    // 跳过首次立即触发：连接时已发送首轮工具快照。
+    discovery_ticker.tick().await;
     let mut pairing_banner_ticker = tokio::time::interval(cfg.pairing_banner_refresh_interval);
     pairing_banner_ticker.set_missed_tick_behavior(MissedTickBehavior::Skip);
     // 跳过 interval 的首次“立即触发”，避免连接后重复打印两次 banner。
@@ -485,6 +743,16 @@ async fn run_session(cfg: &Config) -> Result<()> {
     details_dispatch_ticker.set_missed_tick_behavior(MissedTickBehavior::Skip);
     // 跳过首次立即触发，避免连接瞬间重复跑一次详情。
     details_dispatch_ticker.tick().await;
+    let mut details_focus_ticker = tokio::time::interval(details_focus.interval());
+    details_focus_ticker.set_missed_tick_behavior(MissedTickBehavior::Skip);
+    let mut collection_errors_ticker = tokio::time::interval(cfg.collection_errors_window);
+    collection_errors_ticker.set_missed_tick_behavior(MissedTickBehavior::Skip);
+    // 跳过首次立即触发，首个摘要覆盖完整窗口。
+    collection_errors_ticker.tick().await;
+    let mut diagnostics_ticker = tokio::time::interval(cfg.diagnostics_interval);
+    diagnostics_ticker.set_missed_tick_behavior(MissedTickBehavior::Skip);
+    // 跳过首次立即触发，连接瞬间的队列状态没有参考价值。
+    diagnostics_ticker.tick().await;
 
     loop {
         tokio::select! {
@@ -493,24 +761,68 @@ async fn run_session(cfg: &Config) -> Result<()> {
                 chat_runtime.abort_all();
                 report_runtime.abort_all();
                 details_worker.abort();
+                send_worker.abort();
                 return Ok(());
             },
             done = &mut reader_task => {
                 chat_runtime.abort_all();
                 report_runtime.abort_all();
                 details_worker.abort();
+                send_worker.abort();
                 match done {
                     Ok(_) => return Err(anyhow!("relay read loop closed")),
                     Err(err) => return Err(anyhow!("relay read task join error: {err}")),
                 }
             }
-            done = &mut details_worker => {
+            done = &mut send_worker => {
                 chat_runtime.abort_all();
                 report_runtime.abort_all();
+                details_worker.abort();
                 match done {
-                    Ok(_) => return Err(anyhow!("details worker exited unexpectedly")),
-                    Err(err) => return Err(anyhow!("details worker join error: {err}")),
+                    Ok(Ok(())) => return Err(anyhow!("relay send worker exited")),
+                    Ok(Err(err)) => return Err(err),
+                    Err(err) => return Err(anyhow!("relay send worker join error: {err}")),
+                }
+            }
+            done = &mut details_worker => {
+                let reason = match done {
+                    Ok(_) => "exited unexpectedly".to_string(),
+                    Err(err) => format!("join error: {err}"),
+                };
+                if details_worker_restarts >= DETAILS_WORKER_MAX_RESTARTS {
+                    chat_runtime.abort_all();
+                    report_runtime.abort_all();
+                    return Err(anyhow!(
+                        "details worker {reason}, restart limit {DETAILS_WORKER_MAX_RESTARTS} reached"
+                    ));
                 }
+                details_worker_restarts = details_worker_restarts.saturating_add(1);
+                session_diagnostics.record_details_worker_restart();
+                warn!(
+                    "details worker {reason}, respawning attempt={}/{}",
+                    details_worker_restarts, DETAILS_WORKER_MAX_RESTARTS
+                );
+                details_worker = spawn_details_worker(
+                    new_details_core(cfg, &collection_errors),
+                    details_req_rx.clone(),
+                    details_event_tx.clone(),
+                );
+                // 重启后补采一次全量详情，避免崩溃期间丢失的刷新让客户端停留在旧数据。
+                enqueue_details_refresh(
+                    &mut details_scheduler,
+                    &mut latest_details_generation,
+                    None,
+                    true,
+                    None,
+                    ToolDetailsRefreshPriority::Background,
+                    ToolDetailsSnapshotTrigger::Command,
+                );
+                dispatch_details_refresh(
+                    &mut details_scheduler,
+                    &details_req_tx,
+                    &discovered_tools,
+                    &whitelist,
+                )?;
             }
             maybe_cmd = high_cmd_rx.recv() => {
                 let Some(command_envelope) = maybe_cmd else {
@@ -519,20 +831,28 @@ async fn run_session(cfg: &Config) -> Result<()> {
                 let dispatch_now = handle_command_envelope(
                     &mut ws_writer,
                     cfg,
-                    &mut seq,
                     &mut sys,
                     started_at,
+                    &mut net_rate,
+                    &mut metrics_smoother,
+                    metrics_history,
                     &mut discover_core,
                     &mut discovered_tools,
                     &mut whitelist,
+                    &mut labels,
                     &mut controllers,
                     &mut chat_runtime,
                     &chat_event_tx,
                     &mut report_runtime,
                     &report_event_tx,
+                    &details_now,
                     command_envelope,
                     &mut details_scheduler,
                     &mut latest_details_generation,
+                    &mut details_focus,
+                    &mut app_viewers,
+                    &mut command_acks,
+                    &session_diagnostics,
                 )
                 .await?;
                 if dispatch_now {
@@ -551,20 +871,28 @@ async fn run_session(cfg: &Config) -> Result<()> {
                 let dispatch_now = handle_command_envelope(
                     &mut ws_writer,
                     cfg,
-                    &mut seq,
                     &mut sys,
                     started_at,
+                    &mut net_rate,
+                    &mut metrics_smoother,
+                    metrics_history,
                     &mut discover_core,
                     &mut discovered_tools,
                     &mut whitelist,
+                    &mut labels,
                     &mut controllers,
                     &mut chat_runtime,
                     &chat_event_tx,
                     &mut report_runtime,
                     &report_event_tx,
+                    &details_now,
                     command_envelope,
                     &mut details_scheduler,
                     &mut latest_details_generation,
+                    &mut details_focus,
+                    &mut app_viewers,
+                    &mut command_acks,
+                    &session_diagnostics,
                 )
                 .await?;
                 if dispatch_now {
@@ -576,6 +904,25 @@ async fn run_session(cfg: &Config) -> Result<()> {
                     )?;
                 }
             }
+            maybe_signal = viewer_rx.recv() => {
+                let Some(signal) = maybe_signal else {
+                    continue;
+                };
+                if apply_viewer_signal(
+                    &mut app_viewers,
+                    signal,
+                    &mut details_scheduler,
+                    &mut latest_details_generation,
+                    &mut details_focus,
+                ) {
+                    dispatch_details_refresh(
+                        &mut details_scheduler,
+                        &details_req_tx,
+                        &discovered_tools,
+                        &whitelist,
+                    )?;
+                }
+            }
             maybe_chat_event = chat_event_rx.recv() => {
                 let Some(chat_event) = maybe_chat_event else {
                     continue;
@@ -586,7 +933,6 @@ async fn run_session(cfg: &Config) -> Result<()> {
                 send_event(
                     &mut ws_writer,
                     &cfg.system_id,
-                    &mut seq,
                     chat_event.event_type,
                     chat_event.trace_id.as_deref(),
                     chat_event.payload,
@@ -602,12 +948,23 @@ async fn run_session(cfg: &Config) -> Result<()> {
                 send_event(
                     &mut ws_writer,
                     &cfg.system_id,
-                    &mut seq,
                     report_event.event_type,
                     report_event.trace_id.as_deref(),
                     report_event.payload,
                 ).await?;
             }
+            maybe_details_now = details_now_rx.recv() => {
+                let Some(details_now_event) = maybe_details_now else {
+                    continue;
+                };
+                send_event(
+                    &mut ws_writer,
+                    &cfg.system_id,
+                    TOOL_DETAILS_NOW_RESULT_EVENT,
+                    details_now_event.trace_id.as_deref(),
+                    details_now_event.payload,
+                ).await?;
+            }
             maybe_details_event = details_event_rx.recv() => {
                 let Some(details_event) = maybe_details_event else {
                     continue;
@@ -624,6 +981,17 @@ async fn run_session(cfg: &Config) -> Result<()> {
                     continue;
                 }
                 if details_event.details.is_empty() && details_event.connected_tools_count == 0 {
+                    // 没有已接入工具时不下发详情快照，首轮结果即可视为就绪。
+                    if let Some(ready) = session_ready.details_delivered(details_event.trigger, 0) {
+                        send_event(
+                            &mut ws_writer,
+                            &cfg.system_id,
+                            SESSION_READY_EVENT,
+                            None,
+                            serde_json::to_value(ready)?,
+                        )
+                        .await?;
+                    }
                     continue;
                 }
 
@@ -632,7 +1000,6 @@ async fn run_session(cfg: &Config) -> Result<()> {
                 send_tool_details_snapshot(
                     &mut ws_writer,
                     &cfg.system_id,
-                    &mut seq,
                     &details_event.details,
                     ToolDetailsSnapshotMeta {
                         snapshot_id: details_snapshot_id,
@@ -647,6 +1014,11 @@ async fn run_session(cfg: &Config) -> Result<()> {
                 )
                 .await?;
                 let send_ms = send_started_at.elapsed().as_millis().min(u64::MAX as u128) as u64;
+                session_diagnostics.record_details_snapshot(
+                    details_event.queue_wait_ms,
+                    details_event.collect_ms,
+                    details_event.dropped_refreshes,
+                );
                 debug!(
                     concat!(
                         "tool details snapshot sent snapshot_id={} generation={} trigger={:?} ",
@@ -664,32 +1036,110 @@ async fn run_session(cfg: &Config) -> Result<()> {
                     send_ms,
                     details_event.dropped_refreshes,
                 );
+                if let Some(ready) =
+                    session_ready.details_delivered(details_event.trigger, details_event.details.len())
+                {
+                    send_event(
+                        &mut ws_writer,
+                        &cfg.system_id,
+                        SESSION_READY_EVENT,
+                        None,
+                        serde_json::to_value(ready)?,
+                    )
+                    .await?;
+                }
+            }
+            _ = &mut session_ready_timeout, if session_ready.is_pending() => {
+                if let Some(ready) = session_ready.timed_out() {
+                    warn!("session ready timed out waiting for details snapshot");
+                    send_event(
+                        &mut ws_writer,
+                        &cfg.system_id,
+                        SESSION_READY_EVENT,
+                        None,
+                        serde_json::to_value(ready)?,
+                    )
+                    .await?;
+                }
             }
             _ = heartbeat_ticker.tick() => {
                 send_event(
                     &mut ws_writer,
                     &cfg.system_id,
-                    &mut seq,
                     "heartbeat",
                     None,
                     json!({
                         "status": "ONLINE",
-                        "latencyMs": 0,
+                        "latencyMs": heartbeat_rtt.latency_ms(Instant::now()),
                     }),
                 ).await?;
+                // 下一次心跳上报本次探测的往返时延。
+                ws_writer
+                    .send(OutboundFrame::Raw(heartbeat_rtt.start_probe(Instant::now())))
+                    .await?;
             }
-            _ = metrics_ticker.tick() => {
+            _ = reloader.recv() => {
+                if let Some(next) = reloader.reload(cfg)
+                    && apply_store_reload(next, &mut whitelist, &mut controllers)
+                {
+                    // 白名单变化后立即重跑一轮发现，让快照与详情按新白名单下发。
+                    discovery_ticker.reset_immediately();
+                }
+            }
+            _ = discovery_ticker.tick() => {
                 discovered_tools = discover_core.discover_tools(&mut sys);
-                send_snapshots(
-                    &mut ws_writer,
+                let pruned = whitelist_pruner.sweep(
+                    &mut whitelist,
+                    discovered_tools.iter().map(|tool| tool.tool_id.as_str()),
+                    Instant::now(),
+                );
+                for feedback in pruned {
+                    if feedback.ok {
+                        apply_focus_change(
+                            &mut details_focus,
+                            DetailsFocusChange::UnfocusTool(feedback.target.clone()),
+                        );
+                    }
+                    send_event(
+                        &mut ws_writer,
+                        &cfg.system_id,
+                        TOOL_WHITELIST_UPDATED_EVENT,
+                        None,
+                        feedback.to_value(),
+                    )
+                    .await?;
+                }
+                let mut batch = ws_writer.coalescing(QueueKey::ToolsRefresh);
+                send_tool_snapshots(
+                    &mut batch,
+                    cfg,
+                    &discovered_tools,
+                    &whitelist,
+                    &labels,
+                )
+                .await?;
+                ws_writer.commit_latest(batch)?;
+            }
+            _ = metrics_ticker.tick() => {
+                if !app_viewers.has_viewers() {
+                    continue;
+                }
+                let mut batch = ws_writer.coalescing(QueueKey::Metrics);
+                let metrics = send_metrics_snapshot(
+                    &mut batch,
                     cfg,
-                    &mut seq,
                     &mut sys,
                     started_at,
+                    &mut net_rate,
+                    &mut metrics_smoother,
                     &discovered_tools,
                     &whitelist,
+                    &labels,
                 )
                 .await?;
+                ws_writer.commit_latest(batch)?;
+                metrics_ticker.observe(&metrics.system);
+                metrics_history.push(metrics);
             }
             _ = pairing_banner_ticker.tick() => {
                 let refresh_cfg = cfg.clone();
@@ -698,6 +1148,10 @@ async fn run_session(cfg: &Config) -> Result<()> {
                 });
             }
             _ = details_ticker.tick() => {
+                // 没有 App 观看时跳过周期采集，下一个 App 接入时恢复。
+                if !app_viewers.has_viewers() {
+                    continue;
+                }
                 enqueue_details_refresh(
                     &mut details_scheduler,
                     &mut latest_details_generation,
@@ -708,6 +1162,23 @@ async fn run_session(cfg: &Config) -> Result<()> {
                     ToolDetailsSnapshotTrigger::Periodic,
                 );
             }
+            _ = details_focus_ticker.tick() => {
+                let Some(tool_id) = details_focus.take_due(Instant::now()) else {
+                    continue;
+                };
+                if enqueue_focus_details_refresh(
+                    &mut details_scheduler,
+                    latest_details_generation,
+                    tool_id,
+                ) {
+                    dispatch_details_refresh(
+                        &mut details_scheduler,
+                        &details_req_tx,
+                        &discovered_tools,
+                        &whitelist,
+                    )?;
+                }
+            }
             _ = details_dispatch_ticker.tick() => {
                 dispatch_details_refresh(
                     &mut details_scheduler,
@@ -716,10 +1187,49 @@ async fn run_session(cfg: &Config) -> Result<()> {
                     &whitelist,
                 )?;
             }
+            _ = collection_errors_ticker.tick() => {
+                let summary = collection_errors
+                    .lock()
+                    .unwrap_or_else(|poisoned| poisoned.into_inner())
+                    .take_summary();
+                if let Some(summary) = summary {
+                    send_event(
+                        &mut ws_writer,
+                        &cfg.system_id,
+                        COLLECTION_ERRORS_EVENT,
+                        None,
+                        serde_json::to_value(summary)?,
+                    )
+                    .await?;
+                }
+            }
+            _ = diagnostics_ticker.tick() => {
+                send_diagnostics(
+                    &mut ws_writer,
+                    cfg,
+                    None,
+                    SidecarDiagnosticsTrigger::Periodic,
+                    &session_diagnostics,
+                    &details_scheduler,
+                )
+                .await?;
+            }
         }
     }
 }
 
+/// 按配置创建详情采集用的适配器核心（worker 重启时重新创建，错误汇总跨重启保留）。
+fn new_details_core(cfg: &Config, collection_errors: &SharedCollectionErrors) -> ToolAdapterCore {
+    ToolAdapterCore::new(
+        cfg.fallback_tool,
+        cfg.details_interval,
+        cfg.details_command_timeout,
+        cfg.details_max_parallel,
+        cfg.details_refresh_debounce,
+    )
+    .with_collection_errors(collection_errors.clone())
+}
+
 /// 基于当前发现结果和白名单，组装一次详情采集请求。
 fn build_details_collect_request(
     discovered_tools: &[ToolRuntimePayload],
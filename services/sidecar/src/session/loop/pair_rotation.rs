@@ -0,0 +1,98 @@
+//! 配对令牌作废：relay 推送 `pair_token_revoked`，或以 `PAIR_TOKEN_REVOKED` 拒绝握手时，
+//! 结束本次会话，重连前生成并持久化新的 pairToken，relay 以新令牌轮换房间。
+
+use std::sync::{
+    Arc,
+    atomic::{AtomicBool, Ordering},
+};
+
+use serde_json::Value;
+use tokio_tungstenite::tungstenite;
+
+/// relay 配对令牌作废通知事件类型。
+const PAIR_TOKEN_REVOKED_EVENT: &str = "pair_token_revoked";
+/// relay 拒绝已作废令牌握手时的错误码。
+const PAIR_TOKEN_REVOKED_CODE: &str = "PAIR_TOKEN_REVOKED";
+
+/// 判断下行文本是否为配对令牌作废通知。
+pub(super) fn is_pair_token_revoked_notice(raw: &str) -> bool {
+    serde_json::from_str::<Value>(raw)
+        .ok()
+        .and_then(|event| {
+            event
+                .get("type")
+                .and_then(Value::as_str)
+                .map(|event_type| event_type == PAIR_TOKEN_REVOKED_EVENT)
+        })
+        .unwrap_or(false)
+}
+
+/// 判断握手失败是否因 pairToken 已作废。
+pub(super) fn is_pair_token_revoked_rejection(err: &tungstenite::Error) -> bool {
+    let tungstenite::Error::Http(response) = err else {
+        return false;
+    };
+    response.status() == tungstenite::http::StatusCode::UNAUTHORIZED
+        && response
+            .body()
+            .as_deref()
+            .is_some_and(|body| String::from_utf8_lossy(body).contains(PAIR_TOKEN_REVOKED_CODE))
+}
+
+/// 跨会话共享的换令牌请求：reader 任务或握手失败时写入，重连循环读取后清空。
+#[derive(Debug, Clone, Default)]
+pub(super) struct PairTokenRotation {
+    /// 是否需要在下次重连前换新令牌。
+    requested: Arc<AtomicBool>,
+}
+
+impl PairTokenRotation {
+    /// 记录一次换令牌请求。
+    pub(super) fn request(&self) {
+        self.requested.store(true, Ordering::Relaxed);
+    }
+
+    /// 取出并清空换令牌请求。
+    pub(super) fn take(&self) -> bool {
+        self.requested.swap(false, Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio_tungstenite::tungstenite::{self, http::Response};
+
+    use super::{PairTokenRotation, is_pair_token_revoked_notice, is_pair_token_revoked_rejection};
+
+    #[test]
+    fn revoke_notice_and_handshake_rejection_request_rotation() {
+        assert!(is_pair_token_revoked_notice(
+            r#"{"type":"pair_token_revoked","payload":{"reason":"pair_token_revoked"}}"#
+        ));
+        assert!(!is_pair_token_revoked_notice(
+            r#"{"type":"session_revoked","payload":{}}"#
+        ));
+
+        let rejection = |status: u16, body: &str| {
+            tungstenite::Error::Http(Box::new(
+                Response::builder()
+                    .status(status)
+                    .body(Some(body.as_bytes().to_vec()))
+                    .expect("response"),
+            ))
+        };
+        assert!(is_pair_token_revoked_rejection(&rejection(
+            401,
+            "PAIR_TOKEN_REVOKED: pairToken 已作废"
+        )));
+        assert!(!is_pair_token_revoked_rejection(&rejection(
+            401,
+            "PAIR_TOKEN_MISMATCH: pairToken 不匹配"
+        )));
+
+        let rotation = PairTokenRotation::default();
+        rotation.clone().request();
+        assert!(rotation.take());
+        assert!(!rotation.take());
+    }
+}
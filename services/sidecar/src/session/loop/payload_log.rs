@@ -0,0 +1,136 @@
+//! 下行 payload 日志职责：
+//! 1. 按环境变量决定 reader 任务对非命令帧的日志粒度：摘要（默认）、脱敏原文或完整原文。
+//! 2. `SIDECAR_LOG_REDACT_KEYS` 配置后逐层遍历 JSON，把命中的字段值（不区分大小写）替换为 `***`，
+//!    便于排查 envelope 结构而不泄露消息正文；同时开启 `YC_DEBUG_RAW_PAYLOAD` 时以脱敏为准。
+//! 3. 无法解析为 JSON 的帧在脱敏模式下只记录摘要，避免把未知内容原样写入日志。
+
+use std::collections::HashSet;
+
+use serde_json::Value;
+
+use super::url::raw_payload_logging_enabled;
+use crate::session::snapshots::summarize_wire_payload;
+
+/// 需脱敏字段列表环境变量（逗号分隔）。
+const LOG_REDACT_KEYS_ENV: &str = "SIDECAR_LOG_REDACT_KEYS";
+/// 脱敏后的占位值。
+const REDACTED: &str = "***";
+
+/// 下行帧日志模式。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) enum PayloadLogMode {
+    /// 只记录事件摘要。
+    Summary,
+    /// 记录原文，命中字段（小写）替换为占位值。
+    Redacted(HashSet<String>),
+    /// 记录完整原文。
+    Raw,
+}
+
+impl PayloadLogMode {
+    /// 按环境变量解析日志模式。
+    pub(super) fn from_env() -> Self {
+        Self::resolve(
+            std::env::var(LOG_REDACT_KEYS_ENV).ok().as_deref(),
+            raw_payload_logging_enabled(),
+        )
+    }
+
+    /// 脱敏字段非空时进入脱敏模式，否则按原文开关决定。
+    fn resolve(redact_keys: Option<&str>, raw_enabled: bool) -> Self {
+        let keys = redact_keys
+            .unwrap_or_default()
+            .split(',')
+            .map(|key| key.trim().to_ascii_lowercase())
+            .filter(|key| !key.is_empty())
+            .collect::<HashSet<_>>();
+        if !keys.is_empty() {
+            Self::Redacted(keys)
+        } else if raw_enabled {
+            Self::Raw
+        } else {
+            Self::Summary
+        }
+    }
+
+    /// 生成下行帧的日志文本。
+    pub(super) fn render(&self, text: &str) -> String {
+        match self {
+            Self::Summary => format!("incoming event: {}", summarize_wire_payload(text)),
+            Self::Raw => format!("incoming raw: {text}"),
+            Self::Redacted(keys) => match serde_json::from_str::<Value>(text) {
+                Ok(mut value) => {
+                    redact_value(&mut value, keys);
+                    format!("incoming redacted: {value}")
+                }
+                Err(_) => format!("incoming event: {}", summarize_wire_payload(text)),
+            },
+        }
+    }
+}
+
+/// 递归替换命中字段的值；命中字段的子树整体替换，不再向下遍历。
+fn redact_value(value: &mut Value, keys: &HashSet<String>) {
+    match value {
+        Value::Object(map) => {
+            for (key, child) in map.iter_mut() {
+                if keys.contains(&key.to_ascii_lowercase()) {
+                    *child = Value::String(REDACTED.to_string());
+                } else {
+                    redact_value(child, keys);
+                }
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                redact_value(item, keys);
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{Value, json};
+
+    use super::PayloadLogMode;
+
+    #[test]
+    fn redacted_mode_masks_nested_keys_and_keeps_envelope_shape() {
+        assert_eq!(
+            PayloadLogMode::resolve(None, false),
+            PayloadLogMode::Summary
+        );
+        assert_eq!(
+            PayloadLogMode::resolve(Some(" , "), true),
+            PayloadLogMode::Raw
+        );
+        let mode = PayloadLogMode::resolve(Some("text, Content,accessToken"), true);
+
+        let frame = json!({
+            "type": "tool_chat_chunk",
+            "eventId": "evt_1",
+            "payload": {
+                "toolId": "codex_a",
+                "Text": "secret reply",
+                "messages": [{"role": "user", "content": {"parts": ["hi"]}}],
+                "auth": {"accessToken": "tok"},
+            },
+        });
+        let line = mode.render(&frame.to_string());
+        let logged: Value =
+            serde_json::from_str(line.strip_prefix("incoming redacted: ").expect("prefix"))
+                .expect("json");
+        assert_eq!(logged["type"], "tool_chat_chunk");
+        assert_eq!(logged["payload"]["toolId"], "codex_a");
+        assert_eq!(logged["payload"]["Text"], "***");
+        assert_eq!(logged["payload"]["messages"][0]["role"], "user");
+        assert_eq!(logged["payload"]["messages"][0]["content"], "***");
+        assert_eq!(logged["payload"]["auth"]["accessToken"], "***");
+        assert!(!line.contains("secret reply"));
+
+        // 非 JSON 帧在脱敏模式下不输出原文。
+        assert!(!mode.render("text=secret").contains("secret"));
+    }
+}
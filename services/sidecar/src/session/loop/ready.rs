@@ -0,0 +1,171 @@
+//! 会话就绪通知：首轮 tools/metrics 快照与首个详情快照（或超时）下发后，发送一次 `session_ready`，
+//! 供 app 确定性地结束加载态。
+
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use yc_shared_protocol::ToolDetailsSnapshotTrigger;
+
+/// 会话就绪事件。
+pub(crate) const SESSION_READY_EVENT: &str = "session_ready";
+/// 等待首个详情快照的上限，超时后以 `pending` 详情状态发送就绪事件。
+pub(super) const SESSION_READY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 就绪时详情数据的状态。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(super) enum SessionReadyDetails {
+    /// 首个详情快照为实时采集结果。
+    Fresh,
+    /// 首个详情快照来自缓存，实时采集仍在进行。
+    Cached,
+    /// 超时前未收到详情快照。
+    Pending,
+}
+
+/// `session_ready` 事件载荷。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(super) struct SessionReadyPayload {
+    /// 首轮 `tools_snapshot` 中的已接入工具数。
+    pub(super) tool_count: usize,
+    /// 首轮 `tools_candidates` 中的候选工具数。
+    pub(super) candidate_count: usize,
+    /// 首个详情快照包含的工具详情数（超时时为 0）。
+    pub(super) details_count: usize,
+    /// 详情数据状态。
+    pub(super) details: SessionReadyDetails,
+    /// 自会话建立到就绪的耗时（毫秒）。
+    pub(super) elapsed_ms: u64,
+}
+
+/// 单会话就绪跟踪器：保证 `session_ready` 至多产出一次。
+#[derive(Debug)]
+pub(super) struct SessionReadyTracker {
+    /// 会话建立时间。
+    started_at: Instant,
+    /// 首轮已接入工具数。
+    tool_count: usize,
+    /// 首轮候选工具数。
+    candidate_count: usize,
+    /// 是否已产出就绪事件。
+    emitted: bool,
+}
+
+impl SessionReadyTracker {
+    /// 首轮快照发送完成后创建跟踪器。
+    pub(super) fn new(started_at: Instant, tool_count: usize, candidate_count: usize) -> Self {
+        Self {
+            started_at,
+            tool_count,
+            candidate_count,
+            emitted: false,
+        }
+    }
+
+    /// 是否仍在等待就绪。
+    pub(super) fn is_pending(&self) -> bool {
+        !self.emitted
+    }
+
+    /// 首个详情快照已下发（或确认无详情可发）；仅第一次调用返回载荷。
+    pub(super) fn details_delivered(
+        &mut self,
+        trigger: ToolDetailsSnapshotTrigger,
+        details_count: usize,
+    ) -> Option<SessionReadyPayload> {
+        let details = if trigger == ToolDetailsSnapshotTrigger::Cache {
+            SessionReadyDetails::Cached
+        } else {
+            SessionReadyDetails::Fresh
+        };
+        self.finish(details, details_count)
+    }
+
+    /// 等待详情超时；仅在尚未就绪时返回载荷。
+    pub(super) fn timed_out(&mut self) -> Option<SessionReadyPayload> {
+        self.finish(SessionReadyDetails::Pending, 0)
+    }
+
+    /// 标记就绪并生成载荷。
+    fn finish(
+        &mut self,
+        details: SessionReadyDetails,
+        details_count: usize,
+    ) -> Option<SessionReadyPayload> {
+        if self.emitted {
+            return None;
+        }
+        self.emitted = true;
+        Some(SessionReadyPayload {
+            tool_count: self.tool_count,
+            candidate_count: self.candidate_count,
+            details_count,
+            details,
+            elapsed_ms: self.started_at.elapsed().as_millis().min(u64::MAX as u128) as u64,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Instant;
+
+    use serde_json::json;
+    use yc_shared_protocol::ToolDetailsSnapshotTrigger;
+
+    use super::{SessionReadyDetails, SessionReadyTracker};
+
+    #[test]
+    fn emits_once_with_initial_counts() {
+        let mut tracker = SessionReadyTracker::new(Instant::now(), 2, 3);
+        assert!(tracker.is_pending());
+
+        let payload = tracker
+            .details_delivered(ToolDetailsSnapshotTrigger::Cache, 2)
+            .expect("first details snapshot marks session ready");
+        assert!(!tracker.is_pending());
+        assert_eq!(payload.details, SessionReadyDetails::Cached);
+        let mut value = serde_json::to_value(&payload).expect("serialize payload");
+        value
+            .as_object_mut()
+            .expect("payload object")
+            .remove("elapsedMs");
+        assert_eq!(
+            value,
+            json!({
+                "toolCount": 2,
+                "candidateCount": 3,
+                "detailsCount": 2,
+                "details": "cached",
+            })
+        );
+
+        // 后续的实时详情与超时都不再重复发送。
+        assert!(
+            tracker
+                .details_delivered(ToolDetailsSnapshotTrigger::Command, 2)
+                .is_none()
+        );
+        assert!(tracker.timed_out().is_none());
+    }
+
+    #[test]
+    fn timeout_reports_pending_details() {
+        let mut tracker = SessionReadyTracker::new(Instant::now(), 1, 0);
+        let payload = tracker.timed_out().expect("timeout marks session ready");
+        assert_eq!(payload.details, SessionReadyDetails::Pending);
+        assert_eq!((payload.tool_count, payload.details_count), (1, 0));
+        assert!(
+            tracker
+                .details_delivered(ToolDetailsSnapshotTrigger::Periodic, 1)
+                .is_none()
+        );
+
+        let mut fresh = SessionReadyTracker::new(Instant::now(), 1, 0);
+        let payload = fresh
+            .details_delivered(ToolDetailsSnapshotTrigger::Command, 1)
+            .expect("fresh details");
+        assert_eq!(payload.details, SessionReadyDetails::Fresh);
+    }
+}
@@ -0,0 +1,125 @@
+//! 配置热加载职责：
+//! 1. 收到 SIGHUP 时重新执行 `Config::from_env`（环境变量 + 持久化配置文件），并记录变化项。
+//! 2. 工具白名单与控制端设备立即重新读取并作用于当前会话；relay 地址等连接参数暂存，下次重连时生效。
+//! 3. 不支持 SIGHUP 的平台（或信号注册失败）永不触发，行为与未启用热加载一致。
+
+#[cfg(unix)]
+use tokio::signal::unix::{Signal, SignalKind, signal};
+use tracing::{info, warn};
+
+use crate::{
+    config::Config,
+    stores::{ControllerDevicesStore, ToolWhitelistStore},
+};
+
+/// 配置热加载器：跨会话持有信号监听与待生效配置。
+pub(super) struct ConfigReloader {
+    /// SIGHUP 监听；为空时永不触发。
+    #[cfg(unix)]
+    signal: Option<Signal>,
+    /// 已重新读取、等待下次重连生效的配置。
+    pending: Option<Config>,
+}
+
+impl ConfigReloader {
+    /// 注册 SIGHUP 监听；注册失败只告警。
+    pub(super) fn new() -> Self {
+        #[cfg(unix)]
+        let signal = match signal(SignalKind::hangup()) {
+            Ok(signal) => Some(signal),
+            Err(err) => {
+                warn!("register SIGHUP handler failed, config reload disabled: {err}");
+                None
+            }
+        };
+        Self {
+            #[cfg(unix)]
+            signal,
+            pending: None,
+        }
+    }
+
+    /// 等待下一次 SIGHUP；不支持时永远挂起。
+    pub(super) async fn recv(&mut self) {
+        #[cfg(unix)]
+        if let Some(signal) = self.signal.as_mut()
+            && signal.recv().await.is_some()
+        {
+            return;
+        }
+        std::future::pending::<()>().await
+    }
+
+    /// 重新读取配置并暂存；以最近一次生效或暂存的配置为基准记录变化。失败时保留原配置。
+    pub(super) fn reload(&mut self, active: &Config) -> Option<&Config> {
+        let next = match Config::from_env() {
+            Ok(next) => next,
+            Err(err) => {
+                warn!("config reload failed, keep current config: {err}");
+                return None;
+            }
+        };
+        let changes = config_changes(self.pending.as_ref().unwrap_or(active), &next);
+        if changes.is_empty() {
+            info!("config reloaded, no changes");
+        } else {
+            info!("config reloaded, changed: {}", changes.join(", "));
+        }
+        self.pending = Some(next);
+        self.pending.as_ref()
+    }
+
+    /// 取出待生效配置（重连前调用）。
+    pub(super) fn take_pending(&mut self) -> Option<Config> {
+        self.pending.take()
+    }
+}
+
+/// 把新配置中的白名单与控制端设备作用于当前会话，返回白名单是否变化。
+pub(super) fn apply_store_reload(
+    cfg: &Config,
+    whitelist: &mut ToolWhitelistStore,
+    controllers: &mut ControllerDevicesStore,
+) -> bool {
+    let whitelist_changed = whitelist.reload();
+    let controllers_changed = controllers.reload(cfg.controller_bind_policy);
+    if let Err(err) = controllers.seed(&cfg.controller_device_ids) {
+        warn!("seed controller devices failed: {err}");
+    }
+    info!(
+        "stores reloaded whitelist_changed={whitelist_changed} controllers_changed={controllers_changed}"
+    );
+    whitelist_changed
+}
+
+/// 列出会影响连接或授权的配置变化；令牌只报告变化，不输出明文。
+fn config_changes(old: &Config, new: &Config) -> Vec<String> {
+    let mut changes = Vec::new();
+    let mut diff = |name: &str, before: String, after: String| {
+        if before != after {
+            changes.push(format!("{name}: {before} -> {after}"));
+        }
+    };
+    diff(
+        "relayWsUrl",
+        old.relay_ws_url.clone(),
+        new.relay_ws_url.clone(),
+    );
+    diff("systemId", old.system_id.clone(), new.system_id.clone());
+    diff("deviceId", old.device_id.clone(), new.device_id.clone());
+    diff("hostName", old.host_name.clone(), new.host_name.clone());
+    diff(
+        "controllerDeviceIds",
+        old.controller_device_ids.join(","),
+        new.controller_device_ids.join(","),
+    );
+    diff(
+        "controllerBindPolicy",
+        format!("{:?}", old.controller_bind_policy),
+        format!("{:?}", new.controller_bind_policy),
+    );
+    if old.pair_token != new.pair_token {
+        changes.push("pairToken".to_string());
+    }
+    changes
+}
@@ -22,6 +22,7 @@ use crate::control::{
     TOOL_REPORT_FETCH_CHUNK_EVENT, TOOL_REPORT_FETCH_FINISHED_EVENT,
     TOOL_REPORT_FETCH_STARTED_EVENT,
 };
+use crate::home::BaseDirs;
 
 const REPORT_ALLOWED_ROOTS_ENV: &str = "YC_REPORT_ALLOWED_ROOTS";
 
@@ -369,7 +370,7 @@ fn resolve_report_allowed_roots(
         if !canonical.is_dir() {
             continue;
         }
-        if allowed_roots.iter().any(|item| *item == canonical) {
+        if allowed_roots.contains(&canonical) {
             continue;
         }
         allowed_roots.push(canonical);
@@ -428,11 +429,8 @@ fn parse_openclaw_profile_key_from_source(source: Option<&str>) -> String {
 }
 
 fn resolve_openclaw_state_dir(profile_key: &str) -> Option<PathBuf> {
-    let home = env::var("HOME")
-        .ok()
-        .map(|value| value.trim().to_string())
-        .filter(|value| !value.is_empty())?;
-    let home = PathBuf::from(home);
+    let base_dirs = BaseDirs::from_env();
+    let home = base_dirs.home_dir().ok()?;
     let normalized = profile_key.trim();
     let path = match normalized {
         "dev" => home.join(".openclaw-dev"),
@@ -0,0 +1,324 @@
+//! Relay 发送 worker：独占 WebSocket 写半边，按 `QueueScheduler` 出队发送。
+//! 普通事件 FIFO 且有背压；指标与工具快照走 latest-wins 槽位，慢链路下只保留最新一帧。
+//! 事件 seq 在出队时分配，被合并丢弃的快照不占用序号，relay 的断档检测不会误报。
+
+use std::{
+    collections::HashMap,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll, Waker},
+};
+
+use anyhow::{Result, anyhow};
+use futures_util::{Sink, SinkExt};
+use tokio::{sync::Notify, task::JoinHandle};
+use tokio_tungstenite::tungstenite::{Error as WsError, Message};
+use tracing::debug;
+
+use crate::session::{
+    queue::{QueueKey, QueueKeyStats, QueuePolicy, QueueScheduler},
+    transport::{OutboundFrame, encode_event_frame},
+};
+
+/// 普通事件积压上限（帧），超过后写端挂起，等价于原先直接写 socket 的背压。
+const OUTBOUND_MAX_PENDING_FRAMES: usize = 256;
+
+/// 写端与 worker 共享的发送队列状态。
+struct OutboundState {
+    /// 待发送帧：普通事件走 `Control`（FIFO），周期快照走 latest-wins 键。
+    queue: QueueScheduler<Vec<OutboundFrame>>,
+    /// 写端已释放或 worker 写失败。
+    closed: bool,
+    /// 因背压挂起的写端。
+    blocked_writer: Option<Waker>,
+}
+
+/// 共享状态与唤醒信号。
+struct OutboundShared {
+    state: Mutex<OutboundState>,
+    notify: Notify,
+}
+
+impl OutboundShared {
+    /// 加锁读取队列状态（锁中毒时继续使用内部数据）。
+    fn lock(&self) -> std::sync::MutexGuard<'_, OutboundState> {
+        self.state.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    /// 唤醒因背压挂起的写端。
+    fn wake_writer(&self) {
+        if let Some(waker) = self.lock().blocked_writer.take() {
+            waker.wake();
+        }
+    }
+}
+
+/// 发送队列策略：指标与工具快照 latest-wins，其余事件不限长 FIFO（由写端背压限流）。
+fn outbound_queue_policies() -> HashMap<QueueKey, QueuePolicy> {
+    HashMap::from([
+        (QueueKey::Metrics, QueuePolicy::latest_wins()),
+        (QueueKey::ToolsRefresh, QueuePolicy::latest_wins()),
+    ])
+}
+
+/// 会话循环使用的 relay 写端：写入发送队列而非直接写 socket。
+pub(crate) struct RelayWriter {
+    shared: Arc<OutboundShared>,
+}
+
+impl RelayWriter {
+    /// 创建一个收集周期快照帧的批次写端，写完后经 `commit_latest` 入队。
+    pub(crate) fn coalescing(&self, key: QueueKey) -> CoalescingWriter {
+        CoalescingWriter {
+            key,
+            frames: Vec::new(),
+        }
+    }
+
+    /// 发送队列各键的深度与累计丢弃。
+    pub(crate) fn queue_stats(&self) -> Vec<QueueKeyStats> {
+        self.shared.lock().queue.stats()
+    }
+
+    /// 把批次放入 latest-wins 槽位；覆盖未发出的旧批次时记录队列深度。
+    pub(crate) fn commit_latest(&self, batch: CoalescingWriter) -> Result<()> {
+        let key = batch.key;
+        let frames = batch.frames;
+        if frames.is_empty() {
+            return Ok(());
+        }
+        {
+            let mut state = self.shared.lock();
+            if state.closed {
+                return Err(anyhow!("relay send worker closed"));
+            }
+            let report = state.queue.enqueue(key, frames);
+            if report.dropped > 0 {
+                debug!(
+                    "coalesced pending outbound snapshot key={key:?} dropped={} queue_depth={}",
+                    report.dropped,
+                    state.queue.total_depth()
+                );
+            }
+        }
+        self.shared.notify.notify_one();
+        Ok(())
+    }
+}
+
+impl Drop for RelayWriter {
+    /// 写端释放时通知 worker 发完积压后退出。
+    fn drop(&mut self) {
+        self.shared.lock().closed = true;
+        self.shared.notify.notify_one();
+    }
+}
+
+impl Sink<OutboundFrame> for RelayWriter {
+    type Error = WsError;
+
+    /// 普通事件积压达到上限时挂起，等待 worker 发出后唤醒。
+    fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        let mut state = self.shared.lock();
+        if state.closed {
+            return Poll::Ready(Err(WsError::AlreadyClosed));
+        }
+        if state.queue.depth_for_key(QueueKey::Control) >= OUTBOUND_MAX_PENDING_FRAMES {
+            state.blocked_writer = Some(cx.waker().clone());
+            return Poll::Pending;
+        }
+        Poll::Ready(Ok(()))
+    }
+
+    /// 普通事件按 FIFO 入队。
+    fn start_send(self: Pin<&mut Self>, item: OutboundFrame) -> Result<(), Self::Error> {
+        {
+            let mut state = self.shared.lock();
+            if state.closed {
+                return Err(WsError::AlreadyClosed);
+            }
+            state.queue.enqueue(QueueKey::Control, vec![item]);
+        }
+        self.shared.notify.notify_one();
+        Ok(())
+    }
+
+    /// 入队即视为已刷新；写失败由 worker 退出上报。
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        if self.shared.lock().closed {
+            return Poll::Ready(Err(WsError::AlreadyClosed));
+        }
+        Poll::Ready(Ok(()))
+    }
+
+    /// 关闭由 `Drop` 负责，这里无需额外动作。
+    fn poll_close(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+}
+
+/// 周期快照批次写端：只在内存中收集帧，由 `RelayWriter::commit_latest` 整批入队。
+pub(crate) struct CoalescingWriter {
+    key: QueueKey,
+    frames: Vec<OutboundFrame>,
+}
+
+impl Sink<OutboundFrame> for CoalescingWriter {
+    type Error = WsError;
+
+    /// 内存收集，始终就绪。
+    fn poll_ready(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    /// 追加一帧到批次。
+    fn start_send(mut self: Pin<&mut Self>, item: OutboundFrame) -> Result<(), Self::Error> {
+        self.frames.push(item);
+        Ok(())
+    }
+
+    /// 内存收集，无需刷新。
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    /// 内存收集，无需关闭。
+    fn poll_close(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+}
+
+/// 启动发送 worker，返回会话循环使用的写端；worker 写失败时以错误结束。
+pub(super) fn spawn_send_worker<S>(mut sink: S) -> (RelayWriter, JoinHandle<Result<()>>)
+where
+    S: Sink<Message, Error = WsError> + Unpin + Send + 'static,
+{
+    let shared = Arc::new(OutboundShared {
+        state: Mutex::new(OutboundState {
+            queue: QueueScheduler::new(QueuePolicy::fifo(0), outbound_queue_policies()),
+            closed: false,
+            blocked_writer: None,
+        }),
+        notify: Notify::new(),
+    });
+    let worker_shared = shared.clone();
+    let handle = tokio::spawn(async move {
+        let mut seq = 0_u64;
+        loop {
+            let next = {
+                let mut state = worker_shared.lock();
+                match state.queue.pop_next() {
+                    Some(next) => Some(next),
+                    None if state.closed => return Ok(()),
+                    None => None,
+                }
+            };
+            let Some((_key, frames)) = next else {
+                worker_shared.notify.notified().await;
+                continue;
+            };
+            for frame in frames {
+                let sent = match frame {
+                    OutboundFrame::Raw(message) => sink.send(message).await.map_err(Into::into),
+                    OutboundFrame::Event(env) => {
+                        seq += 1;
+                        match encode_event_frame(*env, seq) {
+                            Ok(message) => sink.send(message).await.map_err(Into::into),
+                            Err(err) => Err(err),
+                        }
+                    }
+                };
+                if let Err(err) = sent {
+                    let waker = {
+                        let mut state = worker_shared.lock();
+                        state.closed = true;
+                        state.blocked_writer.take()
+                    };
+                    if let Some(waker) = waker {
+                        waker.wake();
+                    }
+                    return Err(anyhow!("relay write failed: {err}"));
+                }
+            }
+            worker_shared.wake_writer();
+        }
+    });
+    (RelayWriter { shared }, handle)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use futures_util::sink;
+    use serde_json::{Value, json};
+    use tokio::sync::Semaphore;
+    use tokio_tungstenite::tungstenite::{Error as WsError, Message};
+
+    use super::spawn_send_worker;
+    use crate::session::{queue::QueueKey, transport::send_event};
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn pending_metrics_coalesce_to_newest_and_keep_seq_contiguous() {
+        let sent = Arc::new(Mutex::new(Vec::<(String, u64)>::new()));
+        let gate = Arc::new(Semaphore::new(0));
+        let slow_sink = Box::pin(sink::unfold(
+            (sent.clone(), gate.clone()),
+            |(sent, gate), frame: Message| async move {
+                gate.acquire().await.expect("gate open").forget();
+                let env = serde_json::from_str::<Value>(&frame.to_string()).expect("envelope");
+                let label = env["payload"]["label"].as_str().unwrap_or_default();
+                let seq = env["seq"].as_u64().unwrap_or_default();
+                sent.lock().expect("sent").push((label.to_string(), seq));
+                Ok::<_, WsError>((sent, gate))
+            },
+        ));
+        let (mut writer, worker) = spawn_send_worker(slow_sink);
+
+        let event = |label: &str| json!({ "label": label });
+        send_event(&mut writer, "sys", "heartbeat", None, event("heartbeat"))
+            .await
+            .expect("send");
+        // 让 worker 取走首帧并卡在慢链路上。
+        tokio::task::yield_now().await;
+        for sample in ["m1", "m2", "m3"] {
+            let mut batch = writer.coalescing(QueueKey::Metrics);
+            send_event(&mut batch, "sys", "metrics_snapshot", None, event(sample))
+                .await
+                .expect("batch");
+            writer.commit_latest(batch).expect("commit");
+        }
+        let mut tools = writer.coalescing(QueueKey::ToolsRefresh);
+        send_event(&mut tools, "sys", "tools_snapshot", None, event("tools"))
+            .await
+            .expect("batch");
+        send_event(
+            &mut tools,
+            "sys",
+            "tools_candidates",
+            None,
+            event("candidates"),
+        )
+        .await
+        .expect("batch");
+        writer.commit_latest(tools).expect("commit");
+        send_event(&mut writer, "sys", "heartbeat", None, event("after"))
+            .await
+            .expect("send");
+
+        gate.add_permits(16);
+        drop(writer);
+        worker.await.expect("join").expect("worker");
+        let sent = sent.lock().expect("sent").clone();
+        let labels = sent
+            .iter()
+            .map(|(label, _)| label.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            labels,
+            vec!["heartbeat", "after", "m3", "tools", "candidates"]
+        );
+        let seqs = sent.iter().map(|(_, seq)| *seq).collect::<Vec<_>>();
+        assert_eq!(seqs, vec![1, 2, 3, 4, 5]);
+    }
+}
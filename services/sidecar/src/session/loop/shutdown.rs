@@ -0,0 +1,98 @@
+//! relay 停机提示：收到 `server_shutdown` 后记录建议的重连等待时间，
+//! 本次会话结束后按该时间推迟重连，避免在 relay 重启窗口内密集重试。
+
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::Duration,
+};
+
+use serde_json::Value;
+
+/// relay 停机通知事件类型。
+pub(super) const SERVER_SHUTDOWN_EVENT: &str = "server_shutdown";
+/// 通知未携带 `retryAfterSec` 时的默认等待。
+const DEFAULT_RETRY_AFTER: Duration = Duration::from_secs(10);
+/// 建议等待时间上限，防止异常值让 sidecar 长时间不重连。
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(60);
+
+/// 解析 `server_shutdown` 事件，返回建议的重连等待时间；其他事件返回 None。
+pub(super) fn server_shutdown_retry_after(raw: &str) -> Option<Duration> {
+    let event: Value = serde_json::from_str(raw).ok()?;
+    if event.get("type").and_then(Value::as_str) != Some(SERVER_SHUTDOWN_EVENT) {
+        return None;
+    }
+    let retry_after = event
+        .get("payload")
+        .and_then(|payload| payload.get("retryAfterSec"))
+        .and_then(Value::as_u64)
+        .map(Duration::from_secs)
+        .unwrap_or(DEFAULT_RETRY_AFTER);
+    Some(retry_after.clamp(Duration::from_secs(1), MAX_RETRY_AFTER))
+}
+
+/// 跨会话共享的停机提示：reader 任务写入，重连循环读取后清空。
+#[derive(Debug, Clone, Default)]
+pub(super) struct ShutdownHint {
+    /// 建议等待毫秒数（0 表示无提示）。
+    retry_after_ms: Arc<AtomicU64>,
+}
+
+impl ShutdownHint {
+    /// 记录一次停机提示。
+    pub(super) fn set(&self, retry_after: Duration) {
+        let millis = u64::try_from(retry_after.as_millis()).unwrap_or(u64::MAX);
+        self.retry_after_ms.store(millis.max(1), Ordering::Relaxed);
+    }
+
+    /// 取出并清空停机提示。
+    pub(super) fn take(&self) -> Option<Duration> {
+        match self.retry_after_ms.swap(0, Ordering::Relaxed) {
+            0 => None,
+            millis => Some(Duration::from_millis(millis)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{ShutdownHint, server_shutdown_retry_after};
+
+    #[test]
+    fn parses_retry_after_from_shutdown_notice_only() {
+        assert_eq!(
+            server_shutdown_retry_after(
+                r#"{"type":"server_shutdown","payload":{"retryAfterSec":20}}"#
+            ),
+            Some(Duration::from_secs(20))
+        );
+        assert_eq!(
+            server_shutdown_retry_after(r#"{"type":"server_shutdown","payload":{}}"#),
+            Some(Duration::from_secs(10))
+        );
+        assert_eq!(
+            server_shutdown_retry_after(
+                r#"{"type":"server_shutdown","payload":{"retryAfterSec":3600}}"#
+            ),
+            Some(Duration::from_secs(60))
+        );
+        assert_eq!(
+            server_shutdown_retry_after(r#"{"type":"server_presence","payload":{}}"#),
+            None
+        );
+        assert_eq!(server_shutdown_retry_after("not json"), None);
+    }
+
+    #[test]
+    fn hint_is_consumed_once() {
+        let hint = ShutdownHint::default();
+        assert_eq!(hint.take(), None);
+        hint.clone().set(Duration::from_secs(5));
+        assert_eq!(hint.take(), Some(Duration::from_secs(5)));
+        assert_eq!(hint.take(), None);
+    }
+}
@@ -1,14 +1,24 @@
 //! Relay 连接 URL 与日志开关工具。
 
 use anyhow::Result;
+use tokio_tungstenite::tungstenite::{
+    client::IntoClientRequest,
+    handshake::client::Request,
+    http::{HeaderName, HeaderValue},
+};
 use url::Url;
+use yc_shared_protocol::PROTOCOL_VERSION;
 
 use crate::config::Config;
 
 /// 原始 payload 日志开关环境变量（默认关闭）。
 const RAW_PAYLOAD_LOG_ENV: &str = "YC_DEBUG_RAW_PAYLOAD";
+/// relay 要求的 sidecar 注册共享密钥（对应 relay `RELAY_SIDECAR_SHARED_SECRET`）。
+const SIDECAR_SHARED_SECRET_ENV: &str = "SIDECAR_SHARED_SECRET";
+/// 携带共享密钥的握手请求头。
+const SIDECAR_SECRET_HEADER: &str = "x-sidecar-secret";
 
-/// 组装 sidecar 连接 relay 的 WS URL，并注入身份 query 参数。
+/// 组装 sidecar 连接 relay 的 WS URL，并注入身份与协议版本 query 参数。
 pub(crate) fn sidecar_ws_url(cfg: &Config) -> Result<Url> {
     let mut url = Url::parse(&cfg.relay_ws_url)?;
     {
@@ -18,10 +28,25 @@ pub(crate) fn sidecar_ws_url(cfg: &Config) -> Result<Url> {
         pairs.append_pair("deviceId", &cfg.device_id);
         pairs.append_pair("pairToken", &cfg.pair_token);
         pairs.append_pair("hostName", &cfg.host_name);
+        pairs.append_pair("protocolVersion", &PROTOCOL_VERSION.to_string());
     }
     Ok(url)
 }
 
+/// 组装 WS 握手请求：配置共享密钥时附加 `X-Sidecar-Secret` 头。
+pub(crate) fn sidecar_ws_request(url: &Url) -> Result<Request> {
+    let mut request = url.as_str().into_client_request()?;
+    let secret = std::env::var(SIDECAR_SHARED_SECRET_ENV).unwrap_or_default();
+    let secret = secret.trim();
+    if !secret.is_empty() {
+        request.headers_mut().insert(
+            HeaderName::from_static(SIDECAR_SECRET_HEADER),
+            HeaderValue::from_str(secret)?,
+        );
+    }
+    Ok(request)
+}
+
 /// 是否开启原始 payload 日志（默认关闭）。
 pub(crate) fn raw_payload_logging_enabled() -> bool {
     let raw = std::env::var(RAW_PAYLOAD_LOG_ENV).unwrap_or_default();
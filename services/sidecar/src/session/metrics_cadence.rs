@@ -0,0 +1,229 @@
+//! 指标快照自适应周期职责：
+//! 1. 相邻两次系统指标变化都在阈值内时逐步拉长推送周期（翻倍，封顶 `max`）。
+//! 2. 出现超过阈值的变化时立即回落到下限 `min`，保证负载上升时仍及时上报。
+//! 3. 未启用时保持固定的 `METRICS_INTERVAL_SEC` 周期，行为与原 ticker 一致。
+
+use std::time::Duration;
+
+use tokio::time::{Instant, Interval, MissedTickBehavior};
+use tracing::debug;
+use yc_shared_protocol::SystemMetricsPayload;
+
+/// 是否启用自适应周期的环境变量。
+pub(crate) const METRICS_ADAPTIVE_ENV: &str = "METRICS_ADAPTIVE";
+/// 自适应周期下限环境变量（秒）。
+pub(crate) const METRICS_ADAPTIVE_MIN_ENV: &str = "METRICS_ADAPTIVE_MIN_SEC";
+/// 自适应周期上限环境变量（秒）。
+pub(crate) const METRICS_ADAPTIVE_MAX_ENV: &str = "METRICS_ADAPTIVE_MAX_SEC";
+/// 判定“变化显著”的阈值环境变量（百分点）。
+pub(crate) const METRICS_ADAPTIVE_THRESHOLD_ENV: &str = "METRICS_ADAPTIVE_THRESHOLD";
+/// 默认周期下限。
+const DEFAULT_MIN_SEC: u64 = 2;
+/// 默认周期上限。
+const DEFAULT_MAX_SEC: u64 = 60;
+/// 默认变化阈值：CPU/内存/磁盘使用率任一变化超过 5 个百分点即视为显著。
+const DEFAULT_THRESHOLD_PERCENT: f64 = 5.0;
+
+/// 自适应周期参数。
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct AdaptiveMetricsConfig {
+    /// 周期下限。
+    pub(crate) min: Duration,
+    /// 周期上限。
+    pub(crate) max: Duration,
+    /// 使用率变化阈值（百分点）。
+    pub(crate) threshold_percent: f64,
+}
+
+impl AdaptiveMetricsConfig {
+    /// 读取环境变量；未启用时返回 None。
+    pub(crate) fn from_env() -> Option<Self> {
+        let enabled = std::env::var(METRICS_ADAPTIVE_ENV)
+            .map(|raw| {
+                matches!(
+                    raw.trim().to_ascii_lowercase().as_str(),
+                    "1" | "true" | "yes" | "y" | "on"
+                )
+            })
+            .unwrap_or(false);
+        if !enabled {
+            return None;
+        }
+        let secs = |key: &str, fallback: u64| {
+            std::env::var(key)
+                .ok()
+                .and_then(|raw| raw.trim().parse::<u64>().ok())
+                .filter(|v| *v > 0)
+                .unwrap_or(fallback)
+        };
+        let min = secs(METRICS_ADAPTIVE_MIN_ENV, DEFAULT_MIN_SEC);
+        let max = secs(METRICS_ADAPTIVE_MAX_ENV, DEFAULT_MAX_SEC).max(min);
+        let threshold_percent = std::env::var(METRICS_ADAPTIVE_THRESHOLD_ENV)
+            .ok()
+            .and_then(|raw| raw.trim().parse::<f64>().ok())
+            .filter(|v| v.is_finite() && *v > 0.0)
+            .unwrap_or(DEFAULT_THRESHOLD_PERCENT);
+        Some(Self {
+            min: Duration::from_secs(min),
+            max: Duration::from_secs(max),
+            threshold_percent,
+        })
+    }
+}
+
+/// 根据相邻两次指标变化计算下一次推送周期。
+#[derive(Debug)]
+pub(crate) struct AdaptiveMetricsInterval {
+    /// 自适应参数；None 表示固定周期。
+    adaptive: Option<AdaptiveMetricsConfig>,
+    /// 当前周期。
+    current: Duration,
+    /// 上一次观测到的指标。
+    last: Option<SystemMetricsPayload>,
+}
+
+impl AdaptiveMetricsInterval {
+    /// 以基础周期创建；启用自适应时基础周期会被限制在 `[min, max]` 内。
+    pub(crate) fn new(base: Duration, adaptive: Option<AdaptiveMetricsConfig>) -> Self {
+        let current = match adaptive {
+            Some(cfg) => base.clamp(cfg.min, cfg.max),
+            None => base,
+        };
+        Self {
+            adaptive,
+            current,
+            last: None,
+        }
+    }
+
+    /// 当前周期。
+    pub(crate) fn current(&self) -> Duration {
+        self.current
+    }
+
+    /// 记录一次指标观测；周期发生变化时返回新周期。
+    pub(crate) fn observe(&mut self, metrics: &SystemMetricsPayload) -> Option<Duration> {
+        let cfg = self.adaptive?;
+        let previous = self.last.replace(metrics.clone())?;
+        let delta = max_usage_delta(&previous, metrics);
+        let next = if delta > cfg.threshold_percent {
+            cfg.min
+        } else {
+            self.current.saturating_mul(2).min(cfg.max)
+        };
+        if next == self.current {
+            return None;
+        }
+        self.current = next;
+        Some(next)
+    }
+}
+
+/// CPU/内存/磁盘使用率中变化最大的一项（百分点）。
+fn max_usage_delta(previous: &SystemMetricsPayload, current: &SystemMetricsPayload) -> f64 {
+    [
+        (previous.cpu_percent, current.cpu_percent),
+        (previous.memory_used_percent, current.memory_used_percent),
+        (previous.disk_used_percent, current.disk_used_percent),
+    ]
+    .into_iter()
+    .map(|(before, after)| (after - before).abs())
+    .fold(0.0, f64::max)
+}
+
+/// 包装指标 ticker：每次推送后按观测结果重建 interval。
+pub(crate) struct MetricsTicker {
+    /// 底层 tokio interval。
+    ticker: Interval,
+    /// 周期计算器。
+    cadence: AdaptiveMetricsInterval,
+}
+
+impl MetricsTicker {
+    /// 创建 ticker；首次 tick 立即触发，与 `tokio::time::interval` 一致。
+    pub(crate) fn new(base: Duration, adaptive: Option<AdaptiveMetricsConfig>) -> Self {
+        let cadence = AdaptiveMetricsInterval::new(base, adaptive);
+        let mut ticker = tokio::time::interval(cadence.current());
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Skip);
+        Self { ticker, cadence }
+    }
+
+    /// 等待下一次推送时机。
+    pub(crate) async fn tick(&mut self) {
+        self.ticker.tick().await;
+    }
+
+    /// 记录本轮指标；周期变化时从当前时刻起按新周期重新计时。
+    pub(crate) fn observe(&mut self, metrics: &SystemMetricsPayload) {
+        let Some(next) = self.cadence.observe(metrics) else {
+            return;
+        };
+        debug!("metrics interval adjusted interval_ms={}", next.as_millis());
+        self.ticker = tokio::time::interval_at(Instant::now() + next, next);
+        self.ticker
+            .set_missed_tick_behavior(MissedTickBehavior::Skip);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use yc_shared_protocol::SystemMetricsPayload;
+
+    use super::{AdaptiveMetricsConfig, AdaptiveMetricsInterval};
+
+    const CFG: AdaptiveMetricsConfig = AdaptiveMetricsConfig {
+        min: Duration::from_secs(2),
+        max: Duration::from_secs(40),
+        threshold_percent: 5.0,
+    };
+
+    fn metrics(cpu_percent: f64) -> SystemMetricsPayload {
+        SystemMetricsPayload {
+            cpu_percent,
+            memory_used_percent: 40.0,
+            disk_used_percent: 70.0,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn stable_metrics_back_off_up_to_max() {
+        let mut cadence = AdaptiveMetricsInterval::new(Duration::from_secs(10), Some(CFG));
+        assert_eq!(cadence.observe(&metrics(12.0)), None);
+        assert_eq!(
+            cadence.observe(&metrics(13.5)),
+            Some(Duration::from_secs(20))
+        );
+        assert_eq!(
+            cadence.observe(&metrics(11.0)),
+            Some(Duration::from_secs(40))
+        );
+        assert_eq!(cadence.observe(&metrics(12.0)), None);
+        assert_eq!(cadence.current(), Duration::from_secs(40));
+    }
+
+    #[test]
+    fn spike_drops_interval_to_floor() {
+        let mut cadence = AdaptiveMetricsInterval::new(Duration::from_secs(10), Some(CFG));
+        cadence.observe(&metrics(12.0));
+        cadence.observe(&metrics(12.0));
+        assert_eq!(cadence.current(), Duration::from_secs(20));
+
+        assert_eq!(cadence.observe(&metrics(80.0)), Some(CFG.min));
+        // 回落后仍保持稳定，再次逐步拉长。
+        assert_eq!(
+            cadence.observe(&metrics(81.0)),
+            Some(Duration::from_secs(4))
+        );
+    }
+
+    #[test]
+    fn disabled_mode_keeps_fixed_interval() {
+        let mut cadence = AdaptiveMetricsInterval::new(Duration::from_secs(10), None);
+        cadence.observe(&metrics(1.0));
+        assert_eq!(cadence.observe(&metrics(99.0)), None);
+        assert_eq!(cadence.current(), Duration::from_secs(10));
+    }
+}
@@ -0,0 +1,88 @@
+//! 指标历史缓冲职责：
+//! 1. 以固定容量环形缓冲保存最近的周期性 `metrics_snapshot`，超出容量时淘汰最旧样本。
+//! 2. 跨 relay 重连保留，供 `metrics_history_request` 按条数回放（App 重连后绘制趋势图）。
+
+use std::collections::VecDeque;
+
+use yc_shared_protocol::MetricsSnapshotPayload;
+
+/// 默认缓冲容量（条）。
+pub(crate) const DEFAULT_METRICS_HISTORY_LEN: usize = 120;
+
+/// 固定容量的指标历史缓冲。
+#[derive(Debug)]
+pub(crate) struct MetricsHistory {
+    /// 样本（旧 -> 新）。
+    samples: VecDeque<MetricsSnapshotPayload>,
+    /// 容量上限（至少 1）。
+    capacity: usize,
+}
+
+impl MetricsHistory {
+    /// 创建指定容量的缓冲。
+    pub(crate) fn new(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        Self {
+            samples: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    /// 容量上限。
+    pub(crate) fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// 追加一条样本；已满时先淘汰最旧样本。
+    pub(crate) fn push(&mut self, sample: MetricsSnapshotPayload) {
+        if self.samples.len() >= self.capacity {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(sample);
+    }
+
+    /// 返回最近 `limit` 条样本（旧 -> 新）；未指定时返回全部。
+    pub(crate) fn recent(&self, limit: Option<usize>) -> Vec<MetricsSnapshotPayload> {
+        let limit = limit.unwrap_or(self.samples.len()).min(self.samples.len());
+        self.samples
+            .iter()
+            .skip(self.samples.len() - limit)
+            .cloned()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use yc_shared_protocol::{MetricsSnapshotPayload, SystemMetricsPayload};
+
+    use super::MetricsHistory;
+
+    fn sample(uptime_sec: u64) -> MetricsSnapshotPayload {
+        MetricsSnapshotPayload {
+            system: SystemMetricsPayload {
+                uptime_sec,
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    fn uptimes(samples: &[MetricsSnapshotPayload]) -> Vec<u64> {
+        samples.iter().map(|item| item.system.uptime_sec).collect()
+    }
+
+    #[test]
+    fn buffer_evicts_oldest_and_returns_recent_in_order() {
+        let mut history = MetricsHistory::new(3);
+        for uptime in 1..=5 {
+            history.push(sample(uptime));
+        }
+
+        assert_eq!(history.capacity(), 3);
+        assert_eq!(uptimes(&history.recent(None)), vec![3, 4, 5]);
+        assert_eq!(uptimes(&history.recent(Some(2))), vec![4, 5]);
+        assert_eq!(uptimes(&history.recent(Some(10))), vec![3, 4, 5]);
+        assert!(MetricsHistory::new(0).recent(None).is_empty());
+    }
+}
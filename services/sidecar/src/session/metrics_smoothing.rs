@@ -0,0 +1,121 @@
+//! 指标平滑职责：
+//! 1. 按 `METRICS_SMOOTHING_ALPHA` 对系统 CPU/内存使用率做指数移动平均（EMA），
+//!    结果写入 `cpuPercentAvg`/`memoryUsedPercentAvg`，瞬时值保持不变。
+//! 2. alpha 收敛到 `[0, 1]`：`0`（默认）关闭平滑、不下发均值字段；`1` 等同瞬时值。
+//! 3. 平滑状态跨指标周期保留，随会话重连重置。
+
+use tracing::warn;
+use yc_shared_protocol::SystemMetricsPayload;
+
+use crate::round2;
+
+/// 平滑系数环境变量。
+pub(crate) const METRICS_SMOOTHING_ALPHA_ENV: &str = "METRICS_SMOOTHING_ALPHA";
+
+/// 读取平滑系数；未设置或非法时返回 0（关闭）。
+pub(crate) fn metrics_smoothing_alpha_from_env() -> f64 {
+    parse_smoothing_alpha(std::env::var(METRICS_SMOOTHING_ALPHA_ENV).ok().as_deref())
+}
+
+/// 解析平滑系数并收敛到 `[0, 1]`。
+fn parse_smoothing_alpha(raw: Option<&str>) -> f64 {
+    let Some(raw) = raw.map(str::trim).filter(|value| !value.is_empty()) else {
+        return 0.0;
+    };
+    match raw.parse::<f64>() {
+        Ok(alpha) if alpha.is_finite() => alpha.clamp(0.0, 1.0),
+        _ => {
+            warn!("invalid {METRICS_SMOOTHING_ALPHA_ENV}={raw}, smoothing disabled");
+            0.0
+        }
+    }
+}
+
+/// 跨周期保留的 EMA 状态。
+#[derive(Debug, Clone, Default)]
+pub(crate) struct MetricsSmoother {
+    /// 平滑系数，0 表示关闭。
+    alpha: f64,
+    /// 上一周期 CPU 均值。
+    cpu_percent: Option<f64>,
+    /// 上一周期内存使用率均值。
+    memory_used_percent: Option<f64>,
+}
+
+impl MetricsSmoother {
+    /// 以给定系数创建平滑器。
+    pub(crate) fn new(alpha: f64) -> Self {
+        Self {
+            alpha,
+            ..Self::default()
+        }
+    }
+
+    /// 用本周期瞬时值更新均值并写入载荷；关闭时不写入。
+    pub(crate) fn apply(&mut self, system: &mut SystemMetricsPayload) {
+        if self.alpha <= 0.0 {
+            return;
+        }
+        let cpu = ema(self.alpha, self.cpu_percent, system.cpu_percent);
+        let memory = ema(
+            self.alpha,
+            self.memory_used_percent,
+            system.memory_used_percent,
+        );
+        self.cpu_percent = Some(cpu);
+        self.memory_used_percent = Some(memory);
+        system.cpu_percent_avg = Some(round2(cpu));
+        system.memory_used_percent_avg = Some(round2(memory));
+    }
+}
+
+/// 单步 EMA；首个样本直接作为初始均值。
+fn ema(alpha: f64, previous: Option<f64>, current: f64) -> f64 {
+    match previous {
+        Some(previous) => alpha * current + (1.0 - alpha) * previous,
+        None => current,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use yc_shared_protocol::SystemMetricsPayload;
+
+    use super::{MetricsSmoother, parse_smoothing_alpha};
+
+    fn system(cpu_percent: f64, memory_used_percent: f64) -> SystemMetricsPayload {
+        SystemMetricsPayload {
+            cpu_percent,
+            memory_used_percent,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn ema_tracks_across_ticks_and_alpha_is_clamped() {
+        assert_eq!(parse_smoothing_alpha(Some("1.5")), 1.0);
+        assert_eq!(parse_smoothing_alpha(Some("-0.2")), 0.0);
+        assert_eq!(parse_smoothing_alpha(Some("abc")), 0.0);
+        assert_eq!(parse_smoothing_alpha(Some(" 0.3 ")), 0.3);
+        assert_eq!(parse_smoothing_alpha(None), 0.0);
+
+        let mut smoother = MetricsSmoother::new(0.5);
+        let mut first = system(80.0, 40.0);
+        smoother.apply(&mut first);
+        assert_eq!(first.cpu_percent_avg, Some(80.0));
+        let mut second = system(20.0, 60.0);
+        smoother.apply(&mut second);
+        assert_eq!(second.cpu_percent, 20.0);
+        assert_eq!(second.cpu_percent_avg, Some(50.0));
+        assert_eq!(second.memory_used_percent_avg, Some(50.0));
+        let mut third = system(20.0, 60.0);
+        smoother.apply(&mut third);
+        assert_eq!(third.cpu_percent_avg, Some(35.0));
+
+        let mut disabled = system(20.0, 60.0);
+        MetricsSmoother::new(0.0).apply(&mut disabled);
+        assert_eq!(disabled.cpu_percent_avg, None);
+        let json = serde_json::to_value(&disabled).expect("json");
+        assert!(json.get("cpuPercentAvg").is_none());
+    }
+}
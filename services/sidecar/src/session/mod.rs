@@ -1,6 +1,15 @@
 //! Sidecar 会话模块。
 
+pub(crate) mod command_ack;
+pub(crate) mod diagnostics;
+pub(crate) mod gpu_metrics;
+pub(crate) mod heartbeat_rtt;
 pub(crate) mod r#loop;
+pub(crate) mod metrics_cadence;
+pub(crate) mod metrics_history;
+pub(crate) mod metrics_smoothing;
+pub(crate) mod network_rate;
 pub(crate) mod queue;
 pub(crate) mod snapshots;
 pub(crate) mod transport;
+pub(crate) mod whitelist_prune;
@@ -0,0 +1,140 @@
+//! 网络吞吐采样职责：
+//! 1. 持有 sysinfo `Networks` 句柄，按指标周期读取各网卡累计收发字节数（忽略回环网卡）。
+//! 2. 与上一次采样做差并除以间隔，得到每秒收发字节数；首次采样或计数回绕时记为 0。
+
+use std::time::Instant;
+
+use sysinfo::Networks;
+
+use crate::round2;
+
+/// 网络吞吐速率（字节/秒）。
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub(crate) struct NetworkRate {
+    /// 接收速率。
+    pub(crate) rx_bytes_per_sec: f64,
+    /// 发送速率。
+    pub(crate) tx_bytes_per_sec: f64,
+}
+
+/// 单次累计计数采样。
+#[derive(Debug, Clone, Copy)]
+struct NetworkSample {
+    /// 累计接收字节。
+    rx_bytes: u64,
+    /// 累计发送字节。
+    tx_bytes: u64,
+    /// 采样时间。
+    at: Instant,
+}
+
+/// 会话级网络速率跟踪器，由会话循环持有并跨指标周期保留上一次采样。
+pub(crate) struct NetworkRateTracker {
+    /// sysinfo 网卡列表。
+    networks: Networks,
+    /// 上一次采样。
+    last: Option<NetworkSample>,
+}
+
+impl NetworkRateTracker {
+    /// 创建跟踪器并记录首个基线采样。
+    pub(crate) fn new() -> Self {
+        let networks = Networks::new_with_refreshed_list();
+        let (rx_bytes, tx_bytes) = total_bytes(&networks);
+        Self {
+            networks,
+            last: Some(NetworkSample {
+                rx_bytes,
+                tx_bytes,
+                at: Instant::now(),
+            }),
+        }
+    }
+
+    /// 刷新网卡计数并返回距上次采样的平均速率。
+    pub(crate) fn sample(&mut self) -> NetworkRate {
+        self.networks.refresh(true);
+        let (rx_bytes, tx_bytes) = total_bytes(&self.networks);
+        self.observe(NetworkSample {
+            rx_bytes,
+            tx_bytes,
+            at: Instant::now(),
+        })
+    }
+
+    /// 记录一次采样并与上一次做差。
+    fn observe(&mut self, current: NetworkSample) -> NetworkRate {
+        let Some(previous) = self.last.replace(current) else {
+            return NetworkRate::default();
+        };
+        let elapsed = current
+            .at
+            .saturating_duration_since(previous.at)
+            .as_secs_f64();
+        if elapsed <= 0.0 {
+            return NetworkRate::default();
+        }
+        // 网卡重建或计数回绕时累计值可能变小，按 0 处理。
+        let rate = |now: u64, before: u64| round2(now.saturating_sub(before) as f64 / elapsed);
+        NetworkRate {
+            rx_bytes_per_sec: rate(current.rx_bytes, previous.rx_bytes),
+            tx_bytes_per_sec: rate(current.tx_bytes, previous.tx_bytes),
+        }
+    }
+}
+
+/// 汇总非回环网卡的累计收发字节。
+fn total_bytes(networks: &Networks) -> (u64, u64) {
+    networks
+        .list()
+        .iter()
+        .filter(|(name, _)| !name.starts_with("lo"))
+        .fold((0, 0), |(rx, tx), (_, data)| {
+            (
+                rx.saturating_add(data.total_received()),
+                tx.saturating_add(data.total_transmitted()),
+            )
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use sysinfo::Networks;
+
+    use super::{NetworkRate, NetworkRateTracker, NetworkSample};
+
+    /// 构造无基线的跟踪器。
+    fn tracker() -> NetworkRateTracker {
+        NetworkRateTracker {
+            networks: Networks::new(),
+            last: None,
+        }
+    }
+
+    #[test]
+    fn rate_is_delta_over_elapsed_and_zero_on_reset() {
+        let mut tracker = tracker();
+        let start = Instant::now();
+        let sample = |rx_bytes, tx_bytes, offset_sec| NetworkSample {
+            rx_bytes,
+            tx_bytes,
+            at: start + Duration::from_secs(offset_sec),
+        };
+
+        assert_eq!(
+            tracker.observe(sample(1_000, 500, 0)),
+            NetworkRate::default()
+        );
+        assert_eq!(
+            tracker.observe(sample(3_000, 1_500, 2)),
+            NetworkRate {
+                rx_bytes_per_sec: 1_000.0,
+                tx_bytes_per_sec: 500.0,
+            }
+        );
+        let after_reset = tracker.observe(sample(100, 100, 4));
+        assert_eq!(after_reset, NetworkRate::default());
+    }
+}
@@ -14,6 +14,21 @@ pub(crate) enum QueueKey {
     Report,
 }
 
+impl QueueKey {
+    /// 诊断输出使用的稳定名称。
+    pub(crate) const fn as_str(self) -> &'static str {
+        match self {
+            Self::ToolDetails => "tool_details",
+            Self::ToolsRefresh => "tools_refresh",
+            Self::Metrics => "metrics",
+            Self::PairingBanner => "pairing_banner",
+            Self::Control => "control",
+            Self::Chat => "chat",
+            Self::Report => "report",
+        }
+    }
+}
+
 /// 排队语义。
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub(crate) enum QueueSemantics {
@@ -64,6 +79,14 @@ pub(crate) struct QueueEnqueueReport {
     pub(crate) dropped: u32,
 }
 
+/// 单键统计：当前深度与累计丢弃（含 latest-wins 覆盖）。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct QueueKeyStats {
+    pub(crate) key: QueueKey,
+    pub(crate) depth: usize,
+    pub(crate) dropped_total: u64,
+}
+
 /// 通用队列调度器。
 #[derive(Debug)]
 pub(crate) struct QueueScheduler<T> {
@@ -73,6 +96,7 @@ pub(crate) struct QueueScheduler<T> {
     latest_order: VecDeque<QueueKey>,
     fifo: VecDeque<(QueueKey, T)>,
     fifo_depth_by_key: HashMap<QueueKey, usize>,
+    dropped_total_by_key: HashMap<QueueKey, u64>,
 }
 
 impl<T> QueueScheduler<T> {
@@ -88,6 +112,7 @@ impl<T> QueueScheduler<T> {
             latest_order: VecDeque::new(),
             fifo: VecDeque::new(),
             fifo_depth_by_key: HashMap::new(),
+            dropped_total_by_key: HashMap::new(),
         }
     }
 
@@ -114,6 +139,7 @@ impl<T> QueueScheduler<T> {
                 }
             }
         }
+        *self.dropped_total_by_key.entry(key).or_insert(0) += u64::from(dropped);
         QueueEnqueueReport { dropped }
     }
 
@@ -152,6 +178,35 @@ impl<T> QueueScheduler<T> {
                 .copied()
                 .unwrap_or_default()
     }
+
+    /// 全部键的待处理总数。
+    pub(crate) fn total_depth(&self) -> usize {
+        self.latest.len() + self.fifo.len()
+    }
+
+    /// 按键名排序的统计：覆盖已配置策略的键与入队过的键。
+    pub(crate) fn stats(&self) -> Vec<QueueKeyStats> {
+        let mut keys = self
+            .policies
+            .keys()
+            .chain(self.dropped_total_by_key.keys())
+            .copied()
+            .collect::<Vec<QueueKey>>();
+        keys.sort_by_key(|key| key.as_str());
+        keys.dedup();
+        keys.into_iter()
+            .map(|key| QueueKeyStats {
+                key,
+                depth: self.depth_for_key(key),
+                dropped_total: self
+                    .dropped_total_by_key
+                    .get(&key)
+                    .copied()
+                    .unwrap_or_default(),
+            })
+            .collect()
+    }
+
     /// 读取指定键的策略（不存在时返回默认策略）。
     fn policy_for(&self, key: QueueKey) -> QueuePolicy {
         self.policies
@@ -5,16 +5,23 @@ use futures_util::Sink;
 use serde_json::json;
 use std::collections::HashSet;
 use sysinfo::{Disks, ProcessesToUpdate, System};
-use tokio_tungstenite::tungstenite::Message;
 use yc_shared_protocol::{
-    MetricsSnapshotPayload, SidecarMetricsPayload, SystemMetricsPayload, ToolDetailEnvelopePayload,
-    ToolDetailsSnapshotPayload, ToolDetailsSnapshotTrigger, ToolRuntimePayload,
-    ToolsSnapshotPayload, now_rfc3339_nanos,
+    DiskUsagePayload, MetricsSnapshotPayload, SidecarMetricsPayload, SystemMetricsPayload,
+    ToolDetailEnvelopePayload, ToolDetailsSnapshotPayload, ToolDetailsSnapshotTrigger,
+    ToolRuntimePayload, ToolsSnapshotPayload, now_rfc3339_nanos,
 };
 
 use crate::{
-    bytes_to_gb, bytes_to_mb, config::Config, round2, session::transport::send_event,
-    stores::ToolWhitelistStore,
+    bytes_to_gb, bytes_to_mb,
+    config::Config,
+    round2,
+    session::{
+        gpu_metrics::collect_gpu_metrics,
+        metrics_smoothing::MetricsSmoother,
+        network_rate::{NetworkRate, NetworkRateTracker},
+        transport::{OutboundFrame, send_event},
+    },
+    stores::{ToolLabelStore, ToolWhitelistStore},
 };
 
 /// 已接入工具快照事件。
@@ -25,6 +32,8 @@ pub(crate) const TOOLS_CANDIDATES_EVENT: &str = "tools_candidates";
 pub(crate) const METRICS_SNAPSHOT_EVENT: &str = "metrics_snapshot";
 /// 工具详情快照事件。
 pub(crate) const TOOL_DETAILS_SNAPSHOT_EVENT: &str = "tool_details_snapshot";
+/// 采集错误汇总事件。
+pub(crate) const COLLECTION_ERRORS_EVENT: &str = "collection_errors";
 
 /// 详情快照下行元信息。
 #[derive(Debug, Clone)]
@@ -39,73 +48,149 @@ pub(crate) struct ToolDetailsSnapshotMeta {
     pub(crate) dropped_refreshes: u32,
 }
 
+/// 一轮快照发送结果摘要。
+#[derive(Debug, Clone)]
+pub(crate) struct SnapshotsSummary {
+    /// 本轮上报的已接入工具数。
+    pub(crate) tool_count: usize,
+    /// 本轮上报的候选工具数。
+    pub(crate) candidate_count: usize,
+}
+
 /// 一次性发送 tools_snapshot / tools_candidates / metrics_snapshot 三个事件。
+#[allow(clippy::too_many_arguments)]
 pub(crate) async fn send_snapshots<W>(
     ws_writer: &mut W,
     cfg: &Config,
-    seq: &mut u64,
     sys: &mut System,
     started_at: std::time::Instant,
+    net_rate: &mut NetworkRateTracker,
+    metrics_smoother: &mut MetricsSmoother,
     discovered_tools: &[ToolRuntimePayload],
     whitelist: &ToolWhitelistStore,
-) -> Result<()>
+    labels: &ToolLabelStore,
+) -> Result<SnapshotsSummary>
 where
-    W: Sink<Message, Error = tokio_tungstenite::tungstenite::Error> + Unpin,
+    W: Sink<OutboundFrame, Error = tokio_tungstenite::tungstenite::Error> + Unpin,
 {
-    let (connected_tools, candidate_tools) = split_discovered_tools(discovered_tools, whitelist);
+    let (tool_count, candidate_count) =
+        send_tool_snapshots(ws_writer, cfg, discovered_tools, whitelist, labels).await?;
+    send_metrics_snapshot(
+        ws_writer,
+        cfg,
+        sys,
+        started_at,
+        net_rate,
+        metrics_smoother,
+        discovered_tools,
+        whitelist,
+        labels,
+    )
+    .await?;
+
+    Ok(SnapshotsSummary {
+        tool_count,
+        candidate_count,
+    })
+}
+
+/// 按发现周期发送 tools_snapshot / tools_candidates，返回已接入与候选工具数。
+pub(crate) async fn send_tool_snapshots<W>(
+    ws_writer: &mut W,
+    cfg: &Config,
+    discovered_tools: &[ToolRuntimePayload],
+    whitelist: &ToolWhitelistStore,
+    labels: &ToolLabelStore,
+) -> Result<(usize, usize)>
+where
+    W: Sink<OutboundFrame, Error = tokio_tungstenite::tungstenite::Error> + Unpin,
+{
+    let (connected_payload, candidates_payload) =
+        reported_tools(discovered_tools, whitelist, labels, cfg.max_tools);
+    let counts = (
+        connected_payload.tools.len(),
+        candidates_payload.tools.len(),
+    );
 
     send_event(
         ws_writer,
         &cfg.system_id,
-        seq,
         TOOLS_SNAPSHOT_EVENT,
         None,
-        serde_json::to_value(ToolsSnapshotPayload {
-            tools: connected_tools.clone(),
-        })?,
+        serde_json::to_value(connected_payload)?,
     )
     .await?;
 
     send_event(
         ws_writer,
         &cfg.system_id,
-        seq,
         TOOLS_CANDIDATES_EVENT,
         None,
-        serde_json::to_value(ToolsSnapshotPayload {
-            tools: candidate_tools,
-        })?,
+        serde_json::to_value(candidates_payload)?,
     )
     .await?;
 
+    Ok(counts)
+}
+
+/// 按指标周期发送 metrics_snapshot（工具指标基于最近一次发现结果），返回本轮指标快照。
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn send_metrics_snapshot<W>(
+    ws_writer: &mut W,
+    cfg: &Config,
+    sys: &mut System,
+    started_at: std::time::Instant,
+    net_rate: &mut NetworkRateTracker,
+    metrics_smoother: &mut MetricsSmoother,
+    discovered_tools: &[ToolRuntimePayload],
+    whitelist: &ToolWhitelistStore,
+    labels: &ToolLabelStore,
+) -> Result<MetricsSnapshotPayload>
+where
+    W: Sink<OutboundFrame, Error = tokio_tungstenite::tungstenite::Error> + Unpin,
+{
+    let (connected_payload, _) = reported_tools(discovered_tools, whitelist, labels, cfg.max_tools);
+    let mut metrics =
+        collect_metrics_snapshot(sys, started_at, net_rate.sample(), &connected_payload.tools);
+    metrics_smoother.apply(&mut metrics.system);
     send_event(
         ws_writer,
         &cfg.system_id,
-        seq,
         METRICS_SNAPSHOT_EVENT,
         None,
-        serde_json::to_value(collect_metrics_snapshot(sys, started_at, &connected_tools))?,
+        serde_json::to_value(&metrics)?,
     )
     .await?;
+    Ok(metrics)
+}
 
-    Ok(())
+/// 计算本轮上报的已接入/候选工具（应用显示名并按上限截断）。
+fn reported_tools(
+    discovered_tools: &[ToolRuntimePayload],
+    whitelist: &ToolWhitelistStore,
+    labels: &ToolLabelStore,
+    max_tools: Option<usize>,
+) -> (ToolsSnapshotPayload, ToolsSnapshotPayload) {
+    let (mut connected_tools, mut candidate_tools) =
+        split_discovered_tools(discovered_tools, whitelist);
+    labels.apply(&mut connected_tools);
+    labels.apply(&mut candidate_tools);
+    cap_reported_tools(connected_tools, candidate_tools, max_tools)
 }
 
 /// 发送工具详情快照（按 toolId 对齐）。
 pub(crate) async fn send_tool_details_snapshot<W>(
     ws_writer: &mut W,
     system_id: &str,
-    seq: &mut u64,
     details: &[ToolDetailEnvelopePayload],
     meta: ToolDetailsSnapshotMeta,
 ) -> Result<()>
 where
-    W: Sink<Message, Error = tokio_tungstenite::tungstenite::Error> + Unpin,
+    W: Sink<OutboundFrame, Error = tokio_tungstenite::tungstenite::Error> + Unpin,
 {
     send_event(
         ws_writer,
         system_id,
-        seq,
         TOOL_DETAILS_SNAPSHOT_EVENT,
         None,
         serde_json::to_value(ToolDetailsSnapshotPayload {
@@ -168,12 +253,42 @@ fn split_discovered_tools(
     (connected, candidates)
 }
 
+/// 按上限截断上报工具：connected 优先占用名额，剩余名额留给候选。
+fn cap_reported_tools(
+    connected: Vec<ToolRuntimePayload>,
+    candidates: Vec<ToolRuntimePayload>,
+    max_tools: Option<usize>,
+) -> (ToolsSnapshotPayload, ToolsSnapshotPayload) {
+    let limit = max_tools.unwrap_or(usize::MAX);
+    let connected_limit = limit.min(connected.len());
+    let candidates_limit = limit - connected_limit;
+    (
+        truncate_tools(connected, connected_limit),
+        truncate_tools(candidates, candidates_limit),
+    )
+}
+
+/// 截断工具列表并记录截断前总数。
+fn truncate_tools(mut tools: Vec<ToolRuntimePayload>, limit: usize) -> ToolsSnapshotPayload {
+    let total_count = tools.len();
+    tools.truncate(limit);
+    ToolsSnapshotPayload {
+        truncated: tools.len() < total_count,
+        total_count,
+        tools,
+    }
+}
+
 /// 生成白名单离线占位工具，保证“仅左滑删除才从 connected 消失”。
 fn build_whitelist_placeholder_tool(tool_id: &str) -> ToolRuntimePayload {
     let (name, vendor, category, mode, tool_class) = if tool_id.starts_with("openclaw_") {
         ("OpenClaw", "OpenClaw", "CLI", "CLI", "assistant")
     } else if tool_id.starts_with("opencode_") {
         ("OpenCode", "OpenCode", "TUI", "TUI", "code")
+    } else if tool_id.starts_with("codex_") {
+        ("Codex", "OpenAI", "CODE_AGENT", "CLI", "code")
+    } else if tool_id.starts_with("claude_code_") {
+        ("Claude Code", "Anthropic", "CODE_AGENT", "CLI", "code")
     } else {
         ("Connected Tool", "Unknown", "UNKNOWN", "-", "assistant")
     };
@@ -267,6 +382,7 @@ pub(crate) fn summarize_wire_payload(raw: &str) -> String {
 fn collect_metrics_snapshot(
     sys: &mut System,
     started_at: std::time::Instant,
+    network: NetworkRate,
     tools: &[ToolRuntimePayload],
 ) -> MetricsSnapshotPayload {
     sys.refresh_cpu_usage();
@@ -293,11 +409,25 @@ fn collect_metrics_snapshot(
 
     let disk_total_gb = round2(bytes_to_gb(disk_total));
     let disk_used_gb = round2(bytes_to_gb(disk_used));
-    let disk_used_percent = if disk_total_gb <= 0.0 {
-        0.0
-    } else {
-        round2(disk_used_gb / disk_total_gb * 100.0)
-    };
+    let disk_used_percent = used_percent(disk_used_gb, disk_total_gb);
+    let disk_breakdown = disks
+        .list()
+        .iter()
+        .filter(|disk| disk.total_space() > 0)
+        .filter(|disk| !is_pseudo_filesystem(&disk.file_system().to_string_lossy()))
+        .map(|disk| {
+            let total_gb = round2(bytes_to_gb(disk.total_space()));
+            let used_gb = round2(bytes_to_gb(
+                disk.total_space().saturating_sub(disk.available_space()),
+            ));
+            DiskUsagePayload {
+                mount_point: disk.mount_point().display().to_string(),
+                total_gb,
+                used_gb,
+                used_percent: used_percent(used_gb, total_gb),
+            }
+        })
+        .collect();
 
     let mut sidecar_cpu = 0.0;
     let mut sidecar_mem_mb = 0.0;
@@ -308,6 +438,7 @@ fn collect_metrics_snapshot(
         sidecar_mem_mb = round2(bytes_to_mb(proc_info.memory()));
     }
 
+    let collected_at = now_rfc3339_nanos();
     let tool_value = tools
         .first()
         .and_then(|tool| serde_json::to_value(tool).ok())
@@ -323,6 +454,12 @@ fn collect_metrics_snapshot(
             disk_used_gb,
             disk_used_percent,
             uptime_sec: started_at.elapsed().as_secs(),
+            net_rx_bytes_per_sec: network.rx_bytes_per_sec,
+            net_tx_bytes_per_sec: network.tx_bytes_per_sec,
+            // 均值由会话内的 `MetricsSmoother` 按需填充。
+            cpu_percent_avg: None,
+            memory_used_percent_avg: None,
+            gpu: collect_gpu_metrics(),
         },
         sidecar: SidecarMetricsPayload {
             cpu_percent: sidecar_cpu,
@@ -334,16 +471,38 @@ fn collect_metrics_snapshot(
             .iter()
             .cloned()
             .map(|mut tool| {
-                tool.collected_at = Some(now_rfc3339_nanos());
+                tool.collected_at = Some(collected_at.clone());
                 tool
             })
             .collect(),
+        disks: disk_breakdown,
+        collected_at: Some(collected_at),
+    }
+}
+
+/// 伪文件系统类型：不对应物理存储，不进入按盘拆分列表。
+const PSEUDO_FILESYSTEMS: [&str; 8] = [
+    "tmpfs", "devtmpfs", "overlay", "squashfs", "ramfs", "devfs", "autofs", "nullfs",
+];
+
+/// 判断文件系统类型是否为伪文件系统。
+fn is_pseudo_filesystem(file_system: &str) -> bool {
+    let file_system = file_system.trim().to_ascii_lowercase();
+    PSEUDO_FILESYSTEMS.contains(&file_system.as_str())
+}
+
+/// 计算已用百分比；总量为 0 时返回 0。
+fn used_percent(used_gb: f64, total_gb: f64) -> f64 {
+    if total_gb <= 0.0 {
+        0.0
+    } else {
+        round2(used_gb / total_gb * 100.0)
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::split_discovered_tools;
+    use super::{cap_reported_tools, is_pseudo_filesystem, split_discovered_tools, used_percent};
     use crate::stores::ToolWhitelistStore;
     use yc_shared_protocol::ToolRuntimePayload;
 
@@ -395,4 +554,64 @@ mod tests {
         assert_eq!(connected[0].tool_id, "openclaw_ffffeeee1111_gw");
         assert_eq!(connected[0].status, "RUNNING");
     }
+
+    #[test]
+    fn capped_snapshot_should_keep_connected_tools_first() {
+        let connected = vec![make_tool("openclaw_a_gw"), make_tool("opencode_b")];
+        let candidates = vec![make_tool("codex_c"), make_tool("claude_code_d")];
+
+        let (connected, candidates) = cap_reported_tools(connected, candidates, Some(3));
+        assert_eq!(connected.tools.len(), 2);
+        assert!(!connected.truncated);
+        assert_eq!(connected.total_count, 2);
+        assert_eq!(candidates.tools.len(), 1);
+        assert_eq!(candidates.tools[0].tool_id, "codex_c");
+        assert!(candidates.truncated);
+        assert_eq!(candidates.total_count, 2);
+
+        let (connected, candidates) =
+            cap_reported_tools(connected.tools, vec![make_tool("codex_c")], Some(1));
+        assert_eq!(connected.tools.len(), 1);
+        assert_eq!(connected.tools[0].tool_id, "openclaw_a_gw");
+        assert!(connected.truncated);
+        assert!(candidates.tools.is_empty());
+        assert!(candidates.truncated);
+        assert_eq!(candidates.total_count, 1);
+    }
+
+    #[test]
+    fn uncapped_snapshot_should_not_be_truncated() {
+        let (connected, candidates) = cap_reported_tools(
+            vec![make_tool("openclaw_a_gw")],
+            vec![make_tool("codex_c")],
+            None,
+        );
+        assert_eq!(connected.tools.len(), 1);
+        assert_eq!(candidates.tools.len(), 1);
+        assert!(!connected.truncated && !candidates.truncated);
+        assert_eq!(candidates.total_count, 1);
+    }
+
+    #[test]
+    fn whitelisted_codex_without_process_keeps_codex_identity() {
+        let whitelist = ToolWhitelistStore::from_ids_for_test(&["codex_abcd1234ef56_p42"]);
+
+        let (connected, candidates) = split_discovered_tools(&[], &whitelist);
+        assert!(candidates.is_empty());
+        assert_eq!(connected.len(), 1);
+        assert_eq!(connected[0].name, "Codex");
+        assert_eq!(connected[0].vendor, "OpenAI");
+        assert_eq!(connected[0].status, "OFFLINE");
+    }
+
+    #[test]
+    fn pseudo_filesystems_are_excluded_from_disk_breakdown() {
+        assert!(is_pseudo_filesystem("tmpfs"));
+        assert!(is_pseudo_filesystem("Overlay"));
+        assert!(!is_pseudo_filesystem("ext4"));
+        assert!(!is_pseudo_filesystem("apfs"));
+        assert!(!is_pseudo_filesystem("NTFS"));
+        assert_eq!(used_percent(25.0, 100.0), 25.0);
+        assert_eq!(used_percent(1.0, 0.0), 0.0);
+    }
 }
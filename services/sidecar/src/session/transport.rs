@@ -1,32 +1,62 @@
-//! 会话传输层：统一 envelope 下发。
+//! 会话传输层：统一 envelope 下发，超过阈值的大帧按配置压缩为 `compressed` 包装；
+//! 单连接内递增的 seq 在发送 worker 出队时写入。
+
+use std::sync::OnceLock;
 
 use anyhow::Result;
 use futures_util::Sink;
 use serde_json::Value;
 use tokio_tungstenite::tungstenite::Message;
-use yc_shared_protocol::{EventEnvelope, now_rfc3339_nanos};
+use yc_shared_protocol::{EventEnvelope, encode_envelope_text, now_rfc3339_nanos};
+
+/// 大帧压缩阈值环境变量（字节，0 或未设置表示不压缩）。
+const WS_COMPRESS_MIN_BYTES_ENV: &str = "SIDECAR_WS_COMPRESS_MIN_BYTES";
+/// 进程级压缩阈值（首次发送时读取）。
+static WS_COMPRESS_MIN_BYTES: OnceLock<usize> = OnceLock::new();
+
+/// 写入 relay 发送队列的待发帧。
+#[derive(Debug)]
+pub(crate) enum OutboundFrame {
+    /// 无需编号的控制帧（如心跳 Ping）。
+    Raw(Message),
+    /// 出队时才分配 seq 并编码的事件。
+    Event(Box<EventEnvelope>),
+}
 
-/// 发送标准 envelope 事件，并维护单连接内递增 seq。
+/// 读取大帧压缩阈值。
+fn ws_compress_min_bytes() -> usize {
+    *WS_COMPRESS_MIN_BYTES.get_or_init(|| {
+        std::env::var(WS_COMPRESS_MIN_BYTES_ENV)
+            .ok()
+            .and_then(|raw| raw.trim().parse::<usize>().ok())
+            .unwrap_or(0)
+    })
+}
+
+/// 发送标准 envelope 事件；seq 由发送 worker 出队时分配，被合并丢弃的快照不占用序号。
 pub(crate) async fn send_event<W>(
     ws_writer: &mut W,
     system_id: &str,
-    seq: &mut u64,
     event_type: &str,
     trace_id: Option<&str>,
     payload: Value,
 ) -> Result<()>
 where
-    W: Sink<Message, Error = tokio_tungstenite::tungstenite::Error> + Unpin,
+    W: Sink<OutboundFrame, Error = tokio_tungstenite::tungstenite::Error> + Unpin,
 {
-    *seq += 1;
     let mut env = EventEnvelope::new(event_type, system_id, payload);
-    env.seq = Some(*seq);
     env.ts = now_rfc3339_nanos();
     if let Some(value) = trace_id.map(str::trim).filter(|value| !value.is_empty()) {
         env.trace_id = Some(value.to_string());
     }
 
-    let raw = serde_json::to_string(&env)?;
-    futures_util::SinkExt::send(ws_writer, Message::Text(raw.into())).await?;
+    futures_util::SinkExt::send(ws_writer, OutboundFrame::Event(Box::new(env))).await?;
     Ok(())
 }
+
+/// 为出队事件写入 seq 并编码为 WebSocket 文本帧（超过阈值时压缩）。
+pub(crate) fn encode_event_frame(mut env: EventEnvelope, seq: u64) -> Result<Message> {
+    env.seq = Some(seq);
+    let raw = encode_envelope_text(&env, ws_compress_min_bytes())?;
+    Ok(Message::Text(raw.into()))
+}
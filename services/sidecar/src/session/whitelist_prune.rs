@@ -0,0 +1,210 @@
+//! 白名单缺失工具处理职责：
+//! 1. 跟踪已接入（白名单）工具在发现结果中持续缺失的时长。
+//! 2. `auto` 策略下超过宽限期即移出白名单，并产出 `tool_whitelist_updated`（`action=prune`）回执。
+//! 3. `manual` 策略（默认）保持白名单不变，仅对超过宽限期的缺失工具告警一次。
+//!
+//! 缺失时长只在内存中累计，sidecar 重启后重新计时。
+
+use std::{
+    collections::{HashMap, HashSet},
+    time::{Duration, Instant},
+};
+
+use tracing::{info, warn};
+use yc_shared_protocol::{COMMAND_ERROR_STORE_FAILED, CommandFeedbackPayload};
+
+use crate::stores::ToolWhitelistStore;
+
+/// 白名单清理策略环境变量。
+pub(crate) const WHITELIST_PRUNE_ENV: &str = "SIDECAR_WHITELIST_PRUNE";
+/// 白名单清理宽限期环境变量（秒）。
+pub(crate) const WHITELIST_PRUNE_GRACE_ENV: &str = "SIDECAR_WHITELIST_PRUNE_GRACE_SEC";
+/// 默认宽限期：7 天。
+pub(crate) const DEFAULT_WHITELIST_PRUNE_GRACE_SEC: u64 = 7 * 24 * 60 * 60;
+
+/// 白名单缺失工具处理策略。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub(crate) enum WhitelistPrunePolicy {
+    /// 超过宽限期后自动移出白名单。
+    Auto,
+    /// 保留白名单，仅告警（默认）。
+    #[default]
+    Manual,
+}
+
+impl WhitelistPrunePolicy {
+    /// 解析策略文本（大小写不敏感）。
+    fn parse(raw: &str) -> Option<Self> {
+        match raw.trim().to_ascii_lowercase().as_str() {
+            "auto" => Some(Self::Auto),
+            "manual" => Some(Self::Manual),
+            _ => None,
+        }
+    }
+
+    /// 读取当前进程的策略；未设置或无法识别时为 `manual`。
+    pub(crate) fn from_env() -> Self {
+        std::env::var(WHITELIST_PRUNE_ENV)
+            .ok()
+            .and_then(|raw| Self::parse(&raw))
+            .unwrap_or_default()
+    }
+
+    /// 策略名称。
+    pub(crate) fn as_str(self) -> &'static str {
+        match self {
+            Self::Auto => "auto",
+            Self::Manual => "manual",
+        }
+    }
+}
+
+/// 会话级白名单缺失跟踪器。
+#[derive(Debug)]
+pub(crate) struct WhitelistPruner {
+    /// 处理策略。
+    policy: WhitelistPrunePolicy,
+    /// 缺失宽限期。
+    grace: Duration,
+    /// 白名单条目首次被观察到缺失的时间。
+    missing_since: HashMap<String, Instant>,
+    /// 已告警过的条目（manual 策略下每次缺失只告警一次）。
+    warned: HashSet<String>,
+}
+
+impl WhitelistPruner {
+    /// 按策略与宽限期创建跟踪器。
+    pub(crate) fn new(policy: WhitelistPrunePolicy, grace: Duration) -> Self {
+        Self {
+            policy,
+            grace,
+            missing_since: HashMap::new(),
+            warned: HashSet::new(),
+        }
+    }
+
+    /// 以本轮发现结果检查白名单，返回需要下发的 prune 回执（仅 `auto` 策略可能非空）。
+    pub(crate) fn sweep<'a, I>(
+        &mut self,
+        whitelist: &mut ToolWhitelistStore,
+        discovered_ids: I,
+        now: Instant,
+    ) -> Vec<CommandFeedbackPayload>
+    where
+        I: IntoIterator<Item = &'a str>,
+    {
+        let absent = whitelist.absent_ids(discovered_ids);
+        // 重新出现或已被移出白名单的条目重新计时。
+        self.missing_since.retain(|id, _| absent.contains(id));
+        self.warned.retain(|id| absent.contains(id));
+
+        let mut feedbacks = Vec::new();
+        for tool_id in absent {
+            let since = *self.missing_since.entry(tool_id.clone()).or_insert(now);
+            let missing_for = now.saturating_duration_since(since);
+            if missing_for < self.grace {
+                continue;
+            }
+            match self.policy {
+                WhitelistPrunePolicy::Manual => {
+                    if self.warned.insert(tool_id.clone()) {
+                        warn!(
+                            "whitelisted tool missing: tool_id={tool_id} missing_sec={} policy=manual",
+                            missing_for.as_secs()
+                        );
+                    }
+                }
+                WhitelistPrunePolicy::Auto => {
+                    let feedback = CommandFeedbackPayload::new("prune", tool_id.clone());
+                    let feedback = match whitelist.remove(&tool_id) {
+                        Ok(changed) => {
+                            info!(
+                                "whitelisted tool pruned: tool_id={tool_id} missing_sec={}",
+                                missing_for.as_secs()
+                            );
+                            self.missing_since.remove(&tool_id);
+                            feedback.succeeded(changed)
+                        }
+                        Err(err) => feedback
+                            .failed(COMMAND_ERROR_STORE_FAILED, format!("更新白名单失败: {err}")),
+                    };
+                    feedbacks.push(feedback);
+                }
+            }
+        }
+        feedbacks
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::{WhitelistPrunePolicy, WhitelistPruner};
+    use crate::stores::ToolWhitelistStore;
+
+    const GRACE: Duration = Duration::from_secs(60);
+
+    #[test]
+    fn auto_prunes_absent_tool_after_grace() {
+        let mut whitelist =
+            ToolWhitelistStore::from_ids_for_test(&["codex_a", "openclaw_abcd1234ef56_gw"]);
+        let mut pruner = WhitelistPruner::new(WhitelistPrunePolicy::Auto, GRACE);
+        let start = Instant::now();
+        // OpenClaw PID 漂移仍视为在线，不计入缺失。
+        let discovered = ["openclaw_abcd1234ef56_p2048"];
+
+        assert!(pruner.sweep(&mut whitelist, discovered, start).is_empty());
+        let within = start + GRACE - Duration::from_secs(1);
+        assert!(pruner.sweep(&mut whitelist, discovered, within).is_empty());
+        assert!(whitelist.contains("codex_a"));
+
+        let feedbacks = pruner.sweep(&mut whitelist, discovered, start + GRACE);
+        assert_eq!(feedbacks.len(), 1);
+        let value = feedbacks[0].to_value();
+        assert_eq!(value["action"], "prune");
+        assert_eq!(value["toolId"], "codex_a");
+        assert_eq!(value["ok"], true);
+        assert_eq!(value["changed"], true);
+        assert!(!whitelist.contains("codex_a"));
+        assert!(whitelist.contains("openclaw_abcd1234ef56_gw"));
+    }
+
+    #[test]
+    fn manual_retains_absent_tool_and_reappearance_resets_timer() {
+        let mut whitelist = ToolWhitelistStore::from_ids_for_test(&["codex_a"]);
+        let mut pruner = WhitelistPruner::new(WhitelistPrunePolicy::Manual, GRACE);
+        let start = Instant::now();
+
+        assert!(pruner.sweep(&mut whitelist, [], start).is_empty());
+        assert!(
+            pruner
+                .sweep(&mut whitelist, [], start + GRACE * 10)
+                .is_empty()
+        );
+        assert!(whitelist.contains("codex_a"));
+
+        let mut auto = WhitelistPruner::new(WhitelistPrunePolicy::Auto, GRACE);
+        assert!(auto.sweep(&mut whitelist, [], start).is_empty());
+        assert!(
+            auto.sweep(&mut whitelist, ["codex_a"], start + GRACE)
+                .is_empty()
+        );
+        assert!(auto.sweep(&mut whitelist, [], start + GRACE).is_empty());
+        assert!(whitelist.contains("codex_a"));
+    }
+
+    #[test]
+    fn policy_parses_known_values_and_defaults_to_manual() {
+        assert_eq!(
+            WhitelistPrunePolicy::parse(" AUTO "),
+            Some(WhitelistPrunePolicy::Auto)
+        );
+        assert_eq!(
+            WhitelistPrunePolicy::parse("manual"),
+            Some(WhitelistPrunePolicy::Manual)
+        );
+        assert_eq!(WhitelistPrunePolicy::parse("sometimes"), None);
+        assert_eq!(WhitelistPrunePolicy::default().as_str(), "manual");
+    }
+}
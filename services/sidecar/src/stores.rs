@@ -1,16 +1,22 @@
 //! 本地状态存储模块职责：
 //! 1. 维护工具白名单（接入/断开）持久化。
 //! 2. 维护控制端设备白名单（授权绑定）持久化。
-//! 3. 提供最小化文件读写封装，保证主流程只关心业务语义。
+//! 3. 维护工具自定义显示名（按 toolId）持久化。
+//! 4. 提供最小化文件读写封装（临时文件 + rename 原子替换、加载时校验与恢复），
+//!    保证主流程只关心业务语义。
 
 use std::{
-    collections::HashSet,
+    collections::{BTreeMap, BTreeSet, HashSet},
     fs,
+    io::Write,
     path::{Path, PathBuf},
 };
 
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
 use tracing::{info, warn};
+use yc_shared_protocol::ToolRuntimePayload;
+
+use crate::home::sidecar_config_dir;
 
 fn openclaw_identity_hash(tool_id: &str) -> Option<&str> {
     let rest = tool_id.strip_prefix("openclaw_")?;
@@ -51,30 +57,17 @@ pub(crate) struct ToolWhitelistStore {
 }
 
 impl ToolWhitelistStore {
-    /// 从本地文件加载白名单；解析失败时回退为空集合。
+    /// 从本地文件加载白名单；无法恢复时回退为空集合。
     pub(crate) fn load() -> Self {
-        let path = tool_whitelist_path();
-        let Some(path_ref) = path.as_ref() else {
-            return Self {
-                path: None,
-                ids: HashSet::new(),
-            };
-        };
-
-        let bytes = match fs::read(path_ref) {
-            Ok(value) => value,
-            Err(_) => {
-                return Self {
-                    path,
-                    ids: HashSet::new(),
-                };
-            }
-        };
+        Self::load_from(tool_whitelist_path())
+    }
 
-        let parsed = serde_json::from_slice::<ToolWhitelistFile>(&bytes).unwrap_or_else(|err| {
-            warn!("load tool whitelist failed: {err}");
-            ToolWhitelistFile::default()
-        });
+    /// 从指定路径加载白名单。
+    fn load_from(path: Option<PathBuf>) -> Self {
+        let parsed = path
+            .as_deref()
+            .map(|path| load_store_file::<ToolWhitelistFile>(path, "tool whitelist"))
+            .unwrap_or_default();
 
         Self {
             path,
@@ -87,6 +80,27 @@ impl ToolWhitelistStore {
         }
     }
 
+    /// 重新读取白名单文件（配置热加载），返回内容是否变化；
+    /// 读取或解析失败时保留内存中的当前白名单。
+    pub(crate) fn reload(&mut self) -> bool {
+        let Some(parsed) = self
+            .path
+            .as_deref()
+            .and_then(|path| reload_store_file::<ToolWhitelistFile>(path, "tool whitelist"))
+        else {
+            return false;
+        };
+        let ids = parsed
+            .tool_ids
+            .into_iter()
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty())
+            .collect::<HashSet<String>>();
+        let changed = ids != self.ids;
+        self.ids = ids;
+        changed
+    }
+
     /// 判断工具是否已在白名单中。
     pub(crate) fn contains(&self, tool_id: &str) -> bool {
         self.ids.contains(tool_id)
@@ -129,6 +143,49 @@ impl ToolWhitelistStore {
             == 1
     }
 
+    /// 返回未被任何已发现工具命中的白名单条目（已排序）。
+    /// 命中规则与 `contains_compatible` 一致：OpenClaw 身份按 hash 匹配，单实例时允许 hash 漂移。
+    pub(crate) fn absent_ids<'a, I>(&self, discovered_ids: I) -> Vec<String>
+    where
+        I: IntoIterator<Item = &'a str>,
+    {
+        let mut present = HashSet::new();
+        let mut discovered_openclaw_hashes = HashSet::new();
+        for tool_id in discovered_ids {
+            present.insert(tool_id);
+            if let Some(hash) = openclaw_identity_hash(tool_id) {
+                discovered_openclaw_hashes.insert(hash);
+            }
+        }
+        let single_openclaw = self
+            .ids
+            .iter()
+            .filter_map(|id| openclaw_identity_hash(id))
+            .count()
+            == 1;
+
+        let mut absent = self
+            .ids
+            .iter()
+            .filter(|id| {
+                if present.contains(id.as_str()) {
+                    return false;
+                }
+                match openclaw_identity_hash(id) {
+                    Some(hash) => {
+                        let matched = discovered_openclaw_hashes.contains(hash)
+                            || (single_openclaw && !discovered_openclaw_hashes.is_empty());
+                        !matched
+                    }
+                    None => true,
+                }
+            })
+            .cloned()
+            .collect::<Vec<String>>();
+        absent.sort();
+        absent
+    }
+
     /// 将工具加入白名单并立即落盘；返回是否实际发生变更。
     pub(crate) fn add(&mut self, tool_id: &str) -> anyhow::Result<bool> {
         let before = self.ids.clone();
@@ -198,14 +255,11 @@ impl ToolWhitelistStore {
         Ok(removed)
     }
 
-    /// 持久化白名单：创建目录、排序后写入 JSON。
+    /// 持久化白名单：排序后原子写入 JSON。
     fn save(&self) -> anyhow::Result<()> {
         let Some(path) = self.path.as_ref() else {
             return Ok(());
         };
-        if let Some(parent) = path.parent() {
-            fs::create_dir_all(parent)?;
-        }
 
         let mut tool_ids = self
             .ids
@@ -214,9 +268,7 @@ impl ToolWhitelistStore {
             .collect::<Vec<String>>();
         tool_ids.sort();
 
-        let bytes = serde_json::to_vec_pretty(&ToolWhitelistFile { tool_ids })?;
-        fs::write(path, bytes)?;
-        Ok(())
+        save_store_file(path, &ToolWhitelistFile { tool_ids })
     }
 
     #[cfg(test)]
@@ -242,6 +294,68 @@ struct ControllerDevicesFile {
     device_ids: Vec<String>,
 }
 
+/// 控制端绑定策略环境变量。
+pub(crate) const CONTROLLER_BIND_POLICY_ENV: &str = "CONTROLLER_BIND_POLICY";
+/// 同时等待审批的设备数上限，超出后新设备只拒绝、不再登记。
+const MAX_PENDING_CONTROLLERS: usize = 16;
+
+/// 未绑定设备发控制命令时的处理策略。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ControllerBindPolicy {
+    /// 白名单为空时首个 app 自动绑定。
+    First,
+    /// 只接受 `CONTROLLER_DEVICE_IDS` 预置或重绑写入的设备。
+    SeedOnly,
+    /// 未绑定设备的命令被拒绝并登记待审批，由已绑定设备经 `controller_rebind_request` 确认。
+    ManualApprove,
+}
+
+impl ControllerBindPolicy {
+    /// 解析策略文本（大小写不敏感）。
+    fn parse(raw: &str) -> Option<Self> {
+        match raw.trim().to_ascii_lowercase().as_str() {
+            "first" => Some(Self::First),
+            "seed_only" => Some(Self::SeedOnly),
+            "manual_approve" => Some(Self::ManualApprove),
+            _ => None,
+        }
+    }
+
+    /// 解析 `CONTROLLER_BIND_POLICY`；未设置或无法识别时按首绑开关回退为 first / seed_only。
+    pub(crate) fn resolve(raw: Option<&str>, allow_first_bind: bool) -> Self {
+        raw.and_then(Self::parse).unwrap_or(if allow_first_bind {
+            Self::First
+        } else {
+            Self::SeedOnly
+        })
+    }
+
+    /// 策略名称。
+    pub(crate) fn as_str(self) -> &'static str {
+        match self {
+            Self::First => "first",
+            Self::SeedOnly => "seed_only",
+            Self::ManualApprove => "manual_approve",
+        }
+    }
+}
+
+/// 控制命令授权结果。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum ControllerAuthorization {
+    /// 允许执行。
+    Allowed,
+    /// 拒绝执行。
+    Denied(String),
+    /// 来源设备等待审批，命令被拒绝；`newly_requested` 为真时需向控制端发出绑定请求。
+    PendingApproval {
+        /// 拒绝原因。
+        reason: String,
+        /// 是否本次新登记为待审批。
+        newly_requested: bool,
+    },
+}
+
 /// 控制设备白名单存储。
 #[derive(Debug, Clone)]
 pub(crate) struct ControllerDevicesStore {
@@ -249,34 +363,24 @@ pub(crate) struct ControllerDevicesStore {
     path: Option<PathBuf>,
     /// 内存集合，避免重复查询文件。
     ids: HashSet<String>,
+    /// 未绑定设备的处理策略。
+    policy: ControllerBindPolicy,
+    /// 等待审批的设备（仅内存，sidecar 重启后清空）。
+    pending: BTreeSet<String>,
 }
 
 impl ControllerDevicesStore {
-    /// 从本地文件加载控制设备列表；失败时返回空集合。
-    pub(crate) fn load() -> Self {
-        let path = controller_devices_path();
-        let Some(path_ref) = path.as_ref() else {
-            return Self {
-                path: None,
-                ids: HashSet::new(),
-            };
-        };
-
-        let bytes = match fs::read(path_ref) {
-            Ok(value) => value,
-            Err(_) => {
-                return Self {
-                    path,
-                    ids: HashSet::new(),
-                };
-            }
-        };
+    /// 从本地文件加载控制设备列表；无法恢复时返回空集合。
+    pub(crate) fn load(policy: ControllerBindPolicy) -> Self {
+        Self::load_from(controller_devices_path(), policy)
+    }
 
-        let parsed =
-            serde_json::from_slice::<ControllerDevicesFile>(&bytes).unwrap_or_else(|err| {
-                warn!("load controller devices failed: {err}");
-                ControllerDevicesFile::default()
-            });
+    /// 从指定路径加载控制设备列表。
+    fn load_from(path: Option<PathBuf>, policy: ControllerBindPolicy) -> Self {
+        let parsed = path
+            .as_deref()
+            .map(|path| load_store_file::<ControllerDevicesFile>(path, "controller devices"))
+            .unwrap_or_default();
 
         Self {
             path,
@@ -286,46 +390,101 @@ impl ControllerDevicesStore {
                 .map(|value| value.trim().to_string())
                 .filter(|value| !value.is_empty())
                 .collect(),
+            policy,
+            pending: BTreeSet::new(),
+        }
+    }
+
+    /// 重新读取控制设备文件并切换绑定策略（配置热加载），返回内容或策略是否变化；
+    /// 仍在等待审批的设备保留，已被绑定的从待审批中移除；文件读取或解析失败时保留当前设备列表。
+    pub(crate) fn reload(&mut self, policy: ControllerBindPolicy) -> bool {
+        let reloaded = self
+            .path
+            .as_deref()
+            .and_then(|path| reload_store_file::<ControllerDevicesFile>(path, "controller devices"))
+            .map(|parsed| {
+                parsed
+                    .device_ids
+                    .into_iter()
+                    .map(|value| value.trim().to_string())
+                    .filter(|value| !value.is_empty())
+                    .collect::<HashSet<String>>()
+            });
+        let ids_changed = reloaded.as_ref().is_some_and(|ids| *ids != self.ids);
+        let changed = ids_changed || policy != self.policy;
+        if let Some(ids) = reloaded {
+            self.ids = ids;
         }
+        self.policy = policy;
+        let ids = &self.ids;
+        self.pending.retain(|device_id| !ids.contains(device_id));
+        changed
     }
 
-    /// 校验命令来源是否有权限；必要时执行首次绑定。
+    /// 校验命令来源是否有权限；按策略执行首次绑定或登记待审批。
     pub(crate) fn authorize_or_bind(
         &mut self,
         source_client_type: &str,
         source_device_id: &str,
-        allow_first_bind: bool,
-    ) -> anyhow::Result<(bool, String)> {
+    ) -> anyhow::Result<ControllerAuthorization> {
         // 仅允许 app 客户端发控制命令，sidecar/其他类型全部拒绝。
         if source_client_type != "app" {
-            return Ok((false, "仅接受 app 客户端控制命令。".to_string()));
+            return Ok(ControllerAuthorization::Denied(
+                "仅接受 app 客户端控制命令。".to_string(),
+            ));
         }
 
         let device_id = source_device_id.trim();
         if device_id.is_empty() {
-            return Ok((false, "缺少来源设备标识。".to_string()));
+            return Ok(ControllerAuthorization::Denied(
+                "缺少来源设备标识。".to_string(),
+            ));
         }
 
-        // 未绑定任何设备时可按配置自动绑定首个设备，降低首启门槛。
-        if self.ids.is_empty() {
-            if !allow_first_bind {
-                return Ok((
-                    false,
-                    "当前未绑定控制设备。请在 sidecar 环境变量设置 CONTROLLER_DEVICE_IDS，或开启 ALLOW_FIRST_CONTROLLER_BIND。"
+        if self.ids.contains(device_id) {
+            return Ok(ControllerAuthorization::Allowed);
+        }
+
+        match self.policy {
+            // 未绑定任何设备时自动绑定首个设备，降低首启门槛。
+            ControllerBindPolicy::First if self.ids.is_empty() => {
+                self.ids.insert(device_id.to_string());
+                self.save()?;
+                info!("controller device bound: {device_id}");
+                Ok(ControllerAuthorization::Allowed)
+            }
+            _ if self.ids.is_empty() => Ok(ControllerAuthorization::Denied(
+                "当前未绑定控制设备。请在 sidecar 环境变量设置 CONTROLLER_DEVICE_IDS，或将 CONTROLLER_BIND_POLICY 设为 first。"
+                    .to_string(),
+            )),
+            ControllerBindPolicy::ManualApprove => {
+                let newly_requested = self.pending.len() < MAX_PENDING_CONTROLLERS
+                    && self.pending.insert(device_id.to_string());
+                if newly_requested {
+                    info!("controller device pending approval: {device_id}");
+                }
+                Ok(ControllerAuthorization::PendingApproval {
+                    reason: "该设备尚未获批控制当前 sidecar，已请求已绑定设备审批，批准后请重试。"
                         .to_string(),
-                ));
+                    newly_requested,
+                })
             }
-            self.ids.insert(device_id.to_string());
-            self.save()?;
-            info!("controller device bound: {device_id}");
-            return Ok((true, String::new()));
+            _ => Ok(ControllerAuthorization::Denied(
+                "该设备未被授权控制当前 sidecar。".to_string(),
+            )),
         }
+    }
 
-        if self.ids.contains(device_id) {
-            return Ok((true, String::new()));
-        }
+    /// 等待审批的设备 ID（升序）。
+    pub(crate) fn pending_ids(&self) -> Vec<String> {
+        self.pending.iter().cloned().collect()
+    }
 
-        Ok((false, "该设备未被授权控制当前 sidecar。".to_string()))
+    /// 校验重绑发起方：`manual_approve` 策略下必须是已绑定 app 设备，其他策略不限制。
+    pub(crate) fn rebind_denied_reason(&self, source_device_id: &str) -> Option<&'static str> {
+        (self.policy == ControllerBindPolicy::ManualApprove
+            && !self.ids.contains(source_device_id.trim()))
+        .then_some("当前策略为 manual_approve，仅已绑定控制设备可重绑或审批。")
     }
 
     /// 用环境变量预置设备 ID 初始化白名单。
@@ -346,13 +505,21 @@ impl ControllerDevicesStore {
         Ok(())
     }
 
-    /// 把控制端白名单重绑为单个设备（覆盖原集合）。
+    /// 把控制端白名单重绑为单个设备（覆盖原集合）；目标为待审批设备时改为追加授权。
     pub(crate) fn rebind(&mut self, device_id: &str) -> anyhow::Result<bool> {
         let value = device_id.trim();
         if value.is_empty() {
             return Ok(false);
         }
 
+        // 审批：待审批设备追加进白名单，不覆盖已绑定设备。
+        if self.pending.remove(value) {
+            self.ids.insert(value.to_string());
+            self.save()?;
+            info!("controller device approved: {value}");
+            return Ok(true);
+        }
+
         let unchanged = self.ids.len() == 1 && self.ids.contains(value);
         if unchanged {
             return Ok(false);
@@ -370,9 +537,6 @@ impl ControllerDevicesStore {
         let Some(path) = self.path.as_ref() else {
             return Ok(());
         };
-        if let Some(parent) = path.parent() {
-            fs::create_dir_all(parent)?;
-        }
 
         let mut device_ids = self
             .ids
@@ -380,45 +544,218 @@ impl ControllerDevicesStore {
             .map(|value| value.to_string())
             .collect::<Vec<String>>();
         device_ids.sort();
-        let bytes = serde_json::to_vec_pretty(&ControllerDevicesFile { device_ids })?;
-        fs::write(path, bytes)?;
-        Ok(())
+        save_store_file(path, &ControllerDevicesFile { device_ids })
     }
 }
 
-/// 工具白名单文件路径：`~/.config/yourconnector/sidecar/tool-whitelist.json`。
-fn tool_whitelist_path() -> Option<PathBuf> {
-    let home = std::env::var("HOME").ok()?;
-    if home.trim().is_empty() {
+/// 工具显示名最大字符数，超出部分截断。
+const TOOL_LABEL_MAX_CHARS: usize = 64;
+
+/// 工具显示名文件结构。
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ToolLabelsFile {
+    /// toolId -> 自定义显示名。
+    #[serde(default)]
+    labels: BTreeMap<String, String>,
+}
+
+/// 工具自定义显示名存储：只写入 `displayName`，不改动适配器识别出的 `name/vendor/category`。
+#[derive(Debug, Clone)]
+pub(crate) struct ToolLabelStore {
+    /// 存储文件路径；为空时表示无法落盘。
+    path: Option<PathBuf>,
+    /// 内存中的显示名映射。
+    labels: BTreeMap<String, String>,
+}
+
+impl ToolLabelStore {
+    /// 从本地文件加载显示名；解析失败时回退为空映射。
+    pub(crate) fn load() -> Self {
+        let path = tool_labels_path();
+        let parsed = path
+            .as_deref()
+            .map(|path| load_store_file::<ToolLabelsFile>(path, "tool labels"))
+            .unwrap_or_default();
+
+        Self {
+            path,
+            labels: parsed
+                .labels
+                .into_iter()
+                .filter_map(|(tool_id, label)| {
+                    let tool_id = tool_id.trim().to_string();
+                    let label = normalize_tool_label(&label)?;
+                    (!tool_id.is_empty()).then_some((tool_id, label))
+                })
+                .collect(),
+        }
+    }
+
+    /// 设置工具显示名并落盘；空白显示名表示清除。返回是否实际发生变更。
+    pub(crate) fn set(&mut self, tool_id: &str, label: &str) -> anyhow::Result<bool> {
+        let tool_id = tool_id.trim();
+        if tool_id.is_empty() {
+            return Ok(false);
+        }
+        let changed = match normalize_tool_label(label) {
+            Some(label) => self.labels.insert(tool_id.to_string(), label.clone()) != Some(label),
+            None => self.labels.remove(tool_id).is_some(),
+        };
+        if changed {
+            self.save()?;
+        }
+        Ok(changed)
+    }
+
+    /// 把显示名写入工具列表的 `displayName` 字段。
+    pub(crate) fn apply(&self, tools: &mut [ToolRuntimePayload]) {
+        for tool in tools {
+            tool.display_name = self.labels.get(&tool.tool_id).cloned();
+        }
+    }
+
+    /// 持久化显示名映射。
+    fn save(&self) -> anyhow::Result<()> {
+        let Some(path) = self.path.as_ref() else {
+            return Ok(());
+        };
+        save_store_file(
+            path,
+            &ToolLabelsFile {
+                labels: self.labels.clone(),
+            },
+        )
+    }
+
+    #[cfg(test)]
+    /// 测试辅助：构造不落盘的空存储。
+    pub(crate) fn empty_for_test() -> Self {
+        Self {
+            path: None,
+            labels: BTreeMap::new(),
+        }
+    }
+}
+
+/// 规整显示名：去除首尾空白并截断；空白返回 None。
+fn normalize_tool_label(label: &str) -> Option<String> {
+    let label = label.trim();
+    if label.is_empty() {
         return None;
     }
-    Some(
-        Path::new(&home)
-            .join(".config")
-            .join("yourconnector")
-            .join("sidecar")
-            .join("tool-whitelist.json"),
-    )
+    Some(label.chars().take(TOOL_LABEL_MAX_CHARS).collect())
+}
+
+/// 原子写入存储文件：先写同目录临时文件并 fsync，再 rename 覆盖正式文件，
+/// 进程在任意时刻中断都不会留下半截的正式文件。
+fn save_store_file<T: Serialize>(path: &Path, value: &T) -> anyhow::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let bytes = serde_json::to_vec_pretty(value)?;
+    let temp = store_temp_path(path);
+    {
+        let mut file = fs::File::create(&temp)?;
+        file.write_all(&bytes)?;
+        file.sync_all()?;
+    }
+    fs::rename(&temp, path)?;
+    Ok(())
+}
+
+/// 加载存储文件：
+/// 1. 正式文件有效时直接使用，并清理中断写入残留的临时文件；
+/// 2. 正式文件缺失或损坏时，尝试已完整写入但未来得及 rename 的临时文件；
+/// 3. 仍无法恢复时把损坏文件备份为 `.corrupt` 并告警，从空状态开始。
+fn load_store_file<T: DeserializeOwned + Default>(path: &Path, what: &str) -> T {
+    let temp = store_temp_path(path);
+    let primary = match fs::read(path) {
+        Ok(bytes) => Some(serde_json::from_slice::<T>(&bytes)),
+        Err(_) => None,
+    };
+    if let Some(Ok(value)) = primary {
+        let _ = fs::remove_file(&temp);
+        return value;
+    }
+
+    if let Some(value) = fs::read(&temp)
+        .ok()
+        .and_then(|bytes| serde_json::from_slice::<T>(&bytes).ok())
+    {
+        warn!("{what} store recovered from temp file: {}", temp.display());
+        if let Err(err) = fs::rename(&temp, path) {
+            warn!("promote {what} temp file failed: {err}");
+        }
+        return value;
+    }
+    let _ = fs::remove_file(&temp);
+
+    if let Some(Err(err)) = primary {
+        let backup = path.with_extension("json.corrupt");
+        warn!(
+            "load {what} failed: {err}; starting fresh, corrupt file moved to {}",
+            backup.display()
+        );
+        if let Err(err) = fs::rename(path, &backup) {
+            warn!("backup corrupt {what} file failed: {err}");
+        }
+    }
+    T::default()
+}
+
+/// 热加载读取存储文件：读取或解析失败时告警并返回 `None`，由调用方保留内存中的当前内容；
+/// 与启动加载不同，不回退临时文件，也不把文件移为 `.corrupt`（可能只是编辑到一半）。
+fn reload_store_file<T: DeserializeOwned>(path: &Path, what: &str) -> Option<T> {
+    let bytes = match fs::read(path) {
+        Ok(bytes) => bytes,
+        Err(err) => {
+            warn!("reload {what} failed: {err}; keeping current entries");
+            return None;
+        }
+    };
+    match serde_json::from_slice::<T>(&bytes) {
+        Ok(value) => Some(value),
+        Err(err) => {
+            warn!("reload {what} failed: {err}; keeping current entries");
+            None
+        }
+    }
+}
+
+/// 存储文件对应的临时文件路径（同目录，保证 rename 原子）。
+fn store_temp_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".tmp");
+    path.with_file_name(name)
+}
+
+/// 工具白名单文件路径：`~/.config/yourconnector/sidecar/tool-whitelist.json`。
+fn tool_whitelist_path() -> Option<PathBuf> {
+    sidecar_config_dir().map(|dir| dir.join("tool-whitelist.json"))
 }
 
 /// 控制设备白名单文件路径：`~/.config/yourconnector/sidecar/controller-devices.json`。
 fn controller_devices_path() -> Option<PathBuf> {
-    let home = std::env::var("HOME").ok()?;
-    if home.trim().is_empty() {
-        return None;
-    }
-    Some(
-        Path::new(&home)
-            .join(".config")
-            .join("yourconnector")
-            .join("sidecar")
-            .join("controller-devices.json"),
-    )
+    sidecar_config_dir().map(|dir| dir.join("controller-devices.json"))
+}
+
+/// 工具显示名文件路径：`~/.config/yourconnector/sidecar/tool-labels.json`。
+fn tool_labels_path() -> Option<PathBuf> {
+    sidecar_config_dir().map(|dir| dir.join("tool-labels.json"))
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{ToolWhitelistStore, openclaw_identity_hash};
+    use yc_shared_protocol::ToolRuntimePayload;
+
+    use std::{fs, path::PathBuf};
+
+    use super::{
+        ControllerAuthorization, ControllerBindPolicy, ControllerDevicesStore, ToolLabelStore,
+        ToolWhitelistStore, openclaw_identity_hash, store_temp_path,
+    };
+    use crate::tooling::adapters::codex;
 
     #[test]
     fn openclaw_identity_hash_should_support_gateway_and_pid_variants() {
@@ -482,4 +819,199 @@ mod tests {
         assert!(changed);
         assert!(whitelist.list_ids().is_empty());
     }
+
+    #[test]
+    fn tool_label_overrides_display_name_without_affecting_matching() {
+        let mut labels = ToolLabelStore::empty_for_test();
+        let mut tools = vec![ToolRuntimePayload {
+            tool_id: "codex_abcd".to_string(),
+            name: "Codex".to_string(),
+            vendor: "OpenAI".to_string(),
+            ..ToolRuntimePayload::default()
+        }];
+
+        assert!(labels.set("codex_abcd", "  Prod deploy agent ").unwrap());
+        assert!(!labels.set("codex_abcd", "Prod deploy agent").unwrap());
+        labels.apply(&mut tools);
+        assert_eq!(tools[0].display_name.as_deref(), Some("Prod deploy agent"));
+        assert_eq!(tools[0].name, "Codex");
+        assert!(codex::matches_tool(&tools[0]));
+
+        assert!(labels.set("codex_abcd", " ").unwrap());
+        labels.apply(&mut tools);
+        assert_eq!(tools[0].display_name, None);
+    }
+
+    fn make_temp_dir(prefix: &str) -> PathBuf {
+        let mut dir = std::env::temp_dir();
+        dir.push(format!(
+            "yc_sidecar_store_test_{prefix}_{}_{}",
+            std::process::id(),
+            uuid::Uuid::new_v4()
+        ));
+        fs::create_dir_all(&dir).expect("create temp dir");
+        dir
+    }
+
+    #[test]
+    fn interrupted_write_keeps_last_good_whitelist() {
+        let dir = make_temp_dir("whitelist");
+        let path = dir.join("tool-whitelist.json");
+        let mut whitelist = ToolWhitelistStore::load_from(Some(path.clone()));
+        whitelist.add("codex_a").expect("add should succeed");
+        whitelist.add("claude_b").expect("add should succeed");
+
+        // 模拟写临时文件途中崩溃：留下半截 JSON，正式文件不受影响。
+        let temp = store_temp_path(&path);
+        fs::write(&temp, b"{\"toolIds\":[\"codex_a\",").expect("write partial temp");
+
+        let restored = ToolWhitelistStore::load_from(Some(path));
+        assert_eq!(restored.list_ids(), vec!["claude_b", "codex_a"]);
+        assert!(!temp.exists());
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn corrupt_store_recovers_from_complete_temp_or_starts_fresh() {
+        let dir = make_temp_dir("controllers");
+        let path = dir.join("controller-devices.json");
+        fs::write(&path, b"{not json").expect("write corrupt primary");
+        fs::write(store_temp_path(&path), br#"{"deviceIds":["ios_a"]}"#)
+            .expect("write complete temp");
+
+        let mut controllers =
+            ControllerDevicesStore::load_from(Some(path.clone()), ControllerBindPolicy::SeedOnly);
+        let decision = controllers
+            .authorize_or_bind("app", "ios_a")
+            .expect("authorize should succeed");
+        assert_eq!(decision, ControllerAuthorization::Allowed);
+        assert!(fs::read_to_string(&path).unwrap().contains("ios_a"));
+
+        fs::write(&path, b"{not json").expect("write corrupt primary");
+        let mut controllers =
+            ControllerDevicesStore::load_from(Some(path.clone()), ControllerBindPolicy::SeedOnly);
+        let decision = controllers
+            .authorize_or_bind("app", "ios_a")
+            .expect("authorize should succeed");
+        assert!(matches!(decision, ControllerAuthorization::Denied(_)));
+        assert!(path.with_extension("json.corrupt").exists());
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn reload_picks_up_external_edits_and_switches_policy() {
+        let dir = make_temp_dir("reload");
+        let whitelist_path = dir.join("tool-whitelist.json");
+        let mut whitelist = ToolWhitelistStore::load_from(Some(whitelist_path.clone()));
+        whitelist.add("codex_a").expect("add should succeed");
+        assert!(!whitelist.reload(), "unchanged file reloads as no-op");
+        fs::write(&whitelist_path, br#"{"toolIds":["claude_b"]}"#).expect("edit whitelist");
+        assert!(whitelist.reload());
+        assert_eq!(whitelist.list_ids(), vec!["claude_b"]);
+        // 编辑到一半的文件不清空内存白名单，也不被移为 .corrupt。
+        fs::write(&whitelist_path, br#"{"toolIds":["#).expect("truncate whitelist");
+        assert!(!whitelist.reload());
+        assert_eq!(whitelist.list_ids(), vec!["claude_b"]);
+        assert!(whitelist_path.exists());
+
+        let controllers_path = dir.join("controller-devices.json");
+        let mut controllers = ControllerDevicesStore::load_from(
+            Some(controllers_path.clone()),
+            ControllerBindPolicy::ManualApprove,
+        );
+        controllers.seed(&["ios_a".to_string()]).expect("seed");
+        let decision = controllers
+            .authorize_or_bind("app", "ios_b")
+            .expect("authorize");
+        assert!(matches!(
+            decision,
+            ControllerAuthorization::PendingApproval { .. }
+        ));
+        fs::write(&controllers_path, br#"{"deviceIds":["ios_a","ios_b"]}"#)
+            .expect("edit controllers");
+        assert!(controllers.reload(ControllerBindPolicy::ManualApprove));
+        assert!(controllers.pending_ids().is_empty());
+        assert_eq!(
+            controllers
+                .authorize_or_bind("app", "ios_b")
+                .expect("authorize"),
+            ControllerAuthorization::Allowed
+        );
+        fs::write(&controllers_path, b"not json").expect("corrupt controllers");
+        assert!(!controllers.reload(ControllerBindPolicy::ManualApprove));
+        assert_eq!(
+            controllers
+                .authorize_or_bind("app", "ios_b")
+                .expect("authorize"),
+            ControllerAuthorization::Allowed
+        );
+        assert!(controllers.reload(ControllerBindPolicy::SeedOnly));
+        assert!(matches!(
+            controllers
+                .authorize_or_bind("app", "ios_c")
+                .expect("authorize"),
+            ControllerAuthorization::Denied(_)
+        ));
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn bind_policy_resolves_env_with_legacy_fallback() {
+        assert_eq!(
+            ControllerBindPolicy::resolve(Some(" Manual_Approve "), true),
+            ControllerBindPolicy::ManualApprove
+        );
+        assert_eq!(
+            ControllerBindPolicy::resolve(Some("bogus"), true),
+            ControllerBindPolicy::First
+        );
+        assert_eq!(
+            ControllerBindPolicy::resolve(None, false),
+            ControllerBindPolicy::SeedOnly
+        );
+    }
+
+    #[test]
+    fn manual_approve_holds_unbound_device_until_bound_device_approves() {
+        let mut controllers =
+            ControllerDevicesStore::load_from(None, ControllerBindPolicy::ManualApprove);
+        let decision = controllers
+            .authorize_or_bind("app", "ios_a")
+            .expect("authorize");
+        assert!(matches!(decision, ControllerAuthorization::Denied(_)));
+
+        controllers.seed(&["ios_a".to_string()]).expect("seed");
+        let first = controllers
+            .authorize_or_bind("app", "ios_b")
+            .expect("authorize");
+        assert!(matches!(
+            first,
+            ControllerAuthorization::PendingApproval {
+                newly_requested: true,
+                ..
+            }
+        ));
+        let repeat = controllers
+            .authorize_or_bind("app", "ios_b")
+            .expect("authorize");
+        assert!(matches!(
+            repeat,
+            ControllerAuthorization::PendingApproval {
+                newly_requested: false,
+                ..
+            }
+        ));
+        assert_eq!(controllers.pending_ids(), vec!["ios_b"]);
+
+        assert!(controllers.rebind_denied_reason("ios_b").is_some());
+        assert!(controllers.rebind_denied_reason("ios_a").is_none());
+        assert!(controllers.rebind("ios_b").expect("approve"));
+        assert!(controllers.pending_ids().is_empty());
+        for device in ["ios_a", "ios_b"] {
+            let decision = controllers
+                .authorize_or_bind("app", device)
+                .expect("authorize");
+            assert_eq!(decision, ControllerAuthorization::Allowed);
+        }
+    }
 }
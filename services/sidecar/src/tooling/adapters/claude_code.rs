@@ -57,6 +57,7 @@ pub(crate) fn discover(context: &ToolDiscoveryContext<'_>) -> Vec<ToolRuntimePay
         tools.push(ToolRuntimePayload {
             tool_id,
             name: "Claude Code".to_string(),
+            display_name: None,
             tool_class: "code".to_string(),
             category: "CODE_AGENT".to_string(),
             vendor: "Anthropic".to_string(),
@@ -87,6 +88,7 @@ pub(crate) fn discover(context: &ToolDiscoveryContext<'_>) -> Vec<ToolRuntimePay
             latest_tokens: Some(LatestTokensPayload::default()),
             model_usage: Vec::new(),
             collected_at: Some(now_rfc3339_nanos()),
+            extra: Default::default(),
         });
     }
     tools
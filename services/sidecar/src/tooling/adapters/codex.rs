@@ -60,6 +60,7 @@ pub(crate) fn discover(context: &ToolDiscoveryContext<'_>) -> Vec<ToolRuntimePay
         tools.push(ToolRuntimePayload {
             tool_id,
             name: "Codex".to_string(),
+            display_name: None,
             tool_class: "code".to_string(),
             category: "CODE_AGENT".to_string(),
             vendor: "OpenAI".to_string(),
@@ -90,6 +91,7 @@ pub(crate) fn discover(context: &ToolDiscoveryContext<'_>) -> Vec<ToolRuntimePay
             latest_tokens: Some(LatestTokensPayload::default()),
             model_usage: Vec::new(),
             collected_at: Some(now_rfc3339_nanos()),
+            extra: Default::default(),
         });
     }
     tools
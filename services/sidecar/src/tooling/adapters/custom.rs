@@ -0,0 +1,401 @@
+//! 自定义工具适配器职责：
+//! 1. 从 `<配置根目录>/yourconnector/tools.toml` 加载用户声明的工具定义（命令子串、名称、厂商、
+//!    模式识别正则），格式错误的条目告警后跳过，不影响其它定义与内置发现。
+//! 2. 按定义发现匹配进程并输出 custom.v1 详情，回显命中的定义字段。
+
+use std::{collections::HashSet, path::PathBuf, sync::OnceLock};
+
+use regex::Regex;
+use serde::Deserialize;
+use serde_json::json;
+use tracing::{info, warn};
+use yc_shared_protocol::{ToolRuntimePayload, now_rfc3339_nanos};
+
+use crate::{
+    home::BaseDirs,
+    tooling::{
+        adapters::CUSTOM_SCHEMA_V1,
+        core::types::{ToolDetailCollectOptions, ToolDetailCollectResult, ToolDiscoveryContext},
+    },
+};
+
+/// 自定义工具 ID 前缀。
+const CUSTOM_TOOL_ID_PREFIX: &str = "custom_";
+/// 自定义工具 source 前缀，后接定义 ID。
+const CUSTOM_SOURCE_PREFIX: &str = "custom-tool-probe:id=";
+/// 未命中任何模式正则时的默认运行模式。
+const DEFAULT_CUSTOM_MODE: &str = "CLI";
+
+/// 进程级缓存的自定义工具定义（首次使用时加载）。
+static CUSTOM_TOOL_DEFINITIONS: OnceLock<Vec<CustomToolDefinition>> = OnceLock::new();
+
+/// `tools.toml` 顶层结构；条目逐个解析，避免单条错误导致整体失败。
+#[derive(Debug, Deserialize)]
+struct CustomToolsFile {
+    /// 工具定义列表（`[[tools]]`）。
+    #[serde(default)]
+    tools: Vec<toml::Value>,
+}
+
+/// 单条工具定义的原始字段。
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawCustomTool {
+    /// 定义 ID，仅允许字母、数字、`-`，参与生成 toolId。
+    id: String,
+    /// 显示名称。
+    name: String,
+    /// 厂商。
+    #[serde(default)]
+    vendor: String,
+    /// 命令行子串（大小写不敏感）。
+    #[serde(rename = "match")]
+    command_match: String,
+    /// 工具业务类别（assistant|code）。
+    #[serde(default = "default_tool_class")]
+    tool_class: String,
+    /// 工具类别。
+    #[serde(default = "default_category")]
+    category: String,
+    /// 模式识别规则，按声明顺序取首个命中者。
+    #[serde(default)]
+    modes: Vec<RawCustomMode>,
+}
+
+/// 单条模式识别规则的原始字段。
+#[derive(Debug, Deserialize)]
+struct RawCustomMode {
+    /// 命中后的运行模式（如 TUI/CLI/SERVE）。
+    mode: String,
+    /// 匹配完整命令行的正则。
+    pattern: String,
+}
+
+/// 默认工具业务类别。
+fn default_tool_class() -> String {
+    "code".to_string()
+}
+
+/// 默认工具类别。
+fn default_category() -> String {
+    "CODE_AGENT".to_string()
+}
+
+/// 已校验的自定义工具定义。
+#[derive(Debug, Clone)]
+pub(crate) struct CustomToolDefinition {
+    /// 定义 ID。
+    pub(crate) id: String,
+    /// 显示名称。
+    pub(crate) name: String,
+    /// 厂商。
+    pub(crate) vendor: String,
+    /// 小写命令行子串。
+    pub(crate) command_match: String,
+    /// 工具业务类别。
+    pub(crate) tool_class: String,
+    /// 工具类别。
+    pub(crate) category: String,
+    /// 模式识别规则（模式名，正则）。
+    pub(crate) modes: Vec<(String, Regex)>,
+}
+
+impl CustomToolDefinition {
+    /// 校验并编译原始定义。
+    fn from_raw(raw: RawCustomTool) -> Result<Self, String> {
+        let id = raw.id.trim().to_ascii_lowercase();
+        if id.is_empty() || !id.chars().all(|ch| ch.is_ascii_alphanumeric() || ch == '-') {
+            return Err(format!("invalid id `{}`, expected [a-z0-9-]+", raw.id));
+        }
+        let command_match = raw.command_match.trim().to_lowercase();
+        if command_match.is_empty() {
+            return Err("match must not be empty".to_string());
+        }
+        let name = raw.name.trim().to_string();
+        if name.is_empty() {
+            return Err("name must not be empty".to_string());
+        }
+        let modes = raw
+            .modes
+            .into_iter()
+            .map(|rule| {
+                Regex::new(&rule.pattern)
+                    .map(|pattern| (rule.mode.trim().to_ascii_uppercase(), pattern))
+                    .map_err(|err| format!("invalid mode pattern `{}`: {err}", rule.pattern))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self {
+            id,
+            name,
+            vendor: raw.vendor.trim().to_string(),
+            command_match,
+            tool_class: raw.tool_class.trim().to_string(),
+            category: raw.category.trim().to_string(),
+            modes,
+        })
+    }
+
+    /// 判断小写命令行是否命中本定义。
+    fn matches_command(&self, cmd_lower: &str) -> bool {
+        cmd_lower.contains(&self.command_match)
+    }
+
+    /// 按模式规则识别运行模式，未命中时为 `CLI`。
+    fn detect_mode(&self, cmd: &str) -> String {
+        self.modes
+            .iter()
+            .find(|(_, pattern)| pattern.is_match(cmd))
+            .map(|(mode, _)| mode.clone())
+            .unwrap_or_else(|| DEFAULT_CUSTOM_MODE.to_string())
+    }
+}
+
+/// 自定义工具定义文件路径：`<配置根目录>/yourconnector/tools.toml`。
+fn custom_tools_file_path() -> Option<PathBuf> {
+    BaseDirs::from_env()
+        .config_dir()
+        .ok()
+        .map(|dir| dir.join("yourconnector").join("tools.toml"))
+}
+
+/// 解析定义文件内容；格式错误的条目告警后跳过。
+pub(crate) fn parse_custom_tools(raw: &str) -> Vec<CustomToolDefinition> {
+    let file = match toml::from_str::<CustomToolsFile>(raw) {
+        Ok(file) => file,
+        Err(err) => {
+            warn!("parse custom tools file failed: {err}");
+            return Vec::new();
+        }
+    };
+    let mut seen_ids = HashSet::new();
+    let mut definitions = Vec::new();
+    for (index, entry) in file.tools.into_iter().enumerate() {
+        let parsed = entry
+            .try_into::<RawCustomTool>()
+            .map_err(|err| err.to_string())
+            .and_then(CustomToolDefinition::from_raw);
+        match parsed {
+            Ok(definition) if !seen_ids.insert(definition.id.clone()) => {
+                warn!(
+                    "skip custom tool #{index}: duplicate id `{}`",
+                    definition.id
+                );
+            }
+            Ok(definition) => definitions.push(definition),
+            Err(err) => warn!("skip custom tool #{index}: {err}"),
+        }
+    }
+    definitions
+}
+
+/// 已加载的自定义工具定义；文件不存在时为空。
+pub(crate) fn custom_tool_definitions() -> &'static [CustomToolDefinition] {
+    CUSTOM_TOOL_DEFINITIONS.get_or_init(|| {
+        let Some(path) = custom_tools_file_path() else {
+            return Vec::new();
+        };
+        let Ok(raw) = std::fs::read_to_string(&path) else {
+            return Vec::new();
+        };
+        let definitions = parse_custom_tools(&raw);
+        info!(
+            "custom tools loaded path={} definitions={}",
+            path.display(),
+            definitions.len()
+        );
+        definitions
+    })
+}
+
+/// 判断小写命令行是否命中任一自定义工具定义（用于发现阶段进程过滤）。
+pub(crate) fn is_custom_candidate_command(cmd_lower: &str) -> bool {
+    custom_tool_definitions()
+        .iter()
+        .any(|definition| definition.matches_command(cmd_lower))
+}
+
+/// 按定义发现工具实例；包装进程（子进程同样命中）只保留实际运行进程。
+pub(crate) fn discover(
+    definitions: &[CustomToolDefinition],
+    context: &ToolDiscoveryContext<'_>,
+) -> Vec<ToolRuntimePayload> {
+    let mut tools = Vec::new();
+    for definition in definitions {
+        let mut candidate_pids = context
+            .all
+            .values()
+            .filter(|info| definition.matches_command(&info.cmd.to_lowercase()))
+            .map(|info| info.pid)
+            .collect::<Vec<i32>>();
+        candidate_pids.sort_unstable();
+        let candidate_set = candidate_pids.iter().copied().collect::<HashSet<i32>>();
+
+        for pid in candidate_pids {
+            let is_wrapper = context
+                .children_by_ppid
+                .get(&pid)
+                .map(|children| children.iter().any(|child| candidate_set.contains(child)))
+                .unwrap_or(false);
+            if is_wrapper {
+                continue;
+            }
+            let Some(info) = context.all.get(&pid) else {
+                continue;
+            };
+            let workspace = crate::normalize_path(&info.cwd);
+            tools.push(ToolRuntimePayload {
+                tool_id: crate::build_custom_tool_id(&definition.id, workspace.as_str(), pid),
+                name: definition.name.clone(),
+                display_name: None,
+                tool_class: definition.tool_class.clone(),
+                category: definition.category.clone(),
+                vendor: definition.vendor.clone(),
+                mode: definition.detect_mode(&info.cmd),
+                status: "RUNNING".to_string(),
+                connected: true,
+                endpoint: String::new(),
+                pid: Some(pid),
+                reason: crate::option_non_empty(format!(
+                    "已按自定义定义 {} 发现进程",
+                    definition.id
+                )),
+                cpu_percent: Some(crate::round2(info.cpu_percent)),
+                memory_mb: Some(crate::round2(info.memory_mb)),
+                source: Some(format!("{CUSTOM_SOURCE_PREFIX}{}", definition.id)),
+                workspace_dir: crate::option_non_empty(workspace),
+                session_id: None,
+                session_title: None,
+                session_updated_at: None,
+                agent_mode: None,
+                provider_id: None,
+                model_id: None,
+                model: None,
+                latest_tokens: None,
+                model_usage: Vec::new(),
+                collected_at: Some(now_rfc3339_nanos()),
+                extra: Default::default(),
+            });
+        }
+    }
+    tools
+}
+
+/// 判断指定工具是否由自定义定义发现。
+pub(crate) fn matches_tool(tool: &ToolRuntimePayload) -> bool {
+    tool.tool_id.starts_with(CUSTOM_TOOL_ID_PREFIX)
+        && tool
+            .source
+            .as_deref()
+            .is_some_and(|source| source.starts_with(CUSTOM_SOURCE_PREFIX))
+}
+
+/// 输出 custom.v1 详情：回显命中的定义字段与运行信息。
+pub(crate) fn collect_details(
+    definitions: &[CustomToolDefinition],
+    tools: &[ToolRuntimePayload],
+    _options: &ToolDetailCollectOptions,
+) -> Vec<ToolDetailCollectResult> {
+    tools
+        .iter()
+        .map(|tool| {
+            let definition_id = tool
+                .source
+                .as_deref()
+                .and_then(|source| source.strip_prefix(CUSTOM_SOURCE_PREFIX))
+                .unwrap_or_default();
+            let Some(definition) = definitions.iter().find(|item| item.id == definition_id) else {
+                return ToolDetailCollectResult::failed(
+                    tool.tool_id.clone(),
+                    CUSTOM_SCHEMA_V1,
+                    None,
+                    format!("自定义工具定义 {definition_id} 已不存在"),
+                );
+            };
+            ToolDetailCollectResult::success(
+                tool.tool_id.clone(),
+                CUSTOM_SCHEMA_V1,
+                None,
+                json!({
+                    "definitionId": definition.id,
+                    "name": definition.name,
+                    "vendor": definition.vendor,
+                    "match": definition.command_match,
+                    "mode": tool.mode,
+                    "workspaceDir": tool.workspace_dir.clone().unwrap_or_default(),
+                    "pid": tool.pid,
+                    "collectedAt": now_rfc3339_nanos(),
+                }),
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::{ProcInfo, tooling::core::types::ToolDiscoveryContext};
+
+    use super::{discover, matches_tool, parse_custom_tools};
+
+    fn proc_info(pid: i32, cmd: &str) -> ProcInfo {
+        ProcInfo {
+            pid,
+            cmd: cmd.to_string(),
+            cwd: "/workspace/acme".to_string(),
+            cpu_percent: 0.0,
+            memory_mb: 0.0,
+        }
+    }
+
+    #[test]
+    fn valid_definitions_discover_processes_and_malformed_ones_are_skipped() {
+        let definitions = parse_custom_tools(
+            r#"
+            [[tools]]
+            id = "acme"
+            name = "Acme Agent"
+            vendor = "Acme"
+            match = "acme-agent"
+            modes = [
+              { mode = "serve", pattern = '\bserve\b' },
+              { mode = "TUI", pattern = '--tui' },
+            ]
+
+            [[tools]]
+            id = "broken"
+            name = "Broken"
+            match = "broken"
+            modes = [{ mode = "SERVE", pattern = "(" }]
+
+            [[tools]]
+            name = "Missing id"
+            match = "missing"
+            "#,
+        );
+        assert_eq!(definitions.len(), 1);
+
+        let mut all = HashMap::<i32, ProcInfo>::new();
+        all.insert(
+            10,
+            proc_info(10, "node /usr/bin/acme-agent serve --port 9000"),
+        );
+        all.insert(11, proc_info(11, "/opt/acme/ACME-AGENT serve --port 9000"));
+        all.insert(20, proc_info(20, "acme-agent --tui"));
+        let children_by_ppid = HashMap::from([(10, vec![11])]);
+        let context = ToolDiscoveryContext {
+            all: &all,
+            children_by_ppid: &children_by_ppid,
+        };
+
+        let tools = discover(&definitions, &context);
+        assert_eq!(tools.len(), 2, "wrapper pid 10 is skipped");
+        assert_eq!(tools[0].pid, Some(11));
+        assert_eq!(tools[0].mode, "SERVE");
+        assert_eq!(tools[0].name, "Acme Agent");
+        assert!(tools[0].tool_id.starts_with("custom_acme_"));
+        assert_eq!(tools[1].mode, "TUI");
+        assert!(tools.iter().all(matches_tool));
+        assert!(parse_custom_tools("tools = 3").is_empty());
+    }
+}
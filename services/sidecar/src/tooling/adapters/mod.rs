@@ -1,11 +1,13 @@
 //! 工具适配器注册模块职责：
-//! 1. 汇总 OpenCode/OpenClaw 适配器并对外暴露统一入口。
+//! 1. 汇总 OpenCode/OpenClaw/Codex/Claude Code 与用户自定义工具适配器，经 `registry` 对外暴露统一入口。
 //! 2. 定义工具详情 schema 常量，确保跨端字段约定稳定。
 
 pub(crate) mod claude_code;
 pub(crate) mod codex;
+pub(crate) mod custom;
 pub(crate) mod openclaw;
 pub(crate) mod opencode;
+pub(crate) mod registry;
 
 /// OpenClaw 详情结构版本标识。
 pub(crate) const OPENCLAW_SCHEMA_V1: &str = "openclaw.v1";
@@ -15,3 +17,5 @@ pub(crate) const OPENCODE_SCHEMA_V1: &str = "opencode.v1";
 pub(crate) const CODEX_SCHEMA_V1: &str = "codex.v1";
 /// Claude Code 详情结构版本标识。
 pub(crate) const CLAUDE_CODE_SCHEMA_V1: &str = "claude-code.v1";
+/// 自定义工具详情结构版本标识。
+pub(crate) const CUSTOM_SCHEMA_V1: &str = "custom.v1";
@@ -3,6 +3,7 @@
 //! 2. 采集 OpenClaw 运行态数据并组装 `openclaw.v1` 结构化详情。
 //! 3. 在采集失败时仅标记 stale，不清空最近一次成功数据。
 //! 4. 仅读取非敏感本地配置白名单字段（上下文/模型窗口/费率）。
+//! 5. 按 `SIDECAR_OPENCLAW_REDACT` 在下发前剔除用户指定的敏感字段路径。
 
 use std::{
     cmp::Reverse,
@@ -18,9 +19,13 @@ use serde_json::{Map, Value, json};
 use tokio::{process::Command, time::timeout};
 use yc_shared_protocol::{LatestTokensPayload, ToolRuntimePayload, now_rfc3339_nanos};
 
+use crate::home::BaseDirs;
 use crate::tooling::{
     adapters::OPENCLAW_SCHEMA_V1,
-    core::types::{ToolDetailCollectOptions, ToolDetailCollectResult, ToolDiscoveryContext},
+    core::types::{
+        ToolDetailCollectOptions, ToolDetailCollectResult, ToolDiscoveryContext, effective_timeout,
+    },
+    tool_logs::ToolLogLocation,
 };
 
 /// `status --json --usage` 的超时上限（毫秒）。
@@ -45,6 +50,8 @@ const USAGE_WINDOW_SEC_1H: i64 = 3600;
 const INACTIVE_SESSION_SEC: i64 = 6 * 3600;
 /// 会话“24 小时活跃”阈值（秒）。
 const ACTIVE_SESSION_24H_SEC: i64 = 24 * 3600;
+/// 详情字段脱敏路径配置（逗号分隔，点号分层，如 `usage.estimatedCost`）。
+const OPENCLAW_REDACT_ENV: &str = "SIDECAR_OPENCLAW_REDACT";
 
 /// 单模型费率配置（来自 openclaw.json 白名单字段）。
 #[derive(Debug, Clone, Default)]
@@ -113,6 +120,7 @@ pub(crate) fn discover(context: &ToolDiscoveryContext<'_>) -> Vec<ToolRuntimePay
         tools.push(ToolRuntimePayload {
             tool_id,
             name: "OpenClaw".to_string(),
+            display_name: None,
             tool_class: "assistant".to_string(),
             category: "DEV_WORKER".to_string(),
             vendor: "OpenClaw".to_string(),
@@ -136,6 +144,7 @@ pub(crate) fn discover(context: &ToolDiscoveryContext<'_>) -> Vec<ToolRuntimePay
             latest_tokens: Some(LatestTokensPayload::default()),
             model_usage: Vec::new(),
             collected_at: Some(now_rfc3339_nanos()),
+            extra: Default::default(),
         });
     }
 
@@ -210,7 +219,7 @@ pub(crate) async fn collect_details(
     }
 
     let max_parallel = options.max_parallel.max(1);
-    stream::iter(grouped.into_iter())
+    stream::iter(grouped)
         .map(|(profile_key, profile_tools)| async move {
             collect_profile_details(&profile_key, &profile_tools, options, include_deep_details)
                 .await
@@ -408,6 +417,7 @@ async fn collect_profile_details(
     let security_findings = parse_security_findings(&status_json, security_status.as_ref());
     let memory_index = parse_memory_index(&status_json, memory_status.as_ref());
     let dashboard_meta = parse_dashboard_meta(&status_json, gateway_status.as_ref());
+    let redact_paths = redact_paths_from_env();
 
     tools
         .iter()
@@ -456,7 +466,7 @@ async fn collect_profile_details(
                 "healthSummary": health_summary,
             });
 
-            let data = json!({
+            let mut data = json!({
                 "overview": overview,
                 "agents": scoped_agents,
                 "sessions": sessions_payload,
@@ -471,6 +481,7 @@ async fn collect_profile_details(
                 "channelOverview": channel_overview,
                 "healthSummary": health_summary,
             });
+            apply_redactions(&mut data, &redact_paths);
 
             ToolDetailCollectResult::success(
                 tool.tool_id.clone(),
@@ -482,6 +493,58 @@ async fn collect_profile_details(
         .collect()
 }
 
+/// 读取脱敏字段路径配置；未配置时返回空列表。
+fn redact_paths_from_env() -> Vec<Vec<String>> {
+    env::var(OPENCLAW_REDACT_ENV)
+        .map(|raw| parse_redact_paths(&raw))
+        .unwrap_or_default()
+}
+
+/// 解析逗号分隔的字段路径列表，每条路径按点号拆分。
+fn parse_redact_paths(raw: &str) -> Vec<Vec<String>> {
+    raw.split(',')
+        .map(str::trim)
+        .filter(|path| !path.is_empty())
+        .map(|path| {
+            path.split('.')
+                .map(str::trim)
+                .filter(|segment| !segment.is_empty())
+                .map(ToString::to_string)
+                .collect::<Vec<String>>()
+        })
+        .filter(|segments| !segments.is_empty())
+        .collect()
+}
+
+/// 从 `openclaw.v1` 详情中删除指定路径字段。
+fn apply_redactions(data: &mut Value, paths: &[Vec<String>]) {
+    for path in paths {
+        redact_path(data, path);
+    }
+}
+
+/// 删除单条路径；途经数组时对每个元素递归应用剩余路径。
+fn redact_path(value: &mut Value, path: &[String]) {
+    let Some((head, rest)) = path.split_first() else {
+        return;
+    };
+    match value {
+        Value::Array(items) => {
+            for item in items {
+                redact_path(item, path);
+            }
+        }
+        Value::Object(map) => {
+            if rest.is_empty() {
+                map.remove(head);
+            } else if let Some(child) = map.get_mut(head) {
+                redact_path(child, rest);
+            }
+        }
+        _ => {}
+    }
+}
+
 /// 运行 status：优先 `--usage`，失败时自动降级到纯 status。
 async fn run_status_json(profile_key: &str, command_timeout: Duration) -> Result<Value> {
     match run_openclaw_json(
@@ -496,15 +559,6 @@ async fn run_status_json(profile_key: &str, command_timeout: Duration) -> Result
     }
 }
 
-/// 按“全局超时 + 命令级上限”计算本次命令的有效超时。
-fn effective_timeout(global_timeout: Duration, command_cap_ms: u64) -> Duration {
-    let command_cap = Duration::from_millis(command_cap_ms.max(1));
-    if global_timeout.is_zero() {
-        return command_cap;
-    }
-    global_timeout.min(command_cap)
-}
-
 /// 执行 openclaw 子命令并解析 JSON 输出。
 async fn run_openclaw_json(
     profile_key: &str,
@@ -591,22 +645,40 @@ fn parse_profile_key_from_tool(tool: &ToolRuntimePayload) -> String {
     "default".to_string()
 }
 
-/// 根据 profileKey 推导本地状态目录。
-fn resolve_profile_state_dir(profile_key: &str) -> PathBuf {
-    let home = env::var("HOME")
+/// 工具日志位置：profile 状态目录下的 `logs/`。
+pub(crate) fn log_location(tool: &ToolRuntimePayload) -> Option<ToolLogLocation> {
+    let root = resolve_profile_state_dir(&parse_profile_key_from_tool(tool))?;
+    Some(ToolLogLocation {
+        dir: root.join("logs"),
+        root,
+    })
+}
+
+/// 根据 profileKey 推导本地状态目录；用户目录不可解析时返回 None。
+fn resolve_profile_state_dir(profile_key: &str) -> Option<PathBuf> {
+    profile_state_dir_in(&BaseDirs::from_env(), profile_key)
+}
+
+/// 在给定基础目录下推导状态目录：优先用户主目录（Windows 含 USERPROFILE），
+/// 主目录不可解析时回退 Windows `%APPDATA%`。
+fn profile_state_dir_in(base_dirs: &BaseDirs, profile_key: &str) -> Option<PathBuf> {
+    let root = base_dirs
+        .home_dir()
         .ok()
-        .map(PathBuf::from)
-        .unwrap_or_else(|| PathBuf::from("."));
-    match profile_key.trim() {
-        "dev" => home.join(".openclaw-dev"),
-        "" | "default" => home.join(".openclaw"),
-        name => home.join(format!(".openclaw-{name}")),
-    }
+        .or_else(|| base_dirs.app_data_dir())?;
+    let state_dir = match profile_key.trim() {
+        "dev" => root.join(".openclaw-dev"),
+        "" | "default" => root.join(".openclaw"),
+        name => root.join(format!(".openclaw-{name}")),
+    };
+    Some(state_dir)
 }
 
 /// 读取 openclaw.json 白名单字段（上下文/模型窗口/费率）。
 fn load_profile_config_whitelist(profile_key: &str) -> LocalProfileConfig {
-    let state_dir = resolve_profile_state_dir(profile_key);
+    let Some(state_dir) = resolve_profile_state_dir(profile_key) else {
+        return LocalProfileConfig::default();
+    };
     let config_path = state_dir.join("openclaw.json");
     let raw = fs::read_to_string(config_path).ok();
     let Some(text) = raw else {
@@ -2644,18 +2716,49 @@ fn set_if_non_empty(obj: &mut Map<String, Value>, key: &str, value: String) {
 
 #[cfg(test)]
 mod tests {
-    use std::collections::HashMap;
+    use std::{collections::HashMap, path::PathBuf};
 
     use serde_json::json;
+    use yc_shared_protocol::DirsPlatform;
 
     use super::{
-        attach_agent_context_metrics, build_model_lookup, build_sessions_payload, discover,
-        parse_auth_user_by_provider, parse_channel_identities, parse_dashboard_meta,
-        parse_gateway_runtime, parse_profile_key_from_cmd, parse_status_default_agent_id,
-        parse_status_recent_sessions, parse_usage_windows, resolve_profile_state_dir,
-        select_agents_by_workspace, select_sessions_by_agents, to_percent,
+        apply_redactions, attach_agent_context_metrics, build_model_lookup, build_sessions_payload,
+        discover, parse_auth_user_by_provider, parse_channel_identities, parse_dashboard_meta,
+        parse_gateway_runtime, parse_profile_key_from_cmd, parse_redact_paths,
+        parse_status_default_agent_id, parse_status_recent_sessions, parse_usage_windows,
+        profile_state_dir_in, resolve_profile_state_dir, select_agents_by_workspace,
+        select_sessions_by_agents, to_percent,
     };
-    use crate::{ProcInfo, tooling::core::types::ToolDiscoveryContext};
+    use crate::{ProcInfo, home::BaseDirs, tooling::core::types::ToolDiscoveryContext};
+
+    #[test]
+    fn redact_estimated_cost_keeps_other_usage_fields() {
+        let mut data = json!({
+            "usage": {
+                "windowPreset": "1h",
+                "estimatedCost": {"totalUsd": 1.25},
+                "modelTotals": [{"model": "gpt-5", "tokens": 42}]
+            },
+            "overview": {"channels": [{"channel": "telegram", "username": "alice"}]}
+        });
+        let paths = parse_redact_paths(" usage.estimatedCost, ,overview.channels.username ");
+        assert_eq!(paths.len(), 2);
+
+        apply_redactions(&mut data, &paths);
+
+        assert!(data["usage"].get("estimatedCost").is_none());
+        assert_eq!(data["usage"]["windowPreset"], "1h");
+        assert_eq!(data["usage"]["modelTotals"][0]["tokens"], 42);
+        assert!(data["overview"]["channels"][0].get("username").is_none());
+        assert_eq!(data["overview"]["channels"][0]["channel"], "telegram");
+    }
+
+    #[test]
+    fn redact_unknown_path_is_noop() {
+        let mut data = json!({"usage": {"estimatedCost": 1}});
+        apply_redactions(&mut data, &parse_redact_paths("usage.missing.leaf,agents"));
+        assert_eq!(data, json!({"usage": {"estimatedCost": 1}}));
+    }
 
     #[test]
     fn parse_profile_key_supports_dev_profile_and_default() {
@@ -2675,21 +2778,67 @@ mod tests {
     fn resolve_state_dir_by_profile() {
         assert!(
             resolve_profile_state_dir("default")
+                .expect("home should resolve in tests")
                 .to_string_lossy()
                 .contains(".openclaw")
         );
         assert!(
             resolve_profile_state_dir("dev")
+                .expect("home should resolve in tests")
                 .to_string_lossy()
                 .contains(".openclaw-dev")
         );
         assert!(
             resolve_profile_state_dir("team")
+                .expect("home should resolve in tests")
                 .to_string_lossy()
                 .contains(".openclaw-team")
         );
     }
 
+    #[test]
+    fn state_dir_resolves_on_unix_and_windows() {
+        let lookup = |vars: &'static [(&'static str, &'static str)]| {
+            move |key: &str| {
+                vars.iter()
+                    .find(|(name, _)| *name == key)
+                    .map(|(_, value)| value.to_string())
+            }
+        };
+        let unix = BaseDirs::resolve(lookup(&[("HOME", "/home/a")]), || None, DirsPlatform::Unix);
+        assert_eq!(
+            profile_state_dir_in(&unix, "team"),
+            Some(PathBuf::from("/home/a/.openclaw-team"))
+        );
+
+        let windows = BaseDirs::resolve(
+            lookup(&[("USERPROFILE", "/users/a"), ("APPDATA", "/users/a/roaming")]),
+            || None,
+            DirsPlatform::Windows,
+        );
+        assert_eq!(
+            profile_state_dir_in(&windows, "default"),
+            Some(PathBuf::from("/users/a/.openclaw"))
+        );
+
+        let app_data_only = BaseDirs::resolve(
+            lookup(&[("APPDATA", "/users/a/roaming")]),
+            || None,
+            DirsPlatform::Windows,
+        );
+        assert_eq!(
+            profile_state_dir_in(&app_data_only, "dev"),
+            Some(PathBuf::from("/users/a/roaming/.openclaw-dev"))
+        );
+        assert_eq!(
+            profile_state_dir_in(
+                &BaseDirs::resolve(lookup(&[]), || None, DirsPlatform::Unix),
+                "default"
+            ),
+            None
+        );
+    }
+
     #[test]
     fn parse_status_paths_from_nested_objects() {
         let status = json!({
@@ -1,18 +1,41 @@
 //! OpenCode 适配器职责：
 //! 1. 基于进程与本地会话文件发现 OpenCode 工具实例。
 //! 2. 输出 opencode.v1 详情数据，统一接入 Tool Adapter Core。
+//! 3. 附带工作区 git 分支与未提交改动标记；git 不可用或超时时字段为 null，不标记 stale。
 
-use std::{collections::HashSet, process::Command};
+use std::{
+    collections::{HashMap, HashSet},
+    path::PathBuf,
+    process::Command,
+    time::Duration,
+};
 
 use chrono::{Duration as ChronoDuration, Utc};
 use serde_json::{Value, json};
+use tokio::{process::Command as AsyncCommand, time::timeout};
 use yc_shared_protocol::{ToolRuntimePayload, now_rfc3339_nanos};
 
+use crate::home::BaseDirs;
 use crate::tooling::{
     adapters::OPENCODE_SCHEMA_V1,
-    core::types::{ToolDetailCollectOptions, ToolDetailCollectResult, ToolDiscoveryContext},
+    core::types::{
+        ToolDetailCollectOptions, ToolDetailCollectResult, ToolDiscoveryContext, effective_timeout,
+    },
+    tool_logs::ToolLogLocation,
 };
 
+/// 单条 git 命令的超时上限（毫秒）。
+const GIT_TIMEOUT_CAP_MS: u64 = 2_000;
+
+/// 工作区 git 状态；非仓库、git 缺失或命令失败时对应字段为空。
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct WorkspaceGitState {
+    /// 当前分支（detached HEAD 时为 `HEAD`）。
+    branch: Option<String>,
+    /// 是否存在未提交改动（含未跟踪文件）。
+    dirty: Option<bool>,
+}
+
 /// 发现所有 OpenCode 工具实例。
 pub(crate) fn discover(context: &ToolDiscoveryContext<'_>) -> Vec<ToolRuntimePayload> {
     // 第一层：优先发现 wrapper 进程（`opencode`），并绑定其 runtime 子进程。
@@ -111,6 +134,7 @@ fn build_tool_from_process(
     Some(ToolRuntimePayload {
         tool_id,
         name: "OpenCode".to_string(),
+        display_name: None,
         tool_class: "code".to_string(),
         category: "CODE_AGENT".to_string(),
         vendor: "OpenCode".to_string(),
@@ -134,6 +158,7 @@ fn build_tool_from_process(
         latest_tokens: Some(state.latest_tokens),
         model_usage: state.model_usage,
         collected_at: Some(now_rfc3339_nanos()),
+        extra: Default::default(),
     })
 }
 
@@ -145,8 +170,23 @@ pub(crate) fn matches_tool(tool: &ToolRuntimePayload) -> bool {
     tool_id.starts_with("opencode_") || name.contains("opencode") || vendor.contains("opencode")
 }
 
+/// 工具日志位置：OpenCode 数据目录下的 `log/`。
+pub(crate) fn log_location(_tool: &ToolRuntimePayload) -> Option<ToolLogLocation> {
+    let root = resolve_data_dir()?;
+    Some(ToolLogLocation {
+        dir: root.join("log"),
+        root,
+    })
+}
+
+/// 推导 OpenCode 本地数据目录（`~/.local/share/opencode`）；数据根目录不可解析时返回 None。
+fn resolve_data_dir() -> Option<PathBuf> {
+    let base_dirs = BaseDirs::from_env();
+    Some(base_dirs.data_dir().ok()?.join("opencode"))
+}
+
 /// 采集 OpenCode 详情数据（opencode.v1）。
-pub(crate) fn collect_details(
+pub(crate) async fn collect_details(
     tools: &[ToolRuntimePayload],
     options: &ToolDetailCollectOptions,
 ) -> Vec<ToolDetailCollectResult> {
@@ -154,10 +194,18 @@ pub(crate) fn collect_details(
     let config_json = run_opencode_debug_json(&["debug", "config"]);
     let skill_snapshot = collect_skill_snapshot(config_json.as_ref());
     let mcp_snapshot = collect_mcp_snapshot(config_json.as_ref());
+    let git_timeout = effective_timeout(options.command_timeout, GIT_TIMEOUT_CAP_MS);
+    // 同一工作区的多个实例只探测一次。
+    let mut git_states: HashMap<String, WorkspaceGitState> = HashMap::new();
 
     for tool in tools {
         let workspace = tool.workspace_dir.clone().unwrap_or_default();
         let session_state = crate::collect_opencode_session_state(&workspace);
+        if !git_states.contains_key(&workspace) {
+            let state = collect_workspace_git_state(&workspace, git_timeout).await;
+            git_states.insert(workspace.clone(), state);
+        }
+        let git_state = &git_states[&workspace];
         let data = json!({
             "workspaceDir": workspace,
             "sessionId": session_state.session_id,
@@ -171,6 +219,8 @@ pub(crate) fn collect_details(
             "modelUsage": session_state.model_usage,
             "skills": skill_snapshot,
             "mcp": mcp_snapshot,
+            "gitBranch": git_state.branch,
+            "gitDirty": git_state.dirty,
         });
 
         results.push(ToolDetailCollectResult::success(
@@ -217,6 +267,48 @@ fn run_opencode_debug_json(args: &[&str]) -> Option<Value> {
     serde_json::from_slice::<Value>(&output.stdout).ok()
 }
 
+/// 探测工作区 git 分支与是否有未提交改动；两条命令各自失败互不影响。
+async fn collect_workspace_git_state(
+    workspace: &str,
+    command_timeout: Duration,
+) -> WorkspaceGitState {
+    if workspace.trim().is_empty() {
+        return WorkspaceGitState::default();
+    }
+    let branch = run_git(
+        workspace,
+        &["rev-parse", "--abbrev-ref", "HEAD"],
+        command_timeout,
+    )
+    .await
+    .map(|stdout| stdout.trim().to_string())
+    .filter(|branch| !branch.is_empty());
+    let dirty = run_git(workspace, &["status", "--porcelain"], command_timeout)
+        .await
+        .map(|stdout| stdout.lines().any(|line| !line.trim().is_empty()));
+    WorkspaceGitState { branch, dirty }
+}
+
+/// 在工作区执行 git 子命令，返回 stdout；失败或超时返回 None（超时时终止子进程）。
+async fn run_git(workspace: &str, args: &[&str], command_timeout: Duration) -> Option<String> {
+    let mut command = AsyncCommand::new("git");
+    command
+        .arg("-C")
+        .arg(workspace)
+        .args(args)
+        // 只读探测不抢占 index.lock，避免干扰用户正在进行的 git 操作。
+        .env("GIT_OPTIONAL_LOCKS", "0")
+        .kill_on_drop(true);
+    let output = timeout(command_timeout, command.output())
+        .await
+        .ok()?
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
 /// 收集 skills 详情，并给出启用/未启用分类。
 fn collect_skill_snapshot(config_json: Option<&Value>) -> Value {
     let skills_rows = run_opencode_debug_json(&["debug", "skill"])
@@ -334,7 +426,10 @@ mod tests {
 
     use serde_json::json;
 
-    use super::{collect_mcp_snapshot, collect_skill_snapshot, discover, skill_allowed};
+    use super::{
+        collect_mcp_snapshot, collect_skill_snapshot, collect_workspace_git_state, discover,
+        skill_allowed,
+    };
     use crate::{ProcInfo, tooling::core::types::ToolDiscoveryContext};
 
     fn proc(pid: i32, cmd: &str, cwd: &str) -> ProcInfo {
@@ -444,4 +539,38 @@ mod tests {
                 .is_some()
         );
     }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn git_state_reports_branch_and_dirty_and_degrades_outside_repo() {
+        let timeout = std::time::Duration::from_secs(5);
+        let dir = std::env::temp_dir().join(format!("yc-opencode-git-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).expect("create dir");
+        let workspace = dir.to_string_lossy().to_string();
+
+        let outside = collect_workspace_git_state(&workspace, timeout).await;
+        assert_eq!(outside.branch, None);
+        assert_eq!(outside.dirty, None);
+
+        let git = |args: &[&str]| {
+            let status = std::process::Command::new("git")
+                .arg("-C")
+                .arg(&dir)
+                .args(["-c", "user.name=yc", "-c", "user.email=yc@example.com"])
+                .args(args)
+                .output()
+                .expect("run git")
+                .status;
+            assert!(status.success(), "git {args:?}");
+        };
+        git(&["init", "-q", "-b", "feature/x"]);
+        git(&["commit", "-q", "--allow-empty", "-m", "init"]);
+        let clean = collect_workspace_git_state(&workspace, timeout).await;
+        assert_eq!(clean.branch.as_deref(), Some("feature/x"));
+        assert_eq!(clean.dirty, Some(false));
+
+        std::fs::write(dir.join("notes.txt"), "wip").expect("write file");
+        let dirty = collect_workspace_git_state(&workspace, timeout).await;
+        assert_eq!(dirty.dirty, Some(true));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
 }
@@ -0,0 +1,318 @@
+//! 工具适配器注册表职责：
+//! 1. 定义 `ToolAdapter` trait，统一发现、归属判断、schema、详情采集与日志位置入口。
+//! 2. 按注册顺序维护适配器列表；核心层遍历注册表完成发现与分派，不再硬编码分支。
+
+use std::fmt::Debug;
+
+use futures_util::future::BoxFuture;
+use yc_shared_protocol::ToolRuntimePayload;
+
+use super::{
+    CLAUDE_CODE_SCHEMA_V1, CODEX_SCHEMA_V1, CUSTOM_SCHEMA_V1, OPENCLAW_SCHEMA_V1,
+    OPENCODE_SCHEMA_V1, claude_code, codex,
+    custom::{self, CustomToolDefinition, custom_tool_definitions},
+    openclaw, opencode,
+};
+use crate::tooling::{
+    core::types::{ToolDetailCollectOptions, ToolDetailCollectResult, ToolDiscoveryContext},
+    tool_logs::ToolLogLocation,
+};
+
+/// 工具适配器：负责一类工具的进程发现与详情采集。
+pub(crate) trait ToolAdapter: Debug + Send + Sync {
+    /// 详情结构版本标识（如 `openclaw.v1`），也用于失败兜底与错误统计。
+    fn schema(&self) -> &'static str;
+
+    /// 判断工具是否归属于本适配器。
+    fn matches_tool(&self, tool: &ToolRuntimePayload) -> bool;
+
+    /// 从进程索引中发现本适配器管理的工具实例。
+    fn discover(&self, context: &ToolDiscoveryContext<'_>) -> Vec<ToolRuntimePayload>;
+
+    /// 采集详情；`deep` 为 true 表示单工具强制刷新，可补采高成本数据。
+    fn collect_details<'a>(
+        &'a self,
+        tools: &'a [ToolRuntimePayload],
+        options: &'a ToolDetailCollectOptions,
+        deep: bool,
+    ) -> BoxFuture<'a, Vec<ToolDetailCollectResult>>;
+
+    /// 工具运行日志位置；运行时不落日志文件的工具返回 None。
+    fn log_location(&self, _tool: &ToolRuntimePayload) -> Option<ToolLogLocation> {
+        None
+    }
+}
+
+/// 适配器注册表；归属判断按注册顺序取首个命中者。
+#[derive(Debug, Default)]
+pub(crate) struct ToolAdapterRegistry {
+    /// 已注册适配器。
+    adapters: Vec<Box<dyn ToolAdapter>>,
+}
+
+impl ToolAdapterRegistry {
+    /// 内置适配器注册表。OpenClaw 需先于 OpenCode 判断，避免名称子串误判；
+    /// 自定义工具排在最前，其归属只认 `custom_` 前缀，不会抢占内置工具。
+    pub(crate) fn builtin() -> Self {
+        let definitions = custom_tool_definitions();
+        let registry = if definitions.is_empty() {
+            Self::default()
+        } else {
+            Self::default().register(CustomToolAdapter {
+                definitions: definitions.to_vec(),
+            })
+        };
+        registry
+            .register(OpenClawAdapter)
+            .register(OpenCodeAdapter)
+            .register(CodexAdapter)
+            .register(ClaudeCodeAdapter)
+    }
+
+    /// 追加注册一个适配器。
+    pub(crate) fn register(mut self, adapter: impl ToolAdapter + 'static) -> Self {
+        self.adapters.push(Box::new(adapter));
+        self
+    }
+
+    /// 已注册适配器（按注册顺序）。
+    pub(crate) fn adapters(&self) -> &[Box<dyn ToolAdapter>] {
+        &self.adapters
+    }
+
+    /// 查找工具归属适配器的下标。
+    pub(crate) fn position(&self, tool: &ToolRuntimePayload) -> Option<usize> {
+        self.adapters
+            .iter()
+            .position(|adapter| adapter.matches_tool(tool))
+    }
+
+    /// 查找工具的日志位置；无适配器命中或适配器不支持时返回 None。
+    pub(crate) fn log_location(&self, tool: &ToolRuntimePayload) -> Option<ToolLogLocation> {
+        self.position(tool)
+            .and_then(|idx| self.adapters[idx].log_location(tool))
+    }
+
+    /// 推断工具的详情 schema；无适配器命中时返回 `unknown.v1`。
+    pub(crate) fn schema_for_tool(&self, tool: &ToolRuntimePayload) -> &'static str {
+        self.position(tool)
+            .map(|idx| self.adapters[idx].schema())
+            .unwrap_or(UNKNOWN_SCHEMA)
+    }
+}
+
+/// 无适配器命中时的 schema。
+pub(crate) const UNKNOWN_SCHEMA: &str = "unknown.v1";
+
+/// OpenClaw 适配器。
+#[derive(Debug)]
+struct OpenClawAdapter;
+
+impl ToolAdapter for OpenClawAdapter {
+    /// OpenClaw schema。
+    fn schema(&self) -> &'static str {
+        OPENCLAW_SCHEMA_V1
+    }
+
+    /// 委托 `openclaw::matches_tool`。
+    fn matches_tool(&self, tool: &ToolRuntimePayload) -> bool {
+        openclaw::matches_tool(tool)
+    }
+
+    /// 委托 `openclaw::discover`。
+    fn discover(&self, context: &ToolDiscoveryContext<'_>) -> Vec<ToolRuntimePayload> {
+        openclaw::discover(context)
+    }
+
+    /// 委托 `openclaw::collect_details`，`deep` 控制 memory/security 补采。
+    fn collect_details<'a>(
+        &'a self,
+        tools: &'a [ToolRuntimePayload],
+        options: &'a ToolDetailCollectOptions,
+        deep: bool,
+    ) -> BoxFuture<'a, Vec<ToolDetailCollectResult>> {
+        Box::pin(openclaw::collect_details(tools, options, deep))
+    }
+
+    /// 委托 `openclaw::log_location`。
+    fn log_location(&self, tool: &ToolRuntimePayload) -> Option<ToolLogLocation> {
+        openclaw::log_location(tool)
+    }
+}
+
+/// OpenCode 适配器。
+#[derive(Debug)]
+struct OpenCodeAdapter;
+
+impl ToolAdapter for OpenCodeAdapter {
+    /// OpenCode schema。
+    fn schema(&self) -> &'static str {
+        OPENCODE_SCHEMA_V1
+    }
+
+    /// 委托 `opencode::matches_tool`。
+    fn matches_tool(&self, tool: &ToolRuntimePayload) -> bool {
+        opencode::matches_tool(tool)
+    }
+
+    /// 委托 `opencode::discover`。
+    fn discover(&self, context: &ToolDiscoveryContext<'_>) -> Vec<ToolRuntimePayload> {
+        opencode::discover(context)
+    }
+
+    /// 委托 `opencode::collect_details`（读取本地会话库并探测工作区 git 状态）。
+    fn collect_details<'a>(
+        &'a self,
+        tools: &'a [ToolRuntimePayload],
+        options: &'a ToolDetailCollectOptions,
+        _deep: bool,
+    ) -> BoxFuture<'a, Vec<ToolDetailCollectResult>> {
+        Box::pin(opencode::collect_details(tools, options))
+    }
+
+    /// 委托 `opencode::log_location`。
+    fn log_location(&self, tool: &ToolRuntimePayload) -> Option<ToolLogLocation> {
+        opencode::log_location(tool)
+    }
+}
+
+/// Codex 适配器。
+#[derive(Debug)]
+struct CodexAdapter;
+
+impl ToolAdapter for CodexAdapter {
+    /// Codex schema。
+    fn schema(&self) -> &'static str {
+        CODEX_SCHEMA_V1
+    }
+
+    /// 委托 `codex::matches_tool`。
+    fn matches_tool(&self, tool: &ToolRuntimePayload) -> bool {
+        codex::matches_tool(tool)
+    }
+
+    /// 委托 `codex::discover`。
+    fn discover(&self, context: &ToolDiscoveryContext<'_>) -> Vec<ToolRuntimePayload> {
+        codex::discover(context)
+    }
+
+    /// 委托 `codex::collect_details`。
+    fn collect_details<'a>(
+        &'a self,
+        tools: &'a [ToolRuntimePayload],
+        options: &'a ToolDetailCollectOptions,
+        _deep: bool,
+    ) -> BoxFuture<'a, Vec<ToolDetailCollectResult>> {
+        Box::pin(async move { codex::collect_details(tools, options) })
+    }
+}
+
+/// Claude Code 适配器。
+#[derive(Debug)]
+struct ClaudeCodeAdapter;
+
+impl ToolAdapter for ClaudeCodeAdapter {
+    /// Claude Code schema。
+    fn schema(&self) -> &'static str {
+        CLAUDE_CODE_SCHEMA_V1
+    }
+
+    /// 委托 `claude_code::matches_tool`。
+    fn matches_tool(&self, tool: &ToolRuntimePayload) -> bool {
+        claude_code::matches_tool(tool)
+    }
+
+    /// 委托 `claude_code::discover`。
+    fn discover(&self, context: &ToolDiscoveryContext<'_>) -> Vec<ToolRuntimePayload> {
+        claude_code::discover(context)
+    }
+
+    /// 委托 `claude_code::collect_details`。
+    fn collect_details<'a>(
+        &'a self,
+        tools: &'a [ToolRuntimePayload],
+        options: &'a ToolDetailCollectOptions,
+        _deep: bool,
+    ) -> BoxFuture<'a, Vec<ToolDetailCollectResult>> {
+        Box::pin(async move { claude_code::collect_details(tools, options) })
+    }
+}
+
+/// 自定义工具适配器：持有 `tools.toml` 中的全部有效定义。
+#[derive(Debug)]
+struct CustomToolAdapter {
+    /// 已校验的定义。
+    definitions: Vec<CustomToolDefinition>,
+}
+
+impl ToolAdapter for CustomToolAdapter {
+    /// 自定义工具 schema。
+    fn schema(&self) -> &'static str {
+        CUSTOM_SCHEMA_V1
+    }
+
+    /// 委托 `custom::matches_tool`。
+    fn matches_tool(&self, tool: &ToolRuntimePayload) -> bool {
+        custom::matches_tool(tool)
+    }
+
+    /// 委托 `custom::discover`。
+    fn discover(&self, context: &ToolDiscoveryContext<'_>) -> Vec<ToolRuntimePayload> {
+        custom::discover(&self.definitions, context)
+    }
+
+    /// 委托 `custom::collect_details`。
+    fn collect_details<'a>(
+        &'a self,
+        tools: &'a [ToolRuntimePayload],
+        options: &'a ToolDetailCollectOptions,
+        _deep: bool,
+    ) -> BoxFuture<'a, Vec<ToolDetailCollectResult>> {
+        Box::pin(async move { custom::collect_details(&self.definitions, tools, options) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::{
+        ProcInfo,
+        tooling::{adapters::CODEX_SCHEMA_V1, core::types::ToolDiscoveryContext},
+    };
+
+    use super::ToolAdapterRegistry;
+
+    #[test]
+    fn codex_process_is_discovered_and_routed_to_codex_adapter() {
+        let mut all = HashMap::<i32, ProcInfo>::new();
+        all.insert(
+            42,
+            ProcInfo {
+                pid: 42,
+                cmd: "/usr/local/bin/codex --model gpt-5-codex".to_string(),
+                cwd: "/workspace/project".to_string(),
+                cpu_percent: 0.0,
+                memory_mb: 0.0,
+            },
+        );
+        let children_by_ppid = HashMap::new();
+        let context = ToolDiscoveryContext {
+            all: &all,
+            children_by_ppid: &children_by_ppid,
+        };
+        let registry = ToolAdapterRegistry::builtin();
+
+        let tools = registry
+            .adapters()
+            .iter()
+            .flat_map(|adapter| adapter.discover(&context))
+            .collect::<Vec<_>>();
+        assert_eq!(tools.len(), 1);
+        assert!(tools[0].tool_id.starts_with("codex_"));
+        assert_eq!(tools[0].model.as_deref(), Some("gpt-5-codex"));
+        assert_eq!(registry.schema_for_tool(&tools[0]), CODEX_SCHEMA_V1);
+        let position = registry.position(&tools[0]).expect("codex adapter");
+        assert_eq!(registry.adapters()[position].schema(), CODEX_SCHEMA_V1);
+    }
+}
@@ -109,9 +109,13 @@ fn contains_command_word(cmd_lower: &str, word: &str) -> bool {
         || cmd_lower.contains(&format!(" {word} "))
         || cmd_lower.ends_with(&format!("/{word}"))
         || cmd_lower.contains(&format!("/{word} "))
+        // Windows 下命令多为 `C:\...\opencode.exe` 形态，逐 token 去掉目录与扩展名后比较。
+        || cmd_lower
+            .split_whitespace()
+            .any(|token| token_matches_command(token, word))
 }
 
-/// 判断 token 是否是目标命令本体（支持 /path/codex 形态）。
+/// 判断 token 是否是目标命令本体（支持 /path/codex、C:\path\codex.exe 形态）。
 fn token_matches_command(token: &str, word: &str) -> bool {
     let trimmed = token.trim_matches(|ch| ch == '"' || ch == '\'');
     if trimmed == word {
@@ -119,7 +123,43 @@ fn token_matches_command(token: &str, word: &str) -> bool {
     }
     let unix_name = trimmed.rsplit('/').next().unwrap_or(trimmed);
     let windows_name = unix_name.rsplit('\\').next().unwrap_or(unix_name);
-    windows_name == word
+    let bare_name = [".exe", ".cmd"]
+        .iter()
+        .find_map(|ext| windows_name.strip_suffix(ext))
+        .unwrap_or(windows_name);
+    bare_name == word
+}
+
+/// 显式指定工作目录的常见 CLI 参数。
+const CWD_FLAGS: [&str; 5] = ["--cwd", "--cd", "-C", "--dir", "--workspace"];
+
+/// 进程 cwd 不可读时（如 Windows 下跨权限进程）从命令行推断工作目录：
+/// 优先显式目录参数，其次首个位于命令本体之后、`is_dir` 判定为目录的绝对路径参数。
+pub(crate) fn infer_cwd_from_cmd(cmd: &str, is_dir: impl Fn(&str) -> bool) -> Option<String> {
+    let unquote = |value: &str| value.trim_matches(|ch| ch == '"' || ch == '\'').to_string();
+    if let Some(dir) = CWD_FLAGS
+        .iter()
+        .find_map(|flag| parse_cli_flag_value(cmd, flag))
+        .map(|value| unquote(&value))
+        .filter(|value| is_absolute_path(value))
+    {
+        return Some(dir);
+    }
+    cmd.split_whitespace()
+        .skip(1)
+        .map(unquote)
+        .find(|token| !token.starts_with('-') && is_absolute_path(token) && is_dir(token))
+}
+
+/// 判断是否为 Unix 绝对路径或 Windows 盘符/UNC 路径（与当前平台无关）。
+fn is_absolute_path(value: &str) -> bool {
+    let bytes = value.as_bytes();
+    value.starts_with('/')
+        || value.starts_with("\\\\")
+        || (bytes.len() >= 3
+            && bytes[0].is_ascii_alphabetic()
+            && bytes[1] == b':'
+            && matches!(bytes[2], b'\\' | b'/'))
 }
 
 /// 判断是否是可接入的 opencode 运行命令。
@@ -322,8 +362,8 @@ pub(crate) fn detect_openclaw_mode(cmd: &str) -> &'static str {
 #[cfg(test)]
 mod tests {
     use super::{
-        evaluate_openclaw_connection, is_claude_code_candidate_command, is_codex_candidate_command,
-        is_openclaw_candidate_command,
+        evaluate_openclaw_connection, infer_cwd_from_cmd, is_claude_code_candidate_command,
+        is_codex_candidate_command, is_openclaw_candidate_command, is_opencode_candidate_command,
     };
 
     #[test]
@@ -387,4 +427,39 @@ mod tests {
             "/applications/claude.app/contents/macos/claude"
         ));
     }
+
+    #[test]
+    fn candidates_accept_windows_executable_paths() {
+        assert!(is_opencode_candidate_command(
+            r"c:\users\a\appdata\local\opencode\opencode.exe serve --port 4096"
+        ));
+        assert!(is_openclaw_candidate_command(
+            r"c:\users\a\appdata\roaming\npm\openclaw.cmd gateway"
+        ));
+        assert!(!is_opencode_candidate_command(r"c:\tools\opencoder.exe"));
+    }
+
+    #[test]
+    fn infers_cwd_from_flags_then_directory_arguments() {
+        let no_dirs = |_: &str| false;
+        assert_eq!(
+            infer_cwd_from_cmd(r#"codex --cd "D:\work\demo" run"#, no_dirs),
+            Some(r"D:\work\demo".to_string())
+        );
+        assert_eq!(
+            infer_cwd_from_cmd("openclaw --workspace=/srv/agent", no_dirs),
+            Some("/srv/agent".to_string())
+        );
+        assert_eq!(infer_cwd_from_cmd("opencode --dir relative", no_dirs), None);
+
+        let is_project = |path: &str| path == r"C:\proj";
+        assert_eq!(
+            infer_cwd_from_cmd(r"C:\bin\opencode.exe C:\proj\readme.md C:\proj", is_project),
+            Some(r"C:\proj".to_string())
+        );
+        assert_eq!(
+            infer_cwd_from_cmd(r"C:\proj --model gpt-5", is_project),
+            None
+        );
+    }
 }
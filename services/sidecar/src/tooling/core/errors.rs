@@ -0,0 +1,127 @@
+//! 采集错误汇总：按适配器统计窗口内的失败次数与最近错误，供会话循环周期下发 `collection_errors`。
+
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use yc_shared_protocol::{AdapterErrorSummaryPayload, CollectionErrorsPayload, now_rfc3339_nanos};
+
+/// 默认统计窗口（秒）。
+pub(crate) const DEFAULT_COLLECTION_ERRORS_WINDOW_SEC: u64 = 60;
+
+/// 会话循环与详情 worker 共享的错误汇总句柄。
+pub(crate) type SharedCollectionErrors = Arc<Mutex<CollectionErrorTracker>>;
+
+/// 单适配器窗口内的失败统计。
+#[derive(Debug, Clone)]
+struct AdapterErrors {
+    /// 窗口内失败次数。
+    count: u32,
+    /// 最近一次失败原因。
+    last_error: String,
+    /// 最近一次失败时间（RFC3339）。
+    last_error_at: String,
+}
+
+/// 采集错误窗口统计器。
+#[derive(Debug)]
+pub(crate) struct CollectionErrorTracker {
+    /// 统计窗口长度。
+    window: Duration,
+    /// 当前窗口内按适配器聚合的失败。
+    by_adapter: BTreeMap<String, AdapterErrors>,
+    /// 上一次下发的摘要是否非空（用于下发一次“已恢复”的空摘要）。
+    last_reported_non_empty: bool,
+}
+
+impl CollectionErrorTracker {
+    /// 创建统计器。
+    pub(crate) fn new(window: Duration) -> Self {
+        Self {
+            window: window.max(Duration::from_secs(1)),
+            by_adapter: BTreeMap::new(),
+            last_reported_non_empty: false,
+        }
+    }
+
+    /// 创建共享句柄。
+    pub(crate) fn shared(window: Duration) -> SharedCollectionErrors {
+        Arc::new(Mutex::new(Self::new(window)))
+    }
+
+    /// 记录一次采集失败。
+    pub(crate) fn record(&mut self, adapter: &str, error: &str) {
+        let entry = self
+            .by_adapter
+            .entry(adapter.to_string())
+            .or_insert_with(|| AdapterErrors {
+                count: 0,
+                last_error: String::new(),
+                last_error_at: String::new(),
+            });
+        entry.count = entry.count.saturating_add(1);
+        entry.last_error = error.to_string();
+        entry.last_error_at = now_rfc3339_nanos();
+    }
+
+    /// 结束当前窗口：返回摘要并清零计数；连续两个窗口都无错误时返回 None。
+    pub(crate) fn take_summary(&mut self) -> Option<CollectionErrorsPayload> {
+        let adapters = std::mem::take(&mut self.by_adapter)
+            .into_iter()
+            .map(|(adapter, errors)| AdapterErrorSummaryPayload {
+                adapter,
+                count: errors.count,
+                last_error: errors.last_error,
+                last_error_at: Some(errors.last_error_at),
+            })
+            .collect::<Vec<_>>();
+        let non_empty = !adapters.is_empty();
+        let should_report = non_empty || self.last_reported_non_empty;
+        self.last_reported_non_empty = non_empty;
+        should_report.then_some(CollectionErrorsPayload {
+            window_sec: self.window.as_secs(),
+            adapters,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::CollectionErrorTracker;
+
+    #[test]
+    fn failures_accumulate_per_adapter_within_window() {
+        let mut tracker = CollectionErrorTracker::new(Duration::from_secs(60));
+        tracker.record("openclaw", "status timeout");
+        tracker.record("openclaw", "status timeout again");
+        tracker.record("opencode", "parse failed");
+
+        let summary = tracker.take_summary().expect("summary should be emitted");
+        assert_eq!(summary.window_sec, 60);
+        assert_eq!(summary.adapters.len(), 2);
+        assert_eq!(summary.adapters[0].adapter, "openclaw");
+        assert_eq!(summary.adapters[0].count, 2);
+        assert_eq!(summary.adapters[0].last_error, "status timeout again");
+        assert_eq!(summary.adapters[1].adapter, "opencode");
+        assert_eq!(summary.adapters[1].count, 1);
+    }
+
+    #[test]
+    fn counts_reset_after_window_and_recovery_is_reported_once() {
+        let mut tracker = CollectionErrorTracker::new(Duration::from_secs(60));
+        tracker.record("openclaw", "status timeout");
+        assert_eq!(tracker.take_summary().unwrap().adapters[0].count, 1);
+
+        // 下一个窗口无错误：下发一次空摘要表示已恢复，之后不再重复下发。
+        let recovered = tracker.take_summary().expect("recovery summary");
+        assert!(recovered.adapters.is_empty());
+        assert!(tracker.take_summary().is_none());
+
+        tracker.record("openclaw", "status timeout");
+        assert_eq!(tracker.take_summary().unwrap().adapters[0].count, 1);
+    }
+}
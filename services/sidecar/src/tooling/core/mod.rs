@@ -1,37 +1,38 @@
 //! Tool Adapter Core 模块职责：
-//! 1. 统一调度 OpenCode/OpenClaw 的发现与详情采集。
+//! 1. 遍历适配器注册表，统一调度各工具的发现与详情采集。
 //! 2. 维护工具详情缓存、过期标记与按需刷新去抖策略。
 //! 3. 对会话循环提供稳定的发现与详情快照接口。
 
 pub(crate) mod cache;
+pub(crate) mod errors;
+pub(crate) mod process_index;
 pub(crate) mod scheduler;
 pub(crate) mod types;
 
 use std::{
     collections::HashMap,
+    path::Path,
     time::{Duration, Instant},
 };
 
 use chrono::{Duration as ChronoDuration, Utc};
-use sysinfo::{ProcessRefreshKind, ProcessesToUpdate, System, UpdateKind};
+use sysinfo::{Pid, Process, ProcessRefreshKind, ProcessesToUpdate, System, UpdateKind};
+use tracing::{debug, warn};
 use yc_shared_protocol::{ToolDetailEnvelopePayload, ToolRuntimePayload, now_rfc3339_nanos};
 
 use self::{
     cache::ToolDetailsCache,
+    errors::{CollectionErrorTracker, SharedCollectionErrors},
+    process_index::{MAX_RELEVANT_PROCESSES, ProcessIndex, is_discovery_relevant},
     scheduler::{default_detail_ttl, filter_tools_by_target},
-    types::{
-        ToolDetailCollectOptions, ToolDetailCollectResult, ToolDetailsCollectRequest,
-        ToolDiscoveryContext,
-    },
+    types::{ToolDetailCollectOptions, ToolDetailCollectResult, ToolDetailsCollectRequest},
 };
 use crate::{
     ProcInfo, fallback_tools_or_empty,
     tooling::{
-        adapters::{
-            CLAUDE_CODE_SCHEMA_V1, CODEX_SCHEMA_V1, OPENCLAW_SCHEMA_V1, OPENCODE_SCHEMA_V1,
-            claude_code, codex, openclaw, opencode,
-        },
+        adapters::registry::{ToolAdapterRegistry, UNKNOWN_SCHEMA},
         bytes_to_mb,
+        cli_parse::infer_cwd_from_cmd,
     },
 };
 
@@ -46,6 +47,12 @@ pub(crate) struct ToolAdapterCore {
     detail_options: ToolDetailCollectOptions,
     /// 按需刷新去抖窗口。
     detail_debounce: Duration,
+    /// 采集错误汇总（可选，由会话循环共享）。
+    collection_errors: Option<SharedCollectionErrors>,
+    /// 发现阶段进程索引（跨周期复用缓冲）。
+    process_index: ProcessIndex,
+    /// 工具适配器注册表。
+    registry: ToolAdapterRegistry,
 }
 
 impl ToolAdapterCore {
@@ -66,22 +73,27 @@ impl ToolAdapterCore {
                 max_parallel: detail_max_parallel.max(1),
             },
             detail_debounce,
+            collection_errors: None,
+            process_index: ProcessIndex::default(),
+            registry: ToolAdapterRegistry::builtin(),
         }
     }
 
+    /// 挂接采集错误汇总句柄，采集失败会按适配器计入窗口统计。
+    pub(crate) fn with_collection_errors(mut self, errors: SharedCollectionErrors) -> Self {
+        self.collection_errors = Some(errors);
+        self
+    }
+
     /// 扫描系统进程并发现工具实例。
-    pub(crate) fn discover_tools(&self, sys: &mut System) -> Vec<ToolRuntimePayload> {
-        let (all, children_by_ppid) = collect_process_snapshot(sys);
-        let context = ToolDiscoveryContext {
-            all: &all,
-            children_by_ppid: &children_by_ppid,
-        };
+    pub(crate) fn discover_tools(&mut self, sys: &mut System) -> Vec<ToolRuntimePayload> {
+        collect_process_snapshot(sys, &mut self.process_index);
+        let context = self.process_index.context();
 
         let mut tools = Vec::new();
-        tools.extend(opencode::discover(&context));
-        tools.extend(openclaw::discover(&context));
-        tools.extend(codex::discover(&context));
-        tools.extend(claude_code::discover(&context));
+        for adapter in self.registry.adapters() {
+            tools.extend(adapter.discover(&context));
+        }
 
         if tools.is_empty() {
             return fallback_tools_or_empty(self.fallback_tool);
@@ -133,43 +145,42 @@ impl ToolAdapterCore {
             return self.details_cache.snapshot_for_tool_order(&ordered_ids);
         }
 
-        let (opencode_tools, openclaw_tools, codex_tools, claude_tools, unknown_tools) =
-            partition_tools_by_adapter(&collect_targets);
+        let (grouped, unknown_tools) = partition_tools_by_adapter(&self.registry, &collect_targets);
 
         let mut results = Vec::new();
-        results.extend(opencode::collect_details(
-            &opencode_tools,
-            &self.detail_options,
-        ));
-        let include_openclaw_deep_details = request.force && request.target_tool_id.is_some();
-        results.extend(
-            openclaw::collect_details(
-                &openclaw_tools,
-                &self.detail_options,
-                include_openclaw_deep_details,
-            )
-            .await,
-        );
-        results.extend(codex::collect_details(&codex_tools, &self.detail_options));
-        results.extend(claude_code::collect_details(
-            &claude_tools,
-            &self.detail_options,
-        ));
+        let deep = request.force && request.target_tool_id.is_some();
+        for (adapter, tools) in self.registry.adapters().iter().zip(&grouped) {
+            if tools.is_empty() {
+                continue;
+            }
+            results.extend(
+                adapter
+                    .collect_details(tools, &self.detail_options, deep)
+                    .await,
+            );
+        }
 
         for tool in unknown_tools {
             results.push(ToolDetailCollectResult::failed(
                 tool.tool_id,
-                "unknown.v1",
+                UNKNOWN_SCHEMA,
                 None,
                 "当前工具类型未实现详情采集",
             ));
         }
 
+        let mut errors = self.collection_errors.as_ref().map(|errors| {
+            errors
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner())
+        });
         apply_collect_results(
+            &self.registry,
             &mut self.details_cache,
             &collect_targets,
             results,
             &self.detail_options,
+            errors.as_deref_mut(),
         );
         self.details_cache.snapshot_for_tool_order(&ordered_ids)
     }
@@ -188,57 +199,30 @@ impl ToolAdapterCore {
     }
 }
 
-/// 按适配器类型拆分工具集合。
+/// 按注册表顺序拆分工具集合：返回与适配器一一对应的分组，以及无适配器命中的工具。
 fn partition_tools_by_adapter(
+    registry: &ToolAdapterRegistry,
     tools: &[ToolRuntimePayload],
-) -> (
-    Vec<ToolRuntimePayload>,
-    Vec<ToolRuntimePayload>,
-    Vec<ToolRuntimePayload>,
-    Vec<ToolRuntimePayload>,
-    Vec<ToolRuntimePayload>,
-) {
-    let mut opencode_tools = Vec::new();
-    let mut openclaw_tools = Vec::new();
-    let mut codex_tools = Vec::new();
-    let mut claude_tools = Vec::new();
+) -> (Vec<Vec<ToolRuntimePayload>>, Vec<ToolRuntimePayload>) {
+    let mut grouped = vec![Vec::new(); registry.adapters().len()];
     let mut unknown_tools = Vec::new();
-
     for tool in tools {
-        if openclaw::matches_tool(tool) {
-            openclaw_tools.push(tool.clone());
-            continue;
-        }
-        if opencode::matches_tool(tool) {
-            opencode_tools.push(tool.clone());
-            continue;
-        }
-        if codex::matches_tool(tool) {
-            codex_tools.push(tool.clone());
-            continue;
-        }
-        if claude_code::matches_tool(tool) {
-            claude_tools.push(tool.clone());
-            continue;
+        match registry.position(tool) {
+            Some(idx) => grouped[idx].push(tool.clone()),
+            None => unknown_tools.push(tool.clone()),
         }
-        unknown_tools.push(tool.clone());
     }
-
-    (
-        opencode_tools,
-        openclaw_tools,
-        codex_tools,
-        claude_tools,
-        unknown_tools,
-    )
+    (grouped, unknown_tools)
 }
 
 /// 把采集结果合并到缓存：成功写新值，失败标记 stale 并保留旧 data。
 fn apply_collect_results(
+    registry: &ToolAdapterRegistry,
     cache: &mut ToolDetailsCache,
     targets: &[ToolRuntimePayload],
     results: Vec<ToolDetailCollectResult>,
     options: &ToolDetailCollectOptions,
+    mut errors: Option<&mut CollectionErrorTracker>,
 ) {
     let mut result_by_tool: HashMap<String, ToolDetailCollectResult> = HashMap::new();
     for result in results {
@@ -252,13 +236,17 @@ fn apply_collect_results(
     let expires_at = expires.to_rfc3339_opts(chrono::SecondsFormat::Secs, true);
 
     for tool in targets {
-        let default_schema = schema_for_tool(tool);
+        let default_schema = registry.schema_for_tool(tool);
         let Some(result) = result_by_tool.remove(&tool.tool_id) else {
+            let error = "详情采集失败：未返回结果";
+            if let Some(errors) = errors.as_deref_mut() {
+                errors.record(adapter_name(default_schema), error);
+            }
             cache.mark_stale(
                 &tool.tool_id,
                 default_schema,
                 None,
-                "详情采集失败：未返回结果",
+                error,
                 Some(expires_at.clone()),
             );
             continue;
@@ -281,6 +269,10 @@ fn apply_collect_results(
             continue;
         }
 
+        let error = result.error.as_deref().unwrap_or("详情采集失败：未知错误");
+        if let Some(errors) = errors.as_deref_mut() {
+            errors.record(adapter_name(default_schema), error);
+        }
         cache.mark_stale(
             &tool.tool_id,
             if result.schema.trim().is_empty() {
@@ -289,87 +281,102 @@ fn apply_collect_results(
                 result.schema.as_str()
             },
             result.profile_key,
-            result.error.as_deref().unwrap_or("详情采集失败：未知错误"),
+            error,
             Some(expires_at.clone()),
         );
     }
 }
 
-/// 根据工具标识推断 schema，供失败兜底分支使用。
-fn schema_for_tool(tool: &ToolRuntimePayload) -> &'static str {
-    if openclaw::matches_tool(tool) {
-        return OPENCLAW_SCHEMA_V1;
-    }
-    if opencode::matches_tool(tool) {
-        return OPENCODE_SCHEMA_V1;
-    }
-    if codex::matches_tool(tool) {
-        return CODEX_SCHEMA_V1;
-    }
-    if claude_code::matches_tool(tool) {
-        return CLAUDE_CODE_SCHEMA_V1;
-    }
-    "unknown.v1"
+/// 由 schema 推导适配器名称（如 `openclaw.v1` -> `openclaw`）。
+fn adapter_name(schema: &str) -> &str {
+    schema.split('.').next().unwrap_or(schema)
 }
 
-/// 从 sysinfo 采集进程快照并构建父子关系索引。
-fn collect_process_snapshot(sys: &mut System) -> (HashMap<i32, ProcInfo>, HashMap<i32, Vec<i32>>) {
+/// 从 sysinfo 采集进程快照，仅为相关进程构建进程映射与父子关系索引。
+fn collect_process_snapshot(sys: &mut System, index: &mut ProcessIndex) {
+    let started_at = Instant::now();
     sys.refresh_processes_specifics(
         ProcessesToUpdate::All,
         true,
         discovery_process_refresh_kind(),
     );
 
-    let mut all: HashMap<i32, ProcInfo> = HashMap::new();
-    let mut children_by_ppid: HashMap<i32, Vec<i32>> = HashMap::new();
-
+    index.begin();
     for process in sys.processes().values() {
         if process.thread_kind().is_some() {
             // Linux 下 task(TID) 也可能出现在 process 列表里，过滤掉线程避免候选工具重复。
             continue;
         }
+        let cmd = process_cmd(process);
+        if cmd.trim().is_empty() {
+            continue;
+        }
         let pid = process.pid().as_u32() as i32;
         let ppid = process
             .parent()
             .map(|parent| parent.as_u32() as i32)
             .unwrap_or(0);
-        let cmd = {
-            let joined = process
-                .cmd()
-                .iter()
-                .map(|item| item.to_string_lossy().to_string())
-                .collect::<Vec<String>>()
-                .join(" ");
-            if joined.trim().is_empty() {
-                // macOS 某些 launchd 托管进程可能拿不到完整 cmdline，
-                // 回退到进程名，避免工具发现“偶发空白”。
-                process.name().to_string_lossy().to_string()
-            } else {
-                joined
-            }
-        };
-        if cmd.trim().is_empty() {
+        let relevant_info =
+            is_discovery_relevant(&cmd.to_lowercase()).then(|| build_proc_info(process, cmd));
+        index.record(pid, ppid, relevant_info);
+    }
+    for pid in index.select() {
+        let Some(process) = sys.process(Pid::from_u32(pid as u32)) else {
             continue;
-        }
-        let cwd = process
-            .cwd()
-            .map(|dir| dir.display().to_string())
-            .unwrap_or_default();
-
-        all.insert(
-            pid,
-            ProcInfo {
-                pid,
-                cmd,
-                cwd,
-                cpu_percent: process.cpu_usage() as f64,
-                memory_mb: bytes_to_mb(process.memory()),
-            },
+        };
+        let cmd = process_cmd(process);
+        index.insert(build_proc_info(process, cmd));
+    }
+    let stats = index.finish();
+
+    if stats.truncated {
+        warn!(
+            "process index truncated: relevant={} cap={MAX_RELEVANT_PROCESSES}",
+            stats.relevant
         );
-        children_by_ppid.entry(ppid).or_default().push(pid);
     }
+    debug!(
+        "process index built: scanned={} relevant={} indexed={} elapsed_ms={}",
+        stats.scanned,
+        stats.relevant,
+        stats.indexed,
+        started_at.elapsed().as_millis()
+    );
+}
+
+/// 读取进程命令行；拿不到完整 cmdline 时回退到进程名。
+fn process_cmd(process: &Process) -> String {
+    let joined = process
+        .cmd()
+        .iter()
+        .map(|item| item.to_string_lossy().to_string())
+        .collect::<Vec<String>>()
+        .join(" ");
+    if joined.trim().is_empty() {
+        // macOS 某些 launchd 托管进程可能拿不到完整 cmdline，
+        // 回退到进程名，避免工具发现“偶发空白”。
+        process.name().to_string_lossy().to_string()
+    } else {
+        joined
+    }
+}
 
-    (all, children_by_ppid)
+/// 由 sysinfo 进程构造发现阶段进程信息。
+fn build_proc_info(process: &Process, cmd: String) -> ProcInfo {
+    let cwd = process
+        .cwd()
+        .map(|dir| dir.display().to_string())
+        .filter(|dir| !dir.trim().is_empty())
+        // Windows 下读取其他会话/提权进程的 cwd 常失败，回退命令行推断。
+        .or_else(|| infer_cwd_from_cmd(&cmd, |path| Path::new(path).is_dir()))
+        .unwrap_or_default();
+    ProcInfo {
+        pid: process.pid().as_u32() as i32,
+        cmd,
+        cwd,
+        cpu_percent: process.cpu_usage() as f64,
+        memory_mb: bytes_to_mb(process.memory()),
+    }
 }
 
 /// 发现阶段的进程刷新配置：保留与默认 `refresh_processes` 相同的字段，
@@ -388,9 +395,77 @@ fn discovery_process_refresh_kind() -> ProcessRefreshKind {
 
 #[cfg(test)]
 mod tests {
+    use std::{
+        sync::{
+            Arc,
+            atomic::{AtomicUsize, Ordering},
+        },
+        time::Duration,
+    };
+
+    use futures_util::future::BoxFuture;
+    use serde_json::json;
     use sysinfo::UpdateKind;
+    use yc_shared_protocol::ToolRuntimePayload;
+
+    use super::{
+        ToolAdapterCore, apply_collect_results,
+        cache::ToolDetailsCache,
+        discovery_process_refresh_kind,
+        errors::CollectionErrorTracker,
+        types::{
+            ToolDetailCollectOptions, ToolDetailCollectResult, ToolDetailsCollectRequest,
+            ToolDiscoveryContext,
+        },
+    };
+    use crate::tooling::adapters::registry::{ToolAdapter, ToolAdapterRegistry};
+
+    /// 测试用适配器：认领 `fake_` 前缀工具并记录被分派的工具数。
+    #[derive(Debug, Default)]
+    struct FakeAdapter {
+        /// 累计收到的采集工具数。
+        collected: Arc<AtomicUsize>,
+    }
+
+    impl ToolAdapter for FakeAdapter {
+        /// 测试 schema。
+        fn schema(&self) -> &'static str {
+            "fake.v1"
+        }
 
-    use super::{ToolAdapterCore, discovery_process_refresh_kind};
+        /// 认领 `fake_` 前缀工具。
+        fn matches_tool(&self, tool: &ToolRuntimePayload) -> bool {
+            tool.tool_id.starts_with("fake_")
+        }
+
+        /// 不参与发现。
+        fn discover(&self, _context: &ToolDiscoveryContext<'_>) -> Vec<ToolRuntimePayload> {
+            Vec::new()
+        }
+
+        /// 为每个工具返回固定详情。
+        fn collect_details<'a>(
+            &'a self,
+            tools: &'a [ToolRuntimePayload],
+            _options: &'a ToolDetailCollectOptions,
+            _deep: bool,
+        ) -> BoxFuture<'a, Vec<ToolDetailCollectResult>> {
+            self.collected.fetch_add(tools.len(), Ordering::SeqCst);
+            Box::pin(async move {
+                tools
+                    .iter()
+                    .map(|tool| {
+                        ToolDetailCollectResult::success(
+                            tool.tool_id.clone(),
+                            "",
+                            None,
+                            json!({"fake": true}),
+                        )
+                    })
+                    .collect()
+            })
+        }
+    }
 
     #[test]
     fn core_keeps_parallelism_positive() {
@@ -404,6 +479,45 @@ mod tests {
         assert!(core.detail_options.max_parallel >= 1);
     }
 
+    #[test]
+    fn failed_collect_results_accumulate_into_error_summary() {
+        let tool = ToolRuntimePayload {
+            tool_id: "openclaw_abcd_gw".to_string(),
+            name: "OpenClaw".to_string(),
+            ..ToolRuntimePayload::default()
+        };
+        let options = ToolDetailCollectOptions {
+            detail_ttl: Duration::from_secs(30),
+            command_timeout: Duration::from_secs(2),
+            max_parallel: 1,
+        };
+        let mut cache = ToolDetailsCache::new();
+        let mut tracker = CollectionErrorTracker::new(Duration::from_secs(60));
+        for _ in 0..3 {
+            let failed = ToolDetailCollectResult::failed(
+                tool.tool_id.clone(),
+                "openclaw.v1",
+                None,
+                "status timed out",
+            );
+            apply_collect_results(
+                &ToolAdapterRegistry::builtin(),
+                &mut cache,
+                std::slice::from_ref(&tool),
+                vec![failed],
+                &options,
+                Some(&mut tracker),
+            );
+        }
+
+        let summary = tracker.take_summary().expect("summary should be emitted");
+        assert_eq!(summary.adapters.len(), 1);
+        assert_eq!(summary.adapters[0].adapter, "openclaw");
+        assert_eq!(summary.adapters[0].count, 3);
+        assert_eq!(summary.adapters[0].last_error, "status timed out");
+        assert!(tracker.take_summary().unwrap().adapters.is_empty());
+    }
+
     #[test]
     fn discovery_refresh_kind_enables_cmd_cwd_and_disables_tasks() {
         let kind = discovery_process_refresh_kind();
@@ -411,4 +525,41 @@ mod tests {
         assert_eq!(kind.cwd(), UpdateKind::Always);
         assert!(!kind.tasks());
     }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn registered_adapter_receives_matching_tools() {
+        let collected = Arc::new(AtomicUsize::new(0));
+        let mut core = ToolAdapterCore::new(
+            false,
+            Duration::from_secs(30),
+            Duration::from_secs(2),
+            1,
+            Duration::from_secs(3),
+        );
+        core.registry = ToolAdapterRegistry::default().register(FakeAdapter {
+            collected: collected.clone(),
+        });
+        let tool = |tool_id: &str| ToolRuntimePayload {
+            tool_id: tool_id.to_string(),
+            ..ToolRuntimePayload::default()
+        };
+
+        let details = core
+            .collect_details_snapshot(ToolDetailsCollectRequest {
+                tools: vec![tool("fake_a"), tool("other_b")],
+                target_tool_id: None,
+                force: true,
+            })
+            .await;
+
+        assert_eq!(collected.load(Ordering::SeqCst), 1);
+        assert_eq!(details.len(), 2);
+        assert_eq!(details[0].tool_id, "fake_a");
+        assert_eq!(details[0].schema, "fake.v1");
+        assert!(!details[0].stale);
+        assert_eq!(details[0].data, json!({"fake": true}));
+        assert_eq!(details[1].tool_id, "other_b");
+        assert_eq!(details[1].schema, "unknown.v1");
+        assert!(details[1].stale);
+    }
 }
@@ -0,0 +1,229 @@
+//! 发现阶段进程索引职责：
+//! 1. 跨发现周期复用进程映射与父子索引缓冲，避免进程数量巨大的主机上每轮重复分配。
+//! 2. 仅索引与已注册适配器相关的进程及其祖先/直接子进程（供 wrapper、gateway 覆盖与元数据回溯使用）。
+//! 3. 限制单轮相关进程数量，并输出扫描规模统计。
+
+use std::collections::{HashMap, HashSet};
+
+use super::types::ToolDiscoveryContext;
+use crate::{ProcInfo, tooling::adapters::custom::is_custom_candidate_command};
+
+/// 单轮最多索引的相关进程数，超出部分按 PID 升序截断。
+pub(crate) const MAX_RELEVANT_PROCESSES: usize = 1024;
+/// 相关进程向上回溯的祖先层数（与 codex/claude 元数据回溯深度一致）。
+pub(crate) const MAX_ANCESTOR_DEPTH: usize = 4;
+
+/// 判断进程命令是否与任一已注册适配器（含自定义工具定义）相关。
+pub(crate) fn is_discovery_relevant(cmd_lower: &str) -> bool {
+    crate::is_opencode_candidate_command(cmd_lower)
+        || crate::is_openclaw_candidate_command(cmd_lower)
+        || crate::is_codex_candidate_command(cmd_lower)
+        || crate::is_claude_code_candidate_command(cmd_lower)
+        || is_custom_candidate_command(cmd_lower)
+}
+
+/// 单轮索引构建统计。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct ProcessIndexStats {
+    /// 扫描到的进程数（不含线程与空命令进程）。
+    pub(crate) scanned: usize,
+    /// 命中适配器的相关进程数（截断前）。
+    pub(crate) relevant: usize,
+    /// 最终进入索引的进程数。
+    pub(crate) indexed: usize,
+    /// 相关进程是否因超过上限被截断。
+    pub(crate) truncated: bool,
+}
+
+/// 可复用的发现阶段进程索引。
+#[derive(Debug, Default)]
+pub(crate) struct ProcessIndex {
+    /// 已索引进程映射（pid -> 进程信息）。
+    all: HashMap<i32, ProcInfo>,
+    /// 已索引进程的父子关系（ppid -> children pid 列表）。
+    children_by_ppid: HashMap<i32, Vec<i32>>,
+    /// 本轮全部进程的父进程映射（仅整数，用于回溯祖先与查找子进程）。
+    parent_by_pid: HashMap<i32, i32>,
+    /// 本轮命中适配器的进程 PID。
+    relevant: Vec<i32>,
+    /// 本轮需要索引的进程 PID（相关进程 + 祖先 + 直接子进程）。
+    selected: HashSet<i32>,
+    /// 本轮统计。
+    stats: ProcessIndexStats,
+}
+
+impl ProcessIndex {
+    /// 开始新一轮采集：清空内容但保留已分配容量。
+    pub(crate) fn begin(&mut self) {
+        self.all.clear();
+        self.children_by_ppid.clear();
+        self.parent_by_pid.clear();
+        self.relevant.clear();
+        self.selected.clear();
+        self.stats = ProcessIndexStats::default();
+    }
+
+    /// 记录一个扫描到的进程；`relevant_info` 非空表示该进程命中适配器。
+    pub(crate) fn record(&mut self, pid: i32, ppid: i32, relevant_info: Option<ProcInfo>) {
+        self.stats.scanned += 1;
+        self.parent_by_pid.insert(pid, ppid);
+        if let Some(info) = relevant_info {
+            self.relevant.push(pid);
+            self.all.insert(pid, info);
+        }
+    }
+
+    /// 计算本轮需要索引的进程集合，返回仍缺少进程信息的 PID（祖先或子进程）。
+    pub(crate) fn select(&mut self) -> Vec<i32> {
+        self.relevant.sort_unstable();
+        self.relevant.dedup();
+        self.stats.relevant = self.relevant.len();
+        if self.relevant.len() > MAX_RELEVANT_PROCESSES {
+            for pid in self.relevant.drain(MAX_RELEVANT_PROCESSES..) {
+                self.all.remove(&pid);
+            }
+            self.stats.truncated = true;
+        }
+
+        for pid in &self.relevant {
+            self.selected.insert(*pid);
+            let mut current = *pid;
+            for _ in 0..MAX_ANCESTOR_DEPTH {
+                let Some(parent) = self.parent_by_pid.get(&current).copied() else {
+                    break;
+                };
+                if !self.parent_by_pid.contains_key(&parent) || !self.selected.insert(parent) {
+                    break;
+                }
+                current = parent;
+            }
+        }
+        for (child, parent) in &self.parent_by_pid {
+            if self.relevant.binary_search(parent).is_ok() {
+                self.selected.insert(*child);
+            }
+        }
+
+        let mut missing = self
+            .selected
+            .iter()
+            .copied()
+            .filter(|pid| !self.all.contains_key(pid))
+            .collect::<Vec<i32>>();
+        missing.sort_unstable();
+        missing
+    }
+
+    /// 补充祖先/子进程的进程信息。
+    pub(crate) fn insert(&mut self, info: ProcInfo) {
+        if self.selected.contains(&info.pid) {
+            self.all.insert(info.pid, info);
+        }
+    }
+
+    /// 按已索引进程构建父子关系并返回统计。
+    pub(crate) fn finish(&mut self) -> ProcessIndexStats {
+        let mut pids = self.all.keys().copied().collect::<Vec<i32>>();
+        pids.sort_unstable();
+        for pid in pids {
+            let ppid = self.parent_by_pid.get(&pid).copied().unwrap_or(0);
+            self.children_by_ppid.entry(ppid).or_default().push(pid);
+        }
+        self.stats.indexed = self.all.len();
+        self.stats
+    }
+
+    /// 以当前索引构造发现上下文。
+    pub(crate) fn context(&self) -> ToolDiscoveryContext<'_> {
+        ToolDiscoveryContext {
+            all: &self.all,
+            children_by_ppid: &self.children_by_ppid,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::{MAX_RELEVANT_PROCESSES, ProcessIndex, is_discovery_relevant};
+    use crate::ProcInfo;
+
+    fn info(pid: i32, cmd: &str) -> ProcInfo {
+        ProcInfo {
+            pid,
+            cmd: cmd.to_string(),
+            cwd: "/tmp".to_string(),
+            cpu_percent: 0.0,
+            memory_mb: 0.0,
+        }
+    }
+
+    /// 模拟一轮采集：合成进程集合 (pid, ppid, cmd)。
+    fn build(index: &mut ProcessIndex, procs: &[(i32, i32, &str)]) -> super::ProcessIndexStats {
+        index.begin();
+        for (pid, ppid, cmd) in procs {
+            let relevant = is_discovery_relevant(&cmd.to_lowercase()).then(|| info(*pid, cmd));
+            index.record(*pid, *ppid, relevant);
+        }
+        for pid in index.select() {
+            let (_, _, cmd) = procs.iter().find(|(p, _, _)| *p == pid).expect("known pid");
+            index.insert(info(pid, cmd));
+        }
+        index.finish()
+    }
+
+    #[test]
+    fn filtered_index_only_contains_relevant_pids() {
+        let mut procs = vec![
+            (1, 0, "/sbin/init"),
+            (10, 1, "bash"),
+            (11, 10, "node /usr/local/bin/codex --model o3"),
+            (20, 1, "openclaw gateway"),
+            (21, 20, "openclaw-gateway --port 18789"),
+            (30, 1, "opencode"),
+            (31, 30, "helper-runtime"),
+        ];
+        for pid in 1000..6000 {
+            procs.push((pid, 1, "/usr/bin/unrelated-daemon"));
+        }
+
+        let mut index = ProcessIndex::default();
+        let stats = build(&mut index, &procs);
+        let context = index.context();
+
+        let indexed = context.all.keys().copied().collect::<HashSet<i32>>();
+        // 相关进程、其祖先（bash/init）与 opencode wrapper 的直接子进程。
+        let expected = HashSet::from([1, 10, 11, 20, 21, 30, 31]);
+        assert_eq!(indexed, expected);
+        assert_eq!(stats.scanned, procs.len());
+        assert_eq!(stats.relevant, 4);
+        assert_eq!(stats.indexed, expected.len());
+        assert_eq!(context.children_by_ppid.get(&20), Some(&vec![21]));
+        assert_eq!(context.children_by_ppid.get(&30), Some(&vec![31]));
+        assert!(
+            context
+                .children_by_ppid
+                .values()
+                .flatten()
+                .all(|pid| expected.contains(pid))
+        );
+
+        // 复用缓冲：下一轮不残留上一轮的进程。
+        let stats = build(&mut index, &[(5, 1, "/usr/bin/unrelated-daemon")]);
+        assert_eq!(stats.indexed, 0);
+        assert!(index.context().children_by_ppid.is_empty());
+    }
+
+    #[test]
+    fn relevant_processes_are_capped() {
+        let procs = (1..=(MAX_RELEVANT_PROCESSES as i32 + 10))
+            .map(|pid| (pid, 0, "codex"))
+            .collect::<Vec<_>>();
+        let mut index = ProcessIndex::default();
+        let stats = build(&mut index, &procs);
+        assert!(stats.truncated);
+        assert_eq!(stats.relevant, MAX_RELEVANT_PROCESSES + 10);
+        assert_eq!(stats.indexed, MAX_RELEVANT_PROCESSES);
+    }
+}
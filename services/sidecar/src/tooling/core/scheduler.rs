@@ -1,8 +1,9 @@
 //! Tool Adapter Core 调度辅助职责：
 //! 1. 提供详情采集默认周期与去抖常量。
 //! 2. 提供面向目标工具的过滤函数，减少主流程样板代码。
+//! 3. 维护单工具“聚焦”高频补采状态，不影响其他工具的常规周期。
 
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use yc_shared_protocol::ToolRuntimePayload;
 
@@ -14,6 +15,12 @@ pub(crate) const DEFAULT_DETAILS_DEBOUNCE_SEC: u64 = 3;
 pub(crate) const DEFAULT_DETAILS_COMMAND_TIMEOUT_MS: u64 = 8_000;
 /// 详情采集默认并发上限。
 pub(crate) const DEFAULT_DETAILS_MAX_PARALLEL: usize = 2;
+/// 聚焦工具详情补采默认周期（毫秒）。
+pub(crate) const DEFAULT_DETAILS_FOCUS_INTERVAL_MS: u64 = 3_000;
+/// 聚焦补采周期下限（毫秒），避免外部 CLI 被高频拉起。
+const MIN_DETAILS_FOCUS_INTERVAL_MS: u64 = 1_000;
+/// 聚焦补采周期上限（毫秒）。
+const MAX_DETAILS_FOCUS_INTERVAL_MS: u64 = 30_000;
 
 /// 详情条目默认 TTL：取 `details_interval * 2`，避免短暂抖动导致频繁过期。
 pub(crate) fn default_detail_ttl(details_interval: Duration) -> Duration {
@@ -39,3 +46,146 @@ pub(crate) fn filter_tools_by_target(
         .cloned()
         .collect()
 }
+
+/// 把聚焦补采周期限制在 [1s, 30s] 区间内。
+pub(crate) fn clamp_focus_interval(interval: Duration) -> Duration {
+    interval.clamp(
+        Duration::from_millis(MIN_DETAILS_FOCUS_INTERVAL_MS),
+        Duration::from_millis(MAX_DETAILS_FOCUS_INTERVAL_MS),
+    )
+}
+
+/// 单工具聚焦状态：同一时刻最多聚焦一个工具，按独立短周期补采。
+#[derive(Debug, Clone)]
+pub(crate) struct DetailsFocusState {
+    /// 当前聚焦工具 ID。
+    tool_id: Option<String>,
+    /// 聚焦补采周期。
+    interval: Duration,
+    /// 最近一次聚焦补采时间。
+    last_collected_at: Option<Instant>,
+}
+
+impl DetailsFocusState {
+    /// 按给定周期创建聚焦状态（周期会被限制在允许区间内）。
+    pub(crate) fn new(interval: Duration) -> Self {
+        Self {
+            tool_id: None,
+            interval: clamp_focus_interval(interval),
+            last_collected_at: None,
+        }
+    }
+
+    /// 返回聚焦补采周期。
+    pub(crate) fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    /// 返回当前聚焦工具 ID。
+    pub(crate) fn focused_tool_id(&self) -> Option<&str> {
+        self.tool_id.as_deref()
+    }
+
+    /// 聚焦指定工具；返回是否发生变化。
+    pub(crate) fn focus(&mut self, tool_id: &str) -> bool {
+        let normalized = tool_id.trim();
+        if normalized.is_empty() || self.focused_tool_id() == Some(normalized) {
+            return false;
+        }
+        self.tool_id = Some(normalized.to_string());
+        self.last_collected_at = None;
+        true
+    }
+
+    /// 取消聚焦；返回是否发生变化。
+    pub(crate) fn unfocus(&mut self) -> bool {
+        self.last_collected_at = None;
+        self.tool_id.take().is_some()
+    }
+
+    /// 若当前聚焦的正是指定工具则取消聚焦（工具断开时使用）。
+    pub(crate) fn unfocus_tool(&mut self, tool_id: &str) -> bool {
+        if self.focused_tool_id() != Some(tool_id.trim()) {
+            return false;
+        }
+        self.unfocus()
+    }
+
+    /// 到期时返回需要补采的聚焦工具，并记录本次补采时间。
+    pub(crate) fn take_due(&mut self, now: Instant) -> Option<String> {
+        let tool_id = self.tool_id.clone()?;
+        let due = self
+            .last_collected_at
+            .map(|last| now.saturating_duration_since(last) >= self.interval)
+            .unwrap_or(true);
+        if !due {
+            return None;
+        }
+        self.last_collected_at = Some(now);
+        Some(tool_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::{DetailsFocusState, clamp_focus_interval};
+
+    /// 在给定时间窗口内按 1 秒步进模拟 ticker，统计聚焦补采次数。
+    fn count_due(state: &mut DetailsFocusState, start: Instant, window_sec: u64) -> usize {
+        (0..window_sec)
+            .filter(|offset| {
+                state
+                    .take_due(start + Duration::from_secs(*offset))
+                    .is_some()
+            })
+            .count()
+    }
+
+    #[test]
+    fn focused_tool_is_collected_more_often_than_details_interval() {
+        let mut state = DetailsFocusState::new(Duration::from_secs(3));
+        assert!(state.focus("openclaw_a"));
+
+        let start = Instant::now();
+        let focused_count = count_due(&mut state, start, 45);
+        // 常规周期 45s 内只补采一次，聚焦工具应明显更频繁。
+        assert_eq!(focused_count, 15);
+        assert_eq!(state.focused_tool_id(), Some("openclaw_a"));
+    }
+
+    #[test]
+    fn unfocus_restores_normal_cadence() {
+        let mut state = DetailsFocusState::new(Duration::from_secs(3));
+        state.focus("openclaw_a");
+        let start = Instant::now();
+        assert!(state.take_due(start).is_some());
+
+        assert!(state.unfocus());
+        assert_eq!(count_due(&mut state, start, 45), 0);
+        assert!(!state.unfocus());
+    }
+
+    #[test]
+    fn disconnecting_focused_tool_clears_focus() {
+        let mut state = DetailsFocusState::new(Duration::from_secs(3));
+        state.focus("openclaw_a");
+
+        assert!(!state.unfocus_tool("opencode_b"));
+        assert!(state.unfocus_tool("openclaw_a"));
+        assert_eq!(state.focused_tool_id(), None);
+    }
+
+    #[test]
+    fn focus_interval_is_bounded() {
+        assert_eq!(
+            clamp_focus_interval(Duration::from_millis(10)),
+            Duration::from_secs(1)
+        );
+        assert_eq!(
+            clamp_focus_interval(Duration::from_secs(600)),
+            Duration::from_secs(30)
+        );
+    }
+}
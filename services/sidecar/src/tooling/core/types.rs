@@ -28,6 +28,15 @@ pub(crate) struct ToolDetailCollectOptions {
     pub(crate) max_parallel: usize,
 }
 
+/// 按“全局超时 + 命令级上限”计算本次命令的有效超时。
+pub(crate) fn effective_timeout(global_timeout: Duration, command_cap_ms: u64) -> Duration {
+    let command_cap = Duration::from_millis(command_cap_ms.max(1));
+    if global_timeout.is_zero() {
+        return command_cap;
+    }
+    global_timeout.min(command_cap)
+}
+
 /// 适配器返回的单工具详情结果（成功或失败）。
 #[derive(Debug, Clone)]
 pub(crate) struct ToolDetailCollectResult {
@@ -6,6 +6,7 @@ pub(crate) mod core;
 pub(crate) mod num;
 pub(crate) mod opencode_session;
 pub(crate) mod tool_id;
+pub(crate) mod tool_logs;
 
 pub(crate) use cli_parse::{
     detect_openclaw_mode, detect_opencode_mode, evaluate_openclaw_connection,
@@ -17,5 +18,6 @@ pub(crate) use cli_parse::{
 pub(crate) use num::{bytes_to_gb, bytes_to_mb, round2};
 pub(crate) use opencode_session::collect_opencode_session_state;
 pub(crate) use tool_id::{
-    build_claude_code_tool_id, build_codex_tool_id, build_openclaw_tool_id, build_opencode_tool_id,
+    build_claude_code_tool_id, build_codex_tool_id, build_custom_tool_id, build_openclaw_tool_id,
+    build_opencode_tool_id,
 };
@@ -7,6 +7,7 @@ use std::{
 
 use serde::Deserialize;
 
+use crate::home::BaseDirs;
 use crate::tooling::opencode_session::types::{DirSignature, OpenCodeSessionMeta};
 
 /// 计算文件集签名（数量 + 最新 mtime）。
@@ -71,17 +72,9 @@ pub(super) fn read_json_file<T: for<'de> Deserialize<'de>>(path: &Path) -> Optio
 
 /// 获取 OpenCode storage 根目录。
 pub(super) fn opencode_storage_root() -> Option<PathBuf> {
-    let home = std::env::var("HOME").ok()?;
-    if home.trim().is_empty() {
-        return None;
-    }
-    Some(
-        Path::new(&home)
-            .join(".local")
-            .join("share")
-            .join("opencode")
-            .join("storage"),
-    )
+    let base_dirs = BaseDirs::from_env();
+    let data_dir = base_dirs.data_dir().ok()?;
+    Some(data_dir.join("opencode").join("storage"))
 }
 
 /// 在候选 session 元数据中按目录优先、更新时间次之，选择目标 session。
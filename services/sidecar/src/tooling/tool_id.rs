@@ -68,6 +68,21 @@ pub(crate) fn build_claude_code_tool_id(workspace: &str, fallback_pid: i32) -> S
     format!("claude_code_{}_{instance}", &hex[..12])
 }
 
+/// 依据“定义 ID + 工作区 + 实例”生成自定义工具 ID。
+pub(crate) fn build_custom_tool_id(
+    definition_id: &str,
+    workspace: &str,
+    fallback_pid: i32,
+) -> String {
+    let instance = normalize_tool_instance_suffix(fallback_pid);
+    let normalized = normalize_path(workspace);
+    if normalized.trim().is_empty() {
+        return format!("custom_{definition_id}_{instance}");
+    }
+    let hex = format!("{:016x}", fnv1a64(normalized.as_bytes()));
+    format!("custom_{definition_id}_{}_{instance}", &hex[..12])
+}
+
 /// FNV-1a 64 位哈希，用于稳定生成 toolId。
 fn fnv1a64(bytes: &[u8]) -> u64 {
     const OFFSET_BASIS: u64 = 0xcbf29ce484222325;
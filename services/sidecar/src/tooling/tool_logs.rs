@@ -0,0 +1,174 @@
+//! 工具日志尾部读取职责：
+//! 1. 在适配器给出的日志目录中选取最近修改的日志文件，读取末尾 N 行供 `tool_logs` 回放。
+//! 2. 只读取状态目录内的普通文件：跳过符号链接，并按规范化路径校验不越出状态目录。
+
+use std::{
+    fs::{self, File},
+    io::{Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+/// 单次请求默认行数。
+pub(crate) const DEFAULT_TOOL_LOG_LINES: usize = 100;
+/// 单次请求行数上限。
+pub(crate) const MAX_TOOL_LOG_LINES: usize = 500;
+/// 从文件末尾最多读取的字节数，防止超长单行拖垮内存。
+const TAIL_MAX_BYTES: u64 = 512 * 1024;
+
+/// 工具日志位置：`dir` 必须位于 `root`（工具状态目录）之内。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ToolLogLocation {
+    /// 工具状态目录。
+    pub(crate) root: PathBuf,
+    /// 日志目录。
+    pub(crate) dir: PathBuf,
+}
+
+/// 日志尾部读取结果。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ToolLogTail {
+    /// 日志文件名（不含目录）。
+    pub(crate) file_name: String,
+    /// 末尾日志行（旧 -> 新）。
+    pub(crate) lines: Vec<String>,
+}
+
+/// 读取日志目录中最近修改文件的末尾 `lines` 行（行数按上限截断）。
+pub(crate) fn read_latest_log_tail(
+    location: &ToolLogLocation,
+    lines: usize,
+) -> Result<ToolLogTail, String> {
+    let root =
+        fs::canonicalize(&location.root).map_err(|err| format!("工具状态目录不可访问: {err}"))?;
+    let dir = fs::canonicalize(&location.dir).map_err(|err| format!("日志目录不可访问: {err}"))?;
+    if !dir.starts_with(&root) {
+        return Err("日志目录不在工具状态目录内".to_string());
+    }
+
+    let path = latest_log_file(&dir, &root)?.ok_or_else(|| "未找到日志文件".to_string())?;
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let lines = tail_lines(&path, lines.clamp(1, MAX_TOOL_LOG_LINES))
+        .map_err(|err| format!("读取日志失败: {err}"))?;
+    Ok(ToolLogTail { file_name, lines })
+}
+
+/// 选取目录下最近修改的普通文件；符号链接与越界路径一律跳过。
+fn latest_log_file(dir: &Path, root: &Path) -> Result<Option<PathBuf>, String> {
+    let entries = fs::read_dir(dir).map_err(|err| format!("读取日志目录失败: {err}"))?;
+    let mut latest: Option<(SystemTime, PathBuf)> = None;
+    for entry in entries.flatten() {
+        let Ok(meta) = fs::symlink_metadata(entry.path()) else {
+            continue;
+        };
+        if !meta.is_file() {
+            continue;
+        }
+        let Ok(path) = fs::canonicalize(entry.path()) else {
+            continue;
+        };
+        if !path.starts_with(root) {
+            continue;
+        }
+        let modified = meta.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+        if latest.as_ref().is_none_or(|(best, _)| modified > *best) {
+            latest = Some((modified, path));
+        }
+    }
+    Ok(latest.map(|(_, path)| path))
+}
+
+/// 读取文件末尾 `count` 行；只扫描末尾 `TAIL_MAX_BYTES` 字节，首个可能不完整的行会被丢弃。
+fn tail_lines(path: &Path, count: usize) -> std::io::Result<Vec<String>> {
+    let mut file = File::open(path)?;
+    let len = file.metadata()?.len();
+    let start = len.saturating_sub(TAIL_MAX_BYTES);
+    file.seek(SeekFrom::Start(start))?;
+    let mut buf = Vec::new();
+    file.take(TAIL_MAX_BYTES).read_to_end(&mut buf)?;
+
+    let text = String::from_utf8_lossy(&buf);
+    let mut lines = text.lines().collect::<Vec<_>>();
+    if start > 0 && !lines.is_empty() {
+        lines.remove(0);
+    }
+    let skip = lines.len().saturating_sub(count);
+    Ok(lines
+        .into_iter()
+        .skip(skip)
+        .map(ToString::to_string)
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::PathBuf};
+
+    use super::{ToolLogLocation, read_latest_log_tail};
+
+    fn make_temp_dir(prefix: &str) -> PathBuf {
+        let mut dir = std::env::temp_dir();
+        dir.push(format!(
+            "yc_sidecar_tool_logs_test_{prefix}_{}_{}",
+            std::process::id(),
+            uuid::Uuid::new_v4()
+        ));
+        fs::create_dir_all(&dir).expect("create temp dir");
+        dir
+    }
+
+    #[test]
+    fn tails_latest_file_and_clamps_line_count() {
+        let root = make_temp_dir("tail");
+        let dir = root.join("logs");
+        fs::create_dir_all(&dir).expect("create log dir");
+        fs::write(dir.join("old.log"), "stale\n").expect("write old log");
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        let body = (1..=600)
+            .map(|idx| format!("line {idx}\n"))
+            .collect::<String>();
+        fs::write(dir.join("gateway.log"), body).expect("write log");
+        let location = ToolLogLocation {
+            root: root.clone(),
+            dir,
+        };
+
+        let tail = read_latest_log_tail(&location, 3).expect("tail");
+        assert_eq!(tail.file_name, "gateway.log");
+        assert_eq!(tail.lines, vec!["line 598", "line 599", "line 600"]);
+        let capped = read_latest_log_tail(&location, 10_000).expect("capped tail");
+        assert_eq!(capped.lines.len(), 500);
+        assert_eq!(capped.lines.first().map(String::as_str), Some("line 101"));
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlinks_and_dirs_outside_root_are_rejected() {
+        let root = make_temp_dir("root");
+        let outside = make_temp_dir("outside");
+        fs::write(outside.join("secret.log"), "secret\n").expect("write outside");
+        let dir = root.join("logs");
+        fs::create_dir_all(&dir).expect("create log dir");
+        std::os::unix::fs::symlink(outside.join("secret.log"), dir.join("evil.log"))
+            .expect("symlink file");
+
+        let location = ToolLogLocation {
+            root: root.clone(),
+            dir: dir.clone(),
+        };
+        assert!(read_latest_log_tail(&location, 10).is_err());
+
+        std::os::unix::fs::symlink(&outside, root.join("linked")).expect("symlink dir");
+        let escaped = ToolLogLocation {
+            root: root.clone(),
+            dir: root.join("linked"),
+        };
+        assert!(read_latest_log_tail(&escaped, 10).is_err());
+        let _ = fs::remove_dir_all(root);
+        let _ = fs::remove_dir_all(outside);
+    }
+}